@@ -45,8 +45,8 @@ pub use patt::PatternResource;
 pub use samp::normalize_brush_texture;
 pub use types::{
     AbrBrush, AbrDynamics, AbrFile, AbrVersion, BrushPreset, ColorDynamicsSettings, ControlSource,
-    CursorBoundsData, CursorComplexityData, GrayscaleImage, ScatterSettings, ShapeDynamicsSettings,
-    TextureBlendMode, TextureSettings, TransferSettings,
+    CursorBoundsData, CursorComplexityData, DualBlendMode, DualBrushSettings, GrayscaleImage,
+    ScatterSettings, ShapeDynamicsSettings, TextureBlendMode, TextureSettings, TransferSettings,
 };
 
 #[cfg(test)]
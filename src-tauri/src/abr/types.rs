@@ -5,6 +5,7 @@
 use serde::{Deserialize, Serialize};
 
 use super::patt::PatternResource;
+use crate::core::pressure_curve::PressureCurve;
 
 /// Parsed ABR file containing brushes and patterns
 #[derive(Debug, Clone)]
@@ -521,6 +522,8 @@ pub struct BrushPreset {
     pub base_opacity: Option<f32>,
     /// Base flow (0..1)
     pub base_flow: Option<f32>,
+    /// Custom pressure curve for this preset (None = use the global tablet curve)
+    pub pressure_curve: Option<PressureCurve>,
 }
 
 /// Cursor bounds data for frontend
@@ -645,6 +648,7 @@ impl BrushPreset {
             noise_enabled: brush.noise_enabled,
             base_opacity: brush.base_opacity,
             base_flow: brush.base_flow,
+            pressure_curve: None,
         }
     }
 }
@@ -236,6 +236,42 @@ pub fn blend_lighten_premul(src: Pixel, dst: Pixel) -> Pixel {
     }
 }
 
+/// Source-atop for premultiplied alpha ("Lock transparent pixels")
+///
+/// Formula: result = src * dst.a + dst * (1 - src.a), alpha = dst.a
+#[inline]
+pub fn blend_source_atop_premul(src: Pixel, dst: Pixel) -> Pixel {
+    let inv_src_a = 1.0 - src.a;
+    Pixel {
+        r: src.r * dst.a + dst.r * inv_src_a,
+        g: src.g * dst.a + dst.g * inv_src_a,
+        b: src.b * dst.a + dst.b * inv_src_a,
+        a: dst.a,
+    }
+}
+
+/// Destination-out for premultiplied alpha (eraser)
+///
+/// Formula: result = dst * (1 - src.a)
+#[inline]
+pub fn blend_destination_out_premul(src: Pixel, dst: Pixel) -> Pixel {
+    let inv_src_a = 1.0 - src.a;
+    Pixel {
+        r: dst.r * inv_src_a,
+        g: dst.g * inv_src_a,
+        b: dst.b * inv_src_a,
+        a: dst.a * inv_src_a,
+    }
+}
+
+/// Destination-over for premultiplied alpha ("Behind")
+///
+/// Formula: result = dst + src * (1 - dst.a)
+#[inline]
+pub fn blend_destination_over_premul(src: Pixel, dst: Pixel) -> Pixel {
+    blend_normal_premul(dst, src)
+}
+
 /// Blend mode enum matching the existing BlendMode
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum BlendFunc {
@@ -331,6 +367,49 @@ mod tests {
         assert!(approx_eq(result.g, 0.0));
     }
 
+    #[test]
+    fn test_blend_source_atop_keeps_alpha() {
+        let src = Pixel::new(1.0, 0.0, 0.0, 1.0);
+        let half_green = Pixel::new(0.0, 0.5, 0.0, 0.5);
+
+        let result = blend_source_atop_premul(src, half_green);
+        assert!(approx_eq(result.a, 0.5));
+        assert!(approx_eq(result.r, 0.5));
+        assert!(approx_eq(result.g, 0.0));
+
+        let result = blend_source_atop_premul(src, Pixel::transparent());
+        assert!(approx_eq(result.a, 0.0));
+        assert!(approx_eq(result.r, 0.0));
+    }
+
+    #[test]
+    fn test_blend_destination_out() {
+        let dst = Pixel::new(0.0, 1.0, 0.0, 1.0);
+
+        let result = blend_destination_out_premul(Pixel::new(0.0, 0.0, 0.0, 0.25), dst);
+        assert!(approx_eq(result.a, 0.75));
+        assert!(approx_eq(result.g, 0.75));
+
+        let result = blend_destination_out_premul(Pixel::new(1.0, 1.0, 1.0, 1.0), dst);
+        assert!(approx_eq(result.a, 0.0));
+    }
+
+    #[test]
+    fn test_blend_destination_over() {
+        let red = Pixel::new(1.0, 0.0, 0.0, 1.0);
+        let green = Pixel::new(0.0, 1.0, 0.0, 1.0);
+
+        // Opaque destination hides the source
+        let result = blend_destination_over_premul(red, green);
+        assert!(approx_eq(result.g, 1.0));
+        assert!(approx_eq(result.r, 0.0));
+
+        // Transparent destination shows the source
+        let result = blend_destination_over_premul(red, Pixel::transparent());
+        assert!(approx_eq(result.r, 1.0));
+        assert!(approx_eq(result.a, 1.0));
+    }
+
     #[test]
     fn test_blend_func_enum() {
         let src = Pixel::new(1.0, 0.0, 0.0, 1.0);
@@ -0,0 +1,560 @@
+//! Bulk import of brush packs: folders and zip archives holding ABR and PAT
+//! files. Other files (preview images, readmes) are ignored, as are archives
+//! inside archives.
+//!
+//! Files are read and parsed in parallel. Adding them to the libraries is
+//! serialized, because ABR import caches tips under their brush UUIDs and packs
+//! that reuse a UUID would otherwise overwrite each other's tips mid-import.
+//! Each file lands in a group named after its path inside the picked folder or
+//! archive, e.g. `Pack/Inks/Dry` for `Pack.zip/Inks/Dry.abr`. A file that fails
+//! is reported and the rest still import. Symlinked folders are skipped, so a
+//! link loop cannot recurse forever, and archive entries are capped in size.
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::io::{BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+use zip::ZipArchive;
+
+use super::library::{self as brush_library, BrushLibraryImportResult, BrushLibrarySnapshot};
+use crate::abr::BrushPreset;
+use crate::pattern::library as pattern_library;
+use crate::pattern::pat::{parse_pat_data, ParsedPattern};
+use crate::pattern::types::ImportResult;
+
+/// Largest archive entry read into memory; bigger entries fail instead of
+/// exhausting memory on a zip bomb
+const MAX_ARCHIVE_ENTRY_BYTES: u64 = 512 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkImportKind {
+    Abr,
+    Pat,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ItemSource {
+    File(PathBuf),
+    ZipEntry { archive: PathBuf, name: String },
+}
+
+/// One ABR or PAT file found in the picked folders and archives
+#[derive(Debug, Clone)]
+pub struct BulkImportItem {
+    pub kind: BulkImportKind,
+    /// File path, or `<archive>/<entry>` for files inside a zip
+    pub path: String,
+    /// Path relative to the picked folder or archive (prefixed with its name),
+    /// without the extension
+    pub group: String,
+    source: ItemSource,
+}
+
+impl BulkImportItem {
+    fn read(&self) -> Result<Vec<u8>, String> {
+        match &self.source {
+            ItemSource::File(path) => {
+                std::fs::read(path).map_err(|e| format!("Failed to read file: {}", e))
+            }
+            ItemSource::ZipEntry { archive, name } => {
+                let mut archive = open_archive(archive)?;
+                let entry = archive
+                    .by_name(name)
+                    .map_err(|e| format!("Failed to read archive entry: {}", e))?;
+                let declared = entry.size();
+                read_capped(entry, declared, MAX_ARCHIVE_ENTRY_BYTES)
+            }
+        }
+    }
+}
+
+/// Read at most `limit` bytes; the declared size is checked up front and the
+/// actual size while reading, since a crafted archive can lie about either
+fn read_capped(reader: impl Read, declared: u64, limit: u64) -> Result<Vec<u8>, String> {
+    let too_large = || format!("Archive entry is larger than {} bytes", limit);
+    if declared > limit {
+        return Err(too_large());
+    }
+    let mut bytes = Vec::with_capacity(declared as usize);
+    reader
+        .take(limit + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read archive entry: {}", e))?;
+    if bytes.len() as u64 > limit {
+        return Err(too_large());
+    }
+    Ok(bytes)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkImportFileError {
+    pub path: String,
+    pub error: String,
+}
+
+/// Sent after each file is imported or fails
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkImportProgress {
+    pub completed: usize,
+    pub total: usize,
+    pub path: String,
+    pub error: Option<String>,
+}
+
+/// Combined result of all files; `snapshot` is the library after the import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkImportResult {
+    #[serde(flatten)]
+    pub library: BrushLibraryImportResult,
+    pub imported_pattern_count: usize,
+    pub skipped_pattern_count: usize,
+    pub imported_file_count: usize,
+    pub errors: Vec<BulkImportFileError>,
+}
+
+impl BulkImportResult {
+    fn new() -> Self {
+        Self {
+            library: BrushLibraryImportResult {
+                imported_preset_count: 0,
+                skipped_preset_count: 0,
+                imported_tip_count: 0,
+                skipped_tip_count: 0,
+                snapshot: BrushLibrarySnapshot {
+                    presets: Vec::new(),
+                    tips: Vec::new(),
+                    groups: Vec::new(),
+                },
+            },
+            imported_pattern_count: 0,
+            skipped_pattern_count: 0,
+            imported_file_count: 0,
+            errors: Vec::new(),
+        }
+    }
+
+    fn add_brushes(&mut self, result: BrushLibraryImportResult) {
+        let library = &mut self.library;
+        library.imported_preset_count += result.imported_preset_count;
+        library.skipped_preset_count += result.skipped_preset_count;
+        library.imported_tip_count += result.imported_tip_count;
+        library.skipped_tip_count += result.skipped_tip_count;
+        library.snapshot = result.snapshot;
+    }
+
+    fn add_patterns(&mut self, result: ImportResult) {
+        self.imported_pattern_count += result.imported_count;
+        self.skipped_pattern_count += result.skipped_count;
+    }
+}
+
+/// Import every ABR and PAT file under `paths` into the global libraries.
+/// `parse_abr` runs in parallel; `build_abr` turns a parsed file into presets
+/// and tips (caching their bitmaps) one file at a time on the calling thread.
+pub fn import_brush_packs<P: Send>(
+    paths: &[PathBuf],
+    parse_abr: impl Fn(&[u8]) -> Result<P, String> + Sync,
+    build_abr: impl Fn(P) -> Result<(Vec<BrushPreset>, Vec<BrushPreset>), String>,
+    on_progress: impl FnMut(BulkImportProgress),
+) -> BulkImportResult {
+    let (items, collect_errors) = collect_items(paths);
+    let mut result = run_bulk_import(
+        &items,
+        parse_abr,
+        |item, parsed| {
+            let (presets, tips) = build_abr(parsed)?;
+            brush_library::import_from_abr_into_group(&item.path, &item.group, presets, tips)
+        },
+        |item, patterns| pattern_library::import_parsed_patterns(patterns, &item.path, &item.group),
+        on_progress,
+    );
+    result.errors.extend(collect_errors);
+    result.errors.sort_by(|a, b| a.path.cmp(&b.path));
+    result.library.snapshot = brush_library::get_library_snapshot();
+    result
+}
+
+/// Find every ABR and PAT file in `paths`, which may be files, folders or zip
+/// archives. Paths that cannot be read or are not supported become errors.
+pub fn collect_items(paths: &[PathBuf]) -> (Vec<BulkImportItem>, Vec<BulkImportFileError>) {
+    let mut items = Vec::new();
+    let mut errors = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let root = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or_default();
+            collect_dir(path, path, root, &mut items, &mut errors);
+            continue;
+        }
+        let group = path
+            .file_name()
+            .map(|name| group_name("", Path::new(name)))
+            .unwrap_or_default();
+        match collect_file(path, group, &mut items) {
+            Ok(true) => {}
+            Ok(false) => errors.push(file_error(path, "Unsupported file type".to_string())),
+            Err(error) => errors.push(file_error(path, error)),
+        }
+    }
+    items.sort_by(|a, b| a.path.cmp(&b.path));
+    (items, errors)
+}
+
+/// Parse `items` in parallel, then hand them to `add_abr` / `add_pat` one at a
+/// time on the calling thread. The adders may run rayon work of their own, so
+/// they are only called once the parallel parse has finished.
+pub fn run_bulk_import<P: Send>(
+    items: &[BulkImportItem],
+    parse_abr: impl Fn(&[u8]) -> Result<P, String> + Sync,
+    mut add_abr: impl FnMut(&BulkImportItem, P) -> Result<BrushLibraryImportResult, String>,
+    mut add_pat: impl FnMut(&BulkImportItem, Vec<ParsedPattern>) -> Result<ImportResult, String>,
+    mut on_progress: impl FnMut(BulkImportProgress),
+) -> BulkImportResult {
+    enum Parsed<P> {
+        Abr(P),
+        Pat(Vec<ParsedPattern>),
+    }
+
+    let parsed: Vec<Result<Parsed<P>, String>> = items
+        .par_iter()
+        .map(|item| {
+            item.read().and_then(|bytes| match item.kind {
+                BulkImportKind::Abr => parse_abr(&bytes).map(Parsed::Abr),
+                BulkImportKind::Pat => parse_pat_data(&bytes)
+                    .map(Parsed::Pat)
+                    .map_err(|e| format!("Failed to parse PAT file: {}", e)),
+            })
+        })
+        .collect();
+
+    let total = items.len();
+    let mut result = BulkImportResult::new();
+    for (index, (item, parsed)) in items.iter().zip(parsed).enumerate() {
+        let outcome = match parsed {
+            Ok(Parsed::Abr(abr)) => add_abr(item, abr).map(|added| result.add_brushes(added)),
+            Ok(Parsed::Pat(patterns)) => {
+                add_pat(item, patterns).map(|added| result.add_patterns(added))
+            }
+            Err(error) => Err(error),
+        };
+        let error = match outcome {
+            Ok(()) => {
+                result.imported_file_count += 1;
+                None
+            }
+            Err(error) => {
+                tracing::warn!("Bulk import of {} failed: {}", item.path, error);
+                result.errors.push(BulkImportFileError {
+                    path: item.path.clone(),
+                    error: error.clone(),
+                });
+                Some(error)
+            }
+        };
+        on_progress(BulkImportProgress {
+            completed: index + 1,
+            total,
+            path: item.path.clone(),
+            error,
+        });
+    }
+
+    result.errors.sort_by(|a, b| a.path.cmp(&b.path));
+    result
+}
+
+fn collect_dir(
+    dir: &Path,
+    base: &Path,
+    root: &str,
+    items: &mut Vec<BulkImportItem>,
+    errors: &mut Vec<BulkImportFileError>,
+) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            errors.push(file_error(dir, format!("Failed to read folder: {}", e)));
+            return;
+        }
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            // A symlinked folder can point back up the tree
+            let linked_dir =
+                entry.file_type().is_ok_and(|kind| kind.is_symlink()) && entry.path().is_dir();
+            if linked_dir {
+                tracing::debug!("Skipping symlinked folder {:?}", entry.path());
+            }
+            !linked_dir
+        })
+        .map(|entry| entry.path())
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        if path.is_dir() {
+            collect_dir(&path, base, root, items, errors);
+            continue;
+        }
+        let relative = path.strip_prefix(base).unwrap_or(&path);
+        if let Err(error) = collect_file(&path, group_name(root, relative), items) {
+            errors.push(file_error(&path, error));
+        }
+    }
+}
+
+/// Add `path` if it is an ABR, PAT or zip file. Returns false for other files.
+fn collect_file(
+    path: &Path,
+    group: String,
+    items: &mut Vec<BulkImportItem>,
+) -> Result<bool, String> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("zip") => {
+            collect_zip(path, &group, items)?;
+            Ok(true)
+        }
+        Some(extension) => match kind_for_extension(extension) {
+            Some(kind) => {
+                items.push(BulkImportItem {
+                    kind,
+                    path: path.display().to_string(),
+                    group,
+                    source: ItemSource::File(path.to_path_buf()),
+                });
+                Ok(true)
+            }
+            None => Ok(false),
+        },
+        None => Ok(false),
+    }
+}
+
+fn collect_zip(
+    archive_path: &Path,
+    prefix: &str,
+    items: &mut Vec<BulkImportItem>,
+) -> Result<(), String> {
+    let archive = open_archive(archive_path)?;
+    for name in archive.file_names() {
+        let entry_path = Path::new(name);
+        let is_metadata = entry_path.components().any(|component| {
+            component
+                .as_os_str()
+                .to_str()
+                .is_some_and(|part| part == "__MACOSX" || part.starts_with("._"))
+        });
+        if name.ends_with('/') || is_metadata {
+            continue;
+        }
+        let kind = entry_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| kind_for_extension(&ext.to_ascii_lowercase()));
+        if let Some(kind) = kind {
+            items.push(BulkImportItem {
+                kind,
+                path: format!("{}/{}", archive_path.display(), name),
+                group: group_name(prefix, entry_path),
+                source: ItemSource::ZipEntry {
+                    archive: archive_path.to_path_buf(),
+                    name: name.to_string(),
+                },
+            });
+        }
+    }
+    Ok(())
+}
+
+fn open_archive(path: &Path) -> Result<ZipArchive<BufReader<std::fs::File>>, String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("Failed to open archive: {}", e))?;
+    ZipArchive::new(BufReader::new(file)).map_err(|e| format!("Failed to read archive: {}", e))
+}
+
+fn kind_for_extension(extension: &str) -> Option<BulkImportKind> {
+    match extension {
+        "abr" => Some(BulkImportKind::Abr),
+        "pat" => Some(BulkImportKind::Pat),
+        _ => None,
+    }
+}
+
+/// `prefix` and `relative` without its extension, joined with '/'
+fn group_name(prefix: &str, relative: &Path) -> String {
+    let without_extension = relative.with_extension("");
+    let parts = without_extension
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => part.to_str(),
+            _ => None,
+        });
+    std::iter::once(prefix)
+        .filter(|prefix| !prefix.is_empty())
+        .chain(parts)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn file_error(path: &Path, error: String) -> BulkImportFileError {
+    BulkImportFileError {
+        path: path.display().to_string(),
+        error,
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::app_meta::APP_STORAGE_PREFIX;
+    use parking_lot::Mutex;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    /// A valid .pat file holding no patterns
+    const EMPTY_PAT: &[u8] = b"8BPT\x00\x01\x00\x00\x00\x00";
+
+    fn write(path: &Path, bytes: &[u8]) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, bytes).unwrap();
+    }
+
+    #[test]
+    fn packs_import_by_relative_path_and_report_failures_per_file() {
+        let root = std::env::temp_dir().join(format!(
+            "{}_bulk_import_{}",
+            APP_STORAGE_PREFIX,
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        let pack = root.join("Pack");
+        write(&pack.join("Inks/Dry.abr"), b"dry");
+        write(&pack.join("Inks/preview.png"), b"png");
+        write(&pack.join("Broken.abr"), b"bad");
+        write(&pack.join("Papers.pat"), EMPTY_PAT);
+
+        let mut zip = ZipWriter::new(std::fs::File::create(pack.join("Extras.zip")).unwrap());
+        for (name, bytes) in [
+            ("Soft/Round.ABR", b"round".as_slice()),
+            ("Soft/Grain.pat", b"not a pattern".as_slice()),
+            ("__MACOSX/Soft/._Round.ABR", b"bad".as_slice()),
+            ("readme.txt", b"hi".as_slice()),
+        ] {
+            zip.start_file(name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(bytes).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let (items, errors) = collect_items(&[pack.clone(), root.join("notes.txt")]);
+        let mut found: Vec<(BulkImportKind, &str)> = items
+            .iter()
+            .map(|item| (item.kind, item.group.as_str()))
+            .collect();
+        found.sort_by(|a, b| a.1.cmp(b.1));
+        assert_eq!(
+            found,
+            vec![
+                (BulkImportKind::Abr, "Pack/Broken"),
+                (BulkImportKind::Pat, "Pack/Extras/Soft/Grain"),
+                (BulkImportKind::Abr, "Pack/Extras/Soft/Round"),
+                (BulkImportKind::Abr, "Pack/Inks/Dry"),
+                (BulkImportKind::Pat, "Pack/Papers"),
+            ]
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].path.ends_with("notes.txt"));
+
+        let imported = Mutex::new(Vec::new());
+        let progress = Mutex::new(Vec::new());
+        let result = run_bulk_import(
+            &items,
+            |bytes| match bytes {
+                b"bad" => Err("Failed to parse ABR file".to_string()),
+                _ => Ok(String::from_utf8(bytes.to_vec()).unwrap()),
+            },
+            |item, name| {
+                // Building ABR presets runs rayon work of its own
+                let _: u32 = (0..64u32).into_par_iter().sum();
+                imported.lock().push((item.group.clone(), name));
+                let mut added = BulkImportResult::new().library;
+                added.imported_preset_count = 2;
+                Ok(added)
+            },
+            |item, patterns| {
+                imported
+                    .lock()
+                    .push((item.group.clone(), "pat".to_string()));
+                Ok(ImportResult {
+                    imported_count: patterns.len(),
+                    skipped_count: 0,
+                    pattern_ids: Vec::new(),
+                })
+            },
+            |event| progress.lock().push(event.completed),
+        );
+
+        let mut imported = imported.into_inner();
+        imported.sort();
+        assert_eq!(
+            imported,
+            vec![
+                ("Pack/Extras/Soft/Round".to_string(), "round".to_string()),
+                ("Pack/Inks/Dry".to_string(), "dry".to_string()),
+                ("Pack/Papers".to_string(), "pat".to_string()),
+            ]
+        );
+        assert_eq!(result.imported_file_count, 3);
+        assert_eq!(result.library.imported_preset_count, 4);
+        let failed: Vec<&str> = result.errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(failed.len(), 2);
+        assert!(failed[0].ends_with("Broken.abr"));
+        assert!(failed[1].ends_with("Extras.zip/Soft/Grain.pat"));
+        assert_eq!(progress.into_inner(), vec![1, 2, 3, 4, 5]);
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn oversized_archive_entries_are_rejected() {
+        assert_eq!(read_capped(&b"abcd"[..], 4, 4).unwrap(), b"abcd");
+        assert!(read_capped(&b"abcd"[..], 5, 4).is_err());
+        // A declared size that understates the data does not get past the cap
+        assert!(read_capped(&b"abcdef"[..], 2, 4).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_folders_are_not_followed() {
+        let root = std::env::temp_dir().join(format!(
+            "{}_bulk_import_link_{}",
+            APP_STORAGE_PREFIX,
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        let pack = root.join("Pack");
+        write(&pack.join("Inks/Dry.abr"), b"dry");
+        std::os::unix::fs::symlink(&pack, pack.join("Inks/Loop")).unwrap();
+
+        let (items, errors) = collect_items(&[pack]);
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].group, "Pack/Inks/Dry");
+        assert!(errors.is_empty());
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,510 @@
+//! Self-contained brush bundles (`.sutubrush`) for sharing presets.
+//!
+//! A bundle is a zip archive:
+//! - `mimetype`: stored uncompressed as the first entry
+//! - `manifest.json`: presets, groups, tip metadata and pattern metadata
+//! - `tips/<n>.png`: Gray8 bitmap of `manifest.tips[n]` (absent for computed tips)
+//! - `patterns/<n>.png`: RGBA pixels of `manifest.patterns[n]`
+//!
+//! Import dedupes tips and presets the same way as ABR import (`source_uuid`, then
+//! `content_hash`) and patterns by content hash, then remaps texture references to
+//! the resolved pattern IDs.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use super::library::{
+    self as brush_library, BrushLibraryGroup, BrushLibraryImportResult, BrushLibraryPreset,
+    BrushTipResource,
+};
+use super::{cache_brush_gray_ref, get_cached_brush, get_cached_pattern};
+use crate::abr::BrushPreset;
+use crate::file::FileError;
+use crate::pattern::{self, PatternMode, PatternResource};
+
+pub const BRUSH_BUNDLE_EXTENSION: &str = "sutubrush";
+pub const BRUSH_BUNDLE_MIMETYPE: &str = "application/x-sutu-brush-bundle";
+pub const BRUSH_BUNDLE_VERSION: u32 = 1;
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrushBundleManifest {
+    pub version: u32,
+    pub presets: Vec<BrushLibraryPreset>,
+    pub groups: Vec<BrushLibraryGroup>,
+    pub tips: Vec<BrushTipResource>,
+    pub patterns: Vec<PatternResource>,
+}
+
+/// Uncompressed bitmap carried by a bundle
+#[derive(Debug, Clone, PartialEq)]
+pub struct BundleBitmap {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+/// Bundle contents in memory
+#[derive(Debug, Clone)]
+pub struct BrushBundle {
+    pub manifest: BrushBundleManifest,
+    /// Gray8 tip bitmaps keyed by tip ID
+    pub tip_bitmaps: HashMap<String, BundleBitmap>,
+    /// RGBA pattern pixels keyed by pattern ID
+    pub pattern_bitmaps: HashMap<String, BundleBitmap>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrushBundleExportResult {
+    pub preset_count: usize,
+    pub tip_count: usize,
+    pub pattern_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrushBundleImportResult {
+    #[serde(flatten)]
+    pub library: BrushLibraryImportResult,
+    pub imported_pattern_count: usize,
+    pub skipped_pattern_count: usize,
+}
+
+fn encode_png(bitmap: &BundleBitmap, color: image::ColorType) -> Result<Vec<u8>, FileError> {
+    let mut png = Cursor::new(Vec::new());
+    image::write_buffer_with_format(
+        &mut png,
+        &bitmap.pixels,
+        bitmap.width,
+        bitmap.height,
+        color,
+        image::ImageFormat::Png,
+    )?;
+    Ok(png.into_inner())
+}
+
+fn read_png_entry<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+    gray: bool,
+) -> Result<Option<BundleBitmap>, FileError> {
+    let mut bytes = Vec::new();
+    match archive.by_name(name) {
+        Ok(mut entry) => {
+            entry.read_to_end(&mut bytes)?;
+        }
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    let image = image::load_from_memory_with_format(&bytes, image::ImageFormat::Png)?;
+    let (width, height) = (image.width(), image.height());
+    let pixels = if gray {
+        image.into_luma8().into_raw()
+    } else {
+        image.into_rgba8().into_raw()
+    };
+    Ok(Some(BundleBitmap {
+        width,
+        height,
+        pixels,
+    }))
+}
+
+/// Write a bundle archive
+pub fn write_bundle<W: Write + Seek>(writer: W, bundle: &BrushBundle) -> Result<(), FileError> {
+    let mut zip = ZipWriter::new(writer);
+
+    let options_stored = SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Stored)
+        .unix_permissions(0o644);
+    zip.start_file("mimetype", options_stored)?;
+    zip.write_all(BRUSH_BUNDLE_MIMETYPE.as_bytes())?;
+
+    let options_deflate = SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .unix_permissions(0o644);
+    zip.start_file(MANIFEST_ENTRY, options_deflate)?;
+    zip.write_all(&serde_json::to_vec_pretty(&bundle.manifest)?)?;
+
+    // PNG data is already compressed
+    for (index, tip) in bundle.manifest.tips.iter().enumerate() {
+        if let Some(bitmap) = bundle.tip_bitmaps.get(&tip.tip.id) {
+            zip.start_file(format!("tips/{}.png", index), options_stored)?;
+            zip.write_all(&encode_png(bitmap, image::ColorType::L8)?)?;
+        }
+    }
+    for (index, resource) in bundle.manifest.patterns.iter().enumerate() {
+        if let Some(bitmap) = bundle.pattern_bitmaps.get(&resource.id) {
+            zip.start_file(format!("patterns/{}.png", index), options_stored)?;
+            zip.write_all(&encode_png(bitmap, image::ColorType::Rgba8)?)?;
+        }
+    }
+
+    zip.finish()?;
+    Ok(())
+}
+
+/// Read a bundle archive, rejecting other archives and newer format versions
+pub fn read_bundle<R: Read + Seek>(reader: R) -> Result<BrushBundle, FileError> {
+    let mut archive = ZipArchive::new(reader)?;
+
+    let mut mimetype = String::new();
+    archive.by_name("mimetype")?.read_to_string(&mut mimetype)?;
+    if mimetype.trim() != BRUSH_BUNDLE_MIMETYPE {
+        return Err(FileError::InvalidFormat(format!(
+            "Not a brush bundle (mimetype '{}')",
+            mimetype.trim()
+        )));
+    }
+
+    let mut manifest_json = Vec::new();
+    archive
+        .by_name(MANIFEST_ENTRY)?
+        .read_to_end(&mut manifest_json)?;
+    let manifest: BrushBundleManifest = serde_json::from_slice(&manifest_json)?;
+    if manifest.version > BRUSH_BUNDLE_VERSION {
+        return Err(FileError::InvalidFormat(format!(
+            "Brush bundle version {} is newer than supported version {}",
+            manifest.version, BRUSH_BUNDLE_VERSION
+        )));
+    }
+
+    let mut tip_bitmaps = HashMap::new();
+    for (index, tip) in manifest.tips.iter().enumerate() {
+        if let Some(bitmap) = read_png_entry(&mut archive, &format!("tips/{}.png", index), true)? {
+            tip_bitmaps.insert(tip.tip.id.clone(), bitmap);
+        }
+    }
+    let mut pattern_bitmaps = HashMap::new();
+    for (index, resource) in manifest.patterns.iter().enumerate() {
+        let name = format!("patterns/{}.png", index);
+        if let Some(bitmap) = read_png_entry(&mut archive, &name, false)? {
+            pattern_bitmaps.insert(resource.id.clone(), bitmap);
+        }
+    }
+
+    Ok(BrushBundle {
+        manifest,
+        tip_bitmaps,
+        pattern_bitmaps,
+    })
+}
+
+/// Pattern IDs referenced by preset or tip texture settings
+fn referenced_pattern_ids<'a>(presets: impl Iterator<Item = &'a BrushPreset>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    presets
+        .filter_map(|preset| preset.texture_settings.as_ref()?.pattern_id.clone())
+        .filter(|id| seen.insert(id.clone()))
+        .collect()
+}
+
+fn remap_pattern_id(preset: &mut BrushPreset, aliases: &HashMap<String, String>) {
+    if let Some(texture) = preset.texture_settings.as_mut() {
+        if let Some(id) = texture.pattern_id.as_ref().and_then(|id| aliases.get(id)) {
+            texture.pattern_id = Some(id.clone());
+        }
+    }
+}
+
+/// Collect the selected presets/groups with their tips and patterns into a bundle
+pub fn collect_bundle(
+    preset_ids: &[String],
+    group_names: &[String],
+) -> Result<BrushBundle, String> {
+    let snapshot = brush_library::selection_snapshot(preset_ids, group_names)?;
+
+    let mut tip_bitmaps = HashMap::new();
+    for tip in &snapshot.tips {
+        let Some(cached) = get_cached_brush(&tip.tip.id) else {
+            continue;
+        };
+        let pixels = lz4_flex::decompress_size_prepended(&cached.data)
+            .map_err(|e| format!("Failed to decompress tip {}: {}", tip.tip.id, e))?;
+        tip_bitmaps.insert(
+            tip.tip.id.clone(),
+            BundleBitmap {
+                width: cached.width,
+                height: cached.height,
+                pixels,
+            },
+        );
+    }
+
+    let mut patterns = Vec::new();
+    let mut pattern_bitmaps = HashMap::new();
+    let pattern_ids = referenced_pattern_ids(
+        snapshot
+            .presets
+            .iter()
+            .map(|entry| &entry.preset)
+            .chain(snapshot.tips.iter().map(|tip| &tip.tip)),
+    );
+    for id in pattern_ids {
+        let Some(cached) = get_cached_pattern(&id) else {
+            tracing::warn!("[BrushBundle] Pattern {} is not cached, skipping", id);
+            continue;
+        };
+        let pixels = lz4_flex::decompress_size_prepended(&cached.data)
+            .map_err(|e| format!("Failed to decompress pattern {}: {}", id, e))?;
+        let resource = pattern::library::get_pattern(&id).unwrap_or_else(|| PatternResource {
+            id: id.clone(),
+            name: cached.name.clone(),
+            content_hash: String::new(),
+            width: cached.width,
+            height: cached.height,
+            mode: PatternMode::from_name(&cached.mode).unwrap_or(PatternMode::RGB),
+            source: "brush".to_string(),
+            group: None,
+            tags: Vec::new(),
+            last_used_ms: None,
+        });
+        pattern_bitmaps.insert(
+            id,
+            BundleBitmap {
+                width: cached.width,
+                height: cached.height,
+                pixels,
+            },
+        );
+        patterns.push(resource);
+    }
+
+    Ok(BrushBundle {
+        manifest: BrushBundleManifest {
+            version: BRUSH_BUNDLE_VERSION,
+            presets: snapshot.presets,
+            groups: snapshot.groups,
+            tips: snapshot.tips,
+            patterns,
+        },
+        tip_bitmaps,
+        pattern_bitmaps,
+    })
+}
+
+/// Export presets and whole groups to a `.sutubrush` file
+pub fn export_bundle(
+    path: &Path,
+    preset_ids: &[String],
+    group_names: &[String],
+) -> Result<BrushBundleExportResult, String> {
+    let bundle = collect_bundle(preset_ids, group_names)?;
+    if bundle.manifest.presets.is_empty() {
+        return Err("No presets selected for export".to_string());
+    }
+
+    let file =
+        File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    write_bundle(file, &bundle).map_err(|e| e.to_string())?;
+
+    tracing::info!(
+        "[BrushBundle] Exported {} presets, {} tips, {} patterns to {}",
+        bundle.manifest.presets.len(),
+        bundle.manifest.tips.len(),
+        bundle.manifest.patterns.len(),
+        path.display()
+    );
+    Ok(BrushBundleExportResult {
+        preset_count: bundle.manifest.presets.len(),
+        tip_count: bundle.manifest.tips.len(),
+        pattern_count: bundle.manifest.patterns.len(),
+    })
+}
+
+/// Import a `.sutubrush` file into the brush and pattern libraries
+pub fn import_bundle(path: &Path) -> Result<BrushBundleImportResult, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let bundle = read_bundle(BufReader::new(file)).map_err(|e| e.to_string())?;
+    let source = path.to_string_lossy().to_string();
+
+    let mut pattern_aliases = HashMap::new();
+    let mut imported_pattern_count = 0usize;
+    let mut skipped_pattern_count = 0usize;
+    for resource in &bundle.manifest.patterns {
+        let Some(bitmap) = bundle.pattern_bitmaps.get(&resource.id) else {
+            continue;
+        };
+        // Trust the decoded bitmap over the manifest for the pixel size
+        let resource = PatternResource {
+            width: bitmap.width,
+            height: bitmap.height,
+            ..resource.clone()
+        };
+        let (id, added) =
+            pattern::library::import_pattern(&resource, bitmap.pixels.clone(), &source)?;
+        if added {
+            imported_pattern_count += 1;
+        } else {
+            skipped_pattern_count += 1;
+        }
+        pattern_aliases.insert(resource.id.clone(), id);
+    }
+
+    let BrushBundle {
+        manifest,
+        tip_bitmaps,
+        ..
+    } = bundle;
+    let presets = manifest
+        .presets
+        .into_iter()
+        .map(|mut entry| {
+            remap_pattern_id(&mut entry.preset, &pattern_aliases);
+            entry
+        })
+        .collect();
+    let tips: Vec<BrushPreset> = manifest
+        .tips
+        .into_iter()
+        .map(|resource| {
+            let mut tip = resource.tip;
+            remap_pattern_id(&mut tip, &pattern_aliases);
+            tip
+        })
+        .collect();
+    let tip_names: HashMap<String, String> = tips
+        .iter()
+        .map(|tip| (tip.id.clone(), tip.name.clone()))
+        .collect();
+
+    let library = brush_library::import_bundle(&source, presets, tips, |bundle_id, tip_id| {
+        if let Some(bitmap) = tip_bitmaps.get(bundle_id) {
+            cache_brush_gray_ref(
+                tip_id.to_string(),
+                &bitmap.pixels,
+                bitmap.width,
+                bitmap.height,
+                tip_names.get(bundle_id).cloned().unwrap_or_default(),
+            );
+        }
+    })?;
+
+    Ok(BrushBundleImportResult {
+        library,
+        imported_pattern_count,
+        skipped_pattern_count,
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn make_tip(id: &str) -> BrushPreset {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": "Tip",
+            "diameter": 12.0,
+            "spacing": 25.0,
+            "hardness": 100.0,
+            "angle": 0.0,
+            "roundness": 100.0,
+            "hasTexture": true,
+            "isComputed": false,
+            "sizePressure": true,
+            "opacityPressure": false,
+        }))
+        .unwrap()
+    }
+
+    fn make_bundle() -> BrushBundle {
+        let pattern = PatternResource {
+            id: "pattern-1".to_string(),
+            name: "Paper".to_string(),
+            content_hash: String::new(),
+            width: 2,
+            height: 1,
+            mode: PatternMode::RGB,
+            source: "test".to_string(),
+            group: None,
+            tags: Vec::new(),
+            last_used_ms: None,
+        };
+        BrushBundle {
+            manifest: BrushBundleManifest {
+                version: BRUSH_BUNDLE_VERSION,
+                presets: Vec::new(),
+                groups: Vec::new(),
+                tips: vec![
+                    BrushTipResource {
+                        tip: make_tip("tip-1"),
+                        source: "test".to_string(),
+                        content_hash: "a".to_string(),
+                    },
+                    BrushTipResource {
+                        tip: make_tip("computed"),
+                        source: "test".to_string(),
+                        content_hash: "b".to_string(),
+                    },
+                ],
+                patterns: vec![pattern],
+            },
+            tip_bitmaps: HashMap::from([(
+                "tip-1".to_string(),
+                BundleBitmap {
+                    width: 3,
+                    height: 2,
+                    pixels: vec![0, 64, 128, 192, 255, 7],
+                },
+            )]),
+            pattern_bitmaps: HashMap::from([(
+                "pattern-1".to_string(),
+                BundleBitmap {
+                    width: 2,
+                    height: 1,
+                    pixels: vec![10, 20, 30, 255, 40, 50, 60, 128],
+                },
+            )]),
+        }
+    }
+
+    #[test]
+    fn bundle_round_trips_manifest_and_bitmaps() {
+        let bundle = make_bundle();
+        let mut buffer = Cursor::new(Vec::new());
+        write_bundle(&mut buffer, &bundle).unwrap();
+        buffer.set_position(0);
+
+        let read = read_bundle(buffer).unwrap();
+        assert_eq!(read.manifest.tips.len(), 2);
+        assert_eq!(read.manifest.patterns[0].name, "Paper");
+        assert_eq!(read.tip_bitmaps, bundle.tip_bitmaps);
+        assert_eq!(read.pattern_bitmaps, bundle.pattern_bitmaps);
+        assert!(!read.tip_bitmaps.contains_key("computed"));
+    }
+
+    #[test]
+    fn rejects_newer_versions_and_foreign_archives() {
+        let mut bundle = make_bundle();
+        bundle.manifest.version = BRUSH_BUNDLE_VERSION + 1;
+        let mut buffer = Cursor::new(Vec::new());
+        write_bundle(&mut buffer, &bundle).unwrap();
+        buffer.set_position(0);
+        assert!(matches!(
+            read_bundle(buffer),
+            Err(FileError::InvalidFormat(_))
+        ));
+
+        let mut buffer = Cursor::new(Vec::new());
+        let mut zip = ZipWriter::new(&mut buffer);
+        zip.start_file("mimetype", SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"image/openraster").unwrap();
+        zip.finish().unwrap();
+        buffer.set_position(0);
+        assert!(matches!(
+            read_bundle(buffer),
+            Err(FileError::InvalidFormat(_))
+        ));
+    }
+}
@@ -13,7 +13,7 @@ use lz4_flex::compress_prepend_size;
 use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Cached brush texture data
 #[derive(Debug, Clone)]
@@ -114,8 +114,26 @@ impl BrushCache {
 
 // === Disk persistence ===
 
+/// Whether `id` can name a cache file: a non-empty `[A-Za-z0-9_-]+` token
+pub(crate) fn is_safe_blob_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+}
+
+/// `<dir>/<id>.bin`, or `None` (logged) when `id` is not a safe blob id
+pub(crate) fn blob_path(dir: &Path, id: &str) -> Option<PathBuf> {
+    if is_safe_blob_id(id) {
+        Some(dir.join(format!("{}.bin", id)))
+    } else {
+        tracing::warn!("Refusing cache path for unsafe id {:?}", id);
+        None
+    }
+}
+
 /// Get the brush cache directory path
-fn get_brush_cache_dir() -> PathBuf {
+pub fn get_brush_cache_dir() -> PathBuf {
     dirs::data_dir()
         .unwrap_or_else(|| PathBuf::from("."))
         .join(APP_CONFIG_DIR_NAME)
@@ -135,11 +153,16 @@ pub fn ensure_cache_dir() {
 /// Save brush to disk
 /// Format: width(4) + height(4) + name_len(4) + name_bytes + compressed_data
 fn save_brush_to_disk(brush_id: &str, brush: &CachedBrush) {
-    let dir = get_brush_cache_dir();
-    let file_path = dir.join(format!("{}.bin", brush_id));
+    save_brush_to_disk_in_dir(&get_brush_cache_dir(), brush_id, brush);
+}
+
+pub(crate) fn save_brush_to_disk_in_dir(dir: &Path, brush_id: &str, brush: &CachedBrush) {
+    let Some(file_path) = blob_path(dir, brush_id) else {
+        return;
+    };
 
     let result = (|| -> std::io::Result<()> {
-        std::fs::create_dir_all(&dir)?;
+        std::fs::create_dir_all(dir)?;
 
         let mut file = std::fs::File::create(&file_path)?;
         let name_bytes = brush.name.as_bytes();
@@ -163,7 +186,11 @@ fn save_brush_to_disk(brush_id: &str, brush: &CachedBrush) {
 
 /// Load brush from disk
 fn load_brush_from_disk(brush_id: &str) -> Option<CachedBrush> {
-    let file_path = get_brush_cache_dir().join(format!("{}.bin", brush_id));
+    load_brush_from_disk_in_dir(&get_brush_cache_dir(), brush_id)
+}
+
+pub(crate) fn load_brush_from_disk_in_dir(dir: &Path, brush_id: &str) -> Option<CachedBrush> {
+    let file_path = blob_path(dir, brush_id)?;
 
     if !file_path.exists() {
         return None;
@@ -214,6 +241,28 @@ fn load_brush_from_disk(brush_id: &str) -> Option<CachedBrush> {
     }
 }
 
+/// Every readable brush persisted in a cache directory, keyed by brush ID.
+/// Used to rebuild the library index when it is lost.
+pub fn read_cached_brushes_in_dir(dir: &Path) -> Vec<(String, CachedBrush)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut brushes: Vec<(String, CachedBrush)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension()? != "bin" {
+                return None;
+            }
+            let brush_id = path.file_stem()?.to_str()?.to_string();
+            let brush = load_brush_from_disk_in_dir(dir, &brush_id)?;
+            Some((brush_id, brush))
+        })
+        .collect();
+    brushes.sort_by(|a, b| a.0.cmp(&b.0));
+    brushes
+}
+
 // === Global cache operations ===
 
 /// Initialize the global brush cache
@@ -315,7 +364,9 @@ pub fn delete_cached_brush(brush_id: &str) {
         }
     }
 
-    let file_path = get_brush_cache_dir().join(format!("{}.bin", brush_id));
+    let Some(file_path) = blob_path(&get_brush_cache_dir(), brush_id) else {
+        return;
+    };
     if let Err(err) = std::fs::remove_file(&file_path) {
         if err.kind() != std::io::ErrorKind::NotFound {
             tracing::warn!("Failed to delete brush cache {}: {}", brush_id, err);
@@ -0,0 +1,231 @@
+//! Stroke effects - Wet Edges and tip Noise (Photoshop-compatible)
+//!
+//! Both effects only reshape alpha; color is left to the compositing step.
+//! - Wet Edges is a stroke-level remap applied once when the stroke buffer is
+//!   composited to the layer (matches frontend `strokeBuffer.ts:buildWetEdgeLut`)
+//! - Noise is a per-dab grain applied to the soft edge of each tip mask
+
+/// Center keeps 65% of the original opacity
+const WET_EDGE_CENTER_OPACITY: f32 = 0.65;
+/// Maximum edge boost for soft brushes
+const WET_EDGE_MAX_BOOST: f32 = 1.8;
+/// Minimum edge boost for hard brushes (must stay above center opacity)
+const WET_EDGE_MIN_BOOST: f32 = 1.4;
+/// Hardness above which the boost fades toward `WET_EDGE_MIN_BOOST`
+const WET_EDGE_HARD_THRESHOLD: f32 = 0.7;
+/// Gamma applied to soft brush alpha before tone mapping
+const WET_EDGE_SOFT_GAMMA: f32 = 1.3;
+
+/// Wet Edges alpha remapping
+///
+/// Edges (low alpha) are boosted and the center (high alpha) fades, which
+/// produces the darker rim of a watercolor stroke. Hard brushes skip the
+/// gamma shaping so their 1px anti-aliased edge does not turn into a halo.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WetEdge {
+    /// Effect strength (0 = off, 1 = full)
+    strength: f32,
+    /// Brush hardness (0-1) the stroke was painted with
+    hardness: f32,
+    /// Edge boost resolved from hardness
+    boost: f32,
+}
+
+impl WetEdge {
+    /// Create wet edge parameters for a stroke
+    ///
+    /// # Arguments
+    /// * `strength` - Effect strength (0-1)
+    /// * `hardness` - Brush hardness (0-1)
+    pub fn new(strength: f32, hardness: f32) -> Self {
+        let strength = strength.clamp(0.0, 1.0);
+        let hardness = hardness.clamp(0.0, 1.0);
+
+        let boost = if hardness > WET_EDGE_HARD_THRESHOLD {
+            // Transition zone: smooth interpolation
+            let t = (hardness - WET_EDGE_HARD_THRESHOLD) / (1.0 - WET_EDGE_HARD_THRESHOLD);
+            WET_EDGE_MAX_BOOST * (1.0 - t) + WET_EDGE_MIN_BOOST * t
+        } else {
+            WET_EDGE_MAX_BOOST
+        };
+
+        Self {
+            strength,
+            hardness,
+            boost,
+        }
+    }
+
+    /// Whether the effect changes anything
+    pub fn is_active(&self) -> bool {
+        self.strength > 0.001
+    }
+
+    /// Remap stroke alpha (0-1)
+    #[inline]
+    pub fn apply(&self, alpha: f32) -> f32 {
+        let alpha = alpha.clamp(0.0, 1.0);
+        let shaped = if self.hardness > WET_EDGE_HARD_THRESHOLD {
+            alpha
+        } else {
+            alpha.powf(WET_EDGE_SOFT_GAMMA)
+        };
+
+        // Core tone mapping: edge (low alpha) -> boost, center (high alpha) -> fade
+        let multiplier = self.boost - (self.boost - WET_EDGE_CENTER_OPACITY) * shaped;
+        let wet = alpha * multiplier;
+
+        (alpha * (1.0 - self.strength) + wet * self.strength).min(1.0)
+    }
+}
+
+/// Seeded tip noise
+///
+/// The grain is fixed in canvas space (like Photoshop), so overlapping dabs
+/// reinforce the same pattern instead of averaging it out. Only the soft edge
+/// of the mask (0 < alpha < 1) is affected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TipNoise {
+    /// Seed mixed into the per-pixel hash
+    seed: u32,
+    /// Grain cell size in pixels
+    grain: u32,
+    /// Effect strength (0-1)
+    strength: f32,
+}
+
+impl TipNoise {
+    /// Create tip noise with 1px grain
+    pub fn new(seed: u32, strength: f32) -> Self {
+        Self {
+            seed,
+            grain: 1,
+            strength: strength.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Set grain cell size in pixels
+    pub fn with_grain(mut self, grain: u32) -> Self {
+        self.grain = grain.max(1);
+        self
+    }
+
+    /// Noise value (0-1) at a canvas pixel
+    #[inline]
+    pub fn sample(&self, x: i32, y: i32) -> f32 {
+        let grain = self.grain as i32;
+        hash_noise01(
+            x.div_euclid(grain) as u32,
+            y.div_euclid(grain) as u32,
+            self.seed,
+        )
+    }
+
+    /// Apply grain to a tip mask value (0-1) at a canvas pixel
+    #[inline]
+    pub fn apply(&self, mask: f32, x: i32, y: i32) -> f32 {
+        if self.strength <= 0.001 || mask <= 0.001 || mask >= 0.999 {
+            return mask;
+        }
+        let over = overlay(mask, self.sample(x, y));
+        (mask + (over - mask) * self.strength).clamp(0.0, 1.0)
+    }
+}
+
+/// Overlay of `value` (base) with `noise` (blend)
+#[inline]
+fn overlay(value: f32, noise: f32) -> f32 {
+    if value < 0.5 {
+        2.0 * value * noise
+    } else {
+        1.0 - 2.0 * (1.0 - value) * (1.0 - noise)
+    }
+}
+
+/// Integer hash noise in [0, 1] (same mixing as the frontend dissolve noise)
+#[inline]
+fn hash_noise01(x: u32, y: u32, seed: u32) -> f32 {
+    let n = x
+        .wrapping_mul(1973)
+        .wrapping_add(y.wrapping_mul(9277))
+        .wrapping_add(seed.wrapping_mul(26699))
+        .wrapping_add(89173);
+    let m = (n << 13) ^ n;
+    let mm = m.wrapping_mul(m);
+    let t = m
+        .wrapping_mul(mm.wrapping_mul(15731).wrapping_add(789221))
+        .wrapping_add(1376312589);
+    (t & 0x00ff_ffff) as f32 / 0x00ff_ffff as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wet_edge_fades_center_and_boosts_edge() {
+        let wet = WetEdge::new(1.0, 0.0);
+
+        // Center: fully covered pixels keep ~65%
+        assert!((wet.apply(1.0) - WET_EDGE_CENTER_OPACITY).abs() < 0.01);
+        // Edge: low alpha is boosted
+        assert!(wet.apply(0.2) > 0.2);
+        // Zero stays zero
+        assert_eq!(wet.apply(0.0), 0.0);
+    }
+
+    #[test]
+    fn test_wet_edge_strength_zero_is_identity() {
+        let wet = WetEdge::new(0.0, 0.5);
+        assert!(!wet.is_active());
+        for i in 0..=10 {
+            let a = i as f32 / 10.0;
+            assert!((wet.apply(a) - a).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_wet_edge_hard_brush_uses_min_boost() {
+        let hard = WetEdge::new(1.0, 1.0);
+        let soft = WetEdge::new(1.0, 0.0);
+        assert!((hard.boost - WET_EDGE_MIN_BOOST).abs() < 1e-6);
+        assert!((soft.boost - WET_EDGE_MAX_BOOST).abs() < 1e-6);
+        // Hard brush edge gets less boost than soft brush edge
+        assert!(hard.apply(0.1) < soft.apply(0.1));
+    }
+
+    #[test]
+    fn test_noise_is_deterministic_per_seed() {
+        let a = TipNoise::new(7, 1.0);
+        let b = TipNoise::new(7, 1.0);
+        let c = TipNoise::new(8, 1.0);
+
+        let mut differs = false;
+        for i in 0..32 {
+            assert_eq!(a.sample(i, i * 3), b.sample(i, i * 3));
+            if a.sample(i, i * 3) != c.sample(i, i * 3) {
+                differs = true;
+            }
+        }
+        assert!(differs);
+    }
+
+    #[test]
+    fn test_noise_only_touches_soft_edge() {
+        let noise = TipNoise::new(1, 1.0);
+        for x in 0..16 {
+            assert_eq!(noise.apply(0.0, x, 0), 0.0);
+            assert_eq!(noise.apply(1.0, x, 0), 1.0);
+        }
+
+        let changed = (0..64).any(|x| (noise.apply(0.5, x, 3) - 0.5).abs() > 0.05);
+        assert!(changed);
+    }
+
+    #[test]
+    fn test_noise_grain_quantizes_cells() {
+        let noise = TipNoise::new(3, 1.0).with_grain(4);
+        assert_eq!(noise.sample(0, 0), noise.sample(3, 3));
+        assert_eq!(noise.sample(-1, -1), noise.sample(-4, -4));
+    }
+}
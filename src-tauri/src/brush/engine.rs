@@ -1,9 +1,17 @@
 //! Brush engine - processes raw input into renderable brush strokes
 
+use super::effects::{TipNoise, WetEdge};
 use super::interpolation::{interpolate_catmull_rom, InterpolationMode};
+use super::stabilizer::{Stabilizer, StabilizerMode};
+use super::stamper::StamperConfig;
+use super::stroke_buffer::StrokeBuffer;
 use super::{BlendMode, BrushPoint, PressureCurve, StrokeSegment};
+use crate::abr::BrushPreset;
 use crate::input::RawInputPoint;
 
+/// Seed of the tip Noise grain
+const TIP_NOISE_SEED: u32 = 0x5eed;
+
 /// Brush settings
 #[derive(Debug, Clone)]
 pub struct BrushSettings {
@@ -25,6 +33,57 @@ pub struct BrushSettings {
     pub opacity_curve: PressureCurve,
     /// Interpolation mode
     pub interpolation: InterpolationMode,
+    /// Stabilizer applied before interpolation
+    pub stabilizer: StabilizerMode,
+    /// Flow (opacity per dab, 0-1)
+    pub flow: f32,
+    /// Wet Edges: darken the stroke rim when compositing
+    pub wet_edge: bool,
+    /// Build-up: keep stamping while the pen rests
+    pub buildup: bool,
+    /// Noise: grain on the soft edge of each tip
+    pub noise: bool,
+}
+
+impl BrushSettings {
+    /// Settings of a library preset, including its Wet Edges, Build-up and
+    /// Noise toggles
+    pub fn from_preset(preset: &BrushPreset) -> Self {
+        Self {
+            size: preset.diameter.max(1.0),
+            opacity: preset.base_opacity.unwrap_or(1.0).clamp(0.0, 1.0),
+            hardness: (preset.hardness / 100.0).clamp(0.0, 1.0),
+            spacing: (preset.spacing / 100.0).max(0.01),
+            pressure_size: preset.size_pressure,
+            pressure_opacity: preset.opacity_pressure,
+            flow: preset.base_flow.unwrap_or(1.0).clamp(0.0, 1.0),
+            wet_edge: preset.wet_edge_enabled.unwrap_or(false),
+            buildup: preset.buildup_enabled.unwrap_or(false),
+            noise: preset.noise_enabled.unwrap_or(false),
+            ..Self::default()
+        }
+    }
+
+    /// Stamper configuration for these settings
+    pub fn stamper_config(&self) -> StamperConfig {
+        StamperConfig {
+            size: self.size,
+            spacing: self.spacing,
+            flow: self.flow,
+            hardness: self.hardness,
+            pressure_size: self.pressure_size,
+            pressure_alpha: self.pressure_opacity,
+            buildup: self.buildup,
+            stabilizer: self.stabilizer,
+            ..StamperConfig::default()
+        }
+    }
+
+    /// Set Wet Edges and tip Noise on the buffer the stroke is painted into
+    pub fn configure_stroke_buffer(&self, buffer: &mut StrokeBuffer) {
+        buffer.set_wet_edge(self.wet_edge.then(|| WetEdge::new(1.0, self.hardness)));
+        buffer.set_tip_noise(self.noise.then(|| TipNoise::new(TIP_NOISE_SEED, 1.0)));
+    }
 }
 
 impl Default for BrushSettings {
@@ -39,6 +98,11 @@ impl Default for BrushSettings {
             size_curve: PressureCurve::Linear,
             opacity_curve: PressureCurve::Linear,
             interpolation: InterpolationMode::CatmullRom,
+            stabilizer: StabilizerMode::Off,
+            flow: 1.0,
+            wet_edge: false,
+            buildup: false,
+            noise: false,
         }
     }
 }
@@ -91,8 +155,9 @@ impl BrushEngine {
             return vec![];
         }
 
-        // Interpolate points for smoother strokes
-        let interpolated = self.interpolate_points(points);
+        // Remove hand jitter, then interpolate points for smoother strokes
+        let stabilized = self.stabilize_points(points);
+        let interpolated = self.interpolate_points(&stabilized);
 
         // Convert to brush points with pressure curves applied
         let brush_points: Vec<BrushPoint> = interpolated
@@ -109,6 +174,18 @@ impl BrushEngine {
         }]
     }
 
+    /// Stabilize a complete stroke, including the tail that catches up with the pen
+    fn stabilize_points(&self, points: &[RawInputPoint]) -> Vec<RawInputPoint> {
+        if self.settings.stabilizer == StabilizerMode::Off {
+            return points.to_vec();
+        }
+
+        let mut stabilizer = Stabilizer::new(self.settings.stabilizer);
+        let mut result: Vec<RawInputPoint> = points.iter().map(|p| stabilizer.process(p)).collect();
+        result.extend(stabilizer.finish());
+        result
+    }
+
     /// Interpolate raw points based on settings
     fn interpolate_points(&self, points: &[RawInputPoint]) -> Vec<RawInputPoint> {
         match self.settings.interpolation {
@@ -268,4 +345,99 @@ mod tests {
 
         assert!(bp_high.size > bp_low.size);
     }
+
+    #[test]
+    fn test_preset_effect_flags_reach_stamper_and_stroke_buffer() {
+        let preset: BrushPreset = match serde_json::from_value(serde_json::json!({
+            "id": "wet",
+            "name": "Wet",
+            "diameter": 12.0,
+            "spacing": 20.0,
+            "hardness": 50.0,
+            "angle": 0.0,
+            "roundness": 100.0,
+            "hasTexture": false,
+            "isComputed": true,
+            "sizePressure": false,
+            "opacityPressure": false,
+            "wetEdgeEnabled": true,
+            "buildupEnabled": true,
+            "noiseEnabled": true,
+        })) {
+            Ok(preset) => preset,
+            Err(e) => panic!("preset should parse: {}", e),
+        };
+        let settings = BrushSettings::from_preset(&preset);
+        assert!(settings.wet_edge && settings.buildup && settings.noise);
+
+        let config = settings.stamper_config();
+        assert!(config.buildup);
+        assert_eq!(
+            (config.size, config.spacing, config.hardness),
+            (12.0, 0.2, 0.5)
+        );
+
+        // Wet Edges thins the center of a composited full-coverage dab
+        let composite = |settings: &BrushSettings| {
+            let mut buffer = StrokeBuffer::new(32, 32);
+            settings.configure_stroke_buffer(&mut buffer);
+            buffer.begin_stroke();
+            buffer.stamp_dab(16.0, 16.0, 6.0, [0.0; 3], 1.0, settings.hardness);
+            let mut layer = vec![0u8; 32 * 32 * 4];
+            buffer.end_stroke(&mut layer, 1.0);
+            layer[(16 * 32 + 16) * 4 + 3]
+        };
+        let plain = BrushSettings {
+            wet_edge: false,
+            noise: false,
+            ..settings.clone()
+        };
+        assert!(composite(&settings) < composite(&plain));
+    }
+
+    #[test]
+    fn test_stabilizer_smooths_jitter_and_reaches_end() {
+        // Zig-zag tremor of +-3px around y = 0
+        let points: Vec<RawInputPoint> = (0..40)
+            .map(|i| RawInputPoint {
+                x: i as f32 * 4.0,
+                y: if i % 2 == 0 { 3.0 } else { -3.0 },
+                pressure: 0.5,
+                tilt_x: 0.0,
+                tilt_y: 0.0,
+                timestamp_ms: i as u64 * 5,
+            })
+            .collect();
+
+        // Largest deviation in the middle of the stroke (the tail ends on the pen)
+        let max_deviation = |settings: BrushSettings| {
+            let engine = BrushEngine::with_settings(settings);
+            let segments = engine.process(&points);
+            let stroke = &segments[0].points;
+            let Some(last) = stroke.last() else {
+                panic!("stroke should not be empty");
+            };
+            assert!((last.x - 156.0).abs() < 0.01, "stroke ends at {}", last.x);
+            stroke[stroke.len() / 4..stroke.len() * 3 / 4]
+                .iter()
+                .map(|p| p.y.abs())
+                .fold(0.0f32, f32::max)
+        };
+
+        let raw = max_deviation(BrushSettings {
+            interpolation: InterpolationMode::Linear,
+            ..Default::default()
+        });
+        let stabilized = max_deviation(BrushSettings {
+            interpolation: InterpolationMode::Linear,
+            stabilizer: StabilizerMode::WeightedAverage { samples: 8 },
+            ..Default::default()
+        });
+        assert!(
+            stabilized < raw * 0.5,
+            "raw {} stabilized {}",
+            raw,
+            stabilized
+        );
+    }
 }
@@ -0,0 +1,307 @@
+//! Brush preset revision history.
+//!
+//! Every preset keeps its "original" state (as imported or first created) plus a
+//! bounded list of saved revisions. History lives in `history.json` next to the
+//! library index so the index stays small.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeSet, HashMap};
+
+use crate::abr::BrushPreset;
+
+/// Saved revisions kept per preset, not counting the original
+pub const MAX_PRESET_REVISIONS: usize = 20;
+
+/// Revision number of the original snapshot; never evicted
+pub const ORIGINAL_REVISION: u32 = 0;
+
+/// Identity and derived fields left out of diffs (renames are not revisions;
+/// cursor outlines follow the tip)
+const IGNORED_FIELDS: &[&str] = &["id", "name", "sourceUuid"];
+const IGNORED_PREFIXES: &[&str] = &["cursor"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BrushPresetRevisionKind {
+    Original,
+    Save,
+    Revert,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrushPresetRevision {
+    pub revision: u32,
+    pub saved_at_ms: u64,
+    pub kind: BrushPresetRevisionKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reverted_from: Option<u32>,
+    pub tip_id: Option<String>,
+    pub preset: BrushPreset,
+}
+
+/// Revision listing entry: metadata plus fields changed since the previous one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrushPresetRevisionSummary {
+    pub revision: u32,
+    pub saved_at_ms: u64,
+    pub kind: BrushPresetRevisionKind,
+    pub reverted_from: Option<u32>,
+    pub changed_fields: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrushPresetFieldChange {
+    /// Dotted camelCase path, e.g. `shapeDynamics.sizeJitter`
+    pub field: String,
+    pub before: Value,
+    pub after: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrushPresetHistory {
+    pub original: BrushPresetRevision,
+    /// Oldest first
+    pub revisions: Vec<BrushPresetRevision>,
+    next_revision: u32,
+}
+
+impl BrushPresetHistory {
+    pub fn new(preset: &BrushPreset, tip_id: Option<String>, now_ms: u64) -> Self {
+        Self {
+            original: BrushPresetRevision {
+                revision: ORIGINAL_REVISION,
+                saved_at_ms: now_ms,
+                kind: BrushPresetRevisionKind::Original,
+                reverted_from: None,
+                tip_id,
+                preset: preset.clone(),
+            },
+            revisions: Vec::new(),
+            next_revision: ORIGINAL_REVISION + 1,
+        }
+    }
+
+    pub fn latest(&self) -> &BrushPresetRevision {
+        self.revisions.last().unwrap_or(&self.original)
+    }
+
+    pub fn get(&self, revision: u32) -> Option<&BrushPresetRevision> {
+        if revision == ORIGINAL_REVISION {
+            return Some(&self.original);
+        }
+        self.revisions.iter().find(|r| r.revision == revision)
+    }
+
+    /// Append a revision unless it matches the latest state. Returns the new
+    /// revision number, or `None` when nothing changed.
+    pub fn record(
+        &mut self,
+        preset: &BrushPreset,
+        tip_id: Option<String>,
+        kind: BrushPresetRevisionKind,
+        reverted_from: Option<u32>,
+        now_ms: u64,
+    ) -> Option<u32> {
+        let latest = self.latest();
+        if latest.tip_id == tip_id && diff_presets(&latest.preset, preset).is_empty() {
+            return None;
+        }
+
+        let revision = self.next_revision;
+        self.next_revision += 1;
+        self.revisions.push(BrushPresetRevision {
+            revision,
+            saved_at_ms: now_ms,
+            kind,
+            reverted_from,
+            tip_id,
+            preset: preset.clone(),
+        });
+        if self.revisions.len() > MAX_PRESET_REVISIONS {
+            let excess = self.revisions.len() - MAX_PRESET_REVISIONS;
+            self.revisions.drain(..excess);
+        }
+        Some(revision)
+    }
+
+    /// Original first, then saved revisions oldest first
+    pub fn summaries(&self) -> Vec<BrushPresetRevisionSummary> {
+        let mut previous: Option<&BrushPresetRevision> = None;
+        std::iter::once(&self.original)
+            .chain(self.revisions.iter())
+            .map(|revision| {
+                let changed_fields = previous
+                    .map(|prev| {
+                        diff_revisions(prev, revision)
+                            .into_iter()
+                            .map(|change| change.field)
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                previous = Some(revision);
+                BrushPresetRevisionSummary {
+                    revision: revision.revision,
+                    saved_at_ms: revision.saved_at_ms,
+                    kind: revision.kind,
+                    reverted_from: revision.reverted_from,
+                    changed_fields,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Per-preset histories, persisted as `history.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrushPresetHistoryIndex {
+    pub presets: HashMap<String, BrushPresetHistory>,
+}
+
+/// Field-by-field diff of two revisions, including the main tip
+pub fn diff_revisions(
+    before: &BrushPresetRevision,
+    after: &BrushPresetRevision,
+) -> Vec<BrushPresetFieldChange> {
+    let mut changes = diff_presets(&before.preset, &after.preset);
+    if before.tip_id != after.tip_id {
+        changes.push(BrushPresetFieldChange {
+            field: "tipId".to_string(),
+            before: before.tip_id.clone().map_or(Value::Null, Value::String),
+            after: after.tip_id.clone().map_or(Value::Null, Value::String),
+        });
+        changes.sort_by(|a, b| a.field.cmp(&b.field));
+    }
+    changes
+}
+
+/// Field-by-field diff of two presets. Nested settings objects are flattened to
+/// dotted paths; arrays (e.g. curves) compare as a whole.
+pub fn diff_presets(before: &BrushPreset, after: &BrushPreset) -> Vec<BrushPresetFieldChange> {
+    let mut before_fields = Map::new();
+    let mut after_fields = Map::new();
+    flatten_into("", to_value(before), &mut before_fields);
+    flatten_into("", to_value(after), &mut after_fields);
+
+    let keys: BTreeSet<&String> = before_fields.keys().chain(after_fields.keys()).collect();
+    keys.into_iter()
+        .filter(|key| !is_ignored(key))
+        .filter_map(|key| {
+            let old = before_fields.get(key).cloned().unwrap_or(Value::Null);
+            let new = after_fields.get(key).cloned().unwrap_or(Value::Null);
+            (old != new).then(|| BrushPresetFieldChange {
+                field: key.clone(),
+                before: old,
+                after: new,
+            })
+        })
+        .collect()
+}
+
+fn to_value(preset: &BrushPreset) -> Value {
+    serde_json::to_value(preset).unwrap_or(Value::Null)
+}
+
+fn flatten_into(prefix: &str, value: Value, out: &mut Map<String, Value>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                let path = if prefix.is_empty() {
+                    key
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten_into(&path, value, out);
+            }
+        }
+        other => {
+            out.insert(prefix.to_string(), other);
+        }
+    }
+}
+
+fn is_ignored(field: &str) -> bool {
+    let top_level = field.split('.').next().unwrap_or(field);
+    IGNORED_FIELDS.contains(&top_level)
+        || IGNORED_PREFIXES
+            .iter()
+            .any(|prefix| top_level.starts_with(prefix))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn preset() -> BrushPreset {
+        serde_json::from_value(serde_json::json!({
+            "id": "preset-1",
+            "name": "Preset 1",
+            "diameter": 20.0,
+            "spacing": 25.0,
+            "hardness": 100.0,
+            "angle": 0.0,
+            "roundness": 100.0,
+            "hasTexture": false,
+            "isComputed": true,
+            "sizePressure": true,
+            "opacityPressure": false
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn diff_reports_nested_fields_and_ignores_identity() {
+        let before = preset();
+        let mut after = before.clone();
+        after.name = "Renamed".to_string();
+        after.spacing += 5.0;
+        after.dual_brush_settings = Some(crate::abr::DualBrushSettings {
+            enabled: true,
+            ..Default::default()
+        });
+
+        let changes = diff_presets(&before, &after);
+        let fields: Vec<&str> = changes.iter().map(|c| c.field.as_str()).collect();
+        assert!(fields.contains(&"spacing"));
+        assert!(fields.contains(&"dualBrushSettings.enabled"));
+        assert!(!fields.contains(&"name"));
+    }
+
+    #[test]
+    fn history_is_bounded_but_keeps_original() {
+        let base = preset();
+        let mut history = BrushPresetHistory::new(&base, None, 1);
+        assert_eq!(
+            history.record(&base, None, BrushPresetRevisionKind::Save, None, 2),
+            None
+        );
+
+        for step in 0..(MAX_PRESET_REVISIONS + 5) {
+            let mut next = base.clone();
+            next.spacing = step as f32 + 100.0;
+            history.record(
+                &next,
+                None,
+                BrushPresetRevisionKind::Save,
+                None,
+                step as u64,
+            );
+        }
+
+        assert_eq!(history.revisions.len(), MAX_PRESET_REVISIONS);
+        assert_eq!(history.revisions[0].revision, 6);
+        assert!(history.get(ORIGINAL_REVISION).is_some());
+        assert!(history.get(1).is_none());
+
+        let summaries = history.summaries();
+        assert_eq!(summaries.len(), MAX_PRESET_REVISIONS + 1);
+        assert!(summaries[0].changed_fields.is_empty());
+        assert_eq!(summaries[1].changed_fields, vec!["spacing".to_string()]);
+    }
+}
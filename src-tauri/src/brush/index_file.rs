@@ -0,0 +1,316 @@
+//! Crash-safe, versioned JSON index files shared by the brush and pattern libraries.
+//!
+//! Writes go to `<file>.tmp`, are flushed to disk, then renamed over the original,
+//! so a crash leaves either the old or the new index. Each file carries a
+//! `schemaVersion`; older layouts are migrated on load. Files that cannot be parsed
+//! are moved aside to `<stem>.corrupt-<timestamp>.json` instead of being dropped;
+//! files that fail to read or come from a newer app version are left untouched.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub const SCHEMA_VERSION_KEY: &str = "schemaVersion";
+pub const USAGE_FILE_NAME: &str = "usage.json";
+const USAGE_SCHEMA_VERSION: u32 = 1;
+
+/// Upgrades a raw index by one version; `migrations[n]` takes version `n` to `n + 1`.
+/// Files written before versioning count as version 0.
+pub type IndexMigration = fn(&mut Value) -> Result<(), String>;
+
+#[derive(Debug)]
+pub enum IndexLoad<T> {
+    Missing,
+    Loaded {
+        index: T,
+        /// Schema version the file was migrated from, if it was older
+        migrated_from: Option<u32>,
+    },
+    /// The file was unusable; it was moved to `quarantined` when possible
+    Corrupt {
+        error: String,
+        quarantined: Option<PathBuf>,
+    },
+    /// The file could not be read or has a newer schema version. It is left in
+    /// place, and the caller must not write over it.
+    Unavailable {
+        error: String,
+    },
+}
+
+enum ParseFailure {
+    /// Written by a newer app version; valid, just not for us
+    Newer(String),
+    Invalid(String),
+}
+
+pub fn load_index<T: DeserializeOwned>(
+    path: &Path,
+    current_version: u32,
+    migrations: &[IndexMigration],
+) -> IndexLoad<T> {
+    load_index_inner(path, current_version, migrations, true)
+}
+
+/// Like `load_index`, but an invalid file stays where it is (for files this app
+/// does not own, such as a shared sync manifest)
+pub fn read_index<T: DeserializeOwned>(
+    path: &Path,
+    current_version: u32,
+    migrations: &[IndexMigration],
+) -> IndexLoad<T> {
+    load_index_inner(path, current_version, migrations, false)
+}
+
+fn load_index_inner<T: DeserializeOwned>(
+    path: &Path,
+    current_version: u32,
+    migrations: &[IndexMigration],
+    quarantine_invalid: bool,
+) -> IndexLoad<T> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return IndexLoad::Missing,
+        Err(err) => {
+            tracing::warn!("Failed to read index {}: {}", path.display(), err);
+            return IndexLoad::Unavailable {
+                error: err.to_string(),
+            };
+        }
+    };
+
+    match parse_index(&bytes, current_version, migrations) {
+        Ok((index, migrated_from)) => IndexLoad::Loaded {
+            index,
+            migrated_from,
+        },
+        Err(ParseFailure::Newer(error)) => {
+            tracing::warn!("Leaving index {} untouched: {}", path.display(), error);
+            IndexLoad::Unavailable { error }
+        }
+        Err(ParseFailure::Invalid(error)) => {
+            tracing::warn!("Index {} is unusable: {}", path.display(), error);
+            IndexLoad::Corrupt {
+                error,
+                quarantined: if quarantine_invalid {
+                    quarantine(path)
+                } else {
+                    None
+                },
+            }
+        }
+    }
+}
+
+fn parse_index<T: DeserializeOwned>(
+    bytes: &[u8],
+    current_version: u32,
+    migrations: &[IndexMigration],
+) -> Result<(T, Option<u32>), ParseFailure> {
+    let invalid = ParseFailure::Invalid;
+    let mut value: Value = serde_json::from_slice(bytes).map_err(|e| invalid(e.to_string()))?;
+    let object = value
+        .as_object_mut()
+        .ok_or_else(|| invalid("Index root is not an object".to_string()))?;
+    let version = match object.remove(SCHEMA_VERSION_KEY) {
+        None => 0,
+        Some(raw) => raw
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| invalid(format!("Invalid schema version: {}", raw)))?,
+    };
+    if version > current_version {
+        return Err(ParseFailure::Newer(format!(
+            "Schema version {} is newer than supported version {}",
+            version, current_version
+        )));
+    }
+
+    for step in version..current_version {
+        let migrate = migrations
+            .get(step as usize)
+            .ok_or_else(|| invalid(format!("No migration from schema version {}", step)))?;
+        migrate(&mut value).map_err(invalid)?;
+    }
+
+    let index = serde_json::from_value(value).map_err(|e| invalid(e.to_string()))?;
+    Ok((index, (version < current_version).then_some(version)))
+}
+
+pub fn save_index<T: Serialize>(path: &Path, version: u32, index: &T) -> std::io::Result<()> {
+    let mut value = serde_json::to_value(index)?;
+    if let Value::Object(object) = &mut value {
+        object.insert(SCHEMA_VERSION_KEY.to_string(), Value::from(version));
+    }
+    let json = serde_json::to_vec_pretty(&value)?;
+    write_atomic(path, &json)
+}
+
+/// Use counts of one library, kept in `usage.json` beside its index so recording a
+/// brush or pattern switch writes a few bytes instead of the whole index
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageIndex {
+    #[serde(default)]
+    pub entries: HashMap<String, UsageEntry>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageEntry {
+    #[serde(default)]
+    pub use_count: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used_ms: Option<u64>,
+}
+
+/// Load `usage.json` from `dir`; a missing or corrupt file yields no entries.
+/// Fails when the file exists but must not be overwritten.
+pub fn load_usage(dir: &Path) -> Result<UsageIndex, String> {
+    match load_index(&dir.join(USAGE_FILE_NAME), USAGE_SCHEMA_VERSION, &[]) {
+        IndexLoad::Loaded { index, .. } => Ok(index),
+        IndexLoad::Missing | IndexLoad::Corrupt { .. } => Ok(UsageIndex::default()),
+        IndexLoad::Unavailable { error } => Err(error),
+    }
+}
+
+pub fn save_usage(dir: &Path, usage: &UsageIndex) -> std::io::Result<()> {
+    save_index(&dir.join(USAGE_FILE_NAME), USAGE_SCHEMA_VERSION, usage)
+}
+
+/// Write via a synced temp file and rename, so readers never see a partial file
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp_path = sibling_path(path, ".tmp");
+    {
+        let mut file = std::fs::File::create(&tmp_path)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+    }
+    std::fs::rename(&tmp_path, path)
+}
+
+fn quarantine(path: &Path) -> Option<PathBuf> {
+    let stem = path.file_stem()?.to_string_lossy().to_string();
+    let stamp = crate::input::current_time_ms();
+    let target = path.with_file_name(format!("{}.corrupt-{}.json", stem, stamp));
+    match std::fs::rename(path, &target) {
+        Ok(()) => {
+            tracing::warn!("Quarantined {} as {}", path.display(), target.display());
+            Some(target)
+        }
+        Err(err) => {
+            tracing::warn!("Failed to quarantine {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    type TestIndex = HashMap<String, u32>;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "{}_index_file_{}_{}",
+            crate::app_meta::APP_STORAGE_PREFIX,
+            name,
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        dir.join("index.json")
+    }
+
+    fn add_b(value: &mut Value) -> Result<(), String> {
+        value["b"] = Value::from(2);
+        Ok(())
+    }
+
+    #[test]
+    fn roundtrip_and_migrate_unversioned_file() {
+        let path = temp_path("roundtrip");
+        let index: TestIndex = [("a".to_string(), 1)].into_iter().collect();
+        save_index(&path, 1, &index).unwrap();
+        assert!(!sibling_path(&path, ".tmp").exists());
+        match load_index::<TestIndex>(&path, 1, &[add_b]) {
+            IndexLoad::Loaded {
+                index: loaded,
+                migrated_from,
+            } => {
+                assert_eq!(loaded, index);
+                assert_eq!(migrated_from, None);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        std::fs::write(&path, br#"{"a": 1}"#).unwrap();
+        match load_index::<TestIndex>(&path, 1, &[add_b]) {
+            IndexLoad::Loaded {
+                index: loaded,
+                migrated_from,
+            } => {
+                assert_eq!(loaded.get("b"), Some(&2));
+                assert_eq!(migrated_from, Some(0));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn truncated_file_is_quarantined() {
+        let path = temp_path("truncated");
+        let index: TestIndex = (0..50).map(|i| (format!("key-{}", i), i)).collect();
+        save_index(&path, 1, &index).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() / 2]).unwrap();
+
+        match load_index::<TestIndex>(&path, 1, &[add_b]) {
+            IndexLoad::Corrupt { quarantined, .. } => {
+                let quarantined = quarantined.unwrap();
+                assert_eq!(std::fs::read(&quarantined).unwrap().len(), bytes.len() / 2);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(matches!(
+            load_index::<TestIndex>(&path, 1, &[add_b]),
+            IndexLoad::Missing
+        ));
+    }
+
+    #[test]
+    fn future_or_unreadable_file_is_left_in_place() {
+        let path = temp_path("future");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, br#"{"schemaVersion": 9}"#).unwrap();
+        assert!(matches!(
+            load_index::<TestIndex>(&path, 1, &[add_b]),
+            IndexLoad::Unavailable { .. }
+        ));
+        assert_eq!(std::fs::read(&path).unwrap(), br#"{"schemaVersion": 9}"#);
+
+        // A directory in place of the file fails to read rather than to parse
+        let dir_path = temp_path("unreadable");
+        std::fs::create_dir_all(&dir_path).unwrap();
+        assert!(matches!(
+            load_index::<TestIndex>(&dir_path, 1, &[add_b]),
+            IndexLoad::Unavailable { .. }
+        ));
+        assert!(dir_path.is_dir());
+    }
+}
@@ -0,0 +1,721 @@
+//! Integrity checks for the brush and pattern libraries.
+//!
+//! Indexes and cached bitmaps are written separately, so they can drift apart:
+//! a preset may point at a tip or pattern that is gone, a group may list IDs
+//! that no longer exist, and bitmaps may outlive their entries.
+//! [`check_libraries`] reports all of these. In repair mode it also fixes what
+//! it can:
+//!
+//! - texture links are re-linked by pattern UUID, then by name (like the ABR
+//!   import's fallback)
+//! - tips missing from the index are restored from the brush cache
+//! - group lists are rebuilt from each entry's group
+//! - orphan pattern thumbnails are deleted (they are regenerated on demand)
+//!
+//! Entries whose own bitmap is missing cannot be repaired and are only reported.
+//! Orphan tip and pattern bitmaps are only reported too: the caches also hold
+//! brushes and patterns of ABR files opened without importing them.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::cache::{get_brush_cache_dir, load_brush_from_disk_in_dir};
+use super::library::{self as brush_library, BrushLibrary};
+use super::pattern_cache::get_pattern_cache_dir;
+use crate::abr::TextureSettings;
+use crate::pattern::library::{self as pattern_library, PatternLibrary};
+use crate::pattern::types::PatternResource;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LibraryKind {
+    Brushes,
+    Patterns,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LibraryIssueKind {
+    /// Tip entry without a bitmap in the brush cache
+    MissingTipBlob,
+    /// Preset tip or dual brush tip that is not in the library
+    MissingTip,
+    /// Texture pattern that is neither in the pattern library nor the cache
+    MissingPattern,
+    /// Pattern entry without a bitmap in the pattern cache
+    MissingPatternBlob,
+    /// Group listing an ID the library does not have
+    DanglingGroupEntry,
+    /// Entry whose group does not list it
+    UngroupedEntry,
+    /// Cached tip bitmap that no tip or preset uses; may belong to an opened ABR
+    OrphanTipBlob,
+    /// Cached pattern bitmap or thumbnail that no pattern or preset uses; a
+    /// bitmap may belong to an opened ABR
+    OrphanPatternBlob,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LibraryRepair {
+    RelinkedByUuid,
+    RelinkedByName,
+    RestoredFromCache,
+    GroupsRebuilt,
+    Deleted,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryIssue {
+    pub library: LibraryKind,
+    pub kind: LibraryIssueKind,
+    /// Preset, tip, pattern or bitmap ID with the problem
+    pub id: String,
+    /// What it refers to: the missing tip or pattern, the group, or the
+    /// thumbnail folder of an orphan thumbnail
+    pub target: Option<String>,
+    /// How repair mode fixed it; `None` when it was left as is
+    pub repair: Option<LibraryRepair>,
+    /// Pattern a texture was re-linked to
+    pub relinked_to: Option<String>,
+}
+
+impl LibraryIssue {
+    fn new(library: LibraryKind, kind: LibraryIssueKind, id: &str, target: Option<&str>) -> Self {
+        Self {
+            library,
+            kind,
+            id: id.to_string(),
+            target: target.map(ToString::to_string),
+            repair: None,
+            relinked_to: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryIntegrityReport {
+    pub issues: Vec<LibraryIssue>,
+    pub repaired_count: usize,
+    pub unresolved_count: usize,
+}
+
+/// Where the cached bitmaps live
+#[derive(Debug, Clone)]
+pub struct LibraryIntegrityEnv {
+    pub brush_cache_dir: PathBuf,
+    pub pattern_cache_dir: PathBuf,
+}
+
+/// Check the global libraries against the caches, repairing them if asked
+pub fn verify_libraries(repair: bool) -> Result<LibraryIntegrityReport, String> {
+    let env = LibraryIntegrityEnv {
+        brush_cache_dir: get_brush_cache_dir(),
+        pattern_cache_dir: get_pattern_cache_dir(),
+    };
+    brush_library::with_library_write(|brushes| {
+        pattern_library::with_library_write(|patterns| {
+            check_libraries(brushes, patterns, &env, repair)
+        })
+    })
+}
+
+pub fn check_libraries(
+    brushes: &mut BrushLibrary,
+    patterns: &mut PatternLibrary,
+    env: &LibraryIntegrityEnv,
+    repair: bool,
+) -> Result<LibraryIntegrityReport, String> {
+    let snapshot = brushes.snapshot();
+    let mut presets = snapshot.presets;
+    presets.sort_by(|a, b| a.preset.id.cmp(&b.preset.id));
+    let mut pattern_list = patterns.get_all_patterns();
+    pattern_list.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let tip_blobs = blob_ids(&env.brush_cache_dir);
+    let pattern_blobs = blob_ids(&env.pattern_cache_dir);
+    let mut tip_ids: BTreeSet<String> = snapshot.tips.iter().map(|t| t.tip.id.clone()).collect();
+    let pattern_ids: BTreeSet<String> = pattern_list.iter().map(|p| p.id.clone()).collect();
+    let mut issues = Vec::new();
+
+    for tip_id in &tip_ids {
+        if !tip_blobs.contains(tip_id) {
+            issues.push(LibraryIssue::new(
+                LibraryKind::Brushes,
+                LibraryIssueKind::MissingTipBlob,
+                tip_id,
+                None,
+            ));
+        }
+    }
+
+    // Tip references; a tip entry can be restored when its bitmap is still cached
+    let mut referenced_tips: HashSet<String> = HashSet::new();
+    let mut restored: BTreeSet<String> = BTreeSet::new();
+    for entry in &presets {
+        let dual_tip = entry
+            .preset
+            .dual_brush_settings
+            .as_ref()
+            .and_then(|dual| dual.brush_id.as_deref());
+        for tip_id in entry.tip_id.as_deref().into_iter().chain(dual_tip) {
+            referenced_tips.insert(tip_id.to_string());
+            if tip_ids.contains(tip_id) {
+                continue;
+            }
+            let mut issue = LibraryIssue::new(
+                LibraryKind::Brushes,
+                LibraryIssueKind::MissingTip,
+                &entry.preset.id,
+                Some(tip_id),
+            );
+            if repair {
+                let restorable = restored.contains(tip_id)
+                    || load_brush_from_disk_in_dir(&env.brush_cache_dir, tip_id)
+                        .is_some_and(|cached| brushes.restore_tip_from_cache(tip_id, cached));
+                if restorable {
+                    restored.insert(tip_id.to_string());
+                    issue.repair = Some(LibraryRepair::RestoredFromCache);
+                }
+            }
+            issues.push(issue);
+        }
+    }
+    tip_ids.extend(restored);
+
+    // Texture links; brush-attached patterns may live only in the cache
+    let mut referenced_patterns = pattern_ids.clone();
+    for entry in &presets {
+        let Some(texture) = entry.preset.texture_settings.as_ref() else {
+            continue;
+        };
+        let linked = texture.pattern_id.as_deref();
+        if let Some(id) =
+            linked.filter(|id| pattern_ids.contains(*id) || pattern_blobs.contains(*id))
+        {
+            referenced_patterns.insert(id.to_string());
+            continue;
+        }
+        if linked.is_none() && !texture.enabled {
+            continue;
+        }
+        let mut issue = LibraryIssue::new(
+            LibraryKind::Brushes,
+            LibraryIssueKind::MissingPattern,
+            &entry.preset.id,
+            linked.or(texture.pattern_name.as_deref()),
+        );
+        if repair {
+            if let Some((pattern_id, how)) = resolve_pattern(texture, &pattern_list) {
+                brushes.relink_texture_pattern(&entry.preset.id, &pattern_id)?;
+                issue.repair = Some(how);
+                issue.relinked_to = Some(pattern_id);
+            }
+        }
+        issues.push(issue);
+    }
+
+    for pattern in &pattern_list {
+        if !pattern_blobs.contains(&pattern.id) {
+            issues.push(LibraryIssue::new(
+                LibraryKind::Patterns,
+                LibraryIssueKind::MissingPatternBlob,
+                &pattern.id,
+                None,
+            ));
+        }
+    }
+
+    let brush_groups: HashMap<String, Vec<String>> = snapshot
+        .groups
+        .into_iter()
+        .map(|group| (group.name, group.preset_ids))
+        .collect();
+    let mut brush_group_issues = group_issues(
+        LibraryKind::Brushes,
+        &brush_groups,
+        presets
+            .iter()
+            .map(|entry| (entry.preset.id.as_str(), entry.group.as_deref())),
+    );
+    if repair && !brush_group_issues.is_empty() {
+        brushes.rebuild_groups();
+        mark_repaired(&mut brush_group_issues, LibraryRepair::GroupsRebuilt);
+    }
+    issues.extend(brush_group_issues);
+
+    let mut pattern_group_issues = group_issues(
+        LibraryKind::Patterns,
+        patterns.groups(),
+        pattern_list
+            .iter()
+            .map(|pattern| (pattern.id.as_str(), pattern.group.as_deref())),
+    );
+    if repair && !pattern_group_issues.is_empty() {
+        patterns.rebuild_groups();
+        mark_repaired(&mut pattern_group_issues, LibraryRepair::GroupsRebuilt);
+    }
+    issues.extend(pattern_group_issues);
+
+    for tip_id in &tip_blobs {
+        if !tip_ids.contains(tip_id) && !referenced_tips.contains(tip_id) {
+            issues.push(LibraryIssue::new(
+                LibraryKind::Brushes,
+                LibraryIssueKind::OrphanTipBlob,
+                tip_id,
+                None,
+            ));
+        }
+    }
+
+    for pattern_id in pattern_blobs.difference(&referenced_patterns) {
+        issues.push(LibraryIssue::new(
+            LibraryKind::Patterns,
+            LibraryIssueKind::OrphanPatternBlob,
+            pattern_id,
+            None,
+        ));
+    }
+    for (thumb_dir, folder) in thumb_dirs(&env.pattern_cache_dir) {
+        for pattern_id in blob_ids(&thumb_dir).difference(&referenced_patterns) {
+            let path = thumb_dir.join(format!("{}.bin", pattern_id));
+            issues.push(orphan_thumbnail_issue(pattern_id, &folder, &path, repair));
+        }
+    }
+
+    if repair {
+        brushes
+            .save()
+            .map_err(|e| format!("Failed to save brush library: {}", e))?;
+        patterns
+            .save()
+            .map_err(|e| format!("Failed to save pattern library: {}", e))?;
+    }
+
+    let repaired_count = issues.iter().filter(|i| i.repair.is_some()).count();
+    if !issues.is_empty() {
+        tracing::info!(
+            "Library integrity: {} issues, {} repaired",
+            issues.len(),
+            repaired_count
+        );
+    }
+    Ok(LibraryIntegrityReport {
+        unresolved_count: issues.len() - repaired_count,
+        repaired_count,
+        issues,
+    })
+}
+
+/// Drop group entries for IDs not in `members` and append members missing from
+/// their group's list. Empty groups are removed.
+pub(crate) fn repair_group_lists<'a>(
+    groups: &mut HashMap<String, Vec<String>>,
+    members: impl Iterator<Item = (&'a str, Option<&'a str>)>,
+) {
+    let members: HashMap<&str, Option<&str>> = members.collect();
+    for ids in groups.values_mut() {
+        ids.retain(|id| members.contains_key(id.as_str()));
+    }
+    for (id, group) in unlisted_members(groups, &members) {
+        groups
+            .entry(group.to_string())
+            .or_default()
+            .push(id.to_string());
+    }
+    groups.retain(|_, ids| !ids.is_empty());
+}
+
+fn group_issues<'a>(
+    library: LibraryKind,
+    groups: &HashMap<String, Vec<String>>,
+    members: impl Iterator<Item = (&'a str, Option<&'a str>)>,
+) -> Vec<LibraryIssue> {
+    let members: HashMap<&str, Option<&str>> = members.collect();
+    let mut names: Vec<&String> = groups.keys().collect();
+    names.sort();
+
+    let mut issues = Vec::new();
+    for name in names {
+        for id in &groups[name] {
+            if !members.contains_key(id.as_str()) {
+                issues.push(LibraryIssue::new(
+                    library,
+                    LibraryIssueKind::DanglingGroupEntry,
+                    id,
+                    Some(name),
+                ));
+            }
+        }
+    }
+    for (id, group) in unlisted_members(groups, &members) {
+        issues.push(LibraryIssue::new(
+            library,
+            LibraryIssueKind::UngroupedEntry,
+            id,
+            Some(group),
+        ));
+    }
+    issues
+}
+
+/// `(id, group)` of members their group does not list, sorted by ID
+fn unlisted_members<'a>(
+    groups: &HashMap<String, Vec<String>>,
+    members: &HashMap<&'a str, Option<&'a str>>,
+) -> Vec<(&'a str, &'a str)> {
+    let mut unlisted: Vec<(&str, &str)> = members
+        .iter()
+        .filter_map(|(id, group)| {
+            let group = (*group)?;
+            let listed = groups
+                .get(group)
+                .is_some_and(|ids| ids.iter().any(|listed| listed == id));
+            (!listed).then_some((*id, group))
+        })
+        .collect();
+    unlisted.sort();
+    unlisted
+}
+
+fn mark_repaired(issues: &mut [LibraryIssue], repair: LibraryRepair) {
+    for issue in issues {
+        issue.repair = Some(repair);
+    }
+}
+
+/// Pattern a broken texture link most likely meant. Like the ABR import's
+/// fallback: the same UUID first (including the `-N` copies made on ID
+/// clashes), then the same name.
+fn resolve_pattern(
+    texture: &TextureSettings,
+    patterns: &[PatternResource],
+) -> Option<(String, LibraryRepair)> {
+    let uuids: Vec<&str> = [
+        texture.pattern_id.as_deref(),
+        texture.pattern_uuid.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    let by_uuid = patterns.iter().find(|p| {
+        uuids
+            .iter()
+            .any(|uuid| p.id == *uuid || is_suffixed_copy(&p.id, uuid))
+    });
+    if let Some(pattern) = by_uuid {
+        return Some((pattern.id.clone(), LibraryRepair::RelinkedByUuid));
+    }
+
+    let name = texture.pattern_name.as_deref()?;
+    patterns
+        .iter()
+        .find(|p| p.name == name)
+        .map(|p| (p.id.clone(), LibraryRepair::RelinkedByName))
+}
+
+/// `id` is `base` plus a `-N` suffix
+fn is_suffixed_copy(id: &str, base: &str) -> bool {
+    id.strip_prefix(base)
+        .and_then(|rest| rest.strip_prefix('-'))
+        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+fn orphan_thumbnail_issue(id: &str, folder: &str, path: &Path, repair: bool) -> LibraryIssue {
+    let mut issue = LibraryIssue::new(
+        LibraryKind::Patterns,
+        LibraryIssueKind::OrphanPatternBlob,
+        id,
+        Some(folder),
+    );
+    if repair {
+        match std::fs::remove_file(path) {
+            Ok(()) => issue.repair = Some(LibraryRepair::Deleted),
+            Err(e) => tracing::warn!("Failed to delete orphan {:?}: {}", path, e),
+        }
+    }
+    issue
+}
+
+/// IDs of the `.bin` files directly in `dir`
+fn blob_ids(dir: &Path) -> BTreeSet<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return BTreeSet::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            if !path.is_file() || path.extension()? != "bin" {
+                return None;
+            }
+            Some(path.file_stem()?.to_str()?.to_string())
+        })
+        .collect()
+}
+
+/// `thumb_<size>` folders of the pattern cache with their names
+fn thumb_dirs(pattern_cache_dir: &Path) -> Vec<(PathBuf, String)> {
+    let Ok(entries) = std::fs::read_dir(pattern_cache_dir) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<(PathBuf, String)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let name = path.file_name()?.to_str()?.to_string();
+            (path.is_dir() && name.starts_with("thumb_")).then_some((path, name))
+        })
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::abr::BrushPreset;
+    use crate::app_meta::APP_STORAGE_PREFIX;
+    use crate::brush::cache::{save_brush_to_disk_in_dir, CachedBrush};
+    use crate::brush::library::{BrushLibraryPreset, BrushTipResource};
+    use crate::brush::pattern_cache::{save_pattern_to_disk_in_dir, CachedPattern};
+    use crate::pattern::types::PatternMode;
+
+    fn brush_preset(id: &str) -> BrushPreset {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": id,
+            "diameter": 20.0,
+            "spacing": 25.0,
+            "hardness": 100.0,
+            "angle": 0.0,
+            "roundness": 100.0,
+            "hasTexture": true,
+            "isComputed": false,
+            "sizePressure": true,
+            "opacityPressure": false
+        }))
+        .unwrap()
+    }
+
+    fn entry(id: &str, tip_id: &str, pattern_id: &str, pattern_name: &str) -> BrushLibraryPreset {
+        let mut preset = brush_preset(id);
+        preset.texture_settings = Some(TextureSettings {
+            enabled: true,
+            pattern_id: Some(pattern_id.to_string()),
+            pattern_name: Some(pattern_name.to_string()),
+            ..Default::default()
+        });
+        BrushLibraryPreset {
+            preset,
+            tip_id: Some(tip_id.to_string()),
+            group: Some("Inks".to_string()),
+            source: "test".to_string(),
+            content_hash: String::new(),
+            tags: Vec::new(),
+            favorite: false,
+            use_count: 0,
+            last_used_ms: None,
+            modified_ms: None,
+        }
+    }
+
+    fn pattern(id: &str, name: &str) -> PatternResource {
+        PatternResource {
+            id: id.to_string(),
+            name: name.to_string(),
+            content_hash: format!("{}-hash", id),
+            width: 2,
+            height: 2,
+            mode: PatternMode::Grayscale,
+            source: "test".to_string(),
+            group: Some("Papers".to_string()),
+            tags: Vec::new(),
+            last_used_ms: None,
+        }
+    }
+
+    fn save_tip_blob(dir: &Path, id: &str) {
+        save_brush_to_disk_in_dir(
+            dir,
+            id,
+            &CachedBrush {
+                data: lz4_flex::compress_prepend_size(&[200u8; 4 * 4]),
+                width: 4,
+                height: 4,
+                name: id.to_string(),
+            },
+        );
+    }
+
+    fn save_pattern_blob(dir: &Path, id: &str) {
+        save_pattern_to_disk_in_dir(
+            dir,
+            id,
+            &CachedPattern {
+                data: lz4_flex::compress_prepend_size(&[90u8; 2 * 2]),
+                width: 2,
+                height: 2,
+                name: id.to_string(),
+                mode: PatternMode::Grayscale.name().to_string(),
+            },
+        );
+    }
+
+    fn kinds(
+        report: &LibraryIntegrityReport,
+    ) -> Vec<(LibraryIssueKind, &str, Option<LibraryRepair>)> {
+        report
+            .issues
+            .iter()
+            .map(|issue| (issue.kind, issue.id.as_str(), issue.repair))
+            .collect()
+    }
+
+    #[test]
+    fn verify_reports_dangling_links_and_repair_relinks_restores_and_prunes() {
+        let root = std::env::temp_dir().join(format!(
+            "{}_library_integrity_{}",
+            APP_STORAGE_PREFIX,
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        let env = LibraryIntegrityEnv {
+            brush_cache_dir: root.join("brush_cache"),
+            pattern_cache_dir: root.join("pattern_cache"),
+        };
+        let mut brushes = BrushLibrary::new(root.join("brushes"));
+        let mut patterns = PatternLibrary::new(root.join("patterns"));
+
+        // `tip-b` is cached but missing from the index; `tip-gone` is the reverse
+        save_tip_blob(&env.brush_cache_dir, "tip-a");
+        save_tip_blob(&env.brush_cache_dir, "tip-b");
+        save_tip_blob(&env.brush_cache_dir, "stale-tip");
+        save_pattern_blob(&env.pattern_cache_dir, "paper-1");
+        save_pattern_blob(&env.pattern_cache_dir, "canvas");
+        save_pattern_blob(&env.pattern_cache_dir, "stale-pattern");
+        save_pattern_blob(&env.pattern_cache_dir.join("thumb_80"), "stale-pattern");
+        patterns
+            .apply_sync_changes(
+                vec![pattern("paper-1", "Paper"), pattern("canvas", "Canvas")],
+                &[],
+            )
+            .unwrap();
+        let tip = |id: &str| BrushTipResource {
+            tip: brush_preset(id),
+            source: "test".to_string(),
+            content_hash: format!("{}-hash", id),
+        };
+        brushes
+            .apply_sync_changes(
+                vec![tip("tip-a"), tip("tip-gone")],
+                vec![
+                    entry("by-uuid", "tip-a", "paper", "Old Paper"),
+                    entry("by-name", "tip-b", "deleted", "Canvas"),
+                    entry("lost", "tip-a", "nowhere", "Nope"),
+                ],
+                &[],
+            )
+            .unwrap();
+
+        let report = check_libraries(&mut brushes, &mut patterns, &env, false).unwrap();
+        use LibraryIssueKind::*;
+        assert_eq!(
+            kinds(&report),
+            vec![
+                (MissingTipBlob, "tip-gone", None),
+                (MissingTip, "by-name", None),
+                (MissingPattern, "by-name", None),
+                (MissingPattern, "by-uuid", None),
+                (MissingPattern, "lost", None),
+                (OrphanTipBlob, "stale-tip", None),
+                (OrphanPatternBlob, "stale-pattern", None),
+                (OrphanPatternBlob, "stale-pattern", None),
+            ]
+        );
+        assert_eq!(report.repaired_count, 0);
+
+        let report = check_libraries(&mut brushes, &mut patterns, &env, true).unwrap();
+        let relinked: Vec<(&str, Option<&str>)> = report
+            .issues
+            .iter()
+            .filter(|issue| issue.kind == MissingPattern)
+            .map(|issue| (issue.id.as_str(), issue.relinked_to.as_deref()))
+            .collect();
+        assert_eq!(
+            relinked,
+            vec![
+                ("by-name", Some("canvas")),
+                ("by-uuid", Some("paper-1")),
+                ("lost", None)
+            ]
+        );
+        assert_eq!(report.repaired_count, 4);
+        // Orphan bitmaps may belong to an opened ABR; only the thumbnail goes
+        assert!(env.brush_cache_dir.join("stale-tip.bin").exists());
+        assert!(env.pattern_cache_dir.join("stale-pattern.bin").exists());
+        assert!(!env
+            .pattern_cache_dir
+            .join("thumb_80/stale-pattern.bin")
+            .exists());
+
+        // Only what repair cannot fix or leaves alone is left
+        let report = check_libraries(&mut brushes, &mut patterns, &env, false).unwrap();
+        assert_eq!(
+            kinds(&report),
+            vec![
+                (MissingTipBlob, "tip-gone", None),
+                (MissingPattern, "lost", None),
+                (OrphanTipBlob, "stale-tip", None),
+                (OrphanPatternBlob, "stale-pattern", None),
+            ]
+        );
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn group_lists_drop_missing_ids_and_list_ungrouped_entries() {
+        let mut groups: HashMap<String, Vec<String>> = HashMap::from([
+            (
+                "Inks".to_string(),
+                vec!["a".to_string(), "ghost".to_string()],
+            ),
+            ("Gone".to_string(), vec!["ghost-2".to_string()]),
+        ]);
+        let members = [("a", Some("Inks")), ("b", Some("Pencils")), ("c", None)];
+
+        let issues = group_issues(LibraryKind::Patterns, &groups, members.into_iter());
+        let found: Vec<(LibraryIssueKind, &str, Option<&str>)> = issues
+            .iter()
+            .map(|issue| (issue.kind, issue.id.as_str(), issue.target.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    LibraryIssueKind::DanglingGroupEntry,
+                    "ghost-2",
+                    Some("Gone")
+                ),
+                (LibraryIssueKind::DanglingGroupEntry, "ghost", Some("Inks")),
+                (LibraryIssueKind::UngroupedEntry, "b", Some("Pencils")),
+            ]
+        );
+
+        repair_group_lists(&mut groups, members.into_iter());
+        assert_eq!(
+            groups,
+            HashMap::from([
+                ("Inks".to_string(), vec!["a".to_string()]),
+                ("Pencils".to_string(), vec!["b".to_string()]),
+            ])
+        );
+        assert!(group_issues(LibraryKind::Patterns, &groups, members.into_iter()).is_empty());
+    }
+}
@@ -6,9 +6,20 @@ use sha2::{Digest, Sha256};
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
-use crate::abr::BrushPreset;
+use crate::abr::{AbrBrush, BrushPreset, GrayscaleImage};
 use crate::app_meta::APP_CONFIG_DIR_NAME;
+use crate::brush::cache::{
+    get_brush_cache_dir, is_safe_blob_id, read_cached_brushes_in_dir, CachedBrush,
+};
+use crate::brush::history::{
+    self, BrushPresetFieldChange, BrushPresetHistory, BrushPresetHistoryIndex, BrushPresetRevision,
+    BrushPresetRevisionKind, BrushPresetRevisionSummary,
+};
+use crate::brush::index_file::{self, IndexLoad, UsageEntry, UsageIndex};
+use crate::brush::integrity::repair_group_lists;
+use crate::brush::search::{self, BrushPresetSearchFilters};
 use crate::brush::{clone_cached_brush, delete_cached_brush};
+use crate::input::current_time_ms;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -28,6 +39,17 @@ pub struct BrushLibraryPreset {
     pub group: Option<String>,
     pub source: String,
     pub content_hash: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub favorite: bool,
+    #[serde(default)]
+    pub use_count: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used_ms: Option<u64>,
+    /// Last edit to settings, name, group, tags or favorite (drives sync conflicts)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_ms: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +60,15 @@ pub struct BrushLibraryPresetPayload {
     pub group: Option<String>,
 }
 
+/// Incoming preset for `import_resources`: target group, the incoming ID of its
+/// tip when known, and tags to keep
+struct PendingPreset {
+    preset: BrushPreset,
+    group: String,
+    tip_ref: Option<String>,
+    tags: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BrushLibraryGroup {
@@ -71,38 +102,125 @@ struct BrushLibraryIndex {
     groups: HashMap<String, Vec<String>>,
 }
 
+const INDEX_FILE_NAME: &str = "index.json";
+const HISTORY_FILE_NAME: &str = "history.json";
+const INDEX_SCHEMA_VERSION: u32 = 1;
+const HISTORY_SCHEMA_VERSION: u32 = 1;
+
+/// Group and source for entries rebuilt from the cache after index corruption
+const RECOVERED_GROUP: &str = "Recovered";
+const RECOVERED_SOURCE: &str = "recovered";
+
 static LIBRARY: RwLock<Option<BrushLibrary>> = RwLock::new(None);
 
 #[derive(Debug)]
 pub struct BrushLibrary {
     index: BrushLibraryIndex,
+    history: BrushPresetHistoryIndex,
     library_dir: PathBuf,
+    usage: UsageIndex,
     dirty: bool,
+    history_dirty: bool,
+    usage_dirty: bool,
+    /// Why saving is refused: a library file failed to read or is from a newer
+    /// app version, and writing would replace it
+    read_only: Option<String>,
 }
 
 impl BrushLibrary {
     pub fn new(library_dir: PathBuf) -> Self {
         Self {
             index: BrushLibraryIndex::default(),
+            history: BrushPresetHistoryIndex::default(),
+            usage: UsageIndex::default(),
             library_dir,
             dirty: false,
+            history_dirty: false,
+            usage_dirty: false,
+            read_only: None,
         }
     }
 
     pub fn load(library_dir: PathBuf) -> Self {
-        let index_path = library_dir.join("index.json");
-        let index = if index_path.exists() {
-            match std::fs::read_to_string(&index_path) {
-                Ok(json) => serde_json::from_str(&json).unwrap_or_default(),
-                Err(err) => {
-                    tracing::warn!("Failed to load brush library index: {}", err);
-                    BrushLibraryIndex::default()
-                }
+        Self::load_with_cache_dir(library_dir, &get_brush_cache_dir())
+    }
+
+    /// Load the index and history. A corrupt index is quarantined and rebuilt from
+    /// the tips in `cache_dir` and the last recorded state of each preset. An
+    /// unreadable or newer index is rebuilt the same way but left on disk, and
+    /// the library stays read-only.
+    pub fn load_with_cache_dir(library_dir: PathBuf, cache_dir: &Path) -> Self {
+        let mut read_only = None;
+        let history_path = library_dir.join(HISTORY_FILE_NAME);
+        let (history, history_dirty) = match index_file::load_index::<BrushPresetHistoryIndex>(
+            &history_path,
+            HISTORY_SCHEMA_VERSION,
+            &[migrate_unversioned],
+        ) {
+            IndexLoad::Loaded {
+                index,
+                migrated_from,
+            } => (index, migrated_from.is_some()),
+            IndexLoad::Missing => (BrushPresetHistoryIndex::default(), false),
+            IndexLoad::Corrupt { .. } => (BrushPresetHistoryIndex::default(), true),
+            IndexLoad::Unavailable { error } => {
+                read_only = Some(error);
+                (BrushPresetHistoryIndex::default(), false)
+            }
+        };
+
+        let index_path = library_dir.join(INDEX_FILE_NAME);
+        let (mut index, dirty) = match index_file::load_index::<BrushLibraryIndex>(
+            &index_path,
+            INDEX_SCHEMA_VERSION,
+            &[migrate_unversioned],
+        ) {
+            IndexLoad::Loaded {
+                index,
+                migrated_from,
+            } => (index, migrated_from.is_some()),
+            IndexLoad::Missing => (BrushLibraryIndex::default(), false),
+            IndexLoad::Corrupt { error, quarantined } => {
+                let rebuilt = rebuild_index(cache_dir, &history);
+                tracing::warn!(
+                    "Brush library index was corrupt ({}), moved to {:?}; rebuilt {} presets and {} tips",
+                    error,
+                    quarantined,
+                    rebuilt.presets.len(),
+                    rebuilt.tips.len()
+                );
+                (rebuilt, true)
+            }
+            IndexLoad::Unavailable { error } => {
+                let rebuilt = rebuild_index(cache_dir, &history);
+                tracing::warn!(
+                    "Brush library index is unavailable ({}); using {} rebuilt presets read-only",
+                    error,
+                    rebuilt.presets.len()
+                );
+                read_only = Some(error);
+                (rebuilt, false)
             }
-        } else {
-            BrushLibraryIndex::default()
         };
 
+        // Usage stats in the side file are newer than the copies in the index
+        let mut usage = index_file::load_usage(&library_dir).unwrap_or_else(|error| {
+            read_only = Some(error);
+            UsageIndex::default()
+        });
+        let usage_count = usage.entries.len();
+        usage
+            .entries
+            .retain(|id, entry| match index.presets.get_mut(id) {
+                Some(preset) => {
+                    preset.use_count = entry.use_count;
+                    preset.last_used_ms = entry.last_used_ms;
+                    true
+                }
+                None => false,
+            });
+        let usage_dirty = usage.entries.len() != usage_count;
+
         tracing::info!(
             "Loaded brush library: {} presets, {} tips, {} groups",
             index.presets.len(),
@@ -110,26 +228,61 @@ impl BrushLibrary {
             index.groups.len()
         );
 
-        Self {
+        let mut library = Self {
             index,
+            history,
+            usage,
             library_dir,
-            dirty: false,
+            dirty,
+            history_dirty,
+            usage_dirty,
+            read_only,
+        };
+        // Persist migrations and rebuilds right away so a quarantined index is
+        // never followed by an empty one
+        if let Err(err) = library.save() {
+            tracing::warn!("Failed to save brush library after load: {}", err);
         }
+        library
     }
 
     pub fn save(&mut self) -> std::io::Result<()> {
-        if !self.dirty {
-            return Ok(());
+        if let Some(reason) = &self.read_only {
+            if self.dirty || self.history_dirty || self.usage_dirty {
+                return Err(std::io::Error::other(format!(
+                    "Brush library is read-only: {}",
+                    reason
+                )));
+            }
+        }
+        if self.dirty {
+            index_file::save_index(
+                &self.library_dir.join(INDEX_FILE_NAME),
+                INDEX_SCHEMA_VERSION,
+                &self.index,
+            )?;
+            self.dirty = false;
+        }
+        if self.history_dirty {
+            index_file::save_index(
+                &self.library_dir.join(HISTORY_FILE_NAME),
+                HISTORY_SCHEMA_VERSION,
+                &self.history,
+            )?;
+            self.history_dirty = false;
+        }
+        if self.usage_dirty {
+            index_file::save_usage(&self.library_dir, &self.usage)?;
+            self.usage_dirty = false;
         }
-
-        std::fs::create_dir_all(&self.library_dir)?;
-        let index_path = self.library_dir.join("index.json");
-        let json = serde_json::to_string_pretty(&self.index)?;
-        std::fs::write(&index_path, json)?;
-        self.dirty = false;
         Ok(())
     }
 
+    /// Get preset by ID
+    pub fn get_preset(&self, id: &str) -> Option<&BrushLibraryPreset> {
+        self.index.presets.get(id)
+    }
+
     pub fn snapshot(&self) -> BrushLibrarySnapshot {
         let mut presets: Vec<_> = self.index.presets.values().cloned().collect();
         presets.sort_by(|a, b| {
@@ -161,19 +314,129 @@ impl BrushLibrary {
         }
     }
 
+    /// Snapshot restricted to the given presets and groups, with every tip they use
+    pub fn selection_snapshot(
+        &self,
+        preset_ids: &[String],
+        group_names: &[String],
+    ) -> Result<BrushLibrarySnapshot, String> {
+        let mut selected: HashSet<String> = HashSet::new();
+        for id in preset_ids {
+            if !self.index.presets.contains_key(id) {
+                return Err(format!("Preset not found: {}", id));
+            }
+            selected.insert(id.clone());
+        }
+        for name in group_names {
+            let ids = self
+                .index
+                .groups
+                .get(name.trim())
+                .ok_or_else(|| format!("Group not found: {}", name))?;
+            selected.extend(ids.iter().cloned());
+        }
+
+        let mut snapshot = self.snapshot();
+        snapshot
+            .presets
+            .retain(|entry| selected.contains(&entry.preset.id));
+        let used_tip_ids: HashSet<&str> = snapshot
+            .presets
+            .iter()
+            .flat_map(|entry| {
+                let dual = entry
+                    .preset
+                    .dual_brush_settings
+                    .as_ref()
+                    .and_then(|dual| dual.brush_id.as_deref());
+                entry.tip_id.as_deref().into_iter().chain(dual)
+            })
+            .collect();
+        snapshot
+            .tips
+            .retain(|tip| used_tip_ids.contains(tip.tip.id.as_str()));
+        for group in &mut snapshot.groups {
+            group.preset_ids.retain(|id| selected.contains(id));
+        }
+        snapshot.groups.retain(|group| !group.preset_ids.is_empty());
+        Ok(snapshot)
+    }
+
     pub fn import_from_abr(
         &mut self,
         source_path: &str,
         presets: Vec<BrushPreset>,
         tips: Vec<BrushPreset>,
+    ) -> Result<BrushLibraryImportResult, String> {
+        let group_name = source_group_name(source_path);
+        self.import_from_abr_into_group(source_path, &group_name, presets, tips)
+    }
+
+    /// Import ABR presets into an explicit group (bulk imports name groups after
+    /// the file's path inside the pack)
+    pub fn import_from_abr_into_group(
+        &mut self,
+        source_path: &str,
+        group_name: &str,
+        presets: Vec<BrushPreset>,
+        tips: Vec<BrushPreset>,
+    ) -> Result<BrushLibraryImportResult, String> {
+        let presets = presets
+            .into_iter()
+            .map(|preset| PendingPreset {
+                preset,
+                group: group_name.to_string(),
+                tip_ref: None,
+                tags: Vec::new(),
+            })
+            .collect();
+
+        // ABR tips are already cached under their incoming IDs
+        self.import_resources(source_path, presets, tips, |incoming_id, tip_id| {
+            if incoming_id != tip_id {
+                let _ = clone_cached_brush(incoming_id, tip_id);
+            }
+        })
+    }
+
+    /// Import presets and tips from a brush bundle. Presets keep their bundle group
+    /// (falling back to the bundle file name); `store_tip(bundle_id, library_id)`
+    /// is called for each tip that is not already in the library.
+    pub fn import_bundle(
+        &mut self,
+        source_path: &str,
+        presets: Vec<BrushLibraryPreset>,
+        tips: Vec<BrushPreset>,
+        store_tip: impl FnMut(&str, &str),
+    ) -> Result<BrushLibraryImportResult, String> {
+        let fallback_group = source_group_name(source_path);
+        let presets = presets
+            .into_iter()
+            .map(|entry| {
+                let group = sanitize_optional_id(entry.group.as_deref())
+                    .unwrap_or_else(|| fallback_group.clone());
+                PendingPreset {
+                    preset: entry.preset,
+                    group,
+                    tip_ref: entry.tip_id,
+                    tags: normalize_tags(entry.tags),
+                }
+            })
+            .collect();
+
+        self.import_resources(source_path, presets, tips, store_tip)
+    }
+
+    /// Shared import path: dedupes tips and presets by `source_uuid`, then by
+    /// `content_hash`.
+    fn import_resources(
+        &mut self,
+        source_path: &str,
+        presets: Vec<PendingPreset>,
+        tips: Vec<BrushPreset>,
+        mut store_tip: impl FnMut(&str, &str),
     ) -> Result<BrushLibraryImportResult, String> {
         let source = source_path.to_string();
-        let group_name = Path::new(source_path)
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .filter(|s| !s.trim().is_empty())
-            .unwrap_or("Imported")
-            .to_string();
 
         let mut imported_tip_count = 0usize;
         let mut skipped_tip_count = 0usize;
@@ -197,9 +460,7 @@ impl BrushLibrary {
                 existing_id
             } else {
                 let unique_tip_id = self.ensure_unique_tip_id(&incoming_tip_id);
-                if unique_tip_id != incoming_tip_id {
-                    let _ = clone_cached_brush(&incoming_tip_id, &unique_tip_id);
-                }
+                store_tip(&incoming_tip_id, &unique_tip_id);
                 tip.id = unique_tip_id.clone();
 
                 let resource = BrushTipResource {
@@ -219,8 +480,20 @@ impl BrushLibrary {
             }
         }
 
-        for mut preset in presets {
-            let primary_tip_id = self.resolve_primary_tip_id(&preset, &tip_aliases);
+        for pending in presets {
+            let PendingPreset {
+                mut preset,
+                group: group_name,
+                tip_ref,
+                tags,
+            } = pending;
+            let primary_tip_id = match tip_ref {
+                Some(tip_ref) => tip_aliases
+                    .get(&tip_ref)
+                    .cloned()
+                    .or_else(|| self.resolve_tip_candidate(&tip_ref)),
+                None => self.resolve_primary_tip_id(&preset, &tip_aliases),
+            };
             let mapped_dual = preset
                 .dual_brush_settings
                 .as_ref()
@@ -260,8 +533,14 @@ impl BrushLibrary {
                 group: Some(group_name.clone()),
                 source: source.clone(),
                 content_hash,
+                tags,
+                favorite: false,
+                use_count: 0,
+                last_used_ms: None,
+                modified_ms: Some(current_time_ms()),
             };
 
+            self.record_original(&entry);
             self.index.presets.insert(unique_id.clone(), entry);
             self.index
                 .groups
@@ -297,11 +576,20 @@ impl BrushLibrary {
             .get_mut(id)
             .ok_or_else(|| format!("Preset not found: {}", id))?;
         entry.preset.name = name;
+        entry.modified_ms = Some(current_time_ms());
         self.dirty = true;
         self.save().map_err(|e| e.to_string())
     }
 
     pub fn delete_preset(&mut self, id: &str) -> Result<(), String> {
+        self.remove_preset_entry(id)?;
+        self.cleanup_groups();
+        self.dirty = true;
+        self.save().map_err(|e| e.to_string())
+    }
+
+    /// Remove a preset, its history and any tips nothing else references
+    fn remove_preset_entry(&mut self, id: &str) -> Result<(), String> {
         let removed = self
             .index
             .presets
@@ -309,6 +597,11 @@ impl BrushLibrary {
             .ok_or_else(|| format!("Preset not found: {}", id))?;
 
         self.remove_preset_from_groups(id);
+        self.drop_history(id);
+        super::preview::evict_brush_preview(id);
+        if self.usage.entries.remove(id).is_some() {
+            self.usage_dirty = true;
+        }
 
         let mut candidate_tip_ids: HashSet<String> = HashSet::new();
         if let Some(tip_id) = removed.tip_id {
@@ -326,10 +619,7 @@ impl BrushLibrary {
                 delete_cached_brush(&tip_id);
             }
         }
-
-        self.cleanup_groups();
-        self.dirty = true;
-        self.save().map_err(|e| e.to_string())
+        Ok(())
     }
 
     pub fn move_preset_to_group(&mut self, id: &str, group: String) -> Result<(), String> {
@@ -345,6 +635,7 @@ impl BrushLibrary {
             .ok_or_else(|| format!("Preset not found: {}", id))?;
 
         entry.group = Some(group_name.clone());
+        entry.modified_ms = Some(current_time_ms());
 
         self.remove_preset_from_groups(id);
         self.index
@@ -374,6 +665,7 @@ impl BrushLibrary {
         for preset_id in &preset_ids {
             if let Some(preset) = self.index.presets.get_mut(preset_id) {
                 preset.group = Some(normalized_new.to_string());
+                preset.modified_ms = Some(current_time_ms());
             }
         }
 
@@ -402,6 +694,7 @@ impl BrushLibrary {
 
         let mut candidate_tip_ids: HashSet<String> = HashSet::new();
         for preset_id in preset_ids {
+            self.drop_history(&preset_id);
             if let Some(removed) = self.index.presets.remove(&preset_id) {
                 if let Some(tip_id) = removed.tip_id {
                     candidate_tip_ids.insert(tip_id);
@@ -445,13 +738,39 @@ impl BrushLibrary {
         }
 
         let mut preset = payload.preset;
-        preset.id = preset_id.clone();
+        preset.id = preset_id;
         preset.name = name;
 
-        let tip_id = self.resolve_payload_tip_id(payload.tip_id.as_deref());
+        self.update_preset(
+            existing,
+            preset,
+            payload.tip_id.as_deref(),
+            payload.group,
+            BrushPresetRevisionKind::Save,
+            None,
+        )
+    }
+
+    /// Overwrite an existing preset's settings and record the new state as a revision
+    fn update_preset(
+        &mut self,
+        existing: BrushLibraryPreset,
+        mut preset: BrushPreset,
+        payload_tip_id: Option<&str>,
+        group: Option<String>,
+        kind: BrushPresetRevisionKind,
+        reverted_from: Option<u32>,
+    ) -> Result<BrushLibraryPreset, String> {
+        let preset_id = preset.id.clone();
+        let tip_id = self.resolve_payload_tip_id(payload_tip_id);
         self.remap_dual_brush_tip(&mut preset);
 
-        let group = payload.group.or(existing.group.clone());
+        // Presets from before history tracking get their current state as original
+        if !self.history.presets.contains_key(&preset_id) {
+            self.record_original(&existing);
+        }
+
+        let group = group.or(existing.group.clone());
         let content_hash = hash_preset(&preset, tip_id.as_deref());
 
         let updated = BrushLibraryPreset {
@@ -460,8 +779,28 @@ impl BrushLibrary {
             group: group.clone(),
             source: existing.source,
             content_hash,
+            tags: existing.tags,
+            favorite: existing.favorite,
+            use_count: existing.use_count,
+            last_used_ms: existing.last_used_ms,
+            modified_ms: Some(current_time_ms()),
         };
 
+        if let Some(history) = self.history.presets.get_mut(&preset_id) {
+            if history
+                .record(
+                    &updated.preset,
+                    updated.tip_id.clone(),
+                    kind,
+                    reverted_from,
+                    current_time_ms(),
+                )
+                .is_some()
+            {
+                self.history_dirty = true;
+            }
+        }
+
         self.index
             .presets
             .insert(preset_id.clone(), updated.clone());
@@ -516,6 +855,13 @@ impl BrushLibrary {
         });
 
         let content_hash = hash_preset(&preset, tip_id.as_deref());
+        // A copy keeps the original's tags but starts with fresh usage stats
+        let tags = self
+            .index
+            .presets
+            .get(&base_id)
+            .map(|original| original.tags.clone())
+            .unwrap_or_default();
 
         let created = BrushLibraryPreset {
             preset,
@@ -523,8 +869,14 @@ impl BrushLibrary {
             group: group.clone(),
             source: "user-saved".to_string(),
             content_hash,
+            tags,
+            favorite: false,
+            use_count: 0,
+            last_used_ms: None,
+            modified_ms: Some(current_time_ms()),
         };
 
+        self.record_original(&created);
         self.index.presets.insert(new_id.clone(), created.clone());
         if let Some(group_name) = group {
             self.index
@@ -541,6 +893,274 @@ impl BrushLibrary {
         Ok(created)
     }
 
+    /// Revision list for a preset: the original first, then saved revisions
+    pub fn list_preset_revisions(
+        &self,
+        id: &str,
+    ) -> Result<Vec<BrushPresetRevisionSummary>, String> {
+        Ok(self.preset_history(id)?.summaries())
+    }
+
+    pub fn get_preset_revision(
+        &self,
+        id: &str,
+        revision: u32,
+    ) -> Result<BrushPresetRevision, String> {
+        self.preset_history(id)?
+            .get(revision)
+            .cloned()
+            .ok_or_else(|| format!("Revision {} not found for preset {}", revision, id))
+    }
+
+    pub fn diff_preset_revisions(
+        &self,
+        id: &str,
+        from: u32,
+        to: u32,
+    ) -> Result<Vec<BrushPresetFieldChange>, String> {
+        let before = self.get_preset_revision(id, from)?;
+        let after = self.get_preset_revision(id, to)?;
+        Ok(history::diff_revisions(&before, &after))
+    }
+
+    /// Restore a revision's settings (the preset keeps its name and group). The
+    /// result is recorded as a new revision, so reverts can be undone.
+    pub fn revert_preset(&mut self, id: &str, revision: u32) -> Result<BrushLibraryPreset, String> {
+        let target = self.get_preset_revision(id, revision)?;
+        let existing = self
+            .index
+            .presets
+            .get(id)
+            .cloned()
+            .ok_or_else(|| format!("Preset not found: {}", id))?;
+
+        let mut preset = target.preset;
+        preset.id = existing.preset.id.clone();
+        preset.name = existing.preset.name.clone();
+
+        self.update_preset(
+            existing,
+            preset,
+            target.tip_id.as_deref(),
+            None,
+            BrushPresetRevisionKind::Revert,
+            Some(revision),
+        )
+    }
+
+    /// Recorded history, or a one-entry history of the current state for presets
+    /// saved before history tracking
+    fn preset_history(&self, id: &str) -> Result<BrushPresetHistory, String> {
+        if let Some(history) = self.history.presets.get(id) {
+            return Ok(history.clone());
+        }
+        let entry = self
+            .index
+            .presets
+            .get(id)
+            .ok_or_else(|| format!("Preset not found: {}", id))?;
+        Ok(BrushPresetHistory::new(
+            &entry.preset,
+            entry.tip_id.clone(),
+            0,
+        ))
+    }
+
+    fn record_original(&mut self, entry: &BrushLibraryPreset) {
+        let history =
+            BrushPresetHistory::new(&entry.preset, entry.tip_id.clone(), current_time_ms());
+        self.history
+            .presets
+            .insert(entry.preset.id.clone(), history);
+        self.history_dirty = true;
+    }
+
+    fn drop_history(&mut self, id: &str) {
+        if self.history.presets.remove(id).is_some() {
+            self.history_dirty = true;
+        }
+    }
+
+    pub fn library_dir(&self) -> &Path {
+        &self.library_dir
+    }
+
+    /// Apply entries merged from a sync replica: new tips (their bitmaps must
+    /// already be in the brush cache), whole-preset upserts and deletions. Upserts
+    /// keep this machine's usage stats and are recorded in the preset history.
+    pub fn apply_sync_changes(
+        &mut self,
+        tips: Vec<BrushTipResource>,
+        upserts: Vec<BrushLibraryPreset>,
+        deletions: &[String],
+    ) -> Result<(), String> {
+        for tip in tips {
+            self.index.tips.insert(tip.tip.id.clone(), tip);
+        }
+
+        for mut entry in upserts {
+            let preset_id = entry.preset.id.clone();
+            entry.content_hash = hash_preset(&entry.preset, entry.tip_id.as_deref());
+            match self.index.presets.get(&preset_id).cloned() {
+                Some(existing) => {
+                    entry.use_count = existing.use_count;
+                    entry.last_used_ms = existing.last_used_ms;
+                    if !self.history.presets.contains_key(&preset_id) {
+                        self.record_original(&existing);
+                    }
+                    if let Some(history) = self.history.presets.get_mut(&preset_id) {
+                        history.record(
+                            &entry.preset,
+                            entry.tip_id.clone(),
+                            BrushPresetRevisionKind::Save,
+                            None,
+                            current_time_ms(),
+                        );
+                        self.history_dirty = true;
+                    }
+                }
+                None => self.record_original(&entry),
+            }
+
+            self.remove_preset_from_groups(&preset_id);
+            if let Some(group_name) = entry.group.clone() {
+                self.index
+                    .groups
+                    .entry(group_name)
+                    .or_default()
+                    .push(preset_id.clone());
+            }
+            self.index.presets.insert(preset_id, entry);
+        }
+
+        for id in deletions {
+            if self.index.presets.contains_key(id) {
+                self.remove_preset_entry(id)?;
+            }
+        }
+
+        self.cleanup_groups();
+        self.dirty = true;
+        self.save().map_err(|e| e.to_string())
+    }
+
+    /// Replace a preset's tags (trimmed, deduped case-insensitively, sorted)
+    pub fn set_preset_tags(
+        &mut self,
+        id: &str,
+        tags: Vec<String>,
+    ) -> Result<BrushLibraryPreset, String> {
+        let entry = self.preset_mut(id)?;
+        entry.tags = normalize_tags(tags);
+        entry.modified_ms = Some(current_time_ms());
+        let updated = entry.clone();
+        self.dirty = true;
+        self.save().map_err(|e| e.to_string())?;
+        Ok(updated)
+    }
+
+    pub fn set_preset_favorite(
+        &mut self,
+        id: &str,
+        favorite: bool,
+    ) -> Result<BrushLibraryPreset, String> {
+        let entry = self.preset_mut(id)?;
+        entry.favorite = favorite;
+        entry.modified_ms = Some(current_time_ms());
+        let updated = entry.clone();
+        self.dirty = true;
+        self.save().map_err(|e| e.to_string())?;
+        Ok(updated)
+    }
+
+    /// Bump the usage counter and "recently used" timestamp. Only the usage side
+    /// file is written, not the index.
+    pub fn record_preset_use(
+        &mut self,
+        id: &str,
+        now_ms: u64,
+    ) -> Result<BrushLibraryPreset, String> {
+        let entry = self.preset_mut(id)?;
+        entry.use_count = entry.use_count.saturating_add(1);
+        entry.last_used_ms = Some(now_ms);
+        let updated = entry.clone();
+        self.usage.entries.insert(
+            id.to_string(),
+            UsageEntry {
+                use_count: updated.use_count,
+                last_used_ms: updated.last_used_ms,
+            },
+        );
+        self.usage_dirty = true;
+        self.save().map_err(|e| e.to_string())?;
+        Ok(updated)
+    }
+
+    pub fn search_presets(
+        &self,
+        query: &str,
+        filters: &BrushPresetSearchFilters,
+    ) -> Vec<BrushLibraryPreset> {
+        search::search_presets(self.index.presets.values(), query, filters)
+    }
+
+    /// Re-create a missing tip entry from its bitmap in the brush cache. Returns
+    /// false when the bitmap cannot be used as a tip.
+    pub(crate) fn restore_tip_from_cache(&mut self, tip_id: &str, cached: CachedBrush) -> bool {
+        let Some(tip) = recovered_tip(tip_id, cached) else {
+            return false;
+        };
+        let content_hash = hash_tip(&tip);
+        self.index.tips.insert(
+            tip_id.to_string(),
+            BrushTipResource {
+                tip,
+                source: RECOVERED_SOURCE.to_string(),
+                content_hash,
+            },
+        );
+        self.dirty = true;
+        true
+    }
+
+    /// Point a preset's texture at another pattern
+    pub(crate) fn relink_texture_pattern(
+        &mut self,
+        preset_id: &str,
+        pattern_id: &str,
+    ) -> Result<(), String> {
+        let entry = self.preset_mut(preset_id)?;
+        let texture = entry
+            .preset
+            .texture_settings
+            .as_mut()
+            .ok_or_else(|| format!("Preset has no texture: {}", preset_id))?;
+        texture.pattern_id = Some(pattern_id.to_string());
+        entry.content_hash = hash_preset(&entry.preset, entry.tip_id.as_deref());
+        entry.modified_ms = Some(current_time_ms());
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Drop group entries for missing presets and list every preset in its group
+    pub(crate) fn rebuild_groups(&mut self) {
+        repair_group_lists(
+            &mut self.index.groups,
+            self.index
+                .presets
+                .iter()
+                .map(|(id, entry)| (id.as_str(), entry.group.as_deref())),
+        );
+        self.dirty = true;
+    }
+
+    fn preset_mut(&mut self, id: &str) -> Result<&mut BrushLibraryPreset, String> {
+        self.index
+            .presets
+            .get_mut(id)
+            .ok_or_else(|| format!("Preset not found: {}", id))
+    }
+
     fn resolve_primary_tip_id(
         &self,
         preset: &BrushPreset,
@@ -657,8 +1277,9 @@ fn ensure_unique_id<F>(preferred: &str, exists: F) -> String
 where
     F: Fn(&str) -> bool,
 {
+    // Incoming IDs name cache files, so anything but a plain token is replaced
     let trimmed = preferred.trim();
-    let base = if trimmed.is_empty() {
+    let base = if !is_safe_blob_id(trimmed) {
         unique_id_token()
     } else {
         trimmed.to_string()
@@ -678,6 +1299,15 @@ where
     }
 }
 
+fn source_group_name(source_path: &str) -> String {
+    Path::new(source_path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or("Imported")
+        .to_string()
+}
+
 fn sanitize_optional_id(value: Option<&str>) -> Option<String> {
     value
         .map(str::trim)
@@ -685,6 +1315,141 @@ fn sanitize_optional_id(value: Option<&str>) -> Option<String> {
         .map(ToString::to_string)
 }
 
+/// Version 0 files (written before schema versioning) already match version 1
+fn migrate_unversioned(_value: &mut serde_json::Value) -> Result<(), String> {
+    Ok(())
+}
+
+/// Rebuild an index from cached tip bitmaps and the latest recorded revision of
+/// each preset. Tips no recovered preset uses get a plain preset of their own so
+/// they stay reachable. Everything lands in the "Recovered" group.
+fn rebuild_index(cache_dir: &Path, history: &BrushPresetHistoryIndex) -> BrushLibraryIndex {
+    let mut index = BrushLibraryIndex::default();
+    for (tip_id, cached) in read_cached_brushes_in_dir(cache_dir) {
+        if let Some(tip) = recovered_tip(&tip_id, cached) {
+            let content_hash = hash_tip(&tip);
+            index.tips.insert(
+                tip_id,
+                BrushTipResource {
+                    tip,
+                    source: RECOVERED_SOURCE.to_string(),
+                    content_hash,
+                },
+            );
+        }
+    }
+
+    let mut used_tips: HashSet<String> = HashSet::new();
+    for (preset_id, preset_history) in &history.presets {
+        let latest = preset_history.latest();
+        let mut preset = latest.preset.clone();
+        preset.id = preset_id.clone();
+        if let Some(dual) = preset.dual_brush_settings.as_mut() {
+            dual.brush_id = dual
+                .brush_id
+                .take()
+                .filter(|id| index.tips.contains_key(id));
+            used_tips.extend(dual.brush_id.clone());
+        }
+        let tip_id = latest
+            .tip_id
+            .clone()
+            .filter(|id| index.tips.contains_key(id));
+        used_tips.extend(tip_id.clone());
+        insert_recovered_preset(&mut index, preset, tip_id);
+    }
+
+    let orphan_tips: Vec<BrushPreset> = index
+        .tips
+        .values()
+        .filter(|tip| !used_tips.contains(&tip.tip.id) && !index.presets.contains_key(&tip.tip.id))
+        .map(|tip| tip.tip.clone())
+        .collect();
+    for tip in orphan_tips {
+        let tip_id = Some(tip.id.clone());
+        insert_recovered_preset(&mut index, tip, tip_id);
+    }
+
+    if let Some(ids) = index.groups.get_mut(RECOVERED_GROUP) {
+        ids.sort();
+    }
+    index
+}
+
+fn insert_recovered_preset(
+    index: &mut BrushLibraryIndex,
+    preset: BrushPreset,
+    tip_id: Option<String>,
+) {
+    let preset_id = preset.id.clone();
+    let content_hash = hash_preset(&preset, tip_id.as_deref());
+    index.presets.insert(
+        preset_id.clone(),
+        BrushLibraryPreset {
+            preset,
+            tip_id,
+            group: Some(RECOVERED_GROUP.to_string()),
+            source: RECOVERED_SOURCE.to_string(),
+            content_hash,
+            tags: Vec::new(),
+            favorite: false,
+            use_count: 0,
+            last_used_ms: None,
+            modified_ms: None,
+        },
+    );
+    index
+        .groups
+        .entry(RECOVERED_GROUP.to_string())
+        .or_default()
+        .push(preset_id);
+}
+
+/// Tip resource for a cached Gray8 bitmap, with default sampled-brush settings
+fn recovered_tip(tip_id: &str, cached: CachedBrush) -> Option<BrushPreset> {
+    let data = lz4_flex::decompress_size_prepended(&cached.data).ok()?;
+    if data.len() != cached.width as usize * cached.height as usize {
+        return None;
+    }
+    let name = if cached.name.is_empty() {
+        tip_id.to_string()
+    } else {
+        cached.name
+    };
+    let brush = AbrBrush {
+        name,
+        uuid: None,
+        diameter: cached.width.max(cached.height) as f32,
+        tip_image: Some(GrayscaleImage::new(cached.width, cached.height, data)),
+        spacing: 0.25,
+        angle: 0.0,
+        roundness: 1.0,
+        hardness: None,
+        dynamics: None,
+        is_computed: false,
+        is_tip_only: true,
+        texture_settings: None,
+        dual_brush_settings: None,
+        shape_dynamics_enabled: None,
+        shape_dynamics: None,
+        scatter_enabled: None,
+        scatter: None,
+        color_dynamics_enabled: None,
+        color_dynamics: None,
+        transfer_enabled: None,
+        transfer: None,
+        wet_edge_enabled: None,
+        buildup_enabled: None,
+        noise_enabled: None,
+        base_opacity: None,
+        base_flow: None,
+    };
+    let mut tip = BrushPreset::from(brush);
+    tip.id = tip_id.to_string();
+    tip.source_uuid = None;
+    Some(tip)
+}
+
 fn hash_tip(tip: &BrushPreset) -> String {
     let mut normalized = tip.clone();
     normalized.id.clear();
@@ -708,6 +1473,17 @@ fn hash_serde<T: Serialize>(value: &T) -> String {
     hex::encode(hasher.finalize())
 }
 
+pub(crate) fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut normalized: Vec<String> = tags
+        .into_iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty() && seen.insert(tag.to_lowercase()))
+        .collect();
+    normalized.sort_by_key(|tag| tag.to_lowercase());
+    normalized
+}
+
 fn unique_id_token() -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -739,6 +1515,11 @@ pub fn get_library_snapshot() -> BrushLibrarySnapshot {
     })
 }
 
+/// Get a preset's library entry by ID
+pub fn get_preset(id: &str) -> Option<BrushLibraryPreset> {
+    with_library_read(|library| library.get_preset(id).cloned()).flatten()
+}
+
 pub fn import_from_abr(
     source_path: &str,
     presets: Vec<BrushPreset>,
@@ -747,6 +1528,17 @@ pub fn import_from_abr(
     with_library_write(|library| library.import_from_abr(source_path, presets, tips))
 }
 
+pub fn import_from_abr_into_group(
+    source_path: &str,
+    group_name: &str,
+    presets: Vec<BrushPreset>,
+    tips: Vec<BrushPreset>,
+) -> Result<BrushLibraryImportResult, String> {
+    with_library_write(|library| {
+        library.import_from_abr_into_group(source_path, group_name, presets, tips)
+    })
+}
+
 pub fn rename_preset(id: &str, new_name: String) -> Result<(), String> {
     with_library_write(|library| library.rename_preset(id, new_name))
 }
@@ -779,12 +1571,72 @@ pub fn save_preset_as(
     with_library_write(|library| library.save_preset_as(payload, new_name, target_group))
 }
 
-fn with_library_read<T>(f: impl FnOnce(&BrushLibrary) -> T) -> Option<T> {
+pub fn selection_snapshot(
+    preset_ids: &[String],
+    group_names: &[String],
+) -> Result<BrushLibrarySnapshot, String> {
+    with_library_read(|library| library.selection_snapshot(preset_ids, group_names))
+        .unwrap_or_else(|| Err("Brush library not initialized".to_string()))
+}
+
+pub fn import_bundle(
+    source_path: &str,
+    presets: Vec<BrushLibraryPreset>,
+    tips: Vec<BrushPreset>,
+    store_tip: impl FnMut(&str, &str),
+) -> Result<BrushLibraryImportResult, String> {
+    with_library_write(|library| library.import_bundle(source_path, presets, tips, store_tip))
+}
+
+pub fn set_preset_tags(id: &str, tags: Vec<String>) -> Result<BrushLibraryPreset, String> {
+    with_library_write(|library| library.set_preset_tags(id, tags))
+}
+
+pub fn set_preset_favorite(id: &str, favorite: bool) -> Result<BrushLibraryPreset, String> {
+    with_library_write(|library| library.set_preset_favorite(id, favorite))
+}
+
+pub fn record_preset_use(id: &str) -> Result<BrushLibraryPreset, String> {
+    with_library_write(|library| library.record_preset_use(id, current_time_ms()))
+}
+
+pub fn search_presets(
+    query: &str,
+    filters: &BrushPresetSearchFilters,
+) -> Result<Vec<BrushLibraryPreset>, String> {
+    with_library_read(|library| library.search_presets(query, filters))
+        .ok_or_else(|| "Brush library not initialized".to_string())
+}
+
+pub fn list_preset_revisions(id: &str) -> Result<Vec<BrushPresetRevisionSummary>, String> {
+    with_library_read(|library| library.list_preset_revisions(id))
+        .unwrap_or_else(|| Err("Brush library not initialized".to_string()))
+}
+
+pub fn get_preset_revision(id: &str, revision: u32) -> Result<BrushPresetRevision, String> {
+    with_library_read(|library| library.get_preset_revision(id, revision))
+        .unwrap_or_else(|| Err("Brush library not initialized".to_string()))
+}
+
+pub fn diff_preset_revisions(
+    id: &str,
+    from: u32,
+    to: u32,
+) -> Result<Vec<BrushPresetFieldChange>, String> {
+    with_library_read(|library| library.diff_preset_revisions(id, from, to))
+        .unwrap_or_else(|| Err("Brush library not initialized".to_string()))
+}
+
+pub fn revert_preset(id: &str, revision: u32) -> Result<BrushLibraryPreset, String> {
+    with_library_write(|library| library.revert_preset(id, revision))
+}
+
+pub(crate) fn with_library_read<T>(f: impl FnOnce(&BrushLibrary) -> T) -> Option<T> {
     let guard = LIBRARY.read();
     guard.as_ref().map(f)
 }
 
-fn with_library_write<T>(
+pub(crate) fn with_library_write<T>(
     f: impl FnOnce(&mut BrushLibrary) -> Result<T, String>,
 ) -> Result<T, String> {
     let mut guard = LIBRARY.write();
@@ -843,6 +1695,7 @@ mod tests {
             noise_enabled: None,
             base_opacity: None,
             base_flow: None,
+            pressure_curve: None,
         }
     }
 
@@ -967,4 +1820,314 @@ mod tests {
             .iter()
             .any(|preset| preset.group.as_deref() == Some("GroupB")));
     }
+
+    #[test]
+    fn bundle_import_keeps_groups_and_dedupes_like_abr() {
+        let mut library = make_library();
+
+        let tip = make_preset("tip-b", "Tip B", Some("tip-b-src"), true);
+        let mut dual_tip = make_preset("tip-d", "Tip D", Some("tip-d-src"), true);
+        dual_tip.diameter = 40.0;
+        let mut preset = make_preset("preset-b", "Preset B", Some("preset-b-src"), true);
+        preset.dual_brush_settings = Some(crate::abr::DualBrushSettings {
+            brush_id: Some("tip-d".to_string()),
+            ..Default::default()
+        });
+        let entry = BrushLibraryPreset {
+            preset,
+            tip_id: Some("tip-b".to_string()),
+            group: Some("Inks".to_string()),
+            source: "elsewhere".to_string(),
+            content_hash: String::new(),
+            tags: vec!["ink".to_string()],
+            favorite: true,
+            use_count: 3,
+            last_used_ms: Some(1),
+            modified_ms: None,
+        };
+        let mut stored = Vec::new();
+        let first = library
+            .import_bundle(
+                "/tmp/Shared.sutubrush",
+                vec![entry.clone()],
+                vec![tip.clone(), dual_tip.clone()],
+                |bundle_id, tip_id| stored.push((bundle_id.to_string(), tip_id.to_string())),
+            )
+            .unwrap();
+        assert_eq!(first.imported_preset_count, 1);
+        assert_eq!(first.imported_tip_count, 2);
+        assert_eq!(stored.len(), 2);
+
+        let imported = first
+            .snapshot
+            .presets
+            .iter()
+            .find(|p| p.preset.name == "Preset B")
+            .unwrap();
+        assert_eq!(imported.group.as_deref(), Some("Inks"));
+        assert_eq!(imported.tip_id.as_deref(), Some("tip-b"));
+        assert_eq!(imported.tags, vec!["ink".to_string()]);
+        assert!(!imported.favorite);
+        assert_eq!(imported.use_count, 0);
+
+        let selection = library
+            .selection_snapshot(std::slice::from_ref(&imported.preset.id), &[])
+            .unwrap();
+        assert_eq!(selection.presets.len(), 1);
+        assert_eq!(selection.tips.len(), 2);
+        assert_eq!(selection.groups.len(), 1);
+
+        stored.clear();
+        let second = library
+            .import_bundle(
+                "/tmp/Shared.sutubrush",
+                vec![entry],
+                vec![tip, dual_tip],
+                |bundle_id, tip_id| stored.push((bundle_id.to_string(), tip_id.to_string())),
+            )
+            .unwrap();
+        assert_eq!(second.skipped_preset_count, 1);
+        assert_eq!(second.skipped_tip_count, 2);
+        assert!(stored.is_empty());
+    }
+
+    #[test]
+    fn bundle_import_replaces_ids_that_are_not_plain_tokens() {
+        let mut library = make_library();
+
+        let tip = make_preset("../../evil", "Tip", Some("evil-src"), true);
+        let mut preset = make_preset("preset/../x", "Preset", Some("evil-preset"), true);
+        preset.dual_brush_settings = None;
+        let entry = BrushLibraryPreset {
+            preset,
+            tip_id: Some("../../evil".to_string()),
+            group: None,
+            source: String::new(),
+            content_hash: String::new(),
+            tags: Vec::new(),
+            favorite: false,
+            use_count: 0,
+            last_used_ms: None,
+            modified_ms: None,
+        };
+        let mut stored = Vec::new();
+        let result = library
+            .import_bundle(
+                "/tmp/Evil.sutubrush",
+                vec![entry],
+                vec![tip],
+                |_, tip_id| stored.push(tip_id.to_string()),
+            )
+            .unwrap();
+
+        assert_eq!(stored.len(), 1);
+        assert!(is_safe_blob_id(&stored[0]));
+        let imported = &result.snapshot.presets[0];
+        assert!(is_safe_blob_id(&imported.preset.id));
+        assert_eq!(imported.tip_id.as_deref(), Some(stored[0].as_str()));
+        assert!(!is_safe_blob_id("a.b") && !is_safe_blob_id(""));
+    }
+
+    #[test]
+    fn tags_favorites_and_usage_persist_and_drive_search() {
+        let mut library = make_library();
+        let mut big = make_preset("preset-ink", "Dry Ink", Some("ink-src"), false);
+        big.diameter = 120.0;
+        let small = make_preset("preset-pencil", "Pencil", Some("pencil-src"), false);
+        library
+            .import_from_abr("C:/brushes/Sketch.abr", vec![big, small], Vec::new())
+            .unwrap();
+
+        let tagged = library
+            .set_preset_tags(
+                "preset-ink",
+                vec![
+                    " Inking ".to_string(),
+                    "inking".to_string(),
+                    "Comic".to_string(),
+                ],
+            )
+            .unwrap();
+        assert_eq!(tagged.tags, vec!["Comic".to_string(), "Inking".to_string()]);
+        library.set_preset_favorite("preset-pencil", true).unwrap();
+        library.record_preset_use("preset-ink", 10).unwrap();
+        library.record_preset_use("preset-ink", 20).unwrap();
+        let on_disk: BrushLibraryIndex = serde_json::from_slice(
+            &std::fs::read(library.library_dir.join(INDEX_FILE_NAME)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(on_disk.presets["preset-ink"].use_count, 0);
+
+        let reloaded = BrushLibrary::load(library.library_dir.clone());
+        let ink = &reloaded.index.presets["preset-ink"];
+        assert_eq!(ink.use_count, 2);
+        assert_eq!(ink.last_used_ms, Some(20));
+        assert!(reloaded.index.presets["preset-pencil"].favorite);
+
+        let by_tag = reloaded.search_presets("comic", &BrushPresetSearchFilters::default());
+        assert_eq!(by_tag.len(), 1);
+        assert_eq!(by_tag[0].preset.id, "preset-ink");
+
+        let fuzzy = reloaded.search_presets("pncl", &BrushPresetSearchFilters::default());
+        assert_eq!(fuzzy.len(), 1);
+        assert_eq!(fuzzy[0].preset.id, "preset-pencil");
+
+        let large = BrushPresetSearchFilters {
+            min_size: Some(50.0),
+            ..Default::default()
+        };
+        assert_eq!(reloaded.search_presets("", &large).len(), 1);
+
+        let favorites = BrushPresetSearchFilters {
+            favorites_only: true,
+            ..Default::default()
+        };
+        let favorites = reloaded.search_presets("", &favorites);
+        assert_eq!(favorites.len(), 1);
+        assert_eq!(favorites[0].preset.id, "preset-pencil");
+
+        let recent = BrushPresetSearchFilters {
+            sort: search::BrushPresetSort::RecentlyUsed,
+            ..Default::default()
+        };
+        let recent = reloaded.search_presets("", &recent);
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].preset.id, "preset-ink");
+    }
+
+    #[test]
+    fn save_records_revisions_and_revert_restores_original() {
+        let mut library = make_library();
+        let preset = make_preset("preset-h", "Preset H", Some("preset-h-src"), false);
+        library
+            .import_from_abr("C:/brushes/H.abr", vec![preset], Vec::new())
+            .unwrap();
+
+        let mut edited = library.index.presets["preset-h"].clone();
+        for spacing in [40.0, 60.0] {
+            edited.preset.spacing = spacing;
+            library
+                .save_preset(BrushLibraryPresetPayload {
+                    preset: edited.preset.clone(),
+                    tip_id: edited.tip_id.clone(),
+                    group: None,
+                })
+                .unwrap();
+        }
+        library
+            .rename_preset("preset-h", "Renamed".to_string())
+            .unwrap();
+
+        let revisions = library.list_preset_revisions("preset-h").unwrap();
+        assert_eq!(revisions.len(), 3);
+        assert_eq!(revisions[0].kind, BrushPresetRevisionKind::Original);
+        assert_eq!(revisions[2].changed_fields, vec!["spacing".to_string()]);
+
+        let changes = library
+            .diff_preset_revisions(
+                "preset-h",
+                history::ORIGINAL_REVISION,
+                revisions[2].revision,
+            )
+            .unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].before, serde_json::json!(25.0));
+        assert_eq!(changes[0].after, serde_json::json!(60.0));
+
+        let reverted = library
+            .revert_preset("preset-h", history::ORIGINAL_REVISION)
+            .unwrap();
+        assert_eq!(reverted.preset.spacing, 25.0);
+        assert_eq!(reverted.preset.name, "Renamed");
+
+        let reloaded = BrushLibrary::load(library.library_dir.clone());
+        let revisions = reloaded.list_preset_revisions("preset-h").unwrap();
+        assert_eq!(revisions.len(), 4);
+        assert_eq!(revisions[3].kind, BrushPresetRevisionKind::Revert);
+        assert_eq!(revisions[3].reverted_from, Some(history::ORIGINAL_REVISION));
+
+        library.delete_preset("preset-h").unwrap();
+        assert!(library.list_preset_revisions("preset-h").is_err());
+        assert!(!library.history.presets.contains_key("preset-h"));
+    }
+
+    #[test]
+    fn truncated_index_is_quarantined_and_rebuilt_from_cache_and_history() {
+        let mut library = make_library();
+        let tip = make_preset("tip-r", "Tip R", Some("tip-r-src"), true);
+        let mut preset = make_preset("preset-r", "Preset R", Some("preset-r-src"), true);
+        preset.id = "tip-r".to_string();
+        library
+            .import_from_abr("C:/brushes/R.abr", vec![preset], vec![tip])
+            .unwrap();
+        let mut edited = library.index.presets["tip-r"].clone();
+        edited.preset.spacing = 55.0;
+        library
+            .save_preset(BrushLibraryPresetPayload {
+                preset: edited.preset.clone(),
+                tip_id: edited.tip_id.clone(),
+                group: None,
+            })
+            .unwrap();
+
+        let cache_dir = library.library_dir.join("cache");
+        let gray = vec![128u8; 4 * 4];
+        for id in ["tip-r", "orphan"] {
+            crate::brush::cache::save_brush_to_disk_in_dir(
+                &cache_dir,
+                id,
+                &CachedBrush {
+                    data: lz4_flex::compress_prepend_size(&gray),
+                    width: 4,
+                    height: 4,
+                    name: id.to_string(),
+                },
+            );
+        }
+
+        let index_path = library.library_dir.join(INDEX_FILE_NAME);
+        let bytes = std::fs::read(&index_path).unwrap();
+        std::fs::write(&index_path, &bytes[..bytes.len() / 3]).unwrap();
+
+        let recovered = BrushLibrary::load_with_cache_dir(library.library_dir.clone(), &cache_dir);
+        let entry = &recovered.index.presets["tip-r"];
+        assert_eq!(entry.preset.spacing, 55.0);
+        assert_eq!(entry.tip_id.as_deref(), Some("tip-r"));
+        assert_eq!(entry.group.as_deref(), Some(RECOVERED_GROUP));
+        assert_eq!(recovered.index.tips.len(), 2);
+        assert_eq!(
+            recovered.index.presets["orphan"].tip_id.as_deref(),
+            Some("orphan")
+        );
+
+        let quarantined = std::fs::read_dir(&library.library_dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .any(|entry| entry.file_name().to_string_lossy().contains(".corrupt-"));
+        assert!(quarantined);
+
+        let reloaded = BrushLibrary::load_with_cache_dir(library.library_dir.clone(), &cache_dir);
+        assert_eq!(reloaded.index.presets.len(), 2);
+    }
+
+    #[test]
+    fn newer_index_is_left_in_place_and_library_is_read_only() {
+        let mut library = make_library();
+        let preset = make_preset("preset-n", "Preset N", Some("preset-n-src"), false);
+        library
+            .import_from_abr("C:/brushes/N.abr", vec![preset], Vec::new())
+            .unwrap();
+
+        let index_path = library.library_dir.join(INDEX_FILE_NAME);
+        let future = br#"{"schemaVersion": 99, "presets": {}}"#;
+        std::fs::write(&index_path, future).unwrap();
+
+        let cache_dir = library.library_dir.join("cache");
+        let mut reopened =
+            BrushLibrary::load_with_cache_dir(library.library_dir.clone(), &cache_dir);
+        assert!(reopened.read_only.is_some());
+        let err = reopened.set_preset_favorite("preset-n", true).unwrap_err();
+        assert!(err.contains("read-only"), "{}", err);
+        assert_eq!(std::fs::read(&index_path).unwrap(), future);
+    }
 }
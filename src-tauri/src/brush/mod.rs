@@ -7,34 +7,59 @@
 //!
 //! 1. **Dab Level** (stamper.rs): Individual brush stamps with Flow-controlled alpha
 //! 2. **Stroke Buffer** (stroke_buffer.rs): Accumulates dabs within a single stroke
-//! 3. **Layer Level**: Composites stroke with Opacity as ceiling
+//! 3. **Layer Level**: Composites stroke with Opacity as ceiling, using the
+//!    stroke composite mode (Paint, Erase, Behind, optional alpha lock)
 //!
 //! This separation allows Flow to accumulate within a stroke while Opacity
 //! acts as a maximum limit.
+//!
+//! The mixer brush (smudge.rs) uses the same pipeline: it only changes the
+//! color of each dab by sampling the target layer under the stroke buffer.
 
 mod blend;
+pub mod bulk_import;
+pub mod bundle;
 pub mod cache;
+mod effects;
 mod engine;
+pub mod history;
+pub mod index_file;
+pub mod integrity;
 mod interpolation;
 pub mod library;
 pub mod pattern_cache;
+pub mod preview;
+pub mod search;
+mod smudge;
 pub mod soft_dab;
+mod stabilizer;
 mod stamper;
 mod stroke_buffer;
+pub mod sync;
 
-pub use blend::{blend_normal_premul, BlendFunc};
+pub use blend::{
+    blend_destination_out_premul, blend_destination_over_premul, blend_normal_premul,
+    blend_source_atop_premul, BlendFunc,
+};
 pub use cache::{
     cache_brush_gray, cache_brush_gray_ref, clear_brush_cache, clone_cached_brush,
     delete_cached_brush, get_brush_cache_stats, get_cached_brush, init_brush_cache, CachedBrush,
 };
+pub use effects::{TipNoise, WetEdge};
 pub use engine::{BrushEngine, BrushSettings};
 pub use interpolation::{interpolate_catmull_rom, InterpolationMode};
 pub use pattern_cache::{
     cache_pattern_rgba, clear_pattern_cache, delete_cached_pattern, get_cached_pattern,
-    get_cached_pattern_thumb, get_pattern_cache_stats, init_pattern_cache, CachedPattern,
+    get_cached_pattern_thumb, get_pattern_cache_stats, init_pattern_cache,
+    spawn_thumbnail_generation, CachedPattern,
 };
+pub use preview::{evict_brush_preview, get_brush_preview, CachedBrushPreview};
+pub use smudge::{SmudgeBrush, SmudgeSettings};
+pub use stabilizer::{Stabilizer, StabilizerMode};
 pub use stamper::{BrushStamper, Dab, StamperConfig};
-pub use stroke_buffer::{Pixel, Rect, StrokeBuffer};
+pub use stroke_buffer::{Pixel, Rect, StrokeBuffer, StrokeCompositeMode};
+
+pub use crate::core::pressure_curve::{CustomPressureCurve, PressureCurve, PressureCurvePoint};
 
 use serde::{Deserialize, Serialize};
 
@@ -85,43 +110,6 @@ pub enum BlendMode {
     Exclusion,
 }
 
-/// Pressure curve types
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
-pub enum PressureCurve {
-    /// Linear mapping (1:1)
-    #[default]
-    Linear,
-    /// Soft curve (more sensitive at low pressure)
-    Soft,
-    /// Hard curve (less sensitive at low pressure)
-    Hard,
-    /// Custom curve (uses control points)
-    Custom,
-}
-
-impl PressureCurve {
-    /// Apply the pressure curve to a raw pressure value
-    pub fn apply(&self, pressure: f32) -> f32 {
-        let p = pressure.clamp(0.0, 1.0);
-
-        match self {
-            PressureCurve::Linear => p,
-            PressureCurve::Soft => {
-                // Ease-out: more sensitive at low pressure
-                1.0 - (1.0 - p).powi(2)
-            }
-            PressureCurve::Hard => {
-                // Ease-in: less sensitive at low pressure
-                p.powi(2)
-            }
-            PressureCurve::Custom => {
-                // TODO: Implement custom curve with control points
-                p
-            }
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -7,10 +7,16 @@
 //! ## Two-level caching strategy
 //! 1. **Memory cache**: Fast access for current session
 //! 2. **Disk cache**: Persistent storage across sessions
+//!
+//! Thumbnails at the standard sizes are rendered on the rayon pool after import
+//! (see [`spawn_thumbnail_generation`]); a thumbnail requested before that
+//! finishes is rendered on demand.
 
+use super::cache::blob_path;
 use crate::app_meta::APP_CONFIG_DIR_NAME;
 use lz4_flex::{compress_prepend_size, decompress_size_prepended};
 use parking_lot::RwLock;
+use rayon::prelude::*;
 use std::collections::HashMap;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
@@ -203,8 +209,19 @@ fn render_square_thumbnail_rgba(src_rgba: &[u8], src_w: u32, src_h: u32, size: u
     out
 }
 
+fn render_thumb(rgba: &[u8], width: u32, height: u32, size: u32) -> CachedPattern {
+    let thumb_rgba = render_square_thumbnail_rgba(rgba, width, height, size);
+    CachedPattern {
+        data: compress_prepend_size(&thumb_rgba),
+        width: size,
+        height: size,
+        name: String::new(),
+        mode: String::new(),
+    }
+}
+
 /// Get the pattern cache directory path
-fn get_pattern_cache_dir() -> PathBuf {
+pub fn get_pattern_cache_dir() -> PathBuf {
     std::env::var("SUTU_TEST_DATA_DIR")
         .ok()
         .or_else(|| std::env::var("PAINTBOARD_TEST_DATA_DIR").ok())
@@ -238,8 +255,10 @@ pub fn ensure_cache_dir() {
 /// - mode_len (4 bytes)
 /// - mode_bytes (variable)
 /// - compressed_data (rest)
-fn save_pattern_to_disk_in_dir(dir: &Path, pattern_id: &str, pattern: &CachedPattern) {
-    let file_path = dir.join(format!("{}.bin", pattern_id));
+pub(crate) fn save_pattern_to_disk_in_dir(dir: &Path, pattern_id: &str, pattern: &CachedPattern) {
+    let Some(file_path) = blob_path(dir, pattern_id) else {
+        return;
+    };
 
     let result = (|| -> std::io::Result<()> {
         std::fs::create_dir_all(dir)?;
@@ -272,7 +291,7 @@ fn save_pattern_to_disk_in_dir(dir: &Path, pattern_id: &str, pattern: &CachedPat
 
 /// Load pattern from disk
 fn load_pattern_from_disk_in_dir(dir: &Path, pattern_id: &str) -> Option<CachedPattern> {
-    let file_path = dir.join(format!("{}.bin", pattern_id));
+    let file_path = blob_path(dir, pattern_id)?;
 
     if !file_path.exists() {
         return None;
@@ -331,6 +350,28 @@ fn load_pattern_from_disk_in_dir(dir: &Path, pattern_id: &str) -> Option<CachedP
     }
 }
 
+/// Every readable full-size pattern persisted in a cache directory, keyed by
+/// pattern ID (thumbnails are skipped). Used to rebuild the pattern index.
+pub fn read_cached_patterns_in_dir(dir: &Path) -> Vec<(String, CachedPattern)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut patterns: Vec<(String, CachedPattern)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            if !path.is_file() || path.extension()? != "bin" {
+                return None;
+            }
+            let pattern_id = path.file_stem()?.to_str()?.to_string();
+            let pattern = load_pattern_from_disk_in_dir(dir, &pattern_id)?;
+            Some((pattern_id, pattern))
+        })
+        .collect();
+    patterns.sort_by(|a, b| a.0.cmp(&b.0));
+    patterns
+}
+
 fn save_pattern_to_disk(pattern_id: &str, pattern: &CachedPattern) {
     let dir = get_pattern_cache_dir();
     save_pattern_to_disk_in_dir(&dir, pattern_id, pattern);
@@ -407,28 +448,93 @@ pub fn cache_pattern_rgba(
     // Save to disk first (persistent)
     save_pattern_to_disk(&pattern_id, &pattern);
 
-    // Generate and save thumbnails (persistent)
-    let mut thumbs = Vec::new();
-    for &thumb_size in &THUMB_SIZES {
-        let thumb_rgba = render_square_thumbnail_rgba(&data, width, height, thumb_size);
-        let thumb = CachedPattern {
-            data: compress_prepend_size(&thumb_rgba),
-            width: thumb_size,
-            height: thumb_size,
-            name: String::new(),
-            mode: String::new(),
-        };
-        save_thumb_to_disk(&pattern_id, thumb_size, &thumb);
-        thumbs.push((thumb_size, thumb));
+    // Store in memory cache; stale thumbnails of a replaced pattern are dropped
+    // and regenerated by the next thumbnail pass
+    let mut guard = PATTERN_CACHE.write();
+    let cache = guard.get_or_insert_with(PatternCache::new);
+    cache.remove_pattern_and_thumbs(&pattern_id);
+    cache.insert_compressed(pattern_id.clone(), pattern);
+    drop(guard);
+    for &size in &THUMB_SIZES {
+        if let Some(path) = blob_path(&get_pattern_cache_thumb_dir(size), &pattern_id) {
+            let _ = std::fs::remove_file(path);
+        }
     }
+}
+
+/// Render missing standard-size thumbnails for `pattern_ids` on the rayon pool
+/// without blocking the caller
+pub fn spawn_thumbnail_generation(pattern_ids: Vec<String>) {
+    if pattern_ids.is_empty() {
+        return;
+    }
+    rayon::spawn(move || {
+        let generated = generate_missing_thumbnails(&pattern_ids);
+        tracing::debug!(
+            "Generated {} pattern thumbnails for {} patterns",
+            generated,
+            pattern_ids.len()
+        );
+    });
+}
+
+/// Render and persist every standard-size thumbnail missing on disk, in
+/// parallel. Returns how many thumbnails were written.
+pub fn generate_missing_thumbnails(pattern_ids: &[String]) -> usize {
+    pattern_ids
+        .par_iter()
+        .map(|pattern_id| generate_pattern_thumbnails(pattern_id))
+        .sum()
+}
+
+fn generate_pattern_thumbnails(pattern_id: &str) -> usize {
+    let missing: Vec<u32> = THUMB_SIZES
+        .iter()
+        .copied()
+        .filter(|&size| {
+            blob_path(&get_pattern_cache_thumb_dir(size), pattern_id)
+                .is_some_and(|path| !path.exists())
+        })
+        .collect();
+    if missing.is_empty() {
+        return 0;
+    }
+
+    // Read the full pattern without keeping it in memory; a large library would
+    // otherwise end up fully resident
+    let memory_hit = PATTERN_CACHE
+        .read()
+        .as_ref()
+        .and_then(|cache| cache.get_pattern(pattern_id).cloned());
+    let Some(full) = memory_hit.or_else(|| load_pattern_from_disk(pattern_id)) else {
+        return 0;
+    };
+    let rgba = match decompress_size_prepended(&full.data) {
+        Ok(rgba) => rgba,
+        Err(e) => {
+            tracing::warn!(
+                "Failed to decompress pattern {} for thumbs: {}",
+                pattern_id,
+                e
+            );
+            return 0;
+        }
+    };
 
-    // Store in memory cache
+    let thumbs: Vec<(u32, CachedPattern)> = missing
+        .into_iter()
+        .map(|size| (size, render_thumb(&rgba, full.width, full.height, size)))
+        .collect();
+    for (size, thumb) in &thumbs {
+        save_thumb_to_disk(pattern_id, *size, thumb);
+    }
+    let count = thumbs.len();
     let mut guard = PATTERN_CACHE.write();
     let cache = guard.get_or_insert_with(PatternCache::new);
-    for (thumb_size, thumb) in thumbs {
-        cache.insert_thumb_compressed(pattern_id.clone(), thumb_size, thumb);
+    for (size, thumb) in thumbs {
+        cache.insert_thumb_compressed(pattern_id.to_string(), size, thumb);
     }
-    cache.insert_compressed(pattern_id, pattern);
+    count
 }
 
 /// Get pattern data from global cache (with disk fallback)
@@ -492,15 +598,7 @@ pub fn get_cached_pattern_thumb(pattern_id: &str, requested_size: u32) -> Option
         }
     };
 
-    let thumb_rgba = render_square_thumbnail_rgba(&rgba, full.width, full.height, size);
-    let thumb = CachedPattern {
-        data: compress_prepend_size(&thumb_rgba),
-        width: size,
-        height: size,
-        name: String::new(),
-        mode: String::new(),
-    };
-
+    let thumb = render_thumb(&rgba, full.width, full.height, size);
     save_thumb_to_disk(pattern_id, size, &thumb);
 
     let mut guard = PATTERN_CACHE.write();
@@ -521,11 +619,14 @@ pub fn delete_cached_pattern(pattern_id: &str) {
     }
 
     // Remove disk files (best-effort)
-    let full_path = get_pattern_cache_dir().join(format!("{}.bin", pattern_id));
+    let Some(full_path) = blob_path(&get_pattern_cache_dir(), pattern_id) else {
+        return;
+    };
     let _ = std::fs::remove_file(&full_path);
     for &size in &THUMB_SIZES {
-        let p = get_pattern_cache_thumb_dir(size).join(format!("{}.bin", pattern_id));
-        let _ = std::fs::remove_file(&p);
+        if let Some(p) = blob_path(&get_pattern_cache_thumb_dir(size), pattern_id) {
+            let _ = std::fs::remove_file(&p);
+        }
     }
 }
 
@@ -624,4 +725,53 @@ mod tests {
         std::env::remove_var("SUTU_TEST_DATA_DIR");
         let _ = std::fs::remove_dir_all(&base);
     }
+
+    #[test]
+    fn missing_thumbnails_are_generated_once() {
+        let _guard = TEST_LOCK.lock().expect("failed to lock test mutex");
+
+        let uniq = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time before UNIX_EPOCH")
+            .as_nanos();
+        let base = std::env::temp_dir().join(format!(
+            "{}_pattern_thumbs_test_{}",
+            crate::app_meta::APP_STORAGE_PREFIX,
+            uniq
+        ));
+        std::fs::create_dir_all(&base).expect("failed to create test dir");
+        std::env::set_var("SUTU_TEST_DATA_DIR", &base);
+
+        let ids = vec!["t1".to_string(), "t2".to_string()];
+        for id in &ids {
+            cache_pattern_rgba(
+                id.clone(),
+                vec![90; 3 * 2 * 4],
+                3,
+                2,
+                "n".into(),
+                "RGB".into(),
+            );
+        }
+        let thumb_path =
+            |id: &str, size: u32| get_pattern_cache_thumb_dir(size).join(format!("{}.bin", id));
+        assert!(
+            !thumb_path("t1", 32).exists(),
+            "import should not render thumbs inline"
+        );
+
+        assert_eq!(
+            generate_missing_thumbnails(&ids),
+            ids.len() * THUMB_SIZES.len()
+        );
+        for id in &ids {
+            for &size in &THUMB_SIZES {
+                assert!(thumb_path(id, size).exists());
+            }
+        }
+        assert_eq!(generate_missing_thumbnails(&ids), 0);
+
+        std::env::remove_var("SUTU_TEST_DATA_DIR");
+        let _ = std::fs::remove_dir_all(&base);
+    }
 }
@@ -0,0 +1,943 @@
+//! Brush preview strokes - renders a standard S-curve for a library preset
+//!
+//! Tip thumbnails (`project://brush/{id}`) only show a single dab. The preview
+//! paints a whole stroke with a pressure ramp (light -> full -> light) so size
+//! and flow dynamics, scattering, Texture and Dual Brush are visible.
+//!
+//! Dynamics are approximated on the CPU with a fixed random seed, so a preset
+//! always renders the same preview; the canvas renderer stays the reference.
+//! Previews are kept in memory keyed by preset ID, re-rendered when the
+//! preset's `content_hash` changes, and served as LZ4 RGBA via
+//! `project://brush-preview/{id}` off the protocol thread. Deleting a preset
+//! evicts its preview.
+
+use super::effects::{TipNoise, WetEdge};
+use super::library::BrushLibraryPreset;
+use super::{get_cached_brush, get_cached_pattern};
+use crate::abr::{
+    BrushPreset, ControlSource, DualBlendMode, DualBrushSettings, TextureBlendMode, TextureSettings,
+};
+use lz4_flex::{compress_prepend_size, decompress_size_prepended};
+use parking_lot::RwLock;
+use std::collections::HashMap;
+
+/// Preview width in pixels
+pub const PREVIEW_WIDTH: u32 = 256;
+/// Preview height in pixels
+pub const PREVIEW_HEIGHT: u32 = 64;
+
+/// Stroke color (straight RGB); coverage goes to alpha so the UI can tint it
+const PREVIEW_INK: [u8; 3] = [255, 255, 255];
+/// Gap kept between the stroke and the left/right edges
+const PREVIEW_MARGIN: f32 = 8.0;
+/// Largest dab diameter as a fraction of the preview height
+const MAX_DIAMETER_RATIO: f32 = 0.4;
+/// Pressure at both ends of the ramp (0 would emit invisible dabs)
+const MIN_PRESSURE: f32 = 0.05;
+/// Polyline segments used to approximate the S-curve
+const PATH_SEGMENTS: usize = 256;
+/// Smallest distance between dabs, in pixels
+const MIN_SPACING_PX: f32 = 0.5;
+/// Upper bound on dab positions per stroke
+const MAX_PREVIEW_DABS: usize = 4096;
+/// Seed for jitter, scatter and noise
+const PREVIEW_SEED: u64 = 0x5eed_b7a5;
+
+/// Global preview cache: preset ID -> rendered preview
+static PREVIEW_CACHE: RwLock<Option<HashMap<String, CachedBrushPreview>>> = RwLock::new(None);
+
+/// Rendered preview of a preset
+#[derive(Debug, Clone)]
+pub struct CachedBrushPreview {
+    /// `content_hash` of the preset the preview was rendered from
+    pub content_hash: String,
+    /// LZ4 compressed RGBA data (with prepended size)
+    pub data: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Coverage bitmap (0 = empty, 255 = full) sampled by the renderer
+#[derive(Debug, Clone)]
+pub struct PreviewMask {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl PreviewMask {
+    /// Bilinear sample in pixel coordinates; outside the bitmap is empty
+    fn sample(&self, x: f32, y: f32) -> f32 {
+        let x = x - 0.5;
+        let y = y - 0.5;
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let fx = x - x0;
+        let fy = y - y0;
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let top = lerp(self.texel(x0, y0), self.texel(x0 + 1, y0), fx);
+        let bottom = lerp(self.texel(x0, y0 + 1), self.texel(x0 + 1, y0 + 1), fx);
+        lerp(top, bottom, fy)
+    }
+
+    /// Nearest sample with the bitmap tiled in both directions
+    fn sample_tiled(&self, x: f32, y: f32) -> f32 {
+        let x = (x.floor() as i64).rem_euclid(i64::from(self.width.max(1)));
+        let y = (y.floor() as i64).rem_euclid(i64::from(self.height.max(1)));
+        self.texel(x, y)
+    }
+
+    fn texel(&self, x: i64, y: i64) -> f32 {
+        if x < 0 || y < 0 || x >= i64::from(self.width) || y >= i64::from(self.height) {
+            return 0.0;
+        }
+        let idx = (y * i64::from(self.width) + x) as usize;
+        self.data.get(idx).map_or(0.0, |&v| f32::from(v) / 255.0)
+    }
+
+    /// Luminance of straight RGBA pixels, with transparency counting as black
+    fn from_rgba(rgba: &[u8], width: u32, height: u32) -> Self {
+        let data = rgba
+            .chunks_exact(4)
+            .map(|px| {
+                let luma =
+                    (u32::from(px[0]) * 77 + u32::from(px[1]) * 150 + u32::from(px[2]) * 29) >> 8;
+                (luma * u32::from(px[3]) / 255) as u8
+            })
+            .collect();
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+}
+
+/// Bitmaps a preset refers to; any of them may be missing
+#[derive(Debug, Clone, Default)]
+pub struct PreviewResources {
+    /// Sampled tip (None = computed round tip)
+    pub tip: Option<PreviewMask>,
+    /// Dual Brush secondary tip
+    pub dual_tip: Option<PreviewMask>,
+    /// Texture pattern as luminance
+    pub pattern: Option<PreviewMask>,
+}
+
+impl PreviewResources {
+    /// Load the tip, dual tip and texture pattern from the brush and pattern caches
+    pub fn resolve(entry: &BrushLibraryPreset) -> Self {
+        let tip = entry
+            .tip_id
+            .as_deref()
+            .filter(|_| entry.preset.has_texture && !entry.preset.is_computed)
+            .and_then(load_tip);
+        let dual_tip = entry
+            .preset
+            .dual_brush_settings
+            .as_ref()
+            .filter(|dual| dual.enabled)
+            .and_then(|dual| dual.brush_id.as_deref())
+            .and_then(load_tip);
+        let pattern = entry
+            .preset
+            .texture_settings
+            .as_ref()
+            .filter(|texture| texture.enabled)
+            .and_then(|texture| texture.pattern_id.as_deref())
+            .and_then(load_pattern);
+        Self {
+            tip,
+            dual_tip,
+            pattern,
+        }
+    }
+}
+
+fn load_tip(tip_id: &str) -> Option<PreviewMask> {
+    let cached = get_cached_brush(tip_id)?;
+    let data = decompress_size_prepended(&cached.data).ok()?;
+    Some(PreviewMask {
+        width: cached.width,
+        height: cached.height,
+        data,
+    })
+}
+
+fn load_pattern(pattern_id: &str) -> Option<PreviewMask> {
+    let cached = get_cached_pattern(pattern_id)?;
+    let rgba = decompress_size_prepended(&cached.data).ok()?;
+    Some(PreviewMask::from_rgba(&rgba, cached.width, cached.height))
+}
+
+/// Get the preview of a library preset, rendering it on a miss or when the
+/// preset changed since the cached render
+pub fn get_brush_preview(preset_id: &str) -> Option<CachedBrushPreview> {
+    let entry = super::library::get_preset(preset_id)?;
+    {
+        let guard = PREVIEW_CACHE.read();
+        if let Some(cached) = guard.as_ref().and_then(|cache| cache.get(preset_id)) {
+            if cached.content_hash == entry.content_hash {
+                return Some(cached.clone());
+            }
+        }
+    }
+
+    let resources = PreviewResources::resolve(&entry);
+    let rgba = render_preset_preview(&entry.preset, &resources, PREVIEW_WIDTH, PREVIEW_HEIGHT);
+    let preview = CachedBrushPreview {
+        content_hash: entry.content_hash,
+        data: compress_prepend_size(&rgba),
+        width: PREVIEW_WIDTH,
+        height: PREVIEW_HEIGHT,
+    };
+    PREVIEW_CACHE
+        .write()
+        .get_or_insert_with(HashMap::new)
+        .insert(preset_id.to_string(), preview.clone());
+    Some(preview)
+}
+
+/// Drop the cached preview of a preset that left the library
+pub fn evict_brush_preview(preset_id: &str) {
+    if let Some(cache) = PREVIEW_CACHE.write().as_mut() {
+        cache.remove(preset_id);
+    }
+}
+
+/// Render the preview stroke of a preset as straight RGBA
+pub fn render_preset_preview(
+    preset: &BrushPreset,
+    resources: &PreviewResources,
+    width: u32,
+    height: u32,
+) -> Vec<u8> {
+    let width = width.max(1);
+    let height = height.max(1);
+    let diameter = preset
+        .diameter
+        .clamp(1.0, (height as f32 * MAX_DIAMETER_RATIO).max(1.0));
+    let path = PreviewPath::s_curve(width as f32, height as f32, diameter);
+    let mut canvas = Canvas::new(width, height);
+    let mut rng = PreviewRng::new(PREVIEW_SEED);
+
+    let hardness = (preset.hardness / 100.0).clamp(0.0, 1.0);
+    let noise = preset
+        .noise_enabled
+        .unwrap_or(false)
+        .then(|| TipNoise::new(PREVIEW_SEED as u32, 1.0));
+    let texture = preset
+        .texture_settings
+        .as_ref()
+        .filter(|settings| settings.enabled)
+        .zip(resources.pattern.as_ref());
+
+    let spacing = (preset.spacing / 100.0).max(0.01);
+    let total = path.length();
+    let mut distance = 0.0;
+    let mut emitted = 0;
+    while distance <= total && emitted < MAX_PREVIEW_DABS {
+        let t = if total > 0.0 { distance / total } else { 0.0 };
+        let pressure = pressure_ramp(t);
+        let (x, y, direction) = path.sample(distance);
+        let dabs = primary_dabs(
+            preset,
+            diameter,
+            x,
+            y,
+            direction,
+            path.start_direction(),
+            t,
+            pressure,
+            &mut rng,
+        );
+        let step_size = dabs.first().map_or(diameter, |dab| dab.size);
+        for dab in dabs {
+            let depth = texture
+                .filter(|(settings, _)| settings.texture_each_tip)
+                .map(|(settings, _)| texture_depth(settings, pressure, &mut rng));
+            canvas.stamp(
+                &dab,
+                resources.tip.as_ref(),
+                hardness,
+                noise.as_ref(),
+                |px, py, mask| match (texture, depth) {
+                    (Some((settings, pattern)), Some(depth)) => apply_texture(
+                        mask,
+                        pattern_value(settings, pattern, px, py),
+                        depth,
+                        settings.mode,
+                    ),
+                    _ => mask,
+                },
+            );
+        }
+        emitted += 1;
+        distance += (step_size * spacing).max(MIN_SPACING_PX);
+    }
+
+    if let Some((settings, pattern)) = texture.filter(|(settings, _)| !settings.texture_each_tip) {
+        let depth = (settings.depth / 100.0).clamp(0.0, 1.0);
+        canvas.map(|px, py, alpha| {
+            apply_texture(
+                alpha,
+                pattern_value(settings, pattern, px, py),
+                depth,
+                settings.mode,
+            )
+        });
+    }
+
+    let dual = preset
+        .dual_brush_settings
+        .as_ref()
+        .filter(|dual| dual.enabled)
+        .zip(resources.dual_tip.as_ref());
+    if let Some((dual, dual_tip)) = dual {
+        let secondary = paint_dual(
+            dual,
+            dual_tip,
+            preset.diameter,
+            diameter,
+            &path,
+            width,
+            height,
+            &mut rng,
+        );
+        canvas.map(|px, py, alpha| blend_dual(alpha, secondary.alpha(px, py), dual.mode));
+    }
+
+    let opacity = preset.base_opacity.unwrap_or(1.0).clamp(0.0, 1.0);
+    let wet_edge = preset
+        .wet_edge_enabled
+        .unwrap_or(false)
+        .then(|| WetEdge::new(1.0, hardness));
+    canvas.map(|_, _, alpha| {
+        let alpha = wet_edge.as_ref().map_or(alpha, |wet| wet.apply(alpha));
+        alpha.min(opacity)
+    });
+
+    canvas.to_rgba(PREVIEW_INK)
+}
+
+/// Rise-and-fall pressure along the stroke (t = 0..1)
+fn pressure_ramp(t: f32) -> f32 {
+    (std::f32::consts::PI * t.clamp(0.0, 1.0))
+        .sin()
+        .max(MIN_PRESSURE)
+}
+
+/// Value (0-1) of a control source at a point of the stroke
+fn control_value(control: ControlSource, pressure: f32, t: f32) -> f32 {
+    match control {
+        ControlSource::PenPressure => pressure,
+        ControlSource::Fade => 1.0 - t,
+        _ => 1.0,
+    }
+}
+
+/// A dab of the preview stroke
+#[derive(Debug, Clone, Copy)]
+struct PreviewDab {
+    x: f32,
+    y: f32,
+    /// Diameter in pixels
+    size: f32,
+    /// Rotation in radians
+    angle: f32,
+    /// Minor/major axis ratio (0-1)
+    roundness: f32,
+    flip_x: bool,
+    flip_y: bool,
+    /// Dab alpha (0-1)
+    flow: f32,
+}
+
+/// Dabs for one position of the primary tip (several when scattering)
+#[allow(clippy::too_many_arguments)]
+fn primary_dabs(
+    preset: &BrushPreset,
+    diameter: f32,
+    x: f32,
+    y: f32,
+    direction: f32,
+    initial_direction: f32,
+    t: f32,
+    pressure: f32,
+    rng: &mut PreviewRng,
+) -> Vec<PreviewDab> {
+    let shape = preset
+        .shape_dynamics
+        .as_ref()
+        .filter(|_| preset.shape_dynamics_enabled.unwrap_or(false));
+    let scatter = preset
+        .scatter
+        .as_ref()
+        .filter(|_| preset.scatter_enabled.unwrap_or(false));
+    let transfer = preset
+        .transfer
+        .as_ref()
+        .filter(|_| preset.transfer_enabled.unwrap_or(false));
+
+    let count = scatter.map_or(1, |scatter| {
+        let base = scatter.count.clamp(1, 16) as f32;
+        let controlled = base * control_value(scatter.count_control, pressure, t);
+        let jittered = controlled * (1.0 - scatter.count_jitter / 100.0 * rng.next_f32());
+        jittered.round().max(1.0) as usize
+    });
+
+    let mut dabs = Vec::with_capacity(count);
+    for _ in 0..count {
+        let mut size_factor = if preset.size_pressure { pressure } else { 1.0 };
+        let mut angle = preset.angle.to_radians();
+        let mut roundness = (preset.roundness / 100.0).clamp(0.01, 1.0);
+        let mut flip_x = false;
+        let mut flip_y = false;
+        if let Some(shape) = shape {
+            let minimum = (shape.minimum_diameter / 100.0).clamp(0.0, 1.0);
+            size_factor *= lerp(minimum, 1.0, control_value(shape.size_control, pressure, t));
+            size_factor *= 1.0 - shape.size_jitter / 100.0 * rng.next_f32();
+            size_factor =
+                size_factor.max(minimum * if preset.size_pressure { pressure } else { 1.0 });
+
+            angle += match shape.angle_control {
+                ControlSource::Direction => direction,
+                ControlSource::Initial => initial_direction,
+                _ => 0.0,
+            };
+            angle += (shape.angle_jitter * (rng.next_f32() - 0.5)).to_radians();
+
+            let minimum_roundness = (shape.minimum_roundness / 100.0).clamp(0.01, 1.0);
+            let jitter = shape.roundness_jitter / 100.0 * rng.next_f32();
+            roundness = (roundness * (1.0 - jitter)).max(minimum_roundness.min(roundness));
+
+            flip_x = shape.flip_x_jitter && rng.next_f32() < 0.5;
+            flip_y = shape.flip_y_jitter && rng.next_f32() < 0.5;
+        }
+        let size = (diameter * size_factor).max(1.0);
+
+        let mut flow = preset.base_flow.unwrap_or(1.0).clamp(0.0, 1.0);
+        if preset.opacity_pressure {
+            flow *= pressure;
+        }
+        if let Some(transfer) = transfer {
+            let minimum_flow = (transfer.minimum_flow / 100.0).clamp(0.0, 1.0);
+            flow *= lerp(
+                minimum_flow,
+                1.0,
+                control_value(transfer.flow_control, pressure, t),
+            );
+            flow *= 1.0 - transfer.flow_jitter / 100.0 * rng.next_f32();
+            let minimum_opacity = (transfer.minimum_opacity / 100.0).clamp(0.0, 1.0);
+            flow *= lerp(
+                minimum_opacity,
+                1.0,
+                control_value(transfer.opacity_control, pressure, t),
+            );
+            flow *= 1.0 - transfer.opacity_jitter / 100.0 * rng.next_f32();
+        }
+
+        let (mut dx, mut dy) = (0.0, 0.0);
+        if let Some(scatter) = scatter {
+            let amount = scatter.scatter / 100.0
+                * size
+                * control_value(scatter.scatter_control, pressure, t);
+            let (along, across) = scatter_offset(amount, scatter.both_axes, rng);
+            (dx, dy) = rotate(along, across, direction);
+        }
+
+        dabs.push(PreviewDab {
+            x: x + dx,
+            y: y + dy,
+            size,
+            angle,
+            roundness,
+            flip_x,
+            flip_y,
+            flow,
+        });
+    }
+    dabs
+}
+
+/// Random offset along and across the stroke direction
+fn scatter_offset(amount: f32, both_axes: bool, rng: &mut PreviewRng) -> (f32, f32) {
+    let across = amount * (rng.next_f32() * 2.0 - 1.0);
+    let along = if both_axes {
+        amount * (rng.next_f32() * 2.0 - 1.0)
+    } else {
+        0.0
+    };
+    (along, across)
+}
+
+/// Paint the Dual Brush secondary tip along the same path
+#[allow(clippy::too_many_arguments)]
+fn paint_dual(
+    dual: &DualBrushSettings,
+    tip: &PreviewMask,
+    saved_diameter: f32,
+    diameter: f32,
+    path: &PreviewPath,
+    width: u32,
+    height: u32,
+    rng: &mut PreviewRng,
+) -> Canvas {
+    let mut canvas = Canvas::new(width, height);
+    let size = if dual.size_ratio > 0.0 {
+        diameter * dual.size_ratio
+    } else {
+        dual.size * diameter / saved_diameter.max(1.0)
+    }
+    .max(1.0);
+    let roundness = (dual.roundness / 100.0).clamp(0.01, 1.0);
+    let step = (size * dual.spacing.max(0.01)).max(MIN_SPACING_PX);
+    let total = path.length();
+
+    let mut distance = 0.0;
+    let mut emitted = 0;
+    while distance <= total && emitted < MAX_PREVIEW_DABS {
+        let (x, y, direction) = path.sample(distance);
+        for _ in 0..dual.count.clamp(1, 16) {
+            let amount = dual.scatter / 100.0 * size;
+            let (along, across) = scatter_offset(amount, dual.both_axes, rng);
+            let (dx, dy) = rotate(along, across, direction);
+            let dab = PreviewDab {
+                x: x + dx,
+                y: y + dy,
+                size,
+                angle: 0.0,
+                roundness,
+                flip_x: dual.flip,
+                flip_y: false,
+                flow: 1.0,
+            };
+            canvas.stamp(&dab, Some(tip), 1.0, None, |_, _, mask| mask);
+        }
+        emitted += 1;
+        distance += step;
+    }
+    canvas
+}
+
+/// Texture depth of one dab, honoring the pressure control and depth jitter
+fn texture_depth(settings: &TextureSettings, pressure: f32, rng: &mut PreviewRng) -> f32 {
+    let depth = (settings.depth / 100.0).clamp(0.0, 1.0);
+    let minimum = (settings.minimum_depth / 100.0).clamp(0.0, 1.0);
+    // Descriptor control code 2 is pen pressure
+    let controlled = if settings.depth_control == 2 {
+        lerp(minimum * depth, depth, pressure)
+    } else {
+        depth
+    };
+    controlled * (1.0 - settings.depth_jitter / 100.0 * rng.next_f32())
+}
+
+/// Pattern value (0-1) at a canvas pixel after scale, brightness, contrast and invert
+fn pattern_value(settings: &TextureSettings, pattern: &PreviewMask, x: u32, y: u32) -> f32 {
+    let scale = (settings.scale / 100.0).max(0.01);
+    let value = pattern.sample_tiled(x as f32 / scale, y as f32 / scale);
+    let value = value + settings.brightness as f32 / 255.0;
+    let value = (value - 0.5) * (1.0 + settings.contrast as f32 / 50.0) + 0.5;
+    let value = value.clamp(0.0, 1.0);
+    if settings.invert {
+        1.0 - value
+    } else {
+        value
+    }
+}
+
+/// Modulate coverage by a texture value; dark areas of the pattern eat into
+/// the stroke. Modes without a coverage-only equivalent fall back to Multiply
+fn apply_texture(alpha: f32, value: f32, depth: f32, mode: TextureBlendMode) -> f32 {
+    let cut = depth * (1.0 - value);
+    match mode {
+        TextureBlendMode::Subtract
+        | TextureBlendMode::LinearBurn
+        | TextureBlendMode::Height
+        | TextureBlendMode::LinearHeight => (alpha - cut).max(0.0),
+        TextureBlendMode::Darken => alpha.min(1.0 - cut),
+        TextureBlendMode::HardMix => {
+            if alpha > cut {
+                alpha
+            } else {
+                0.0
+            }
+        }
+        _ => alpha * (1.0 - cut),
+    }
+}
+
+/// Combine primary coverage with the Dual Brush coverage
+fn blend_dual(primary: f32, secondary: f32, mode: DualBlendMode) -> f32 {
+    match mode {
+        DualBlendMode::Darken => primary.min(secondary),
+        DualBlendMode::ColorBurn | DualBlendMode::LinearBurn => {
+            (primary + secondary - 1.0).max(0.0)
+        }
+        DualBlendMode::HardMix => {
+            if primary + secondary >= 1.0 {
+                primary
+            } else {
+                0.0
+            }
+        }
+        _ => primary * secondary,
+    }
+}
+
+/// Single-channel coverage buffer
+struct Canvas {
+    width: u32,
+    height: u32,
+    alpha: Vec<f32>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            alpha: vec![0.0; width as usize * height as usize],
+        }
+    }
+
+    fn alpha(&self, x: u32, y: u32) -> f32 {
+        self.alpha
+            .get((y * self.width + x) as usize)
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Accumulate one dab (Flow builds up within the stroke)
+    ///
+    /// `shade` adjusts the tip mask at a pixel before accumulation (per-tip texture).
+    fn stamp(
+        &mut self,
+        dab: &PreviewDab,
+        tip: Option<&PreviewMask>,
+        hardness: f32,
+        noise: Option<&TipNoise>,
+        shade: impl Fn(u32, u32, f32) -> f32,
+    ) {
+        if dab.flow <= 0.001 {
+            return;
+        }
+        let radius = dab.size / 2.0;
+        // Tip bitmaps fit their longer side to the dab size; the corners reach further
+        let (tip_scale, reach) = match tip {
+            Some(tip) => {
+                let longest = tip.width.max(tip.height).max(1) as f32;
+                let diagonal = (tip.width as f32).hypot(tip.height as f32) / longest;
+                (longest / dab.size, radius * diagonal)
+            }
+            None => (0.0, radius),
+        };
+        let left = (dab.x - reach).floor().max(0.0) as u32;
+        let top = (dab.y - reach).floor().max(0.0) as u32;
+        let right = ((dab.x + reach).ceil().max(0.0) as u32).min(self.width);
+        let bottom = ((dab.y + reach).ceil().max(0.0) as u32).min(self.height);
+        let (sin, cos) = dab.angle.sin_cos();
+
+        for py in top..bottom {
+            for px in left..right {
+                let dx = px as f32 + 0.5 - dab.x;
+                let dy = py as f32 + 0.5 - dab.y;
+                // Into the dab's frame: undo rotation, then roundness squash
+                let mut lx = dx * cos + dy * sin;
+                let mut ly = (-dx * sin + dy * cos) / dab.roundness;
+                if dab.flip_x {
+                    lx = -lx;
+                }
+                if dab.flip_y {
+                    ly = -ly;
+                }
+
+                let mut mask = match tip {
+                    Some(tip) => tip.sample(
+                        lx * tip_scale + tip.width as f32 / 2.0,
+                        ly * tip_scale + tip.height as f32 / 2.0,
+                    ),
+                    None => round_mask(lx.hypot(ly) / radius.max(0.5), hardness),
+                };
+                if let Some(noise) = noise {
+                    mask = noise.apply(mask, px as i32, py as i32);
+                }
+                let mask = shade(px, py, mask) * dab.flow;
+                if mask < 0.001 {
+                    continue;
+                }
+                let idx = (py * self.width + px) as usize;
+                if let Some(alpha) = self.alpha.get_mut(idx) {
+                    *alpha += mask * (1.0 - *alpha);
+                }
+            }
+        }
+    }
+
+    /// Rewrite every pixel's coverage
+    fn map(&mut self, f: impl Fn(u32, u32, f32) -> f32) {
+        for py in 0..self.height {
+            for px in 0..self.width {
+                let idx = (py * self.width + px) as usize;
+                if let Some(alpha) = self.alpha.get_mut(idx) {
+                    if *alpha > 0.0 {
+                        *alpha = f(px, py, *alpha).clamp(0.0, 1.0);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Straight RGBA with coverage in alpha
+    fn to_rgba(&self, ink: [u8; 3]) -> Vec<u8> {
+        let mut rgba = Vec::with_capacity(self.alpha.len() * 4);
+        for &alpha in &self.alpha {
+            rgba.extend_from_slice(&ink);
+            rgba.push((alpha.clamp(0.0, 1.0) * 255.0).round() as u8);
+        }
+        rgba
+    }
+}
+
+/// Computed round tip with a linear hardness falloff (matches `StrokeBuffer::stamp_dab`)
+fn round_mask(distance: f32, hardness: f32) -> f32 {
+    if distance > 1.0 {
+        0.0
+    } else if distance <= hardness {
+        1.0
+    } else {
+        1.0 - (distance - hardness) / (1.0 - hardness).max(0.001)
+    }
+}
+
+/// The S-curve as a polyline with cumulative lengths
+struct PreviewPath {
+    points: Vec<(f32, f32)>,
+    lengths: Vec<f32>,
+}
+
+impl PreviewPath {
+    /// One full sine period across the preview, kept clear of the top and
+    /// bottom edges by the dab radius
+    fn s_curve(width: f32, height: f32, diameter: f32) -> Self {
+        let left = PREVIEW_MARGIN.min(width / 4.0);
+        let span = (width - left * 2.0).max(1.0);
+        let amplitude = ((height - diameter) / 2.0 - 2.0).max(0.0) * 0.8;
+        let center = height / 2.0;
+
+        let mut points: Vec<(f32, f32)> = Vec::with_capacity(PATH_SEGMENTS + 1);
+        let mut lengths = Vec::with_capacity(PATH_SEGMENTS + 1);
+        let mut total = 0.0;
+        for i in 0..=PATH_SEGMENTS {
+            let t = i as f32 / PATH_SEGMENTS as f32;
+            let point = (
+                left + span * t,
+                center - amplitude * (std::f32::consts::TAU * t).sin(),
+            );
+            if let Some(&(px, py)) = points.last() {
+                total += (point.0 - px).hypot(point.1 - py);
+            }
+            points.push(point);
+            lengths.push(total);
+        }
+        Self { points, lengths }
+    }
+
+    fn length(&self) -> f32 {
+        self.lengths.last().copied().unwrap_or(0.0)
+    }
+
+    fn start_direction(&self) -> f32 {
+        self.sample(0.0).2
+    }
+
+    /// Position and direction (radians) at an arc length
+    fn sample(&self, distance: f32) -> (f32, f32, f32) {
+        let last = self.points.len().saturating_sub(1);
+        let segment = self
+            .lengths
+            .partition_point(|&length| length < distance)
+            .clamp(1, last.max(1));
+        let (Some(&(x0, y0)), Some(&(x1, y1))) =
+            (self.points.get(segment - 1), self.points.get(segment))
+        else {
+            return (0.0, 0.0, 0.0);
+        };
+        let start = self.lengths.get(segment - 1).copied().unwrap_or(0.0);
+        let end = self.lengths.get(segment).copied().unwrap_or(start);
+        let t = if end > start {
+            ((distance - start) / (end - start)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        (lerp(x0, x1, t), lerp(y0, y1, t), (y1 - y0).atan2(x1 - x0))
+    }
+}
+
+/// Rotate an (along, across) offset into canvas space
+fn rotate(along: f32, across: f32, direction: f32) -> (f32, f32) {
+    let (sin, cos) = direction.sin_cos();
+    (along * cos - across * sin, along * sin + across * cos)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// SplitMix64; deterministic so a preset always renders the same preview
+struct PreviewRng(u64);
+
+impl PreviewRng {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// Uniform value in [0, 1)
+    fn next_f32(&mut self) -> f32 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn round_preset() -> BrushPreset {
+        BrushPreset {
+            id: "round".to_string(),
+            source_uuid: None,
+            name: "Round".to_string(),
+            diameter: 20.0,
+            spacing: 10.0,
+            hardness: 100.0,
+            angle: 0.0,
+            roundness: 100.0,
+            has_texture: false,
+            is_computed: true,
+            texture_width: None,
+            texture_height: None,
+            size_pressure: true,
+            opacity_pressure: false,
+            cursor_path: None,
+            cursor_bounds: None,
+            cursor_path_lod0: None,
+            cursor_path_lod1: None,
+            cursor_path_lod2: None,
+            cursor_complexity_lod0: None,
+            cursor_complexity_lod1: None,
+            cursor_complexity_lod2: None,
+            texture_settings: None,
+            dual_brush_settings: None,
+            shape_dynamics_enabled: None,
+            shape_dynamics: None,
+            scatter_enabled: None,
+            scatter: None,
+            color_dynamics_enabled: None,
+            color_dynamics: None,
+            transfer_enabled: None,
+            transfer: None,
+            wet_edge_enabled: None,
+            buildup_enabled: None,
+            noise_enabled: None,
+            base_opacity: None,
+            base_flow: None,
+            pressure_curve: None,
+        }
+    }
+
+    fn coverage(rgba: &[u8]) -> Vec<u8> {
+        rgba.chunks_exact(4).map(|px| px[3]).collect()
+    }
+
+    fn column_coverage(alpha: &[u8], x: u32) -> u32 {
+        (0..PREVIEW_HEIGHT)
+            .map(|y| u32::from(alpha[(y * PREVIEW_WIDTH + x) as usize]))
+            .sum()
+    }
+
+    #[test]
+    fn preview_follows_pressure_ramp_and_dynamics() {
+        let preset = round_preset();
+        let rgba = render_preset_preview(
+            &preset,
+            &PreviewResources::default(),
+            PREVIEW_WIDTH,
+            PREVIEW_HEIGHT,
+        );
+        assert_eq!(rgba.len(), (PREVIEW_WIDTH * PREVIEW_HEIGHT * 4) as usize);
+        // Deterministic, so cached previews and fresh renders agree
+        let again = render_preset_preview(
+            &preset,
+            &PreviewResources::default(),
+            PREVIEW_WIDTH,
+            PREVIEW_HEIGHT,
+        );
+        assert_eq!(rgba, again);
+
+        // Size pressure: the stroke is thickest mid-way and thin at the ends
+        let alpha = coverage(&rgba);
+        let middle = column_coverage(&alpha, PREVIEW_WIDTH / 2);
+        let near_end = column_coverage(&alpha, PREVIEW_WIDTH - PREVIEW_MARGIN as u32 - 4);
+        assert!(middle > near_end * 2, "middle {middle} vs end {near_end}");
+        assert_eq!(column_coverage(&alpha, 0), 0);
+
+        // Scattering spreads dabs away from the curve
+        let mut scattered = preset.clone();
+        scattered.scatter_enabled = Some(true);
+        scattered.scatter = Some(crate::abr::ScatterSettings {
+            scatter: 300.0,
+            count: 3,
+            ..Default::default()
+        });
+        let scattered_alpha = coverage(&render_preset_preview(
+            &scattered,
+            &PreviewResources::default(),
+            PREVIEW_WIDTH,
+            PREVIEW_HEIGHT,
+        ));
+        assert_ne!(scattered_alpha, alpha);
+
+        // A dual tip that is empty masks the whole stroke out
+        let mut dual = preset;
+        dual.dual_brush_settings = Some(DualBrushSettings {
+            enabled: true,
+            brush_id: Some("dual".to_string()),
+            ..Default::default()
+        });
+        let resources = PreviewResources {
+            dual_tip: Some(PreviewMask {
+                width: 4,
+                height: 4,
+                data: vec![0; 16],
+            }),
+            ..Default::default()
+        };
+        let masked = render_preset_preview(&dual, &resources, PREVIEW_WIDTH, PREVIEW_HEIGHT);
+        assert!(coverage(&masked).iter().all(|&a| a == 0));
+    }
+
+    #[test]
+    fn evict_drops_cached_preview() {
+        let preview = CachedBrushPreview {
+            content_hash: "h".to_string(),
+            data: Vec::new(),
+            width: 1,
+            height: 1,
+        };
+        PREVIEW_CACHE
+            .write()
+            .get_or_insert_with(HashMap::new)
+            .insert("evict-me".to_string(), preview);
+
+        evict_brush_preview("evict-me");
+        let guard = PREVIEW_CACHE.read();
+        assert!(guard.as_ref().is_some_and(|c| !c.contains_key("evict-me")));
+    }
+}
@@ -0,0 +1,203 @@
+//! Brush library search: fuzzy name/tag matching plus property filters.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+use super::library::BrushLibraryPreset;
+
+/// Result ordering for `search_brush_presets`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BrushPresetSort {
+    /// Best match first; favorites win ties
+    #[default]
+    Relevance,
+    Name,
+    /// Most recent first; presets never used are left out
+    RecentlyUsed,
+    MostUsed,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BrushPresetSearchFilters {
+    /// Presets must carry every one of these tags (case-insensitive)
+    pub tags: Vec<String>,
+    pub group: Option<String>,
+    pub favorites_only: bool,
+    /// Pattern texture enabled
+    pub textured: Option<bool>,
+    /// Dual brush enabled
+    pub dual: Option<bool>,
+    /// Procedural (computed) tip
+    pub computed: Option<bool>,
+    /// Diameter range in pixels (inclusive)
+    pub min_size: Option<f32>,
+    pub max_size: Option<f32>,
+    pub sort: BrushPresetSort,
+    pub limit: Option<usize>,
+}
+
+/// Score how well a lowercase `query` matches `text`; `None` when it does not.
+/// Substrings beat scattered subsequences; prefixes and word starts score higher.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+    if query.is_empty() {
+        return Some(0);
+    }
+    let text = text.to_lowercase();
+    if let Some(pos) = text.find(query) {
+        let at_word_start = !text[..pos]
+            .chars()
+            .next_back()
+            .is_some_and(char::is_alphanumeric);
+        let mut score = 1000 - pos.min(500) as u32;
+        if text == query {
+            score += 1000;
+        } else if pos == 0 {
+            score += 500;
+        } else if at_word_start {
+            score += 250;
+        }
+        return Some(score);
+    }
+
+    let mut score: u32 = 500;
+    let mut chars = text.chars().enumerate();
+    let mut last_index: Option<usize> = None;
+    for query_char in query.chars() {
+        let (index, _) = chars.by_ref().find(|(_, c)| *c == query_char)?;
+        if let Some(last) = last_index {
+            score = score.saturating_sub((index - last - 1) as u32 * 10);
+        }
+        last_index = Some(index);
+    }
+    Some(score.max(1))
+}
+
+/// Best score of one query token against a preset's name, tags and group
+fn token_score(token: &str, entry: &BrushLibraryPreset) -> Option<u32> {
+    let name = fuzzy_score(token, &entry.preset.name);
+    let tag = entry
+        .tags
+        .iter()
+        .filter_map(|tag| fuzzy_score(token, tag))
+        .max()
+        .map(|score| score / 2);
+    let group = entry
+        .group
+        .as_deref()
+        .and_then(|group| fuzzy_score(token, group))
+        .map(|score| score / 4);
+    [name, tag, group].into_iter().flatten().max()
+}
+
+fn matches_filters(entry: &BrushLibraryPreset, filters: &BrushPresetSearchFilters) -> bool {
+    let preset = &entry.preset;
+    let textured = preset
+        .texture_settings
+        .as_ref()
+        .is_some_and(|texture| texture.enabled);
+    let dual = preset
+        .dual_brush_settings
+        .as_ref()
+        .is_some_and(|dual| dual.enabled);
+
+    let has_all_tags = filters.tags.iter().all(|wanted| {
+        entry
+            .tags
+            .iter()
+            .any(|tag| tag.eq_ignore_ascii_case(wanted.trim()))
+    });
+    if !has_all_tags || (filters.favorites_only && !entry.favorite) {
+        return false;
+    }
+    if let Some(group) = filters.group.as_deref() {
+        if entry.group.as_deref() != Some(group) {
+            return false;
+        }
+    }
+    if filters.sort == BrushPresetSort::RecentlyUsed && entry.last_used_ms.is_none() {
+        return false;
+    }
+
+    let flags = [
+        (filters.textured, textured),
+        (filters.dual, dual),
+        (filters.computed, preset.is_computed),
+    ];
+    if flags
+        .iter()
+        .any(|(wanted, actual)| wanted.is_some_and(|wanted| wanted != *actual))
+    {
+        return false;
+    }
+
+    !filters.min_size.is_some_and(|min| preset.diameter < min)
+        && !filters.max_size.is_some_and(|max| preset.diameter > max)
+}
+
+/// Presets matching every whitespace-separated query token and all filters
+pub fn search_presets<'a>(
+    presets: impl Iterator<Item = &'a BrushLibraryPreset>,
+    query: &str,
+    filters: &BrushPresetSearchFilters,
+) -> Vec<BrushLibraryPreset> {
+    let query = query.to_lowercase();
+    let tokens: Vec<&str> = query.split_whitespace().collect();
+
+    let mut hits: Vec<(u32, &BrushLibraryPreset)> = presets
+        .filter(|entry| matches_filters(entry, filters))
+        .filter_map(|entry| {
+            tokens
+                .iter()
+                .map(|token| token_score(token, entry))
+                .sum::<Option<u32>>()
+                .map(|score| (score, entry))
+        })
+        .collect();
+
+    let by_name = |a: &BrushLibraryPreset, b: &BrushLibraryPreset| {
+        a.preset
+            .name
+            .to_lowercase()
+            .cmp(&b.preset.name.to_lowercase())
+    };
+    hits.sort_by(|(score_a, a), (score_b, b)| {
+        let primary = match filters.sort {
+            BrushPresetSort::Relevance => score_b
+                .cmp(score_a)
+                .then_with(|| b.favorite.cmp(&a.favorite)),
+            BrushPresetSort::Name => Ordering::Equal,
+            BrushPresetSort::RecentlyUsed => b.last_used_ms.cmp(&a.last_used_ms),
+            BrushPresetSort::MostUsed => b.use_count.cmp(&a.use_count),
+        };
+        primary.then_with(|| by_name(a, b))
+    });
+
+    let limit = filters.limit.unwrap_or(usize::MAX);
+    hits.into_iter()
+        .take(limit)
+        .map(|(_, entry)| entry.clone())
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_score_prefers_prefix_over_substring_over_subsequence() {
+        let exact = fuzzy_score("ink", "Ink").unwrap();
+        let prefix = fuzzy_score("ink", "Inking Pen").unwrap();
+        let word = fuzzy_score("ink", "Dry Ink").unwrap();
+        let inner = fuzzy_score("ink", "Blinking").unwrap();
+        let scattered = fuzzy_score("ink", "I Need Kraft").unwrap();
+        assert!(exact > prefix);
+        assert!(prefix > word);
+        assert!(word > inner);
+        assert!(inner > scattered);
+        assert_eq!(fuzzy_score("ink", "Pencil"), None);
+        assert_eq!(fuzzy_score("", "Pencil"), Some(0));
+    }
+}
@@ -0,0 +1,344 @@
+//! Smudge / Mixer Brush - picks up canvas color and carries it along the stroke
+//!
+//! Works on top of the stroke buffer like regular paint (so Flow/Opacity keep
+//! their three-level behavior), but the color of each dab is mixed from:
+//! - **Reservoir**: the paint loaded on the brush at stroke start
+//! - **Canvas**: the layer under the dab, with the current stroke composited over it
+//!
+//! Parameters follow Photoshop's Mixer Brush:
+//! - **Wetness**: how much canvas color the brush picks up per dab (the first
+//!   dab always loads the brush with the canvas under it, like Krita's smudge)
+//! - **Load**: how much paint the reservoir holds (drains along the stroke)
+//! - **Mix**: ratio of canvas color to reservoir paint in each deposit
+//! - **Flow**: per-dab deposit alpha (accumulates in the stroke buffer)
+
+use serde::{Deserialize, Serialize};
+
+use super::stroke_buffer::{Pixel, StrokeBuffer};
+
+/// Reservoir drained per dab at Load = 0 (empty after 20 dabs)
+const LOAD_DRAIN_PER_DAB: f32 = 0.05;
+
+/// Mixer brush settings (all values 0-1)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmudgeSettings {
+    /// Canvas color picked up per dab
+    pub wetness: f32,
+    /// Reservoir capacity (1 = never runs dry)
+    pub load: f32,
+    /// Canvas share of the deposited color (1 = pure smudge)
+    pub mix: f32,
+    /// Per-dab deposit alpha
+    pub flow: f32,
+}
+
+impl Default for SmudgeSettings {
+    fn default() -> Self {
+        Self {
+            wetness: 0.5,
+            load: 0.5,
+            mix: 0.5,
+            flow: 1.0,
+        }
+    }
+}
+
+/// Mixer brush state for a single stroke
+pub struct SmudgeBrush {
+    settings: SmudgeSettings,
+    /// Paint loaded at stroke start (premultiplied)
+    reservoir: Pixel,
+    /// Paint currently on the brush (premultiplied), None until first contact
+    carried: Option<Pixel>,
+    /// Remaining reservoir paint (1 = full, 0 = dry)
+    paint_left: f32,
+}
+
+impl SmudgeBrush {
+    /// Create a mixer brush with given settings
+    pub fn new(settings: SmudgeSettings) -> Self {
+        Self {
+            settings,
+            reservoir: Pixel::transparent(),
+            carried: None,
+            paint_left: 1.0,
+        }
+    }
+
+    /// Update settings
+    pub fn set_settings(&mut self, settings: SmudgeSettings) {
+        self.settings = settings;
+    }
+
+    /// Get current settings
+    pub fn settings(&self) -> &SmudgeSettings {
+        &self.settings
+    }
+
+    /// Reset for a new stroke and load the reservoir with a color
+    ///
+    /// # Arguments
+    /// * `color` - RGB color (0-1) loaded on the brush
+    pub fn begin_stroke(&mut self, color: [f32; 3]) {
+        self.reservoir = Pixel::new(color[0], color[1], color[2], 1.0);
+        self.carried = None;
+        self.paint_left = 1.0;
+    }
+
+    /// Paint currently carried by the brush (premultiplied)
+    pub fn carried(&self) -> Option<Pixel> {
+        self.carried
+    }
+
+    /// Remaining reservoir paint (1 = full, 0 = dry)
+    pub fn paint_left(&self) -> f32 {
+        self.paint_left
+    }
+
+    /// Pick up canvas color under the dab, mix it with the reservoir and
+    /// stamp the result into the stroke buffer
+    ///
+    /// # Arguments
+    /// * `buffer` - Active stroke buffer
+    /// * `layer_data` - Target layer RGBA data (straight alpha, u8), read only
+    /// * `cx`, `cy` - Dab center
+    /// * `radius` - Dab radius in pixels
+    /// * `hardness` - Edge hardness (0 = soft, 1 = hard)
+    pub fn stamp(
+        &mut self,
+        buffer: &mut StrokeBuffer,
+        layer_data: &[u8],
+        cx: f32,
+        cy: f32,
+        radius: f32,
+        hardness: f32,
+    ) {
+        let wetness = self.settings.wetness.clamp(0.0, 1.0);
+        let mix = self.settings.mix.clamp(0.0, 1.0);
+        let load = self.settings.load.clamp(0.0, 1.0);
+        let flow = self.settings.flow.clamp(0.0, 1.0);
+
+        // Pick up canvas color (first contact loads the brush with the canvas)
+        let canvas = buffer.sample_composite(layer_data, cx, cy, radius, hardness);
+        let picked = match self.carried {
+            Some(carried) => lerp_pixel(carried, canvas, wetness),
+            None => canvas,
+        };
+
+        // Refill from the reservoir while it still has paint
+        let reservoir_weight = (1.0 - mix) * self.paint_left;
+        let mixed = lerp_pixel(picked, self.reservoir, reservoir_weight);
+
+        self.carried = Some(mixed);
+        self.paint_left = (self.paint_left - (1.0 - load) * LOAD_DRAIN_PER_DAB).max(0.0);
+
+        if mixed.a < 0.001 {
+            return;
+        }
+
+        let inv_a = 1.0 / mixed.a;
+        let color = [
+            (mixed.r * inv_a).clamp(0.0, 1.0),
+            (mixed.g * inv_a).clamp(0.0, 1.0),
+            (mixed.b * inv_a).clamp(0.0, 1.0),
+        ];
+        buffer.stamp_dab(cx, cy, radius, color, flow * mixed.a, hardness);
+    }
+}
+
+/// Linear interpolation between premultiplied pixels
+#[inline]
+fn lerp_pixel(a: Pixel, b: Pixel, t: f32) -> Pixel {
+    Pixel {
+        r: a.r + (b.r - a.r) * t,
+        g: a.g + (b.g - a.g) * t,
+        b: a.b + (b.b - a.b) * t,
+        a: a.a + (b.a - a.a) * t,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brush::{BrushStamper, StamperConfig};
+    use crate::input::RawInputPoint;
+
+    const W: u32 = 100;
+    const H: u32 = 40;
+
+    /// Opaque horizontal gradient: red at x = 0, blue at x = W
+    fn gradient_layer() -> Vec<u8> {
+        let mut data = vec![0u8; (W * H * 4) as usize];
+        for y in 0..H {
+            for x in 0..W {
+                let t = x as f32 / (W - 1) as f32;
+                let idx = ((y * W + x) * 4) as usize;
+                data[idx] = ((1.0 - t) * 255.0).round() as u8;
+                data[idx + 1] = 0;
+                data[idx + 2] = (t * 255.0).round() as u8;
+                data[idx + 3] = 255;
+            }
+        }
+        data
+    }
+
+    /// Render a straight horizontal stroke and return the composited layer
+    fn render_stroke(
+        layer: &[u8],
+        settings: SmudgeSettings,
+        color: [f32; 3],
+        from_x: f32,
+        to_x: f32,
+    ) -> Vec<u8> {
+        let mut stamper = BrushStamper::new(StamperConfig {
+            size: 12.0,
+            spacing: 0.1,
+            pressure_size: false,
+            pressure_alpha: false,
+            ..Default::default()
+        });
+        let mut buffer = StrokeBuffer::new(W, H);
+        let mut brush = SmudgeBrush::new(settings);
+
+        stamper.begin_stroke();
+        buffer.begin_stroke();
+        brush.begin_stroke(color);
+
+        let y = H as f32 / 2.0;
+        let steps = 20;
+        for i in 0..=steps {
+            let x = from_x + (to_x - from_x) * i as f32 / steps as f32;
+            for dab in stamper.process_point(&RawInputPoint::new(x, y, 1.0)) {
+                brush.stamp(&mut buffer, layer, dab.x, dab.y, dab.size / 2.0, 1.0);
+            }
+        }
+
+        let mut out = layer.to_vec();
+        buffer.end_stroke(&mut out, 1.0);
+        out
+    }
+
+    fn rgba_at(data: &[u8], x: u32, y: u32) -> [u8; 4] {
+        let idx = ((y * W + x) * 4) as usize;
+        [data[idx], data[idx + 1], data[idx + 2], data[idx + 3]]
+    }
+
+    #[test]
+    fn test_dry_brush_paints_reservoir_color() {
+        let layer = gradient_layer();
+        let settings = SmudgeSettings {
+            wetness: 0.0,
+            load: 1.0,
+            mix: 0.0,
+            flow: 1.0,
+        };
+        let out = render_stroke(&layer, settings, [0.0, 1.0, 0.0], 10.0, 90.0);
+
+        let px = rgba_at(&out, 50, H / 2);
+        assert!(px[1] > 250, "expected green, got {:?}", px);
+        assert!(px[0] < 5 && px[2] < 5);
+    }
+
+    #[test]
+    fn test_smudge_drags_color_along_stroke() {
+        let layer = gradient_layer();
+        let settings = SmudgeSettings {
+            wetness: 0.2,
+            load: 1.0,
+            mix: 1.0,
+            flow: 1.0,
+        };
+        let out = render_stroke(&layer, settings, [0.0, 1.0, 0.0], 10.0, 70.0);
+
+        let before = rgba_at(&layer, 60, H / 2);
+        let after = rgba_at(&out, 60, H / 2);
+
+        // Red from the left side is carried to the right
+        assert!(
+            after[0] > before[0] + 20,
+            "before {:?}, after {:?}",
+            before,
+            after
+        );
+        // Pure smudge never deposits the reservoir color
+        assert!(after[1] < 5);
+
+        // Untouched rows keep the gradient
+        assert_eq!(rgba_at(&out, 60, 0), rgba_at(&layer, 60, 0));
+    }
+
+    #[test]
+    fn test_mix_blends_reservoir_and_canvas() {
+        let layer = gradient_layer();
+        let settings = SmudgeSettings {
+            wetness: 1.0,
+            load: 1.0,
+            mix: 0.5,
+            flow: 1.0,
+        };
+        let mut buffer = StrokeBuffer::new(W, H);
+        let mut brush = SmudgeBrush::new(settings);
+        buffer.begin_stroke();
+        brush.begin_stroke([0.0, 1.0, 0.0]);
+        brush.stamp(&mut buffer, &layer, 50.0, 20.0, 6.0, 1.0);
+
+        let mut out = layer.clone();
+        buffer.end_stroke(&mut out, 1.0);
+
+        let px = rgba_at(&out, 50, H / 2);
+        // Both reservoir (green) and canvas (red/blue) contribute
+        assert!(px[1] > 60 && px[1] < 200, "got {:?}", px);
+        assert!(px[0] as u32 + px[2] as u32 > 60, "got {:?}", px);
+    }
+
+    #[test]
+    fn test_low_load_runs_dry() {
+        let settings = SmudgeSettings {
+            wetness: 0.5,
+            load: 0.0,
+            mix: 0.0,
+            flow: 1.0,
+        };
+        let layer = gradient_layer();
+        let mut buffer = StrokeBuffer::new(W, H);
+        let mut brush = SmudgeBrush::new(settings);
+        buffer.begin_stroke();
+        brush.begin_stroke([0.0, 1.0, 0.0]);
+
+        let carried_green = |brush: &SmudgeBrush| brush.carried().map(|p| p.g).unwrap_or(0.0);
+
+        brush.stamp(&mut buffer, &layer, 20.0, 20.0, 5.0, 1.0);
+        let early_green = carried_green(&brush);
+        for i in 0..40 {
+            brush.stamp(&mut buffer, &layer, 20.0 + i as f32, 20.0, 5.0, 1.0);
+        }
+
+        assert_eq!(brush.paint_left(), 0.0);
+        assert!(carried_green(&brush) < early_green);
+    }
+
+    #[test]
+    fn test_smudge_spreads_paint_into_transparency() {
+        // Left half opaque red, right half transparent
+        let mut layer = vec![0u8; (W * H * 4) as usize];
+        for y in 0..H {
+            for x in 0..W / 2 {
+                let idx = ((y * W + x) * 4) as usize;
+                layer[idx..idx + 4].copy_from_slice(&[255, 0, 0, 255]);
+            }
+        }
+
+        let settings = SmudgeSettings {
+            wetness: 0.3,
+            load: 1.0,
+            mix: 1.0,
+            flow: 1.0,
+        };
+        let out = render_stroke(&layer, settings, [0.0, 0.0, 1.0], 30.0, 70.0);
+
+        let px = rgba_at(&out, 55, H / 2);
+        assert!(px[3] > 0, "expected smeared paint, got {:?}", px);
+        assert!(px[0] > 200 && px[2] < 5, "got {:?}", px);
+    }
+}
@@ -0,0 +1,436 @@
+//! Stroke stabilizer - removes hand jitter before dabs are placed
+//!
+//! Interpolation (interpolation.rs) only smooths *between* input samples;
+//! the stabilizer moves the samples themselves:
+//! - **Weighted average**: moving average over the last N samples, newer
+//!   samples weigh more
+//! - **Pulled string** (lazy mouse / Lazy Nezumi): the brush trails the pen on
+//!   a string of fixed length and only moves once the string is taut
+//! - **Time-based** (Krita "Stabilizer"): average of the pen positions over a
+//!   fixed time window, so slow careful lines are smoothed more than fast ones
+//!
+//! Averaging modes lag behind the pen; `finish` drags the stroke out to the
+//! final pen position so lines do not end short.
+
+use std::collections::VecDeque;
+
+use crate::input::RawInputPoint;
+
+/// Tail points closer than this to the previous output are dropped
+const TAIL_MIN_DISTANCE: f32 = 0.01;
+
+/// Stabilizer mode for brush strokes
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum StabilizerMode {
+    /// Raw input positions
+    #[default]
+    Off,
+    /// Weighted moving average over the last `samples` input points
+    WeightedAverage { samples: usize },
+    /// Brush follows the pen on a string of `radius` pixels
+    PulledString { radius: f32 },
+    /// Average of pen positions within the last `window_ms` milliseconds
+    TimeBased { window_ms: f32 },
+}
+
+/// Per-stroke stabilizer state
+pub struct Stabilizer {
+    mode: StabilizerMode,
+    /// Recent pen samples (averaging modes)
+    samples: VecDeque<RawInputPoint>,
+    /// Current brush position on the string (pulled string mode)
+    anchor: Option<RawInputPoint>,
+    /// Last stabilized output
+    last_output: Option<RawInputPoint>,
+}
+
+impl Stabilizer {
+    /// Create a stabilizer with given mode
+    pub fn new(mode: StabilizerMode) -> Self {
+        Self {
+            mode,
+            samples: VecDeque::new(),
+            anchor: None,
+            last_output: None,
+        }
+    }
+
+    /// Update mode
+    pub fn set_mode(&mut self, mode: StabilizerMode) {
+        self.mode = mode;
+    }
+
+    /// Get current mode
+    pub fn mode(&self) -> StabilizerMode {
+        self.mode
+    }
+
+    /// Reset for a new stroke
+    pub fn begin_stroke(&mut self) {
+        self.samples.clear();
+        self.anchor = None;
+        self.last_output = None;
+    }
+
+    /// Feed a pen sample and return the stabilized point
+    ///
+    /// Pressure, tilt and timestamp always follow the pen; only the position
+    /// is stabilized.
+    pub fn process(&mut self, point: &RawInputPoint) -> RawInputPoint {
+        let output = match self.mode {
+            StabilizerMode::Off => *point,
+            StabilizerMode::WeightedAverage { samples } => {
+                self.samples.push_back(*point);
+                while self.samples.len() > samples.max(1) {
+                    self.samples.pop_front();
+                }
+                self.weighted_average(point)
+            }
+            StabilizerMode::PulledString { radius } => self.pull_string(point, radius),
+            StabilizerMode::TimeBased { window_ms } => {
+                self.samples.push_back(*point);
+                let cutoff = point.timestamp_ms.saturating_sub(window_ms.max(0.0) as u64);
+                while self.samples.len() > 1
+                    && self
+                        .samples
+                        .front()
+                        .is_some_and(|s| s.timestamp_ms < cutoff)
+                {
+                    self.samples.pop_front();
+                }
+                self.mean(point)
+            }
+        };
+
+        self.last_output = Some(output);
+        output
+    }
+
+    /// Finish the stroke and return the tail that catches up with the pen
+    ///
+    /// Averaging modes keep feeding the last pen sample until the output
+    /// reaches it. Pulled string leaves the brush where the string ends. Tail
+    /// points carry the last pen timestamp, so no time passes during the tail,
+    /// and never move past the pen.
+    pub fn finish(&mut self) -> Vec<RawInputPoint> {
+        let Some(pen) = self.samples.back().copied() else {
+            return Vec::new();
+        };
+
+        let steps = match self.mode {
+            StabilizerMode::WeightedAverage { samples } => samples.max(1) - 1,
+            StabilizerMode::TimeBased { .. } => self.samples.len() - 1,
+            StabilizerMode::Off | StabilizerMode::PulledString { .. } => 0,
+        };
+
+        let start = self.last_output;
+        let mut tail = Vec::with_capacity(steps);
+        for _ in 0..steps {
+            let previous = self.last_output;
+            let output = match self.mode {
+                // Replace the oldest sample instead of advancing the clock
+                StabilizerMode::TimeBased { .. } => {
+                    self.samples.pop_front();
+                    self.samples.push_back(pen);
+                    let output = self.mean(&pen);
+                    self.last_output = Some(output);
+                    output
+                }
+                _ => self.process(&pen),
+            };
+            let output = clamp_to_pen(start.as_ref(), &pen, output);
+            self.last_output = Some(output);
+            let stalled = previous.is_some_and(|p| distance(&p, &output) <= TAIL_MIN_DISTANCE);
+            if !stalled {
+                tail.push(output);
+            }
+        }
+
+        // Land exactly on the pen (averages can leave float residue)
+        if let Some(last) = tail.last_mut() {
+            last.x = pen.x;
+            last.y = pen.y;
+        }
+
+        tail
+    }
+
+    /// Linearly weighted average of buffered positions (newest weighs most)
+    fn weighted_average(&self, pen: &RawInputPoint) -> RawInputPoint {
+        let (mut x, mut y, mut total) = (0.0, 0.0, 0.0);
+        for (i, sample) in self.samples.iter().enumerate() {
+            let weight = (i + 1) as f32;
+            x += sample.x * weight;
+            y += sample.y * weight;
+            total += weight;
+        }
+        RawInputPoint {
+            x: x / total,
+            y: y / total,
+            ..*pen
+        }
+    }
+
+    /// Plain average of buffered positions
+    fn mean(&self, pen: &RawInputPoint) -> RawInputPoint {
+        let n = self.samples.len() as f32;
+        let x = self.samples.iter().map(|s| s.x).sum::<f32>() / n;
+        let y = self.samples.iter().map(|s| s.y).sum::<f32>() / n;
+        RawInputPoint { x, y, ..*pen }
+    }
+
+    /// Move the anchor toward the pen only when the string is taut
+    fn pull_string(&mut self, pen: &RawInputPoint, radius: f32) -> RawInputPoint {
+        self.samples.clear();
+        self.samples.push_back(*pen);
+
+        let Some(anchor) = self.anchor else {
+            self.anchor = Some(*pen);
+            return *pen;
+        };
+
+        let dist = distance(&anchor, pen);
+        let radius = radius.max(0.0);
+        let (x, y) = if dist > radius {
+            let t = (dist - radius) / dist;
+            (
+                anchor.x + (pen.x - anchor.x) * t,
+                anchor.y + (pen.y - anchor.y) * t,
+            )
+        } else {
+            (anchor.x, anchor.y)
+        };
+
+        let output = RawInputPoint { x, y, ..*pen };
+        self.anchor = Some(output);
+        output
+    }
+}
+
+impl Default for Stabilizer {
+    fn default() -> Self {
+        Self::new(StabilizerMode::Off)
+    }
+}
+
+/// Snap `point` onto the pen if it lies beyond the pen as seen from `start`
+/// (a window that still holds samples from before the pen turned back)
+fn clamp_to_pen(
+    start: Option<&RawInputPoint>,
+    pen: &RawInputPoint,
+    point: RawInputPoint,
+) -> RawInputPoint {
+    let Some(start) = start else {
+        return point;
+    };
+    let (dx, dy) = (pen.x - start.x, pen.y - start.y);
+    let length_sq = dx * dx + dy * dy;
+    let t = if length_sq > f32::EPSILON {
+        ((point.x - start.x) * dx + (point.y - start.y) * dy) / length_sq
+    } else {
+        1.0
+    };
+    if t >= 1.0 {
+        RawInputPoint {
+            x: pen.x,
+            y: pen.y,
+            ..point
+        }
+    } else {
+        point
+    }
+}
+
+#[inline]
+fn distance(a: &RawInputPoint, b: &RawInputPoint) -> f32 {
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+    (dx * dx + dy * dy).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Recorded-style jittery stroke: a horizontal line at y = 50 sampled
+    /// every 5ms, with deterministic +-3px hand tremor on both axes
+    fn jittery_line() -> Vec<RawInputPoint> {
+        let mut seed: u32 = 0x5eed;
+        let mut jitter = move || {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            ((seed >> 8) as f32 / (1u32 << 24) as f32 - 0.5) * 6.0
+        };
+        (0..120)
+            .map(|i| RawInputPoint {
+                x: i as f32 * 2.0 + jitter(),
+                y: 50.0 + jitter(),
+                pressure: 0.6,
+                tilt_x: 0.0,
+                tilt_y: 0.0,
+                timestamp_ms: i as u64 * 5,
+            })
+            .collect()
+    }
+
+    /// RMS distance from the ideal line, skipping the stroke start
+    fn rms_error(points: &[RawInputPoint]) -> f32 {
+        let tail = &points[10..];
+        let sum: f32 = tail.iter().map(|p| (p.y - 50.0).powi(2)).sum();
+        (sum / tail.len() as f32).sqrt()
+    }
+
+    fn run(mode: StabilizerMode, input: &[RawInputPoint]) -> Vec<RawInputPoint> {
+        let mut stabilizer = Stabilizer::new(mode);
+        stabilizer.begin_stroke();
+        input.iter().map(|p| stabilizer.process(p)).collect()
+    }
+
+    #[test]
+    fn test_off_passes_input_through() {
+        let input = jittery_line();
+        let output = run(StabilizerMode::Off, &input);
+        for (a, b) in input.iter().zip(&output) {
+            assert_eq!((a.x, a.y), (b.x, b.y));
+        }
+    }
+
+    #[test]
+    fn test_modes_reduce_jitter() {
+        let input = jittery_line();
+        let raw = rms_error(&input);
+
+        for mode in [
+            StabilizerMode::WeightedAverage { samples: 8 },
+            StabilizerMode::PulledString { radius: 6.0 },
+            StabilizerMode::TimeBased { window_ms: 40.0 },
+        ] {
+            let smoothed = rms_error(&run(mode, &input));
+            assert!(
+                smoothed < raw * 0.6,
+                "{:?}: raw {} smoothed {}",
+                mode,
+                raw,
+                smoothed
+            );
+        }
+    }
+
+    #[test]
+    fn test_output_keeps_pen_pressure_and_time() {
+        let input = jittery_line();
+        let output = run(StabilizerMode::WeightedAverage { samples: 4 }, &input);
+        for (a, b) in input.iter().zip(&output) {
+            assert_eq!(a.pressure, b.pressure);
+            assert_eq!(a.timestamp_ms, b.timestamp_ms);
+        }
+    }
+
+    #[test]
+    fn test_pulled_string_stays_within_radius() {
+        let input = jittery_line();
+        let radius = 6.0;
+        let output = run(StabilizerMode::PulledString { radius }, &input);
+        for (pen, brush) in input.iter().zip(&output) {
+            assert!(distance(pen, brush) <= radius + 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_pulled_string_ignores_small_movement() {
+        let mut stabilizer = Stabilizer::new(StabilizerMode::PulledString { radius: 10.0 });
+        let start = stabilizer.process(&RawInputPoint::new(0.0, 0.0, 1.0));
+        let wobble = stabilizer.process(&RawInputPoint::new(5.0, -4.0, 1.0));
+        assert_eq!((start.x, start.y), (wobble.x, wobble.y));
+
+        // Taut string: brush trails the pen by exactly the radius
+        let pulled = stabilizer.process(&RawInputPoint::new(30.0, 0.0, 1.0));
+        assert!((pulled.x - 20.0).abs() < 1e-4);
+        assert!(pulled.y.abs() < 1e-4);
+        assert!(stabilizer.finish().is_empty());
+    }
+
+    #[test]
+    fn test_finish_drags_stroke_to_pen() {
+        let input = jittery_line();
+        let Some(pen) = input.last() else {
+            panic!("input should not be empty");
+        };
+
+        for mode in [
+            StabilizerMode::WeightedAverage { samples: 8 },
+            StabilizerMode::TimeBased { window_ms: 40.0 },
+        ] {
+            let mut stabilizer = Stabilizer::new(mode);
+            let Some(before) = input.iter().map(|p| stabilizer.process(p)).last() else {
+                panic!("stabilizer should produce output");
+            };
+            assert!(distance(&before, pen) > 1.0, "{:?} should lag", mode);
+
+            let tail = stabilizer.finish();
+            let Some(end) = tail.last() else {
+                panic!("{:?} should produce a tail", mode);
+            };
+            assert_eq!((end.x, end.y), (pen.x, pen.y));
+            assert!(tail.iter().all(|p| p.timestamp_ms == pen.timestamp_ms));
+            // Tail approaches the pen monotonically
+            for pair in tail.windows(2) {
+                assert!(distance(&pair[1], pen) <= distance(&pair[0], pen) + 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_finish_never_passes_the_pen() {
+        let point = |x: f32, t: u64| RawInputPoint {
+            timestamp_ms: t,
+            ..RawInputPoint::new(x, 0.0, 1.0)
+        };
+        // The pen turns back at the end; the window still holds x = 20
+        let input = [
+            point(0.0, 0),
+            point(10.0, 5),
+            point(20.0, 10),
+            point(15.0, 15),
+        ];
+        for mode in [
+            StabilizerMode::WeightedAverage { samples: 4 },
+            StabilizerMode::TimeBased { window_ms: 40.0 },
+        ] {
+            let mut stabilizer = Stabilizer::new(mode);
+            for p in &input {
+                stabilizer.process(p);
+            }
+            let tail = stabilizer.finish();
+            assert!(
+                tail.iter().all(|p| p.x <= 15.0),
+                "{:?} overshoots: {:?}",
+                mode,
+                tail.iter().map(|p| p.x).collect::<Vec<_>>()
+            );
+            assert_eq!(tail.last().map(|p| p.x), Some(15.0));
+        }
+    }
+
+    #[test]
+    fn test_time_based_window_follows_timestamps() {
+        let mut stabilizer = Stabilizer::new(StabilizerMode::TimeBased { window_ms: 10.0 });
+        let point = |x: f32, t: u64| RawInputPoint {
+            timestamp_ms: t,
+            ..RawInputPoint::new(x, 0.0, 1.0)
+        };
+        stabilizer.process(&point(0.0, 0));
+        stabilizer.process(&point(10.0, 5));
+        // Sample at t = 0 falls out of the 10ms window
+        let out = stabilizer.process(&point(20.0, 11));
+        assert!((out.x - 15.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_begin_stroke_resets_state() {
+        let mut stabilizer = Stabilizer::new(StabilizerMode::WeightedAverage { samples: 4 });
+        stabilizer.process(&RawInputPoint::new(100.0, 100.0, 1.0));
+        stabilizer.begin_stroke();
+        let out = stabilizer.process(&RawInputPoint::new(0.0, 0.0, 1.0));
+        assert_eq!((out.x, out.y), (0.0, 0.0));
+    }
+}
@@ -3,7 +3,12 @@
 //! This module handles the conversion of input points to brush dabs,
 //! using distance accumulation to ensure consistent spacing regardless
 //! of input device sampling rate.
+//!
+//! With Build-up enabled, dabs are also emitted on a timer while the pen is
+//! stationary (airbrush behavior), driven by input timestamps or by
+//! `tick_buildup` when no input arrives.
 
+use super::stabilizer::{Stabilizer, StabilizerMode};
 use crate::input::RawInputPoint;
 
 /// A single brush dab to be rendered
@@ -80,6 +85,12 @@ pub struct StamperConfig {
     pub min_size_ratio: f32,
     /// Minimum alpha ratio when pressure = 0 (0-1)
     pub min_alpha_ratio: f32,
+    /// Build-up: keep emitting dabs while the pen is stationary
+    pub buildup: bool,
+    /// Build-up emission rate (dabs per second)
+    pub buildup_rate: f32,
+    /// Stabilizer applied to input points before dabs are placed
+    pub stabilizer: StabilizerMode,
 }
 
 impl Default for StamperConfig {
@@ -93,10 +104,18 @@ impl Default for StamperConfig {
             pressure_alpha: true,
             min_size_ratio: 0.0,
             min_alpha_ratio: 0.0,
+            buildup: false,
+            buildup_rate: DEFAULT_BUILDUP_RATE,
+            stabilizer: StabilizerMode::Off,
         }
     }
 }
 
+/// Build-up dabs per second (matches frontend stroke processor)
+const DEFAULT_BUILDUP_RATE: f32 = 5.0;
+/// Upper bound on build-up dabs per call, so a long stall does not flood the stroke
+const MAX_BUILDUP_DABS_PER_CALL: usize = 8;
+
 /// Brush stamper that converts input points to dabs
 pub struct BrushStamper {
     config: StamperConfig,
@@ -108,22 +127,35 @@ pub struct BrushStamper {
     point_history: Vec<PathPoint>,
     /// Whether this is the first point of a stroke
     is_stroke_start: bool,
+    /// Most recent input point (build-up dabs are stamped here)
+    last_input_point: Option<PathPoint>,
+    /// Timestamp of the most recent input point
+    last_timestamp_ms: Option<u64>,
+    /// Stationary time accumulated toward the next build-up dab
+    buildup_elapsed_ms: f32,
+    /// Jitter removal ahead of interpolation
+    stabilizer: Stabilizer,
 }
 
 impl BrushStamper {
     /// Create a new stamper with given configuration
     pub fn new(config: StamperConfig) -> Self {
         Self {
+            stabilizer: Stabilizer::new(config.stabilizer),
             config,
             accumulated_distance: 0.0,
             last_stamp_point: None,
             point_history: Vec::with_capacity(4),
             is_stroke_start: true,
+            last_input_point: None,
+            last_timestamp_ms: None,
+            buildup_elapsed_ms: 0.0,
         }
     }
 
     /// Update configuration
     pub fn set_config(&mut self, config: StamperConfig) {
+        self.stabilizer.set_mode(config.stabilizer);
         self.config = config;
     }
 
@@ -138,13 +170,30 @@ impl BrushStamper {
         self.last_stamp_point = None;
         self.point_history.clear();
         self.is_stroke_start = true;
+        self.last_input_point = None;
+        self.last_timestamp_ms = None;
+        self.buildup_elapsed_ms = 0.0;
+        self.stabilizer.begin_stroke();
     }
 
     /// Process a new input point and return dabs to render
     pub fn process_point(&mut self, point: &RawInputPoint) -> Vec<Dab> {
+        let stabilized = self.stabilizer.process(point);
+        self.process_stabilized(&stabilized)
+    }
+
+    /// Place dabs along the path up to an already stabilized point
+    fn process_stabilized(&mut self, point: &RawInputPoint) -> Vec<Dab> {
         let path_point = PathPoint::from_raw(point);
         let mut dabs = Vec::new();
 
+        let elapsed_ms = self
+            .last_timestamp_ms
+            .map(|last| point.timestamp_ms.saturating_sub(last) as f32)
+            .unwrap_or(0.0);
+        self.last_timestamp_ms = Some(point.timestamp_ms);
+        self.last_input_point = Some(path_point);
+
         // Add to history for interpolation
         self.point_history.push(path_point);
         if self.point_history.len() > 4 {
@@ -204,13 +253,58 @@ impl BrushStamper {
             self.last_stamp_point = Some(path_point);
         }
 
+        if dabs.is_empty() {
+            dabs.extend(self.tick_buildup(elapsed_ms));
+        } else {
+            self.buildup_elapsed_ms = 0.0;
+        }
+
+        dabs
+    }
+
+    /// Advance the Build-up timer while the pen is stationary
+    ///
+    /// Call this from a frame timer when no input arrives (some tablet drivers
+    /// stop sending packets while the pen does not move). Returns the build-up
+    /// dabs due at the last input position.
+    pub fn tick_buildup(&mut self, elapsed_ms: f32) -> Vec<Dab> {
+        let mut dabs = Vec::new();
+        if !self.config.buildup || self.config.buildup_rate <= 0.0 {
+            return dabs;
+        }
+        let Some(point) = self.last_input_point else {
+            return dabs;
+        };
+
+        let interval_ms = 1000.0 / self.config.buildup_rate;
+        self.buildup_elapsed_ms += elapsed_ms.max(0.0);
+
+        while self.buildup_elapsed_ms >= interval_ms {
+            self.buildup_elapsed_ms -= interval_ms;
+            dabs.push(self.create_dab(&point));
+            if dabs.len() >= MAX_BUILDUP_DABS_PER_CALL {
+                self.buildup_elapsed_ms = 0.0;
+                break;
+            }
+        }
+
+        if !dabs.is_empty() {
+            self.last_stamp_point = Some(point);
+            self.accumulated_distance = 0.0;
+        }
+
         dabs
     }
 
     /// Finish stroke and return any remaining dabs
+    ///
+    /// With an averaging stabilizer, this drags the stroke out to the final
+    /// pen position.
     pub fn finish_stroke(&mut self) -> Vec<Dab> {
-        // Could emit a final dab at the exact end point if needed
-        let dabs = Vec::new();
+        let mut dabs = Vec::new();
+        for point in self.stabilizer.finish() {
+            dabs.extend(self.process_stabilized(&point));
+        }
         self.begin_stroke();
         dabs
     }
@@ -458,6 +552,189 @@ mod tests {
         assert!((dabs[0].alpha - 0.5).abs() < 0.01);
     }
 
+    fn make_timed_point(x: f32, y: f32, timestamp_ms: u64) -> RawInputPoint {
+        RawInputPoint {
+            timestamp_ms,
+            ..RawInputPoint::new(x, y, 1.0)
+        }
+    }
+
+    #[test]
+    fn test_buildup_emits_while_stationary() {
+        let mut stamper = BrushStamper::new(StamperConfig {
+            buildup: true,
+            buildup_rate: 10.0, // one dab per 100ms
+            ..Default::default()
+        });
+        stamper.begin_stroke();
+
+        assert_eq!(
+            stamper
+                .process_point(&make_timed_point(50.0, 50.0, 0))
+                .len(),
+            1
+        );
+        assert!(stamper
+            .process_point(&make_timed_point(50.0, 50.0, 50))
+            .is_empty());
+
+        let dabs = stamper.process_point(&make_timed_point(50.0, 50.0, 350));
+        assert_eq!(dabs.len(), 3);
+        assert!(dabs.iter().all(|d| d.x == 50.0 && d.y == 50.0));
+    }
+
+    #[test]
+    fn test_buildup_disabled_emits_nothing_while_stationary() {
+        let mut stamper = BrushStamper::new(StamperConfig::default());
+        stamper.begin_stroke();
+
+        stamper.process_point(&make_timed_point(50.0, 50.0, 0));
+        assert!(stamper
+            .process_point(&make_timed_point(50.0, 50.0, 1000))
+            .is_empty());
+        assert!(stamper.tick_buildup(1000.0).is_empty());
+    }
+
+    #[test]
+    fn test_buildup_tick_without_input() {
+        let mut stamper = BrushStamper::new(StamperConfig {
+            buildup: true,
+            buildup_rate: 5.0, // one dab per 200ms
+            ..Default::default()
+        });
+        stamper.begin_stroke();
+
+        // No input yet: nothing to stamp
+        assert!(stamper.tick_buildup(1000.0).is_empty());
+
+        stamper.process_point(&make_timed_point(10.0, 20.0, 0));
+        assert!(stamper.tick_buildup(150.0).is_empty());
+        let dabs = stamper.tick_buildup(100.0);
+        assert_eq!(dabs.len(), 1);
+        assert_eq!((dabs[0].x, dabs[0].y), (10.0, 20.0));
+
+        // Long stall is capped
+        assert_eq!(
+            stamper.tick_buildup(60_000.0).len(),
+            MAX_BUILDUP_DABS_PER_CALL
+        );
+    }
+
+    #[test]
+    fn test_buildup_resets_when_moving() {
+        let mut stamper = BrushStamper::new(StamperConfig {
+            buildup: true,
+            buildup_rate: 10.0,
+            ..Default::default()
+        });
+        stamper.begin_stroke();
+
+        stamper.process_point(&make_timed_point(0.0, 0.0, 0));
+        stamper.process_point(&make_timed_point(0.0, 0.0, 90));
+        // Movement emits spacing dabs and resets the build-up timer
+        let moving = stamper.process_point(&make_timed_point(50.0, 0.0, 95));
+        assert!(!moving.is_empty());
+        // 90ms were pending before the move; without the reset this would emit
+        assert!(stamper.tick_buildup(55.0).is_empty());
+    }
+
+    /// Horizontal line at y = 50 with deterministic +-3px tremor, sampled every 5ms
+    fn jittery_line() -> Vec<RawInputPoint> {
+        (0..100)
+            .map(|i| {
+                let tremor = ((i * 7919) % 13) as f32 / 12.0 * 6.0 - 3.0;
+                make_timed_point(i as f32 * 3.0, 50.0 + tremor, i as u64 * 5)
+            })
+            .collect()
+    }
+
+    fn stroke_dabs(stabilizer: StabilizerMode) -> Vec<Dab> {
+        let mut stamper = BrushStamper::new(StamperConfig {
+            size: 10.0,
+            stabilizer,
+            ..Default::default()
+        });
+        stamper.begin_stroke();
+        let mut dabs: Vec<Dab> = jittery_line()
+            .iter()
+            .flat_map(|p| stamper.process_point(p))
+            .collect();
+        dabs.extend(stamper.finish_stroke());
+        dabs
+    }
+
+    fn rms_deviation(dabs: &[Dab]) -> f32 {
+        let body = &dabs[10..dabs.len() - 10];
+        let sum: f32 = body.iter().map(|d| (d.y - 50.0).powi(2)).sum();
+        (sum / body.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_stabilizer_reduces_dab_jitter() {
+        let raw = rms_deviation(&stroke_dabs(StabilizerMode::Off));
+        for mode in [
+            StabilizerMode::WeightedAverage { samples: 8 },
+            StabilizerMode::PulledString { radius: 8.0 },
+            StabilizerMode::TimeBased { window_ms: 40.0 },
+        ] {
+            let smoothed = rms_deviation(&stroke_dabs(mode));
+            assert!(
+                smoothed < raw * 0.5,
+                "{:?}: raw {} smoothed {}",
+                mode,
+                raw,
+                smoothed
+            );
+        }
+    }
+
+    #[test]
+    fn test_finish_stroke_drags_out_stabilized_tail() {
+        let mut stamper = BrushStamper::new(StamperConfig {
+            size: 10.0,
+            stabilizer: StabilizerMode::TimeBased { window_ms: 80.0 },
+            ..Default::default()
+        });
+        stamper.begin_stroke();
+        let mut last_x = 0.0;
+        for p in jittery_line() {
+            if let Some(d) = stamper.process_point(&p).last() {
+                last_x = d.x;
+            }
+        }
+
+        let tail = stamper.finish_stroke();
+        let Some(end) = tail.last() else {
+            panic!("stabilized stroke should emit tail dabs");
+        };
+        assert!(end.x > last_x + 5.0, "tail {} vs body {}", end.x, last_x);
+        // Deterministic: the same input yields the same dabs
+        let a = stroke_dabs(StabilizerMode::TimeBased { window_ms: 80.0 });
+        let b = stroke_dabs(StabilizerMode::TimeBased { window_ms: 80.0 });
+        assert_eq!(a.len(), b.len());
+        assert!(a.iter().zip(&b).all(|(a, b)| a.x == b.x && a.y == b.y));
+    }
+
+    #[test]
+    fn test_stabilized_tail_adds_no_buildup_dabs() {
+        let tail = |buildup: bool| {
+            let mut stamper = BrushStamper::new(StamperConfig {
+                size: 10.0,
+                buildup,
+                buildup_rate: 1000.0,
+                stabilizer: StabilizerMode::TimeBased { window_ms: 80.0 },
+                ..Default::default()
+            });
+            stamper.begin_stroke();
+            for p in jittery_line() {
+                stamper.process_point(&p);
+            }
+            stamper.finish_stroke().len()
+        };
+        // The tail happens at the last pen timestamp, so no build-up time passes
+        assert_eq!(tail(true), tail(false));
+    }
+
     #[test]
     fn test_begin_stroke_resets_state() {
         let mut stamper = BrushStamper::new(StamperConfig::default());
@@ -4,7 +4,13 @@
 //! - Flow controls individual dab opacity (accumulates within stroke)
 //! - Opacity acts as a ceiling (maximum alpha for the entire stroke)
 
-use super::blend::blend_normal_premul;
+use serde::{Deserialize, Serialize};
+
+use super::blend::{
+    blend_destination_out_premul, blend_destination_over_premul, blend_normal_premul,
+    blend_source_atop_premul,
+};
+use super::effects::{TipNoise, WetEdge};
 
 /// A simple rectangle for dirty region tracking
 #[derive(Debug, Clone, Copy, Default)]
@@ -63,6 +69,22 @@ impl Rect {
     }
 }
 
+/// How the finished stroke is composited onto the layer
+///
+/// All modes use the stroke alpha after Flow accumulation and the Opacity
+/// ceiling, so erasing behaves exactly like painting with a different operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StrokeCompositeMode {
+    /// Paint over the layer (source-over)
+    #[default]
+    Paint,
+    /// Remove layer alpha (destination-out)
+    Erase,
+    /// Paint only where the layer is transparent (destination-over)
+    Behind,
+}
+
 /// RGBA pixel in premultiplied alpha format
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Pixel {
@@ -134,6 +156,14 @@ pub struct StrokeBuffer {
     dirty_rect: Rect,
     /// Whether a stroke is currently active
     active: bool,
+    /// Wet Edges remap applied when compositing the stroke
+    wet_edge: Option<WetEdge>,
+    /// Grain applied to the soft edge of each dab
+    tip_noise: Option<TipNoise>,
+    /// Operator used to composite the stroke onto the layer
+    composite_mode: StrokeCompositeMode,
+    /// Lock transparent pixels: layer alpha is never changed
+    preserve_alpha: bool,
 }
 
 impl StrokeBuffer {
@@ -146,6 +176,10 @@ impl StrokeBuffer {
             data: vec![Pixel::transparent(); size],
             dirty_rect: Rect::empty(),
             active: false,
+            wet_edge: None,
+            tip_noise: None,
+            composite_mode: StrokeCompositeMode::Paint,
+            preserve_alpha: false,
         }
     }
 
@@ -178,6 +212,39 @@ impl StrokeBuffer {
         self.active
     }
 
+    /// Set Wet Edges for subsequent strokes (None = off)
+    pub fn set_wet_edge(&mut self, wet_edge: Option<WetEdge>) {
+        self.wet_edge = wet_edge.filter(|w| w.is_active());
+    }
+
+    /// Set tip Noise for subsequent dabs (None = off)
+    pub fn set_tip_noise(&mut self, tip_noise: Option<TipNoise>) {
+        self.tip_noise = tip_noise;
+    }
+
+    /// Set how subsequent strokes are composited onto the layer
+    pub fn set_composite_mode(&mut self, mode: StrokeCompositeMode) {
+        self.composite_mode = mode;
+    }
+
+    /// Get the current composite mode
+    pub fn composite_mode(&self) -> StrokeCompositeMode {
+        self.composite_mode
+    }
+
+    /// Lock transparent pixels (alpha preserve) for subsequent strokes
+    ///
+    /// Painting only recolors existing pixels; Erase and Behind change alpha
+    /// by definition, so they leave the layer untouched while locked.
+    pub fn set_preserve_alpha(&mut self, preserve_alpha: bool) {
+        self.preserve_alpha = preserve_alpha;
+    }
+
+    /// Check if transparent pixels are locked
+    pub fn preserve_alpha(&self) -> bool {
+        self.preserve_alpha
+    }
+
     /// Get pixel at coordinates
     pub fn get_pixel(&self, x: u32, y: u32) -> Pixel {
         if x >= self.width || y >= self.height {
@@ -258,14 +325,20 @@ impl StrokeBuffer {
                 }
 
                 // Calculate falloff
-                let dab_alpha = if dist <= inner_radius {
-                    alpha
+                let mut mask = if dist <= inner_radius {
+                    1.0
                 } else if fade_width > 0.001 {
-                    alpha * (1.0 - (dist - inner_radius) / fade_width)
+                    1.0 - (dist - inner_radius) / fade_width
                 } else {
-                    alpha
+                    1.0
                 };
 
+                if let Some(noise) = &self.tip_noise {
+                    mask = noise.apply(mask, px, py);
+                }
+
+                let dab_alpha = alpha * mask;
+
                 if dab_alpha < 0.001 {
                     continue;
                 }
@@ -283,8 +356,103 @@ impl StrokeBuffer {
         }
     }
 
+    /// Sample the average color under a dab from the layer with the current
+    /// stroke composited over it (premultiplied)
+    ///
+    /// Pixels are weighted by the same hardness falloff `stamp_dab` uses, so
+    /// the sample matches what the dab covers. Pixels outside the buffer count
+    /// as transparent.
+    ///
+    /// # Arguments
+    /// * `layer_data` - Target layer RGBA data (straight alpha, u8)
+    /// * `cx`, `cy` - Center position
+    /// * `radius` - Dab radius in pixels
+    /// * `hardness` - Edge hardness (0 = soft, 1 = hard)
+    pub fn sample_composite(
+        &self,
+        layer_data: &[u8],
+        cx: f32,
+        cy: f32,
+        radius: f32,
+        hardness: f32,
+    ) -> Pixel {
+        let r = radius.max(0.5);
+        let left = (cx - r).floor() as i32;
+        let top = (cy - r).floor() as i32;
+        let right = (cx + r).ceil() as i32;
+        let bottom = (cy + r).ceil() as i32;
+
+        let inner_radius = r * hardness;
+        let fade_width = r - inner_radius;
+
+        let mut sum = Pixel::transparent();
+        let mut weight_sum = 0.0f32;
+
+        for py in top..=bottom {
+            for px in left..=right {
+                let dx = px as f32 + 0.5 - cx;
+                let dy = py as f32 + 0.5 - cy;
+                let dist = (dx * dx + dy * dy).sqrt();
+
+                if dist > r {
+                    continue;
+                }
+
+                let weight = if dist <= inner_radius || fade_width <= 0.001 {
+                    1.0
+                } else {
+                    1.0 - (dist - inner_radius) / fade_width
+                };
+
+                if weight < 0.001 {
+                    continue;
+                }
+                weight_sum += weight;
+
+                if px < 0 || py < 0 || px >= self.width as i32 || py >= self.height as i32 {
+                    continue;
+                }
+
+                let idx = (py as u32 * self.width + px as u32) as usize;
+                let layer_idx = idx * 4;
+                let layer_pixel = if layer_idx + 3 < layer_data.len() {
+                    Pixel::from_rgba_u8(
+                        layer_data[layer_idx],
+                        layer_data[layer_idx + 1],
+                        layer_data[layer_idx + 2],
+                        layer_data[layer_idx + 3],
+                    )
+                } else {
+                    Pixel::transparent()
+                };
+                let stroke_pixel = self.data.get(idx).copied().unwrap_or_default();
+                let composite = blend_normal_premul(stroke_pixel, layer_pixel);
+
+                sum.r += composite.r * weight;
+                sum.g += composite.g * weight;
+                sum.b += composite.b * weight;
+                sum.a += composite.a * weight;
+            }
+        }
+
+        if weight_sum <= 0.0 {
+            return Pixel::transparent();
+        }
+
+        Pixel {
+            r: sum.r / weight_sum,
+            g: sum.g / weight_sum,
+            b: sum.b / weight_sum,
+            a: sum.a / weight_sum,
+        }
+    }
+
     /// End the stroke and composite to layer data with opacity ceiling
     ///
+    /// Wet Edges (if set) remaps the accumulated stroke alpha before the
+    /// opacity ceiling is applied. The result is composited with the current
+    /// `StrokeCompositeMode`, honoring alpha preserve.
+    ///
     /// # Arguments
     /// * `layer_data` - Target layer RGBA data (will be modified)
     /// * `opacity` - Maximum opacity (ceiling) for this stroke
@@ -298,6 +466,10 @@ impl StrokeBuffer {
 
         self.active = false;
 
+        if self.preserve_alpha && self.composite_mode != StrokeCompositeMode::Paint {
+            return Rect::empty();
+        }
+
         // Clamp dirty rect to buffer bounds
         let mut rect = self.dirty_rect;
         rect.clamp_to(self.width as i32, self.height as i32);
@@ -316,8 +488,13 @@ impl StrokeBuffer {
                     continue;
                 }
 
+                let stroke_alpha = match &self.wet_edge {
+                    Some(wet_edge) => wet_edge.apply(stroke_pixel.a),
+                    None => stroke_pixel.a,
+                };
+
                 // Apply opacity ceiling
-                let clamped_alpha = stroke_pixel.a.min(opacity);
+                let clamped_alpha = stroke_alpha.min(opacity);
                 let clamped_pixel = stroke_pixel.with_alpha(clamped_alpha);
 
                 // Get layer pixel
@@ -334,7 +511,20 @@ impl StrokeBuffer {
                 );
 
                 // Blend stroke onto layer
-                let result = blend_normal_premul(clamped_pixel, layer_pixel);
+                let result = match (self.composite_mode, self.preserve_alpha) {
+                    (StrokeCompositeMode::Paint, false) => {
+                        blend_normal_premul(clamped_pixel, layer_pixel)
+                    }
+                    (StrokeCompositeMode::Paint, true) => {
+                        blend_source_atop_premul(clamped_pixel, layer_pixel)
+                    }
+                    (StrokeCompositeMode::Erase, _) => {
+                        blend_destination_out_premul(clamped_pixel, layer_pixel)
+                    }
+                    (StrokeCompositeMode::Behind, _) => {
+                        blend_destination_over_premul(clamped_pixel, layer_pixel)
+                    }
+                };
                 let rgba = result.to_rgba_u8();
 
                 layer_data[layer_idx] = rgba[0];
@@ -432,6 +622,188 @@ mod tests {
         assert!(center.a > 0.5);
     }
 
+    #[test]
+    fn test_wet_edge_darkens_rim() {
+        let composite = |wet_edge: Option<WetEdge>| {
+            let mut buffer = StrokeBuffer::new(40, 40);
+            buffer.set_wet_edge(wet_edge);
+            buffer.begin_stroke();
+            buffer.stamp_dab(20.0, 20.0, 12.0, [0.0, 0.0, 0.0], 1.0, 0.0);
+            let mut layer_data = vec![0u8; 40 * 40 * 4];
+            buffer.end_stroke(&mut layer_data, 1.0);
+            layer_data
+        };
+
+        let plain = composite(None);
+        let wet = composite(Some(WetEdge::new(1.0, 0.0)));
+
+        let center = (20 * 40 + 20) * 4 + 3;
+        let rim = (20 * 40 + 29) * 4 + 3;
+
+        // Center fades, rim gets relatively stronger than the center
+        assert!(wet[center] < plain[center]);
+        assert!(wet[rim] >= plain[rim]);
+        assert!(wet[rim] as f32 / wet[center] as f32 > plain[rim] as f32 / plain[center] as f32);
+    }
+
+    #[test]
+    fn test_tip_noise_is_seeded() {
+        let stamp = |seed: u32| {
+            let mut buffer = StrokeBuffer::new(32, 32);
+            buffer.set_tip_noise(Some(TipNoise::new(seed, 1.0)));
+            buffer.begin_stroke();
+            buffer.stamp_dab(16.0, 16.0, 12.0, [1.0, 1.0, 1.0], 1.0, 0.0);
+            (0..32)
+                .map(|x| buffer.get_pixel(x, 16).a)
+                .collect::<Vec<f32>>()
+        };
+
+        assert_eq!(stamp(42), stamp(42));
+        assert_ne!(stamp(42), stamp(43));
+
+        // Hard core is untouched by noise
+        let mut buffer = StrokeBuffer::new(32, 32);
+        buffer.set_tip_noise(Some(TipNoise::new(42, 1.0)));
+        buffer.begin_stroke();
+        buffer.stamp_dab(16.0, 16.0, 12.0, [1.0, 1.0, 1.0], 1.0, 0.5);
+        assert!(buffer.get_pixel(16, 16).a > 0.99);
+    }
+
+    fn opaque_layer(width: usize, height: usize, rgba: [u8; 4]) -> Vec<u8> {
+        let mut layer_data = vec![0u8; width * height * 4];
+        for px in layer_data.chunks_exact_mut(4) {
+            px.copy_from_slice(&rgba);
+        }
+        layer_data
+    }
+
+    #[test]
+    fn test_erase_honors_opacity_ceiling() {
+        let mut buffer = StrokeBuffer::new(10, 10);
+        buffer.set_composite_mode(StrokeCompositeMode::Erase);
+        buffer.begin_stroke();
+        buffer.stamp_dab(5.0, 5.0, 3.0, [0.0, 0.0, 0.0], 1.0, 1.0);
+
+        let mut layer_data = opaque_layer(10, 10, [0, 255, 0, 255]);
+        buffer.end_stroke(&mut layer_data, 0.5);
+
+        // Half of the alpha removed, color kept
+        let idx = (5 * 10 + 5) * 4;
+        let alpha = layer_data[idx + 3] as f32 / 255.0;
+        assert!((alpha - 0.5).abs() < 0.02);
+        assert!(layer_data[idx + 1] > 250);
+
+        // Outside the dab is untouched
+        assert_eq!(layer_data[3], 255);
+    }
+
+    #[test]
+    fn test_erase_flow_accumulates() {
+        let erase_with = |dabs: usize| {
+            let mut buffer = StrokeBuffer::new(20, 20);
+            buffer.set_composite_mode(StrokeCompositeMode::Erase);
+            buffer.begin_stroke();
+            for _ in 0..dabs {
+                buffer.stamp_dab(10.0, 10.0, 5.0, [0.0, 0.0, 0.0], 0.2, 1.0);
+            }
+            let mut layer_data = opaque_layer(20, 20, [255, 255, 255, 255]);
+            buffer.end_stroke(&mut layer_data, 1.0);
+            layer_data[(10 * 20 + 10) * 4 + 3]
+        };
+
+        let one = erase_with(1);
+        let five = erase_with(5);
+        assert!(one > 190 && one < 215);
+        assert!(five < 100);
+    }
+
+    #[test]
+    fn test_preserve_alpha_only_recolors() {
+        let mut buffer = StrokeBuffer::new(10, 10);
+        buffer.set_preserve_alpha(true);
+        buffer.begin_stroke();
+        buffer.stamp_dab(5.0, 5.0, 4.0, [1.0, 0.0, 0.0], 1.0, 1.0);
+
+        // Left half opaque green, right half transparent
+        let mut layer_data = vec![0u8; 10 * 10 * 4];
+        for y in 0..10 {
+            for x in 0..5 {
+                let idx = (y * 10 + x) * 4;
+                layer_data[idx..idx + 4].copy_from_slice(&[0, 255, 0, 255]);
+            }
+        }
+        buffer.end_stroke(&mut layer_data, 1.0);
+
+        let left = (5 * 10 + 4) * 4;
+        assert_eq!(layer_data[left + 3], 255);
+        assert!(layer_data[left] > 250 && layer_data[left + 1] < 5);
+
+        let right = (5 * 10 + 6) * 4;
+        assert_eq!(layer_data[right + 3], 0);
+    }
+
+    #[test]
+    fn test_preserve_alpha_blocks_erase() {
+        let mut buffer = StrokeBuffer::new(10, 10);
+        buffer.set_composite_mode(StrokeCompositeMode::Erase);
+        buffer.set_preserve_alpha(true);
+        buffer.begin_stroke();
+        buffer.stamp_dab(5.0, 5.0, 3.0, [0.0, 0.0, 0.0], 1.0, 1.0);
+
+        let mut layer_data = opaque_layer(10, 10, [0, 255, 0, 255]);
+        let original = layer_data.clone();
+        let rect = buffer.end_stroke(&mut layer_data, 1.0);
+
+        assert!(rect.is_empty());
+        assert_eq!(layer_data, original);
+        assert!(!buffer.is_active());
+    }
+
+    #[test]
+    fn test_behind_fills_only_transparent() {
+        let mut buffer = StrokeBuffer::new(10, 10);
+        buffer.set_composite_mode(StrokeCompositeMode::Behind);
+        buffer.begin_stroke();
+        buffer.stamp_dab(5.0, 5.0, 4.0, [1.0, 0.0, 0.0], 1.0, 1.0);
+
+        let mut layer_data = vec![0u8; 10 * 10 * 4];
+        for y in 0..10 {
+            for x in 0..5 {
+                let idx = (y * 10 + x) * 4;
+                layer_data[idx..idx + 4].copy_from_slice(&[0, 255, 0, 255]);
+            }
+        }
+        buffer.end_stroke(&mut layer_data, 1.0);
+
+        // Opaque pixel keeps its color
+        let left = (5 * 10 + 4) * 4;
+        assert_eq!(&layer_data[left..left + 4], &[0, 255, 0, 255]);
+
+        // Transparent pixel gets the stroke
+        let right = (5 * 10 + 6) * 4;
+        assert!(layer_data[right] > 250 && layer_data[right + 3] > 250);
+    }
+
+    #[test]
+    fn test_sample_composite_sees_layer_and_stroke() {
+        // Opaque red layer
+        let layer_data = opaque_layer(20, 20, [255, 0, 0, 255]);
+
+        let mut buffer = StrokeBuffer::new(20, 20);
+        buffer.begin_stroke();
+        let sample = buffer.sample_composite(&layer_data, 10.0, 10.0, 4.0, 1.0);
+        assert!(sample.r > 0.99 && sample.b < 0.01 && sample.a > 0.99);
+
+        // Opaque blue stroke over it wins
+        buffer.stamp_dab(10.0, 10.0, 8.0, [0.0, 0.0, 1.0], 1.0, 1.0);
+        let sample = buffer.sample_composite(&layer_data, 10.0, 10.0, 4.0, 1.0);
+        assert!(sample.b > 0.99 && sample.r < 0.01);
+
+        // Off-canvas half counts as transparent
+        let sample = buffer.sample_composite(&layer_data, 0.0, 0.0, 4.0, 1.0);
+        assert!(sample.a < 0.5);
+    }
+
     #[test]
     fn test_rect_operations() {
         let mut rect = Rect::empty();
@@ -0,0 +1,1242 @@
+//! Folder-based sync for the brush and pattern libraries.
+//!
+//! A replica folder (e.g. on a NAS or a synced drive) holds a manifest plus
+//! content-addressed blobs:
+//!
+//! ```text
+//! <folder>/sutu-library/manifest.json
+//! <folder>/sutu-library/tips/<content-hash>.bin
+//! <folder>/sutu-library/patterns/<content-hash>.bin
+//! ```
+//!
+//! Tips and patterns are matched by content hash, so machines that imported the
+//! same files under different IDs share one copy. Presets are merged per ID
+//! against the state of the last sync (kept in `sync_state.json` next to the
+//! brush index): a side that did not change takes the other side's version; when
+//! both changed, the later edit wins and the other version is kept as a conflict
+//! copy on both machines. Usage stats stay local.
+//!
+//! A sync holds `manifest.lock` while it runs, only writes over the manifest
+//! generation it read and changes the local libraries only after that write. A
+//! manifest that cannot be parsed is never replaced, and a
+//! recreated manifest (new `replicaId`) is merged as a first sync, so a lost
+//! manifest never turns into local deletions.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::cache::{blob_path, get_brush_cache_dir, is_safe_blob_id};
+use super::index_file::{self, IndexLoad};
+use super::library::{self as brush_library, BrushLibrary, BrushLibraryPreset, BrushTipResource};
+use super::pattern_cache::get_pattern_cache_dir;
+use crate::app_meta::APP_STORAGE_PREFIX;
+use crate::input::current_time_ms;
+use crate::pattern::library::{self as pattern_library, PatternLibrary};
+use crate::pattern::types::PatternResource;
+
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+const MANIFEST_SCHEMA_VERSION: u32 = 1;
+const LOCK_FILE_NAME: &str = "manifest.lock";
+/// A lock this old was left behind by a sync that crashed
+const STALE_LOCK_AGE: Duration = Duration::from_secs(10 * 60);
+const STATE_FILE_NAME: &str = "sync_state.json";
+const STATE_SCHEMA_VERSION: u32 = 1;
+const TIPS_DIR: &str = "tips";
+const PATTERNS_DIR: &str = "patterns";
+
+/// Preset as stored in the replica. Tip and pattern references hold content
+/// hashes instead of machine-local IDs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplicaPreset {
+    pub entry: BrushLibraryPreset,
+    pub sync_hash: String,
+    pub modified_ms: u64,
+    /// Machine that wrote this version
+    pub origin: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplicaManifest {
+    /// Minted when the manifest is created; a new ID means the replica was
+    /// recreated and every machine merges with it as a first sync
+    #[serde(default)]
+    pub replica_id: String,
+    /// Bumped on every write
+    #[serde(default)]
+    pub generation: u64,
+    pub presets: HashMap<String, ReplicaPreset>,
+    /// Keyed by tip content hash
+    pub tips: HashMap<String, BrushTipResource>,
+    /// Keyed by pattern content hash
+    pub patterns: HashMap<String, PatternResource>,
+}
+
+/// What this machine saw at its last sync with `replica_dir`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibrarySyncState {
+    pub replica_dir: Option<PathBuf>,
+    pub last_synced_ms: Option<u64>,
+    /// `replicaId` of the manifest seen at the last sync
+    #[serde(default)]
+    replica_id: Option<String>,
+    /// Preset ID -> sync hash
+    #[serde(default)]
+    presets: HashMap<String, String>,
+    /// Pattern content hashes
+    #[serde(default)]
+    patterns: HashSet<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibrarySyncReport {
+    pub pulled_presets: usize,
+    pub pushed_presets: usize,
+    pub deleted_local_presets: usize,
+    pub deleted_remote_presets: usize,
+    /// Conflict copies created for presets edited on both sides
+    pub conflicts: usize,
+    pub pulled_tips: usize,
+    pub pushed_tips: usize,
+    pub pulled_patterns: usize,
+    pub pushed_patterns: usize,
+    pub deleted_local_patterns: usize,
+    pub deleted_remote_patterns: usize,
+}
+
+/// Where a sync reads and writes
+#[derive(Debug, Clone)]
+pub struct LibrarySyncEnv {
+    pub replica_dir: PathBuf,
+    pub brush_cache_dir: PathBuf,
+    pub pattern_cache_dir: PathBuf,
+    /// Name recorded on pushed presets and used in conflict copy names
+    pub machine: String,
+    pub now_ms: u64,
+}
+
+impl LibrarySyncEnv {
+    fn root(&self) -> PathBuf {
+        self.replica_dir
+            .join(format!("{}-library", APP_STORAGE_PREFIX))
+    }
+}
+
+/// Sync the global libraries with a replica folder
+pub fn sync_with_folder(replica_dir: &Path) -> Result<LibrarySyncReport, String> {
+    let env = LibrarySyncEnv {
+        replica_dir: replica_dir.to_path_buf(),
+        brush_cache_dir: get_brush_cache_dir(),
+        pattern_cache_dir: get_pattern_cache_dir(),
+        machine: machine_name(),
+        now_ms: current_time_ms(),
+    };
+    brush_library::with_library_write(|brushes| {
+        pattern_library::with_library_write(|patterns| sync_libraries(brushes, patterns, &env))
+    })
+}
+
+/// Sync state of the global brush library (folder and last sync time)
+pub fn get_sync_state() -> Result<LibrarySyncState, String> {
+    brush_library::with_library_read(|brushes| load_state(brushes.library_dir()))
+        .ok_or_else(|| "Brush library not initialized".to_string())?
+}
+
+pub fn sync_libraries(
+    brushes: &mut BrushLibrary,
+    patterns: &mut PatternLibrary,
+    env: &LibrarySyncEnv,
+) -> Result<LibrarySyncReport, String> {
+    let root = env.root();
+    std::fs::create_dir_all(root.join(TIPS_DIR))
+        .and_then(|_| std::fs::create_dir_all(root.join(PATTERNS_DIR)))
+        .map_err(|e| format!("Failed to prepare sync folder {}: {}", root.display(), e))?;
+
+    let _lock = ManifestLock::acquire(&root, env)?;
+    let mut state = load_state(brushes.library_dir())?;
+    let manifest_path = root.join(MANIFEST_FILE_NAME);
+    let loaded = read_manifest(&manifest_path)?;
+    let read_generation = loaded.as_ref().map(|manifest| manifest.generation);
+    let mut manifest = loaded.unwrap_or_default();
+    if manifest.replica_id.is_empty() {
+        manifest.replica_id = new_replica_id(env);
+    }
+    sanitize_manifest(&mut manifest);
+    // Against a new or recreated manifest, or a different folder, the last-sync
+    // state says nothing about the replica; merging as a first sync never deletes
+    if state.replica_id.as_deref() != Some(manifest.replica_id.as_str())
+        || state.replica_dir.as_deref() != Some(env.replica_dir.as_path())
+    {
+        state.presets.clear();
+        state.patterns.clear();
+    }
+    let manifest = &mut manifest;
+
+    // Local changes and replica blob deletions are staged until the manifest
+    // write is known to go through
+    let mut report = LibrarySyncReport::default();
+    let mut removed_blobs = Vec::new();
+    let pattern_changes = sync_patterns(
+        patterns,
+        manifest,
+        &mut state,
+        env,
+        &mut report,
+        &mut removed_blobs,
+    );
+
+    let snapshot = brushes.snapshot();
+    let mut pattern_ids = pattern_hash_map(patterns);
+    for pattern in &pattern_changes.added {
+        pattern_ids
+            .id_by_hash
+            .insert(pattern.content_hash.clone(), pattern.id.clone());
+    }
+    let mut tips = TipIndex::new(&snapshot.tips);
+    let new_tips = sync_tips(&snapshot.tips, &mut tips, manifest, env, &mut report);
+
+    let local: HashMap<String, (BrushLibraryPreset, String)> = snapshot
+        .presets
+        .iter()
+        .map(|entry| {
+            let portable = to_portable(entry, &tips.hash_by_id, &pattern_ids.hash_by_id);
+            let hash = sync_hash(&portable);
+            (entry.preset.id.clone(), (portable, hash))
+        })
+        .collect();
+    let merge = merge_presets(&local, manifest, &state.presets, env);
+    report.pulled_presets = merge.pull.len();
+    report.pushed_presets = merge.push.len();
+    report.deleted_local_presets = merge.delete_local.len();
+    report.deleted_remote_presets = merge.delete_remote.len();
+    report.conflicts = merge.conflicts;
+
+    for (id, replica) in merge.push {
+        manifest.presets.insert(id, replica);
+    }
+    for id in &merge.delete_remote {
+        manifest.presets.remove(id);
+    }
+    prune_replica_tips(manifest, &root, &mut removed_blobs);
+
+    let upserts: Vec<BrushLibraryPreset> = merge
+        .pull
+        .into_iter()
+        .map(|(id, portable)| {
+            from_portable(id, portable, &tips.id_by_hash, &pattern_ids.id_by_hash)
+        })
+        .collect();
+
+    // Another machine may have written the manifest despite the lock (e.g. on
+    // a drive that syncs files lazily); only write over the generation we read.
+    // Nothing local has changed yet, so a failed sync leaves the base intact.
+    let discard_pulled = || {
+        let pulled_tips = new_tips
+            .iter()
+            .filter_map(|tip| blob_path(&env.brush_cache_dir, &tip.tip.id));
+        let pulled_patterns = pattern_changes
+            .added
+            .iter()
+            .filter_map(|pattern| blob_path(&env.pattern_cache_dir, &pattern.id));
+        for blob in pulled_tips.chain(pulled_patterns) {
+            let _ = std::fs::remove_file(blob);
+        }
+    };
+    let current = match read_manifest(&manifest_path) {
+        Ok(current) => current.map(|manifest| manifest.generation),
+        Err(error) => {
+            discard_pulled();
+            return Err(error);
+        }
+    };
+    if current != read_generation {
+        discard_pulled();
+        return Err("Sync manifest changed during sync; sync again".to_string());
+    }
+    manifest.generation = read_generation.map_or(1, |generation| generation + 1);
+    if let Err(e) = index_file::save_index(&manifest_path, MANIFEST_SCHEMA_VERSION, manifest) {
+        discard_pulled();
+        return Err(format!("Failed to write sync manifest: {}", e));
+    }
+
+    patterns.apply_sync_changes(pattern_changes.added, &pattern_changes.deleted)?;
+    brushes.apply_sync_changes(new_tips, upserts, &merge.delete_local)?;
+    for blob in removed_blobs {
+        let _ = std::fs::remove_file(blob);
+    }
+
+    state.replica_dir = Some(env.replica_dir.clone());
+    state.replica_id = Some(manifest.replica_id.clone());
+    state.last_synced_ms = Some(env.now_ms);
+    state.presets = merge.base;
+    state.patterns = manifest.patterns.keys().cloned().collect();
+    index_file::save_index(
+        &brushes.library_dir().join(STATE_FILE_NAME),
+        STATE_SCHEMA_VERSION,
+        &state,
+    )
+    .map_err(|e| format!("Failed to write sync state: {}", e))?;
+
+    tracing::info!("Library sync with {:?}: {:?}", env.replica_dir, report);
+    Ok(report)
+}
+
+/// `None` when the replica has no manifest yet. An unreadable manifest is an
+/// error and is left untouched.
+fn read_manifest(path: &Path) -> Result<Option<ReplicaManifest>, String> {
+    match index_file::read_index::<ReplicaManifest>(path, MANIFEST_SCHEMA_VERSION, &[]) {
+        IndexLoad::Loaded { index, .. } => Ok(Some(index)),
+        IndexLoad::Missing => Ok(None),
+        IndexLoad::Corrupt { error, .. } | IndexLoad::Unavailable { error } => Err(format!(
+            "Sync manifest {} is unreadable ({}); fix or remove it to sync",
+            path.display(),
+            error
+        )),
+    }
+}
+
+/// Drop tips and patterns whose keys are not content hashes and replace IDs
+/// that are not plain tokens, since both end up in file names
+fn sanitize_manifest(manifest: &mut ReplicaManifest) {
+    manifest.tips.retain(|hash, tip| {
+        if !is_content_hash(hash) {
+            tracing::warn!("Ignoring replica tip with invalid hash {:?}", hash);
+            return false;
+        }
+        if !is_safe_blob_id(&tip.tip.id) {
+            tip.tip.id = hash.chars().take(16).collect();
+        }
+        true
+    });
+    manifest.patterns.retain(|hash, pattern| {
+        if !is_content_hash(hash) {
+            tracing::warn!("Ignoring replica pattern with invalid hash {:?}", hash);
+            return false;
+        }
+        if !is_safe_blob_id(&pattern.id) {
+            pattern.id = hash.chars().take(16).collect();
+        }
+        true
+    });
+}
+
+fn is_content_hash(hash: &str) -> bool {
+    !hash.is_empty() && hash.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Exclusive claim on a replica's manifest for the length of one sync
+struct ManifestLock {
+    path: PathBuf,
+}
+
+impl ManifestLock {
+    fn acquire(root: &Path, env: &LibrarySyncEnv) -> Result<Self, String> {
+        let path = root.join(LOCK_FILE_NAME);
+        for _ in 0..2 {
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(mut file) => {
+                    let _ = writeln!(file, "{} {}", env.machine, env.now_ms);
+                    return Ok(Self { path });
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let stale = std::fs::metadata(&path)
+                        .and_then(|meta| meta.modified())
+                        .ok()
+                        .and_then(|modified| modified.elapsed().ok())
+                        .is_some_and(|age| age > STALE_LOCK_AGE);
+                    if !stale {
+                        let holder = std::fs::read_to_string(&path).unwrap_or_default();
+                        return Err(format!(
+                            "Sync folder {} is in use by another sync ({}); try again later",
+                            root.display(),
+                            holder.trim()
+                        ));
+                    }
+                    tracing::warn!("Removing stale sync lock {}", path.display());
+                    let _ = std::fs::remove_file(&path);
+                }
+                Err(err) => {
+                    return Err(format!(
+                        "Failed to lock sync folder {}: {}",
+                        root.display(),
+                        err
+                    ))
+                }
+            }
+        }
+        Err(format!("Failed to lock sync folder {}", root.display()))
+    }
+}
+
+impl Drop for ManifestLock {
+    fn drop(&mut self) {
+        if let Err(err) = std::fs::remove_file(&self.path) {
+            tracing::warn!("Failed to release {}: {}", self.path.display(), err);
+        }
+    }
+}
+
+fn new_replica_id(env: &LibrarySyncEnv) -> String {
+    let seed = format!(
+        "{}-{}-{:?}-{}",
+        env.machine,
+        env.now_ms,
+        std::time::SystemTime::now(),
+        std::process::id()
+    );
+    hex::encode(Sha256::digest(seed.as_bytes()))[..16].to_string()
+}
+
+fn load_state(library_dir: &Path) -> Result<LibrarySyncState, String> {
+    match index_file::load_index(
+        &library_dir.join(STATE_FILE_NAME),
+        STATE_SCHEMA_VERSION,
+        &[],
+    ) {
+        IndexLoad::Loaded { index, .. } => Ok(index),
+        IndexLoad::Missing | IndexLoad::Corrupt { .. } => Ok(LibrarySyncState::default()),
+        IndexLoad::Unavailable { error } => Err(format!("Sync state is unavailable: {}", error)),
+    }
+}
+
+/// Pattern changes for the local library, applied once the manifest is written
+struct PatternChanges {
+    added: Vec<PatternResource>,
+    /// Local pattern IDs
+    deleted: Vec<String>,
+}
+
+/// Patterns are immutable content: additions and deletions propagate, names and
+/// groups follow whichever copy arrived first. Replica blobs of patterns deleted
+/// here are added to `removed_blobs`.
+fn sync_patterns(
+    patterns: &PatternLibrary,
+    manifest: &mut ReplicaManifest,
+    state: &mut LibrarySyncState,
+    env: &LibrarySyncEnv,
+    report: &mut LibrarySyncReport,
+    removed_blobs: &mut Vec<PathBuf>,
+) -> PatternChanges {
+    let blob_dir = env.root().join(PATTERNS_DIR);
+    let local: HashMap<String, PatternResource> = patterns
+        .get_all_patterns()
+        .into_iter()
+        .map(|pattern| (pattern.content_hash.clone(), pattern))
+        .collect();
+    let mut taken: HashSet<String> = local.values().map(|p| p.id.clone()).collect();
+
+    let hashes: BTreeSet<String> = local
+        .keys()
+        .chain(manifest.patterns.keys())
+        .cloned()
+        .collect();
+    let mut added = Vec::new();
+    let mut deleted = Vec::new();
+    for hash in hashes {
+        let in_base = state.patterns.contains(&hash);
+        match (local.get(&hash), manifest.patterns.get(&hash).cloned()) {
+            (Some(_), Some(_)) | (None, None) => {}
+            (Some(pattern), None) if in_base => {
+                deleted.push(pattern.id.clone());
+                report.deleted_local_patterns += 1;
+            }
+            (Some(pattern), None) => {
+                let (Some(source), Some(target)) = (
+                    blob_path(&env.pattern_cache_dir, &pattern.id),
+                    replica_blob_path(&blob_dir, &hash),
+                ) else {
+                    continue;
+                };
+                if copy_blob(&source, &target) {
+                    // Usage stays local, like preset usage stats
+                    let mut pattern = pattern.clone();
+                    pattern.last_used_ms = None;
+                    manifest.patterns.insert(hash, pattern);
+                    report.pushed_patterns += 1;
+                }
+            }
+            (None, Some(_)) if in_base => {
+                manifest.patterns.remove(&hash);
+                removed_blobs.extend(replica_blob_path(&blob_dir, &hash));
+                report.deleted_remote_patterns += 1;
+            }
+            (None, Some(mut pattern)) => {
+                let id = unique_id(&pattern.id, &taken, &env.pattern_cache_dir);
+                let (Some(source), Some(target)) = (
+                    replica_blob_path(&blob_dir, &hash),
+                    blob_path(&env.pattern_cache_dir, &id),
+                ) else {
+                    continue;
+                };
+                if copy_blob(&source, &target) {
+                    taken.insert(id.clone());
+                    pattern.id = id;
+                    added.push(pattern);
+                    report.pulled_patterns += 1;
+                }
+            }
+        }
+    }
+    PatternChanges { added, deleted }
+}
+
+struct HashMaps {
+    hash_by_id: HashMap<String, String>,
+    id_by_hash: HashMap<String, String>,
+}
+
+fn pattern_hash_map(patterns: &PatternLibrary) -> HashMaps {
+    let mut maps = HashMaps {
+        hash_by_id: HashMap::new(),
+        id_by_hash: HashMap::new(),
+    };
+    for pattern in patterns.get_all_patterns() {
+        maps.hash_by_id
+            .insert(pattern.id.clone(), pattern.content_hash.clone());
+        maps.id_by_hash.insert(pattern.content_hash, pattern.id);
+    }
+    maps
+}
+
+struct TipIndex {
+    hash_by_id: HashMap<String, String>,
+    id_by_hash: HashMap<String, String>,
+}
+
+impl TipIndex {
+    fn new(tips: &[BrushTipResource]) -> Self {
+        let mut index = Self {
+            hash_by_id: HashMap::new(),
+            id_by_hash: HashMap::new(),
+        };
+        for tip in tips {
+            index.insert(&tip.tip.id, &tip.content_hash);
+        }
+        index
+    }
+
+    fn insert(&mut self, id: &str, hash: &str) {
+        self.hash_by_id.insert(id.to_string(), hash.to_string());
+        self.id_by_hash
+            .entry(hash.to_string())
+            .or_insert_with(|| id.to_string());
+    }
+}
+
+/// Push tips the replica lacks and pull tips this machine lacks. Returns the
+/// pulled tips (with local IDs) for the brush library.
+fn sync_tips(
+    local_tips: &[BrushTipResource],
+    tips: &mut TipIndex,
+    manifest: &mut ReplicaManifest,
+    env: &LibrarySyncEnv,
+    report: &mut LibrarySyncReport,
+) -> Vec<BrushTipResource> {
+    let blob_dir = env.root().join(TIPS_DIR);
+
+    for tip in local_tips {
+        if manifest.tips.contains_key(&tip.content_hash) {
+            continue;
+        }
+        let (Some(source), Some(target)) = (
+            blob_path(&env.brush_cache_dir, &tip.tip.id),
+            replica_blob_path(&blob_dir, &tip.content_hash),
+        ) else {
+            continue;
+        };
+        if copy_blob(&source, &target) {
+            manifest.tips.insert(tip.content_hash.clone(), tip.clone());
+            report.pushed_tips += 1;
+        }
+    }
+
+    let mut pulled = Vec::new();
+    let mut taken: HashSet<String> = tips.hash_by_id.keys().cloned().collect();
+    for (hash, tip) in &manifest.tips {
+        if tips.id_by_hash.contains_key(hash) {
+            continue;
+        }
+        let id = unique_id(&tip.tip.id, &taken, &env.brush_cache_dir);
+        let (Some(source), Some(target)) = (
+            replica_blob_path(&blob_dir, hash),
+            blob_path(&env.brush_cache_dir, &id),
+        ) else {
+            continue;
+        };
+        if copy_blob(&source, &target) {
+            let mut tip = tip.clone();
+            tip.tip.id = id.clone();
+            taken.insert(id.clone());
+            tips.insert(&id, hash);
+            pulled.push(tip);
+            report.pulled_tips += 1;
+        }
+    }
+    pulled
+}
+
+/// Drop replica tips no replica preset references; their blobs are added to
+/// `removed_blobs`
+fn prune_replica_tips(
+    manifest: &mut ReplicaManifest,
+    root: &Path,
+    removed_blobs: &mut Vec<PathBuf>,
+) {
+    let referenced: HashSet<String> = manifest
+        .presets
+        .values()
+        .flat_map(|replica| {
+            let dual = replica
+                .entry
+                .preset
+                .dual_brush_settings
+                .as_ref()
+                .and_then(|dual| dual.brush_id.clone());
+            replica.entry.tip_id.clone().into_iter().chain(dual)
+        })
+        .collect();
+    manifest.tips.retain(|hash, _| {
+        let keep = referenced.contains(hash);
+        if !keep {
+            removed_blobs.extend(replica_blob_path(&root.join(TIPS_DIR), hash));
+        }
+        keep
+    });
+}
+
+struct PresetMerge {
+    pull: Vec<(String, BrushLibraryPreset)>,
+    push: Vec<(String, ReplicaPreset)>,
+    delete_local: Vec<String>,
+    delete_remote: Vec<String>,
+    conflicts: usize,
+    /// Sync hashes both sides agree on after the merge
+    base: HashMap<String, String>,
+}
+
+fn merge_presets(
+    local: &HashMap<String, (BrushLibraryPreset, String)>,
+    manifest: &ReplicaManifest,
+    base: &HashMap<String, String>,
+    env: &LibrarySyncEnv,
+) -> PresetMerge {
+    let mut merge = PresetMerge {
+        pull: Vec::new(),
+        push: Vec::new(),
+        delete_local: Vec::new(),
+        delete_remote: Vec::new(),
+        conflicts: 0,
+        base: HashMap::new(),
+    };
+    let local_hashes: HashSet<&str> = local.values().map(|(_, hash)| hash.as_str()).collect();
+    let remote_hashes: HashSet<&str> = manifest
+        .presets
+        .values()
+        .map(|replica| replica.sync_hash.as_str())
+        .collect();
+
+    let ids: BTreeSet<&String> = local
+        .keys()
+        .chain(manifest.presets.keys())
+        .chain(base.keys())
+        .collect();
+    for id in ids {
+        let local_entry = local.get(id);
+        let remote = manifest.presets.get(id);
+        let l = local_entry.map(|(_, hash)| hash);
+        let r = remote.map(|replica| &replica.sync_hash);
+        let b = base.get(id);
+
+        if l == r {
+            if let Some(hash) = l {
+                merge.base.insert(id.clone(), hash.clone());
+            }
+            continue;
+        }
+
+        let push = |(portable, hash): &(BrushLibraryPreset, String)| ReplicaPreset {
+            entry: portable.clone(),
+            sync_hash: hash.clone(),
+            modified_ms: portable.modified_ms.unwrap_or(0),
+            origin: env.machine.clone(),
+        };
+
+        if l == b {
+            // Only the replica changed
+            match remote {
+                Some(replica) => {
+                    // Same preset already here under another ID
+                    if b.is_none() && local_hashes.contains(replica.sync_hash.as_str()) {
+                        continue;
+                    }
+                    merge.pull.push((id.clone(), replica.entry.clone()));
+                    merge.base.insert(id.clone(), replica.sync_hash.clone());
+                }
+                None => merge.delete_local.push(id.clone()),
+            }
+        } else if r == b {
+            // Only this machine changed
+            match local_entry {
+                Some(entry) => {
+                    if b.is_none() && remote_hashes.contains(entry.1.as_str()) {
+                        continue;
+                    }
+                    merge.push.push((id.clone(), push(entry)));
+                    merge.base.insert(id.clone(), entry.1.clone());
+                }
+                None => merge.delete_remote.push(id.clone()),
+            }
+        } else {
+            match (local_entry, remote) {
+                // An edit beats a deletion on the other side
+                (None, Some(replica)) => {
+                    merge.pull.push((id.clone(), replica.entry.clone()));
+                    merge.base.insert(id.clone(), replica.sync_hash.clone());
+                }
+                (Some(entry), None) => {
+                    merge.push.push((id.clone(), push(entry)));
+                    merge.base.insert(id.clone(), entry.1.clone());
+                }
+                (Some(entry), Some(replica)) => {
+                    let ours = push(entry);
+                    let local_wins = (ours.modified_ms, &ours.sync_hash)
+                        >= (replica.modified_ms, &replica.sync_hash);
+                    let (winner, loser) = if local_wins {
+                        (ours, replica.clone())
+                    } else {
+                        (replica.clone(), ours)
+                    };
+                    let copy = conflict_copy(id, &loser);
+
+                    if local_wins {
+                        merge.push.push((id.clone(), winner.clone()));
+                    } else {
+                        merge.pull.push((id.clone(), winner.entry.clone()));
+                    }
+                    merge.base.insert(id.clone(), winner.sync_hash.clone());
+
+                    let copy_id = copy.entry.preset.id.clone();
+                    merge.pull.push((copy_id.clone(), copy.entry.clone()));
+                    merge.base.insert(copy_id.clone(), copy.sync_hash.clone());
+                    merge.push.push((copy_id, copy));
+                    merge.conflicts += 1;
+                }
+                (None, None) => {}
+            }
+        }
+    }
+    merge
+}
+
+/// The losing side of a conflict under a new ID. ID and name derive from the
+/// loser's content, so both machines create the same copy.
+fn conflict_copy(id: &str, loser: &ReplicaPreset) -> ReplicaPreset {
+    let mut entry = loser.entry.clone();
+    let short_hash = loser.sync_hash.get(..8).unwrap_or(&loser.sync_hash);
+    entry.preset.id = format!("{}-conflict-{}", id, short_hash);
+    entry.preset.name = format!("{} (conflict, {})", entry.preset.name, loser.origin);
+    let sync_hash = sync_hash(&entry);
+    ReplicaPreset {
+        entry,
+        sync_hash,
+        modified_ms: loser.modified_ms,
+        origin: loser.origin.clone(),
+    }
+}
+
+/// Replace machine-local tip and pattern IDs with content hashes and drop
+/// per-machine fields
+fn to_portable(
+    entry: &BrushLibraryPreset,
+    tip_hashes: &HashMap<String, String>,
+    pattern_hashes: &HashMap<String, String>,
+) -> BrushLibraryPreset {
+    let mut portable = entry.clone();
+    portable.tip_id = entry
+        .tip_id
+        .as_ref()
+        .and_then(|id| tip_hashes.get(id).cloned());
+    if let Some(dual) = portable.preset.dual_brush_settings.as_mut() {
+        dual.brush_id = dual
+            .brush_id
+            .as_ref()
+            .and_then(|id| tip_hashes.get(id).cloned());
+    }
+    if let Some(texture) = portable.preset.texture_settings.as_mut() {
+        texture.pattern_id = texture
+            .pattern_id
+            .as_ref()
+            .and_then(|id| pattern_hashes.get(id).cloned());
+    }
+    portable.content_hash.clear();
+    portable.use_count = 0;
+    portable.last_used_ms = None;
+    portable
+}
+
+fn from_portable(
+    id: String,
+    mut entry: BrushLibraryPreset,
+    tip_ids: &HashMap<String, String>,
+    pattern_ids: &HashMap<String, String>,
+) -> BrushLibraryPreset {
+    entry.preset.id = id;
+    entry.tip_id = entry
+        .tip_id
+        .as_ref()
+        .and_then(|hash| tip_ids.get(hash).cloned());
+    if let Some(dual) = entry.preset.dual_brush_settings.as_mut() {
+        dual.brush_id = dual
+            .brush_id
+            .as_ref()
+            .and_then(|hash| tip_ids.get(hash).cloned());
+    }
+    if let Some(texture) = entry.preset.texture_settings.as_mut() {
+        texture.pattern_id = texture
+            .pattern_id
+            .as_ref()
+            .and_then(|hash| pattern_ids.get(hash).cloned());
+    }
+    entry
+}
+
+/// Identity of a portable preset's synced content. ID, source and timestamps are
+/// excluded so the same preset imported on two machines matches.
+fn sync_hash(portable: &BrushLibraryPreset) -> String {
+    let mut normalized = portable.clone();
+    normalized.preset.id.clear();
+    normalized.source.clear();
+    normalized.modified_ms = None;
+    let bytes = serde_json::to_vec(&normalized).unwrap_or_default();
+    hex::encode(Sha256::digest(bytes))
+}
+
+fn unique_id(preferred: &str, taken: &HashSet<String>, cache_dir: &Path) -> String {
+    let is_free = |id: &str| !taken.contains(id) && !cache_dir.join(format!("{}.bin", id)).exists();
+    if is_free(preferred) {
+        return preferred.to_string();
+    }
+    (2..)
+        .map(|n| format!("{}_{}", preferred, n))
+        .find(|id| is_free(id))
+        .unwrap_or_else(|| preferred.to_string())
+}
+
+/// `<dir>/<hash>.bin` in the replica, or `None` for a hash that is not hex
+fn replica_blob_path(dir: &Path, hash: &str) -> Option<PathBuf> {
+    if is_content_hash(hash) {
+        Some(dir.join(format!("{}.bin", hash)))
+    } else {
+        tracing::warn!("Skipping blob with invalid content hash {:?}", hash);
+        None
+    }
+}
+
+/// Copy a blob unless the target already exists. Returns whether the target
+/// exists afterwards.
+fn copy_blob(source: &Path, target: &Path) -> bool {
+    if target.exists() {
+        return true;
+    }
+    let result = std::fs::read(source).and_then(|bytes| index_file::write_atomic(target, &bytes));
+    match result {
+        Ok(()) => true,
+        Err(err) => {
+            tracing::warn!(
+                "Failed to copy {} to {}: {}",
+                source.display(),
+                target.display(),
+                err
+            );
+            false
+        }
+    }
+}
+
+fn machine_name() -> String {
+    host_name()
+        .map(|name| name.split('.').next().unwrap_or_default().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "another device".to_string())
+}
+
+#[cfg(unix)]
+fn host_name() -> Option<String> {
+    let mut name = [0u8; 256];
+    // SAFETY: `name` is a live buffer of `name.len()` bytes; gethostname writes
+    // at most that many
+    let result = unsafe { libc::gethostname(name.as_mut_ptr().cast(), name.len()) };
+    if result != 0 {
+        return None;
+    }
+    let len = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+    Some(String::from_utf8_lossy(&name[..len]).into_owned())
+}
+
+#[cfg(windows)]
+fn host_name() -> Option<String> {
+    // Windows always sets this for every process
+    std::env::var("COMPUTERNAME").ok()
+}
+
+#[cfg(not(any(unix, windows)))]
+fn host_name() -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::abr::{BrushPreset, TextureSettings};
+    use crate::brush::cache::{save_brush_to_disk_in_dir, CachedBrush};
+    use crate::brush::pattern_cache::{save_pattern_to_disk_in_dir, CachedPattern};
+    use crate::pattern::types::PatternMode;
+
+    struct Machine {
+        brushes: BrushLibrary,
+        patterns: PatternLibrary,
+        env: LibrarySyncEnv,
+    }
+
+    impl Machine {
+        fn new(root: &Path, name: &str) -> Self {
+            let dir = root.join(name);
+            Self {
+                brushes: BrushLibrary::new(dir.join("brushes")),
+                patterns: PatternLibrary::new(dir.join("patterns")),
+                env: LibrarySyncEnv {
+                    replica_dir: root.join("replica"),
+                    brush_cache_dir: dir.join("brush_cache"),
+                    pattern_cache_dir: dir.join("pattern_cache"),
+                    machine: name.to_string(),
+                    now_ms: 1,
+                },
+            }
+        }
+
+        fn sync(&mut self) -> LibrarySyncReport {
+            sync_libraries(&mut self.brushes, &mut self.patterns, &self.env).unwrap()
+        }
+
+        fn preset(&self, id: &str) -> Option<BrushLibraryPreset> {
+            self.brushes
+                .snapshot()
+                .presets
+                .into_iter()
+                .find(|entry| entry.preset.id == id)
+        }
+
+        fn edit(&mut self, id: &str, spacing: f32, modified_ms: u64) {
+            let mut entry = self.preset(id).unwrap();
+            entry.preset.spacing = spacing;
+            entry.modified_ms = Some(modified_ms);
+            self.brushes
+                .apply_sync_changes(Vec::new(), vec![entry], &[])
+                .unwrap();
+        }
+    }
+
+    fn test_root(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "{}_library_sync_{}_{}",
+            APP_STORAGE_PREFIX,
+            name,
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ))
+    }
+
+    fn brush_preset(id: &str, name: &str) -> BrushPreset {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": name,
+            "diameter": 20.0,
+            "spacing": 25.0,
+            "hardness": 100.0,
+            "angle": 0.0,
+            "roundness": 100.0,
+            "hasTexture": true,
+            "isComputed": false,
+            "sizePressure": true,
+            "opacityPressure": false
+        }))
+        .unwrap()
+    }
+
+    /// Machine A owns tip `tip-a`, pattern `pat-a` and a textured preset using both
+    fn seed(machine: &mut Machine) {
+        let gray = vec![200u8; 4 * 4];
+        save_brush_to_disk_in_dir(
+            &machine.env.brush_cache_dir,
+            "tip-a",
+            &CachedBrush {
+                data: lz4_flex::compress_prepend_size(&gray),
+                width: 4,
+                height: 4,
+                name: "Tip A".to_string(),
+            },
+        );
+        save_pattern_to_disk_in_dir(
+            &machine.env.pattern_cache_dir,
+            "pat-a",
+            &CachedPattern {
+                data: lz4_flex::compress_prepend_size(&[90u8; 2 * 2]),
+                width: 2,
+                height: 2,
+                name: "Paper".to_string(),
+                mode: PatternMode::Grayscale.name().to_string(),
+            },
+        );
+        machine
+            .patterns
+            .apply_sync_changes(
+                vec![PatternResource {
+                    id: "pat-a".to_string(),
+                    name: "Paper".to_string(),
+                    content_hash: "feed01".to_string(),
+                    width: 2,
+                    height: 2,
+                    mode: PatternMode::Grayscale,
+                    source: "test".to_string(),
+                    group: Some("Papers".to_string()),
+                    tags: vec!["paper".to_string()],
+                    last_used_ms: Some(5),
+                }],
+                &[],
+            )
+            .unwrap();
+
+        let mut preset = brush_preset("preset-a", "Grainy Ink");
+        preset.texture_settings = Some(TextureSettings {
+            enabled: true,
+            pattern_id: Some("pat-a".to_string()),
+            ..Default::default()
+        });
+        machine
+            .brushes
+            .apply_sync_changes(
+                vec![BrushTipResource {
+                    tip: brush_preset("tip-a", "Tip A"),
+                    source: "test".to_string(),
+                    content_hash: "c0ffee".to_string(),
+                }],
+                vec![BrushLibraryPreset {
+                    preset,
+                    tip_id: Some("tip-a".to_string()),
+                    group: Some("Inks".to_string()),
+                    source: "test".to_string(),
+                    content_hash: String::new(),
+                    tags: vec!["ink".to_string()],
+                    favorite: false,
+                    use_count: 4,
+                    last_used_ms: Some(10),
+                    modified_ms: Some(10),
+                }],
+                &[],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn presets_tips_and_patterns_propagate_and_deletions_follow() {
+        let root = test_root("propagate");
+        let mut a = Machine::new(&root, "a");
+        let mut b = Machine::new(&root, "b");
+        // B already has the same pattern under its own ID
+        save_pattern_to_disk_in_dir(
+            &b.env.pattern_cache_dir,
+            "pat-a",
+            &CachedPattern {
+                data: Vec::new(),
+                width: 1,
+                height: 1,
+                name: "Unrelated".to_string(),
+                mode: PatternMode::Grayscale.name().to_string(),
+            },
+        );
+        seed(&mut a);
+
+        let pushed = a.sync();
+        assert_eq!(pushed.pushed_presets, 1);
+        assert_eq!(pushed.pushed_tips, 1);
+        assert_eq!(pushed.pushed_patterns, 1);
+
+        let pulled = b.sync();
+        assert_eq!(pulled.pulled_presets, 1);
+        assert_eq!(pulled.pulled_tips, 1);
+        assert_eq!(pulled.pulled_patterns, 1);
+
+        let entry = b.preset("preset-a").unwrap();
+        assert_eq!(entry.group.as_deref(), Some("Inks"));
+        assert_eq!(entry.tags, vec!["ink".to_string()]);
+        assert_eq!(entry.use_count, 0);
+        assert_eq!(entry.tip_id.as_deref(), Some("tip-a"));
+        assert!(b.env.brush_cache_dir.join("tip-a.bin").exists());
+        // `pat-a` was taken in B's cache, so the pulled pattern got a new ID
+        let pattern_id = entry.preset.texture_settings.unwrap().pattern_id.unwrap();
+        assert_eq!(pattern_id, "pat-a_2");
+        let pattern = b.patterns.get_pattern(&pattern_id).unwrap();
+        assert_eq!(pattern.content_hash, "feed01");
+        assert_eq!(pattern.tags, vec!["paper".to_string()]);
+        assert_eq!(pattern.last_used_ms, None);
+        assert!(b
+            .env
+            .pattern_cache_dir
+            .join(format!("{}.bin", pattern_id))
+            .exists());
+
+        assert_eq!(a.sync(), LibrarySyncReport::default());
+
+        b.brushes.delete_preset("preset-a").unwrap();
+        assert_eq!(b.sync().deleted_remote_presets, 1);
+        assert_eq!(a.sync().deleted_local_presets, 1);
+        assert!(a.preset("preset-a").is_none());
+        assert!(!root
+            .join("replica")
+            .join(format!("{}-library", APP_STORAGE_PREFIX))
+            .join(TIPS_DIR)
+            .join("c0ffee.bin")
+            .exists());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn concurrent_edits_keep_later_edit_and_a_conflict_copy() {
+        let root = test_root("conflict");
+        let mut a = Machine::new(&root, "a");
+        let mut b = Machine::new(&root, "b");
+        seed(&mut a);
+        a.sync();
+        b.sync();
+
+        a.edit("preset-a", 40.0, 100);
+        b.edit("preset-a", 60.0, 200);
+        a.sync();
+        let report = b.sync();
+        assert_eq!(report.conflicts, 1);
+        a.sync();
+
+        for machine in [&a, &b] {
+            assert_eq!(machine.preset("preset-a").unwrap().preset.spacing, 60.0);
+            let copies: Vec<BrushLibraryPreset> = machine
+                .brushes
+                .snapshot()
+                .presets
+                .into_iter()
+                .filter(|entry| entry.preset.id.starts_with("preset-a-conflict-"))
+                .collect();
+            assert_eq!(copies.len(), 1);
+            assert_eq!(copies[0].preset.spacing, 40.0);
+            assert_eq!(copies[0].preset.name, "Grainy Ink (conflict, a)");
+        }
+        assert_eq!(a.sync(), LibrarySyncReport::default());
+        assert_eq!(b.sync(), LibrarySyncReport::default());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    fn replica_root(root: &Path) -> PathBuf {
+        root.join("replica")
+            .join(format!("{}-library", APP_STORAGE_PREFIX))
+    }
+
+    #[test]
+    fn recreated_manifest_is_merged_as_a_first_sync() {
+        let root = test_root("recreated");
+        let mut a = Machine::new(&root, "a");
+        let mut b = Machine::new(&root, "b");
+        seed(&mut a);
+        a.sync();
+        b.sync();
+
+        std::fs::remove_file(replica_root(&root).join(MANIFEST_FILE_NAME)).unwrap();
+        let report = b.sync();
+        assert_eq!(report.deleted_local_presets, 0);
+        assert_eq!(report.pushed_presets, 1);
+        assert!(b.preset("preset-a").is_some());
+
+        assert_eq!(a.sync().deleted_local_presets, 0);
+        assert!(a.preset("preset-a").is_some());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn unreadable_manifest_and_held_lock_stop_the_sync() {
+        let root = test_root("unreadable");
+        let mut a = Machine::new(&root, "a");
+        seed(&mut a);
+        a.sync();
+        let replica = replica_root(&root);
+        assert!(!replica.join(LOCK_FILE_NAME).exists());
+
+        let manifest_path = replica.join(MANIFEST_FILE_NAME);
+        std::fs::write(&manifest_path, b"{ truncated").unwrap();
+        assert!(sync_libraries(&mut a.brushes, &mut a.patterns, &a.env).is_err());
+        assert_eq!(std::fs::read(&manifest_path).unwrap(), b"{ truncated");
+        assert!(a.preset("preset-a").is_some());
+        std::fs::remove_file(&manifest_path).unwrap();
+
+        std::fs::write(replica.join(LOCK_FILE_NAME), b"b 1").unwrap();
+        let err = sync_libraries(&mut a.brushes, &mut a.patterns, &a.env).unwrap_err();
+        assert!(err.contains("in use"), "{}", err);
+        std::fs::remove_file(replica.join(LOCK_FILE_NAME)).unwrap();
+        a.sync();
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn replica_ids_and_hashes_never_escape_the_cache_dirs() {
+        let root = test_root("sanitize");
+        let mut a = Machine::new(&root, "a");
+        let mut b = Machine::new(&root, "b");
+        seed(&mut a);
+        a.sync();
+
+        let manifest_path = replica_root(&root).join(MANIFEST_FILE_NAME);
+        let mut manifest: ReplicaManifest =
+            match index_file::read_index(&manifest_path, MANIFEST_SCHEMA_VERSION, &[]) {
+                IndexLoad::Loaded { index, .. } => index,
+                other => panic!("unexpected {:?}", other),
+            };
+        manifest.patterns.get_mut("feed01").unwrap().id = "../../evil".to_string();
+        let tip = manifest.tips["c0ffee"].clone();
+        manifest.tips.insert("../escape".to_string(), tip);
+        index_file::save_index(&manifest_path, MANIFEST_SCHEMA_VERSION, &manifest).unwrap();
+
+        let report = b.sync();
+        assert_eq!(report.pulled_tips, 1);
+        assert_eq!(report.pulled_patterns, 1);
+        assert!(b.patterns.get_pattern("feed01").is_some());
+        assert!(!root.join("evil.bin").exists());
+
+        let replica = ReplicaPreset {
+            entry: a.preset("preset-a").unwrap(),
+            sync_hash: "abc".to_string(),
+            modified_ms: 0,
+            origin: "a".to_string(),
+        };
+        assert_eq!(
+            conflict_copy("preset-a", &replica).entry.preset.id,
+            "preset-a-conflict-abc"
+        );
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -1,6 +1,6 @@
 //! Tauri commands - IPC interface between frontend and backend
 
-use crate::brush::{BrushEngine, StrokeSegment};
+use crate::brush::{BrushEngine, PressureCurvePoint, StrokeSegment};
 use crate::input::wintab_spike::SpikeResult;
 use crate::input::{
     InputBackpressureMode, InputPhase, InputQueueMetrics, PressureCurve, RawInputPoint,
@@ -12,7 +12,7 @@ use tauri::{AppHandle, Emitter};
 
 #[cfg(target_os = "windows")]
 use raw_window_handle::{HasWindowHandle, RawWindowHandle};
-#[cfg(target_os = "windows")]
+#[cfg(any(target_os = "windows", target_os = "linux"))]
 use tauri::Manager;
 
 /// Document information returned after creation
@@ -123,6 +123,7 @@ pub enum BackendType {
     WinTab,
     MacNative,
     PointerEvent,
+    LinuxEvdev,
 }
 
 /// Tablet state holding the active backend
@@ -131,24 +132,49 @@ struct TabletState {
     backend_type: BackendType,
     wintab: Option<crate::input::WinTabBackend>,
     macnative: Option<crate::input::MacNativeBackend>,
+    evdev: Option<crate::input::LinuxEvdevBackend>,
     pointer: Option<crate::input::PointerEventBackend>,
     config: TabletConfig,
     app_handle: Option<AppHandle>,
     emitter_running: bool,
+    recorder: Option<crate::input::InputRecorder>,
+    predictor: crate::input::InputPredictor,
+    tools: crate::input::ToolTracker,
+    hover: crate::input::HoverStream,
+    actions: crate::input::TabletActionMapper,
+    gestures: crate::input::GestureRecognizer,
+    mapping: crate::input::TabletMappingStore,
 }
 
 impl TabletState {
     fn new() -> Self {
         let default_backend = default_backend_for_platform();
+        let mapping =
+            crate::input::TabletMappingStore::load(&crate::input::default_tablet_mapping_path());
         Self {
             requested_backend: default_backend,
             backend_type: default_backend,
             wintab: None,
             macnative: None,
+            evdev: None,
             pointer: None,
-            config: TabletConfig::default(),
+            config: TabletConfig {
+                mapping: mapping.mapping(),
+                ..TabletConfig::default()
+            },
             app_handle: None,
             emitter_running: false,
+            recorder: None,
+            predictor: crate::input::InputPredictor::default(),
+            tools: crate::input::ToolTracker::new(crate::input::ToolProfileStore::load(
+                &crate::input::default_tool_profiles_path(),
+            )),
+            hover: crate::input::HoverStream::default(),
+            actions: crate::input::TabletActionMapper::new(crate::input::TabletBindingStore::load(
+                &crate::input::default_tablet_bindings_path(),
+            )),
+            gestures: crate::input::GestureRecognizer::new(),
+            mapping,
         }
     }
 
@@ -157,6 +183,7 @@ impl TabletState {
             BackendType::WinTab => self.wintab.as_mut().map(|b| b as &mut dyn TabletBackend),
             BackendType::MacNative => self.macnative.as_mut().map(|b| b as &mut dyn TabletBackend),
             BackendType::PointerEvent => self.pointer.as_mut().map(|b| b as &mut dyn TabletBackend),
+            BackendType::LinuxEvdev => self.evdev.as_mut().map(|b| b as &mut dyn TabletBackend),
         }
     }
 }
@@ -167,18 +194,26 @@ fn get_tablet_state() -> Arc<Mutex<TabletState>> {
         .clone()
 }
 
-fn parse_pressure_curve(curve: Option<&str>) -> PressureCurve {
-    match curve {
-        Some("soft") => PressureCurve::Soft,
-        Some("hard") => PressureCurve::Hard,
-        Some("scurve") => PressureCurve::SCurve,
-        _ => PressureCurve::Linear,
+fn parse_pressure_curve(
+    curve: Option<&str>,
+    points: Option<Vec<PressureCurvePoint>>,
+) -> PressureCurve {
+    // The frontend only sends points that differ from the named preset
+    if let Some(points) = points {
+        match PressureCurve::custom(points) {
+            Ok(custom) => return custom,
+            Err(e) => tracing::warn!("[Tablet] Ignoring custom pressure curve: {}", e),
+        }
     }
+    curve
+        .and_then(PressureCurve::from_preset_name)
+        .unwrap_or_default()
 }
 
 fn parse_backpressure_mode(mode: Option<&str>) -> InputBackpressureMode {
     match mode {
         Some("latency_capped") => InputBackpressureMode::LatencyCapped,
+        Some("coalescing") => InputBackpressureMode::Coalescing,
         _ => InputBackpressureMode::Lossless,
     }
 }
@@ -188,6 +223,7 @@ fn backend_type_name(backend: BackendType) -> &'static str {
         BackendType::WinTab => "wintab",
         BackendType::MacNative => "macnative",
         BackendType::PointerEvent => "pointerevent",
+        BackendType::LinuxEvdev => "linuxevdev",
     }
 }
 
@@ -196,6 +232,7 @@ fn input_source_name(source: crate::input::InputSource) -> &'static str {
         crate::input::InputSource::WinTab => "wintab",
         crate::input::InputSource::MacNative => "macnative",
         crate::input::InputSource::PointerEvent => "pointerevent",
+        crate::input::InputSource::LinuxEvdev => "linuxevdev",
     }
 }
 
@@ -218,18 +255,25 @@ fn normalize_requested_backend_for_platform(requested_backend: BackendType) -> B
     #[cfg(target_os = "windows")]
     {
         match requested_backend {
-            BackendType::MacNative => BackendType::WinTab,
+            BackendType::MacNative | BackendType::LinuxEvdev => BackendType::WinTab,
             other => other,
         }
     }
     #[cfg(target_os = "macos")]
     {
         match requested_backend {
-            BackendType::WinTab => BackendType::MacNative,
+            BackendType::WinTab | BackendType::LinuxEvdev => BackendType::MacNative,
             other => other,
         }
     }
-    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    #[cfg(target_os = "linux")]
+    {
+        match requested_backend {
+            BackendType::LinuxEvdev => BackendType::LinuxEvdev,
+            _ => BackendType::PointerEvent,
+        }
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
     {
         let _ = requested_backend;
         BackendType::PointerEvent
@@ -240,18 +284,20 @@ fn apply_tablet_runtime_config(
     state: &mut TabletState,
     polling_rate: Option<u32>,
     pressure_curve: Option<&str>,
+    pressure_curve_points: Option<Vec<PressureCurvePoint>>,
     backpressure_mode: Option<&str>,
 ) {
     if let Some(rate) = polling_rate {
         state.config.polling_rate_hz = rate;
     }
-    state.config.pressure_curve = parse_pressure_curve(pressure_curve);
+    state.config.pressure_curve = parse_pressure_curve(pressure_curve, pressure_curve_points);
     state.config.backpressure_mode = parse_backpressure_mode(backpressure_mode);
 }
 
 fn current_tablet_status_response(state: &mut TabletState) -> TabletStatusResponse {
     let requested_backend = backend_type_name(state.requested_backend).to_string();
     let backpressure_mode = state.config.backpressure_mode;
+    let known_tools = state.tools.known_tools();
 
     if let Some(backend) = state.active_backend() {
         let backend_name = backend.name().to_string();
@@ -267,6 +313,7 @@ fn current_tablet_status_response(state: &mut TabletState) -> TabletStatusRespon
             backpressure_mode,
             queue_metrics,
             v3_diagnostics,
+            known_tools,
             info,
         }
     } else {
@@ -278,6 +325,7 @@ fn current_tablet_status_response(state: &mut TabletState) -> TabletStatusRespon
             backpressure_mode,
             queue_metrics: InputQueueMetrics::default(),
             v3_diagnostics: TabletV3Diagnostics::default(),
+            known_tools,
             info: None,
         }
     }
@@ -347,6 +395,58 @@ fn build_mac_native_backend(
     Ok(macnative)
 }
 
+/// Map the tablet area onto the configured monitor, or the one hosting the main
+/// window (client CSS px)
+#[cfg(target_os = "linux")]
+fn resolve_evdev_output_mapping(
+    app: &AppHandle,
+    mapping: &crate::input::TabletMappingV1,
+) -> Option<crate::input::EvdevOutputMapping> {
+    let window = app.get_webview_window("main")?;
+    let selected = mapping.monitor.and_then(|index| {
+        window
+            .available_monitors()
+            .ok()?
+            .into_iter()
+            .nth(index as usize)
+    });
+    let monitor = match selected {
+        Some(monitor) => monitor,
+        None => window.current_monitor().ok().flatten()?,
+    };
+    let inner = window.inner_position().ok()?;
+    let scale = window.scale_factor().ok().filter(|s| *s > 0.0)?;
+    let size = monitor.size();
+    let origin = monitor.position();
+    Some(crate::input::EvdevOutputMapping {
+        screen_width_px: (size.width as f64 / scale) as f32,
+        screen_height_px: (size.height as f64 / scale) as f32,
+        client_offset_x_px: ((inner.x - origin.x) as f64 / scale) as f32,
+        client_offset_y_px: ((inner.y - origin.y) as f64 / scale) as f32,
+        tablet: *mapping,
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn resolve_evdev_output_mapping(
+    _app: &AppHandle,
+    _mapping: &crate::input::TabletMappingV1,
+) -> Option<crate::input::EvdevOutputMapping> {
+    None
+}
+
+fn build_linux_evdev_backend(
+    config: &TabletConfig,
+    app_handle: &AppHandle,
+) -> Result<crate::input::LinuxEvdevBackend, String> {
+    let mut evdev = crate::input::LinuxEvdevBackend::new();
+    if let Some(mapping) = resolve_evdev_output_mapping(app_handle, &config.mapping) {
+        evdev.set_output_mapping(mapping);
+    }
+    evdev.init(config)?;
+    Ok(evdev)
+}
+
 fn start_backend(state: &mut TabletState, backend: BackendType) -> Result<(), String> {
     match backend {
         BackendType::WinTab => state
@@ -364,6 +464,11 @@ fn start_backend(state: &mut TabletState, backend: BackendType) -> Result<(), St
             .as_mut()
             .ok_or_else(|| "PointerEvent backend is not initialized".to_string())?
             .start(),
+        BackendType::LinuxEvdev => state
+            .evdev
+            .as_mut()
+            .ok_or_else(|| "LinuxEvdev backend is not initialized".to_string())?
+            .start(),
     }
 }
 
@@ -389,6 +494,7 @@ fn select_backend(
     state.backend_type = normalized_backend;
     state.wintab = None;
     state.macnative = None;
+    state.evdev = None;
     state.pointer = None;
 
     match normalized_backend {
@@ -401,6 +507,9 @@ fn select_backend(
         BackendType::PointerEvent => {
             state.pointer = Some(build_pointer_backend(&state.config)?);
         }
+        BackendType::LinuxEvdev => {
+            state.evdev = Some(build_linux_evdev_backend(&state.config, app_handle)?);
+        }
     }
 
     Ok(())
@@ -416,6 +525,7 @@ pub struct TabletStatusResponse {
     pub backpressure_mode: InputBackpressureMode,
     pub queue_metrics: InputQueueMetrics,
     pub v3_diagnostics: TabletV3Diagnostics,
+    pub known_tools: Vec<crate::input::KnownToolV3>,
     pub info: Option<TabletInfo>,
 }
 
@@ -514,6 +624,7 @@ pub fn init_tablet(
     backend: Option<BackendType>,
     polling_rate: Option<u32>,
     pressure_curve: Option<String>,
+    pressure_curve_points: Option<Vec<PressureCurvePoint>>,
     backpressure_mode: Option<String>,
 ) -> Result<TabletStatusResponse, String> {
     let state = get_tablet_state();
@@ -521,7 +632,11 @@ pub fn init_tablet(
 
     state.app_handle = Some(app.clone());
 
-    if state.wintab.is_some() || state.macnative.is_some() || state.pointer.is_some() {
+    if state.wintab.is_some()
+        || state.macnative.is_some()
+        || state.evdev.is_some()
+        || state.pointer.is_some()
+    {
         tracing::info!("[Tablet] Already initialized, returning current status");
         return Ok(current_tablet_status_response(&mut state));
     }
@@ -532,6 +647,7 @@ pub fn init_tablet(
         &mut state,
         None,
         pressure_curve.as_deref(),
+        pressure_curve_points,
         backpressure_mode.as_deref(),
     );
 
@@ -547,6 +663,7 @@ pub fn switch_tablet_backend(
     backend: BackendType,
     polling_rate: Option<u32>,
     pressure_curve: Option<String>,
+    pressure_curve_points: Option<Vec<PressureCurvePoint>>,
     backpressure_mode: Option<String>,
 ) -> Result<TabletStatusResponse, String> {
     let state = get_tablet_state();
@@ -558,6 +675,7 @@ pub fn switch_tablet_backend(
         &mut state,
         polling_rate,
         pressure_curve.as_deref(),
+        pressure_curve_points,
         backpressure_mode.as_deref(),
     );
 
@@ -595,6 +713,9 @@ pub fn start_tablet() -> Result<(), String> {
                 tracing::info!("[Tablet] Event emitter thread started");
                 let mut events = Vec::with_capacity(64);
                 let mut active_backend_name = "none".to_string();
+                let mut hover_due = None;
+                let mut actions = Vec::new();
+                let mut gestures = Vec::new();
 
                 loop {
                     let should_continue = {
@@ -607,8 +728,40 @@ pub fn start_tablet() -> Result<(), String> {
                             false
                         } else {
                             active_backend_name = backend_type_name(state.backend_type).to_string();
+                            let mut hover = None;
                             if let Some(backend) = state.active_backend() {
                                 backend.poll(&mut events);
+                                hover = backend.take_hover();
+                            }
+                            if events.iter().any(|e| match e {
+                                TabletEventV3::ProximityLeave => true,
+                                TabletEventV3::Input(s) => s.phase == InputPhase::Down,
+                                _ => false,
+                            }) {
+                                state.hover.clear();
+                            }
+                            if let Some(recorder) = &state.recorder {
+                                recorder.record_batch(&events);
+                                if let Some(hover) = &hover {
+                                    recorder.record_hover(hover);
+                                }
+                            }
+                            if let Some(hover) = hover {
+                                state.hover.push(hover);
+                            }
+                            hover_due = state.hover.take_due(crate::input::current_time_us());
+                            state.tools.process(&mut events);
+                            actions = state.actions.map(&events);
+                            let now_us = crate::input::current_time_us();
+                            state.gestures.observe_pen(&events, now_us);
+                            gestures = state.gestures.take_pending();
+                            let tap_actions: Vec<_> = gestures
+                                .iter()
+                                .filter_map(|g| state.actions.map_gesture(g))
+                                .collect();
+                            actions.extend(tap_actions);
+                            if state.config.prediction_enabled {
+                                state.predictor.process(&mut events);
                             }
                             true
                         }
@@ -618,6 +771,24 @@ pub fn start_tablet() -> Result<(), String> {
                         break;
                     }
 
+                    if let Some(hover) = hover_due.take() {
+                        if let Err(e) = app.emit("tablet-hover-v1", &hover) {
+                            tracing::error!("[Tablet] Failed to emit hover sample: {}", e);
+                        }
+                    }
+
+                    for gesture in gestures.drain(..) {
+                        if let Err(e) = app.emit("tablet-gesture-v1", &gesture) {
+                            tracing::error!("[Tablet] Failed to emit touch gesture: {}", e);
+                        }
+                    }
+
+                    for action in actions.drain(..) {
+                        if let Err(e) = app.emit("tablet-action-v1", &action) {
+                            tracing::error!("[Tablet] Failed to emit tablet action: {}", e);
+                        }
+                    }
+
                     if !events.is_empty() {
                         let emit_poll_time_us = crate::input::current_time_us();
                         let metrics_before_emit = collect_emitter_batch_metrics(
@@ -676,6 +847,133 @@ pub fn get_tablet_status() -> Result<TabletStatusResponse, String> {
     Ok(current_tablet_status_response(&mut state))
 }
 
+/// Start teeing tablet events into a JSONL recording at `path`.
+#[tauri::command]
+pub fn start_input_recording(path: String) -> Result<(), String> {
+    let state = get_tablet_state();
+    let backend = {
+        let state = state.lock().map_err(|e| format!("Lock error: {}", e))?;
+        if state.recorder.is_some() {
+            return Err("Input recording already in progress".to_string());
+        }
+        backend_type_name(state.backend_type)
+    };
+
+    // Create the file outside the lock so the emitter thread is not stalled on disk
+    let recorder = crate::input::InputRecorder::create(std::path::Path::new(&path), backend)?;
+    let mut state = state.lock().map_err(|e| format!("Lock error: {}", e))?;
+    if state.recorder.is_some() {
+        return Err("Input recording already in progress".to_string());
+    }
+    state.recorder = Some(recorder);
+    tracing::info!("[Tablet] Recording input to {}", path);
+    Ok(())
+}
+
+/// Stop the active input recording. Returns the number of recorded events.
+#[tauri::command]
+pub fn stop_input_recording() -> Result<u64, String> {
+    let recorder = {
+        let state = get_tablet_state();
+        let mut state = state.lock().map_err(|e| format!("Lock error: {}", e))?;
+        state
+            .recorder
+            .take()
+            .ok_or_else(|| "No input recording in progress".to_string())?
+    };
+    recorder.finish()
+}
+
+/// Get persisted per-tool profiles keyed by `tool_profile_key`.
+#[tauri::command]
+pub fn get_tablet_tool_profiles(
+) -> Result<std::collections::BTreeMap<String, crate::input::ToolProfileV1>, String> {
+    let state = get_tablet_state();
+    let state = state.lock().map_err(|e| format!("Lock error: {}", e))?;
+
+    Ok(state.tools.store().profiles().clone())
+}
+
+/// Set or clear (`None`) the profile for a tool key and persist it.
+#[tauri::command]
+pub fn set_tablet_tool_profile(
+    key: String,
+    profile: Option<crate::input::ToolProfileV1>,
+) -> Result<(), String> {
+    let state = get_tablet_state();
+    let mut state = state.lock().map_err(|e| format!("Lock error: {}", e))?;
+
+    state.tools.store_mut().set(&key, profile)?;
+    // Re-announce the active tool so the new binding takes effect immediately
+    state.tools.reset();
+    Ok(())
+}
+
+/// Choose which pen axes rotate and squash the brush cursor in `tablet-hover-v1`.
+#[tauri::command]
+pub fn set_tablet_cursor_pose_config(
+    config: crate::input::CursorPoseConfigV1,
+) -> Result<(), String> {
+    let state = get_tablet_state();
+    let mut state = state.lock().map_err(|e| format!("Lock error: {}", e))?;
+
+    state.hover.set_config(config);
+    Ok(())
+}
+
+/// Get persisted tablet control bindings keyed by `tablet_control_key`.
+#[tauri::command]
+pub fn get_tablet_bindings(
+) -> Result<std::collections::BTreeMap<String, crate::input::TabletActionV1>, String> {
+    let state = get_tablet_state();
+    let state = state.lock().map_err(|e| format!("Lock error: {}", e))?;
+
+    Ok(state.actions.store().bindings().clone())
+}
+
+/// Bind or unbind (`None`) a pen button, express key or touch ring and persist it.
+#[tauri::command]
+pub fn set_tablet_binding(
+    key: String,
+    action: Option<crate::input::TabletActionV1>,
+) -> Result<(), String> {
+    let state = get_tablet_state();
+    let mut state = state.lock().map_err(|e| format!("Lock error: {}", e))?;
+
+    state.actions.store_mut().set(&key, action)
+}
+
+/// Get the persisted tablet mapping (active area, rotation, aspect lock, monitor).
+#[tauri::command]
+pub fn get_tablet_mapping() -> Result<crate::input::TabletMappingV1, String> {
+    let state = get_tablet_state();
+    let state = state.lock().map_err(|e| format!("Lock error: {}", e))?;
+
+    Ok(state.mapping.mapping())
+}
+
+/// Persist the tablet mapping and apply it to the initialized backends.
+#[tauri::command]
+pub fn set_tablet_mapping(
+    app: AppHandle,
+    mapping: crate::input::TabletMappingV1,
+) -> Result<crate::input::TabletMappingV1, String> {
+    let state = get_tablet_state();
+    let mut state = state.lock().map_err(|e| format!("Lock error: {}", e))?;
+
+    let mapping = state.mapping.set(mapping)?;
+    state.config.mapping = mapping;
+    if let Some(evdev) = state.evdev.as_mut() {
+        if let Some(output) = resolve_evdev_output_mapping(&app, &mapping) {
+            evdev.set_output_mapping(output);
+        }
+    }
+    if let Some(wintab) = state.wintab.as_mut() {
+        wintab.set_mapping(mapping)?;
+    }
+    Ok(mapping)
+}
+
 /// Toggle WinTab backend trace logs in Rust terminal output.
 #[tauri::command]
 pub fn set_wintab_trace_enabled(enabled: bool) -> Result<bool, String> {
@@ -702,12 +1000,28 @@ pub struct PointerEventPayload {
     pub pointer_id: Option<u32>,
     pub phase: Option<InputPhase>,
     pub device_time_us: Option<u64>,
+    pub tool_type: Option<crate::input::ToolTypeV3>,
+    /// Airbrush wheel in [0, 1]
+    pub wheel: Option<f32>,
 }
 
+/// Feed a frontend pointer sample. Finger contacts go to the gesture recognizer
+/// (with palm rejection), everything else to the PointerEvent backend.
 #[tauri::command]
 pub fn push_pointer_event(payload: PointerEventPayload) -> Result<(), String> {
     let state = get_tablet_state();
-    let state = state.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let mut state = state.lock().map_err(|e| format!("Lock error: {}", e))?;
+
+    if payload.tool_type == Some(crate::input::ToolTypeV3::Touch) {
+        state.gestures.push_touch(
+            payload.pointer_id.unwrap_or(0),
+            payload.phase.unwrap_or(InputPhase::Move),
+            payload.x,
+            payload.y,
+            crate::input::current_time_us(),
+        );
+        return Ok(());
+    }
 
     if let Some(pointer) = &state.pointer {
         pointer.push_input(
@@ -720,6 +1034,8 @@ pub fn push_pointer_event(payload: PointerEventPayload) -> Result<(), String> {
             payload.pointer_id.unwrap_or(0),
             payload.phase.unwrap_or(InputPhase::Move),
             payload.device_time_us,
+            payload.tool_type.unwrap_or_default(),
+            payload.wheel,
         );
     }
 
@@ -735,7 +1051,8 @@ use crate::brush::soft_dab::{render_soft_dab, GaussParams};
 // ABR Brush Import
 // ============================================================================
 
-use crate::abr::{AbrBrush, AbrParser, BrushPreset, CursorLodPathLenLimits};
+use crate::abr::{AbrBrush, AbrFile, AbrParser, BrushPreset, CursorLodPathLenLimits};
+use crate::brush::bulk_import::{BulkImportProgress, BulkImportResult};
 use crate::brush::library as brush_library;
 use crate::brush::library::{
     BrushLibraryImportResult, BrushLibraryPreset, BrushLibraryPresetPayload, BrushLibrarySnapshot,
@@ -931,12 +1248,43 @@ pub async fn import_abr_to_brush_library(
     brush_library::import_from_abr(&path, import_result.presets, import_result.tips)
 }
 
+/// Import every ABR and PAT file in the given files, folders and zip archives.
+/// Each file goes to a group named after its relative path; failures are
+/// reported per file. Progress is sent after each file.
+#[tauri::command]
+pub async fn import_brush_packs(
+    paths: Vec<String>,
+    options: Option<ImportAbrOptions>,
+    on_progress: tauri::ipc::Channel<BulkImportProgress>,
+) -> Result<BulkImportResult, String> {
+    let paths: Vec<std::path::PathBuf> = paths.iter().map(std::path::PathBuf::from).collect();
+    let result = crate::brush::bulk_import::import_brush_packs(
+        &paths,
+        |data| AbrParser::parse(data).map_err(|e| format!("Failed to parse ABR file: {}", e)),
+        |abr_file| {
+            let import_result = build_abr_import(
+                abr_file,
+                options.as_ref(),
+                std::time::Instant::now(),
+                0.0,
+                0.0,
+            )?;
+            Ok((import_result.presets, import_result.tips))
+        },
+        |progress| {
+            if let Err(e) = on_progress.send(progress) {
+                tracing::warn!("Failed to send bulk import progress: {}", e);
+            }
+        },
+    );
+    Ok(result)
+}
+
 fn import_abr_file_internal(
     path: &str,
     options: Option<&ImportAbrOptions>,
 ) -> Result<ImportAbrResult, String> {
     let total_start = std::time::Instant::now();
-    let cursor_lod_limits = resolve_cursor_lod_path_len_limits(options);
 
     // Step 1: Read file
     let read_start = std::time::Instant::now();
@@ -949,6 +1297,19 @@ fn import_abr_file_internal(
         AbrParser::parse(&data).map_err(|e| format!("Failed to parse ABR file: {}", e))?;
     let parse_ms = parse_start.elapsed().as_secs_f64() * 1000.0;
 
+    build_abr_import(abr_file, options, total_start, read_ms, parse_ms)
+}
+
+/// Cache the textures of a parsed ABR file and build its presets and tips
+fn build_abr_import(
+    abr_file: AbrFile,
+    options: Option<&ImportAbrOptions>,
+    total_start: std::time::Instant,
+    read_ms: f64,
+    parse_ms: f64,
+) -> Result<ImportAbrResult, String> {
+    let cursor_lod_limits = resolve_cursor_lod_path_len_limits(options);
+
     // Step 3: Cache textures and build presets
     let cache_start = std::time::Instant::now();
     let mut raw_bytes: usize = 0;
@@ -1004,6 +1365,10 @@ fn import_abr_file_internal(
         raw_bytes += pattern.data.len();
     }
 
+    crate::brush::spawn_thumbnail_generation(
+        pattern_infos.iter().map(|info| info.id.clone()).collect(),
+    );
+
     let mut presets: Vec<BrushPreset> = Vec::with_capacity(abr_file.brushes.len());
     let mut tips: Vec<BrushPreset> = Vec::with_capacity(abr_file.brushes.len());
     let mut pending_preset_builds: Vec<PendingPresetBuild> =
@@ -1347,13 +1712,109 @@ pub fn save_brush_preset_as(
     brush_library::save_preset_as(payload, new_name, target_group)
 }
 
+#[tauri::command]
+pub fn set_brush_preset_tags(id: String, tags: Vec<String>) -> Result<BrushLibraryPreset, String> {
+    brush_library::set_preset_tags(&id, tags)
+}
+
+#[tauri::command]
+pub fn set_brush_preset_favorite(id: String, favorite: bool) -> Result<BrushLibraryPreset, String> {
+    brush_library::set_preset_favorite(&id, favorite)
+}
+
+/// Count a preset activation for usage stats and "recently used"
+#[tauri::command]
+pub fn record_brush_preset_use(id: String) -> Result<BrushLibraryPreset, String> {
+    brush_library::record_preset_use(&id)
+}
+
+/// Revision history of a preset: the original import first, then saved revisions
+#[tauri::command]
+pub fn list_brush_preset_revisions(
+    id: String,
+) -> Result<Vec<crate::brush::history::BrushPresetRevisionSummary>, String> {
+    brush_library::list_preset_revisions(&id)
+}
+
+#[tauri::command]
+pub fn get_brush_preset_revision(
+    id: String,
+    revision: u32,
+) -> Result<crate::brush::history::BrushPresetRevision, String> {
+    brush_library::get_preset_revision(&id, revision)
+}
+
+#[tauri::command]
+pub fn diff_brush_preset_revisions(
+    id: String,
+    from: u32,
+    to: u32,
+) -> Result<Vec<crate::brush::history::BrushPresetFieldChange>, String> {
+    brush_library::diff_preset_revisions(&id, from, to)
+}
+
+/// Restore a revision's settings; revision 0 is the original import
+#[tauri::command]
+pub fn revert_brush_preset(id: String, revision: u32) -> Result<BrushLibraryPreset, String> {
+    brush_library::revert_preset(&id, revision)
+}
+
+/// Fuzzy search over preset names, tags and groups with property filters
+#[tauri::command]
+pub fn search_brush_presets(
+    query: String,
+    filters: Option<crate::brush::search::BrushPresetSearchFilters>,
+) -> Result<Vec<BrushLibraryPreset>, String> {
+    brush_library::search_presets(&query, &filters.unwrap_or_default())
+}
+
+/// Export presets and whole groups, with their tips and patterns, to a `.sutubrush` file
+#[tauri::command]
+pub async fn export_brush_bundle(
+    path: String,
+    preset_ids: Vec<String>,
+    group_names: Vec<String>,
+) -> Result<crate::brush::bundle::BrushBundleExportResult, String> {
+    crate::brush::bundle::export_bundle(std::path::Path::new(&path), &preset_ids, &group_names)
+}
+
+/// Import a `.sutubrush` file into the brush and pattern libraries
+#[tauri::command]
+pub async fn import_brush_bundle(
+    path: String,
+) -> Result<crate::brush::bundle::BrushBundleImportResult, String> {
+    crate::brush::bundle::import_bundle(std::path::Path::new(&path))
+}
+
+/// Two-way sync of the brush and pattern libraries with a replica folder
+#[tauri::command]
+pub async fn sync_libraries_with_folder(
+    folder: String,
+) -> Result<crate::brush::sync::LibrarySyncReport, String> {
+    crate::brush::sync::sync_with_folder(std::path::Path::new(&folder))
+}
+
+#[tauri::command]
+pub fn get_library_sync_state() -> Result<crate::brush::sync::LibrarySyncState, String> {
+    crate::brush::sync::get_sync_state()
+}
+
+/// Report dangling references and orphan bitmaps in the brush and pattern
+/// libraries; with `repair`, re-link, restore and prune what can be fixed
+#[tauri::command]
+pub async fn verify_libraries(
+    repair: bool,
+) -> Result<crate::brush::integrity::LibraryIntegrityReport, String> {
+    crate::brush::integrity::verify_libraries(repair)
+}
+
 // ============================================================================
 // Pattern Library Commands
 // ============================================================================
 
 use crate::pattern::{
-    self, AddPatternFromBrushResult, ImportResult as PatternImportResult, PatternMode,
-    PatternResource,
+    self, AddPatternFromBrushResult, ImportResult as PatternImportResult, PatternCreateOptions,
+    PatternMode, PatternRect, PatternResource,
 };
 
 /// Get all patterns from the library
@@ -1411,12 +1872,75 @@ pub fn add_pattern_from_brush(
     )
 }
 
+/// Add an image file (PNG, JPEG, WebP, TIFF...) as a pattern
+#[tauri::command]
+pub async fn add_pattern_from_image(
+    path: String,
+    options: Option<PatternCreateOptions>,
+) -> Result<AddPatternFromBrushResult, String> {
+    pattern::library::add_from_image(std::path::Path::new(&path), &options.unwrap_or_default())
+}
+
+/// Add a region of a layer as a pattern; `layer_image` is the layer's encoded PNG
+#[tauri::command]
+pub async fn add_pattern_from_region(
+    layer_image: Vec<u8>,
+    rect: PatternRect,
+    options: Option<PatternCreateOptions>,
+) -> Result<AddPatternFromBrushResult, String> {
+    pattern::library::add_from_region(&layer_image, rect, &options.unwrap_or_default())
+}
+
+/// Tiling preview of a library pattern (wrapped by half its size) as a PNG data URL
+#[tauri::command]
+pub fn get_pattern_offset_preview(id: String) -> Result<String, String> {
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+
+    let cached = crate::brush::get_cached_pattern(&id)
+        .ok_or_else(|| format!("Pattern not found in cache: {}", id))?;
+    let rgba = lz4_flex::decompress_size_prepended(&cached.data)
+        .map_err(|e| format!("Failed to decompress pattern {}: {}", id, e))?;
+    let preview = pattern::seamless::offset_preview(&rgba, cached.width, cached.height);
+    let image = image::RgbaImage::from_raw(cached.width, cached.height, preview)
+        .ok_or_else(|| format!("Pattern {} has an unexpected size", id))?;
+
+    let mut png = std::io::Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode preview: {}", e))?;
+    Ok(format!(
+        "data:image/png;base64,{}",
+        BASE64.encode(png.into_inner())
+    ))
+}
+
 /// Delete a pattern from the library
 #[tauri::command]
 pub fn delete_pattern(id: String) -> Result<(), String> {
     pattern::library::delete_pattern(&id)
 }
 
+/// Replace a pattern's tags
+#[tauri::command]
+pub fn set_pattern_tags(id: String, tags: Vec<String>) -> Result<PatternResource, String> {
+    pattern::library::set_pattern_tags(&id, tags)
+}
+
+/// Remember that a pattern was picked, for recent-use sorting
+#[tauri::command]
+pub fn record_pattern_use(id: String) -> Result<PatternResource, String> {
+    pattern::library::record_pattern_use(&id)
+}
+
+/// Fuzzy search over pattern names, tags and groups with mode/size filters
+#[tauri::command]
+pub fn search_patterns(
+    query: String,
+    filters: Option<pattern::search::PatternSearchFilters>,
+) -> Vec<PatternResource> {
+    pattern::library::search_patterns(&query, &filters.unwrap_or_default())
+}
+
 /// Rename a pattern
 #[tauri::command]
 pub fn rename_pattern(id: String, new_name: String) -> Result<(), String> {
@@ -1552,6 +2076,14 @@ mod tests {
         assert_eq!(backend_type_name(BackendType::MacNative), "macnative");
     }
 
+    #[test]
+    fn test_backend_type_linux_evdev_serde_name() {
+        assert_eq!(backend_type_name(BackendType::LinuxEvdev), "linuxevdev");
+        let decoded: BackendType =
+            serde_json::from_str("\"linuxevdev\"").expect("deserialize backend");
+        assert_eq!(decoded, BackendType::LinuxEvdev);
+    }
+
     #[test]
     fn test_default_backend_for_platform() {
         #[cfg(target_os = "windows")]
@@ -1590,6 +2122,12 @@ mod tests {
             );
         }
 
+        #[cfg(target_os = "linux")]
+        assert_eq!(
+            normalize_requested_backend_for_platform(BackendType::LinuxEvdev),
+            BackendType::LinuxEvdev
+        );
+
         #[cfg(not(any(target_os = "windows", target_os = "macos")))]
         {
             assert_eq!(
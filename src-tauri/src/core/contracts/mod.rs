@@ -2,8 +2,8 @@ use crate::benchmark::BackendBenchmark;
 use serde::{Deserialize, Serialize};
 pub mod pressure_v1;
 pub use pressure_v1::{
-    DabRequestV1, GateArtifactV1, GateCaseResultV1, GateEnvV1, GatePresetResultV1, GateRunMetaV1,
-    GateSummaryV1, PaintInfoV1, RawInputSampleV1,
+    DabRequestV1, GateArtifactV1, GateCaseResultV1, GateEnvV1, GateMetricMapV1, GatePresetResultV1,
+    GateRunMetaV1, GateSummaryV1, PaintInfoV1, RawInputSampleV1,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
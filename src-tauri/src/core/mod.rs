@@ -8,3 +8,5 @@ pub mod brush_model;
 pub mod contracts;
 pub mod errors;
 pub mod formats;
+pub mod pressure_curve;
+pub mod pressure_gate;
@@ -0,0 +1,379 @@
+//! Pressure curves shared by the brush engine and tablet backends.
+//!
+//! Presets are evaluated analytically. Custom curves use monotone cubic
+//! Hermite interpolation (Fritsch-Carlson) through user control points and
+//! are baked into a LUT once, so `apply` stays cheap on the input hot path.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::errors::CoreError;
+
+/// LUT resolution for custom curves (matches frontend `PRESSURE_CURVE_LUT_SIZE`)
+pub const PRESSURE_CURVE_LUT_SIZE: usize = 2048;
+
+/// Control point of a custom pressure curve (both axes 0-1)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PressureCurvePoint {
+    /// Input pressure
+    pub x: f32,
+    /// Output pressure
+    pub y: f32,
+}
+
+impl PressureCurvePoint {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+}
+
+/// Custom curve through monotone control points
+///
+/// Serialized as its control points only; the LUT is rebuilt on load.
+/// Inputs outside the first/last control point hold the endpoint value.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "Vec<PressureCurvePoint>", into = "Vec<PressureCurvePoint>")]
+pub struct CustomPressureCurve {
+    /// Control points sorted by x
+    points: Vec<PressureCurvePoint>,
+    /// Baked curve samples over [0, 1] (shared between clones)
+    lut: Arc<[f32]>,
+}
+
+impl CustomPressureCurve {
+    /// Build a curve from control points
+    ///
+    /// Points are clamped to 0-1 and sorted by x. Fails if there are fewer
+    /// than two points, a coordinate is not finite, two points share the
+    /// same x, or the output decreases anywhere.
+    pub fn new(points: Vec<PressureCurvePoint>) -> Result<Self, CoreError> {
+        if points.len() < 2 {
+            return Err(CoreError::InvalidInput(
+                "Pressure curve needs at least 2 control points".to_string(),
+            ));
+        }
+        if points.iter().any(|p| !p.x.is_finite() || !p.y.is_finite()) {
+            return Err(CoreError::InvalidInput(
+                "Pressure curve control points must be finite numbers".to_string(),
+            ));
+        }
+
+        let mut points: Vec<PressureCurvePoint> = points
+            .into_iter()
+            .map(|p| PressureCurvePoint::new(p.x.clamp(0.0, 1.0), p.y.clamp(0.0, 1.0)))
+            .collect();
+        points.sort_by(|a, b| a.x.total_cmp(&b.x));
+
+        for pair in points.windows(2) {
+            if pair[1].x <= pair[0].x {
+                return Err(CoreError::InvalidInput(format!(
+                    "Pressure curve has duplicate control point at x = {}",
+                    pair[0].x
+                )));
+            }
+            if pair[1].y < pair[0].y {
+                return Err(CoreError::InvalidInput(
+                    "Pressure curve control points must be monotone (non-decreasing)".to_string(),
+                ));
+            }
+        }
+
+        let tangents = monotone_tangents(&points);
+        let lut: Arc<[f32]> = (0..PRESSURE_CURVE_LUT_SIZE)
+            .map(|i| {
+                let x = i as f32 / (PRESSURE_CURVE_LUT_SIZE - 1) as f32;
+                eval_hermite(&points, &tangents, x).clamp(0.0, 1.0)
+            })
+            .collect();
+
+        Ok(Self { points, lut })
+    }
+
+    /// Control points sorted by x
+    pub fn points(&self) -> &[PressureCurvePoint] {
+        &self.points
+    }
+
+    /// Map a pressure value (clamped to 0-1) through the baked LUT
+    #[inline]
+    pub fn apply(&self, pressure: f32) -> f32 {
+        let pos = pressure.clamp(0.0, 1.0) * (self.lut.len() - 1) as f32;
+        let lo = pos as usize;
+        let hi = (lo + 1).min(self.lut.len() - 1);
+        let t = pos - lo as f32;
+        self.lut[lo] + (self.lut[hi] - self.lut[lo]) * t
+    }
+}
+
+impl PartialEq for CustomPressureCurve {
+    fn eq(&self, other: &Self) -> bool {
+        // The LUT is derived from the points
+        self.points == other.points
+    }
+}
+
+impl TryFrom<Vec<PressureCurvePoint>> for CustomPressureCurve {
+    type Error = CoreError;
+
+    fn try_from(points: Vec<PressureCurvePoint>) -> Result<Self, Self::Error> {
+        Self::new(points)
+    }
+}
+
+impl From<CustomPressureCurve> for Vec<PressureCurvePoint> {
+    fn from(curve: CustomPressureCurve) -> Self {
+        curve.points
+    }
+}
+
+/// Pressure curve types for mapping raw pressure to output
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum PressureCurve {
+    /// Linear mapping (1:1)
+    #[default]
+    Linear,
+    /// Soft curve (more sensitive at low pressure)
+    Soft,
+    /// Hard curve (less sensitive at low pressure)
+    Hard,
+    /// S-curve (soft at extremes, steeper in the middle)
+    SCurve,
+    /// Custom curve through control points
+    Custom(CustomPressureCurve),
+}
+
+impl PressureCurve {
+    /// Build a custom curve from control points
+    pub fn custom(points: Vec<PressureCurvePoint>) -> Result<Self, CoreError> {
+        CustomPressureCurve::new(points).map(Self::Custom)
+    }
+
+    /// Resolve a preset by its frontend name (`linear`, `soft`, `hard`, `scurve`)
+    pub fn from_preset_name(name: &str) -> Option<Self> {
+        match name {
+            "linear" => Some(Self::Linear),
+            "soft" => Some(Self::Soft),
+            "hard" => Some(Self::Hard),
+            "scurve" => Some(Self::SCurve),
+            _ => None,
+        }
+    }
+
+    /// Apply the pressure curve to a raw pressure value (clamped to 0-1)
+    pub fn apply(&self, pressure: f32) -> f32 {
+        let p = pressure.clamp(0.0, 1.0);
+
+        match self {
+            PressureCurve::Linear => p,
+            PressureCurve::Soft => {
+                // Ease-out: more sensitive at low pressure
+                1.0 - (1.0 - p).powi(2)
+            }
+            PressureCurve::Hard => {
+                // Ease-in: less sensitive at low pressure
+                p.powi(2)
+            }
+            PressureCurve::SCurve => {
+                // Smoothstep
+                p * p * (3.0 - 2.0 * p)
+            }
+            PressureCurve::Custom(curve) => curve.apply(p),
+        }
+    }
+}
+
+/// Fritsch-Carlson tangents: keeps each segment monotone for monotone data
+fn monotone_tangents(points: &[PressureCurvePoint]) -> Vec<f32> {
+    let n = points.len();
+    let secants: Vec<f32> = points
+        .windows(2)
+        .map(|pair| (pair[1].y - pair[0].y) / (pair[1].x - pair[0].x))
+        .collect();
+
+    let mut tangents = vec![0.0; n];
+    tangents[0] = secants[0];
+    tangents[n - 1] = secants[n - 2];
+    for k in 1..n - 1 {
+        tangents[k] = if secants[k - 1] * secants[k] <= 0.0 {
+            0.0
+        } else {
+            (secants[k - 1] + secants[k]) * 0.5
+        };
+    }
+
+    for (k, &d) in secants.iter().enumerate() {
+        if d == 0.0 {
+            tangents[k] = 0.0;
+            tangents[k + 1] = 0.0;
+            continue;
+        }
+        let a = tangents[k] / d;
+        let b = tangents[k + 1] / d;
+        let len_sq = a * a + b * b;
+        if len_sq > 9.0 {
+            let tau = 3.0 / len_sq.sqrt();
+            tangents[k] = tau * a * d;
+            tangents[k + 1] = tau * b * d;
+        }
+    }
+
+    tangents
+}
+
+/// Evaluate the cubic Hermite spline at `x`, holding endpoint values outside
+fn eval_hermite(points: &[PressureCurvePoint], tangents: &[f32], x: f32) -> f32 {
+    let first = points[0];
+    let last = points[points.len() - 1];
+    if x <= first.x {
+        return first.y;
+    }
+    if x >= last.x {
+        return last.y;
+    }
+
+    let k = points.partition_point(|p| p.x <= x) - 1;
+    let (p0, p1) = (points[k], points[k + 1]);
+    let h = p1.x - p0.x;
+    let t = (x - p0.x) / h;
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    let h00 = 2.0 * t3 - 3.0 * t2 + 1.0;
+    let h10 = t3 - 2.0 * t2 + t;
+    let h01 = -2.0 * t3 + 3.0 * t2;
+    let h11 = t3 - t2;
+
+    h00 * p0.y + h10 * h * tangents[k] + h01 * p1.y + h11 * h * tangents[k + 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pts(points: &[(f32, f32)]) -> Vec<PressureCurvePoint> {
+        points
+            .iter()
+            .map(|&(x, y)| PressureCurvePoint::new(x, y))
+            .collect()
+    }
+
+    fn assert_monotone(curve: &PressureCurve) {
+        let mut prev = curve.apply(0.0);
+        for i in 1..=1000 {
+            let v = curve.apply(i as f32 / 1000.0);
+            assert!(
+                v >= prev - 1e-6,
+                "curve decreases at {}: {} < {}",
+                i,
+                v,
+                prev
+            );
+            prev = v;
+        }
+    }
+
+    #[test]
+    fn test_presets_hit_endpoints_and_are_monotone() {
+        for curve in [
+            PressureCurve::Linear,
+            PressureCurve::Soft,
+            PressureCurve::Hard,
+            PressureCurve::SCurve,
+        ] {
+            assert_eq!(curve.apply(0.0), 0.0);
+            assert_eq!(curve.apply(1.0), 1.0);
+            assert_monotone(&curve);
+        }
+        assert!(PressureCurve::Soft.apply(0.5) > 0.5);
+        assert!(PressureCurve::Hard.apply(0.5) < 0.5);
+    }
+
+    #[test]
+    fn test_custom_two_points_is_linear() {
+        let Ok(curve) = PressureCurve::custom(pts(&[(0.0, 0.0), (1.0, 1.0)])) else {
+            panic!("valid curve rejected");
+        };
+        for i in 0..=20 {
+            let p = i as f32 / 20.0;
+            assert!((curve.apply(p) - p).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_custom_passes_through_control_points() {
+        let points = pts(&[(0.0, 0.1), (0.25, 0.16), (0.75, 0.84), (1.0, 0.9)]);
+        let Ok(curve) = CustomPressureCurve::new(points.clone()) else {
+            panic!("valid curve rejected");
+        };
+        for p in &points {
+            assert!(
+                (curve.apply(p.x) - p.y).abs() < 1e-3,
+                "{:?} -> {}",
+                p,
+                curve.apply(p.x)
+            );
+        }
+        // Endpoints are exact
+        assert_eq!(curve.apply(0.0), 0.1);
+        assert_eq!(curve.apply(1.0), 0.9);
+    }
+
+    #[test]
+    fn test_custom_is_monotone_with_steep_points() {
+        // Plateau followed by a jump: a natural spline would overshoot here
+        let Ok(curve) = PressureCurve::custom(pts(&[
+            (0.0, 0.0),
+            (0.4, 0.05),
+            (0.5, 0.05),
+            (0.55, 0.9),
+            (1.0, 1.0),
+        ])) else {
+            panic!("valid curve rejected");
+        };
+        assert_monotone(&curve);
+        // Flat segment stays flat
+        assert!((curve.apply(0.45) - 0.05).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_custom_holds_endpoints_outside_points() {
+        let Ok(curve) = PressureCurve::custom(pts(&[(0.8, 1.0), (0.2, 0.3)])) else {
+            panic!("valid curve rejected");
+        };
+        assert_eq!(curve.apply(0.0), 0.3);
+        assert!((curve.apply(0.1) - 0.3).abs() < 1e-6);
+        assert_eq!(curve.apply(1.0), 1.0);
+        assert!((curve.apply(0.9) - 1.0).abs() < 1e-6);
+        assert_eq!(curve.apply(-1.0), 0.3);
+    }
+
+    #[test]
+    fn test_custom_rejects_invalid_points() {
+        assert!(CustomPressureCurve::new(pts(&[(0.5, 0.5)])).is_err());
+        assert!(CustomPressureCurve::new(pts(&[(0.0, 0.0), (f32::NAN, 1.0)])).is_err());
+        assert!(CustomPressureCurve::new(pts(&[(0.0, 0.0), (0.5, 0.5), (0.5, 0.6)])).is_err());
+        assert!(CustomPressureCurve::new(pts(&[(0.0, 0.0), (0.5, 0.8), (1.0, 0.6)])).is_err());
+    }
+
+    #[test]
+    fn test_serde_roundtrip() {
+        let Ok(custom) = PressureCurve::custom(pts(&[(0.0, 0.0), (0.5, 0.72), (1.0, 1.0)])) else {
+            panic!("valid curve rejected");
+        };
+        for curve in [PressureCurve::SCurve, custom] {
+            let json = serde_json::to_string(&curve).expect("serialize");
+            let back: PressureCurve = serde_json::from_str(&json).expect("deserialize");
+            assert_eq!(back, curve);
+            assert_eq!(back.apply(0.3), curve.apply(0.3));
+        }
+
+        // Preset names stay compatible with existing tablet config
+        let linear: PressureCurve = serde_json::from_str("\"Linear\"").expect("deserialize");
+        assert_eq!(linear, PressureCurve::Linear);
+
+        // Invalid control points are rejected on load
+        let bad = r#"{"Custom":[{"x":0.0,"y":1.0},{"x":1.0,"y":0.0}]}"#;
+        assert!(serde_json::from_str::<PressureCurve>(bad).is_err());
+    }
+}
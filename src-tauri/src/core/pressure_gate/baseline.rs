@@ -0,0 +1,345 @@
+//! Frozen Krita baseline loader.
+//!
+//! Reads a capture directory under `artifacts/krita-pressure-full/baseline/`
+//! (stroke capture recorded with Krita's default tablet settings, plus its
+//! `krita_settings.json` and `meta.json`) into a `GateSuiteV1`. Each pen stroke
+//! becomes one case. Expected dabs come from the Krita dab export named by
+//! `meta.json`'s `dabs_file`; without it every case reports a missing baseline.
+
+use super::pipeline::normalize_source;
+use super::{
+    CurveOptionV1, DynamicSensorV1, GateCaseV1, GateInputSettingsV1, GatePresetV1, GateSuiteV1,
+    SensorInputV1,
+};
+use crate::core::contracts::{DabRequestV1, GateEnvV1, RawInputSampleV1};
+use crate::core::errors::CoreError;
+use crate::core::pressure_curve::PressureCurve;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Debug, Deserialize)]
+struct BaselineMeta {
+    baseline_version: String,
+    krita_version: String,
+    tablet: String,
+    os: String,
+    input_file: String,
+    /// Dabs recorded from Krita on the same capture
+    #[serde(default)]
+    dabs_file: Option<String>,
+}
+
+/// Dabs Krita painted for the capture, per preset id and then per pen stroke
+#[derive(Debug, Deserialize)]
+struct KritaDabExport {
+    presets: BTreeMap<String, Vec<Vec<DabRequestV1>>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KritaSettings {
+    #[serde(default = "default_true")]
+    pressure_enabled: bool,
+    #[serde(default)]
+    use_timestamps_for_brush_speed: bool,
+    max_allowed_speed_value: f32,
+    speed_value_smoothing: u32,
+    tablet_pressure_curve: String,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_one() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Deserialize)]
+struct StrokeCapture {
+    metadata: CaptureMetadata,
+    samples: Vec<CaptureSample>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CaptureMetadata {
+    tool: CaptureTool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CaptureTool {
+    brush_size: f32,
+    brush_spacing: f32,
+    #[serde(default = "default_one")]
+    brush_flow: f32,
+    #[serde(default = "default_one")]
+    brush_opacity: f32,
+    #[serde(default)]
+    pressure_size_enabled: bool,
+    #[serde(default)]
+    pressure_flow_enabled: bool,
+    #[serde(default)]
+    pressure_opacity_enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CaptureSample {
+    #[serde(rename = "type")]
+    event_type: String,
+    time_ms: f64,
+    x: f32,
+    y: f32,
+    pressure: f32,
+    #[serde(default)]
+    tilt_x: f32,
+    #[serde(default)]
+    tilt_y: f32,
+    pointer_type: String,
+}
+
+fn is_down(event_type: &str) -> bool {
+    event_type == "pointerdown"
+}
+
+fn is_up(event_type: &str) -> bool {
+    event_type == "pointerup" || event_type == "pointercancel"
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, CoreError> {
+    let bytes = std::fs::read(path)?;
+    serde_json::from_slice(&bytes)
+        .map_err(|e| CoreError::InvalidInput(format!("Invalid {}: {}", path.display(), e)))
+}
+
+impl CaptureSample {
+    fn to_raw(&self, seq: usize) -> RawInputSampleV1 {
+        let time_us = (self.time_ms * 1000.0).round().max(0.0) as u64;
+        let phase = if is_down(&self.event_type) {
+            "down"
+        } else if is_up(&self.event_type) {
+            "up"
+        } else {
+            "move"
+        };
+        RawInputSampleV1 {
+            x_px: self.x,
+            y_px: self.y,
+            pressure_01: self.pressure,
+            tilt_x_deg: self.tilt_x,
+            tilt_y_deg: self.tilt_y,
+            rotation_deg: 0.0,
+            device_time_us: time_us,
+            host_time_us: time_us,
+            // Browser pointer types are not backend aliases
+            source: normalize_source(&self.pointer_type)
+                .unwrap_or("pointerevent")
+                .to_string(),
+            phase: phase.to_string(),
+            seq: Some(seq as u64),
+        }
+    }
+}
+
+impl CaptureTool {
+    /// Same spacing and interval derivation as the frontend gate runner
+    fn preset(&self) -> GatePresetV1 {
+        let size = self.brush_size.max(1.0);
+        let spacing = self.brush_spacing.clamp(0.01, 10.0);
+        let pressure = || CurveOptionV1::with_sensor(DynamicSensorV1::new(SensorInputV1::Pressure));
+        let option = |enabled: bool| {
+            if enabled {
+                pressure()
+            } else {
+                CurveOptionV1::default()
+            }
+        };
+        GatePresetV1 {
+            preset_id: "capture_tool".to_string(),
+            preset_name: "captured brush settings".to_string(),
+            base_size_px: size,
+            base_flow_01: self.brush_flow,
+            base_opacity_01: self.brush_opacity,
+            spacing_px: (size * spacing).max(0.5),
+            max_interval_us: ((spacing * 20.0).clamp(8.0, 40.0) * 1000.0).round() as u64,
+            timed_spacing_enabled: false,
+            size: option(self.pressure_size_enabled),
+            flow: option(self.pressure_flow_enabled),
+            opacity: option(self.pressure_opacity_enabled),
+        }
+    }
+}
+
+/// Split the capture into pen strokes (pointer-down through pointer-up)
+fn split_strokes(samples: &[CaptureSample]) -> Vec<Vec<RawInputSampleV1>> {
+    let mut strokes = Vec::new();
+    let mut current: Option<Vec<RawInputSampleV1>> = None;
+    for (seq, sample) in samples.iter().enumerate() {
+        if is_down(&sample.event_type) {
+            if let Some(stroke) = current.take() {
+                strokes.push(stroke);
+            }
+            current = Some(Vec::new());
+        }
+        let Some(stroke) = current.as_mut() else {
+            continue;
+        };
+        stroke.push(sample.to_raw(seq));
+        if is_up(&sample.event_type) {
+            strokes.extend(current.take());
+        }
+    }
+    strokes.extend(current);
+    strokes.retain(|stroke| stroke.len() >= 2);
+    strokes
+}
+
+/// Load a frozen Krita capture directory as a gate suite
+pub fn load_krita_baseline(dir: &Path) -> Result<GateSuiteV1, CoreError> {
+    let meta: BaselineMeta = read_json(&dir.join("meta.json"))?;
+    let krita: KritaSettings = read_json(&dir.join("krita_settings.json"))?;
+    let capture: StrokeCapture = read_json(&dir.join(&meta.input_file))?;
+
+    let pressure_curve =
+        PressureCurve::from_preset_name(&krita.tablet_pressure_curve).ok_or_else(|| {
+            CoreError::InvalidInput(format!(
+                "Unsupported baseline pressure curve: {}",
+                krita.tablet_pressure_curve
+            ))
+        })?;
+    let settings = GateInputSettingsV1 {
+        pressure_enabled: krita.pressure_enabled,
+        pressure_curve,
+        use_device_time_for_speed: krita.use_timestamps_for_brush_speed,
+        max_allowed_speed_px_per_ms: krita.max_allowed_speed_value,
+        speed_smoothing_samples: krita.speed_value_smoothing,
+    };
+
+    let mut cases = split_strokes(&capture.samples)
+        .into_iter()
+        .enumerate()
+        .map(|(index, samples)| GateCaseV1 {
+            case_id: format!("S{:02}", index + 1),
+            case_name: format!("stroke_{:02}", index + 1),
+            samples,
+            expects_fast_windows: false,
+            baseline_dabs: BTreeMap::new(),
+        })
+        .collect::<Vec<_>>();
+    if cases.is_empty() {
+        return Err(CoreError::InvalidInput(format!(
+            "Baseline capture {} has no pen strokes",
+            meta.input_file
+        )));
+    }
+    if let Some(dabs_file) = &meta.dabs_file {
+        let export: KritaDabExport = read_json(&dir.join(dabs_file))?;
+        for (preset_id, strokes) in export.presets {
+            if strokes.len() != cases.len() {
+                return Err(CoreError::InvalidInput(format!(
+                    "{} has {} strokes for {}, capture has {}",
+                    dabs_file,
+                    strokes.len(),
+                    preset_id,
+                    cases.len()
+                )));
+            }
+            for (case, dabs) in cases.iter_mut().zip(strokes) {
+                case.baseline_dabs.insert(preset_id.clone(), dabs);
+            }
+        }
+    }
+
+    Ok(GateSuiteV1 {
+        baseline_version: meta.baseline_version,
+        env: GateEnvV1 {
+            krita_version: meta.krita_version,
+            tablet: meta.tablet,
+            os: meta.os,
+        },
+        settings,
+        presets: vec![capture.metadata.tool.preset()],
+        cases,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, name: &str, value: serde_json::Value) {
+        std::fs::write(dir.join(name), value.to_string()).expect("write fixture");
+    }
+
+    fn dab(x_px: f32) -> serde_json::Value {
+        serde_json::json!({
+            "x_px": x_px, "y_px": 50.0, "size_px": 10.0,
+            "flow_01": 1.0, "opacity_01": 1.0, "time_us": 1000
+        })
+    }
+
+    #[test]
+    fn krita_dab_export_fills_case_baselines() {
+        let dir = std::env::temp_dir().join(format!("sutu-krita-baseline-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create fixture dir");
+        write(
+            &dir,
+            "meta.json",
+            serde_json::json!({
+                "baseline_version": "test", "krita_version": "5.2", "tablet": "Wacom",
+                "os": "Windows 11", "input_file": "capture.json", "dabs_file": "krita_dabs.json"
+            }),
+        );
+        write(
+            &dir,
+            "krita_settings.json",
+            serde_json::json!({
+                "maxAllowedSpeedValue": 30, "speedValueSmoothing": 3,
+                "tabletPressureCurve": "linear"
+            }),
+        );
+        let sample = |event: &str, x: f32| {
+            serde_json::json!({
+                "type": event, "timeMs": x, "x": x, "y": 50.0, "pressure": 0.5,
+                "pointerType": "pen"
+            })
+        };
+        write(
+            &dir,
+            "capture.json",
+            serde_json::json!({
+                "metadata": { "tool": { "brushSize": 10, "brushSpacing": 0.1 } },
+                "samples": [
+                    sample("pointerdown", 0.0), sample("pointerup", 4.0),
+                    sample("pointerdown", 10.0), sample("pointerup", 14.0)
+                ]
+            }),
+        );
+        write(
+            &dir,
+            "krita_dabs.json",
+            serde_json::json!({ "presets": { "capture_tool": [[dab(0.0), dab(4.0)], [dab(10.0)]] } }),
+        );
+
+        let suite = load_krita_baseline(&dir).expect("load baseline");
+        assert_eq!(suite.cases.len(), 2);
+        let xs: Vec<f32> = suite.cases[1].baseline_dabs["capture_tool"]
+            .iter()
+            .map(|d| d.x_px)
+            .collect();
+        assert_eq!(xs, vec![10.0]);
+
+        // One stroke short of the capture
+        write(
+            &dir,
+            "krita_dabs.json",
+            serde_json::json!({ "presets": { "capture_tool": [[dab(0.0)]] } }),
+        );
+        let error = load_krita_baseline(&dir).expect_err("stroke count mismatch");
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(error.to_string().contains("has 1 strokes"), "{}", error);
+    }
+}
@@ -0,0 +1,1111 @@
+//! Native pressure-parity gate runner.
+//!
+//! Runs recorded `RawInputSampleV1` cases through the paint info pipeline, the
+//! sensor map / combiner and dab emission, then scores the produced dabs against
+//! Krita baseline `DabRequestV1` sequences. The result is a `GateArtifactV1` laid
+//! out like the frontend gate (`scripts/pressure/run-gate.mjs`).
+
+mod baseline;
+mod pipeline;
+mod sensor;
+
+pub use baseline::load_krita_baseline;
+
+pub use sensor::{
+    combine_curve_option, CurveCombineModeV1, CurveOptionV1, DynamicSensorV1, SensorDomainV1,
+    SensorInputV1,
+};
+
+use crate::core::contracts::{
+    DabRequestV1, GateArtifactV1, GateCaseResultV1, GateEnvV1, GateMetricMapV1, GatePresetResultV1,
+    GateRunMetaV1, GateSummaryV1, PaintInfoV1, RawInputSampleV1,
+};
+use crate::core::errors::CoreError;
+use crate::core::pressure_curve::PressureCurve;
+use pipeline::{AnomalyCounts, GatePhase, PipelineConfig, PressurePipeline};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub const GATE_PASS: &str = "pass";
+pub const GATE_FAIL: &str = "fail";
+pub const DEFAULT_THRESHOLD_VERSION: &str = "krita-pressure-thresholds.v1";
+
+const SOURCE_OF_TRUTH: [&str; 3] = [
+    "docs/research/2026-02-18-krita-wacom-pressure-full-chain.md",
+    "docs/testing/krita-pressure-full-gate-spec.md",
+    "docs/testing/krita-pressure-full-test-cases.md",
+];
+
+/// Penalties added once when produced and baseline dab counts differ
+const RATIO_MISMATCH_PENALTY: f64 = 1.0;
+const TIME_MISMATCH_PENALTY_US: f64 = 50_000.0;
+const FAST_SPEED_MIN_01: f64 = 0.75;
+const TAIL_PORTION: f64 = 0.2;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct StageThresholdsV1 {
+    pub pressure_curve_mae_max: f64,
+    pub pressure_curve_p95_max: f64,
+    pub speed_mae_max: f64,
+    pub speed_p95_max: f64,
+    pub dab_count_delta_max: f64,
+    pub carry_distance_error_px_max: f64,
+    pub carry_time_error_ms_max: f64,
+    pub pressure_mix_mae_max: f64,
+    pub speed_mix_mae_max: f64,
+    pub time_mix_mae_us_max: f64,
+    pub sensor_value_mae_max: f64,
+    pub sensor_value_p95_max: f64,
+    pub combiner_output_mae_max: f64,
+    pub combiner_output_p95_max: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct FinalThresholdsV1 {
+    pub width_profile_delta_max: f64,
+    pub tail_decay_delta_max: f64,
+    pub pixel_roi_delta_max: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct FastThresholdsV1 {
+    pub fast_window_min_required: u32,
+    pub fast_speed_p95_max: f64,
+    pub fast_speed_mae_max: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PresetThresholdsV1 {
+    pub sensor_map_mae_max: f64,
+    pub sensor_map_p95_max: f64,
+    pub combiner_output_mae_max: f64,
+    pub combiner_output_p95_max: f64,
+}
+
+/// Gate thresholds (`docs/testing/krita-pressure-thresholds.v1.json`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct GateThresholdsV1 {
+    pub version: String,
+    pub stage: StageThresholdsV1,
+    #[serde(rename = "final")]
+    pub final_stage: FinalThresholdsV1,
+    pub fast: FastThresholdsV1,
+    pub preset: PresetThresholdsV1,
+}
+
+impl Default for GateThresholdsV1 {
+    fn default() -> Self {
+        Self {
+            version: DEFAULT_THRESHOLD_VERSION.to_string(),
+            stage: StageThresholdsV1 {
+                pressure_curve_mae_max: 0.02,
+                pressure_curve_p95_max: 0.04,
+                speed_mae_max: 0.03,
+                speed_p95_max: 0.06,
+                dab_count_delta_max: 2.0,
+                carry_distance_error_px_max: 1.5,
+                carry_time_error_ms_max: 2.0,
+                pressure_mix_mae_max: 0.02,
+                speed_mix_mae_max: 0.03,
+                time_mix_mae_us_max: 1500.0,
+                sensor_value_mae_max: 0.02,
+                sensor_value_p95_max: 0.04,
+                combiner_output_mae_max: 0.03,
+                combiner_output_p95_max: 0.06,
+            },
+            final_stage: FinalThresholdsV1 {
+                width_profile_delta_max: 0.05,
+                tail_decay_delta_max: 0.07,
+                pixel_roi_delta_max: 0.12,
+            },
+            fast: FastThresholdsV1 {
+                fast_window_min_required: 1,
+                fast_speed_p95_max: 1.0,
+                fast_speed_mae_max: 0.04,
+            },
+            preset: PresetThresholdsV1 {
+                sensor_map_mae_max: 0.02,
+                sensor_map_p95_max: 0.04,
+                combiner_output_mae_max: 0.03,
+                combiner_output_p95_max: 0.06,
+            },
+        }
+    }
+}
+
+impl GateThresholdsV1 {
+    pub fn load(path: &Path) -> Result<Self, CoreError> {
+        let bytes = std::fs::read(path)?;
+        serde_json::from_slice(&bytes)
+            .map_err(|e| CoreError::InvalidInput(format!("Invalid gate thresholds: {}", e)))
+    }
+}
+
+/// Tablet settings the baseline was captured with (Krita defaults)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "snake_case")]
+pub struct GateInputSettingsV1 {
+    pub pressure_enabled: bool,
+    pub pressure_curve: PressureCurve,
+    pub use_device_time_for_speed: bool,
+    pub max_allowed_speed_px_per_ms: f32,
+    pub speed_smoothing_samples: u32,
+}
+
+impl Default for GateInputSettingsV1 {
+    fn default() -> Self {
+        Self {
+            pressure_enabled: true,
+            pressure_curve: PressureCurve::Linear,
+            use_device_time_for_speed: false,
+            max_allowed_speed_px_per_ms: 30.0,
+            speed_smoothing_samples: 3,
+        }
+    }
+}
+
+fn default_one() -> f32 {
+    1.0
+}
+
+fn default_max_interval_us() -> u64 {
+    16_000
+}
+
+/// Brush preset whose dynamics produce the dab requests
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct GatePresetV1 {
+    pub preset_id: String,
+    pub preset_name: String,
+    pub base_size_px: f32,
+    #[serde(default = "default_one")]
+    pub base_flow_01: f32,
+    #[serde(default = "default_one")]
+    pub base_opacity_01: f32,
+    pub spacing_px: f32,
+    #[serde(default = "default_max_interval_us")]
+    pub max_interval_us: u64,
+    #[serde(default)]
+    pub timed_spacing_enabled: bool,
+    #[serde(default)]
+    pub size: CurveOptionV1,
+    #[serde(default)]
+    pub flow: CurveOptionV1,
+    #[serde(default)]
+    pub opacity: CurveOptionV1,
+}
+
+impl GatePresetV1 {
+    /// Map one emitted paint info to a dab request through the sensor options
+    pub fn dab_request(&self, info: &PaintInfoV1) -> DabRequestV1 {
+        DabRequestV1 {
+            x_px: info.x_px,
+            y_px: info.y_px,
+            size_px: self.base_size_px * self.size.evaluate(info),
+            flow_01: (self.base_flow_01 * self.flow.evaluate(info)).clamp(0.0, 1.0),
+            opacity_01: (self.base_opacity_01 * self.opacity.evaluate(info)).clamp(0.0, 1.0),
+            time_us: info.time_us,
+        }
+    }
+
+    fn pipeline_config(&self, settings: &GateInputSettingsV1) -> PipelineConfig {
+        PipelineConfig {
+            pressure_enabled: settings.pressure_enabled,
+            pressure_curve: settings.pressure_curve.clone(),
+            use_device_time_for_speed: settings.use_device_time_for_speed,
+            max_allowed_speed_px_per_ms: settings.max_allowed_speed_px_per_ms as f64,
+            speed_smoothing_samples: settings.speed_smoothing_samples as usize,
+            spacing_px: self.spacing_px as f64,
+            max_interval_us: self.max_interval_us as f64,
+            timed_spacing_enabled: self.timed_spacing_enabled,
+        }
+    }
+}
+
+/// One recorded stroke with Krita's dabs per preset id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct GateCaseV1 {
+    pub case_id: String,
+    pub case_name: String,
+    pub samples: Vec<RawInputSampleV1>,
+    /// Require at least one fast window (fast flick style cases)
+    #[serde(default)]
+    pub expects_fast_windows: bool,
+    #[serde(default)]
+    pub baseline_dabs: BTreeMap<String, Vec<DabRequestV1>>,
+}
+
+/// Everything needed for one gate run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct GateSuiteV1 {
+    pub baseline_version: String,
+    pub env: GateEnvV1,
+    #[serde(default)]
+    pub settings: GateInputSettingsV1,
+    pub presets: Vec<GatePresetV1>,
+    pub cases: Vec<GateCaseV1>,
+}
+
+impl GateSuiteV1 {
+    pub fn load(path: &Path) -> Result<Self, CoreError> {
+        let bytes = std::fs::read(path)?;
+        serde_json::from_slice(&bytes)
+            .map_err(|e| CoreError::InvalidInput(format!("Invalid gate suite: {}", e)))
+    }
+
+    fn input_hash(&self) -> String {
+        let bytes = serde_json::to_vec(self).unwrap_or_default();
+        hex::encode(Sha256::digest(&bytes))
+    }
+}
+
+fn status(pass: bool) -> String {
+    if pass { GATE_PASS } else { GATE_FAIL }.to_string()
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn p95(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    sorted[((sorted.len() - 1) as f64 * 0.95).floor() as usize]
+}
+
+/// Paired absolute errors plus one penalty entry when lengths differ
+fn paired_errors<T>(
+    produced: &[T],
+    baseline: &[T],
+    penalty: f64,
+    value: impl Fn(&T, &T) -> f64,
+) -> Vec<f64> {
+    let mut errors: Vec<f64> = produced
+        .iter()
+        .zip(baseline)
+        .map(|(a, b)| value(a, b))
+        .collect();
+    if produced.len() != baseline.len() {
+        errors.push(penalty);
+    }
+    errors
+}
+
+fn tail_mean_size(dabs: &[DabRequestV1]) -> f64 {
+    if dabs.is_empty() {
+        return 0.0;
+    }
+    let count = ((dabs.len() as f64 * TAIL_PORTION).floor() as usize).max(1);
+    let sizes: Vec<f64> = dabs[dabs.len() - count..]
+        .iter()
+        .map(|d| d.size_px as f64)
+        .collect();
+    mean(&sizes)
+}
+
+fn dab_roi_area(dabs: &[DabRequestV1]) -> f64 {
+    let mut bounds: Option<(f64, f64, f64, f64)> = None;
+    for dab in dabs {
+        let radius = (dab.size_px as f64 * 0.5).max(0.5);
+        let (x, y) = (dab.x_px as f64, dab.y_px as f64);
+        let b = bounds.get_or_insert((x - radius, y - radius, x + radius, y + radius));
+        b.0 = b.0.min(x - radius);
+        b.1 = b.1.min(y - radius);
+        b.2 = b.2.max(x + radius);
+        b.3 = b.3.max(y + radius);
+    }
+    bounds.map_or(0.0, |(x0, y0, x1, y1)| {
+        (x1 - x0).max(0.0) * (y1 - y0).max(0.0)
+    })
+}
+
+/// Pipeline output for one case under one preset
+struct CaseRun {
+    current: Vec<PaintInfoV1>,
+    dabs: Vec<PaintInfoV1>,
+    /// Index of the input sample that emitted each dab
+    dab_sources: Vec<usize>,
+    anomalies: AnomalyCounts,
+}
+
+fn run_case_pipeline(samples: &[RawInputSampleV1], config: &PipelineConfig) -> CaseRun {
+    let mut pipeline = PressurePipeline::new(config);
+    let mut current = Vec::with_capacity(samples.len());
+    let mut dabs = Vec::new();
+    let mut dab_sources = Vec::new();
+    for (index, sample) in samples.iter().enumerate() {
+        let (info, emitted) = pipeline.process_sample(sample);
+        current.push(info);
+        dab_sources.extend(std::iter::repeat(index).take(emitted.len()));
+        dabs.extend(emitted);
+    }
+    let anomalies = pipeline.anomalies();
+    let tail = pipeline.finalize();
+    dab_sources.extend(std::iter::repeat(samples.len().saturating_sub(1)).take(tail.len()));
+    dabs.extend(tail);
+    CaseRun {
+        current,
+        dabs,
+        dab_sources,
+        anomalies,
+    }
+}
+
+/// Errors of one (case, preset) pair, poolable across presets and cases
+#[derive(Debug, Clone, Default)]
+struct PairErrors {
+    dab_count_delta: usize,
+    position_px: Vec<f64>,
+    time_us: Vec<f64>,
+    size_ratio: Vec<f64>,
+    combiner: Vec<f64>,
+    fast_size_ratio: Vec<f64>,
+    width_profile_delta: f64,
+    tail_decay_delta: f64,
+    pixel_roi_delta: f64,
+}
+
+impl PairErrors {
+    fn compare(
+        preset: &GatePresetV1,
+        produced: &[DabRequestV1],
+        baseline: &[DabRequestV1],
+        fast_dabs: &[bool],
+    ) -> Self {
+        let base_size = (preset.base_size_px as f64).max(1.0);
+        let size_ratio = |a: &DabRequestV1, b: &DabRequestV1| {
+            (a.size_px as f64 - b.size_px as f64).abs() / base_size
+        };
+        let mut combiner = paired_errors(produced, baseline, RATIO_MISMATCH_PENALTY, |a, b| {
+            (a.flow_01 as f64 - b.flow_01 as f64).abs()
+        });
+        combiner.extend(paired_errors(
+            produced,
+            baseline,
+            RATIO_MISMATCH_PENALTY,
+            |a, b| (a.opacity_01 as f64 - b.opacity_01 as f64).abs(),
+        ));
+        let fast_size_ratio = produced
+            .iter()
+            .zip(baseline)
+            .zip(fast_dabs)
+            .filter(|(_, fast)| **fast)
+            .map(|((a, b), _)| size_ratio(a, b))
+            .collect();
+
+        let mean_size = |dabs: &[DabRequestV1]| {
+            mean(&dabs.iter().map(|d| d.size_px as f64).collect::<Vec<_>>())
+        };
+        let roi_produced = dab_roi_area(produced);
+        let roi_baseline = dab_roi_area(baseline);
+        let pixel_roi_delta = if roi_produced <= 0.0 && roi_baseline <= 0.0 {
+            0.0
+        } else {
+            (roi_produced - roi_baseline).abs() / roi_produced.max(roi_baseline).max(1.0)
+        };
+
+        Self {
+            dab_count_delta: produced.len().abs_diff(baseline.len()),
+            position_px: paired_errors(produced, baseline, base_size, |a, b| {
+                (a.x_px as f64 - b.x_px as f64).hypot(a.y_px as f64 - b.y_px as f64)
+            }),
+            time_us: paired_errors(produced, baseline, TIME_MISMATCH_PENALTY_US, |a, b| {
+                a.time_us.abs_diff(b.time_us) as f64
+            }),
+            size_ratio: paired_errors(produced, baseline, RATIO_MISMATCH_PENALTY, size_ratio),
+            combiner,
+            fast_size_ratio,
+            width_profile_delta: (mean_size(produced) - mean_size(baseline)).abs() / base_size,
+            tail_decay_delta: (tail_mean_size(produced) - tail_mean_size(baseline)).abs()
+                / base_size,
+            pixel_roi_delta,
+        }
+    }
+
+    fn absorb(&mut self, other: PairErrors) {
+        self.dab_count_delta = self.dab_count_delta.max(other.dab_count_delta);
+        self.position_px.extend(other.position_px);
+        self.time_us.extend(other.time_us);
+        self.size_ratio.extend(other.size_ratio);
+        self.combiner.extend(other.combiner);
+        self.fast_size_ratio.extend(other.fast_size_ratio);
+        self.width_profile_delta = self.width_profile_delta.max(other.width_profile_delta);
+        self.tail_decay_delta = self.tail_decay_delta.max(other.tail_decay_delta);
+        self.pixel_roi_delta = self.pixel_roi_delta.max(other.pixel_roi_delta);
+    }
+
+    fn preset_pass(&self, t: &PresetThresholdsV1) -> bool {
+        mean(&self.size_ratio) <= t.sensor_map_mae_max
+            && p95(&self.size_ratio) <= t.sensor_map_p95_max
+            && mean(&self.combiner) <= t.combiner_output_mae_max
+            && p95(&self.combiner) <= t.combiner_output_p95_max
+    }
+}
+
+/// Input sanity and fast-window bookkeeping for one case
+#[derive(Debug, Clone, Default)]
+struct InputStats {
+    anomalies: AnomalyCounts,
+    speed_first_point_is_zero: bool,
+    fast_window_count: usize,
+    fast_window_min_required: u32,
+    fast_speeds: Vec<f64>,
+}
+
+impl InputStats {
+    fn absorb(&mut self, other: InputStats) {
+        self.anomalies.invalid_pressure += other.anomalies.invalid_pressure;
+        self.anomalies.non_monotonic += other.anomalies.non_monotonic;
+        self.anomalies.timestamp_jump += other.anomalies.timestamp_jump;
+        self.anomalies.source_alias_unresolved += other.anomalies.source_alias_unresolved;
+        self.speed_first_point_is_zero &= other.speed_first_point_is_zero;
+        self.fast_window_count += other.fast_window_count;
+        self.fast_window_min_required = self
+            .fast_window_min_required
+            .max(other.fast_window_min_required);
+        self.fast_speeds.extend(other.fast_speeds);
+    }
+}
+
+/// Sample indices whose drawing speed counts as a fast window
+fn fast_sample_mask(current: &[PaintInfoV1]) -> Vec<bool> {
+    let speeds: Vec<f64> = current.iter().map(|i| i.drawing_speed_01 as f64).collect();
+    let threshold = FAST_SPEED_MIN_01.max(p95(&speeds));
+    speeds.iter().map(|s| *s >= threshold).collect()
+}
+
+fn stage_metric_map(stats: &InputStats, errors: &PairErrors) -> GateMetricMapV1 {
+    BTreeMap::from([
+        (
+            "pressure_clamp_violation_count".to_string(),
+            json!(stats.anomalies.invalid_pressure),
+        ),
+        (
+            "source_alias_unresolved_count".to_string(),
+            json!(stats.anomalies.source_alias_unresolved),
+        ),
+        (
+            "timestamp_non_monotonic_count".to_string(),
+            json!(stats.anomalies.non_monotonic),
+        ),
+        (
+            "timestamp_jump_count".to_string(),
+            json!(stats.anomalies.timestamp_jump),
+        ),
+        (
+            "speed_first_point_is_zero".to_string(),
+            json!(stats.speed_first_point_is_zero),
+        ),
+        ("dab_count_delta".to_string(), json!(errors.dab_count_delta)),
+        (
+            "carry_distance_error_px".to_string(),
+            json!(p95(&errors.position_px)),
+        ),
+        (
+            "carry_time_error_ms".to_string(),
+            json!(p95(&errors.time_us) / 1000.0),
+        ),
+        ("time_mix_mae_us".to_string(), json!(mean(&errors.time_us))),
+        (
+            "sensor_value_mae".to_string(),
+            json!(mean(&errors.size_ratio)),
+        ),
+        (
+            "sensor_value_p95".to_string(),
+            json!(p95(&errors.size_ratio)),
+        ),
+        (
+            "combiner_output_mae".to_string(),
+            json!(mean(&errors.combiner)),
+        ),
+        (
+            "combiner_output_p95".to_string(),
+            json!(p95(&errors.combiner)),
+        ),
+    ])
+}
+
+fn final_metric_map(errors: &PairErrors) -> GateMetricMapV1 {
+    BTreeMap::from([
+        (
+            "width_profile_delta".to_string(),
+            json!(errors.width_profile_delta),
+        ),
+        (
+            "tail_decay_delta".to_string(),
+            json!(errors.tail_decay_delta),
+        ),
+        ("pixel_roi_delta".to_string(), json!(errors.pixel_roi_delta)),
+    ])
+}
+
+fn fast_metric_map(stats: &InputStats, errors: &PairErrors) -> GateMetricMapV1 {
+    BTreeMap::from([
+        (
+            "fast_window_count".to_string(),
+            json!(stats.fast_window_count),
+        ),
+        (
+            "fast_window_min_required".to_string(),
+            json!(stats.fast_window_min_required),
+        ),
+        ("fast_speed_p95".to_string(), json!(p95(&stats.fast_speeds))),
+        (
+            "fast_speed_mae".to_string(),
+            json!(mean(&errors.fast_size_ratio)),
+        ),
+    ])
+}
+
+struct GateDecision {
+    stage: bool,
+    final_stage: bool,
+    fast: bool,
+}
+
+impl GateDecision {
+    fn new(stats: &InputStats, errors: &PairErrors, t: &GateThresholdsV1) -> Self {
+        let s = &t.stage;
+        let stage = stats.anomalies.invalid_pressure == 0
+            && stats.anomalies.source_alias_unresolved == 0
+            && stats.anomalies.non_monotonic == 0
+            && stats.speed_first_point_is_zero
+            && errors.dab_count_delta as f64 <= s.dab_count_delta_max
+            && p95(&errors.position_px) <= s.carry_distance_error_px_max
+            && p95(&errors.time_us) / 1000.0 <= s.carry_time_error_ms_max
+            && mean(&errors.time_us) <= s.time_mix_mae_us_max
+            && mean(&errors.size_ratio) <= s.sensor_value_mae_max
+            && p95(&errors.size_ratio) <= s.sensor_value_p95_max
+            && mean(&errors.combiner) <= s.combiner_output_mae_max
+            && p95(&errors.combiner) <= s.combiner_output_p95_max;
+        let f = &t.final_stage;
+        let final_stage = errors.width_profile_delta <= f.width_profile_delta_max
+            && errors.tail_decay_delta <= f.tail_decay_delta_max
+            && errors.pixel_roi_delta <= f.pixel_roi_delta_max;
+        let fast = stats.fast_window_count >= stats.fast_window_min_required as usize
+            && p95(&stats.fast_speeds) <= t.fast.fast_speed_p95_max
+            && mean(&errors.fast_size_ratio) <= t.fast.fast_speed_mae_max;
+        Self {
+            stage,
+            final_stage,
+            fast,
+        }
+    }
+
+    fn pass(&self) -> bool {
+        self.stage && self.final_stage && self.fast
+    }
+
+    fn blocking_failures(&self) -> Vec<String> {
+        [
+            (self.stage, "stage_gate_failed"),
+            (self.final_stage, "final_gate_failed"),
+            (self.fast, "fast_gate_failed"),
+        ]
+        .iter()
+        .filter(|(pass, _)| !pass)
+        .map(|(_, name)| name.to_string())
+        .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct SemanticTally {
+    no_start_distance_gate: bool,
+    no_forced_zero_initial_pressure: bool,
+    pointerup_finalize: bool,
+    baseline_coverage: bool,
+}
+
+fn semantic_checks_for(samples: &[RawInputSampleV1], run: &CaseRun) -> (bool, bool, bool) {
+    let no_start_distance_gate = !run.dabs.is_empty();
+    let no_forced_zero = match (samples.first(), run.dabs.first()) {
+        (Some(input), Some(dab)) => input.pressure_01 <= 1e-4 || dab.pressure_01 > 1e-4,
+        _ => false,
+    };
+    let ends_with_up = samples
+        .last()
+        .is_some_and(|s| GatePhase::parse(&s.phase) == GatePhase::Up);
+    let pointerup_finalize = !ends_with_up
+        || match (samples.last(), run.dabs.last()) {
+            (Some(input), Some(dab)) => {
+                (dab.x_px - input.x_px).hypot(dab.y_px - input.y_px) <= 6.0
+                    && dab.time_us + 5_000 >= input.host_time_us
+            }
+            _ => false,
+        };
+    (no_start_distance_gate, no_forced_zero, pointerup_finalize)
+}
+
+/// `kp_native_<ms base36>` run id and the epoch-millisecond creation time
+fn run_identity() -> (String, String) {
+    let now_ms = crate::input::current_time_ms();
+    let mut millis = now_ms;
+    let mut digits = Vec::new();
+    loop {
+        let digit = (millis % 36) as u32;
+        digits.push(std::char::from_digit(digit, 36).unwrap_or('0'));
+        millis /= 36;
+        if millis == 0 {
+            break;
+        }
+    }
+    let run_id = format!("kp_native_{}", digits.iter().rev().collect::<String>());
+    (run_id, now_ms.to_string())
+}
+
+/// Run every case of `suite` and build the gate artifact
+pub fn run_pressure_gate(suite: &GateSuiteV1, thresholds: &GateThresholdsV1) -> GateArtifactV1 {
+    let mut case_results = Vec::with_capacity(suite.cases.len());
+    let mut total_stats = InputStats {
+        speed_first_point_is_zero: true,
+        ..InputStats::default()
+    };
+    let mut total_errors = PairErrors::default();
+    let mut semantic = SemanticTally {
+        no_start_distance_gate: true,
+        no_forced_zero_initial_pressure: true,
+        pointerup_finalize: true,
+        baseline_coverage: true,
+    };
+    let mut preset_errors: Vec<PairErrors> = vec![PairErrors::default(); suite.presets.len()];
+    let mut preset_case_status: Vec<BTreeMap<String, String>> =
+        vec![BTreeMap::new(); suite.presets.len()];
+
+    for case in &suite.cases {
+        let mut case_stats: Option<InputStats> = None;
+        let mut case_errors = PairErrors::default();
+        let mut missing = Vec::new();
+        let mut dab_count = 0usize;
+
+        for (preset_index, preset) in suite.presets.iter().enumerate() {
+            let run = run_case_pipeline(&case.samples, &preset.pipeline_config(&suite.settings));
+            let fast_mask = fast_sample_mask(&run.current);
+            if case_stats.is_none() {
+                let (start_gate, no_forced_zero, pointerup) =
+                    semantic_checks_for(&case.samples, &run);
+                semantic.no_start_distance_gate &= start_gate;
+                semantic.no_forced_zero_initial_pressure &= no_forced_zero;
+                semantic.pointerup_finalize &= pointerup;
+                dab_count = run.dabs.len();
+                case_stats = Some(InputStats {
+                    anomalies: run.anomalies,
+                    speed_first_point_is_zero: !run
+                        .current
+                        .first()
+                        .is_some_and(|info| info.drawing_speed_01 != 0.0),
+                    fast_window_count: fast_mask.iter().filter(|f| **f).count(),
+                    fast_window_min_required: if case.expects_fast_windows {
+                        thresholds.fast.fast_window_min_required
+                    } else {
+                        0
+                    },
+                    fast_speeds: run
+                        .current
+                        .iter()
+                        .zip(&fast_mask)
+                        .filter(|(_, fast)| **fast)
+                        .map(|(info, _)| info.drawing_speed_01 as f64)
+                        .collect(),
+                });
+            }
+
+            let Some(baseline) = case.baseline_dabs.get(&preset.preset_id) else {
+                missing.push(format!("missing_baseline:{}", preset.preset_id));
+                preset_case_status[preset_index].insert(case.case_id.clone(), status(false));
+                continue;
+            };
+            let produced: Vec<DabRequestV1> = run
+                .dabs
+                .iter()
+                .map(|info| preset.dab_request(info))
+                .collect();
+            let fast_dabs: Vec<bool> = run
+                .dab_sources
+                .iter()
+                .map(|&source| fast_mask.get(source).copied().unwrap_or(false))
+                .collect();
+            let errors = PairErrors::compare(preset, &produced, baseline, &fast_dabs);
+            preset_case_status[preset_index].insert(
+                case.case_id.clone(),
+                status(errors.preset_pass(&thresholds.preset)),
+            );
+            preset_errors[preset_index].absorb(errors.clone());
+            case_errors.absorb(errors);
+        }
+
+        let stats = case_stats.unwrap_or_default();
+        let decision = GateDecision::new(&stats, &case_errors, thresholds);
+        let stage_pass = decision.stage && missing.is_empty();
+        let overall = stage_pass && decision.pass();
+        let mut blocking = decision.blocking_failures();
+        if !missing.is_empty() && decision.stage {
+            blocking.insert(0, "stage_gate_failed".to_string());
+        }
+        semantic.baseline_coverage &= missing.is_empty();
+        blocking.extend(missing);
+
+        case_results.push(GateCaseResultV1 {
+            case_id: case.case_id.clone(),
+            case_name: case.case_name.clone(),
+            sample_count: case.samples.len() as u32,
+            dab_count: dab_count as u32,
+            stage_metrics: stage_metric_map(&stats, &case_errors),
+            final_metrics: final_metric_map(&case_errors),
+            fast_windows_metrics: fast_metric_map(&stats, &case_errors),
+            stage_gate: status(stage_pass),
+            final_gate: status(decision.final_stage),
+            fast_gate: status(decision.fast),
+            overall: status(overall),
+            blocking_failures: blocking,
+        });
+        total_stats.absorb(stats);
+        total_errors.absorb(case_errors);
+    }
+
+    let preset_results: Vec<GatePresetResultV1> = suite
+        .presets
+        .iter()
+        .zip(preset_errors)
+        .zip(preset_case_status)
+        .map(|((preset, errors), case_map)| {
+            let pass =
+                errors.preset_pass(&thresholds.preset) && case_map.values().all(|s| s == GATE_PASS);
+            let mut blocking = Vec::new();
+            if !pass {
+                blocking.push("preset_stage_failed".to_string());
+                blocking.extend(
+                    case_map
+                        .iter()
+                        .filter(|(_, s)| *s != GATE_PASS)
+                        .map(|(id, _)| format!("preset_case_failed:{}", id)),
+                );
+            }
+            GatePresetResultV1 {
+                preset_id: preset.preset_id.clone(),
+                preset_name: preset.preset_name.clone(),
+                case_results: case_map,
+                sensor_map_mae: mean(&errors.size_ratio) as f32,
+                sensor_map_p95: p95(&errors.size_ratio) as f32,
+                combiner_output_mae: mean(&errors.combiner) as f32,
+                combiner_output_p95: p95(&errors.combiner) as f32,
+                stage_gate: status(pass),
+                final_gate: status(pass),
+                fast_gate: status(pass),
+                overall: status(pass),
+                blocking_failures: blocking,
+            }
+        })
+        .collect();
+
+    let semantic_checks = BTreeMap::from([
+        (
+            "no_start_distance_gate".to_string(),
+            status(semantic.no_start_distance_gate),
+        ),
+        (
+            "no_forced_zero_initial_pressure_non_buildup".to_string(),
+            status(semantic.no_forced_zero_initial_pressure),
+        ),
+        (
+            "pointerup_finalize_consumes_pending_segment".to_string(),
+            status(semantic.pointerup_finalize),
+        ),
+        (
+            "baseline_covers_every_preset".to_string(),
+            status(semantic.baseline_coverage),
+        ),
+    ]);
+    let semantic_pass = semantic_checks.values().all(|s| s == GATE_PASS);
+
+    let decision = GateDecision::new(&total_stats, &total_errors, thresholds);
+    let stage_pass = decision.stage && semantic_pass;
+    let case_failures: Vec<&GateCaseResultV1> = case_results
+        .iter()
+        .filter(|c| c.overall != GATE_PASS)
+        .collect();
+    let preset_failures: Vec<&GatePresetResultV1> = preset_results
+        .iter()
+        .filter(|p| p.overall != GATE_PASS)
+        .collect();
+    let overall = stage_pass
+        && decision.final_stage
+        && decision.fast
+        && case_failures.is_empty()
+        && preset_failures.is_empty()
+        && !suite.cases.is_empty();
+
+    let mut blocking_failures = Vec::new();
+    if !stage_pass {
+        blocking_failures.push("stage_gate_failed".to_string());
+    }
+    if !decision.final_stage {
+        blocking_failures.push("final_gate_failed".to_string());
+    }
+    if !decision.fast {
+        blocking_failures.push("fast_gate_failed".to_string());
+    }
+    if suite.cases.is_empty() {
+        blocking_failures.push("no_cases".to_string());
+    }
+    blocking_failures.extend(
+        semantic_checks
+            .iter()
+            .filter(|(_, s)| *s != GATE_PASS)
+            .map(|(name, _)| format!("semantic_check_failed:{}", name)),
+    );
+    blocking_failures.extend(
+        case_failures
+            .iter()
+            .map(|c| format!("case_failed:{}", c.case_id)),
+    );
+    blocking_failures.extend(
+        preset_failures
+            .iter()
+            .map(|p| format!("preset_failed:{}", p.preset_id)),
+    );
+
+    let (run_id, created_at) = run_identity();
+    let summary = GateSummaryV1 {
+        overall: status(overall),
+        stage_gate: status(stage_pass),
+        final_gate: status(decision.final_stage),
+        fast_gate: status(decision.fast),
+        blocking_failures_count: blocking_failures.len() as u32,
+        case_passed: (case_results.len() - case_failures.len()) as u32,
+        case_total: case_results.len() as u32,
+        preset_passed: (preset_results.len() - preset_failures.len()) as u32,
+        preset_total: preset_results.len() as u32,
+    };
+
+    GateArtifactV1 {
+        run_meta: GateRunMetaV1 {
+            run_id,
+            created_at,
+            source_of_truth_version: SOURCE_OF_TRUTH.iter().map(|s| s.to_string()).collect(),
+            env: suite.env.clone(),
+        },
+        input_hash: suite.input_hash(),
+        baseline_version: suite.baseline_version.clone(),
+        threshold_version: thresholds.version.clone(),
+        stage_metrics: stage_metric_map(&total_stats, &total_errors),
+        final_metrics: final_metric_map(&total_errors),
+        fast_windows_metrics: fast_metric_map(&total_stats, &total_errors),
+        semantic_checks,
+        stage_gate: status(stage_pass),
+        final_gate: status(decision.final_stage),
+        fast_gate: status(decision.fast),
+        overall: status(overall),
+        blocking_failures,
+        case_results,
+        preset_results,
+        summary,
+    }
+}
+
+/// Write the artifact as `<output_root>/<run_id>/*.json` (same files as the frontend gate)
+pub fn write_gate_artifact(
+    artifact: &GateArtifactV1,
+    output_root: &Path,
+) -> Result<PathBuf, CoreError> {
+    let dir = output_root.join(&artifact.run_meta.run_id);
+    std::fs::create_dir_all(&dir)?;
+
+    write_json(&dir.join("stage_metrics.json"), &artifact.stage_metrics)?;
+    write_json(&dir.join("final_metrics.json"), &artifact.final_metrics)?;
+    write_json(
+        &dir.join("fast_windows_metrics.json"),
+        &artifact.fast_windows_metrics,
+    )?;
+    write_json(&dir.join("case_results.json"), &artifact.case_results)?;
+    write_json(&dir.join("preset_results.json"), &artifact.preset_results)?;
+    write_json(&dir.join("summary.json"), artifact)?;
+    Ok(dir)
+}
+
+fn write_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), CoreError> {
+    let bytes =
+        serde_json::to_vec_pretty(value).map_err(|e| CoreError::FileFormat(e.to_string()))?;
+    std::fs::write(path, bytes)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLOW_LUT: [f32; 3] = [0.0, 0.25, 1.0];
+
+    fn krita_suite() -> GateSuiteV1 {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .expect("repo root")
+            .join("artifacts/krita-pressure-full/baseline/krita-5.2-default-wintab");
+        load_krita_baseline(&dir).expect("load frozen Krita baseline")
+    }
+
+    /// Frozen Krita capture plus a flow-curve preset on the same brush, with
+    /// the native pipeline's own dabs as baseline. Only for testing the gate
+    /// mechanics; parity with Krita is `native_pipeline_matches_krita_dabs`.
+    fn frozen_suite() -> GateSuiteV1 {
+        let mut suite = krita_suite();
+        let captured = suite.presets[0].clone();
+        let flow_sensor = DynamicSensorV1 {
+            curve_lut: FLOW_LUT.to_vec(),
+            ..DynamicSensorV1::new(SensorInputV1::Pressure)
+        };
+        suite.presets.push(GatePresetV1 {
+            preset_id: "P1_pressure_flow_curve".to_string(),
+            preset_name: "pressure -> flow (curve)".to_string(),
+            base_opacity_01: 0.8,
+            size: CurveOptionV1::default(),
+            flow: CurveOptionV1::with_sensor(flow_sensor),
+            ..captured
+        });
+        for case in &mut suite.cases {
+            for preset in &suite.presets {
+                let run =
+                    run_case_pipeline(&case.samples, &preset.pipeline_config(&suite.settings));
+                let dabs = run.dabs.iter().map(|info| preset.dab_request(info));
+                case.baseline_dabs
+                    .insert(preset.preset_id.clone(), dabs.collect());
+            }
+        }
+        suite
+    }
+
+    #[test]
+    fn thresholds_default_matches_published_v1() {
+        let published: GateThresholdsV1 = serde_json::from_str(include_str!(
+            "../../../../docs/testing/krita-pressure-thresholds.v1.json"
+        ))
+        .expect("parse thresholds json");
+        assert_eq!(published, GateThresholdsV1::default());
+    }
+
+    /// Parity gate: the native pipeline must reproduce the dabs Krita painted
+    #[test]
+    #[ignore = "needs the Krita dab export, see docs/testing/krita-pressure-baseline-freeze-v1.md"]
+    fn native_pipeline_matches_krita_dabs() {
+        let suite = krita_suite();
+        assert!(suite
+            .cases
+            .iter()
+            .all(|case| case.baseline_dabs.contains_key("capture_tool")));
+
+        let artifact = run_pressure_gate(&suite, &GateThresholdsV1::default());
+        assert_eq!(
+            artifact.overall, GATE_PASS,
+            "blocking failures: {:?}\nstage: {:?}",
+            artifact.blocking_failures, artifact.stage_metrics
+        );
+    }
+
+    #[test]
+    fn matching_dabs_pass_and_artifact_roundtrips() {
+        let suite = frozen_suite();
+        assert_eq!(suite.baseline_version, "krita-5.2-default-wintab");
+        assert_eq!(suite.cases.len(), 11);
+        assert_eq!(suite.presets[0].spacing_px, 31.0 * 0.03);
+
+        let artifact = run_pressure_gate(&suite, &GateThresholdsV1::default());
+        assert_eq!(
+            artifact.overall, GATE_PASS,
+            "blocking failures: {:?}\nstage: {:?}",
+            artifact.blocking_failures, artifact.stage_metrics
+        );
+        assert_eq!(artifact.summary.case_passed, 11);
+        assert_eq!(artifact.summary.preset_passed, 2);
+        assert!(artifact.case_results.iter().all(|c| c.dab_count > 1));
+
+        let output_root = std::env::temp_dir().join(format!(
+            "sutu-pressure-gate-{}-{}",
+            std::process::id(),
+            artifact.run_meta.run_id
+        ));
+        let dir = write_gate_artifact(&artifact, &output_root).expect("write artifact");
+        let summary = std::fs::read(dir.join("summary.json")).expect("read summary");
+        let parsed: GateArtifactV1 = serde_json::from_slice(&summary).expect("parse summary");
+        assert_eq!(parsed.overall, GATE_PASS);
+        assert_eq!(parsed.input_hash, suite.input_hash());
+        assert!(dir.join("case_results.json").is_file());
+        let _ = std::fs::remove_dir_all(&output_root);
+    }
+
+    #[test]
+    fn gate_blocks_dab_regressions() {
+        let mut suite = frozen_suite();
+        // Baseline dabs 15% larger than what the pipeline produces for stroke 2
+        for dab in suite.cases[1]
+            .baseline_dabs
+            .get_mut("capture_tool")
+            .into_iter()
+            .flatten()
+        {
+            dab.size_px *= 1.15;
+        }
+        // ...and dabs missing from stroke 3
+        if let Some(dabs) = suite.cases[2]
+            .baseline_dabs
+            .get_mut("P1_pressure_flow_curve")
+        {
+            dabs.truncate(dabs.len().saturating_sub(3));
+        }
+
+        let artifact = run_pressure_gate(&suite, &GateThresholdsV1::default());
+        assert_eq!(artifact.overall, GATE_FAIL);
+        assert_eq!(artifact.case_results[0].overall, GATE_PASS);
+        assert_eq!(artifact.case_results[1].overall, GATE_FAIL);
+        assert_eq!(artifact.case_results[2].stage_gate, GATE_FAIL);
+        assert!(artifact
+            .blocking_failures
+            .contains(&"preset_failed:capture_tool".to_string()));
+        assert!(artifact
+            .blocking_failures
+            .contains(&"case_failed:S03".to_string()));
+    }
+
+    #[test]
+    fn missing_baseline_fails_and_suite_roundtrips() {
+        let mut suite = frozen_suite();
+        suite.cases[3]
+            .baseline_dabs
+            .remove("P1_pressure_flow_curve");
+
+        let json = serde_json::to_string(&suite).expect("serialize suite");
+        let path = std::env::temp_dir().join(format!(
+            "sutu-pressure-gate-suite-{}.json",
+            std::process::id()
+        ));
+        std::fs::write(&path, json).expect("write suite");
+        let loaded = GateSuiteV1::load(&path).expect("load suite");
+        let _ = std::fs::remove_file(&path);
+
+        let artifact = run_pressure_gate(&loaded, &GateThresholdsV1::default());
+        assert_eq!(
+            artifact.semantic_checks["baseline_covers_every_preset"],
+            GATE_FAIL
+        );
+        assert!(artifact.case_results[3]
+            .blocking_failures
+            .contains(&"missing_baseline:P1_pressure_flow_curve".to_string()));
+        assert_eq!(artifact.case_results[0].overall, GATE_PASS);
+    }
+}
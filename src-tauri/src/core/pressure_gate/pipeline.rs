@@ -0,0 +1,525 @@
+//! Krita-parity paint info pipeline.
+//!
+//! Native port of `src/engine/kritaParityInput/core` (paint info builder, speed
+//! smoother, segment sampler) so the gate runner sees the same dab emission as
+//! the frontend.
+
+use crate::core::contracts::{PaintInfoV1, RawInputSampleV1};
+use crate::core::pressure_curve::PressureCurve;
+use std::collections::VecDeque;
+
+const EPSILON: f64 = 1e-6;
+const TIMESTAMP_JUMP_US: u64 = 500_000;
+
+const DEFAULT_DT_US: f64 = 8_000.0;
+const MAX_VALID_DT_US: f64 = 120_000.0;
+const MIN_TRACKING_DISTANCE_PX: f64 = 5.0;
+const DT_WINDOW_SIZE: usize = 200;
+const DT_EFFECTIVE_PORTION: f64 = 0.8;
+const DISTANCE_HISTORY_LIMIT: usize = 512;
+
+const MAX_SEGMENT_SAMPLES: usize = 8192;
+const MIN_STEP_T: f64 = 1.0 / MAX_SEGMENT_SAMPLES as f64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GatePhase {
+    Hover,
+    Down,
+    Move,
+    Up,
+}
+
+impl GatePhase {
+    /// Unknown phases count as moves, matching the frontend normalizer
+    pub fn parse(phase: &str) -> Self {
+        match phase.trim().to_ascii_lowercase().as_str() {
+            "hover" => Self::Hover,
+            "down" => Self::Down,
+            "up" => Self::Up,
+            _ => Self::Move,
+        }
+    }
+}
+
+/// Canonical source name, or `None` for unknown aliases
+pub fn normalize_source(source: &str) -> Option<&'static str> {
+    match source.trim().to_ascii_lowercase().as_str() {
+        "wintab" | "win_tab" => Some("wintab"),
+        "macnative" | "mac_native" => Some("macnative"),
+        "pointerevent" | "pointer_event" => Some("pointerevent"),
+        "linuxevdev" | "linux_evdev" => Some("linuxevdev"),
+        _ => None,
+    }
+}
+
+fn clamp01(value: f64) -> f64 {
+    if !value.is_finite() {
+        return 0.0;
+    }
+    value.clamp(0.0, 1.0)
+}
+
+#[derive(Debug, Clone)]
+pub struct PipelineConfig {
+    pub pressure_enabled: bool,
+    pub pressure_curve: PressureCurve,
+    pub use_device_time_for_speed: bool,
+    pub max_allowed_speed_px_per_ms: f64,
+    pub speed_smoothing_samples: usize,
+    pub spacing_px: f64,
+    pub max_interval_us: f64,
+    pub timed_spacing_enabled: bool,
+}
+
+fn filtered_mean(values: &VecDeque<f64>) -> f64 {
+    match values.len() {
+        0 => return DEFAULT_DT_US,
+        1 => return values[0].max(1.0),
+        _ => {}
+    }
+
+    let useful = ((values.len() as f64 * DT_EFFECTIVE_PORTION).round() as usize).max(1);
+    let cut_total = values.len().saturating_sub(useful);
+    if cut_total == 0 {
+        return (values.iter().sum::<f64>() / values.len() as f64).max(1.0);
+    }
+
+    let mut sorted: Vec<f64> = values.iter().copied().collect();
+    sorted.sort_by(f64::total_cmp);
+    let cut_min = (cut_total as f64 * 0.5).round() as usize;
+    let cut_max = cut_total - cut_min;
+    let end = (sorted.len() - cut_max).max(cut_min + 1);
+    let sliced = &sorted[cut_min..end.min(sorted.len())];
+    if sliced.is_empty() {
+        return DEFAULT_DT_US;
+    }
+    (sliced.iter().sum::<f64>() / sliced.len() as f64).max(1.0)
+}
+
+/// Krita's drawing-speed estimator (KisSpeedSmoother)
+#[derive(Debug, Clone)]
+struct SpeedSmoother {
+    use_device_time: bool,
+    max_allowed_speed_px_per_ms: f64,
+    smoothing_samples: usize,
+    last_point: Option<(f64, f64)>,
+    last_time_us: f64,
+    last_speed_px_per_ms: f64,
+    distance_history: VecDeque<f64>,
+    dt_history_us: VecDeque<f64>,
+}
+
+impl SpeedSmoother {
+    fn new(config: &PipelineConfig) -> Self {
+        Self {
+            use_device_time: config.use_device_time_for_speed,
+            max_allowed_speed_px_per_ms: config.max_allowed_speed_px_per_ms.max(1.0),
+            smoothing_samples: config.speed_smoothing_samples.clamp(3, 100),
+            last_point: None,
+            last_time_us: 0.0,
+            last_speed_px_per_ms: 0.0,
+            distance_history: VecDeque::new(),
+            dt_history_us: VecDeque::new(),
+        }
+    }
+
+    fn next_speed_01(&mut self, sample: &RawInputSampleV1) -> f64 {
+        clamp01(self.next_speed_px_per_ms(sample) / self.max_allowed_speed_px_per_ms)
+    }
+
+    fn next_speed_px_per_ms(&mut self, sample: &RawInputSampleV1) -> f64 {
+        let time_us = if self.use_device_time {
+            sample.device_time_us
+        } else {
+            sample.host_time_us
+        } as f64;
+        let (x, y) = (sample.x_px as f64, sample.y_px as f64);
+
+        let Some((last_x, last_y)) = self.last_point else {
+            self.last_point = Some((x, y));
+            self.last_time_us = time_us;
+            self.last_speed_px_per_ms = 0.0;
+            return 0.0;
+        };
+
+        let distance_px = (x - last_x).hypot(y - last_y);
+        let dt_us = time_us - self.last_time_us;
+        if dt_us > 0.0 && dt_us <= MAX_VALID_DT_US {
+            self.dt_history_us.push_back(dt_us);
+            if self.dt_history_us.len() > DT_WINDOW_SIZE {
+                self.dt_history_us.pop_front();
+            }
+        }
+        let avg_dt_us = filtered_mean(&self.dt_history_us);
+
+        self.last_point = Some((x, y));
+        self.last_time_us = time_us;
+        self.distance_history.push_back(distance_px);
+        if self.distance_history.len() > DISTANCE_HISTORY_LIMIT {
+            self.distance_history.pop_front();
+        }
+
+        let mut total_distance_px = 0.0;
+        let mut total_time_us = 0.0;
+        for (items, distance) in self.distance_history.iter().rev().enumerate() {
+            total_distance_px += distance;
+            total_time_us += avg_dt_us;
+            if items + 1 > self.smoothing_samples && total_distance_px > MIN_TRACKING_DISTANCE_PX {
+                break;
+            }
+        }
+
+        if total_distance_px > MIN_TRACKING_DISTANCE_PX && total_time_us > 0.0 {
+            self.last_speed_px_per_ms = total_distance_px / (total_time_us / 1000.0);
+        }
+        self.last_speed_px_per_ms
+    }
+}
+
+/// Input anomalies seen while building paint infos
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AnomalyCounts {
+    pub invalid_pressure: u32,
+    pub non_monotonic: u32,
+    pub timestamp_jump: u32,
+    pub source_alias_unresolved: u32,
+}
+
+#[derive(Debug, Clone)]
+struct PaintInfoBuilder {
+    pressure_enabled: bool,
+    pressure_curve: PressureCurve,
+    speed: SpeedSmoother,
+    last_time_us: Option<u64>,
+    last_seq: Option<u64>,
+    anomalies: AnomalyCounts,
+}
+
+impl PaintInfoBuilder {
+    fn new(config: &PipelineConfig) -> Self {
+        Self {
+            pressure_enabled: config.pressure_enabled,
+            pressure_curve: config.pressure_curve.clone(),
+            speed: SpeedSmoother::new(config),
+            last_time_us: None,
+            last_seq: None,
+            anomalies: AnomalyCounts::default(),
+        }
+    }
+
+    fn build(&mut self, sample: &RawInputSampleV1) -> PaintInfoV1 {
+        let raw_pressure = sample.pressure_01;
+        if !raw_pressure.is_finite() || !(0.0..=1.0).contains(&raw_pressure) {
+            self.anomalies.invalid_pressure += 1;
+        }
+        if normalize_source(&sample.source).is_none() {
+            self.anomalies.source_alias_unresolved += 1;
+        }
+
+        let pressure_01 = if self.pressure_enabled {
+            self.pressure_curve.apply(raw_pressure)
+        } else {
+            1.0
+        };
+        let drawing_speed_01 = self.speed.next_speed_01(sample);
+
+        let time_us = if sample.host_time_us > 0 {
+            sample.host_time_us
+        } else {
+            sample.device_time_us
+        };
+        if let Some(last) = self.last_time_us {
+            if time_us < last {
+                self.anomalies.non_monotonic += 1;
+            }
+            if time_us.saturating_sub(last) > TIMESTAMP_JUMP_US {
+                self.anomalies.timestamp_jump += 1;
+            }
+        }
+        self.last_time_us = Some(time_us);
+
+        if let Some(seq) = sample.seq {
+            if self.last_seq.is_some_and(|last| seq <= last) {
+                self.anomalies.non_monotonic += 1;
+            }
+            self.last_seq = Some(seq);
+        }
+
+        PaintInfoV1 {
+            x_px: sample.x_px,
+            y_px: sample.y_px,
+            pressure_01: clamp01(pressure_01 as f64) as f32,
+            drawing_speed_01: drawing_speed_01 as f32,
+            time_us,
+        }
+    }
+}
+
+fn normalize_carry(value: f64, step: f64) -> f64 {
+    if step <= EPSILON {
+        return 0.0;
+    }
+    let carry = value % step;
+    if carry < EPSILON || (carry - step).abs() < EPSILON {
+        0.0
+    } else {
+        carry
+    }
+}
+
+/// Spacing sampler that carries distance/time remainders across segments
+#[derive(Debug, Clone, Default)]
+struct SegmentSampler {
+    distance_carry_px: f64,
+    time_carry_us: f64,
+}
+
+impl SegmentSampler {
+    fn push_steps(samples: &mut Vec<f64>, first_t: f64, step_t: f64) {
+        if !first_t.is_finite() || !step_t.is_finite() || step_t <= EPSILON {
+            return;
+        }
+        let step_t = step_t.max(MIN_STEP_T);
+        for i in 0..MAX_SEGMENT_SAMPLES {
+            let t = first_t + step_t * i as f64;
+            if t > 1.0 + EPSILON {
+                break;
+            }
+            if t > EPSILON {
+                samples.push(t.clamp(0.0, 1.0));
+            }
+        }
+    }
+
+    fn sample_segment(
+        &mut self,
+        distance_px: f64,
+        duration_us: f64,
+        spacing_px: f64,
+        max_interval_us: f64,
+        timed_spacing_enabled: bool,
+    ) -> Vec<f64> {
+        let distance_px = distance_px.max(0.0);
+        let duration_us = duration_us.max(0.0);
+        if distance_px <= EPSILON && duration_us <= EPSILON {
+            return Vec::new();
+        }
+
+        let mut samples = Vec::new();
+        if distance_px > EPSILON {
+            Self::push_steps(
+                &mut samples,
+                (spacing_px - self.distance_carry_px) / distance_px,
+                spacing_px / distance_px,
+            );
+        }
+        if timed_spacing_enabled && duration_us > EPSILON {
+            Self::push_steps(
+                &mut samples,
+                (max_interval_us - self.time_carry_us) / duration_us,
+                max_interval_us / duration_us,
+            );
+        }
+
+        self.distance_carry_px = normalize_carry(self.distance_carry_px + distance_px, spacing_px);
+        self.time_carry_us = if timed_spacing_enabled {
+            normalize_carry(self.time_carry_us + duration_us, max_interval_us)
+        } else {
+            0.0
+        };
+
+        samples.sort_by(f64::total_cmp);
+        samples.dedup_by(|b, a| (*b - *a).abs() <= 1e-4);
+        samples
+    }
+}
+
+/// Linear interpolation of every paint info channel
+pub fn mix_paint_info(from: &PaintInfoV1, to: &PaintInfoV1, t: f64) -> PaintInfoV1 {
+    let t = clamp01(t);
+    let lerp = |a: f32, b: f32| (a as f64 + (b as f64 - a as f64) * t) as f32;
+    let time_us = from.time_us as f64 + (to.time_us as f64 - from.time_us as f64) * t;
+    PaintInfoV1 {
+        x_px: lerp(from.x_px, to.x_px),
+        y_px: lerp(from.y_px, to.y_px),
+        pressure_01: lerp(from.pressure_01, to.pressure_01),
+        drawing_speed_01: lerp(from.drawing_speed_01, to.drawing_speed_01),
+        time_us: time_us.round().max(0.0) as u64,
+    }
+}
+
+fn same_point(a: &PaintInfoV1, b: &PaintInfoV1) -> bool {
+    (a.x_px as f64 - b.x_px as f64).abs() <= EPSILON
+        && (a.y_px as f64 - b.y_px as f64).abs() <= EPSILON
+        && a.time_us.abs_diff(b.time_us) <= 1
+}
+
+/// Raw samples -> paint infos -> spaced dab paint infos
+#[derive(Debug, Clone)]
+pub struct PressurePipeline {
+    builder: PaintInfoBuilder,
+    sampler: SegmentSampler,
+    spacing_px: f64,
+    max_interval_us: f64,
+    timed_spacing_enabled: bool,
+    last_info: Option<PaintInfoV1>,
+    last_phase: Option<GatePhase>,
+    seen_pointer_up: bool,
+    emitted_dab_count: usize,
+}
+
+impl PressurePipeline {
+    pub fn new(config: &PipelineConfig) -> Self {
+        Self {
+            builder: PaintInfoBuilder::new(config),
+            sampler: SegmentSampler::default(),
+            spacing_px: config.spacing_px.max(0.5),
+            max_interval_us: config.max_interval_us.max(1_000.0),
+            timed_spacing_enabled: config.timed_spacing_enabled,
+            last_info: None,
+            last_phase: None,
+            seen_pointer_up: false,
+            emitted_dab_count: 0,
+        }
+    }
+
+    pub fn anomalies(&self) -> AnomalyCounts {
+        self.builder.anomalies
+    }
+
+    /// Process one sample; returns its paint info and the dabs it emitted
+    pub fn process_sample(&mut self, sample: &RawInputSampleV1) -> (PaintInfoV1, Vec<PaintInfoV1>) {
+        let info = self.builder.build(sample);
+        let phase = GatePhase::parse(&sample.phase);
+
+        let Some(from) = self.last_info.replace(info.clone()) else {
+            self.last_phase = Some(phase);
+            self.seen_pointer_up = phase == GatePhase::Up;
+            if phase == GatePhase::Up {
+                self.emitted_dab_count += 1;
+                return (info.clone(), vec![info]);
+            }
+            return (info, Vec::new());
+        };
+
+        let to = &info;
+        let distance_px =
+            (to.x_px as f64 - from.x_px as f64).hypot(to.y_px as f64 - from.y_px as f64);
+        let duration_us = to.time_us.saturating_sub(from.time_us) as f64;
+        let ts = self.sampler.sample_segment(
+            distance_px,
+            duration_us,
+            self.spacing_px,
+            self.max_interval_us,
+            self.timed_spacing_enabled,
+        );
+
+        let mut mixed: Vec<PaintInfoV1> =
+            ts.iter().map(|&t| mix_paint_info(&from, to, t)).collect();
+        if ts.is_empty()
+            && distance_px <= EPSILON
+            && duration_us <= EPSILON
+            && phase == GatePhase::Up
+        {
+            mixed.push(to.clone());
+        }
+        if phase == GatePhase::Up && !mixed.last().is_some_and(|tail| same_point(tail, to)) {
+            mixed.push(to.clone());
+        }
+
+        self.last_phase = Some(phase);
+        self.seen_pointer_up |= phase == GatePhase::Up;
+        self.emitted_dab_count += mixed.len();
+        (info, mixed)
+    }
+
+    /// Flush the pending point when the stroke ended without a pointer-up dab
+    pub fn finalize(&mut self) -> Vec<PaintInfoV1> {
+        let Some(last) = self.last_info.take() else {
+            return Vec::new();
+        };
+        let emit = self.emitted_dab_count == 0
+            || (!self.seen_pointer_up && self.last_phase != Some(GatePhase::Up));
+        if emit {
+            vec![last]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(spacing_px: f64) -> PipelineConfig {
+        PipelineConfig {
+            pressure_enabled: true,
+            pressure_curve: PressureCurve::Linear,
+            use_device_time_for_speed: false,
+            max_allowed_speed_px_per_ms: 30.0,
+            speed_smoothing_samples: 3,
+            spacing_px,
+            max_interval_us: 16_000.0,
+            timed_spacing_enabled: false,
+        }
+    }
+
+    fn sample(x: f32, pressure: f32, time_us: u64, phase: &str) -> RawInputSampleV1 {
+        RawInputSampleV1 {
+            x_px: x,
+            y_px: 0.0,
+            pressure_01: pressure,
+            tilt_x_deg: 0.0,
+            tilt_y_deg: 0.0,
+            rotation_deg: 0.0,
+            device_time_us: time_us,
+            host_time_us: time_us,
+            source: "wintab".to_string(),
+            phase: phase.to_string(),
+            seq: None,
+        }
+    }
+
+    #[test]
+    fn sampler_carries_distance_across_segments() {
+        let mut sampler = SegmentSampler::default();
+        assert_eq!(
+            sampler.sample_segment(3.0, 0.0, 2.0, 16_000.0, false),
+            vec![2.0 / 3.0]
+        );
+        // 1px carried over: next dab lands 1px into the following segment
+        let ts = sampler.sample_segment(4.0, 0.0, 2.0, 16_000.0, false);
+        assert_eq!(ts, vec![0.25, 0.75]);
+    }
+
+    #[test]
+    fn pipeline_spaces_dabs_and_interpolates_pressure() {
+        let mut pipeline = PressurePipeline::new(&config(2.0));
+        let (_, first) = pipeline.process_sample(&sample(0.0, 0.2, 1_000, "down"));
+        assert!(first.is_empty());
+        let (_, dabs) = pipeline.process_sample(&sample(10.0, 0.6, 9_000, "move"));
+        assert_eq!(dabs.len(), 5);
+        assert!((dabs[0].x_px - 2.0).abs() < 1e-4);
+        assert!((dabs[0].pressure_01 - 0.28).abs() < 1e-4);
+        assert_eq!(dabs[4].time_us, 9_000);
+        let (_, tail) = pipeline.process_sample(&sample(11.0, 0.6, 9_800, "up"));
+        assert_eq!(tail.len(), 1);
+        assert!((tail[0].x_px - 11.0).abs() < 1e-6);
+        assert!(pipeline.finalize().is_empty());
+    }
+
+    #[test]
+    fn first_speed_is_zero_and_anomalies_are_counted() {
+        let mut pipeline = PressurePipeline::new(&config(2.0));
+        let (first, _) = pipeline.process_sample(&sample(0.0, 1.5, 1_000, "down"));
+        assert_eq!(first.drawing_speed_01, 0.0);
+        assert_eq!(first.pressure_01, 1.0);
+        pipeline.process_sample(&sample(10.0, 0.5, 900_000, "move"));
+        pipeline.process_sample(&sample(20.0, 0.5, 800_000, "move"));
+        let anomalies = pipeline.anomalies();
+        assert_eq!(anomalies.invalid_pressure, 1);
+        assert_eq!(anomalies.timestamp_jump, 1);
+        assert_eq!(anomalies.non_monotonic, 1);
+    }
+}
@@ -0,0 +1,220 @@
+//! Dynamic sensors and curve option combiner.
+//!
+//! Mirrors `kritaParityInput/core/dynamicSensor.ts` and `curveOptionCombiner.ts`.
+
+use crate::core::contracts::PaintInfoV1;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SensorInputV1 {
+    Pressure,
+    Speed,
+    Time,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SensorDomainV1 {
+    #[default]
+    Scaling,
+    Additive,
+    AbsoluteRotation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CurveCombineModeV1 {
+    #[default]
+    Multiply,
+    Add,
+    Max,
+    Min,
+    Difference,
+}
+
+/// One sensor feeding a curve option
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DynamicSensorV1 {
+    pub input: SensorInputV1,
+    #[serde(default)]
+    pub domain: SensorDomainV1,
+    /// Sensor curve LUT over [0, 1]; fewer than two entries means identity
+    #[serde(default)]
+    pub curve_lut: Vec<f32>,
+}
+
+impl DynamicSensorV1 {
+    pub fn new(input: SensorInputV1) -> Self {
+        Self {
+            input,
+            domain: SensorDomainV1::Scaling,
+            curve_lut: Vec::new(),
+        }
+    }
+
+    pub fn evaluate(&self, info: &PaintInfoV1) -> f32 {
+        let raw = match self.input {
+            SensorInputV1::Pressure => info.pressure_01,
+            SensorInputV1::Speed => info.drawing_speed_01,
+            SensorInputV1::Time => (info.time_us as f64 / 1_000_000.0).min(1.0) as f32,
+        };
+        let mapped = sample_lut(&self.curve_lut, to_curve_domain(raw, self.domain));
+        from_curve_domain(mapped, self.domain)
+    }
+}
+
+fn clamp01(value: f32) -> f32 {
+    if !value.is_finite() {
+        return 0.0;
+    }
+    value.clamp(0.0, 1.0)
+}
+
+fn sample_lut(lut: &[f32], value: f32) -> f32 {
+    let p = clamp01(value);
+    if lut.len() < 2 {
+        return p;
+    }
+    let pos = p * (lut.len() - 1) as f32;
+    let lo = pos.floor() as usize;
+    let hi = (lo + 1).min(lut.len() - 1);
+    let t = pos - lo as f32;
+    clamp01(lut[lo] + (lut[hi] - lut[lo]) * t)
+}
+
+fn to_curve_domain(value: f32, domain: SensorDomainV1) -> f32 {
+    match domain {
+        SensorDomainV1::Scaling => clamp01(value),
+        SensorDomainV1::Additive => clamp01((value + 1.0) * 0.5),
+        SensorDomainV1::AbsoluteRotation => clamp01(value.rem_euclid(360.0) / 360.0),
+    }
+}
+
+fn from_curve_domain(value: f32, domain: SensorDomainV1) -> f32 {
+    match domain {
+        SensorDomainV1::Scaling => clamp01(value),
+        SensorDomainV1::Additive => value * 2.0 - 1.0,
+        SensorDomainV1::AbsoluteRotation => value * 360.0,
+    }
+}
+
+fn default_constant() -> f32 {
+    1.0
+}
+
+/// A brush option driven by sensors (size, flow, opacity, ...)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct CurveOptionV1 {
+    #[serde(default = "default_constant")]
+    pub constant: f32,
+    #[serde(default)]
+    pub mode: CurveCombineModeV1,
+    #[serde(default)]
+    pub sensors: Vec<DynamicSensorV1>,
+}
+
+impl Default for CurveOptionV1 {
+    fn default() -> Self {
+        Self {
+            constant: 1.0,
+            mode: CurveCombineModeV1::Multiply,
+            sensors: Vec::new(),
+        }
+    }
+}
+
+impl CurveOptionV1 {
+    pub fn with_sensor(sensor: DynamicSensorV1) -> Self {
+        Self {
+            sensors: vec![sensor],
+            ..Self::default()
+        }
+    }
+
+    /// Combined option value in [0, 1]
+    pub fn evaluate(&self, info: &PaintInfoV1) -> f32 {
+        let values: Vec<f32> = self.sensors.iter().map(|s| s.evaluate(info)).collect();
+        combine_curve_option(self.constant, &values, self.mode)
+    }
+}
+
+/// Reduce sensor values with `mode`, scale by `constant` and clamp to [0, 1]
+pub fn combine_curve_option(constant: f32, values: &[f32], mode: CurveCombineModeV1) -> f32 {
+    let constant = if constant.is_finite() { constant } else { 1.0 };
+    let reduced = values
+        .iter()
+        .copied()
+        .reduce(|acc, value| match mode {
+            CurveCombineModeV1::Multiply => acc * value,
+            CurveCombineModeV1::Add => acc + value,
+            CurveCombineModeV1::Max => acc.max(value),
+            CurveCombineModeV1::Min => acc.min(value),
+            CurveCombineModeV1::Difference => (acc - value).abs(),
+        })
+        .unwrap_or(1.0);
+    clamp01(constant * reduced)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(pressure_01: f32, drawing_speed_01: f32) -> PaintInfoV1 {
+        PaintInfoV1 {
+            x_px: 0.0,
+            y_px: 0.0,
+            pressure_01,
+            drawing_speed_01,
+            time_us: 250_000,
+        }
+    }
+
+    #[test]
+    fn sensor_lut_and_domains() {
+        let mut sensor = DynamicSensorV1::new(SensorInputV1::Pressure);
+        sensor.curve_lut = vec![0.0, 0.25, 1.0];
+        assert!((sensor.evaluate(&info(0.5, 0.0)) - 0.25).abs() < 1e-6);
+        assert!((sensor.evaluate(&info(0.75, 0.0)) - 0.625).abs() < 1e-6);
+
+        let additive = DynamicSensorV1 {
+            input: SensorInputV1::Speed,
+            domain: SensorDomainV1::Additive,
+            curve_lut: Vec::new(),
+        };
+        assert!((additive.evaluate(&info(0.0, 0.5)) - 0.5).abs() < 1e-6);
+        assert!(
+            (DynamicSensorV1::new(SensorInputV1::Time).evaluate(&info(0.0, 0.0)) - 0.25).abs()
+                < 1e-6
+        );
+    }
+
+    #[test]
+    fn combiner_modes() {
+        let values = [0.8, 0.5];
+        assert!(
+            (combine_curve_option(1.0, &values, CurveCombineModeV1::Multiply) - 0.4).abs() < 1e-6
+        );
+        assert_eq!(
+            combine_curve_option(1.0, &values, CurveCombineModeV1::Add),
+            1.0
+        );
+        assert_eq!(
+            combine_curve_option(1.0, &values, CurveCombineModeV1::Max),
+            0.8
+        );
+        assert_eq!(
+            combine_curve_option(0.5, &values, CurveCombineModeV1::Min),
+            0.25
+        );
+        assert!(
+            (combine_curve_option(1.0, &values, CurveCombineModeV1::Difference) - 0.3).abs() < 1e-6
+        );
+        assert_eq!(
+            combine_curve_option(0.7, &[], CurveCombineModeV1::Multiply),
+            0.7
+        );
+    }
+}
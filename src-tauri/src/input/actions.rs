@@ -0,0 +1,377 @@
+//! Tablet button bindings
+//!
+//! Maps pen barrel buttons, express keys, touch rings/strips and multi-finger taps
+//! to named editor actions. Bindings are keyed by control (`"pen_barrel:1"`,
+//! `"express_key:0"`, `"touch_ring:0"`, `"touch_tap:2"`) and persisted as JSON next
+//! to the tool profiles.
+
+use super::backend::{TabletButtonKindV3, TabletDialKindV3, TabletEventV3};
+use super::config_file::{config_file_path, load_config_file, save_config_file};
+use super::gestures::TouchGestureV1;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub const TABLET_BINDINGS_FILE_NAME: &str = "tablet_bindings.json";
+const TABLET_BINDINGS_VERSION: u32 = 1;
+/// Control kind for touch taps; the index is the finger count
+pub const TOUCH_TAP_CONTROL: &str = "touch_tap";
+/// Ring/strip travel that counts as one step for stepped actions (1/24 of a turn)
+const DIAL_STEP_0_1: f32 = 1.0 / 24.0;
+
+/// Editor action a tablet control can trigger
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TabletActionV1 {
+    Undo,
+    Redo,
+    /// Eyedropper while held
+    ColorPick,
+    ToggleEraser,
+    BrushSizeUp,
+    BrushSizeDown,
+    /// Continuous size change from a ring or strip
+    BrushSize,
+}
+
+impl TabletActionV1 {
+    /// Action for travel in the opposite direction on a dial
+    fn reversed(self) -> Self {
+        match self {
+            Self::Undo => Self::Redo,
+            Self::Redo => Self::Undo,
+            Self::BrushSizeUp => Self::BrushSizeDown,
+            Self::BrushSizeDown => Self::BrushSizeUp,
+            other => other,
+        }
+    }
+}
+
+/// Payload of the `tablet-action-v1` event
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TabletActionEventV1 {
+    pub action: TabletActionV1,
+    /// Button edge; dial steps are always `true`. One-shot actions fire on press.
+    pub pressed: bool,
+    /// Dial travel for `brushSize` (signed, 0-1 per full ring/strip), otherwise 0
+    pub amount: f32,
+    /// Binding key of the control that fired
+    pub control: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TabletBindingsFileV1 {
+    version: u32,
+    bindings: BTreeMap<String, TabletActionV1>,
+}
+
+/// Binding key for a control: `"<kind>:<index>"`
+pub fn tablet_control_key(kind: &str, index: u32) -> String {
+    format!("{}:{}", kind, index)
+}
+
+pub fn default_tablet_bindings_path() -> PathBuf {
+    config_file_path(TABLET_BINDINGS_FILE_NAME)
+}
+
+/// Control-to-action bindings, optionally backed by a file
+#[derive(Debug, Clone)]
+pub struct TabletBindingStore {
+    path: Option<PathBuf>,
+    bindings: BTreeMap<String, TabletActionV1>,
+}
+
+impl Default for TabletBindingStore {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+impl TabletBindingStore {
+    fn default_bindings() -> BTreeMap<String, TabletActionV1> {
+        let pen = TabletButtonKindV3::PenBarrel.as_str();
+        let key = TabletButtonKindV3::ExpressKey.as_str();
+        BTreeMap::from([
+            (tablet_control_key(pen, 1), TabletActionV1::ColorPick),
+            (tablet_control_key(pen, 2), TabletActionV1::ToggleEraser),
+            (tablet_control_key(key, 0), TabletActionV1::Undo),
+            (tablet_control_key(key, 1), TabletActionV1::Redo),
+            (
+                tablet_control_key(TabletDialKindV3::TouchRing.as_str(), 0),
+                TabletActionV1::BrushSize,
+            ),
+            (
+                tablet_control_key(TabletDialKindV3::TouchStrip.as_str(), 0),
+                TabletActionV1::BrushSize,
+            ),
+            (
+                tablet_control_key(TOUCH_TAP_CONTROL, 2),
+                TabletActionV1::Undo,
+            ),
+            (
+                tablet_control_key(TOUCH_TAP_CONTROL, 3),
+                TabletActionV1::Redo,
+            ),
+        ])
+    }
+
+    /// Store with default bindings that is never written to disk
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            bindings: Self::default_bindings(),
+        }
+    }
+
+    /// Load bindings from `path`; a missing or unreadable file yields the defaults
+    pub fn load(path: &Path) -> Self {
+        let bindings = load_config_file::<TabletBindingsFileV1>(path, "tablet bindings")
+            .map(|file| file.bindings)
+            .unwrap_or_else(Self::default_bindings);
+        Self {
+            path: Some(path.to_path_buf()),
+            bindings,
+        }
+    }
+
+    pub fn bindings(&self) -> &BTreeMap<String, TabletActionV1> {
+        &self.bindings
+    }
+
+    pub fn get(&self, key: &str) -> Option<TabletActionV1> {
+        self.bindings.get(key).copied()
+    }
+
+    /// Bind (or unbind with `None`) the control `key` and persist
+    pub fn set(&mut self, key: &str, action: Option<TabletActionV1>) -> Result<(), String> {
+        if key.trim().is_empty() {
+            return Err("Tablet control key is empty".to_string());
+        }
+        match action {
+            Some(action) => {
+                self.bindings.insert(key.to_string(), action);
+            }
+            None => {
+                self.bindings.remove(key);
+            }
+        }
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let file = TabletBindingsFileV1 {
+            version: TABLET_BINDINGS_VERSION,
+            bindings: self.bindings.clone(),
+        };
+        save_config_file(path, &file, "tablet bindings")
+    }
+}
+
+/// Turns button and dial events from drained batches into bound actions
+#[derive(Debug, Clone, Default)]
+pub struct TabletActionMapper {
+    store: TabletBindingStore,
+    /// Unspent dial travel per control for stepped actions
+    dial_travel: BTreeMap<String, f32>,
+}
+
+impl TabletActionMapper {
+    pub fn new(store: TabletBindingStore) -> Self {
+        Self {
+            store,
+            dial_travel: BTreeMap::new(),
+        }
+    }
+
+    pub fn store(&self) -> &TabletBindingStore {
+        &self.store
+    }
+
+    pub fn store_mut(&mut self) -> &mut TabletBindingStore {
+        &mut self.store
+    }
+
+    pub fn map(&mut self, events: &[TabletEventV3]) -> Vec<TabletActionEventV1> {
+        let mut out = Vec::new();
+        for event in events {
+            match event {
+                TabletEventV3::Button(button) => {
+                    let control = tablet_control_key(button.kind.as_str(), button.index);
+                    if let Some(action) = self.store.get(&control) {
+                        out.push(TabletActionEventV1 {
+                            action,
+                            pressed: button.pressed,
+                            amount: 0.0,
+                            control,
+                        });
+                    }
+                }
+                TabletEventV3::Dial(dial) => {
+                    let control = tablet_control_key(dial.kind.as_str(), dial.index);
+                    if dial.position_0_1.is_none() {
+                        self.dial_travel.remove(&control);
+                        continue;
+                    }
+                    let Some(action) = self.store.get(&control) else {
+                        continue;
+                    };
+                    if action == TabletActionV1::BrushSize {
+                        if dial.delta != 0.0 {
+                            out.push(TabletActionEventV1 {
+                                action,
+                                pressed: true,
+                                amount: dial.delta,
+                                control,
+                            });
+                        }
+                        continue;
+                    }
+                    let travel = self.dial_travel.entry(control.clone()).or_insert(0.0);
+                    *travel += dial.delta;
+                    while travel.abs() >= DIAL_STEP_0_1 {
+                        let step = DIAL_STEP_0_1.copysign(*travel);
+                        *travel -= step;
+                        out.push(TabletActionEventV1 {
+                            action: if step > 0.0 {
+                                action
+                            } else {
+                                action.reversed()
+                            },
+                            pressed: true,
+                            amount: 0.0,
+                            control: control.clone(),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        out
+    }
+
+    /// Bound action for a recognized tap (fires as a press)
+    pub fn map_gesture(&self, gesture: &TouchGestureV1) -> Option<TabletActionEventV1> {
+        let TouchGestureV1::Tap { fingers } = gesture else {
+            return None;
+        };
+        let control = tablet_control_key(TOUCH_TAP_CONTROL, *fingers);
+        self.store.get(&control).map(|action| TabletActionEventV1 {
+            action,
+            pressed: true,
+            amount: 0.0,
+            control,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::backend::{TabletButtonEventV3, TabletDialEventV3};
+
+    fn button(kind: TabletButtonKindV3, index: u32, pressed: bool) -> TabletEventV3 {
+        TabletEventV3::Button(TabletButtonEventV3 {
+            kind,
+            index,
+            pressed,
+            device_id: "test".to_string(),
+            host_time_us: 1,
+        })
+    }
+
+    fn ring(position_0_1: Option<f32>, delta: f32) -> TabletEventV3 {
+        TabletEventV3::Dial(TabletDialEventV3 {
+            kind: TabletDialKindV3::TouchRing,
+            index: 0,
+            position_0_1,
+            delta,
+            device_id: "test".to_string(),
+            host_time_us: 1,
+        })
+    }
+
+    #[test]
+    fn default_bindings_map_buttons_and_ring() {
+        let mut mapper = TabletActionMapper::default();
+        let actions = mapper.map(&[
+            button(TabletButtonKindV3::PenBarrel, 1, true),
+            button(TabletButtonKindV3::PenBarrel, 1, false),
+            button(TabletButtonKindV3::ExpressKey, 0, true),
+            button(TabletButtonKindV3::ExpressKey, 9, true),
+            ring(Some(0.5), 0.0),
+            ring(Some(0.55), 0.05),
+        ]);
+        let summary: Vec<(TabletActionV1, bool)> =
+            actions.iter().map(|a| (a.action, a.pressed)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (TabletActionV1::ColorPick, true),
+                (TabletActionV1::ColorPick, false),
+                (TabletActionV1::Undo, true),
+                (TabletActionV1::BrushSize, true),
+            ]
+        );
+        assert_eq!(actions[2].control, "express_key:0");
+        assert!((actions[3].amount - 0.05).abs() < 1e-6);
+
+        let tap = mapper
+            .map_gesture(&TouchGestureV1::Tap { fingers: 3 })
+            .expect("three-finger tap");
+        assert_eq!(tap.action, TabletActionV1::Redo);
+        assert!(mapper
+            .map_gesture(&TouchGestureV1::Tap { fingers: 4 })
+            .is_none());
+    }
+
+    #[test]
+    fn stepped_dial_actions_accumulate_and_reverse() {
+        let mut store = TabletBindingStore::in_memory();
+        store
+            .set("touch_ring:0", Some(TabletActionV1::Undo))
+            .expect("bind");
+        let mut mapper = TabletActionMapper::new(store);
+
+        assert!(mapper.map(&[ring(Some(0.02), 0.02)]).is_empty());
+        let forward = mapper.map(&[ring(Some(0.1), 0.08)]);
+        assert_eq!(forward.len(), 2);
+        assert!(forward.iter().all(|a| a.action == TabletActionV1::Undo));
+
+        // Lifting discards leftover travel
+        mapper.map(&[ring(None, 0.0)]);
+        let back = mapper.map(&[ring(Some(0.5), 0.0), ring(Some(0.45), -0.05)]);
+        assert_eq!(back.len(), 1);
+        assert_eq!(back[0].action, TabletActionV1::Redo);
+    }
+
+    #[test]
+    fn binding_store_persists_changes() {
+        let path = std::env::temp_dir().join(format!(
+            "sutu-tablet-bindings-{}-{}.json",
+            std::process::id(),
+            super::super::current_time_us()
+        ));
+        let mut store = TabletBindingStore::load(&path);
+        store
+            .set("express_key:3", Some(TabletActionV1::BrushSizeUp))
+            .expect("bind");
+        store.set("pen_barrel:2", None).expect("unbind");
+
+        let reloaded = TabletBindingStore::load(&path);
+        assert_eq!(
+            reloaded.get("express_key:3"),
+            Some(TabletActionV1::BrushSizeUp)
+        );
+        assert_eq!(reloaded.get("pen_barrel:2"), None);
+        assert_eq!(
+            reloaded.get("pen_barrel:1"),
+            Some(TabletActionV1::ColorPick)
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+}
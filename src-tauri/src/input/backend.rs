@@ -3,14 +3,18 @@
 //! This module defines the common interface that all tablet backends must implement,
 //! allowing seamless switching between WinTab, PointerEvent, and other backends.
 
+use super::krita_v3::TabletMappingV1;
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 use std::sync::{Condvar, Mutex};
 use std::time::Duration;
 
 pub use super::krita_v3::types::{
-    InputPhaseV3 as InputPhase, InputSourceV3 as InputSource, NativeTabletEventV3,
+    CoalescedSpanV3, InputPhaseV3 as InputPhase, InputSourceV3 as InputSource, NativeTabletEventV3,
+    TabletButtonEventV3, TabletButtonKindV3, TabletDialEventV3, TabletDialKindV3, ToolChangeV3,
+    ToolTypeV3,
 };
+pub use crate::core::pressure_curve::PressureCurve;
 
 /// Tablet device information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,6 +47,15 @@ pub enum TabletStatus {
 pub enum TabletEventV3 {
     /// Input point received
     Input(NativeTabletEventV3),
+    /// Provisional point extrapolated past the newest input; superseded by the
+    /// next `Input` of the same stroke
+    Predicted(NativeTabletEventV3),
+    /// Active tool changed (e.g. eraser end flipped)
+    ToolChanged(ToolChangeV3),
+    /// Pen barrel button or express key pressed/released
+    Button(TabletButtonEventV3),
+    /// Touch ring or strip moved
+    Dial(TabletDialEventV3),
     /// Pen entered proximity
     ProximityEnter,
     /// Pen left proximity
@@ -59,6 +72,9 @@ pub enum InputBackpressureMode {
     Lossless,
     /// Keep latency bounded by dropping oldest queued samples when full.
     LatencyCapped,
+    /// Never block or drop: when full, merge Move samples into the queued tail
+    /// Move of the same stroke. Down/Up edges are always queued.
+    Coalescing,
 }
 
 impl InputBackpressureMode {
@@ -66,6 +82,7 @@ impl InputBackpressureMode {
         match self {
             Self::Lossless => 0,
             Self::LatencyCapped => 1,
+            Self::Coalescing => 2,
         }
     }
 }
@@ -74,6 +91,7 @@ impl From<u8> for InputBackpressureMode {
     fn from(value: u8) -> Self {
         match value {
             1 => Self::LatencyCapped,
+            2 => Self::Coalescing,
             _ => Self::Lossless,
         }
     }
@@ -85,6 +103,9 @@ pub struct InputQueueMetrics {
     pub enqueued: u64,
     pub dequeued: u64,
     pub dropped: u64,
+    /// Move samples merged into an already queued sample
+    #[serde(default)]
+    pub coalesced: u64,
     pub max_depth: usize,
     pub current_depth: usize,
     pub latency_p50_us: u64,
@@ -122,9 +143,12 @@ struct InputQueueState {
     enqueued: u64,
     dequeued: u64,
     dropped: u64,
+    coalesced: u64,
     max_depth: usize,
     latency_last_us: u64,
     latency_history_us: VecDeque<u64>,
+    /// Newest hover sample; hover is latest-value and never enters `events`
+    hover: Option<NativeTabletEventV3>,
 }
 
 impl InputQueueState {
@@ -136,9 +160,11 @@ impl InputQueueState {
             enqueued: 0,
             dequeued: 0,
             dropped: 0,
+            coalesced: 0,
             max_depth: 0,
             latency_last_us: 0,
             latency_history_us: VecDeque::with_capacity(LATENCY_HISTORY_LIMIT),
+            hover: None,
         }
     }
 
@@ -189,6 +215,7 @@ impl InputEventQueue {
     pub fn clear(&self) {
         if let Ok(mut state) = self.inner.lock() {
             state.events.clear();
+            state.hover = None;
             self.wake.notify_all();
         }
     }
@@ -203,44 +230,64 @@ impl InputEventQueue {
             return false;
         }
 
-        if self.mode == InputBackpressureMode::Lossless {
-            while guard.events.len() >= self.capacity && !guard.closed {
-                let wait_result = self
-                    .wake
-                    .wait_timeout(guard, Duration::from_millis(LOSSLESS_WAIT_SLICE_MS));
-                guard = match wait_result {
-                    Ok((lock, _)) => lock,
-                    Err(poisoned) => poisoned.into_inner().0,
-                };
+        if sample.phase == InputPhase::Hover {
+            // Hover only drives the cursor: coalesce instead of queueing so it can
+            // neither block the producer nor push stroke samples out
+            normalize_sample_axes(&mut sample);
+            guard.hover = Some(sample);
+            return true;
+        }
+
+        match self.mode {
+            InputBackpressureMode::Lossless => {
+                while guard.events.len() >= self.capacity && !guard.closed {
+                    let wait_result = self
+                        .wake
+                        .wait_timeout(guard, Duration::from_millis(LOSSLESS_WAIT_SLICE_MS));
+                    guard = match wait_result {
+                        Ok((lock, _)) => lock,
+                        Err(poisoned) => poisoned.into_inner().0,
+                    };
+                }
+                if guard.closed {
+                    return false;
+                }
             }
-            if guard.closed {
-                return false;
+            InputBackpressureMode::LatencyCapped => {
+                if guard.events.len() >= self.capacity {
+                    let mut dropped_now = 0u64;
+                    while guard.events.len() >= self.capacity {
+                        if guard.events.pop_front().is_some() {
+                            dropped_now += 1;
+                        } else {
+                            break;
+                        }
+                    }
+                    guard.dropped += dropped_now;
+                }
             }
-        } else if guard.events.len() >= self.capacity {
-            let mut dropped_now = 0u64;
-            while guard.events.len() >= self.capacity {
-                if guard.events.pop_front().is_some() {
-                    dropped_now += 1;
-                } else {
-                    break;
+            InputBackpressureMode::Coalescing => {
+                if guard.events.len() >= self.capacity && sample.phase == InputPhase::Move {
+                    if let Some(TabletEventV3::Input(tail)) = guard.events.back_mut() {
+                        if tail.phase == InputPhase::Move
+                            && tail.stroke_id == sample.stroke_id
+                            && tail.pointer_id == sample.pointer_id
+                        {
+                            normalize_sample_axes(&mut sample);
+                            coalesce_move(tail, sample);
+                            guard.coalesced = guard.coalesced.saturating_add(1);
+                            return true;
+                        }
+                    }
                 }
+                // Edges and samples with no mergeable tail go past capacity; the
+                // overshoot stays small because the next Move merges into them
             }
-            guard.dropped += dropped_now;
         }
 
         sample.seq = guard.next_seq;
         guard.next_seq = guard.next_seq.saturating_add(1);
-        sample.pressure_0_1 = sample.pressure_0_1.clamp(0.0, 1.0);
-        sample.tilt_x_deg = sample.tilt_x_deg.clamp(-90.0, 90.0);
-        sample.tilt_y_deg = sample.tilt_y_deg.clamp(-90.0, 90.0);
-        sample.rotation_deg = if sample.rotation_deg.is_finite() {
-            sample.rotation_deg.rem_euclid(360.0)
-        } else {
-            0.0
-        };
-        if sample.device_time_us.is_none() {
-            sample.device_time_us = Some(sample.host_time_us);
-        }
+        normalize_sample_axes(&mut sample);
 
         guard.events.push_back(TabletEventV3::Input(sample));
         guard.enqueued = guard.enqueued.saturating_add(1);
@@ -256,6 +303,9 @@ impl InputEventQueue {
         if guard.closed {
             return false;
         }
+        if matches!(event, TabletEventV3::ProximityLeave) {
+            guard.hover = None;
+        }
         guard.events.push_back(event);
         guard.max_depth = guard.max_depth.max(guard.events.len());
         true
@@ -282,6 +332,15 @@ impl InputEventQueue {
         count
     }
 
+    /// Take the newest hover sample, if one arrived since the last call
+    pub fn take_hover(&self) -> Option<NativeTabletEventV3> {
+        let mut guard = match self.inner.lock() {
+            Ok(lock) => lock,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        guard.hover.take()
+    }
+
     pub fn metrics_snapshot(&self) -> InputQueueMetrics {
         let guard = match self.inner.lock() {
             Ok(lock) => lock,
@@ -306,6 +365,7 @@ impl InputEventQueue {
             enqueued: guard.enqueued,
             dequeued: guard.dequeued,
             dropped: guard.dropped,
+            coalesced: guard.coalesced,
             max_depth: guard.max_depth,
             current_depth: guard.events.len(),
             latency_p50_us: quantile(0.50),
@@ -320,17 +380,53 @@ impl InputEventQueue {
     }
 }
 
+/// Fold `sample` into the queued `tail`: latest position/pressure, peak pressure kept
+fn coalesce_move(tail: &mut NativeTabletEventV3, sample: NativeTabletEventV3) {
+    let span = tail.coalesced.unwrap_or(CoalescedSpanV3 {
+        sample_count: 1,
+        max_pressure_0_1: tail.pressure_0_1,
+    });
+    let seq = tail.seq;
+    *tail = NativeTabletEventV3 {
+        seq,
+        coalesced: Some(CoalescedSpanV3 {
+            sample_count: span.sample_count.saturating_add(1),
+            max_pressure_0_1: span.max_pressure_0_1.max(sample.pressure_0_1),
+        }),
+        ..sample
+    };
+}
+
+fn normalize_sample_axes(sample: &mut NativeTabletEventV3) {
+    sample.pressure_0_1 = sample.pressure_0_1.clamp(0.0, 1.0);
+    sample.tilt_x_deg = sample.tilt_x_deg.clamp(-90.0, 90.0);
+    sample.tilt_y_deg = sample.tilt_y_deg.clamp(-90.0, 90.0);
+    sample.rotation_deg = if sample.rotation_deg.is_finite() {
+        sample.rotation_deg.rem_euclid(360.0)
+    } else {
+        0.0
+    };
+    if sample.device_time_us.is_none() {
+        sample.device_time_us = Some(sample.host_time_us);
+    }
+}
+
 /// Configuration for tablet backend
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TabletConfig {
     /// Polling rate in Hz (for polling-based backends)
     pub polling_rate_hz: u32,
-    /// Enable input prediction
+    /// Append `TabletEventV3::Predicted` samples to each batch, which the
+    /// canvas draws as provisional ink ahead of the stroke
     pub prediction_enabled: bool,
-    /// Pressure curve type
+    /// Global pressure curve; the canvas applies it to samples from every
+    /// backend, so backends report raw pressure
     pub pressure_curve: PressureCurve,
     /// Queue backpressure mode.
     pub backpressure_mode: InputBackpressureMode,
+    /// Tablet area, rotation and monitor for backends that map raw coordinates
+    #[serde(default)]
+    pub mapping: TabletMappingV1,
 }
 
 impl Default for TabletConfig {
@@ -340,35 +436,7 @@ impl Default for TabletConfig {
             prediction_enabled: true,
             pressure_curve: PressureCurve::Linear,
             backpressure_mode: InputBackpressureMode::Lossless,
-        }
-    }
-}
-
-/// Pressure curve types for mapping raw pressure to output
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum PressureCurve {
-    /// Linear mapping (1:1)
-    Linear,
-    /// Soft curve (easier light pressure)
-    Soft,
-    /// Hard curve (requires more pressure)
-    Hard,
-    /// S-curve (soft at extremes, linear in middle)
-    SCurve,
-}
-
-impl PressureCurve {
-    /// Apply the pressure curve to a normalized pressure value (0.0 - 1.0)
-    pub fn apply(&self, pressure: f32) -> f32 {
-        let p = pressure.clamp(0.0, 1.0);
-        match self {
-            PressureCurve::Linear => p,
-            PressureCurve::Soft => p.sqrt(),
-            PressureCurve::Hard => p * p,
-            PressureCurve::SCurve => {
-                // S-curve using smoothstep
-                p * p * (3.0 - 2.0 * p)
-            }
+            mapping: TabletMappingV1::default(),
         }
     }
 }
@@ -394,6 +462,11 @@ pub trait TabletBackend: Send {
     /// Returns the number of events retrieved
     fn poll(&mut self, events: &mut Vec<TabletEventV3>) -> usize;
 
+    /// Newest hover sample since the last call; hover bypasses `poll`.
+    fn take_hover(&mut self) -> Option<NativeTabletEventV3> {
+        None
+    }
+
     /// Queue telemetry metrics for diagnostics/status response.
     fn queue_metrics(&self) -> InputQueueMetrics;
 
@@ -433,6 +506,13 @@ mod tests {
             rotation_deg: 0.0,
             host_time_us,
             device_time_us: Some(host_time_us),
+            tool_type: ToolTypeV3::Pen,
+            tool_serial: None,
+            wheel_0_1: None,
+            distance_0_1: None,
+            coalesced: None,
+            predicted: false,
+            prediction_index: None,
         }
     }
 
@@ -536,6 +616,90 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_coalescing_queue_merges_moves_and_keeps_edges() {
+        let queue = InputEventQueue::new(InputBackpressureMode::Coalescing, 2);
+        queue.reopen();
+
+        let mut down = make_sample(1_000);
+        down.phase = InputPhase::Down;
+        assert!(queue.enqueue_sample(down));
+        assert!(queue.enqueue_sample(make_sample(2_000)));
+        for (i, pressure) in [0.9, 0.3, 0.4].into_iter().enumerate() {
+            let mut sample = make_sample(3_000 + i as u64 * 1_000);
+            sample.pressure_0_1 = pressure;
+            sample.x_px = 300.0 + i as f32;
+            assert!(queue.enqueue_sample(sample));
+        }
+        let mut up = make_sample(6_000);
+        up.phase = InputPhase::Up;
+        assert!(queue.enqueue_sample(up));
+
+        let metrics = queue.metrics_snapshot();
+        assert_eq!(metrics.coalesced, 3);
+        assert_eq!(metrics.dropped, 0);
+
+        let mut out = Vec::new();
+        queue.drain_into(&mut out, || 7_000);
+        let samples: Vec<&NativeTabletEventV3> = out
+            .iter()
+            .filter_map(|e| match e {
+                TabletEventV3::Input(sample) => Some(sample),
+                _ => None,
+            })
+            .collect();
+        let phases: Vec<InputPhase> = samples.iter().map(|s| s.phase).collect();
+        assert_eq!(
+            phases,
+            vec![InputPhase::Down, InputPhase::Move, InputPhase::Up]
+        );
+        let merged = samples[1];
+        assert_eq!(merged.x_px, 302.0);
+        assert_eq!(merged.pressure_0_1, 0.4);
+        assert_eq!(merged.host_time_us, 5_000);
+        assert_eq!(
+            merged.coalesced,
+            Some(CoalescedSpanV3 {
+                sample_count: 4,
+                max_pressure_0_1: 0.9,
+            })
+        );
+        assert!(samples.windows(2).all(|w| w[0].seq < w[1].seq));
+    }
+
+    #[test]
+    fn test_hover_is_coalesced_outside_the_stroke_queue() {
+        let queue = InputEventQueue::new(InputBackpressureMode::LatencyCapped, 2);
+        queue.reopen();
+        for i in 0..2 {
+            assert!(queue.enqueue_sample(make_sample(1_000 + i)));
+        }
+        for i in 0..5u64 {
+            let mut hover = make_sample(2_000 + i);
+            hover.phase = InputPhase::Hover;
+            hover.rotation_deg = -90.0;
+            assert!(queue.enqueue_sample(hover));
+        }
+
+        let hover = queue.take_hover().expect("hover sample");
+        assert_eq!(hover.host_time_us, 2_004);
+        assert_eq!(hover.rotation_deg, 270.0);
+        assert!(queue.take_hover().is_none());
+
+        let mut out = Vec::new();
+        assert_eq!(queue.drain_into(&mut out, || 3_000), 2);
+        assert_eq!(queue.metrics_snapshot().dropped, 0);
+        assert!(out
+            .iter()
+            .all(|e| matches!(e, TabletEventV3::Input(s) if s.phase == InputPhase::Move)));
+
+        let mut hover = make_sample(4_000);
+        hover.phase = InputPhase::Hover;
+        queue.enqueue_sample(hover);
+        queue.enqueue_event(TabletEventV3::ProximityLeave);
+        assert!(queue.take_hover().is_none());
+    }
+
     #[test]
     fn test_input_source_mac_native_serialization() {
         let encoded = serde_json::to_string(&InputSource::MacNative).expect("serialize source");
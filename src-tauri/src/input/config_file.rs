@@ -0,0 +1,40 @@
+//! JSON settings files in the app config directory
+//!
+//! Shared by the tool profile, binding and mapping stores. Writes go through
+//! `index_file::write_atomic`, so a crash leaves either the old or the new file.
+
+use crate::app_meta::APP_CONFIG_DIR_NAME;
+use crate::brush::index_file::write_atomic;
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::{Path, PathBuf};
+
+/// `<data dir>/<app config dir>/<file_name>`
+pub(crate) fn config_file_path(file_name: &str) -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(APP_CONFIG_DIR_NAME)
+        .join(file_name)
+}
+
+/// Read `path`; `None` when it is missing or invalid (invalid files are logged)
+pub(crate) fn load_config_file<T: DeserializeOwned>(path: &Path, what: &str) -> Option<T> {
+    let bytes = std::fs::read(path).ok()?;
+    match serde_json::from_slice(&bytes) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            tracing::warn!("Ignoring invalid {} {}: {}", what, path.display(), e);
+            None
+        }
+    }
+}
+
+/// Write `value` as pretty JSON to `path`
+pub(crate) fn save_config_file<T: Serialize>(
+    path: &Path,
+    value: &T,
+    what: &str,
+) -> Result<(), String> {
+    let json = serde_json::to_vec_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", what, e))?;
+    write_atomic(path, &json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
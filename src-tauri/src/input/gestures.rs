@@ -0,0 +1,435 @@
+//! Touch gesture recognition
+//!
+//! Finger contacts (`ToolTypeV3::Touch`) never become strokes. `GestureRecognizer`
+//! turns them into two-finger pan/zoom/rotate transforms and multi-finger taps,
+//! delivered on their own `tablet-gesture-v1` channel. Touches that start while the
+//! pen is in proximity (or was just used) are treated as a resting palm and ignored.
+
+use super::backend::{InputPhase, TabletEventV3, ToolTypeV3};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Longest contact sequence that still counts as a tap
+const TAP_MAX_DURATION_US: u64 = 250_000;
+/// Finger travel that turns a tap into a drag
+const TAP_MAX_TRAVEL_PX: f32 = 12.0;
+/// Touches this soon after the last pen sample are palm contacts
+const PALM_REJECTION_GRACE_US: u64 = 500_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TouchGesturePhaseV1 {
+    Begin,
+    Update,
+    End,
+}
+
+/// Payload of the `tablet-gesture-v1` event
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum TouchGestureV1 {
+    /// Two-finger transform; values are relative to the previous update
+    Transform {
+        phase: TouchGesturePhaseV1,
+        center_x_px: f32,
+        center_y_px: f32,
+        pan_x_px: f32,
+        pan_y_px: f32,
+        scale: f32,
+        rotation_deg: f32,
+    },
+    /// Short touch of several fingers without movement
+    Tap { fingers: u32 },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Contact {
+    start_x_px: f32,
+    start_y_px: f32,
+    x_px: f32,
+    y_px: f32,
+}
+
+/// Centroid, span and angle of the two contacts driving a transform
+#[derive(Debug, Clone, Copy)]
+struct PairPose {
+    ids: (u32, u32),
+    center_x_px: f32,
+    center_y_px: f32,
+    distance_px: f32,
+    angle_deg: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TapSequence {
+    start_us: u64,
+    max_fingers: u32,
+    disqualified: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GestureRecognizer {
+    contacts: BTreeMap<u32, Contact>,
+    /// Palm contacts, ignored until lifted
+    rejected: BTreeSet<u32>,
+    pair: Option<PairPose>,
+    sequence: Option<TapSequence>,
+    pen_in_proximity: bool,
+    pen_last_seen_us: Option<u64>,
+    pending: Vec<TouchGestureV1>,
+}
+
+impl GestureRecognizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    fn pen_active(&self, now_us: u64) -> bool {
+        self.pen_in_proximity
+            || self
+                .pen_last_seen_us
+                .is_some_and(|last| now_us.saturating_sub(last) < PALM_REJECTION_GRACE_US)
+    }
+
+    /// Track pen proximity from a drained batch; pen activity cancels touch gestures
+    pub fn observe_pen(&mut self, events: &[TabletEventV3], now_us: u64) {
+        for event in events {
+            match event {
+                TabletEventV3::ProximityEnter => self.pen_in_proximity = true,
+                TabletEventV3::ProximityLeave => {
+                    self.pen_in_proximity = false;
+                    self.pen_last_seen_us = Some(now_us);
+                }
+                TabletEventV3::Input(sample) if sample.tool_type != ToolTypeV3::Touch => {
+                    self.pen_last_seen_us = Some(sample.host_time_us.max(1));
+                }
+                _ => {}
+            }
+        }
+        if self.pen_active(now_us) && !self.contacts.is_empty() {
+            self.reject_all();
+        }
+    }
+
+    /// Demote every live contact to a palm contact
+    fn reject_all(&mut self) {
+        self.end_transform();
+        let ids: Vec<u32> = self.contacts.keys().copied().collect();
+        self.rejected.extend(ids);
+        self.contacts.clear();
+        if let Some(sequence) = &mut self.sequence {
+            sequence.disqualified = true;
+        }
+    }
+
+    pub fn push_touch(
+        &mut self,
+        pointer_id: u32,
+        phase: InputPhase,
+        x_px: f32,
+        y_px: f32,
+        host_time_us: u64,
+    ) {
+        match phase {
+            InputPhase::Hover => {}
+            InputPhase::Down => {
+                if self.pen_active(host_time_us) || self.rejected.contains(&pointer_id) {
+                    self.rejected.insert(pointer_id);
+                    return;
+                }
+                self.contacts.insert(
+                    pointer_id,
+                    Contact {
+                        start_x_px: x_px,
+                        start_y_px: y_px,
+                        x_px,
+                        y_px,
+                    },
+                );
+                let fingers = self.contacts.len() as u32;
+                let sequence = self.sequence.get_or_insert(TapSequence {
+                    start_us: host_time_us,
+                    max_fingers: 0,
+                    disqualified: false,
+                });
+                sequence.max_fingers = sequence.max_fingers.max(fingers);
+                self.update_transform();
+            }
+            InputPhase::Move => {
+                let Some(contact) = self.contacts.get_mut(&pointer_id) else {
+                    return;
+                };
+                contact.x_px = x_px;
+                contact.y_px = y_px;
+                let travel = (x_px - contact.start_x_px).hypot(y_px - contact.start_y_px);
+                if travel > TAP_MAX_TRAVEL_PX {
+                    if let Some(sequence) = &mut self.sequence {
+                        sequence.disqualified = true;
+                    }
+                }
+                self.update_transform();
+            }
+            InputPhase::Up => {
+                if self.rejected.remove(&pointer_id) {
+                    self.finish_sequence(host_time_us);
+                    return;
+                }
+                if self.contacts.remove(&pointer_id).is_none() {
+                    return;
+                }
+                self.update_transform();
+                self.finish_sequence(host_time_us);
+            }
+        }
+    }
+
+    /// Gestures recognized since the last call
+    pub fn take_pending(&mut self) -> Vec<TouchGestureV1> {
+        std::mem::take(&mut self.pending)
+    }
+
+    fn current_pair(&self) -> Option<PairPose> {
+        let mut contacts = self.contacts.iter();
+        let (&id_a, a) = contacts.next()?;
+        let (&id_b, b) = contacts.next()?;
+        Some(PairPose {
+            ids: (id_a, id_b),
+            center_x_px: (a.x_px + b.x_px) * 0.5,
+            center_y_px: (a.y_px + b.y_px) * 0.5,
+            distance_px: (b.x_px - a.x_px).hypot(b.y_px - a.y_px),
+            angle_deg: (b.y_px - a.y_px).atan2(b.x_px - a.x_px).to_degrees(),
+        })
+    }
+
+    fn update_transform(&mut self) {
+        let Some(next) = self.current_pair() else {
+            self.end_transform();
+            return;
+        };
+        let Some(prev) = self.pair.replace(next) else {
+            self.pending.push(TouchGestureV1::Transform {
+                phase: TouchGesturePhaseV1::Begin,
+                center_x_px: next.center_x_px,
+                center_y_px: next.center_y_px,
+                pan_x_px: 0.0,
+                pan_y_px: 0.0,
+                scale: 1.0,
+                rotation_deg: 0.0,
+            });
+            return;
+        };
+        // A different finger pair takes over without a jump
+        if prev.ids != next.ids {
+            return;
+        }
+        let scale = if prev.distance_px > f32::EPSILON {
+            next.distance_px / prev.distance_px
+        } else {
+            1.0
+        };
+        let rotation_deg = (next.angle_deg - prev.angle_deg + 180.0).rem_euclid(360.0) - 180.0;
+        self.pending.push(TouchGestureV1::Transform {
+            phase: TouchGesturePhaseV1::Update,
+            center_x_px: next.center_x_px,
+            center_y_px: next.center_y_px,
+            pan_x_px: next.center_x_px - prev.center_x_px,
+            pan_y_px: next.center_y_px - prev.center_y_px,
+            scale,
+            rotation_deg,
+        });
+    }
+
+    fn end_transform(&mut self) {
+        if let Some(prev) = self.pair.take() {
+            self.pending.push(TouchGestureV1::Transform {
+                phase: TouchGesturePhaseV1::End,
+                center_x_px: prev.center_x_px,
+                center_y_px: prev.center_y_px,
+                pan_x_px: 0.0,
+                pan_y_px: 0.0,
+                scale: 1.0,
+                rotation_deg: 0.0,
+            });
+        }
+    }
+
+    /// Emit a tap once every finger of the sequence has lifted
+    fn finish_sequence(&mut self, now_us: u64) {
+        if !self.contacts.is_empty() || !self.rejected.is_empty() {
+            return;
+        }
+        let Some(sequence) = self.sequence.take() else {
+            return;
+        };
+        if !sequence.disqualified
+            && sequence.max_fingers >= 2
+            && now_us.saturating_sub(sequence.start_us) <= TAP_MAX_DURATION_US
+        {
+            self.pending.push(TouchGestureV1::Tap {
+                fingers: sequence.max_fingers,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch(
+        recognizer: &mut GestureRecognizer,
+        steps: &[(u32, InputPhase, f32, f32, u64)],
+    ) -> Vec<TouchGestureV1> {
+        for &(pointer_id, phase, x, y, time_us) in steps {
+            recognizer.push_touch(pointer_id, phase, x, y, time_us);
+        }
+        recognizer.take_pending()
+    }
+
+    fn taps(gestures: &[TouchGestureV1]) -> Vec<u32> {
+        gestures
+            .iter()
+            .filter_map(|g| match g {
+                TouchGestureV1::Tap { fingers } => Some(*fingers),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn two_finger_drag_pans_zooms_and_rotates() {
+        let mut recognizer = GestureRecognizer::new();
+        let gestures = touch(
+            &mut recognizer,
+            &[
+                (1, InputPhase::Down, 100.0, 100.0, 1_000),
+                (2, InputPhase::Down, 200.0, 100.0, 2_000),
+                // Spread to twice the span, turn 90 degrees about the centre
+                (2, InputPhase::Move, 150.0, 200.0, 10_000),
+                (1, InputPhase::Move, 150.0, 0.0, 11_000),
+                (1, InputPhase::Up, 150.0, 0.0, 400_000),
+                (2, InputPhase::Up, 150.0, 200.0, 401_000),
+            ],
+        );
+
+        let mut scale = 1.0;
+        let mut rotation = 0.0;
+        let mut pan = (0.0, 0.0);
+        let mut phases = Vec::new();
+        for gesture in &gestures {
+            if let TouchGestureV1::Transform {
+                phase,
+                pan_x_px,
+                pan_y_px,
+                scale: step_scale,
+                rotation_deg,
+                ..
+            } = gesture
+            {
+                phases.push(*phase);
+                scale *= step_scale;
+                rotation += rotation_deg;
+                pan = (pan.0 + pan_x_px, pan.1 + pan_y_px);
+            }
+        }
+        assert_eq!(phases.first(), Some(&TouchGesturePhaseV1::Begin));
+        assert_eq!(phases.last(), Some(&TouchGesturePhaseV1::End));
+        assert!((scale - 2.0).abs() < 1e-4);
+        assert!((rotation - 90.0).abs() < 1e-3);
+        assert!((pan.0 - 0.0).abs() < 1e-4 && (pan.1 - 0.0).abs() < 1e-4);
+        assert!(taps(&gestures).is_empty());
+    }
+
+    #[test]
+    fn short_multi_finger_touches_are_taps() {
+        let mut recognizer = GestureRecognizer::new();
+        let two = touch(
+            &mut recognizer,
+            &[
+                (1, InputPhase::Down, 10.0, 10.0, 1_000),
+                (2, InputPhase::Down, 60.0, 10.0, 5_000),
+                (1, InputPhase::Move, 12.0, 11.0, 40_000),
+                (1, InputPhase::Up, 12.0, 11.0, 90_000),
+                (2, InputPhase::Up, 60.0, 10.0, 100_000),
+            ],
+        );
+        assert_eq!(taps(&two), vec![2]);
+
+        let three = touch(
+            &mut recognizer,
+            &[
+                (3, InputPhase::Down, 10.0, 10.0, 1_000_000),
+                (4, InputPhase::Down, 40.0, 10.0, 1_001_000),
+                (5, InputPhase::Down, 70.0, 10.0, 1_002_000),
+                (3, InputPhase::Up, 10.0, 10.0, 1_100_000),
+                (4, InputPhase::Up, 40.0, 10.0, 1_100_000),
+                (5, InputPhase::Up, 70.0, 10.0, 1_100_000),
+            ],
+        );
+        assert_eq!(taps(&three), vec![3]);
+
+        // Held too long
+        let held = touch(
+            &mut recognizer,
+            &[
+                (6, InputPhase::Down, 10.0, 10.0, 2_000_000),
+                (7, InputPhase::Down, 40.0, 10.0, 2_000_000),
+                (6, InputPhase::Up, 10.0, 10.0, 2_400_000),
+                (7, InputPhase::Up, 40.0, 10.0, 2_400_000),
+            ],
+        );
+        assert!(taps(&held).is_empty());
+    }
+
+    #[test]
+    fn touches_near_pen_use_are_rejected() {
+        let mut recognizer = GestureRecognizer::new();
+        recognizer.observe_pen(&[TabletEventV3::ProximityEnter], 1_000);
+        let palm = touch(
+            &mut recognizer,
+            &[
+                (1, InputPhase::Down, 10.0, 10.0, 2_000),
+                (2, InputPhase::Down, 60.0, 10.0, 2_000),
+                (2, InputPhase::Move, 90.0, 10.0, 3_000),
+                (1, InputPhase::Up, 10.0, 10.0, 4_000),
+                (2, InputPhase::Up, 90.0, 10.0, 4_000),
+            ],
+        );
+        assert!(palm.is_empty());
+
+        // Still within the grace period after the pen leaves
+        recognizer.observe_pen(&[TabletEventV3::ProximityLeave], 10_000);
+        recognizer.push_touch(3, InputPhase::Down, 0.0, 0.0, 20_000);
+        recognizer.push_touch(4, InputPhase::Down, 50.0, 0.0, 20_000);
+        assert!(recognizer.take_pending().is_empty());
+        recognizer.push_touch(3, InputPhase::Up, 0.0, 0.0, 30_000);
+        recognizer.push_touch(4, InputPhase::Up, 50.0, 0.0, 30_000);
+
+        // Pen arriving mid-gesture ends the transform and cancels the tap
+        let late = 10_000 + PALM_REJECTION_GRACE_US;
+        recognizer.push_touch(5, InputPhase::Down, 0.0, 0.0, late);
+        recognizer.push_touch(6, InputPhase::Down, 50.0, 0.0, late);
+        recognizer.observe_pen(&[TabletEventV3::ProximityEnter], late + 1_000);
+        recognizer.push_touch(5, InputPhase::Up, 0.0, 0.0, late + 2_000);
+        recognizer.push_touch(6, InputPhase::Up, 50.0, 0.0, late + 2_000);
+        let phases: Vec<_> = recognizer
+            .take_pending()
+            .into_iter()
+            .map(|g| match g {
+                TouchGestureV1::Transform { phase, .. } => Some(phase),
+                TouchGestureV1::Tap { .. } => None,
+            })
+            .collect();
+        assert_eq!(
+            phases,
+            vec![
+                Some(TouchGesturePhaseV1::Begin),
+                Some(TouchGesturePhaseV1::End)
+            ]
+        );
+    }
+}
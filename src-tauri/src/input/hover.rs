@@ -0,0 +1,271 @@
+//! Hover stream and brush cursor pose
+//!
+//! Hover samples bypass the lossless stroke queue (see `InputEventQueue::take_hover`).
+//! `HoverStream` keeps only the newest one and releases it at a bounded rate together
+//! with a `CursorPoseV1` describing how the brush outline (`abr::cursor` LOD paths)
+//! should be rotated and squashed for the current pen tilt and barrel rotation.
+
+use super::backend::NativeTabletEventV3;
+use super::krita_v3::types::ToolTypeV3;
+use serde::{Deserialize, Serialize};
+
+/// ~120 Hz, enough for a cursor without flooding the webview
+pub const DEFAULT_HOVER_EMIT_INTERVAL_US: u64 = 8_000;
+/// Cursor opacity at the far edge of proximity range
+const FAR_HOVER_OPACITY: f32 = 0.4;
+
+/// Pen-driven cursor control, mirroring the brush Shape Dynamics sources
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CursorControlV1 {
+    #[default]
+    Off,
+    PenTilt,
+    Rotation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CursorPoseConfigV1 {
+    pub angle_control: CursorControlV1,
+    pub roundness_control: CursorControlV1,
+    /// Lower bound for controlled roundness (0-100, percent of base roundness)
+    pub minimum_roundness: f32,
+}
+
+impl Default for CursorPoseConfigV1 {
+    fn default() -> Self {
+        Self {
+            angle_control: CursorControlV1::Off,
+            roundness_control: CursorControlV1::Off,
+            minimum_roundness: 25.0,
+        }
+    }
+}
+
+/// Transform applied on top of the brush's own angle and roundness
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CursorPoseV1 {
+    /// Added to the brush angle (whole degrees, 0-360)
+    pub angle_offset_deg: f32,
+    /// Multiplies the brush roundness (0-1, hundredths)
+    pub roundness_scale: f32,
+    /// Fades the outline as the pen lifts away
+    pub opacity: f32,
+}
+
+impl Default for CursorPoseV1 {
+    fn default() -> Self {
+        Self {
+            angle_offset_deg: 0.0,
+            roundness_scale: 1.0,
+            opacity: 1.0,
+        }
+    }
+}
+
+/// Same 0-1 control values the frontend Shape Dynamics use for dabs
+fn control_value(control: CursorControlV1, sample: &NativeTabletEventV3) -> f32 {
+    match control {
+        CursorControlV1::Off => 1.0,
+        CursorControlV1::PenTilt => {
+            let tx = sample.tilt_x_deg / 90.0;
+            let ty = sample.tilt_y_deg / 90.0;
+            (tx * tx + ty * ty).sqrt().min(1.0)
+        }
+        CursorControlV1::Rotation => sample.rotation_deg.rem_euclid(360.0) / 360.0,
+    }
+}
+
+/// Cursor transform for a hover sample. Values are quantized so the frontend can
+/// cache generated cursors instead of rebuilding one per sample.
+pub fn cursor_pose(sample: &NativeTabletEventV3, config: &CursorPoseConfigV1) -> CursorPoseV1 {
+    let angle_offset_deg = match config.angle_control {
+        CursorControlV1::Off => 0.0,
+        control => (control_value(control, sample) * 360.0).round() % 360.0,
+    };
+
+    let minimum = (config.minimum_roundness / 100.0).clamp(0.0, 1.0);
+    let roundness = minimum + (1.0 - minimum) * control_value(config.roundness_control, sample);
+    let roundness_scale = (roundness * 100.0).round() / 100.0;
+
+    let opacity = sample
+        .distance_0_1
+        .map(|d| 1.0 - (1.0 - FAR_HOVER_OPACITY) * d.clamp(0.0, 1.0))
+        .unwrap_or(1.0);
+
+    CursorPoseV1 {
+        angle_offset_deg,
+        roundness_scale,
+        opacity: (opacity * 100.0).round() / 100.0,
+    }
+}
+
+/// Payload of the `tablet-hover-v1` event
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HoverSampleV1 {
+    pub pointer_id: u32,
+    pub tool_type: ToolTypeV3,
+    pub x_px: f32,
+    pub y_px: f32,
+    pub distance_0_1: Option<f32>,
+    pub tilt_x_deg: f32,
+    pub tilt_y_deg: f32,
+    pub rotation_deg: f32,
+    pub host_time_us: u64,
+    pub cursor: CursorPoseV1,
+}
+
+/// Latest-value hover stream with a minimum emit interval
+#[derive(Debug, Clone)]
+pub struct HoverStream {
+    interval_us: u64,
+    config: CursorPoseConfigV1,
+    pending: Option<NativeTabletEventV3>,
+    last_emit_us: Option<u64>,
+}
+
+impl Default for HoverStream {
+    fn default() -> Self {
+        Self::new(DEFAULT_HOVER_EMIT_INTERVAL_US)
+    }
+}
+
+impl HoverStream {
+    pub fn new(interval_us: u64) -> Self {
+        Self {
+            interval_us,
+            config: CursorPoseConfigV1::default(),
+            pending: None,
+            last_emit_us: None,
+        }
+    }
+
+    pub fn config(&self) -> &CursorPoseConfigV1 {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: CursorPoseConfigV1) {
+        self.config = config;
+    }
+
+    /// Replace the pending sample; older unsent samples are dropped
+    pub fn push(&mut self, sample: NativeTabletEventV3) {
+        self.pending = Some(sample);
+    }
+
+    /// Drop the pending sample (pen left proximity or touched down)
+    pub fn clear(&mut self) {
+        self.pending = None;
+    }
+
+    /// Pending sample, if the emit interval has elapsed since the last one
+    pub fn take_due(&mut self, now_us: u64) -> Option<HoverSampleV1> {
+        self.pending.as_ref()?;
+        if self
+            .last_emit_us
+            .is_some_and(|last| now_us.saturating_sub(last) < self.interval_us)
+        {
+            return None;
+        }
+        let sample = self.pending.take()?;
+        self.last_emit_us = Some(now_us);
+        Some(HoverSampleV1 {
+            pointer_id: sample.pointer_id,
+            tool_type: sample.tool_type,
+            x_px: sample.x_px,
+            y_px: sample.y_px,
+            distance_0_1: sample.distance_0_1,
+            tilt_x_deg: sample.tilt_x_deg,
+            tilt_y_deg: sample.tilt_y_deg,
+            rotation_deg: sample.rotation_deg,
+            host_time_us: sample.host_time_us,
+            cursor: cursor_pose(&sample, &self.config),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{InputPhase, InputSource};
+
+    fn hover(host_time_us: u64, x_px: f32) -> NativeTabletEventV3 {
+        NativeTabletEventV3 {
+            seq: 0,
+            stroke_id: 1,
+            pointer_id: 1,
+            device_id: "test".to_string(),
+            source: InputSource::LinuxEvdev,
+            phase: InputPhase::Hover,
+            x_px,
+            y_px: 0.0,
+            pressure_0_1: 0.0,
+            tilt_x_deg: 45.0,
+            tilt_y_deg: 0.0,
+            rotation_deg: 90.0,
+            host_time_us,
+            device_time_us: None,
+            tool_type: ToolTypeV3::Pen,
+            tool_serial: None,
+            wheel_0_1: None,
+            distance_0_1: Some(0.5),
+            coalesced: None,
+            predicted: false,
+            prediction_index: None,
+        }
+    }
+
+    #[test]
+    fn stream_keeps_latest_sample_and_throttles() {
+        let mut stream = HoverStream::new(8_000);
+        stream.push(hover(1_000, 1.0));
+        stream.push(hover(2_000, 2.0));
+        let first = stream.take_due(2_000).expect("first emit");
+        assert_eq!(first.x_px, 2.0);
+        assert_eq!(
+            first.cursor,
+            CursorPoseV1 {
+                opacity: 0.7,
+                ..CursorPoseV1::default()
+            }
+        );
+
+        stream.push(hover(5_000, 3.0));
+        assert!(stream.take_due(5_000).is_none());
+        stream.push(hover(9_000, 4.0));
+        let second = stream.take_due(10_000).expect("second emit");
+        assert_eq!(second.x_px, 4.0);
+        assert!(stream.take_due(30_000).is_none());
+
+        stream.push(hover(31_000, 5.0));
+        stream.clear();
+        assert!(stream.take_due(40_000).is_none());
+    }
+
+    #[test]
+    fn pose_follows_rotation_and_tilt() {
+        let sample = hover(1_000, 0.0);
+        let pose = cursor_pose(
+            &sample,
+            &CursorPoseConfigV1 {
+                angle_control: CursorControlV1::Rotation,
+                roundness_control: CursorControlV1::PenTilt,
+                minimum_roundness: 20.0,
+            },
+        );
+        assert_eq!(pose.angle_offset_deg, 90.0);
+        // Tilt magnitude 0.5 -> 0.2 + 0.8 * 0.5
+        assert_eq!(pose.roundness_scale, 0.6);
+
+        let pose = cursor_pose(
+            &sample,
+            &CursorPoseConfigV1 {
+                angle_control: CursorControlV1::PenTilt,
+                ..CursorPoseConfigV1::default()
+            },
+        );
+        assert_eq!(pose.angle_offset_deg, 180.0);
+        assert_eq!(pose.roundness_scale, 1.0);
+    }
+}
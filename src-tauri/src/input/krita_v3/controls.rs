@@ -0,0 +1,143 @@
+//! Button and dial state shared by the platform adapters
+//!
+//! Platforms report barrel buttons as a bit mask on every pen packet; the trackers
+//! here turn mask and dial position changes into `TabletButtonEventV3` /
+//! `TabletDialEventV3` edges.
+
+use super::types::{TabletButtonEventV3, TabletButtonKindV3, TabletDialEventV3, TabletDialKindV3};
+
+/// Barrel buttons tracked per pen (lower, upper, third)
+const BARREL_BUTTON_COUNT: u32 = 3;
+
+/// Diffs barrel button masks. Bit 0 is barrel button 1 (the tip is not included).
+#[derive(Debug, Clone, Default)]
+pub struct BarrelButtonTracker {
+    mask: u32,
+}
+
+impl BarrelButtonTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(
+        &mut self,
+        mask: u32,
+        device_id: &str,
+        host_time_us: u64,
+    ) -> Vec<TabletButtonEventV3> {
+        let mask = mask & ((1 << BARREL_BUTTON_COUNT) - 1);
+        let changed = mask ^ self.mask;
+        self.mask = mask;
+        (0..BARREL_BUTTON_COUNT)
+            .filter(|bit| changed & (1 << bit) != 0)
+            .map(|bit| TabletButtonEventV3 {
+                kind: TabletButtonKindV3::PenBarrel,
+                index: bit + 1,
+                pressed: mask & (1 << bit) != 0,
+                device_id: device_id.to_string(),
+                host_time_us,
+            })
+            .collect()
+    }
+
+    /// Release everything, e.g. when the pen leaves proximity
+    pub fn release_all(&mut self, device_id: &str, host_time_us: u64) -> Vec<TabletButtonEventV3> {
+        self.update(0, device_id, host_time_us)
+    }
+}
+
+/// Tracks one ring or strip and reports relative travel
+#[derive(Debug, Clone)]
+pub struct DialTracker {
+    kind: TabletDialKindV3,
+    index: u32,
+    position: Option<f32>,
+}
+
+impl DialTracker {
+    pub fn new(kind: TabletDialKindV3, index: u32) -> Self {
+        Self {
+            kind,
+            index,
+            position: None,
+        }
+    }
+
+    /// Feed a new position (`None` = finger lifted); returns an event on change
+    pub fn update(
+        &mut self,
+        position_0_1: Option<f32>,
+        device_id: &str,
+        host_time_us: u64,
+    ) -> Option<TabletDialEventV3> {
+        let position_0_1 = position_0_1.map(|p| p.clamp(0.0, 1.0));
+        if position_0_1 == self.position {
+            return None;
+        }
+        let delta = match (self.position, position_0_1) {
+            (Some(prev), Some(next)) => {
+                let delta = next - prev;
+                if self.kind == TabletDialKindV3::TouchRing {
+                    // Shortest way round the ring
+                    delta - delta.round()
+                } else {
+                    delta
+                }
+            }
+            _ => 0.0,
+        };
+        self.position = position_0_1;
+        Some(TabletDialEventV3 {
+            kind: self.kind,
+            index: self.index,
+            position_0_1,
+            delta,
+            device_id: device_id.to_string(),
+            host_time_us,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn barrel_mask_changes_become_edges() {
+        let mut tracker = BarrelButtonTracker::new();
+        assert!(tracker.update(0, "pen", 1).is_empty());
+
+        let pressed = tracker.update(0b01, "pen", 2);
+        assert_eq!(pressed.len(), 1);
+        assert_eq!(pressed[0].index, 1);
+        assert!(pressed[0].pressed);
+
+        let swapped = tracker.update(0b10, "pen", 3);
+        let edges: Vec<(u32, bool)> = swapped.iter().map(|e| (e.index, e.pressed)).collect();
+        assert_eq!(edges, vec![(1, false), (2, true)]);
+
+        let released = tracker.release_all("pen", 4);
+        assert_eq!(released.len(), 1);
+        assert!(!released[0].pressed);
+        assert!(tracker.update(0, "pen", 5).is_empty());
+    }
+
+    #[test]
+    fn ring_delta_wraps_and_strip_does_not() {
+        let mut ring = DialTracker::new(TabletDialKindV3::TouchRing, 0);
+        let first = ring.update(Some(0.95), "pad", 1).expect("touch");
+        assert_eq!(first.delta, 0.0);
+        let wrapped = ring.update(Some(0.05), "pad", 2).expect("move");
+        assert!((wrapped.delta - 0.1).abs() < 1e-6);
+        assert!(ring.update(Some(0.05), "pad", 3).is_none());
+        let lifted = ring.update(None, "pad", 4).expect("lift");
+        assert_eq!(lifted.position_0_1, None);
+        assert_eq!(lifted.delta, 0.0);
+
+        let mut strip = DialTracker::new(TabletDialKindV3::TouchStrip, 1);
+        strip.update(Some(0.9), "pad", 1);
+        let moved = strip.update(Some(0.1), "pad", 2).expect("move");
+        assert!((moved.delta + 0.8).abs() < 1e-6);
+    }
+}
@@ -1,3 +1,86 @@
+use serde::{Deserialize, Serialize};
+
+/// Smallest active area edge, as a fraction of the tablet
+const MIN_AREA_FRACTION: f32 = 0.01;
+
+/// Clockwise rotation of the physical tablet (180 for left-handed use)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TabletRotationV1 {
+    #[default]
+    #[serde(rename = "0")]
+    Deg0,
+    #[serde(rename = "90")]
+    Deg90,
+    #[serde(rename = "180")]
+    Deg180,
+    #[serde(rename = "270")]
+    Deg270,
+}
+
+impl TabletRotationV1 {
+    fn swaps_axes(self) -> bool {
+        matches!(self, Self::Deg90 | Self::Deg270)
+    }
+}
+
+/// Rectangle in normalized tablet coordinates (0..1, origin top-left)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalizedRectV1 {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Default for NormalizedRectV1 {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            width: 1.0,
+            height: 1.0,
+        }
+    }
+}
+
+impl NormalizedRectV1 {
+    /// Clamp into the unit square, falling back to the full tablet for non-finite input
+    pub fn sanitized(self) -> Self {
+        if ![self.x, self.y, self.width, self.height]
+            .iter()
+            .all(|v| v.is_finite())
+        {
+            return Self::default();
+        }
+        let x = self.x.clamp(0.0, 1.0 - MIN_AREA_FRACTION);
+        let y = self.y.clamp(0.0, 1.0 - MIN_AREA_FRACTION);
+        Self {
+            x,
+            y,
+            width: self.width.max(MIN_AREA_FRACTION).min(1.0 - x),
+            height: self.height.max(MIN_AREA_FRACTION).min(1.0 - y),
+        }
+    }
+}
+
+/// How the tablet surface maps onto the screen
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TabletMappingV1 {
+    /// Part of the tablet that is used, in unrotated tablet coordinates
+    #[serde(default)]
+    pub active_area: NormalizedRectV1,
+    #[serde(default)]
+    pub rotation: TabletRotationV1,
+    /// Shrink the active area so it keeps the output's aspect ratio
+    #[serde(default)]
+    pub lock_aspect: bool,
+    /// Index into the system monitor list; `None` keeps the backend default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monitor: Option<u32>,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct CoordinateMapper {
     width_px: f32,
@@ -7,6 +90,9 @@ pub struct CoordinateMapper {
     raw_y_min: f32,
     raw_y_max: f32,
     invert_y: bool,
+    area: NormalizedRectV1,
+    rotation: TabletRotationV1,
+    lock_aspect: bool,
 }
 
 impl CoordinateMapper {
@@ -21,6 +107,9 @@ impl CoordinateMapper {
             raw_y_min: 0.0,
             raw_y_max: safe_height,
             invert_y: false,
+            area: NormalizedRectV1::default(),
+            rotation: TabletRotationV1::Deg0,
+            lock_aspect: false,
         }
     }
 
@@ -41,6 +130,40 @@ impl CoordinateMapper {
             raw_y_min: raw_y_min as f32,
             raw_y_max: raw_y_max as f32,
             invert_y,
+            area: NormalizedRectV1::default(),
+            rotation: TabletRotationV1::Deg0,
+            lock_aspect: false,
+        }
+    }
+
+    /// Restrict to an active area, rotate and optionally lock the aspect ratio.
+    /// Aspect lock assumes both raw axes use the same physical resolution.
+    pub fn with_mapping(mut self, mapping: &TabletMappingV1) -> Self {
+        self.area = mapping.active_area.sanitized();
+        self.rotation = mapping.rotation;
+        self.lock_aspect = mapping.lock_aspect;
+        self
+    }
+
+    /// Fraction of the rotated active area used on each axis when the aspect is locked
+    fn aspect_fit(&self) -> (f32, f32) {
+        if !self.lock_aspect {
+            return (1.0, 1.0);
+        }
+        let mut area_w = self.area.width * (self.raw_x_max - self.raw_x_min).abs();
+        let mut area_h = self.area.height * (self.raw_y_max - self.raw_y_min).abs();
+        if self.rotation.swaps_axes() {
+            std::mem::swap(&mut area_w, &mut area_h);
+        }
+        if area_w <= f32::EPSILON || area_h <= f32::EPSILON {
+            return (1.0, 1.0);
+        }
+        let area_aspect = area_w / area_h;
+        let output_aspect = self.width_px / self.height_px;
+        if area_aspect > output_aspect {
+            (output_aspect / area_aspect, 1.0)
+        } else {
+            (1.0, area_aspect / output_aspect)
         }
     }
 
@@ -58,8 +181,19 @@ impl CoordinateMapper {
         if self.invert_y {
             normalized_y = 1.0 - normalized_y;
         }
-        let x = (normalized_x * self.width_px).clamp(0.0, self.width_px);
-        let y = (normalized_y * self.height_px).clamp(0.0, self.height_px);
+        let local_x = (normalized_x - self.area.x) / self.area.width;
+        let local_y = (normalized_y - self.area.y) / self.area.height;
+        let (rotated_x, rotated_y) = match self.rotation {
+            TabletRotationV1::Deg0 => (local_x, local_y),
+            TabletRotationV1::Deg90 => (1.0 - local_y, local_x),
+            TabletRotationV1::Deg180 => (1.0 - local_x, 1.0 - local_y),
+            TabletRotationV1::Deg270 => (local_y, 1.0 - local_x),
+        };
+        let (fit_x, fit_y) = self.aspect_fit();
+        let output_x = (rotated_x - (1.0 - fit_x) * 0.5) / fit_x;
+        let output_y = (rotated_y - (1.0 - fit_y) * 0.5) / fit_y;
+        let x = (output_x * self.width_px).clamp(0.0, self.width_px);
+        let y = (output_y * self.height_px).clamp(0.0, self.height_px);
         (x, y)
     }
 
@@ -100,4 +234,91 @@ mod tests {
         assert_eq!(y_top, 80.0);
         assert_eq!(y_bottom, 0.0);
     }
+
+    fn rotated(rotation: TabletRotationV1) -> CoordinateMapper {
+        CoordinateMapper::with_axis_range(200.0, 100.0, 0, 1000, 0, 1000, false).with_mapping(
+            &TabletMappingV1 {
+                rotation,
+                ..TabletMappingV1::default()
+            },
+        )
+    }
+
+    #[test]
+    fn maps_active_area_onto_full_output() {
+        let mapper = CoordinateMapper::with_axis_range(100.0, 100.0, 0, 1000, 0, 1000, false)
+            .with_mapping(&TabletMappingV1 {
+                active_area: NormalizedRectV1 {
+                    x: 0.5,
+                    y: 0.25,
+                    width: 0.5,
+                    height: 0.5,
+                },
+                ..TabletMappingV1::default()
+            });
+        assert_eq!(mapper.map_output_xy(500, 250), (0.0, 0.0));
+        assert_eq!(mapper.map_output_xy(750, 500), (50.0, 50.0));
+        assert_eq!(mapper.map_output_xy(1000, 750), (100.0, 100.0));
+        assert_eq!(mapper.map_output_xy(0, 1000), (0.0, 100.0));
+    }
+
+    #[test]
+    fn rotates_tablet_by_quarter_turns() {
+        // Tablet top-left corner and a point on its top edge
+        let cases = [
+            (TabletRotationV1::Deg0, (0.0, 0.0), (50.0, 0.0)),
+            (TabletRotationV1::Deg90, (200.0, 0.0), (200.0, 25.0)),
+            (TabletRotationV1::Deg180, (200.0, 100.0), (150.0, 100.0)),
+            (TabletRotationV1::Deg270, (0.0, 100.0), (0.0, 75.0)),
+        ];
+        for (rotation, corner, edge) in cases {
+            let mapper = rotated(rotation);
+            assert_eq!(mapper.map_output_xy(0, 0), corner, "{:?}", rotation);
+            assert_eq!(mapper.map_output_xy(250, 0), edge, "{:?}", rotation);
+        }
+    }
+
+    #[test]
+    fn aspect_lock_crops_tablet_to_output_ratio() {
+        // Square tablet onto a 2:1 output uses the middle half of the tablet height
+        let mapper = CoordinateMapper::with_axis_range(200.0, 100.0, 0, 1000, 0, 1000, false)
+            .with_mapping(&TabletMappingV1 {
+                lock_aspect: true,
+                ..TabletMappingV1::default()
+            });
+        assert_eq!(mapper.map_output_xy(0, 250), (0.0, 0.0));
+        assert_eq!(mapper.map_output_xy(1000, 750), (200.0, 100.0));
+        assert_eq!(mapper.map_output_xy(500, 500), (100.0, 50.0));
+        assert_eq!(mapper.map_output_xy(500, 0), (100.0, 0.0));
+
+        // A 2:1 tablet rotated upright onto the same output uses its middle quarter
+        let mapper = CoordinateMapper::with_axis_range(200.0, 100.0, 0, 2000, 0, 1000, false)
+            .with_mapping(&TabletMappingV1 {
+                rotation: TabletRotationV1::Deg90,
+                lock_aspect: true,
+                ..TabletMappingV1::default()
+            });
+        assert_eq!(mapper.map_output_xy(750, 1000), (0.0, 0.0));
+        assert_eq!(mapper.map_output_xy(1250, 0), (200.0, 100.0));
+    }
+
+    #[test]
+    fn sanitizes_out_of_range_active_area() {
+        let area = NormalizedRectV1 {
+            x: -0.5,
+            y: 0.9,
+            width: 3.0,
+            height: 0.0,
+        }
+        .sanitized();
+        assert_eq!(area.x, 0.0);
+        assert_eq!(area.width, 1.0);
+        assert!((area.y - 0.9).abs() < 1e-6);
+        assert!((area.height - MIN_AREA_FRACTION).abs() < 1e-6);
+        let nan = NormalizedRectV1 {
+            x: f32::NAN,
+            ..NormalizedRectV1::default()
+        };
+        assert_eq!(nan.sanitized(), NormalizedRectV1::default());
+    }
 }
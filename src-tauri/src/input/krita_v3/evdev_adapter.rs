@@ -0,0 +1,888 @@
+use super::controls::BarrelButtonTracker;
+use super::coordinate_mapper::{CoordinateMapper, TabletMappingV1};
+use super::phase_machine::PhaseMachine;
+use super::timebase::MonotonicTimebase;
+use super::types::{
+    clamp_pressure_0_1, clamp_tilt_deg, normalize_rotation_deg, InputPhaseV3, InputSourceV3,
+    NativeTabletEventV3, ToolTypeV3,
+};
+use crate::input::backend::{TabletEventV3, TabletV3Diagnostics};
+
+// linux/input-event-codes.h
+pub const EV_SYN: u16 = 0x00;
+pub const EV_KEY: u16 = 0x01;
+pub const EV_ABS: u16 = 0x03;
+pub const EV_MSC: u16 = 0x04;
+pub const SYN_REPORT: u16 = 0;
+pub const SYN_DROPPED: u16 = 3;
+pub const ABS_X: u16 = 0x00;
+pub const ABS_Y: u16 = 0x01;
+pub const ABS_Z: u16 = 0x02;
+pub const ABS_WHEEL: u16 = 0x08;
+pub const ABS_PRESSURE: u16 = 0x18;
+pub const ABS_DISTANCE: u16 = 0x19;
+pub const ABS_TILT_X: u16 = 0x1a;
+pub const ABS_TILT_Y: u16 = 0x1b;
+pub const BTN_TOOL_PEN: u16 = 0x140;
+pub const BTN_TOOL_RUBBER: u16 = 0x141;
+pub const BTN_TOOL_BRUSH: u16 = 0x142;
+pub const BTN_TOOL_PENCIL: u16 = 0x143;
+pub const BTN_TOOL_AIRBRUSH: u16 = 0x144;
+pub const BTN_TOOL_MOUSE: u16 = 0x146;
+pub const BTN_TOOL_LENS: u16 = 0x147;
+pub const BTN_TOUCH: u16 = 0x14a;
+pub const BTN_STYLUS: u16 = 0x14b;
+pub const BTN_STYLUS2: u16 = 0x14c;
+pub const BTN_STYLUS3: u16 = 0x149;
+pub const MSC_SERIAL: u16 = 0x00;
+
+/// Size of `struct input_event` (timeval + type + code + value)
+#[cfg(target_pointer_width = "64")]
+pub const EVDEV_EVENT_SIZE: usize = 24;
+#[cfg(not(target_pointer_width = "64"))]
+pub const EVDEV_EVENT_SIZE: usize = 16;
+
+const PEN_POINTER_ID: u32 = 1;
+const ERASER_POINTER_ID: u32 = 2;
+/// libinput's assumption for tilt axes without a resolution
+const DEFAULT_TILT_RANGE_DEG: f32 = 64.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EvdevRawEvent {
+    pub time_us: u64,
+    pub event_type: u16,
+    pub code: u16,
+    pub value: i32,
+}
+
+impl EvdevRawEvent {
+    pub fn new(time_us: u64, event_type: u16, code: u16, value: i32) -> Self {
+        Self {
+            time_us,
+            event_type,
+            code,
+            value,
+        }
+    }
+
+    /// Decode one native-endian `struct input_event` record
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < EVDEV_EVENT_SIZE {
+            return None;
+        }
+        let half = (EVDEV_EVENT_SIZE - 8) / 2;
+        let (sec, usec) = if half == 8 {
+            (
+                i64::from_ne_bytes(bytes[0..8].try_into().ok()?),
+                i64::from_ne_bytes(bytes[8..16].try_into().ok()?),
+            )
+        } else {
+            (
+                i32::from_ne_bytes(bytes[0..4].try_into().ok()?) as i64,
+                i32::from_ne_bytes(bytes[4..8].try_into().ok()?) as i64,
+            )
+        };
+        let body = &bytes[half * 2..EVDEV_EVENT_SIZE];
+        Some(Self {
+            time_us: (sec.max(0) as u64)
+                .saturating_mul(1_000_000)
+                .saturating_add(usec.max(0) as u64),
+            event_type: u16::from_ne_bytes(body[0..2].try_into().ok()?),
+            code: u16::from_ne_bytes(body[2..4].try_into().ok()?),
+            value: i32::from_ne_bytes(body[4..8].try_into().ok()?),
+        })
+    }
+
+    /// Encode as a native-endian `struct input_event` record
+    pub fn to_bytes(&self) -> [u8; EVDEV_EVENT_SIZE] {
+        let mut bytes = [0u8; EVDEV_EVENT_SIZE];
+        let half = (EVDEV_EVENT_SIZE - 8) / 2;
+        let sec = self.time_us / 1_000_000;
+        let usec = self.time_us % 1_000_000;
+        if half == 8 {
+            bytes[0..8].copy_from_slice(&(sec as i64).to_ne_bytes());
+            bytes[8..16].copy_from_slice(&(usec as i64).to_ne_bytes());
+        } else {
+            bytes[0..4].copy_from_slice(&(sec as i32).to_ne_bytes());
+            bytes[4..8].copy_from_slice(&(usec as i32).to_ne_bytes());
+        }
+        let body = &mut bytes[half * 2..];
+        body[0..2].copy_from_slice(&self.event_type.to_ne_bytes());
+        body[2..4].copy_from_slice(&self.code.to_ne_bytes());
+        body[4..8].copy_from_slice(&self.value.to_ne_bytes());
+        bytes
+    }
+}
+
+/// Range of an absolute axis (`struct input_absinfo`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EvdevAxisInfo {
+    pub min: i32,
+    pub max: i32,
+    /// Units per mm (X/Y) or per radian (tilt); 0 if unknown
+    pub resolution: i32,
+}
+
+impl EvdevAxisInfo {
+    pub fn new(min: i32, max: i32) -> Self {
+        Self {
+            min,
+            max,
+            resolution: 0,
+        }
+    }
+
+    pub(crate) fn normalize(&self, value: i32) -> f32 {
+        let range = self.max as f32 - self.min as f32;
+        if range <= 0.0 {
+            return 0.0;
+        }
+        ((value as f32 - self.min as f32) / range).clamp(0.0, 1.0)
+    }
+}
+
+/// Absolute axes reported by a pen device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EvdevDeviceCaps {
+    pub x: EvdevAxisInfo,
+    pub y: EvdevAxisInfo,
+    pub pressure: Option<EvdevAxisInfo>,
+    pub tilt_x: Option<EvdevAxisInfo>,
+    pub tilt_y: Option<EvdevAxisInfo>,
+    pub distance: Option<EvdevAxisInfo>,
+    pub rotation: Option<EvdevAxisInfo>,
+    /// Airbrush finger wheel
+    pub wheel: Option<EvdevAxisInfo>,
+}
+
+/// Where the tablet area lands in client coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EvdevOutputMapping {
+    /// Screen area the tablet is mapped to (logical px)
+    pub screen_width_px: f32,
+    pub screen_height_px: f32,
+    /// Client area origin relative to the screen origin (logical px)
+    pub client_offset_x_px: f32,
+    pub client_offset_y_px: f32,
+    /// Active area, rotation and aspect lock within the screen area
+    pub tablet: TabletMappingV1,
+}
+
+impl Default for EvdevOutputMapping {
+    fn default() -> Self {
+        Self {
+            screen_width_px: 1920.0,
+            screen_height_px: 1080.0,
+            client_offset_x_px: 0.0,
+            client_offset_y_px: 0.0,
+            tablet: TabletMappingV1::default(),
+        }
+    }
+}
+
+/// Device state read back after `SYN_DROPPED` (`EVIOCGKEY`/`EVIOCGABS`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EvdevSyncState {
+    /// Key codes currently down
+    pub pressed_keys: Vec<u16>,
+    /// Current value of each absolute axis
+    pub abs_values: Vec<(u16, i32)>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct AxisState {
+    x: i32,
+    y: i32,
+    pressure: i32,
+    tilt_x: i32,
+    tilt_y: i32,
+    distance: i32,
+    rotation: i32,
+    wheel: i32,
+    pen: bool,
+    eraser: bool,
+    touching: bool,
+    /// Barrel button mask, bit 0 = `BTN_STYLUS`
+    buttons: u32,
+    tool: ToolTypeV3,
+    serial: Option<u64>,
+}
+
+#[derive(Debug)]
+pub struct EvdevAdapterV3 {
+    device_id: String,
+    caps: EvdevDeviceCaps,
+    mapping: EvdevOutputMapping,
+    mapper: CoordinateMapper,
+    phase_machine: PhaseMachine,
+    timebase: MonotonicTimebase,
+    diagnostics: TabletV3Diagnostics,
+    state: AxisState,
+    barrel: BarrelButtonTracker,
+    in_proximity: bool,
+    last_pointer_id: u32,
+    dropping: bool,
+    dropped_frames: u64,
+    /// Device time of the report that ended a drop, while a resync is due
+    resync_time_us: Option<u64>,
+}
+
+impl EvdevAdapterV3 {
+    pub fn new(device_id: String, caps: EvdevDeviceCaps, mapping: EvdevOutputMapping) -> Self {
+        Self {
+            device_id,
+            caps,
+            mapping,
+            mapper: Self::build_mapper(&caps, &mapping),
+            phase_machine: PhaseMachine::new(),
+            timebase: MonotonicTimebase::new(),
+            diagnostics: TabletV3Diagnostics::default(),
+            state: AxisState::default(),
+            barrel: BarrelButtonTracker::new(),
+            in_proximity: false,
+            last_pointer_id: PEN_POINTER_ID,
+            dropping: false,
+            dropped_frames: 0,
+            resync_time_us: None,
+        }
+    }
+
+    fn build_mapper(caps: &EvdevDeviceCaps, mapping: &EvdevOutputMapping) -> CoordinateMapper {
+        CoordinateMapper::with_axis_range(
+            mapping.screen_width_px,
+            mapping.screen_height_px,
+            caps.x.min,
+            caps.x.max,
+            caps.y.min,
+            caps.y.max,
+            false,
+        )
+        .with_mapping(&mapping.tablet)
+    }
+
+    pub fn reset(&mut self) {
+        self.phase_machine.reset();
+        self.timebase.reset();
+        self.diagnostics = TabletV3Diagnostics::default();
+        self.state = AxisState::default();
+        self.barrel = BarrelButtonTracker::new();
+        self.in_proximity = false;
+        self.last_pointer_id = PEN_POINTER_ID;
+        self.dropping = false;
+        self.dropped_frames = 0;
+        self.resync_time_us = None;
+    }
+
+    pub fn set_output_mapping(&mut self, mapping: EvdevOutputMapping) {
+        self.mapping = mapping;
+        self.mapper = Self::build_mapper(&self.caps, &self.mapping);
+    }
+
+    pub fn diagnostics_snapshot(&self) -> TabletV3Diagnostics {
+        self.diagnostics.clone()
+    }
+
+    /// Frames discarded after the kernel reported `SYN_DROPPED`
+    pub fn dropped_frames(&self) -> u64 {
+        self.dropped_frames
+    }
+
+    /// Feed one raw event; a complete frame (`SYN_REPORT`) appends its output to `out`
+    pub fn process_event(
+        &mut self,
+        event: EvdevRawEvent,
+        host_time_us: u64,
+        out: &mut Vec<TabletEventV3>,
+    ) {
+        match (event.event_type, event.code) {
+            (EV_SYN, SYN_DROPPED) => {
+                self.dropping = true;
+            }
+            (EV_SYN, SYN_REPORT) => {
+                if self.dropping {
+                    // Partial frame: state is stale until the reader resyncs
+                    self.dropping = false;
+                    self.dropped_frames = self.dropped_frames.saturating_add(1);
+                    self.resync_time_us = Some(event.time_us);
+                    return;
+                }
+                self.emit_frame(event.time_us, host_time_us, out);
+            }
+            (EV_KEY, code) => {
+                let pressed = event.value != 0;
+                let tool = match code {
+                    BTN_TOOL_PEN | BTN_TOOL_BRUSH | BTN_TOOL_PENCIL => Some(ToolTypeV3::Pen),
+                    BTN_TOOL_AIRBRUSH => Some(ToolTypeV3::Airbrush),
+                    BTN_TOOL_MOUSE | BTN_TOOL_LENS => Some(ToolTypeV3::Mouse),
+                    _ => None,
+                };
+                if let Some(tool) = tool {
+                    self.state.pen = pressed;
+                    if pressed {
+                        self.state.tool = tool;
+                    }
+                }
+                let button_bit = match code {
+                    BTN_STYLUS => 1,
+                    BTN_STYLUS2 => 1 << 1,
+                    BTN_STYLUS3 => 1 << 2,
+                    _ => 0,
+                };
+                if pressed {
+                    self.state.buttons |= button_bit;
+                } else {
+                    self.state.buttons &= !button_bit;
+                }
+                match code {
+                    BTN_TOOL_RUBBER => self.state.eraser = pressed,
+                    BTN_TOUCH => self.state.touching = pressed,
+                    _ => {}
+                }
+            }
+            (EV_ABS, code) => match code {
+                ABS_X => self.state.x = event.value,
+                ABS_Y => self.state.y = event.value,
+                ABS_PRESSURE => self.state.pressure = event.value,
+                ABS_TILT_X => self.state.tilt_x = event.value,
+                ABS_TILT_Y => self.state.tilt_y = event.value,
+                ABS_DISTANCE => self.state.distance = event.value,
+                ABS_Z => self.state.rotation = event.value,
+                ABS_WHEEL => self.state.wheel = event.value,
+                _ => {}
+            },
+            (EV_MSC, MSC_SERIAL) => {
+                // Serials are 32-bit on the wire; 0 means the tool has none
+                self.state.serial = (event.value != 0).then_some(event.value as u32 as u64);
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether events were dropped and device state must be read back
+    pub fn needs_resync(&self) -> bool {
+        self.resync_time_us.is_some()
+    }
+
+    /// Replace key and axis state with a device snapshot and emit the frame it
+    /// implies, so a pen-up or proximity leave lost in the drop still arrives
+    pub fn resync(
+        &mut self,
+        sync: &EvdevSyncState,
+        host_time_us: u64,
+        out: &mut Vec<TabletEventV3>,
+    ) {
+        let Some(device_time_us) = self.resync_time_us.take() else {
+            return;
+        };
+        self.state.pen = false;
+        self.state.eraser = false;
+        self.state.touching = false;
+        self.state.buttons = 0;
+        let keys = sync
+            .pressed_keys
+            .iter()
+            .map(|&code| EvdevRawEvent::new(device_time_us, EV_KEY, code, 1));
+        let axes = sync
+            .abs_values
+            .iter()
+            .map(|&(code, value)| EvdevRawEvent::new(device_time_us, EV_ABS, code, value));
+        for event in keys.chain(axes) {
+            self.process_event(event, host_time_us, out);
+        }
+        self.emit_frame(device_time_us, host_time_us, out);
+    }
+
+    fn emit_frame(&mut self, device_time_us: u64, host_time_us: u64, out: &mut Vec<TabletEventV3>) {
+        let tool_active = self.state.pen || self.state.eraser;
+        let out_of_range = self
+            .caps
+            .distance
+            .is_some_and(|axis| axis.max > axis.min && self.state.distance >= axis.max);
+        let in_proximity = tool_active && !out_of_range;
+
+        if in_proximity && !self.in_proximity {
+            out.push(TabletEventV3::ProximityEnter);
+        }
+
+        let pointer_id = if self.state.eraser {
+            ERASER_POINTER_ID
+        } else if self.state.pen {
+            PEN_POINTER_ID
+        } else {
+            self.last_pointer_id
+        };
+        self.last_pointer_id = pointer_id;
+
+        let pressure_norm = self
+            .caps
+            .pressure
+            .map(|axis| axis.normalize(self.state.pressure))
+            .unwrap_or(if self.state.touching { 1.0 } else { 0.0 });
+        let in_contact = tool_active && (self.state.touching || pressure_norm > 0.0);
+
+        if let Some(phase_output) = self
+            .phase_machine
+            .resolve(pointer_id, in_contact, in_proximity)
+        {
+            out.push(TabletEventV3::Input(self.build_sample(
+                pointer_id,
+                phase_output.stroke_id,
+                phase_output.phase,
+                pressure_norm,
+                device_time_us,
+                host_time_us,
+            )));
+        }
+
+        // Buttons only count while the pen is in range; leaving releases them
+        let mask = if in_proximity { self.state.buttons } else { 0 };
+        out.extend(
+            self.barrel
+                .update(mask, &self.device_id, host_time_us)
+                .into_iter()
+                .map(TabletEventV3::Button),
+        );
+
+        if !in_proximity && self.in_proximity {
+            out.push(TabletEventV3::ProximityLeave);
+        }
+        self.in_proximity = in_proximity;
+    }
+
+    fn build_sample(
+        &mut self,
+        pointer_id: u32,
+        stroke_id: u64,
+        phase: InputPhaseV3,
+        pressure_norm: f32,
+        device_time_us: u64,
+        host_time_us: u64,
+    ) -> NativeTabletEventV3 {
+        let (screen_x, screen_y) = self.mapper.map_output_xy(self.state.x, self.state.y);
+        let x_px = screen_x - self.mapping.client_offset_x_px;
+        let y_px = screen_y - self.mapping.client_offset_y_px;
+        if x_px < 0.0 || y_px < 0.0 {
+            self.diagnostics.coord_out_of_view_count =
+                self.diagnostics.coord_out_of_view_count.saturating_add(1);
+        }
+
+        let normalized_host = self
+            .timebase
+            .normalize_host_time_us(pointer_id, host_time_us.max(1));
+        if normalized_host != host_time_us.max(1) {
+            self.diagnostics.host_time_non_monotonic_count = self
+                .diagnostics
+                .host_time_non_monotonic_count
+                .saturating_add(1);
+        }
+
+        let pressure_raw = if phase == InputPhaseV3::Up {
+            0.0
+        } else {
+            pressure_norm
+        };
+        self.diagnostics.pressure_total_count =
+            self.diagnostics.pressure_total_count.saturating_add(1);
+
+        let tool_type = if pointer_id == ERASER_POINTER_ID {
+            ToolTypeV3::Eraser
+        } else {
+            self.state.tool
+        };
+        let wheel_0_1 = match (tool_type, self.caps.wheel) {
+            (ToolTypeV3::Airbrush, Some(axis)) => Some(axis.normalize(self.state.wheel)),
+            _ => None,
+        };
+
+        NativeTabletEventV3 {
+            seq: 0,
+            stroke_id,
+            pointer_id,
+            device_id: self.device_id.clone(),
+            source: InputSourceV3::LinuxEvdev,
+            phase,
+            x_px,
+            y_px,
+            pressure_0_1: clamp_pressure_0_1(pressure_raw),
+            tilt_x_deg: self
+                .caps
+                .tilt_x
+                .map(|axis| axis_to_tilt_deg(self.state.tilt_x, &axis))
+                .unwrap_or(0.0),
+            tilt_y_deg: self
+                .caps
+                .tilt_y
+                .map(|axis| axis_to_tilt_deg(self.state.tilt_y, &axis))
+                .unwrap_or(0.0),
+            rotation_deg: self
+                .caps
+                .rotation
+                .map(|axis| normalize_rotation_deg(axis.normalize(self.state.rotation) * 360.0))
+                .unwrap_or(0.0),
+            host_time_us: normalized_host,
+            device_time_us: Some(device_time_us),
+            tool_type,
+            tool_serial: self.state.serial,
+            wheel_0_1,
+            distance_0_1: self
+                .caps
+                .distance
+                .map(|axis| axis.normalize(self.state.distance)),
+            coalesced: None,
+            predicted: false,
+            prediction_index: None,
+        }
+    }
+}
+
+/// Tilt axes report units per radian; without a resolution the range is
+/// assumed to span +-64 degrees
+fn axis_to_tilt_deg(value: i32, axis: &EvdevAxisInfo) -> f32 {
+    if axis.resolution > 0 {
+        return clamp_tilt_deg((value as f32 / axis.resolution as f32).to_degrees());
+    }
+    let center = (axis.min as f32 + axis.max as f32) * 0.5;
+    let half_range = (axis.max as f32 - axis.min as f32) * 0.5;
+    if half_range <= 0.0 {
+        return 0.0;
+    }
+    clamp_tilt_deg((value as f32 - center) / half_range * DEFAULT_TILT_RANGE_DEG)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wacom_caps() -> EvdevDeviceCaps {
+        EvdevDeviceCaps {
+            x: EvdevAxisInfo::new(0, 44_800),
+            y: EvdevAxisInfo::new(0, 29_600),
+            pressure: Some(EvdevAxisInfo::new(0, 8191)),
+            tilt_x: Some(EvdevAxisInfo {
+                min: -64,
+                max: 63,
+                resolution: 57,
+            }),
+            tilt_y: Some(EvdevAxisInfo {
+                min: -64,
+                max: 63,
+                resolution: 57,
+            }),
+            distance: Some(EvdevAxisInfo::new(0, 63)),
+            rotation: None,
+            wheel: Some(EvdevAxisInfo::new(0, 1023)),
+        }
+    }
+
+    fn mapping(width: f32, height: f32) -> EvdevOutputMapping {
+        EvdevOutputMapping {
+            screen_width_px: width,
+            screen_height_px: height,
+            ..EvdevOutputMapping::default()
+        }
+    }
+
+    /// Feed a frame of (type, code, value) and terminate it with SYN_REPORT
+    fn feed(
+        adapter: &mut EvdevAdapterV3,
+        time_us: u64,
+        events: &[(u16, u16, i32)],
+    ) -> Vec<TabletEventV3> {
+        let mut out = Vec::new();
+        for &(event_type, code, value) in events {
+            adapter.process_event(
+                EvdevRawEvent::new(time_us, event_type, code, value),
+                time_us,
+                &mut out,
+            );
+        }
+        adapter.process_event(
+            EvdevRawEvent::new(time_us, EV_SYN, SYN_REPORT, 0),
+            time_us,
+            &mut out,
+        );
+        out
+    }
+
+    fn samples(events: &[TabletEventV3]) -> Vec<NativeTabletEventV3> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                TabletEventV3::Input(sample) => Some(sample.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_raw_event_roundtrips_through_bytes() {
+        let event = EvdevRawEvent::new(1_700_000_123_456, EV_ABS, ABS_PRESSURE, 4096);
+        let bytes = event.to_bytes();
+        assert_eq!(EvdevRawEvent::from_bytes(&bytes), Some(event));
+        assert_eq!(
+            EvdevRawEvent::from_bytes(&bytes[..EVDEV_EVENT_SIZE - 1]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_hover_down_move_up_maps_axes() {
+        let mut adapter = EvdevAdapterV3::new(
+            "evdev-test".to_string(),
+            wacom_caps(),
+            mapping(1000.0, 500.0),
+        );
+
+        let hover = feed(
+            &mut adapter,
+            1_000,
+            &[
+                (EV_KEY, BTN_TOOL_PEN, 1),
+                (EV_ABS, ABS_X, 22_400),
+                (EV_ABS, ABS_Y, 14_800),
+                (EV_ABS, ABS_DISTANCE, 20),
+            ],
+        );
+        assert!(matches!(hover[0], TabletEventV3::ProximityEnter));
+        let hover = samples(&hover);
+        assert_eq!(hover[0].phase, InputPhaseV3::Hover);
+        assert_eq!(hover[0].source, InputSourceV3::LinuxEvdev);
+        assert!((hover[0].x_px - 500.0).abs() < 0.5);
+        assert!((hover[0].y_px - 250.0).abs() < 0.5);
+        let distance = hover[0].distance_0_1.expect("hover distance");
+        assert!((distance - 20.0 / 63.0).abs() < 1e-4);
+
+        let down = samples(&feed(
+            &mut adapter,
+            2_000,
+            &[
+                (EV_KEY, BTN_TOUCH, 1),
+                (EV_ABS, ABS_PRESSURE, 4096),
+                (EV_ABS, ABS_TILT_X, 57),
+                (EV_ABS, ABS_DISTANCE, 0),
+            ],
+        ));
+        assert_eq!(down[0].phase, InputPhaseV3::Down);
+        assert!((down[0].pressure_0_1 - 0.5).abs() < 0.01);
+        // 57 units at 57 units/rad = 1 rad
+        assert!((down[0].tilt_x_deg - 57.2958).abs() < 0.01);
+        assert_eq!(down[0].device_time_us, Some(2_000));
+        assert_eq!(down[0].distance_0_1, Some(0.0));
+
+        let mv = samples(&feed(&mut adapter, 3_000, &[(EV_ABS, ABS_X, 44_800)]));
+        assert_eq!(mv[0].phase, InputPhaseV3::Move);
+        assert!((mv[0].x_px - 1000.0).abs() < 0.01);
+
+        let up = samples(&feed(
+            &mut adapter,
+            4_000,
+            &[(EV_KEY, BTN_TOUCH, 0), (EV_ABS, ABS_PRESSURE, 0)],
+        ));
+        assert_eq!(up[0].phase, InputPhaseV3::Up);
+        assert_eq!(up[0].pressure_0_1, 0.0);
+        assert_eq!(up[0].stroke_id, down[0].stroke_id);
+        assert_eq!(mv[0].stroke_id, down[0].stroke_id);
+
+        let leave = feed(&mut adapter, 5_000, &[(EV_KEY, BTN_TOOL_PEN, 0)]);
+        assert!(matches!(leave.last(), Some(TabletEventV3::ProximityLeave)));
+    }
+
+    #[test]
+    fn test_eraser_uses_separate_pointer() {
+        let mut adapter = EvdevAdapterV3::new(
+            "evdev-test".to_string(),
+            wacom_caps(),
+            mapping(100.0, 100.0),
+        );
+        let pen = samples(&feed(&mut adapter, 1_000, &[(EV_KEY, BTN_TOOL_PEN, 1)]));
+        feed(&mut adapter, 2_000, &[(EV_KEY, BTN_TOOL_PEN, 0)]);
+        let eraser = samples(&feed(&mut adapter, 3_000, &[(EV_KEY, BTN_TOOL_RUBBER, 1)]));
+        assert_eq!(pen[0].pointer_id, PEN_POINTER_ID);
+        assert_eq!(pen[0].tool_type, ToolTypeV3::Pen);
+        assert_eq!(eraser[0].pointer_id, ERASER_POINTER_ID);
+        assert_eq!(eraser[0].tool_type, ToolTypeV3::Eraser);
+    }
+
+    #[test]
+    fn test_airbrush_reports_serial_and_wheel() {
+        let mut adapter = EvdevAdapterV3::new(
+            "evdev-test".to_string(),
+            wacom_caps(),
+            mapping(100.0, 100.0),
+        );
+        let airbrush = samples(&feed(
+            &mut adapter,
+            1_000,
+            &[
+                (EV_KEY, BTN_TOOL_AIRBRUSH, 1),
+                (EV_ABS, ABS_WHEEL, 1023),
+                (EV_MSC, MSC_SERIAL, 0x1234_5678),
+            ],
+        ));
+        assert_eq!(airbrush[0].tool_type, ToolTypeV3::Airbrush);
+        assert_eq!(airbrush[0].tool_serial, Some(0x1234_5678));
+        assert_eq!(airbrush[0].wheel_0_1, Some(1.0));
+
+        feed(&mut adapter, 2_000, &[(EV_KEY, BTN_TOOL_AIRBRUSH, 0)]);
+        let pen = samples(&feed(
+            &mut adapter,
+            3_000,
+            &[(EV_KEY, BTN_TOOL_PEN, 1), (EV_MSC, MSC_SERIAL, 0x42)],
+        ));
+        assert_eq!(pen[0].tool_type, ToolTypeV3::Pen);
+        assert_eq!(pen[0].tool_serial, Some(0x42));
+        assert_eq!(pen[0].wheel_0_1, None);
+    }
+
+    #[test]
+    fn test_barrel_buttons_emit_edges_and_release_on_leave() {
+        let mut adapter = EvdevAdapterV3::new(
+            "evdev-test".to_string(),
+            wacom_caps(),
+            mapping(100.0, 100.0),
+        );
+        let buttons = |events: &[TabletEventV3]| -> Vec<(u32, bool)> {
+            events
+                .iter()
+                .filter_map(|e| match e {
+                    TabletEventV3::Button(button) => Some((button.index, button.pressed)),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        let pressed = feed(
+            &mut adapter,
+            1_000,
+            &[(EV_KEY, BTN_TOOL_PEN, 1), (EV_KEY, BTN_STYLUS2, 1)],
+        );
+        assert_eq!(buttons(&pressed), vec![(2, true)]);
+        assert!(buttons(&feed(&mut adapter, 2_000, &[(EV_ABS, ABS_X, 10)])).is_empty());
+
+        let left = feed(&mut adapter, 3_000, &[(EV_KEY, BTN_TOOL_PEN, 0)]);
+        assert_eq!(buttons(&left), vec![(2, false)]);
+        assert!(matches!(left.last(), Some(TabletEventV3::ProximityLeave)));
+    }
+
+    #[test]
+    fn test_distance_at_max_is_out_of_proximity() {
+        let mut adapter = EvdevAdapterV3::new(
+            "evdev-test".to_string(),
+            wacom_caps(),
+            mapping(100.0, 100.0),
+        );
+        let out = feed(
+            &mut adapter,
+            1_000,
+            &[(EV_KEY, BTN_TOOL_PEN, 1), (EV_ABS, ABS_DISTANCE, 63)],
+        );
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_dropped_frame_is_skipped() {
+        let mut adapter = EvdevAdapterV3::new(
+            "evdev-test".to_string(),
+            wacom_caps(),
+            mapping(100.0, 100.0),
+        );
+        feed(&mut adapter, 1_000, &[(EV_KEY, BTN_TOOL_PEN, 1)]);
+
+        let mut out = Vec::new();
+        adapter.process_event(
+            EvdevRawEvent::new(2_000, EV_SYN, SYN_DROPPED, 0),
+            2_000,
+            &mut out,
+        );
+        adapter.process_event(
+            EvdevRawEvent::new(2_000, EV_ABS, ABS_X, 10),
+            2_000,
+            &mut out,
+        );
+        adapter.process_event(
+            EvdevRawEvent::new(2_000, EV_SYN, SYN_REPORT, 0),
+            2_000,
+            &mut out,
+        );
+        assert!(out.is_empty());
+        assert_eq!(adapter.dropped_frames(), 1);
+
+        // Next frame is processed normally
+        assert_eq!(samples(&feed(&mut adapter, 3_000, &[])).len(), 1);
+    }
+
+    #[test]
+    fn test_resync_after_drop_releases_lost_pen_up() {
+        let mut adapter = EvdevAdapterV3::new(
+            "evdev-test".to_string(),
+            wacom_caps(),
+            mapping(100.0, 100.0),
+        );
+        let down = samples(&feed(
+            &mut adapter,
+            1_000,
+            &[
+                (EV_KEY, BTN_TOOL_PEN, 1),
+                (EV_KEY, BTN_TOUCH, 1),
+                (EV_ABS, ABS_PRESSURE, 4096),
+            ],
+        ));
+        assert_eq!(down[0].phase, InputPhaseV3::Down);
+
+        // The pen-up is lost in the overflow
+        let mut out = Vec::new();
+        for (code, value) in [(SYN_DROPPED, 0), (SYN_REPORT, 0)] {
+            adapter.process_event(
+                EvdevRawEvent::new(2_000, EV_SYN, code, value),
+                2_000,
+                &mut out,
+            );
+        }
+        assert!(out.is_empty());
+        assert!(adapter.needs_resync());
+
+        adapter.resync(
+            &EvdevSyncState {
+                pressed_keys: vec![BTN_TOOL_PEN],
+                abs_values: vec![(ABS_PRESSURE, 0), (ABS_X, 10)],
+            },
+            2_500,
+            &mut out,
+        );
+        assert!(!adapter.needs_resync());
+        let up = samples(&out);
+        assert_eq!(up[0].phase, InputPhaseV3::Up);
+        assert_eq!(up[0].stroke_id, down[0].stroke_id);
+        assert_eq!(up[0].device_time_us, Some(2_000));
+    }
+
+    #[test]
+    fn test_client_offset_and_tilt_without_resolution() {
+        let mut caps = wacom_caps();
+        caps.tilt_y = Some(EvdevAxisInfo::new(-127, 127));
+        let mut adapter = EvdevAdapterV3::new(
+            "evdev-test".to_string(),
+            caps,
+            EvdevOutputMapping {
+                screen_width_px: 1000.0,
+                screen_height_px: 500.0,
+                client_offset_x_px: 100.0,
+                client_offset_y_px: 50.0,
+                ..EvdevOutputMapping::default()
+            },
+        );
+        let out = samples(&feed(
+            &mut adapter,
+            1_000,
+            &[
+                (EV_KEY, BTN_TOOL_PEN, 1),
+                (EV_ABS, ABS_X, 44_800),
+                (EV_ABS, ABS_Y, 29_600),
+                (EV_ABS, ABS_TILT_Y, -127),
+            ],
+        ));
+        assert!((out[0].x_px - 900.0).abs() < 0.01);
+        assert!((out[0].y_px - 450.0).abs() < 0.01);
+        assert!((out[0].tilt_y_deg + DEFAULT_TILT_RANGE_DEG).abs() < 0.01);
+    }
+}
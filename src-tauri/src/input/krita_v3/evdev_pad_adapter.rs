@@ -0,0 +1,332 @@
+//! Tablet pad (express keys, touch rings/strips) on Linux evdev
+//!
+//! Wacom pads show up as a separate event node next to the pen. Keys use the
+//! `BTN_0..`, `BTN_A..` and `BTN_BASE..` ranges; rings report an absolute angle on
+//! `ABS_WHEEL`/`ABS_THROTTLE` and strips a one-hot bit on `ABS_RX`/`ABS_RY`. Both
+//! report their minimum when the finger lifts.
+
+use super::controls::DialTracker;
+use super::evdev_adapter::{
+    EvdevAxisInfo, EvdevRawEvent, EvdevSyncState, ABS_WHEEL, EV_ABS, EV_KEY, EV_SYN, SYN_DROPPED,
+    SYN_REPORT,
+};
+use super::types::{TabletButtonEventV3, TabletButtonKindV3, TabletDialKindV3};
+use crate::input::backend::TabletEventV3;
+
+pub const BTN_0: u16 = 0x100;
+pub const BTN_9: u16 = 0x109;
+pub const BTN_BASE: u16 = 0x126;
+pub const BTN_BASE6: u16 = 0x12b;
+pub const BTN_A: u16 = 0x130;
+pub const BTN_Z: u16 = 0x135;
+pub const ABS_RX: u16 = 0x03;
+pub const ABS_RY: u16 = 0x04;
+pub const ABS_THROTTLE: u16 = 0x06;
+
+/// Express key index for a pad key code, in ascending code order
+/// (`BTN_0`-`BTN_9`, `BTN_BASE`-`BTN_BASE6`, `BTN_A`-`BTN_Z`) as libinput walks
+/// them. Indices are fixed per code; libinput also skips codes a pad lacks.
+pub fn express_key_index(code: u16) -> Option<u32> {
+    match code {
+        BTN_0..=BTN_9 => Some((code - BTN_0) as u32),
+        BTN_BASE..=BTN_BASE6 => Some(10 + (code - BTN_BASE) as u32),
+        BTN_A..=BTN_Z => Some(16 + (code - BTN_A) as u32),
+        _ => None,
+    }
+}
+
+/// Touch controls reported by a pad device
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EvdevPadCaps {
+    pub ring: Option<EvdevAxisInfo>,
+    pub ring2: Option<EvdevAxisInfo>,
+    pub strip: Option<EvdevAxisInfo>,
+    pub strip2: Option<EvdevAxisInfo>,
+}
+
+#[derive(Debug)]
+struct PadDial {
+    code: u16,
+    axis: EvdevAxisInfo,
+    value: i32,
+    dirty: bool,
+    tracker: DialTracker,
+}
+
+impl PadDial {
+    fn position(&self) -> Option<f32> {
+        if self.value <= self.axis.min {
+            return None;
+        }
+        match self.tracker_kind() {
+            TabletDialKindV3::TouchRing => Some(self.axis.normalize(self.value)),
+            TabletDialKindV3::TouchStrip => {
+                // One-hot: the set bit is the finger position
+                let bits = (self.axis.max.max(1) as u32).ilog2().max(1);
+                let bit = u32::try_from(self.value).ok()?.checked_ilog2()?;
+                Some((bit as f32 / bits as f32).clamp(0.0, 1.0))
+            }
+        }
+    }
+
+    fn tracker_kind(&self) -> TabletDialKindV3 {
+        if self.code == ABS_RX || self.code == ABS_RY {
+            TabletDialKindV3::TouchStrip
+        } else {
+            TabletDialKindV3::TouchRing
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct EvdevPadAdapterV3 {
+    device_id: String,
+    dials: Vec<PadDial>,
+    pending_keys: Vec<(u32, bool)>,
+    /// Express keys reported down, for releasing keys lost in a drop
+    held_keys: Vec<u32>,
+    dropping: bool,
+    resync_pending: bool,
+}
+
+impl EvdevPadAdapterV3 {
+    pub fn new(device_id: String, caps: EvdevPadCaps) -> Self {
+        let layout = [
+            (ABS_WHEEL, TabletDialKindV3::TouchRing, 0, caps.ring),
+            (ABS_THROTTLE, TabletDialKindV3::TouchRing, 1, caps.ring2),
+            (ABS_RX, TabletDialKindV3::TouchStrip, 0, caps.strip),
+            (ABS_RY, TabletDialKindV3::TouchStrip, 1, caps.strip2),
+        ];
+        let dials = layout
+            .into_iter()
+            .filter_map(|(code, kind, index, axis)| {
+                axis.map(|axis| PadDial {
+                    code,
+                    axis,
+                    value: axis.min,
+                    dirty: false,
+                    tracker: DialTracker::new(kind, index),
+                })
+            })
+            .collect();
+        Self {
+            device_id,
+            dials,
+            pending_keys: Vec::new(),
+            held_keys: Vec::new(),
+            dropping: false,
+            resync_pending: false,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        let caps_dials = std::mem::take(&mut self.dials);
+        self.dials = caps_dials
+            .into_iter()
+            .map(|dial| PadDial {
+                value: dial.axis.min,
+                dirty: false,
+                tracker: DialTracker::new(dial.tracker_kind(), dial_index(dial.code)),
+                ..dial
+            })
+            .collect();
+        self.pending_keys.clear();
+        self.held_keys.clear();
+        self.dropping = false;
+        self.resync_pending = false;
+    }
+
+    /// Feed one raw event; a complete frame (`SYN_REPORT`) appends its output to `out`
+    pub fn process_event(
+        &mut self,
+        event: EvdevRawEvent,
+        host_time_us: u64,
+        out: &mut Vec<TabletEventV3>,
+    ) {
+        match (event.event_type, event.code) {
+            (EV_SYN, SYN_DROPPED) => {
+                self.dropping = true;
+            }
+            (EV_SYN, SYN_REPORT) => {
+                if self.dropping {
+                    self.dropping = false;
+                    self.pending_keys.clear();
+                    self.resync_pending = true;
+                    return;
+                }
+                self.emit_frame(host_time_us, out);
+            }
+            (EV_KEY, code) => {
+                if let Some(index) = express_key_index(code) {
+                    // Autorepeat (value 2) is not a new press
+                    if event.value != 2 {
+                        self.pending_keys.push((index, event.value != 0));
+                    }
+                }
+            }
+            (EV_ABS, code) => {
+                if let Some(dial) = self.dials.iter_mut().find(|dial| dial.code == code) {
+                    dial.value = event.value;
+                    dial.dirty = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether events were dropped and device state must be read back
+    pub fn needs_resync(&self) -> bool {
+        self.resync_pending
+    }
+
+    /// Diff held keys and ring/strip values against a device snapshot and emit
+    /// the changes lost in a drop
+    pub fn resync(
+        &mut self,
+        sync: &EvdevSyncState,
+        host_time_us: u64,
+        out: &mut Vec<TabletEventV3>,
+    ) {
+        if !std::mem::take(&mut self.resync_pending) {
+            return;
+        }
+        let pressed: Vec<u32> = sync
+            .pressed_keys
+            .iter()
+            .filter_map(|&code| express_key_index(code))
+            .collect();
+        for &index in &self.held_keys {
+            if !pressed.contains(&index) {
+                self.pending_keys.push((index, false));
+            }
+        }
+        for &index in &pressed {
+            if !self.held_keys.contains(&index) {
+                self.pending_keys.push((index, true));
+            }
+        }
+        for &(code, value) in &sync.abs_values {
+            if let Some(dial) = self.dials.iter_mut().find(|dial| dial.code == code) {
+                dial.dirty = dial.value != value;
+                dial.value = value;
+            }
+        }
+        self.emit_frame(host_time_us, out);
+    }
+
+    fn emit_frame(&mut self, host_time_us: u64, out: &mut Vec<TabletEventV3>) {
+        for (index, pressed) in self.pending_keys.drain(..) {
+            if pressed {
+                if !self.held_keys.contains(&index) {
+                    self.held_keys.push(index);
+                }
+            } else {
+                self.held_keys.retain(|&held| held != index);
+            }
+            out.push(TabletEventV3::Button(TabletButtonEventV3 {
+                kind: TabletButtonKindV3::ExpressKey,
+                index,
+                pressed,
+                device_id: self.device_id.clone(),
+                host_time_us,
+            }));
+        }
+        for dial in self.dials.iter_mut().filter(|dial| dial.dirty) {
+            dial.dirty = false;
+            let position = dial.position();
+            if let Some(event) = dial.tracker.update(position, &self.device_id, host_time_us) {
+                out.push(TabletEventV3::Dial(event));
+            }
+        }
+    }
+}
+
+fn dial_index(code: u16) -> u32 {
+    match code {
+        ABS_THROTTLE | ABS_RY => 1,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(
+        adapter: &mut EvdevPadAdapterV3,
+        time_us: u64,
+        events: &[(u16, u16, i32)],
+    ) -> Vec<TabletEventV3> {
+        let mut out = Vec::new();
+        for &(event_type, code, value) in events {
+            adapter.process_event(
+                EvdevRawEvent::new(time_us, event_type, code, value),
+                time_us,
+                &mut out,
+            );
+        }
+        adapter.process_event(
+            EvdevRawEvent::new(time_us, EV_SYN, SYN_REPORT, 0),
+            time_us,
+            &mut out,
+        );
+        out
+    }
+
+    #[test]
+    fn test_express_keys_and_ring_emit_control_events() {
+        let mut adapter = EvdevPadAdapterV3::new(
+            "pad".to_string(),
+            EvdevPadCaps {
+                ring: Some(EvdevAxisInfo::new(0, 71)),
+                strip: Some(EvdevAxisInfo::new(0, 4096)),
+                ..EvdevPadCaps::default()
+            },
+        );
+
+        let keys = feed(
+            &mut adapter,
+            1_000,
+            &[(EV_KEY, BTN_A, 1), (EV_KEY, BTN_0, 2)],
+        );
+        assert_eq!(keys.len(), 1);
+        let TabletEventV3::Button(key) = &keys[0] else {
+            panic!("expected button");
+        };
+        assert_eq!(key.kind, TabletButtonKindV3::ExpressKey);
+        assert_eq!(key.index, 16);
+        assert!(key.pressed);
+
+        feed(&mut adapter, 2_000, &[(EV_ABS, ABS_WHEEL, 36)]);
+        let turned = feed(&mut adapter, 3_000, &[(EV_ABS, ABS_WHEEL, 43)]);
+        let TabletEventV3::Dial(dial) = &turned[0] else {
+            panic!("expected dial");
+        };
+        assert_eq!(dial.kind, TabletDialKindV3::TouchRing);
+        assert!((dial.delta - 7.0 / 71.0).abs() < 1e-4);
+
+        let lifted = feed(&mut adapter, 4_000, &[(EV_ABS, ABS_WHEEL, 0)]);
+        let TabletEventV3::Dial(dial) = &lifted[0] else {
+            panic!("expected dial");
+        };
+        assert_eq!(dial.position_0_1, None);
+
+        let strip = feed(&mut adapter, 5_000, &[(EV_ABS, ABS_RX, 1 << 6)]);
+        let TabletEventV3::Dial(dial) = &strip[0] else {
+            panic!("expected dial");
+        };
+        assert_eq!(dial.kind, TabletDialKindV3::TouchStrip);
+        assert_eq!(dial.position_0_1, Some(0.5));
+    }
+
+    #[test]
+    fn test_key_ranges_map_to_sequential_indices() {
+        assert_eq!(express_key_index(BTN_0), Some(0));
+        assert_eq!(express_key_index(BTN_9), Some(9));
+        assert_eq!(express_key_index(BTN_BASE), Some(10));
+        assert_eq!(express_key_index(BTN_BASE6), Some(15));
+        assert_eq!(express_key_index(BTN_A), Some(16));
+        assert_eq!(express_key_index(BTN_Z), Some(21));
+        assert_eq!(express_key_index(0x14b), None);
+    }
+}
@@ -3,7 +3,7 @@ use super::phase_machine_mac::PhaseMachineMac;
 use super::timebase_mac::MonotonicTimebaseMac;
 use super::types::{
     clamp_pressure_0_1, clamp_tilt_deg, normalize_rotation_deg, InputPhaseV3, InputSourceV3,
-    NativeTabletEventV3,
+    NativeTabletEventV3, ToolTypeV3,
 };
 use crate::input::backend::TabletV3Diagnostics;
 
@@ -28,6 +28,9 @@ pub struct MacNativeRawSample {
     pub rotation_deg: f32,
     pub host_time_us: u64,
     pub device_time_us: Option<u64>,
+    pub tool_type: ToolTypeV3,
+    pub tool_serial: Option<u64>,
+    pub wheel_0_1: Option<f32>,
 }
 
 #[derive(Debug)]
@@ -153,6 +156,14 @@ impl MacNativeAdapterV3 {
             rotation_deg: normalize_rotation_deg(raw.rotation_deg),
             host_time_us,
             device_time_us,
+            tool_type: raw.tool_type,
+            tool_serial: raw.tool_serial,
+            wheel_0_1: raw.wheel_0_1.map(clamp_pressure_0_1),
+            // AppKit does not report hover height
+            distance_0_1: None,
+            coalesced: None,
+            predicted: false,
+            prediction_index: None,
         })
     }
 }
@@ -174,6 +185,9 @@ mod tests {
             rotation_deg: 0.0,
             host_time_us,
             device_time_us: Some(host_time_us),
+            tool_type: ToolTypeV3::Pen,
+            tool_serial: None,
+            wheel_0_1: None,
         }
     }
 
@@ -1,5 +1,8 @@
+pub mod controls;
 pub mod coordinate_mapper;
 pub mod coordinate_mapper_mac;
+pub mod evdev_adapter;
+pub mod evdev_pad_adapter;
 pub mod macnative_adapter;
 pub mod phase_machine;
 pub mod phase_machine_mac;
@@ -8,9 +11,18 @@ pub mod timebase_mac;
 pub mod types;
 #[cfg(target_os = "windows")]
 pub mod wintab_adapter;
+pub mod wintab_pad_adapter;
 
-pub use coordinate_mapper::CoordinateMapper;
+pub use controls::{BarrelButtonTracker, DialTracker};
+pub use coordinate_mapper::{
+    CoordinateMapper, NormalizedRectV1, TabletMappingV1, TabletRotationV1,
+};
 pub use coordinate_mapper_mac::{CoordinateMapperMac, MappedCoordinateMac};
+pub use evdev_adapter::{
+    EvdevAdapterV3, EvdevAxisInfo, EvdevDeviceCaps, EvdevOutputMapping, EvdevRawEvent,
+    EvdevSyncState,
+};
+pub use evdev_pad_adapter::{EvdevPadAdapterV3, EvdevPadCaps};
 pub use macnative_adapter::{MacNativeAdapterV3, MacNativeEventKind, MacNativeRawSample};
 pub use phase_machine::{PhaseMachine, PhaseOutput};
 pub use phase_machine_mac::PhaseOutputMac;
@@ -19,3 +31,4 @@ pub use timebase_mac::MonotonicTimebaseMac;
 pub use types::{InputPhaseV3, InputSourceV3, NativeTabletEventV3};
 #[cfg(target_os = "windows")]
 pub use wintab_adapter::WinTabAdapter;
+pub use wintab_pad_adapter::WinTabPadAdapter;
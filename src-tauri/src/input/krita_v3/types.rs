@@ -6,6 +6,7 @@ pub enum InputSourceV3 {
     WinTab,
     PointerEvent,
     MacNative,
+    LinuxEvdev,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -17,6 +18,126 @@ pub enum InputPhaseV3 {
     Up,
 }
 
+/// Physical tool that produced a sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolTypeV3 {
+    #[default]
+    Pen,
+    Eraser,
+    Airbrush,
+    Mouse,
+    /// Finger contact; routed to gestures, never to strokes
+    Touch,
+}
+
+impl ToolTypeV3 {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pen => "pen",
+            Self::Eraser => "eraser",
+            Self::Airbrush => "airbrush",
+            Self::Mouse => "mouse",
+            Self::Touch => "touch",
+        }
+    }
+}
+
+/// Wacom tool id (WinTab `CSR_TYPE`, AppKit `vendorPointingDeviceType`) to tool type
+pub fn wacom_tool_type(tool_id: u32) -> Option<ToolTypeV3> {
+    match tool_id & 0x0F06 {
+        0x0902 => Some(ToolTypeV3::Airbrush),
+        0x0802 | 0x0804 => Some(ToolTypeV3::Pen),
+        0x0004 | 0x0006 => Some(ToolTypeV3::Mouse),
+        _ => None,
+    }
+}
+
+/// WinTab cursor index to tool type (Wacom drivers expose puck/pen/eraser in groups of three)
+pub fn wintab_cursor_tool_type(cursor: u32) -> ToolTypeV3 {
+    match cursor % 3 {
+        0 => ToolTypeV3::Mouse,
+        2 => ToolTypeV3::Eraser,
+        _ => ToolTypeV3::Pen,
+    }
+}
+
+/// Emitted before the first sample of a tool that differs from the previous one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolChangeV3 {
+    pub pointer_id: u32,
+    pub tool_type: ToolTypeV3,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_serial: Option<u64>,
+    /// Key of the persisted tool profile that applies (empty if none)
+    pub profile_key: String,
+    /// Frontend tool to activate (e.g. "eraser")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_id: Option<String>,
+    /// Brush preset bound to this tool
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brush_preset_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TabletButtonKindV3 {
+    /// Side switch on the pen; index 1 is the lower one
+    PenBarrel,
+    /// Key on the tablet (pad); index 0 is the first key in device order
+    ExpressKey,
+}
+
+impl TabletButtonKindV3 {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::PenBarrel => "pen_barrel",
+            Self::ExpressKey => "express_key",
+        }
+    }
+}
+
+/// Press or release of a non-tip button
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TabletButtonEventV3 {
+    pub kind: TabletButtonKindV3,
+    pub index: u32,
+    pub pressed: bool,
+    pub device_id: String,
+    pub host_time_us: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TabletDialKindV3 {
+    /// Circular touch ring; positions wrap around
+    TouchRing,
+    /// Linear touch strip
+    TouchStrip,
+}
+
+impl TabletDialKindV3 {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::TouchRing => "touch_ring",
+            Self::TouchStrip => "touch_strip",
+        }
+    }
+}
+
+/// Finger movement on a touch ring or strip
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TabletDialEventV3 {
+    pub kind: TabletDialKindV3,
+    pub index: u32,
+    /// Finger position, `None` once the finger lifts
+    pub position_0_1: Option<f32>,
+    /// Signed travel since the previous position (shortest way round for rings)
+    pub delta: f32,
+    pub device_id: String,
+    pub host_time_us: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NativeTabletEventV3 {
     pub seq: u64,
@@ -34,6 +155,36 @@ pub struct NativeTabletEventV3 {
     pub host_time_us: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub device_time_us: Option<u64>,
+    #[serde(default)]
+    pub tool_type: ToolTypeV3,
+    /// Hardware serial of the tool, when the platform reports one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_serial: Option<u64>,
+    /// Airbrush finger wheel position
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wheel_0_1: Option<f32>,
+    /// Hover height: 0 at the surface, 1 at the edge of proximity range
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distance_0_1: Option<f32>,
+    /// Set when congestion merged several Move samples into this one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coalesced: Option<CoalescedSpanV3>,
+    /// Extrapolated past the newest real sample rather than reported by the
+    /// device; such samples keep the `seq` of the real sample they extend
+    #[serde(default)]
+    pub predicted: bool,
+    /// 1-based position of a predicted sample after that real sample
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prediction_index: Option<u32>,
+}
+
+/// Samples folded into one Move by the coalescing backpressure mode
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CoalescedSpanV3 {
+    /// Number of raw samples the event stands for (>= 2)
+    pub sample_count: u32,
+    /// Peak pressure across the merged samples
+    pub max_pressure_0_1: f32,
 }
 
 pub fn clamp_pressure_0_1(value: f32) -> f32 {
@@ -5,13 +5,15 @@ use super::coordinate_mapper::CoordinateMapper;
 use super::phase_machine::PhaseMachine;
 use super::timebase::MonotonicTimebase;
 use super::types::{
-    clamp_pressure_0_1, clamp_tilt_deg, normalize_rotation_deg, InputPhaseV3, InputSourceV3,
-    NativeTabletEventV3,
+    clamp_pressure_0_1, clamp_tilt_deg, normalize_rotation_deg, wintab_cursor_tool_type,
+    InputPhaseV3, InputSourceV3, NativeTabletEventV3, ToolTypeV3,
 };
 
 const WINTAB_ANGLE_TENTHS_PER_DEGREE: f32 = 10.0;
 const WINTAB_PROXIMITY_STATUS_BIT: u32 = 0x01;
 const WINTAB_CONTACT_BUTTON_MASK: u32 = 0x01;
+/// Wacom airbrush wheel range (tangent pressure axis)
+const WINTAB_WHEEL_MAX: f32 = 1023.0;
 
 fn orientation_to_tilt_degrees(azimuth_tenths: i32, altitude_tenths: i32) -> (f32, f32) {
     let azimuth_rad = (azimuth_tenths as f32 / WINTAB_ANGLE_TENTHS_PER_DEGREE).to_radians();
@@ -35,6 +37,10 @@ pub struct WinTabAdapter {
     mapper: CoordinateMapper,
     phase_machine: PhaseMachine,
     timebase: MonotonicTimebase,
+    /// Set once the stylus reports tangent pressure; only airbrushes have a wheel
+    wheel_seen: bool,
+    /// Device Z axis range, used as hover height
+    distance_range: Option<(i32, i32)>,
 }
 
 impl WinTabAdapter {
@@ -51,9 +57,16 @@ impl WinTabAdapter {
             mapper,
             phase_machine: PhaseMachine::new(),
             timebase: MonotonicTimebase::new(),
+            wheel_seen: false,
+            distance_range: None,
         }
     }
 
+    pub fn with_distance_range(mut self, range: Option<(i32, i32)>) -> Self {
+        self.distance_range = range.filter(|(min, max)| max > min);
+        self
+    }
+
     #[cfg(target_os = "windows")]
     pub fn convert_packet(
         &mut self,
@@ -66,6 +79,16 @@ impl WinTabAdapter {
             clamp_pressure_0_1(packet.pkNormalPressure as f32 / self.pressure_max);
         let in_contact = contact_bit || normalized_pressure > 0.0;
 
+        if !proximity_bit && !in_contact {
+            self.wheel_seen = false;
+        } else if packet.pkTangentPressure != 0 {
+            self.wheel_seen = true;
+        }
+        let tool_type = match wintab_cursor_tool_type(packet.pkCursor) {
+            ToolTypeV3::Pen if self.wheel_seen => ToolTypeV3::Airbrush,
+            other => other,
+        };
+
         let phase = self
             .phase_machine
             .resolve(self.pointer_id, in_contact, proximity_bit)?;
@@ -102,6 +125,16 @@ impl WinTabAdapter {
             ),
             host_time_us,
             device_time_us: Some((packet.pkTime as u64).saturating_mul(1000)),
+            tool_type,
+            tool_serial: None,
+            wheel_0_1: (tool_type == ToolTypeV3::Airbrush)
+                .then(|| clamp_pressure_0_1(packet.pkTangentPressure as f32 / WINTAB_WHEEL_MAX)),
+            distance_0_1: self.distance_range.map(|(min, max)| {
+                clamp_pressure_0_1((packet.pkXYZ.z - min) as f32 / (max - min) as f32)
+            }),
+            coalesced: None,
+            predicted: false,
+            prediction_index: None,
         })
     }
 
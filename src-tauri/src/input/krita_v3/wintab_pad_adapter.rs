@@ -0,0 +1,335 @@
+//! Tablet pad (express keys, touch rings/strips) through WinTab extensions
+//!
+//! Wacom exposes pad controls as the WinTab extensions `WTX_EXPKEYS2`,
+//! `WTX_TOUCHRING` and `WTX_TOUCHSTRIP`. Once a context includes their masks in
+//! `lcPktData` and overrides the controls with `WTExtSet`, the driver posts
+//! `WT_PACKETEXT` to the context window; `WTPacket` then returns a `PACKETEXT`.
+//! Every packet carries all three sections, so only sections that differ from
+//! the previous packet are reported. Sliders report position 0 while no finger
+//! is on them.
+
+use super::controls::DialTracker;
+use super::types::{TabletButtonEventV3, TabletButtonKindV3, TabletDialKindV3};
+use crate::input::backend::TabletEventV3;
+use std::collections::HashMap;
+
+pub const WT_PACKETEXT: u32 = 0x7FF8;
+pub const WTI_EXTENSIONS: u32 = 210;
+pub const EXT_TAG: u32 = 2;
+pub const EXT_MASK: u32 = 3;
+pub const WTX_TOUCHSTRIP: u32 = 6;
+pub const WTX_TOUCHRING: u32 = 7;
+pub const WTX_EXPKEYS2: u32 = 8;
+pub const TABLET_PROPERTY_CONTROLCOUNT: u16 = 0;
+pub const TABLET_PROPERTY_FUNCCOUNT: u16 = 1;
+pub const TABLET_PROPERTY_MIN: u16 = 3;
+pub const TABLET_PROPERTY_MAX: u16 = 4;
+pub const TABLET_PROPERTY_OVERRIDE: u16 = 5;
+
+/// `EXTENSIONBASE`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExtensionBaseRaw {
+    pub context: usize,
+    pub status: u32,
+    pub time: u32,
+    pub serial: u32,
+}
+
+/// `EXPKEYSDATA`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExpKeysDataRaw {
+    pub tablet: u8,
+    pub control: u8,
+    pub location: u8,
+    pub reserved: u8,
+    pub state: u32,
+}
+
+/// `SLIDERDATA`, used by touch rings and strips
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SliderDataRaw {
+    pub tablet: u8,
+    pub control: u8,
+    pub mode: u8,
+    pub reserved: u8,
+    pub position: u32,
+}
+
+/// `PACKETEXT` with all three pad extensions enabled
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PacketExtRaw {
+    pub base: ExtensionBaseRaw,
+    pub exp_keys: ExpKeysDataRaw,
+    pub touch_strip: SliderDataRaw,
+    pub touch_ring: SliderDataRaw,
+}
+
+/// `EXTPROPERTY` with room for a 4-byte value (`UINT` ranges, `BOOL` override)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExtPropertyRaw {
+    pub version: u8,
+    pub tablet_index: u8,
+    pub control_index: u8,
+    pub function_index: u8,
+    pub property_id: u16,
+    pub reserved: u16,
+    pub data_size: u32,
+    pub data: [u8; 4],
+}
+
+impl ExtPropertyRaw {
+    pub fn new(tablet: u8, control: u8, function: u8, property_id: u16, value: u32) -> Self {
+        Self {
+            tablet_index: tablet,
+            control_index: control,
+            function_index: function,
+            property_id,
+            data_size: 4,
+            data: value.to_le_bytes(),
+            ..Self::default()
+        }
+    }
+
+    pub fn value(&self) -> u32 {
+        u32::from_le_bytes(self.data)
+    }
+}
+
+#[derive(Debug)]
+struct PadSlider {
+    kind: TabletDialKindV3,
+    min: u32,
+    max: u32,
+    tracker: DialTracker,
+}
+
+impl PadSlider {
+    fn position(&self, raw: u32) -> Option<f32> {
+        if raw == 0 {
+            return None;
+        }
+        let min = self.min.max(1);
+        let span = self.max.saturating_sub(min);
+        if span == 0 {
+            return Some(0.0);
+        }
+        let offset = raw.clamp(min, self.max) - min;
+        let position = match self.kind {
+            // The last ring position sits next to the first
+            TabletDialKindV3::TouchRing => offset as f32 / (span + 1) as f32,
+            TabletDialKindV3::TouchStrip => offset as f32 / span as f32,
+        };
+        Some(position)
+    }
+}
+
+/// Turns `PACKETEXT` updates into `Button` / `Dial` events
+#[derive(Debug)]
+pub struct WinTabPadAdapter {
+    device_id: String,
+    previous: PacketExtRaw,
+    /// Express keys reported down
+    held_keys: Vec<u32>,
+    /// Keyed by kind and control index
+    sliders: HashMap<(TabletDialKindV3, u32), PadSlider>,
+}
+
+impl WinTabPadAdapter {
+    pub fn new(device_id: String) -> Self {
+        Self {
+            device_id,
+            previous: PacketExtRaw::default(),
+            held_keys: Vec::new(),
+            sliders: HashMap::new(),
+        }
+    }
+
+    /// Position range of a ring or strip, from `TABLET_PROPERTY_MIN`/`MAX`
+    pub fn set_slider_range(&mut self, kind: TabletDialKindV3, control: u32, min: u32, max: u32) {
+        self.sliders.insert(
+            (kind, control),
+            PadSlider {
+                kind,
+                min,
+                max,
+                tracker: DialTracker::new(kind, control),
+            },
+        );
+    }
+
+    /// Feed one `PACKETEXT`; events for the sections that changed go to `out`
+    pub fn process_packet(
+        &mut self,
+        packet: &PacketExtRaw,
+        host_time_us: u64,
+        out: &mut Vec<TabletEventV3>,
+    ) {
+        let previous = std::mem::replace(&mut self.previous, *packet);
+        if packet.exp_keys != previous.exp_keys {
+            let index = packet.exp_keys.control as u32;
+            let pressed = packet.exp_keys.state != 0;
+            let held = self.held_keys.contains(&index);
+            if pressed != held {
+                if pressed {
+                    self.held_keys.push(index);
+                } else {
+                    self.held_keys.retain(|&key| key != index);
+                }
+                out.push(TabletEventV3::Button(TabletButtonEventV3 {
+                    kind: TabletButtonKindV3::ExpressKey,
+                    index,
+                    pressed,
+                    device_id: self.device_id.clone(),
+                    host_time_us,
+                }));
+            }
+        }
+        for (kind, slider, previous) in [
+            (
+                TabletDialKindV3::TouchRing,
+                &packet.touch_ring,
+                &previous.touch_ring,
+            ),
+            (
+                TabletDialKindV3::TouchStrip,
+                &packet.touch_strip,
+                &previous.touch_strip,
+            ),
+        ] {
+            if slider == previous {
+                continue;
+            }
+            let control = slider.control as u32;
+            let Some(state) = self.sliders.get_mut(&(kind, control)) else {
+                continue;
+            };
+            let position = state.position(slider.position);
+            if let Some(event) = state
+                .tracker
+                .update(position, &self.device_id, host_time_us)
+            {
+                out.push(TabletEventV3::Dial(event));
+            }
+        }
+    }
+
+    /// Release held keys and lift fingers, e.g. when the context closes
+    pub fn release_all(&mut self, host_time_us: u64, out: &mut Vec<TabletEventV3>) {
+        for index in self.held_keys.drain(..) {
+            out.push(TabletEventV3::Button(TabletButtonEventV3 {
+                kind: TabletButtonKindV3::ExpressKey,
+                index,
+                pressed: false,
+                device_id: self.device_id.clone(),
+                host_time_us,
+            }));
+        }
+        for slider in self.sliders.values_mut() {
+            if let Some(event) = slider.tracker.update(None, &self.device_id, host_time_us) {
+                out.push(TabletEventV3::Dial(event));
+            }
+        }
+        self.previous = PacketExtRaw::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(key: (u8, u32), ring: (u8, u32), strip: (u8, u32)) -> PacketExtRaw {
+        PacketExtRaw {
+            exp_keys: ExpKeysDataRaw {
+                control: key.0,
+                state: key.1,
+                ..ExpKeysDataRaw::default()
+            },
+            touch_ring: SliderDataRaw {
+                control: ring.0,
+                position: ring.1,
+                ..SliderDataRaw::default()
+            },
+            touch_strip: SliderDataRaw {
+                control: strip.0,
+                position: strip.1,
+                ..SliderDataRaw::default()
+            },
+            ..PacketExtRaw::default()
+        }
+    }
+
+    #[test]
+    fn test_changed_sections_emit_key_and_dial_events() {
+        let mut adapter = WinTabPadAdapter::new("wintab_pad".to_string());
+        adapter.set_slider_range(TabletDialKindV3::TouchRing, 0, 1, 72);
+        adapter.set_slider_range(TabletDialKindV3::TouchStrip, 0, 1, 9);
+        let mut out = Vec::new();
+
+        adapter.process_packet(&packet((3, 1), (0, 0), (0, 0)), 1, &mut out);
+        let [TabletEventV3::Button(key)] = out.as_slice() else {
+            panic!("expected one key press, got {:?}", out);
+        };
+        assert_eq!(key.kind, TabletButtonKindV3::ExpressKey);
+        assert_eq!(key.index, 3);
+        assert!(key.pressed);
+
+        // The ring moves while the key is held: the unchanged key section is ignored
+        out.clear();
+        adapter.process_packet(&packet((3, 1), (0, 37), (0, 0)), 2, &mut out);
+        adapter.process_packet(&packet((3, 1), (0, 44), (0, 0)), 3, &mut out);
+        let dials: Vec<_> = out
+            .iter()
+            .map(|event| match event {
+                TabletEventV3::Dial(dial) => dial,
+                other => panic!("expected dial, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(dials.len(), 2);
+        assert_eq!(dials[0].kind, TabletDialKindV3::TouchRing);
+        assert!((dials[1].delta - 7.0 / 72.0).abs() < 1e-4);
+
+        out.clear();
+        adapter.process_packet(&packet((3, 0), (0, 0), (0, 5)), 4, &mut out);
+        assert!(matches!(
+            &out[0],
+            TabletEventV3::Button(key) if key.index == 3 && !key.pressed
+        ));
+        assert!(matches!(
+            &out[1],
+            TabletEventV3::Dial(dial) if dial.kind == TabletDialKindV3::TouchRing
+                && dial.position_0_1.is_none()
+        ));
+        assert!(matches!(
+            &out[2],
+            TabletEventV3::Dial(dial) if dial.kind == TabletDialKindV3::TouchStrip
+                && dial.position_0_1 == Some(0.5)
+        ));
+
+        out.clear();
+        adapter.process_packet(&packet((1, 1), (0, 0), (0, 5)), 5, &mut out);
+        adapter.release_all(6, &mut out);
+        let released: Vec<(u32, bool)> = out
+            .iter()
+            .filter_map(|event| match event {
+                TabletEventV3::Button(key) => Some((key.index, key.pressed)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(released, vec![(1, true), (1, false)]);
+    }
+
+    #[test]
+    fn test_raw_layouts_match_wintab_headers() {
+        assert_eq!(std::mem::size_of::<ExpKeysDataRaw>(), 8);
+        assert_eq!(std::mem::size_of::<SliderDataRaw>(), 8);
+        assert_eq!(std::mem::size_of::<ExtPropertyRaw>(), 16);
+        let property = ExtPropertyRaw::new(0, 2, 1, TABLET_PROPERTY_MAX, 71);
+        assert_eq!(property.value(), 71);
+        assert_eq!(property.data_size, 4);
+    }
+}
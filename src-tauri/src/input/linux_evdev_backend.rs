@@ -0,0 +1,939 @@
+//! Linux evdev tablet backend implementation.
+//!
+//! Reads pen pressure/tilt/distance straight from `/dev/input/event*` instead of the
+//! coalesced WebKitGTK pointer events, and feeds them into the V3 tablet queue.
+//! The device node needs read access (usually membership in the `input` group).
+//!
+//! Any byte stream of `struct input_event` records works as a device, so a
+//! recorded capture file can stand in for real hardware in tests.
+//!
+//! When the pen is auto-discovered, the tablet's pad node (express keys, touch
+//! rings and strips) is read on a second thread into the same queue.
+
+#[cfg(target_os = "linux")]
+use super::backend::NativeTabletEventV3;
+#[cfg(target_os = "linux")]
+use super::backend::{default_event_queue_capacity, InputEventQueue, TabletV3Diagnostics};
+use super::backend::{
+    InputQueueMetrics, TabletBackend, TabletConfig, TabletEventV3, TabletInfo, TabletStatus,
+};
+#[cfg(target_os = "linux")]
+use super::krita_v3::evdev_pad_adapter::{ABS_RX, ABS_RY, ABS_THROTTLE, BTN_0};
+#[cfg(target_os = "linux")]
+use super::krita_v3::{
+    EvdevAdapterV3, EvdevAxisInfo, EvdevPadAdapterV3, EvdevPadCaps, EvdevRawEvent, EvdevSyncState,
+};
+use super::krita_v3::{EvdevDeviceCaps, EvdevOutputMapping};
+use std::path::PathBuf;
+
+#[cfg(target_os = "linux")]
+use super::krita_v3::evdev_adapter::{
+    ABS_DISTANCE, ABS_PRESSURE, ABS_TILT_X, ABS_TILT_Y, ABS_WHEEL, ABS_X, ABS_Y, ABS_Z,
+    BTN_TOOL_PEN, EVDEV_EVENT_SIZE, EV_ABS, EV_KEY,
+};
+#[cfg(target_os = "linux")]
+use std::fs::File;
+#[cfg(target_os = "linux")]
+use std::io::Read;
+#[cfg(target_os = "linux")]
+use std::os::unix::fs::OpenOptionsExt;
+#[cfg(target_os = "linux")]
+use std::os::unix::io::AsRawFd;
+#[cfg(target_os = "linux")]
+use std::path::Path;
+#[cfg(target_os = "linux")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(target_os = "linux")]
+use std::sync::{Arc, Mutex};
+#[cfg(target_os = "linux")]
+use std::thread::{self, JoinHandle};
+
+#[cfg(target_os = "linux")]
+const DEVICE_DIR: &str = "/dev/input";
+#[cfg(target_os = "linux")]
+const READ_POLL_TIMEOUT_MS: i32 = 20;
+#[cfg(target_os = "linux")]
+const READ_BATCH_EVENTS: usize = 64;
+#[cfg(target_os = "linux")]
+const DEVICE_NAME_MAX: usize = 256;
+/// Bitmap sizes covering `KEY_MAX` and `ABS_MAX`
+#[cfg(target_os = "linux")]
+const KEY_BITS_BYTES: usize = 0x300 / 8;
+#[cfg(target_os = "linux")]
+const ABS_BITS_BYTES: usize = 0x40 / 8;
+
+// ioctl request encoding (asm-generic/ioctl.h)
+#[cfg(target_os = "linux")]
+const IOC_READ: u64 = 2;
+
+#[cfg(target_os = "linux")]
+fn evdev_ioc_read(nr: u64, size: usize) -> u64 {
+    (IOC_READ << 30) | ((size as u64) << 16) | ((b'E' as u64) << 8) | nr
+}
+
+#[cfg(target_os = "linux")]
+fn eviocgname(len: usize) -> u64 {
+    evdev_ioc_read(0x06, len)
+}
+
+#[cfg(target_os = "linux")]
+fn eviocgkey(len: usize) -> u64 {
+    evdev_ioc_read(0x18, len)
+}
+
+#[cfg(target_os = "linux")]
+fn eviocgbit(event_type: u16, len: usize) -> u64 {
+    evdev_ioc_read(0x20 + event_type as u64, len)
+}
+
+#[cfg(target_os = "linux")]
+fn eviocgabs(axis: u16) -> u64 {
+    evdev_ioc_read(
+        0x40 + axis as u64,
+        std::mem::size_of::<libc::input_absinfo>(),
+    )
+}
+
+#[cfg(target_os = "linux")]
+fn query_bits(file: &File, event_type: u16, bytes: usize) -> Option<Vec<u8>> {
+    let mut bits = vec![0u8; bytes];
+    // SAFETY: `bits` is a live buffer of exactly `bytes` bytes, the length encoded
+    // in the request, so the kernel cannot write past it.
+    let result = unsafe {
+        libc::ioctl(
+            file.as_raw_fd(),
+            eviocgbit(event_type, bytes) as _,
+            bits.as_mut_ptr(),
+        )
+    };
+    (result >= 0).then_some(bits)
+}
+
+#[cfg(target_os = "linux")]
+fn has_bit(bits: &[u8], code: u16) -> bool {
+    bits.get(code as usize / 8)
+        .is_some_and(|byte| byte & (1 << (code % 8)) != 0)
+}
+
+#[cfg(target_os = "linux")]
+fn query_absinfo(file: &File, axis: u16) -> Option<libc::input_absinfo> {
+    let mut info = libc::input_absinfo {
+        value: 0,
+        minimum: 0,
+        maximum: 0,
+        fuzz: 0,
+        flat: 0,
+        resolution: 0,
+    };
+    // SAFETY: EVIOCGABS writes one `input_absinfo`, the size encoded in the
+    // request, into `info`, which outlives the call.
+    let result = unsafe { libc::ioctl(file.as_raw_fd(), eviocgabs(axis) as _, &mut info) };
+    (result >= 0).then_some(info)
+}
+
+#[cfg(target_os = "linux")]
+fn query_axis(file: &File, axis: u16) -> Option<EvdevAxisInfo> {
+    query_absinfo(file, axis).map(|info| EvdevAxisInfo {
+        min: info.minimum,
+        max: info.maximum,
+        resolution: info.resolution,
+    })
+}
+
+/// Read current key and axis state, used to resync after `SYN_DROPPED`;
+/// `None` for streams that are not devices (recordings)
+#[cfg(target_os = "linux")]
+fn query_sync_state(file: &File) -> Option<EvdevSyncState> {
+    let mut keys = vec![0u8; KEY_BITS_BYTES];
+    // SAFETY: `keys` is a live buffer of exactly `KEY_BITS_BYTES` bytes, the
+    // length encoded in the request.
+    let result = unsafe {
+        libc::ioctl(
+            file.as_raw_fd(),
+            eviocgkey(KEY_BITS_BYTES) as _,
+            keys.as_mut_ptr(),
+        )
+    };
+    if result < 0 {
+        return None;
+    }
+    let abs_bits = query_bits(file, EV_ABS, ABS_BITS_BYTES)?;
+    let pressed_keys = (0..(KEY_BITS_BYTES * 8) as u16)
+        .filter(|&code| has_bit(&keys, code))
+        .collect();
+    let abs_values = (0..(ABS_BITS_BYTES * 8) as u16)
+        .filter(|&axis| has_bit(&abs_bits, axis))
+        .filter_map(|axis| query_absinfo(file, axis).map(|info| (axis, info.value)))
+        .collect();
+    Some(EvdevSyncState {
+        pressed_keys,
+        abs_values,
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn query_device_name(file: &File) -> Option<String> {
+    let mut name = [0u8; DEVICE_NAME_MAX];
+    // SAFETY: `name` holds `DEVICE_NAME_MAX` bytes, the length encoded in the
+    // request; the kernel truncates longer names.
+    let result = unsafe {
+        libc::ioctl(
+            file.as_raw_fd(),
+            eviocgname(DEVICE_NAME_MAX) as _,
+            name.as_mut_ptr(),
+        )
+    };
+    if result < 0 {
+        return None;
+    }
+    let end = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+    Some(String::from_utf8_lossy(&name[..end]).into_owned())
+}
+
+/// Query pen capabilities; `None` if the device is not a pen tablet
+#[cfg(target_os = "linux")]
+fn query_pen_caps(file: &File) -> Option<EvdevDeviceCaps> {
+    let key_bits = query_bits(file, EV_KEY, KEY_BITS_BYTES)?;
+    let abs_bits = query_bits(file, EV_ABS, ABS_BITS_BYTES)?;
+    let is_pen = has_bit(&key_bits, BTN_TOOL_PEN)
+        && has_bit(&abs_bits, ABS_X)
+        && has_bit(&abs_bits, ABS_Y)
+        && has_bit(&abs_bits, ABS_PRESSURE);
+    if !is_pen {
+        return None;
+    }
+
+    let optional_axis = |axis: u16| {
+        if has_bit(&abs_bits, axis) {
+            query_axis(file, axis)
+        } else {
+            None
+        }
+    };
+    Some(EvdevDeviceCaps {
+        x: query_axis(file, ABS_X)?,
+        y: query_axis(file, ABS_Y)?,
+        pressure: optional_axis(ABS_PRESSURE),
+        tilt_x: optional_axis(ABS_TILT_X),
+        tilt_y: optional_axis(ABS_TILT_Y),
+        distance: optional_axis(ABS_DISTANCE),
+        rotation: optional_axis(ABS_Z),
+        wheel: optional_axis(ABS_WHEEL),
+    })
+}
+
+/// Query pad controls; `None` if the device is not a tablet pad
+#[cfg(target_os = "linux")]
+fn query_pad_caps(file: &File) -> Option<EvdevPadCaps> {
+    let key_bits = query_bits(file, EV_KEY, KEY_BITS_BYTES)?;
+    let abs_bits = query_bits(file, EV_ABS, ABS_BITS_BYTES)?;
+    // Pads carry key buttons and a dummy ABS_X, but no pen tool
+    let is_pad =
+        has_bit(&key_bits, BTN_0) && !has_bit(&key_bits, BTN_TOOL_PEN) && has_bit(&abs_bits, ABS_X);
+    if !is_pad {
+        return None;
+    }
+
+    let optional_axis = |axis: u16| {
+        if has_bit(&abs_bits, axis) {
+            query_axis(file, axis)
+        } else {
+            None
+        }
+    };
+    Some(EvdevPadCaps {
+        ring: optional_axis(ABS_WHEEL),
+        ring2: optional_axis(ABS_THROTTLE),
+        strip: optional_axis(ABS_RX),
+        strip2: optional_axis(ABS_RY),
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn open_device(path: &Path) -> std::io::Result<File> {
+    std::fs::OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(path)
+}
+
+#[cfg(target_os = "linux")]
+fn list_event_devices() -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(DEVICE_DIR) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("event"))
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone)]
+struct EvdevDevice {
+    path: PathBuf,
+    name: String,
+    caps: EvdevDeviceCaps,
+}
+
+/// Find the first pen tablet under `/dev/input`
+#[cfg(target_os = "linux")]
+fn discover_pen_device() -> Result<EvdevDevice, String> {
+    let mut permission_denied = 0usize;
+    for path in list_event_devices() {
+        let file = match open_device(&path) {
+            Ok(file) => file,
+            Err(err) => {
+                if err.kind() == std::io::ErrorKind::PermissionDenied {
+                    permission_denied += 1;
+                }
+                continue;
+            }
+        };
+        if let Some(caps) = query_pen_caps(&file) {
+            let name = query_device_name(&file).unwrap_or_else(|| path.display().to_string());
+            return Ok(EvdevDevice { path, name, caps });
+        }
+    }
+
+    if permission_denied > 0 {
+        Err(format!(
+            "No readable pen tablet in {} ({} device(s) denied; add the user to the 'input' group)",
+            DEVICE_DIR, permission_denied
+        ))
+    } else {
+        Err(format!("No pen tablet found in {}", DEVICE_DIR))
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone)]
+struct EvdevPadDevice {
+    path: PathBuf,
+    name: String,
+    caps: EvdevPadCaps,
+}
+
+/// Find the pad that belongs to `pen`; kernels name it after the pen with a
+/// " Pad" suffix, so prefer a shared name prefix over the first pad found
+#[cfg(target_os = "linux")]
+fn discover_pad_device(pen: &EvdevDevice) -> Option<EvdevPadDevice> {
+    let pen_prefix = pen.name.trim_end_matches(" Pen");
+    let mut fallback = None;
+    for path in list_event_devices() {
+        let Ok(file) = open_device(&path) else {
+            continue;
+        };
+        let Some(caps) = query_pad_caps(&file) else {
+            continue;
+        };
+        let name = query_device_name(&file).unwrap_or_else(|| path.display().to_string());
+        let matches_pen = name.starts_with(pen_prefix);
+        let device = EvdevPadDevice { path, name, caps };
+        if matches_pen {
+            return Some(device);
+        }
+        fallback.get_or_insert(device);
+    }
+    fallback
+}
+
+/// Adapter fed by a reader thread
+#[cfg(target_os = "linux")]
+trait EvdevSink: Send {
+    fn process_event(
+        &mut self,
+        event: EvdevRawEvent,
+        host_time_us: u64,
+        out: &mut Vec<TabletEventV3>,
+    );
+
+    fn needs_resync(&self) -> bool;
+
+    fn resync(&mut self, sync: &EvdevSyncState, host_time_us: u64, out: &mut Vec<TabletEventV3>);
+}
+
+#[cfg(target_os = "linux")]
+impl EvdevSink for EvdevAdapterV3 {
+    fn process_event(
+        &mut self,
+        event: EvdevRawEvent,
+        host_time_us: u64,
+        out: &mut Vec<TabletEventV3>,
+    ) {
+        EvdevAdapterV3::process_event(self, event, host_time_us, out);
+    }
+
+    fn needs_resync(&self) -> bool {
+        EvdevAdapterV3::needs_resync(self)
+    }
+
+    fn resync(&mut self, sync: &EvdevSyncState, host_time_us: u64, out: &mut Vec<TabletEventV3>) {
+        EvdevAdapterV3::resync(self, sync, host_time_us, out);
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl EvdevSink for EvdevPadAdapterV3 {
+    fn process_event(
+        &mut self,
+        event: EvdevRawEvent,
+        host_time_us: u64,
+        out: &mut Vec<TabletEventV3>,
+    ) {
+        EvdevPadAdapterV3::process_event(self, event, host_time_us, out);
+    }
+
+    fn needs_resync(&self) -> bool {
+        EvdevPadAdapterV3::needs_resync(self)
+    }
+
+    fn resync(&mut self, sync: &EvdevSyncState, host_time_us: u64, out: &mut Vec<TabletEventV3>) {
+        EvdevPadAdapterV3::resync(self, sync, host_time_us, out);
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[derive(Debug)]
+struct ReaderRuntime<A> {
+    events: Arc<InputEventQueue>,
+    running: AtomicBool,
+    adapter: Mutex<A>,
+}
+
+#[cfg(target_os = "linux")]
+impl<A: EvdevSink> ReaderRuntime<A> {
+    fn new(events: Arc<InputEventQueue>, adapter: A) -> Self {
+        Self {
+            events,
+            running: AtomicBool::new(true),
+            adapter: Mutex::new(adapter),
+        }
+    }
+
+    fn with_adapter<R>(&self, f: impl FnOnce(&mut A) -> R) -> R {
+        let mut adapter = match self.adapter.lock() {
+            Ok(lock) => lock,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        f(&mut adapter)
+    }
+
+    /// Read records until stopped, the device disappears, or a recording ends
+    fn run(&self, mut file: File) {
+        let mut buffer = vec![0u8; EVDEV_EVENT_SIZE * READ_BATCH_EVENTS];
+        let mut pending = 0usize;
+        let mut out = Vec::new();
+
+        while self.running.load(Ordering::SeqCst) {
+            let mut poll_fd = libc::pollfd {
+                fd: file.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            // SAFETY: `poll_fd` is one valid `pollfd` for an fd owned by `file`,
+            // and the count passed is 1.
+            let ready = unsafe { libc::poll(&mut poll_fd, 1, READ_POLL_TIMEOUT_MS) };
+            if ready <= 0 {
+                continue;
+            }
+
+            let read = match file.read(&mut buffer[pending..]) {
+                Ok(0) => {
+                    tracing::info!("[LinuxEvdev] Device stream ended");
+                    break;
+                }
+                Ok(read) => read,
+                Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => continue,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    tracing::warn!("[LinuxEvdev] Device read failed: {}", err);
+                    let _ = self
+                        .events
+                        .enqueue_event(TabletEventV3::StatusChanged(TabletStatus::Error));
+                    break;
+                }
+            };
+
+            let available = pending + read;
+            let whole = available - available % EVDEV_EVENT_SIZE;
+            let host_time_us = super::current_time_us();
+            self.with_adapter(|adapter| {
+                for record in buffer[..whole].chunks_exact(EVDEV_EVENT_SIZE) {
+                    if let Some(event) = EvdevRawEvent::from_bytes(record) {
+                        adapter.process_event(event, host_time_us, &mut out);
+                    }
+                }
+                // Per the evdev protocol, state after SYN_DROPPED is read back
+                // rather than trusted, so a lost pen-up cannot leave a stroke down
+                if adapter.needs_resync() {
+                    match query_sync_state(&file) {
+                        Some(sync) => adapter.resync(&sync, host_time_us, &mut out),
+                        None => tracing::warn!("[LinuxEvdev] Cannot resync after dropped events"),
+                    }
+                }
+            });
+            buffer.copy_within(whole..available, 0);
+            pending = available - whole;
+
+            for event in out.drain(..) {
+                match event {
+                    TabletEventV3::Input(sample) => {
+                        let _ = self.events.enqueue_sample(sample);
+                    }
+                    other => {
+                        let _ = self.events.enqueue_event(other);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Linux evdev backend for tablet input
+#[cfg(target_os = "linux")]
+pub struct LinuxEvdevBackend {
+    status: TabletStatus,
+    info: Option<TabletInfo>,
+    config: TabletConfig,
+    events: Arc<InputEventQueue>,
+    /// Explicit device node or recording (None = auto-discover)
+    device_path: Option<PathBuf>,
+    /// Axis ranges for devices that cannot be queried (recordings)
+    caps_override: Option<EvdevDeviceCaps>,
+    device: Option<EvdevDevice>,
+    pad: Option<EvdevPadDevice>,
+    mapping: EvdevOutputMapping,
+    runtime: Option<Arc<ReaderRuntime<EvdevAdapterV3>>>,
+    reader_thread: Option<JoinHandle<()>>,
+    pad_runtime: Option<Arc<ReaderRuntime<EvdevPadAdapterV3>>>,
+    pad_thread: Option<JoinHandle<()>>,
+}
+
+#[cfg(target_os = "linux")]
+impl LinuxEvdevBackend {
+    /// Create a backend that picks the first pen tablet under `/dev/input`
+    pub fn new() -> Self {
+        Self {
+            status: TabletStatus::Disconnected,
+            info: None,
+            config: TabletConfig::default(),
+            events: Arc::new(InputEventQueue::new(
+                super::backend::InputBackpressureMode::Lossless,
+                default_event_queue_capacity(),
+            )),
+            device_path: None,
+            caps_override: None,
+            device: None,
+            pad: None,
+            mapping: EvdevOutputMapping::default(),
+            runtime: None,
+            reader_thread: None,
+            pad_runtime: None,
+            pad_thread: None,
+        }
+    }
+
+    /// Create a backend for a specific device node or recorded event file
+    ///
+    /// # Arguments
+    /// * `path` - Device node (`/dev/input/eventN`) or capture of `input_event` records
+    /// * `caps` - Axis ranges; required when the source does not answer evdev ioctls
+    pub fn with_device(path: PathBuf, caps: Option<EvdevDeviceCaps>) -> Self {
+        let mut backend = Self::new();
+        backend.device_path = Some(path);
+        backend.caps_override = caps;
+        backend
+    }
+
+    /// Set where the tablet area lands in client coordinates
+    pub fn set_output_mapping(&mut self, mapping: EvdevOutputMapping) {
+        self.mapping = mapping;
+        if let Some(runtime) = &self.runtime {
+            runtime.with_adapter(|adapter| adapter.set_output_mapping(mapping));
+        }
+    }
+
+    fn resolve_device(&self) -> Result<EvdevDevice, String> {
+        let Some(path) = self.device_path.clone() else {
+            return discover_pen_device();
+        };
+        let name = path.display().to_string();
+        if let Some(caps) = self.caps_override {
+            return Ok(EvdevDevice { path, name, caps });
+        }
+        let file = open_device(&path).map_err(|e| format!("Failed to open {}: {}", name, e))?;
+        let caps = query_pen_caps(&file).ok_or_else(|| format!("{} is not a pen tablet", name))?;
+        let name = query_device_name(&file).unwrap_or(name);
+        Ok(EvdevDevice { path, name, caps })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Default for LinuxEvdevBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl TabletBackend for LinuxEvdevBackend {
+    fn init(&mut self, config: &TabletConfig) -> Result<(), String> {
+        self.config = config.clone();
+        self.mapping.tablet = config.mapping;
+        self.events = Arc::new(InputEventQueue::new(
+            config.backpressure_mode,
+            default_event_queue_capacity(),
+        ));
+
+        let device = match self.resolve_device() {
+            Ok(device) => device,
+            Err(err) => {
+                self.status = TabletStatus::Error;
+                return Err(err);
+            }
+        };
+        self.info = Some(TabletInfo {
+            name: device.name.clone(),
+            backend: "LinuxEvdev".to_string(),
+            supports_pressure: device.caps.pressure.is_some(),
+            supports_tilt: device.caps.tilt_x.is_some() && device.caps.tilt_y.is_some(),
+            pressure_range: device
+                .caps
+                .pressure
+                .map(|axis| (axis.min, axis.max))
+                .unwrap_or((0, 1)),
+        });
+        tracing::info!(
+            "[LinuxEvdev] Initialized: {} ({})",
+            device.name,
+            device.path.display()
+        );
+        // Explicit devices and recordings carry no pad
+        self.pad = if self.device_path.is_none() {
+            discover_pad_device(&device)
+        } else {
+            None
+        };
+        if let Some(pad) = &self.pad {
+            tracing::info!("[LinuxEvdev] Pad: {} ({})", pad.name, pad.path.display());
+        }
+        self.device = Some(device);
+        self.status = TabletStatus::Connected;
+        Ok(())
+    }
+
+    fn start(&mut self) -> Result<(), String> {
+        if self.status != TabletStatus::Connected {
+            return Err("Backend not initialized".to_string());
+        }
+        let Some(device) = self.device.clone() else {
+            return Err("Backend not initialized".to_string());
+        };
+        if self.reader_thread.is_some() {
+            return Ok(());
+        }
+
+        let file = open_device(&device.path)
+            .map_err(|e| format!("Failed to open {}: {}", device.path.display(), e))?;
+
+        self.events.reopen();
+        let runtime = Arc::new(ReaderRuntime::new(
+            self.events.clone(),
+            EvdevAdapterV3::new(
+                format!("evdev:{}", device.path.display()),
+                device.caps,
+                self.mapping,
+            ),
+        ));
+
+        let thread_runtime = runtime.clone();
+        let handle = thread::Builder::new()
+            .name("linux-evdev-reader".to_string())
+            .spawn(move || thread_runtime.run(file))
+            .map_err(|e| format!("Failed to spawn evdev reader: {}", e))?;
+
+        self.runtime = Some(runtime);
+        self.reader_thread = Some(handle);
+
+        // The pad is optional: failing to read it only loses express keys
+        if let Some(pad) = self.pad.clone() {
+            match open_device(&pad.path) {
+                Ok(pad_file) => {
+                    let pad_runtime = Arc::new(ReaderRuntime::new(
+                        self.events.clone(),
+                        EvdevPadAdapterV3::new(format!("evdev:{}", pad.path.display()), pad.caps),
+                    ));
+                    let thread_runtime = pad_runtime.clone();
+                    match thread::Builder::new()
+                        .name("linux-evdev-pad-reader".to_string())
+                        .spawn(move || thread_runtime.run(pad_file))
+                    {
+                        Ok(handle) => {
+                            self.pad_runtime = Some(pad_runtime);
+                            self.pad_thread = Some(handle);
+                        }
+                        Err(e) => tracing::warn!("[LinuxEvdev] Failed to spawn pad reader: {}", e),
+                    }
+                }
+                Err(e) => tracing::warn!(
+                    "[LinuxEvdev] Failed to open pad {}: {}",
+                    pad.path.display(),
+                    e
+                ),
+            }
+        }
+        tracing::info!("[LinuxEvdev] Started");
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        if let Some(runtime) = &self.runtime {
+            runtime.running.store(false, Ordering::SeqCst);
+        }
+        if let Some(runtime) = self.pad_runtime.take() {
+            runtime.running.store(false, Ordering::SeqCst);
+        }
+        self.events.close();
+        if let Some(handle) = self.reader_thread.take() {
+            let _ = handle.join();
+        }
+        if let Some(handle) = self.pad_thread.take() {
+            let _ = handle.join();
+        }
+        self.events.clear();
+        tracing::info!("[LinuxEvdev] Stopped");
+    }
+
+    fn status(&self) -> TabletStatus {
+        self.status
+    }
+
+    fn info(&self) -> Option<&TabletInfo> {
+        self.info.as_ref()
+    }
+
+    fn poll(&mut self, events: &mut Vec<TabletEventV3>) -> usize {
+        self.events.drain_into(events, super::current_time_us)
+    }
+
+    fn take_hover(&mut self) -> Option<NativeTabletEventV3> {
+        self.events.take_hover()
+    }
+
+    fn queue_metrics(&self) -> InputQueueMetrics {
+        self.events.metrics_snapshot()
+    }
+
+    fn v3_diagnostics(&self) -> TabletV3Diagnostics {
+        self.runtime
+            .as_ref()
+            .map(|runtime| runtime.with_adapter(|adapter| adapter.diagnostics_snapshot()))
+            .unwrap_or_default()
+    }
+
+    fn is_available() -> bool {
+        discover_pen_device().is_ok()
+    }
+
+    fn name(&self) -> &'static str {
+        "LinuxEvdev"
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub struct LinuxEvdevBackend {
+    status: TabletStatus,
+    info: Option<TabletInfo>,
+}
+
+#[cfg(not(target_os = "linux"))]
+impl LinuxEvdevBackend {
+    pub fn new() -> Self {
+        Self {
+            status: TabletStatus::Disconnected,
+            info: None,
+        }
+    }
+
+    pub fn with_device(_path: PathBuf, _caps: Option<EvdevDeviceCaps>) -> Self {
+        Self::new()
+    }
+
+    pub fn set_output_mapping(&mut self, _mapping: EvdevOutputMapping) {}
+}
+
+#[cfg(not(target_os = "linux"))]
+impl Default for LinuxEvdevBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+impl TabletBackend for LinuxEvdevBackend {
+    fn init(&mut self, _config: &TabletConfig) -> Result<(), String> {
+        Err("LinuxEvdev backend is only available on Linux".to_string())
+    }
+
+    fn start(&mut self) -> Result<(), String> {
+        Err("LinuxEvdev backend is only available on Linux".to_string())
+    }
+
+    fn stop(&mut self) {}
+
+    fn status(&self) -> TabletStatus {
+        self.status
+    }
+
+    fn info(&self) -> Option<&TabletInfo> {
+        self.info.as_ref()
+    }
+
+    fn poll(&mut self, _events: &mut Vec<TabletEventV3>) -> usize {
+        0
+    }
+
+    fn queue_metrics(&self) -> InputQueueMetrics {
+        InputQueueMetrics::default()
+    }
+
+    fn is_available() -> bool {
+        false
+    }
+
+    fn name(&self) -> &'static str {
+        "LinuxEvdev"
+    }
+}
+
+impl Drop for LinuxEvdevBackend {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::input::krita_v3::evdev_adapter::{BTN_TOUCH, EV_SYN, SYN_REPORT};
+    use crate::input::InputPhase;
+    use std::time::{Duration, Instant};
+
+    fn recorded_caps() -> EvdevDeviceCaps {
+        EvdevDeviceCaps {
+            x: EvdevAxisInfo::new(0, 10_000),
+            y: EvdevAxisInfo::new(0, 10_000),
+            pressure: Some(EvdevAxisInfo::new(0, 1023)),
+            tilt_x: None,
+            tilt_y: None,
+            distance: None,
+            rotation: None,
+            wheel: None,
+        }
+    }
+
+    /// Pen enters, draws a short stroke to the right, lifts and leaves
+    fn write_recording() -> PathBuf {
+        let mut frames: Vec<Vec<(u16, u16, i32)>> = vec![vec![
+            (EV_KEY, BTN_TOOL_PEN, 1),
+            (EV_ABS, ABS_X, 1_000),
+            (EV_ABS, ABS_Y, 5_000),
+        ]];
+        frames.push(vec![(EV_KEY, BTN_TOUCH, 1), (EV_ABS, ABS_PRESSURE, 512)]);
+        for step in 1..=5 {
+            frames.push(vec![(EV_ABS, ABS_X, 1_000 + step * 1_000)]);
+        }
+        frames.push(vec![(EV_KEY, BTN_TOUCH, 0), (EV_ABS, ABS_PRESSURE, 0)]);
+        frames.push(vec![(EV_KEY, BTN_TOOL_PEN, 0)]);
+
+        let mut bytes = Vec::new();
+        for (i, frame) in frames.iter().enumerate() {
+            let time_us = 1_000_000 + i as u64 * 5_000;
+            for &(event_type, code, value) in frame {
+                bytes.extend_from_slice(
+                    &EvdevRawEvent::new(time_us, event_type, code, value).to_bytes(),
+                );
+            }
+            bytes.extend_from_slice(&EvdevRawEvent::new(time_us, EV_SYN, SYN_REPORT, 0).to_bytes());
+        }
+
+        let path = std::env::temp_dir().join(format!(
+            "sutu-evdev-recording-{}-{}.bin",
+            std::process::id(),
+            super::super::current_time_us()
+        ));
+        std::fs::write(&path, bytes).expect("write recording");
+        path
+    }
+
+    #[test]
+    fn test_replays_recorded_device_into_queue() {
+        let path = write_recording();
+        let mut backend = LinuxEvdevBackend::with_device(path.clone(), Some(recorded_caps()));
+        backend.init(&TabletConfig::default()).expect("init");
+        backend.set_output_mapping(EvdevOutputMapping {
+            screen_width_px: 1000.0,
+            screen_height_px: 1000.0,
+            ..EvdevOutputMapping::default()
+        });
+        backend.start().expect("start");
+
+        let mut events = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while !events
+            .iter()
+            .any(|e| matches!(e, TabletEventV3::ProximityLeave))
+            && Instant::now() < deadline
+        {
+            backend.poll(&mut events);
+            thread::sleep(Duration::from_millis(5));
+        }
+        backend.stop();
+        let _ = std::fs::remove_file(&path);
+
+        let samples: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                TabletEventV3::Input(sample) => Some(sample),
+                _ => None,
+            })
+            .collect();
+        let phases: Vec<InputPhase> = samples.iter().map(|s| s.phase).collect();
+        // Hover goes to the latest-value slot, not the stroke queue
+        assert_eq!(phases.first(), Some(&InputPhase::Down));
+        assert_eq!(phases.last(), Some(&InputPhase::Up));
+        assert_eq!(phases.iter().filter(|p| **p == InputPhase::Move).count(), 5);
+        assert!(matches!(
+            events.first(),
+            Some(TabletEventV3::ProximityEnter)
+        ));
+
+        let Some(last_move) = samples.iter().rev().find(|s| s.phase == InputPhase::Move) else {
+            panic!("expected move samples");
+        };
+        assert!((last_move.x_px - 600.0).abs() < 0.5);
+        assert!((last_move.pressure_0_1 - 0.5).abs() < 0.01);
+        // Device timestamps come from the recording
+        assert_eq!(samples[0].device_time_us, Some(1_005_000));
+    }
+
+    #[test]
+    fn test_explicit_non_tablet_device_fails_init() {
+        let path = std::env::temp_dir().join(format!(
+            "sutu-evdev-not-a-device-{}.bin",
+            std::process::id()
+        ));
+        std::fs::write(&path, []).expect("write file");
+        let mut backend = LinuxEvdevBackend::with_device(path.clone(), None);
+        assert!(backend.init(&TabletConfig::default()).is_err());
+        assert_eq!(backend.status(), TabletStatus::Error);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -2,14 +2,24 @@
 //!
 //! Uses AppKit `NSEvent` local monitors to capture tablet pressure/tilt/rotation/proximity
 //! and feeds them into the V3 tablet queue.
+//!
+//! Barrel buttons are read from `buttonMask` on tablet events (when the driver sends
+//! them as pen buttons rather than mouse clicks). AppKit does not deliver express
+//! keys or touch rings; the Wacom driver turns those into keystrokes.
 
+#[cfg(target_os = "macos")]
+use super::backend::NativeTabletEventV3;
 #[cfg(target_os = "macos")]
 use super::backend::{default_event_queue_capacity, InputEventQueue, TabletV3Diagnostics};
 use super::backend::{
     InputQueueMetrics, TabletBackend, TabletConfig, TabletEventV3, TabletInfo, TabletStatus,
 };
 #[cfg(target_os = "macos")]
-use super::krita_v3::{MacNativeAdapterV3, MacNativeEventKind, MacNativeRawSample};
+use super::krita_v3::types::{wacom_tool_type, ToolTypeV3};
+#[cfg(target_os = "macos")]
+use super::krita_v3::{
+    BarrelButtonTracker, MacNativeAdapterV3, MacNativeEventKind, MacNativeRawSample,
+};
 #[cfg(target_os = "macos")]
 use std::sync::Arc;
 
@@ -50,6 +60,23 @@ fn normalize_device_timestamp_us(timestamp_seconds: f64, fallback_host_time_us:
     (timestamp_seconds * 1_000_000.0).max(0.0) as u64
 }
 
+/// Tool type and serial from a proximity event
+#[cfg(target_os = "macos")]
+fn proximity_tool(event: &NSEvent) -> (ToolTypeV3, Option<u64>) {
+    let pointing_type = event.pointingDeviceType();
+    let tool_type = if pointing_type == NSPointingDeviceType::Eraser {
+        ToolTypeV3::Eraser
+    } else if let Some(tool_type) = wacom_tool_type(event.vendorPointingDeviceType() as u32) {
+        tool_type
+    } else if pointing_type == NSPointingDeviceType::Cursor {
+        ToolTypeV3::Mouse
+    } else {
+        ToolTypeV3::Pen
+    };
+    let serial = event.uniqueID() as u64;
+    (tool_type, (serial != 0).then_some(serial))
+}
+
 #[cfg(target_os = "macos")]
 fn normalize_pointer_id(raw_pointer_id: u64) -> u32 {
     if raw_pointer_id > u32::MAX as u64 {
@@ -70,7 +97,7 @@ use objc2::runtime::AnyObject;
 #[cfg(target_os = "macos")]
 use objc2::MainThreadMarker;
 #[cfg(target_os = "macos")]
-use objc2_app_kit::{NSEvent, NSEventMask, NSEventType};
+use objc2_app_kit::{NSEvent, NSEventMask, NSEventType, NSPointingDeviceType};
 #[cfg(target_os = "macos")]
 use std::sync::atomic::{AtomicBool, Ordering};
 #[cfg(target_os = "macos")]
@@ -88,6 +115,9 @@ struct MonitorRuntime {
     events: Arc<InputEventQueue>,
     collecting: AtomicBool,
     in_proximity: AtomicBool,
+    /// Tool reported by the last proximity event (type, serial)
+    tool: Mutex<(ToolTypeV3, Option<u64>)>,
+    barrel: Mutex<BarrelButtonTracker>,
     adapter: Mutex<MacNativeAdapterV3>,
 }
 
@@ -98,6 +128,8 @@ impl MonitorRuntime {
             events,
             collecting: AtomicBool::new(false),
             in_proximity: AtomicBool::new(false),
+            tool: Mutex::new((ToolTypeV3::Pen, None)),
+            barrel: Mutex::new(BarrelButtonTracker::new()),
             adapter: Mutex::new(MacNativeAdapterV3::new("macnative".to_string(), 1.0, 1.0)),
         }
     }
@@ -164,10 +196,12 @@ impl MonitorRuntime {
         match event.r#type() {
             NSEventType::TabletProximity => {
                 if event.isEnteringProximity() {
+                    self.set_tool(proximity_tool(event));
                     self.in_proximity.store(true, Ordering::Relaxed);
                     let _ = self.events.enqueue_event(TabletEventV3::ProximityEnter);
                 } else {
                     self.in_proximity.store(false, Ordering::Relaxed);
+                    self.update_barrel(0);
                     let _ = self.events.enqueue_event(TabletEventV3::ProximityLeave);
                 }
             }
@@ -181,6 +215,8 @@ impl MonitorRuntime {
                 self.enqueue_sample(event, MacNativeEventKind::MouseUp, Some(0.0));
             }
             NSEventType::TabletPoint => {
+                // NSEventButtonMask: bit 0 is the tip, bits 1-2 the lower/upper side buttons
+                self.update_barrel((event.buttonMask().0 as u32) >> 1);
                 let pressure = normalize_pressure(event.pressure());
                 self.enqueue_sample(event, MacNativeEventKind::TabletPoint, Some(pressure));
             }
@@ -188,6 +224,34 @@ impl MonitorRuntime {
         }
     }
 
+    fn set_tool(&self, tool: (ToolTypeV3, Option<u64>)) {
+        match self.tool.lock() {
+            Ok(mut lock) => *lock = tool,
+            Err(poisoned) => *poisoned.into_inner() = tool,
+        }
+    }
+
+    fn update_barrel(&self, mask: u32) {
+        let buttons = match self.barrel.lock() {
+            Ok(mut lock) => lock.update(mask, "macnative", super::current_time_us()),
+            Err(poisoned) => {
+                poisoned
+                    .into_inner()
+                    .update(mask, "macnative", super::current_time_us())
+            }
+        };
+        for button in buttons {
+            let _ = self.events.enqueue_event(TabletEventV3::Button(button));
+        }
+    }
+
+    fn current_tool(&self) -> (ToolTypeV3, Option<u64>) {
+        match self.tool.lock() {
+            Ok(lock) => *lock,
+            Err(poisoned) => *poisoned.into_inner(),
+        }
+    }
+
     fn enqueue_sample(
         &self,
         event: &NSEvent,
@@ -199,6 +263,7 @@ impl MonitorRuntime {
         let host_time_us = super::current_time_us();
         let pressure = pressure_override.unwrap_or_else(|| normalize_pressure(event.pressure()));
         let pointer_id = normalize_pointer_id(event.pointingDeviceID() as u64);
+        let (tool_type, tool_serial) = self.current_tool();
         let raw = MacNativeRawSample {
             pointer_id,
             kind,
@@ -214,6 +279,11 @@ impl MonitorRuntime {
                 event.timestamp(),
                 host_time_us,
             )),
+            tool_type,
+            tool_serial,
+            // AppKit reports the airbrush wheel as tangential pressure in [-1, 1]
+            wheel_0_1: (tool_type == ToolTypeV3::Airbrush)
+                .then(|| (event.tangentialPressure() + 1.0) * 0.5),
         };
 
         let sample = self.with_adapter(|adapter| adapter.process_raw_sample(raw));
@@ -457,6 +527,10 @@ impl TabletBackend for MacNativeBackend {
         self.events.drain_into(events, super::current_time_us)
     }
 
+    fn take_hover(&mut self) -> Option<NativeTabletEventV3> {
+        self.events.take_hover()
+    }
+
     fn queue_metrics(&self) -> InputQueueMetrics {
         self.events.metrics_snapshot()
     }
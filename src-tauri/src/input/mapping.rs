@@ -0,0 +1,74 @@
+//! Persisted tablet mapping
+//!
+//! Stores the active area, rotation, aspect lock and target monitor applied by the
+//! WinTab and evdev backends when they build their `CoordinateMapper`. macOS
+//! receives screen coordinates already mapped by the driver, so it ignores this.
+
+use super::config_file::{config_file_path, load_config_file, save_config_file};
+use super::krita_v3::TabletMappingV1;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+pub const TABLET_MAPPING_FILE_NAME: &str = "tablet_mapping.json";
+const TABLET_MAPPING_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TabletMappingFileV1 {
+    version: u32,
+    mapping: TabletMappingV1,
+}
+
+pub fn default_tablet_mapping_path() -> PathBuf {
+    config_file_path(TABLET_MAPPING_FILE_NAME)
+}
+
+/// Tablet mapping, optionally backed by a file
+#[derive(Debug, Clone, Default)]
+pub struct TabletMappingStore {
+    path: Option<PathBuf>,
+    mapping: TabletMappingV1,
+}
+
+impl TabletMappingStore {
+    /// Store with the full-tablet mapping that is never written to disk
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Load the mapping from `path`; a missing or unreadable file yields the default
+    pub fn load(path: &Path) -> Self {
+        let mapping = load_config_file::<TabletMappingFileV1>(path, "tablet mapping")
+            .map(|file| file.mapping)
+            .unwrap_or_default();
+        Self {
+            path: Some(path.to_path_buf()),
+            mapping,
+        }
+    }
+
+    pub fn mapping(&self) -> TabletMappingV1 {
+        self.mapping
+    }
+
+    /// Replace the mapping (active area clamped into the tablet) and persist
+    pub fn set(&mut self, mapping: TabletMappingV1) -> Result<TabletMappingV1, String> {
+        self.mapping = TabletMappingV1 {
+            active_area: mapping.active_area.sanitized(),
+            ..mapping
+        };
+        self.save()?;
+        Ok(self.mapping)
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let file = TabletMappingFileV1 {
+            version: TABLET_MAPPING_VERSION,
+            mapping: self.mapping,
+        };
+        save_config_file(path, &file, "tablet mapping")
+    }
+}
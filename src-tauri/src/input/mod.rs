@@ -1,28 +1,57 @@
 //! Input module - handles tablet/pen input processing
 
+pub mod actions;
 mod backend;
+mod config_file;
+pub mod gestures;
+pub mod hover;
 pub mod krita_v3;
+mod linux_evdev_backend;
 mod macos_backend;
+pub mod mapping;
 mod pointer_backend;
+pub mod prediction;
+pub mod recording;
+mod replay_backend;
 mod tablet;
+pub mod tools;
 pub mod wintab_backend;
 pub mod wintab_spike;
 
+pub use actions::{
+    default_tablet_bindings_path, tablet_control_key, TabletActionEventV1, TabletActionMapper,
+    TabletActionV1, TabletBindingStore,
+};
 pub use backend::{
-    InputBackpressureMode, InputEventQueue, InputPhase, InputQueueMetrics, InputSource,
-    NativeTabletEventV3, PressureCurve, TabletBackend, TabletConfig, TabletEventV3, TabletInfo,
-    TabletStatus, TabletV3Diagnostics,
+    CoalescedSpanV3, InputBackpressureMode, InputEventQueue, InputPhase, InputQueueMetrics,
+    InputSource, NativeTabletEventV3, PressureCurve, TabletBackend, TabletButtonEventV3,
+    TabletButtonKindV3, TabletConfig, TabletDialEventV3, TabletDialKindV3, TabletEventV3,
+    TabletInfo, TabletStatus, TabletV3Diagnostics, ToolChangeV3, ToolTypeV3,
+};
+pub use gestures::{GestureRecognizer, TouchGesturePhaseV1, TouchGestureV1};
+pub use hover::{
+    cursor_pose, CursorControlV1, CursorPoseConfigV1, CursorPoseV1, HoverSampleV1, HoverStream,
 };
 #[cfg(target_os = "windows")]
 pub use krita_v3::WinTabAdapter;
 pub use krita_v3::{
-    CoordinateMapper, CoordinateMapperMac, InputPhaseV3, InputSourceV3, MacNativeAdapterV3,
-    MacNativeEventKind, MacNativeRawSample, MonotonicTimebase, MonotonicTimebaseMac, PhaseMachine,
-    PhaseOutput, PhaseOutputMac,
+    CoordinateMapper, CoordinateMapperMac, EvdevDeviceCaps, EvdevOutputMapping, InputPhaseV3,
+    InputSourceV3, MacNativeAdapterV3, MacNativeEventKind, MacNativeRawSample, MonotonicTimebase,
+    MonotonicTimebaseMac, NormalizedRectV1, PhaseMachine, PhaseOutput, PhaseOutputMac,
+    TabletMappingV1, TabletRotationV1,
 };
+pub use linux_evdev_backend::LinuxEvdevBackend;
 pub use macos_backend::MacNativeBackend;
+pub use mapping::{default_tablet_mapping_path, TabletMappingStore};
 pub use pointer_backend::PointerEventBackend;
+pub use prediction::{InputPredictor, PredictionAccuracy, PredictionConfig};
+pub use recording::{InputRecorder, InputRecording};
+pub use replay_backend::{ReplayBackend, ReplayTiming};
 pub use tablet::TabletManager;
+pub use tools::{
+    default_tool_profiles_path, tool_profile_key, KnownToolV3, ToolProfileStore, ToolProfileV1,
+    ToolTracker,
+};
 pub use wintab_backend::WinTabBackend;
 
 use serde::{Deserialize, Serialize};
@@ -6,8 +6,8 @@
 
 use super::backend::{
     default_event_queue_capacity, InputEventQueue, InputPhase, InputQueueMetrics,
-    NativeTabletEventV3, PressureCurve, TabletBackend, TabletConfig, TabletEventV3, TabletInfo,
-    TabletStatus,
+    NativeTabletEventV3, TabletBackend, TabletConfig, TabletEventV3, TabletInfo, TabletStatus,
+    ToolTypeV3,
 };
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -20,7 +20,6 @@ pub struct PointerEventBackend {
     info: Option<TabletInfo>,
     config: TabletConfig,
     events: Arc<InputEventQueue>,
-    pressure_curve: PressureCurve,
     next_stroke_id: AtomicU64,
     active_strokes: Mutex<HashMap<u32, u64>>,
 }
@@ -36,7 +35,6 @@ impl PointerEventBackend {
                 super::backend::InputBackpressureMode::Lossless,
                 default_event_queue_capacity(),
             )),
-            pressure_curve: PressureCurve::Linear,
             next_stroke_id: AtomicU64::new(1),
             active_strokes: Mutex::new(HashMap::new()),
         }
@@ -81,8 +79,11 @@ impl PointerEventBackend {
         pointer_id: u32,
         phase: InputPhase,
         device_time_us: Option<u64>,
+        tool_type: ToolTypeV3,
+        wheel_0_1: Option<f32>,
     ) {
-        let adjusted_pressure = self.pressure_curve.apply(pressure);
+        // Raw pressure: the canvas applies the global curve to every backend's samples
+        let pressure = pressure.clamp(0.0, 1.0);
         let host_time_us = super::current_time_us();
         let stroke_id = self.resolve_stroke_id(pointer_id, phase);
 
@@ -95,12 +96,19 @@ impl PointerEventBackend {
             source: super::backend::InputSource::PointerEvent,
             x_px: x,
             y_px: y,
-            pressure_0_1: adjusted_pressure,
+            pressure_0_1: pressure,
             tilt_x_deg: tilt_x.clamp(-90.0, 90.0),
             tilt_y_deg: tilt_y.clamp(-90.0, 90.0),
             rotation_deg: rotation.rem_euclid(360.0),
             host_time_us,
             device_time_us: Some(device_time_us.unwrap_or(host_time_us)),
+            tool_type,
+            tool_serial: None,
+            wheel_0_1: wheel_0_1.map(|w| w.clamp(0.0, 1.0)),
+            distance_0_1: None,
+            coalesced: None,
+            predicted: false,
+            prediction_index: None,
         };
 
         let _ = self.events.enqueue_sample(sample);
@@ -126,7 +134,6 @@ impl Default for PointerEventBackend {
 impl TabletBackend for PointerEventBackend {
     fn init(&mut self, config: &TabletConfig) -> Result<(), String> {
         self.config = config.clone();
-        self.pressure_curve = config.pressure_curve;
         self.events = Arc::new(InputEventQueue::new(
             config.backpressure_mode,
             default_event_queue_capacity(),
@@ -172,6 +179,10 @@ impl TabletBackend for PointerEventBackend {
         self.events.drain_into(events, super::current_time_us)
     }
 
+    fn take_hover(&mut self) -> Option<NativeTabletEventV3> {
+        self.events.take_hover()
+    }
+
     fn queue_metrics(&self) -> InputQueueMetrics {
         self.events.metrics_snapshot()
     }
@@ -189,6 +200,7 @@ impl TabletBackend for PointerEventBackend {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input::backend::PressureCurve;
 
     #[test]
     fn test_pointer_backend_creation() {
@@ -211,7 +223,10 @@ mod tests {
     #[test]
     fn test_pointer_backend_push_input() -> Result<(), String> {
         let mut backend = PointerEventBackend::new();
-        backend.init(&TabletConfig::default())?;
+        backend.init(&TabletConfig {
+            pressure_curve: PressureCurve::Soft,
+            ..TabletConfig::default()
+        })?;
         backend.start()?;
 
         backend.push_input(
@@ -224,6 +239,8 @@ mod tests {
             1,
             InputPhase::Move,
             None,
+            ToolTypeV3::Eraser,
+            None,
         );
 
         let mut events = Vec::new();
@@ -235,6 +252,7 @@ mod tests {
             assert_eq!(sample.y_px, 200.0);
             assert_eq!(sample.pressure_0_1, 0.5);
             assert_eq!(sample.pointer_id, 1);
+            assert_eq!(sample.tool_type, ToolTypeV3::Eraser);
         } else {
             panic!("Expected Input event");
         }
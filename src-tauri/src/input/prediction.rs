@@ -0,0 +1,510 @@
+//! Input prediction
+//!
+//! Extrapolates pen position and pressure a few milliseconds past the newest real
+//! sample so the renderer can draw provisional geometry. Predictions are appended
+//! to each drained batch as `TabletEventV3::Predicted` and are superseded by the
+//! next real sample of the same stroke. Predicted samples have `predicted` set and
+//! keep the `seq` of the real sample they extend, with `prediction_index` telling
+//! them apart.
+
+use super::backend::{InputPhase, NativeTabletEventV3, TabletEventV3};
+use super::recording::InputRecording;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+/// Upper bound on predicted samples per batch, whatever the horizon and step
+const MAX_PREDICTIONS: usize = 64;
+
+/// Tuning for `InputPredictor`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PredictionConfig {
+    /// How far past the newest real sample to predict
+    pub horizon_us: u64,
+    /// Spacing between predicted samples
+    pub step_us: u64,
+    /// Number of real samples used for the motion fit
+    pub history_len: usize,
+    /// Samples older than this (relative to the newest) are ignored
+    pub history_window_us: u64,
+    /// Upper bound for predicted speed, avoids overshoot on jittery input
+    pub max_speed_px_per_ms: f32,
+}
+
+impl Default for PredictionConfig {
+    fn default() -> Self {
+        Self {
+            horizon_us: 12_000,
+            step_us: 4_000,
+            history_len: 6,
+            history_window_us: 40_000,
+            max_speed_px_per_ms: 12.0,
+        }
+    }
+}
+
+/// Polynomial-fit predictor over the current stroke
+#[derive(Debug, Clone, Default)]
+pub struct InputPredictor {
+    config: PredictionConfig,
+    history: VecDeque<NativeTabletEventV3>,
+}
+
+fn sample_time_us(sample: &NativeTabletEventV3) -> u64 {
+    sample.device_time_us.unwrap_or(sample.host_time_us)
+}
+
+/// Least-squares fit of `v(t) = a + b t + c t^2`; returns `(b, c)` (`c` is 0 for a linear fit)
+fn fit_motion(ts: &[f64], vs: &[f64], quadratic: bool) -> Option<(f64, f64)> {
+    let n = ts.len() as f64;
+    if ts.len() < 2 {
+        return None;
+    }
+    let mut s = [0.0f64; 5];
+    let mut r = [0.0f64; 3];
+    for (&t, &v) in ts.iter().zip(vs) {
+        let mut tp = 1.0;
+        for (k, sk) in s.iter_mut().enumerate() {
+            *sk += tp;
+            if k < 3 {
+                r[k] += v * tp;
+            }
+            tp *= t;
+        }
+    }
+
+    if quadratic && ts.len() >= 3 {
+        // Cramer's rule on the 3x3 normal equations
+        let det3 = |m: [[f64; 3]; 3]| {
+            m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+                - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+                + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+        };
+        let m = [[s[0], s[1], s[2]], [s[1], s[2], s[3]], [s[2], s[3], s[4]]];
+        let det = det3(m);
+        if det.abs() > 1e-9 {
+            let mut mb = m;
+            let mut mc = m;
+            for row in 0..3 {
+                mb[row][1] = r[row];
+                mc[row][2] = r[row];
+            }
+            return Some((det3(mb) / det, det3(mc) / det));
+        }
+    }
+
+    let det = n * s[2] - s[1] * s[1];
+    if det.abs() <= 1e-9 {
+        return None;
+    }
+    Some(((n * r[1] - s[1] * r[0]) / det, 0.0))
+}
+
+impl InputPredictor {
+    pub fn new(config: PredictionConfig) -> Self {
+        Self {
+            config,
+            history: VecDeque::new(),
+        }
+    }
+
+    pub fn config(&self) -> &PredictionConfig {
+        &self.config
+    }
+
+    /// Forget the current stroke
+    pub fn reset(&mut self) {
+        self.history.clear();
+    }
+
+    /// Feed one real sample
+    pub fn observe(&mut self, sample: &NativeTabletEventV3) {
+        let new_stroke = self
+            .history
+            .back()
+            .is_some_and(|last| last.stroke_id != sample.stroke_id);
+        if sample.phase == InputPhase::Down || new_stroke {
+            self.history.clear();
+        }
+        if matches!(sample.phase, InputPhase::Up | InputPhase::Hover) {
+            self.history.clear();
+            return;
+        }
+
+        // Keep time strictly increasing; a same-time sample replaces its predecessor
+        let time_us = sample_time_us(sample);
+        while self
+            .history
+            .back()
+            .is_some_and(|last| sample_time_us(last) >= time_us)
+        {
+            self.history.pop_back();
+        }
+        self.history.push_back(sample.clone());
+
+        while self.history.len() > self.config.history_len.max(2) {
+            self.history.pop_front();
+        }
+        while self.history.front().is_some_and(|first| {
+            time_us.saturating_sub(sample_time_us(first)) > self.config.history_window_us
+        }) {
+            self.history.pop_front();
+        }
+    }
+
+    /// Predicted samples past the newest observed one (empty without a stroke in progress)
+    pub fn predict(&self) -> Vec<NativeTabletEventV3> {
+        let Some(last) = self.history.back() else {
+            return Vec::new();
+        };
+        let last_time_us = sample_time_us(last);
+        // Times in ms relative to the newest sample, values relative to it as well
+        let ts: Vec<f64> = self
+            .history
+            .iter()
+            .map(|s| (sample_time_us(s) as f64 - last_time_us as f64) / 1000.0)
+            .collect();
+        let axis = |value: fn(&NativeTabletEventV3) -> f32| -> Vec<f64> {
+            self.history
+                .iter()
+                .map(|s| (value(s) - value(last)) as f64)
+                .collect()
+        };
+        let (Some((bx, cx)), Some((by, cy))) = (
+            fit_motion(&ts, &axis(|s| s.x_px), true),
+            fit_motion(&ts, &axis(|s| s.y_px), true),
+        ) else {
+            return Vec::new();
+        };
+        let (bp, _) = fit_motion(&ts, &axis(|s| s.pressure_0_1), false).unwrap_or((0.0, 0.0));
+
+        let step_us = self.config.step_us.max(1);
+        let mut predicted = Vec::new();
+        let mut offset_us = step_us;
+        while offset_us <= self.config.horizon_us && predicted.len() < MAX_PREDICTIONS {
+            let dt = offset_us as f64 / 1000.0;
+            let mut dx = bx * dt + cx * dt * dt;
+            let mut dy = by * dt + cy * dt * dt;
+            let max_distance = self.config.max_speed_px_per_ms as f64 * dt;
+            let distance = dx.hypot(dy);
+            if distance > max_distance && distance > 0.0 {
+                dx *= max_distance / distance;
+                dy *= max_distance / distance;
+            }
+
+            let mut sample = last.clone();
+            sample.predicted = true;
+            sample.prediction_index = Some(predicted.len() as u32 + 1);
+            sample.coalesced = None;
+            sample.phase = InputPhase::Move;
+            sample.x_px = last.x_px + dx as f32;
+            sample.y_px = last.y_px + dy as f32;
+            sample.pressure_0_1 = (last.pressure_0_1 as f64 + bp * dt).clamp(0.0, 1.0) as f32;
+            sample.host_time_us = last.host_time_us + offset_us;
+            sample.device_time_us = last.device_time_us.map(|t| t + offset_us);
+            predicted.push(sample);
+            offset_us += step_us;
+        }
+        predicted
+    }
+
+    /// Observe a drained batch and append predictions when it carried new input
+    pub fn process(&mut self, events: &mut Vec<TabletEventV3>) {
+        let mut saw_input = false;
+        for event in events.iter() {
+            match event {
+                TabletEventV3::Input(sample) => {
+                    self.observe(sample);
+                    saw_input = true;
+                }
+                TabletEventV3::ProximityLeave | TabletEventV3::StatusChanged(_) => {
+                    self.reset();
+                    saw_input = false;
+                }
+                TabletEventV3::ProximityEnter
+                | TabletEventV3::Predicted(_)
+                | TabletEventV3::ToolChanged(_)
+                | TabletEventV3::Button(_)
+                | TabletEventV3::Dial(_) => {}
+            }
+        }
+        if saw_input {
+            events.extend(self.predict().into_iter().map(TabletEventV3::Predicted));
+        }
+    }
+}
+
+/// Prediction error against what the pen actually did
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PredictionAccuracy {
+    /// Predictions that fell inside their stroke and could be scored
+    pub predictions: usize,
+    pub mean_error_px: f64,
+    pub p95_error_px: f64,
+    pub max_error_px: f64,
+    pub pressure_mae: f64,
+    /// Mean error of simply holding the newest sample (no prediction)
+    pub hold_mean_error_px: f64,
+}
+
+/// (time, x, y, pressure) of the real contact samples of one stroke
+type Trajectory = Vec<(u64, f64, f64, f64)>;
+
+fn trajectory_at(trajectory: &Trajectory, time_us: u64) -> Option<(f64, f64, f64)> {
+    let end = trajectory.partition_point(|p| p.0 < time_us);
+    let hi = trajectory.get(end)?;
+    if hi.0 == time_us || end == 0 {
+        return (hi.0 == time_us).then_some((hi.1, hi.2, hi.3));
+    }
+    let lo = &trajectory[end - 1];
+    let t = (time_us - lo.0) as f64 / (hi.0 - lo.0) as f64;
+    Some((
+        lo.1 + (hi.1 - lo.1) * t,
+        lo.2 + (hi.2 - lo.2) * t,
+        lo.3 + (hi.3 - lo.3) * t,
+    ))
+}
+
+/// Replay `recording` sample by sample and score every prediction against the
+/// interpolated real trajectory at the predicted time
+pub fn measure_prediction_accuracy(
+    recording: &InputRecording,
+    config: &PredictionConfig,
+) -> PredictionAccuracy {
+    let mut strokes: BTreeMap<u64, Trajectory> = BTreeMap::new();
+    for recorded in &recording.events {
+        if let TabletEventV3::Input(sample) = &recorded.event {
+            if sample.phase != InputPhase::Hover {
+                strokes.entry(sample.stroke_id).or_default().push((
+                    sample_time_us(sample),
+                    sample.x_px as f64,
+                    sample.y_px as f64,
+                    sample.pressure_0_1 as f64,
+                ));
+            }
+        }
+    }
+
+    let mut predictor = InputPredictor::new(config.clone());
+    let mut errors = Vec::new();
+    let mut hold_errors = Vec::new();
+    let mut pressure_errors = Vec::new();
+    let mut batch = Vec::new();
+    for recorded in &recording.events {
+        batch.clear();
+        batch.push(recorded.event.clone());
+        predictor.process(&mut batch);
+
+        let TabletEventV3::Input(real) = &recorded.event else {
+            continue;
+        };
+        for event in &batch {
+            let TabletEventV3::Predicted(predicted) = event else {
+                continue;
+            };
+            let Some((x, y, pressure)) = strokes
+                .get(&predicted.stroke_id)
+                .and_then(|trajectory| trajectory_at(trajectory, sample_time_us(predicted)))
+            else {
+                continue;
+            };
+            errors.push((predicted.x_px as f64 - x).hypot(predicted.y_px as f64 - y));
+            hold_errors.push((real.x_px as f64 - x).hypot(real.y_px as f64 - y));
+            pressure_errors.push((predicted.pressure_0_1 as f64 - pressure).abs());
+        }
+    }
+
+    if errors.is_empty() {
+        return PredictionAccuracy::default();
+    }
+    let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+    let mut sorted = errors.clone();
+    sorted.sort_by(f64::total_cmp);
+    PredictionAccuracy {
+        predictions: errors.len(),
+        mean_error_px: mean(&errors),
+        p95_error_px: sorted[((sorted.len() - 1) as f64 * 0.95).round() as usize],
+        max_error_px: sorted[sorted.len() - 1],
+        pressure_mae: mean(&pressure_errors),
+        hold_mean_error_px: mean(&hold_errors),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::recording::{InputRecordingHeader, RecordedInputEvent};
+    use crate::input::{InputSource, ToolTypeV3};
+
+    fn sample(
+        stroke_id: u64,
+        phase: InputPhase,
+        x: f32,
+        y: f32,
+        time_us: u64,
+    ) -> NativeTabletEventV3 {
+        NativeTabletEventV3 {
+            seq: time_us / 1000,
+            stroke_id,
+            pointer_id: 1,
+            device_id: "test".to_string(),
+            source: InputSource::WinTab,
+            phase,
+            x_px: x,
+            y_px: y,
+            pressure_0_1: 0.2 + x / 1000.0,
+            tilt_x_deg: 0.0,
+            tilt_y_deg: 0.0,
+            rotation_deg: 0.0,
+            host_time_us: time_us,
+            device_time_us: None,
+            tool_type: ToolTypeV3::Pen,
+            tool_serial: None,
+            wheel_0_1: None,
+            distance_0_1: None,
+            coalesced: None,
+            predicted: false,
+            prediction_index: None,
+        }
+    }
+
+    fn predicted(events: &[TabletEventV3]) -> Vec<&NativeTabletEventV3> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                TabletEventV3::Predicted(sample) => Some(sample),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn extrapolates_constant_velocity_stroke() {
+        let mut predictor = InputPredictor::new(PredictionConfig::default());
+        // 2 px/ms along x, 1 px/ms along y
+        let mut events: Vec<TabletEventV3> = (0..6u64)
+            .map(|i| {
+                let phase = if i == 0 {
+                    InputPhase::Down
+                } else {
+                    InputPhase::Move
+                };
+                let t = i * 4_000;
+                TabletEventV3::Input(sample(1, phase, 8.0 * i as f32, 4.0 * i as f32, t))
+            })
+            .collect();
+        predictor.process(&mut events);
+
+        assert!(matches!(events[5], TabletEventV3::Input(_)));
+        let predicted = predicted(&events);
+        assert_eq!(predicted.len(), 3);
+        for (k, p) in predicted.iter().enumerate() {
+            let ahead_ms = 4.0 * (k + 1) as f32;
+            assert!(
+                (p.x_px - (40.0 + 2.0 * ahead_ms)).abs() < 1e-3,
+                "x {}",
+                p.x_px
+            );
+            assert!((p.y_px - (20.0 + ahead_ms)).abs() < 1e-3, "y {}", p.y_px);
+            assert!((p.pressure_0_1 - (0.2 + p.x_px / 1000.0)).abs() < 1e-4);
+            assert_eq!(p.host_time_us, 20_000 + (k as u64 + 1) * 4_000);
+            assert_eq!(p.phase, InputPhase::Move);
+            assert!(p.predicted);
+            assert_eq!(p.seq, 20);
+            assert_eq!(p.prediction_index, Some(k as u32 + 1));
+        }
+        assert!(events[..6].iter().all(|e| match e {
+            TabletEventV3::Input(sample) => !sample.predicted && sample.prediction_index.is_none(),
+            _ => false,
+        }));
+    }
+
+    #[test]
+    fn stops_predicting_at_stroke_boundaries() {
+        let mut predictor = InputPredictor::new(PredictionConfig::default());
+        let mut events = vec![
+            TabletEventV3::Input(sample(1, InputPhase::Down, 0.0, 0.0, 0)),
+            TabletEventV3::Input(sample(1, InputPhase::Move, 5.0, 0.0, 4_000)),
+            TabletEventV3::Input(sample(1, InputPhase::Up, 10.0, 0.0, 8_000)),
+        ];
+        predictor.process(&mut events);
+        assert!(predicted(&events).is_empty());
+
+        // A single sample of a new stroke is not enough to extrapolate
+        let mut events = vec![TabletEventV3::Input(sample(
+            2,
+            InputPhase::Down,
+            50.0,
+            0.0,
+            20_000,
+        ))];
+        predictor.process(&mut events);
+        assert!(predicted(&events).is_empty());
+
+        let mut events = vec![TabletEventV3::Input(sample(
+            2,
+            InputPhase::Move,
+            52.0,
+            0.0,
+            24_000,
+        ))];
+        predictor.process(&mut events);
+        assert_eq!(predicted(&events).len(), 3);
+
+        let mut events = vec![TabletEventV3::ProximityLeave];
+        predictor.process(&mut events);
+        assert!(predicted(&events).is_empty());
+    }
+
+    #[test]
+    fn clamps_predicted_speed() {
+        let config = PredictionConfig {
+            max_speed_px_per_ms: 1.0,
+            ..PredictionConfig::default()
+        };
+        let mut predictor = InputPredictor::new(config);
+        predictor.observe(&sample(1, InputPhase::Down, 0.0, 0.0, 0));
+        predictor.observe(&sample(1, InputPhase::Move, 100.0, 0.0, 4_000));
+        let predicted = predictor.predict();
+        let last = predicted.last().expect("prediction");
+        assert!((last.x_px - 112.0).abs() < 1e-3, "x {}", last.x_px);
+    }
+
+    #[test]
+    fn prediction_beats_holding_on_replayed_arc() {
+        let mut events = Vec::new();
+        for i in 0..80u64 {
+            let angle = i as f32 * 0.05;
+            let phase = match i {
+                0 => InputPhase::Down,
+                79 => InputPhase::Up,
+                _ => InputPhase::Move,
+            };
+            let time_us = i * 4_000;
+            events.push(RecordedInputEvent {
+                time_us,
+                event: TabletEventV3::Input(sample(
+                    3,
+                    phase,
+                    200.0 + 120.0 * angle.cos(),
+                    200.0 + 120.0 * angle.sin(),
+                    time_us,
+                )),
+            });
+        }
+        let recording = InputRecording {
+            header: InputRecordingHeader::new("test"),
+            events,
+        };
+
+        let accuracy = measure_prediction_accuracy(&recording, &PredictionConfig::default());
+        assert!(accuracy.predictions > 200);
+        assert!(
+            accuracy.mean_error_px < accuracy.hold_mean_error_px * 0.2,
+            "{:?}",
+            accuracy
+        );
+        assert!(accuracy.p95_error_px < 1.0, "{:?}", accuracy);
+    }
+}
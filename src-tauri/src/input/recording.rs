@@ -0,0 +1,396 @@
+//! Input session recording
+//!
+//! Captures the tablet event stream to a JSONL log: one header line followed by
+//! one `RecordedInputEvent` per line. Samples are written after backend processing
+//! (pressure curve, coordinate mapping), so a replay reproduces exactly what the
+//! brush pipeline saw. Writes happen on a dedicated thread so the event emitter
+//! never blocks on disk while holding the tablet state lock.
+
+use super::backend::{NativeTabletEventV3, TabletEventV3};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+/// Format tag written into every recording header
+pub const INPUT_RECORDING_FORMAT: &str = "sutu-input-recording";
+/// Current recording format version
+pub const INPUT_RECORDING_VERSION: u32 = 1;
+
+/// First line of a recording
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputRecordingHeader {
+    pub format: String,
+    pub version: u32,
+    /// Backend that produced the events (e.g. "wintab")
+    pub backend: String,
+    /// Wall-clock start of the recording
+    pub recorded_at_us: u64,
+}
+
+impl InputRecordingHeader {
+    pub fn new(backend: &str) -> Self {
+        Self {
+            format: INPUT_RECORDING_FORMAT.to_string(),
+            version: INPUT_RECORDING_VERSION,
+            backend: backend.to_string(),
+            recorded_at_us: super::current_time_us(),
+        }
+    }
+}
+
+/// One recorded event with its host timestamp
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedInputEvent {
+    pub time_us: u64,
+    pub event: TabletEventV3,
+}
+
+/// Writes tablet events to a JSONL log
+pub struct InputRecorder {
+    sender: mpsc::Sender<Vec<TabletEventV3>>,
+    written: Arc<AtomicU64>,
+    failed: Arc<AtomicBool>,
+    writer_thread: JoinHandle<Result<u64, String>>,
+}
+
+struct RecorderState {
+    writer: Box<dyn Write + Send>,
+    last_time_us: Option<u64>,
+    written: Arc<AtomicU64>,
+    failed: Arc<AtomicBool>,
+}
+
+impl InputRecorder {
+    /// Create a recording file and write its header
+    pub fn create(path: &Path, backend: &str) -> Result<Self, String> {
+        let file = File::create(path)
+            .map_err(|e| format!("Failed to create recording {}: {}", path.display(), e))?;
+        Self::from_writer(Box::new(BufWriter::new(file)), backend)
+    }
+
+    /// Record into an arbitrary writer
+    pub fn from_writer(mut writer: Box<dyn Write + Send>, backend: &str) -> Result<Self, String> {
+        let header = InputRecordingHeader::new(backend);
+        write_json_line(&mut writer, &header)?;
+
+        let written = Arc::new(AtomicU64::new(0));
+        let failed = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = mpsc::channel::<Vec<TabletEventV3>>();
+        let mut state = RecorderState {
+            writer,
+            last_time_us: None,
+            written: written.clone(),
+            failed: failed.clone(),
+        };
+        let writer_thread = thread::Builder::new()
+            .name("input-recorder".to_string())
+            .spawn(move || {
+                let mut result = Ok(());
+                for batch in receiver {
+                    if result.is_ok() {
+                        result = state.write_batch(&batch);
+                    }
+                }
+                result?;
+                state
+                    .writer
+                    .flush()
+                    .map_err(|e| format!("Failed to flush recording: {}", e))?;
+                Ok(state.written.load(Ordering::SeqCst))
+            })
+            .map_err(|e| format!("Failed to spawn recorder thread: {}", e))?;
+
+        Ok(Self {
+            sender,
+            written,
+            failed,
+            writer_thread,
+        })
+    }
+
+    /// Append a batch of events as drained from an `InputEventQueue`
+    ///
+    /// Samples keep their own host time; other events inherit the previous
+    /// timestamp (or the next sample's when nothing was recorded yet) so the
+    /// log stays monotonic.
+    pub fn record_batch(&self, events: &[TabletEventV3]) {
+        if events.is_empty() || self.failed.load(Ordering::SeqCst) {
+            return;
+        }
+        let _ = self.sender.send(events.to_vec());
+    }
+
+    /// Append a hover sample taken from `TabletBackend::take_hover`
+    pub fn record_hover(&self, sample: &NativeTabletEventV3) {
+        self.record_batch(&[TabletEventV3::Input(sample.clone())]);
+    }
+
+    /// Number of events written so far
+    pub fn written(&self) -> u64 {
+        self.written.load(Ordering::SeqCst)
+    }
+
+    /// Write pending events and flush the log; returns the number of recorded events
+    pub fn finish(self) -> Result<u64, String> {
+        drop(self.sender);
+        self.writer_thread
+            .join()
+            .map_err(|_| "Recorder thread panicked".to_string())?
+    }
+}
+
+impl RecorderState {
+    fn write_batch(&mut self, events: &[TabletEventV3]) -> Result<(), String> {
+        for (index, event) in events.iter().enumerate() {
+            let time_us = match (event, self.last_time_us) {
+                (TabletEventV3::Input(sample), Some(last)) => sample.host_time_us.max(last),
+                (TabletEventV3::Input(sample), None) => sample.host_time_us,
+                (_, Some(last)) => last,
+                (_, None) => events[index..]
+                    .iter()
+                    .find_map(|e| match e {
+                        TabletEventV3::Input(sample) => Some(sample.host_time_us),
+                        _ => None,
+                    })
+                    .unwrap_or_else(super::current_time_us),
+            };
+            let record = RecordedInputEvent {
+                time_us,
+                event: event.clone(),
+            };
+            if let Err(e) = write_json_line(&mut self.writer, &record) {
+                tracing::warn!("[InputRecorder] {}", e);
+                self.failed.store(true, Ordering::SeqCst);
+                return Err(e);
+            }
+            self.last_time_us = Some(time_us);
+            self.written.fetch_add(1, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+}
+
+fn write_json_line<T: Serialize>(writer: &mut dyn Write, value: &T) -> Result<(), String> {
+    serde_json::to_writer(&mut *writer, value)
+        .map_err(|e| format!("Failed to write recording: {}", e))?;
+    writer
+        .write_all(b"\n")
+        .map_err(|e| format!("Failed to write recording: {}", e))
+}
+
+/// A recording loaded into memory
+#[derive(Debug, Clone)]
+pub struct InputRecording {
+    pub header: InputRecordingHeader,
+    pub events: Vec<RecordedInputEvent>,
+}
+
+impl InputRecording {
+    /// Load a recording from disk
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let file = File::open(path)
+            .map_err(|e| format!("Failed to open recording {}: {}", path.display(), e))?;
+        Self::from_reader(BufReader::new(file))
+    }
+
+    /// Parse a recording from JSONL
+    pub fn from_reader(reader: impl BufRead) -> Result<Self, String> {
+        let mut header: Option<InputRecordingHeader> = None;
+        let mut events = Vec::new();
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = line.map_err(|e| format!("Failed to read recording: {}", e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let line_no = index + 1;
+            if header.is_none() {
+                let parsed: InputRecordingHeader = serde_json::from_str(&line)
+                    .map_err(|e| format!("Invalid recording header: {}", e))?;
+                if parsed.format != INPUT_RECORDING_FORMAT {
+                    return Err(format!("Not an input recording: {}", parsed.format));
+                }
+                if parsed.version > INPUT_RECORDING_VERSION {
+                    return Err(format!(
+                        "Unsupported recording version {} (max {})",
+                        parsed.version, INPUT_RECORDING_VERSION
+                    ));
+                }
+                header = Some(parsed);
+                continue;
+            }
+            let event: RecordedInputEvent = serde_json::from_str(&line)
+                .map_err(|e| format!("Invalid recording event at line {}: {}", line_no, e))?;
+            events.push(event);
+        }
+
+        let header = header.ok_or_else(|| "Recording is empty".to_string())?;
+        Ok(Self { header, events })
+    }
+
+    /// Recorded span between first and last event
+    pub fn duration_us(&self) -> u64 {
+        match (self.events.first(), self.events.last()) {
+            (Some(first), Some(last)) => last.time_us.saturating_sub(first.time_us),
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{InputPhase, InputSource, ToolTypeV3};
+    use std::sync::Mutex;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            match self.0.lock() {
+                Ok(mut lock) => lock.extend_from_slice(buf),
+                Err(poisoned) => poisoned.into_inner().extend_from_slice(buf),
+            }
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuffer {
+        fn bytes(&self) -> Vec<u8> {
+            match self.0.lock() {
+                Ok(lock) => lock.clone(),
+                Err(poisoned) => poisoned.into_inner().clone(),
+            }
+        }
+    }
+
+    fn sample(phase: InputPhase, x: f32, host_time_us: u64) -> TabletEventV3 {
+        TabletEventV3::Input(NativeTabletEventV3 {
+            seq: 0,
+            stroke_id: 1,
+            pointer_id: 1,
+            device_id: "test".to_string(),
+            source: InputSource::WinTab,
+            phase,
+            x_px: x,
+            y_px: 20.0,
+            pressure_0_1: 0.5,
+            tilt_x_deg: 10.0,
+            tilt_y_deg: -5.0,
+            rotation_deg: 0.0,
+            host_time_us,
+            device_time_us: Some(host_time_us),
+            tool_type: ToolTypeV3::Pen,
+            tool_serial: None,
+            wheel_0_1: None,
+            distance_0_1: None,
+            coalesced: None,
+            predicted: false,
+            prediction_index: None,
+        })
+    }
+
+    #[test]
+    fn recording_roundtrips_events_in_order() {
+        let buffer = SharedBuffer::default();
+        let recorder =
+            InputRecorder::from_writer(Box::new(buffer.clone()), "wintab").expect("recorder");
+        recorder.record_batch(&[
+            TabletEventV3::ProximityEnter,
+            sample(InputPhase::Down, 1.0, 1_000),
+            sample(InputPhase::Move, 2.0, 2_000),
+        ]);
+        recorder.record_batch(&[
+            sample(InputPhase::Up, 3.0, 3_000),
+            TabletEventV3::ProximityLeave,
+        ]);
+        assert_eq!(recorder.finish(), Ok(5));
+
+        let bytes = buffer.bytes();
+        let recording = InputRecording::from_reader(bytes.as_slice()).expect("parse");
+        assert_eq!(recording.header.backend, "wintab");
+        assert_eq!(recording.events.len(), 5);
+        assert!(matches!(
+            recording.events[0].event,
+            TabletEventV3::ProximityEnter
+        ));
+        assert!(matches!(
+            recording.events[4].event,
+            TabletEventV3::ProximityLeave
+        ));
+        assert_eq!(recording.events[4].time_us, 3_000);
+        assert_eq!(recording.events[0].time_us, 1_000);
+        assert_eq!(recording.duration_us(), 2_000);
+        let TabletEventV3::Input(up) = &recording.events[3].event else {
+            panic!("expected sample");
+        };
+        assert_eq!(up.phase, InputPhase::Up);
+        assert_eq!(up.tilt_y_deg, -5.0);
+    }
+
+    #[test]
+    fn recording_times_stay_monotonic() {
+        let buffer = SharedBuffer::default();
+        let recorder =
+            InputRecorder::from_writer(Box::new(buffer.clone()), "pointerevent").expect("recorder");
+        recorder.record_batch(&[
+            sample(InputPhase::Down, 1.0, 500),
+            sample(InputPhase::Move, 2.0, 100),
+            TabletEventV3::ProximityLeave,
+        ]);
+        assert_eq!(recorder.finish(), Ok(3));
+
+        let recording = InputRecording::from_reader(buffer.bytes().as_slice()).expect("parse");
+        let times: Vec<u64> = recording.events.iter().map(|e| e.time_us).collect();
+        assert_eq!(times, vec![500, 500, 500]);
+    }
+
+    #[test]
+    fn recording_keeps_hover_samples() {
+        let buffer = SharedBuffer::default();
+        let recorder =
+            InputRecorder::from_writer(Box::new(buffer.clone()), "wintab").expect("recorder");
+        let TabletEventV3::Input(hover) = sample(InputPhase::Hover, 4.0, 800) else {
+            unreachable!();
+        };
+        recorder.record_hover(&hover);
+        recorder.record_batch(&[sample(InputPhase::Down, 5.0, 900)]);
+        assert_eq!(recorder.finish(), Ok(2));
+
+        let recording = InputRecording::from_reader(buffer.bytes().as_slice()).expect("parse");
+        let TabletEventV3::Input(recorded) = &recording.events[0].event else {
+            panic!("expected hover sample");
+        };
+        assert_eq!(recorded.phase, InputPhase::Hover);
+        assert_eq!(recording.events[0].time_us, 800);
+        assert_eq!(recording.events[1].time_us, 900);
+    }
+
+    #[test]
+    fn rejects_foreign_and_malformed_logs() {
+        assert!(InputRecording::from_reader("".as_bytes()).is_err());
+        assert!(InputRecording::from_reader(
+            "{\"format\":\"other\",\"version\":1,\"backend\":\"x\",\"recordedAtUs\":0}\n"
+                .as_bytes()
+        )
+        .is_err());
+
+        let bad_event = "{\"format\":\"sutu-input-recording\",\"version\":1,\"backend\":\"x\",\"recordedAtUs\":0}\n{\"timeUs\":1}\n";
+        let Err(message) = InputRecording::from_reader(bad_event.as_bytes()) else {
+            panic!("expected parse error");
+        };
+        assert!(message.contains("line 2"));
+    }
+}
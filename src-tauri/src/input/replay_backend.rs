@@ -0,0 +1,389 @@
+//! Replay backend implementation
+//!
+//! Plays an `InputRecording` back through the regular `TabletBackend` interface,
+//! so sessions captured on real tablets can be reproduced without hardware.
+
+use super::backend::{
+    default_event_queue_capacity, InputEventQueue, InputQueueMetrics, NativeTabletEventV3,
+    TabletBackend, TabletConfig, TabletEventV3, TabletInfo, TabletStatus,
+};
+use super::recording::InputRecording;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+const SLEEP_SLICE_US: u64 = 10_000;
+
+/// Playback speed for a replay
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ReplayTiming {
+    /// Keep the recorded gaps between events
+    #[default]
+    Original,
+    /// Divide recorded gaps by this factor
+    Accelerated(f32),
+    /// Enqueue everything at once and keep recorded host timestamps
+    Immediate,
+}
+
+impl ReplayTiming {
+    /// Reject speed factors that are not finite and positive
+    pub fn validate(self) -> Result<(), String> {
+        match self {
+            Self::Accelerated(factor) if !(factor.is_finite() && factor > 0.0) => {
+                Err(format!("Invalid replay speed: {}", factor))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn speed(self) -> Option<f64> {
+        match self {
+            Self::Original => Some(1.0),
+            Self::Accelerated(factor) => Some(factor as f64),
+            Self::Immediate => None,
+        }
+    }
+}
+
+/// Backend that replays a recorded input session
+pub struct ReplayBackend {
+    status: TabletStatus,
+    info: Option<TabletInfo>,
+    config: TabletConfig,
+    events: Arc<InputEventQueue>,
+    recording: Arc<InputRecording>,
+    timing: ReplayTiming,
+    running: Arc<AtomicBool>,
+    finished: Arc<AtomicBool>,
+    replay_thread: Option<JoinHandle<()>>,
+}
+
+impl ReplayBackend {
+    /// Create a replay backend for an in-memory recording
+    pub fn new(recording: InputRecording, timing: ReplayTiming) -> Self {
+        Self {
+            status: TabletStatus::Disconnected,
+            info: None,
+            config: TabletConfig::default(),
+            events: Arc::new(InputEventQueue::new(
+                super::backend::InputBackpressureMode::Lossless,
+                default_event_queue_capacity(),
+            )),
+            recording: Arc::new(recording),
+            timing,
+            running: Arc::new(AtomicBool::new(false)),
+            finished: Arc::new(AtomicBool::new(false)),
+            replay_thread: None,
+        }
+    }
+
+    /// Create a replay backend from a recording file
+    pub fn from_file(path: &Path, timing: ReplayTiming) -> Result<Self, String> {
+        Ok(Self::new(InputRecording::load(path)?, timing))
+    }
+
+    /// True once every recorded event has been enqueued
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::SeqCst)
+    }
+
+    fn join_replay_thread(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        self.events.close();
+        if let Some(handle) = self.replay_thread.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Sleep until `deadline_us`, waking early when the replay is stopped
+fn sleep_until(deadline_us: u64, running: &AtomicBool) {
+    while running.load(Ordering::SeqCst) {
+        let now_us = super::current_time_us();
+        if now_us >= deadline_us {
+            return;
+        }
+        let wait_us = (deadline_us - now_us).min(SLEEP_SLICE_US);
+        thread::sleep(Duration::from_micros(wait_us));
+    }
+}
+
+fn run_replay(
+    recording: &InputRecording,
+    timing: ReplayTiming,
+    events: &InputEventQueue,
+    running: &AtomicBool,
+) {
+    let first_time_us = recording.events.first().map_or(0, |e| e.time_us);
+    let start_us = super::current_time_us();
+
+    for recorded in &recording.events {
+        if !running.load(Ordering::SeqCst) {
+            return;
+        }
+        let offset_us = recorded.time_us.saturating_sub(first_time_us);
+        let host_time_us = match timing.speed() {
+            Some(speed) => {
+                let scaled_us = (offset_us as f64 / speed) as u64;
+                let target_us = start_us.saturating_add(scaled_us);
+                sleep_until(target_us, running);
+                Some(target_us)
+            }
+            None => None,
+        };
+
+        let accepted = match &recorded.event {
+            TabletEventV3::Input(sample) => {
+                let mut sample = sample.clone();
+                if let Some(host_time_us) = host_time_us {
+                    sample.device_time_us =
+                        Some(sample.device_time_us.unwrap_or(sample.host_time_us));
+                    sample.host_time_us = host_time_us;
+                }
+                events.enqueue_sample(sample)
+            }
+            other => events.enqueue_event(other.clone()),
+        };
+        if !accepted {
+            return;
+        }
+    }
+}
+
+impl TabletBackend for ReplayBackend {
+    fn init(&mut self, config: &TabletConfig) -> Result<(), String> {
+        self.timing.validate()?;
+        self.config = config.clone();
+        self.events = Arc::new(InputEventQueue::new(
+            config.backpressure_mode,
+            default_event_queue_capacity(),
+        ));
+        self.info = Some(TabletInfo {
+            name: format!("Replay ({})", self.recording.header.backend),
+            backend: "Replay".to_string(),
+            supports_pressure: true,
+            supports_tilt: true,
+            pressure_range: (0, 1),
+        });
+        self.status = TabletStatus::Connected;
+        tracing::info!(
+            "[Replay] Initialized: {} events over {} ms",
+            self.recording.events.len(),
+            self.recording.duration_us() / 1000
+        );
+        Ok(())
+    }
+
+    fn start(&mut self) -> Result<(), String> {
+        if self.status != TabletStatus::Connected {
+            return Err("Backend not initialized".to_string());
+        }
+        if self.replay_thread.is_some() {
+            return Ok(());
+        }
+
+        self.events.reopen();
+        self.running.store(true, Ordering::SeqCst);
+        self.finished.store(false, Ordering::SeqCst);
+
+        let recording = self.recording.clone();
+        let timing = self.timing;
+        let events = self.events.clone();
+        let running = self.running.clone();
+        let finished = self.finished.clone();
+        let handle = thread::Builder::new()
+            .name("input-replay".to_string())
+            .spawn(move || {
+                run_replay(&recording, timing, &events, &running);
+                finished.store(true, Ordering::SeqCst);
+            })
+            .map_err(|e| format!("Failed to spawn replay thread: {}", e))?;
+        self.replay_thread = Some(handle);
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        self.join_replay_thread();
+        self.events.clear();
+    }
+
+    fn status(&self) -> TabletStatus {
+        self.status
+    }
+
+    fn info(&self) -> Option<&TabletInfo> {
+        self.info.as_ref()
+    }
+
+    fn poll(&mut self, events: &mut Vec<TabletEventV3>) -> usize {
+        self.events.drain_into(events, super::current_time_us)
+    }
+
+    fn take_hover(&mut self) -> Option<NativeTabletEventV3> {
+        self.events.take_hover()
+    }
+
+    fn queue_metrics(&self) -> InputQueueMetrics {
+        self.events.metrics_snapshot()
+    }
+
+    fn is_available() -> bool {
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        "Replay"
+    }
+}
+
+impl Drop for ReplayBackend {
+    fn drop(&mut self) {
+        self.join_replay_thread();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::recording::{InputRecordingHeader, RecordedInputEvent};
+    use crate::input::{InputPhase, InputSource, NativeTabletEventV3, ToolTypeV3};
+    use std::time::Instant;
+
+    fn recorded_stroke(gap_us: u64) -> InputRecording {
+        let mut events = vec![RecordedInputEvent {
+            time_us: 5_000,
+            event: TabletEventV3::ProximityEnter,
+        }];
+        for i in 0..10u64 {
+            let phase = match i {
+                0 => InputPhase::Down,
+                9 => InputPhase::Up,
+                _ => InputPhase::Move,
+            };
+            let time_us = 5_000 + i * gap_us;
+            events.push(RecordedInputEvent {
+                time_us,
+                event: TabletEventV3::Input(NativeTabletEventV3 {
+                    seq: 100 + i,
+                    stroke_id: 7,
+                    pointer_id: 1,
+                    device_id: "wintab".to_string(),
+                    source: InputSource::WinTab,
+                    phase,
+                    x_px: 10.0 + i as f32 * 3.0,
+                    y_px: 40.0,
+                    pressure_0_1: 0.1 * i as f32,
+                    tilt_x_deg: 0.0,
+                    tilt_y_deg: 0.0,
+                    rotation_deg: 0.0,
+                    host_time_us: time_us,
+                    device_time_us: None,
+                    tool_type: ToolTypeV3::Pen,
+                    tool_serial: None,
+                    wheel_0_1: None,
+                    distance_0_1: None,
+                    coalesced: None,
+                    predicted: false,
+                    prediction_index: None,
+                }),
+            });
+        }
+        events.push(RecordedInputEvent {
+            time_us: 5_000 + 9 * gap_us,
+            event: TabletEventV3::ProximityLeave,
+        });
+        InputRecording {
+            header: InputRecordingHeader::new("wintab"),
+            events,
+        }
+    }
+
+    fn replay_all(backend: &mut ReplayBackend) -> Vec<TabletEventV3> {
+        backend.init(&TabletConfig::default()).expect("init");
+        backend.start().expect("start");
+        let mut events = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !backend.is_finished() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(2));
+        }
+        backend.poll(&mut events);
+        backend.stop();
+        events
+    }
+
+    fn samples(events: &[TabletEventV3]) -> Vec<&NativeTabletEventV3> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                TabletEventV3::Input(sample) => Some(sample),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn immediate_replay_is_deterministic() {
+        let mut first = ReplayBackend::new(recorded_stroke(5_000), ReplayTiming::Immediate);
+        let mut second = ReplayBackend::new(recorded_stroke(5_000), ReplayTiming::Immediate);
+        let a = replay_all(&mut first);
+        let b = replay_all(&mut second);
+
+        assert_eq!(a.len(), 12);
+        assert!(matches!(a.first(), Some(TabletEventV3::ProximityEnter)));
+        assert!(matches!(a.last(), Some(TabletEventV3::ProximityLeave)));
+        let (sa, sb) = (samples(&a), samples(&b));
+        assert_eq!(sa.len(), 10);
+        for (x, y) in sa.iter().zip(sb.iter()) {
+            assert_eq!(x.seq, y.seq);
+            assert_eq!(x.phase, y.phase);
+            assert_eq!(x.x_px, y.x_px);
+            assert_eq!(x.pressure_0_1, y.pressure_0_1);
+            assert_eq!(x.host_time_us, y.host_time_us);
+        }
+        assert_eq!(sa[0].host_time_us, 5_000);
+        assert_eq!(sa[9].phase, InputPhase::Up);
+    }
+
+    #[test]
+    fn accelerated_replay_compresses_timing() {
+        // 45 ms of recorded input at 10x should finish well under the original span
+        let mut backend =
+            ReplayBackend::new(recorded_stroke(5_000), ReplayTiming::Accelerated(10.0));
+        let started = Instant::now();
+        let events = replay_all(&mut backend);
+        assert!(started.elapsed() < Duration::from_millis(45));
+
+        let samples = samples(&events);
+        assert_eq!(samples.len(), 10);
+        let span_us = samples[9].host_time_us - samples[0].host_time_us;
+        assert!((4_000..=5_000).contains(&span_us), "span {}", span_us);
+        // Original timestamps are kept as device time
+        assert_eq!(samples[9].device_time_us, Some(5_000 + 9 * 5_000));
+    }
+
+    #[test]
+    fn invalid_speed_fails_init() {
+        for factor in [0.0, -2.0, f32::NAN, f32::INFINITY] {
+            let mut backend =
+                ReplayBackend::new(recorded_stroke(1_000), ReplayTiming::Accelerated(factor));
+            assert!(backend.init(&TabletConfig::default()).is_err());
+            assert_eq!(backend.status(), TabletStatus::Disconnected);
+        }
+    }
+
+    #[test]
+    fn original_timing_preserves_gaps() {
+        let mut backend = ReplayBackend::new(recorded_stroke(3_000), ReplayTiming::Original);
+        let started = Instant::now();
+        let events = replay_all(&mut backend);
+        assert!(started.elapsed() >= Duration::from_micros(27_000));
+
+        let samples = samples(&events);
+        for pair in samples.windows(2) {
+            assert_eq!(pair[1].host_time_us - pair[0].host_time_us, 3_000);
+        }
+    }
+}
@@ -0,0 +1,362 @@
+//! Tool identity tracking and per-tool settings
+//!
+//! `ToolTracker` watches drained batches for tool switches (eraser end, a second
+//! stylus, airbrush, puck), inserts `TabletEventV3::ToolChanged` ahead of the first
+//! sample of the new tool and applies that tool's pressure curve. Per-tool profiles
+//! are persisted as JSON next to the brush and pattern libraries.
+
+use super::backend::{InputSource, PressureCurve, TabletEventV3, ToolChangeV3, ToolTypeV3};
+use super::config_file::{config_file_path, load_config_file, save_config_file};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub const TOOL_PROFILES_FILE_NAME: &str = "tablet_tools.json";
+const TOOL_PROFILES_VERSION: u32 = 1;
+
+/// Settings bound to one physical tool (or to every tool of a type)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolProfileV1 {
+    /// Applied to this tool's samples after backend shaping
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pressure_curve: Option<PressureCurve>,
+    /// Frontend tool to activate when this tool comes into use (e.g. "eraser")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_id: Option<String>,
+    /// Brush preset to select for this tool
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brush_preset_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolProfilesFileV1 {
+    version: u32,
+    profiles: BTreeMap<String, ToolProfileV1>,
+}
+
+/// Profile key for a tool: `"<type>:<serial hex>"` for a specific tool, `"<type>"` for all
+pub fn tool_profile_key(tool_type: ToolTypeV3, tool_serial: Option<u64>) -> String {
+    match tool_serial {
+        Some(serial) => format!("{}:{:x}", tool_type.as_str(), serial),
+        None => tool_type.as_str().to_string(),
+    }
+}
+
+pub fn default_tool_profiles_path() -> PathBuf {
+    config_file_path(TOOL_PROFILES_FILE_NAME)
+}
+
+/// Per-tool profiles, optionally backed by a file
+#[derive(Debug, Clone)]
+pub struct ToolProfileStore {
+    path: Option<PathBuf>,
+    profiles: BTreeMap<String, ToolProfileV1>,
+}
+
+impl Default for ToolProfileStore {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+impl ToolProfileStore {
+    fn default_profiles() -> BTreeMap<String, ToolProfileV1> {
+        BTreeMap::from([(
+            ToolTypeV3::Eraser.as_str().to_string(),
+            ToolProfileV1 {
+                tool_id: Some("eraser".to_string()),
+                ..ToolProfileV1::default()
+            },
+        )])
+    }
+
+    /// Store with default profiles that is never written to disk
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            profiles: Self::default_profiles(),
+        }
+    }
+
+    /// Load profiles from `path`; a missing or unreadable file yields the defaults
+    pub fn load(path: &Path) -> Self {
+        let profiles = load_config_file::<ToolProfilesFileV1>(path, "tool profiles")
+            .map(|file| file.profiles)
+            .unwrap_or_else(Self::default_profiles);
+        Self {
+            path: Some(path.to_path_buf()),
+            profiles,
+        }
+    }
+
+    pub fn profiles(&self) -> &BTreeMap<String, ToolProfileV1> {
+        &self.profiles
+    }
+
+    /// Set (or remove with `None`) the profile for `key` and persist
+    pub fn set(&mut self, key: &str, profile: Option<ToolProfileV1>) -> Result<(), String> {
+        if key.trim().is_empty() {
+            return Err("Tool profile key is empty".to_string());
+        }
+        match profile {
+            Some(profile) => {
+                self.profiles.insert(key.to_string(), profile);
+            }
+            None => {
+                self.profiles.remove(key);
+            }
+        }
+        self.save()
+    }
+
+    /// Most specific profile for a tool: its serial first, then its type
+    pub fn resolve(
+        &self,
+        tool_type: ToolTypeV3,
+        tool_serial: Option<u64>,
+    ) -> Option<(String, &ToolProfileV1)> {
+        let specific = tool_profile_key(tool_type, tool_serial);
+        if let Some(profile) = self.profiles.get(&specific) {
+            return Some((specific, profile));
+        }
+        let generic = tool_profile_key(tool_type, None);
+        self.profiles
+            .get(&generic)
+            .map(|profile| (generic, profile))
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let file = ToolProfilesFileV1 {
+            version: TOOL_PROFILES_VERSION,
+            profiles: self.profiles.clone(),
+        };
+        save_config_file(path, &file, "tool profiles")
+    }
+}
+
+/// A tool seen during this session
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KnownToolV3 {
+    pub tool_type: ToolTypeV3,
+    pub tool_serial: Option<u64>,
+    pub pointer_id: u32,
+    pub device_id: String,
+    pub source: InputSource,
+    pub profile_key: String,
+    pub last_seen_us: u64,
+}
+
+/// Detects tool switches and applies per-tool profiles to drained batches
+#[derive(Debug, Clone, Default)]
+pub struct ToolTracker {
+    store: ToolProfileStore,
+    current: Option<(ToolTypeV3, Option<u64>)>,
+    known: BTreeMap<String, KnownToolV3>,
+}
+
+impl ToolTracker {
+    pub fn new(store: ToolProfileStore) -> Self {
+        Self {
+            store,
+            current: None,
+            known: BTreeMap::new(),
+        }
+    }
+
+    pub fn store(&self) -> &ToolProfileStore {
+        &self.store
+    }
+
+    pub fn store_mut(&mut self) -> &mut ToolProfileStore {
+        &mut self.store
+    }
+
+    /// Tools seen since startup, most recently used first
+    pub fn known_tools(&self) -> Vec<KnownToolV3> {
+        let mut tools: Vec<KnownToolV3> = self.known.values().cloned().collect();
+        tools.sort_by_key(|tool| std::cmp::Reverse(tool.last_seen_us));
+        tools
+    }
+
+    /// Forget the active tool so the next sample announces itself again
+    pub fn reset(&mut self) {
+        self.current = None;
+    }
+
+    pub fn process(&mut self, events: &mut Vec<TabletEventV3>) {
+        if !events.iter().any(|e| matches!(e, TabletEventV3::Input(_))) {
+            return;
+        }
+
+        let mut out = Vec::with_capacity(events.len() + 1);
+        for event in events.drain(..) {
+            let TabletEventV3::Input(mut sample) = event else {
+                out.push(event);
+                continue;
+            };
+
+            let identity = (sample.tool_type, sample.tool_serial);
+            let (profile_key, profile) = match self.store.resolve(identity.0, identity.1) {
+                Some((key, profile)) => (key, Some(profile.clone())),
+                None => (tool_profile_key(identity.0, identity.1), None),
+            };
+
+            if self.current != Some(identity) {
+                self.current = Some(identity);
+                out.push(TabletEventV3::ToolChanged(ToolChangeV3 {
+                    pointer_id: sample.pointer_id,
+                    tool_type: identity.0,
+                    tool_serial: identity.1,
+                    profile_key: profile_key.clone(),
+                    tool_id: profile.as_ref().and_then(|p| p.tool_id.clone()),
+                    brush_preset_id: profile.as_ref().and_then(|p| p.brush_preset_id.clone()),
+                }));
+            }
+
+            self.known.insert(
+                tool_profile_key(identity.0, identity.1),
+                KnownToolV3 {
+                    tool_type: identity.0,
+                    tool_serial: identity.1,
+                    pointer_id: sample.pointer_id,
+                    device_id: sample.device_id.clone(),
+                    source: sample.source,
+                    profile_key,
+                    last_seen_us: sample.host_time_us,
+                },
+            );
+
+            if let Some(curve) = profile.and_then(|p| p.pressure_curve) {
+                sample.pressure_0_1 = curve.apply(sample.pressure_0_1);
+            }
+            out.push(TabletEventV3::Input(sample));
+        }
+        *events = out;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{InputPhase, NativeTabletEventV3};
+
+    fn sample(tool_type: ToolTypeV3, tool_serial: Option<u64>, pressure: f32) -> TabletEventV3 {
+        TabletEventV3::Input(NativeTabletEventV3 {
+            seq: 0,
+            stroke_id: 1,
+            pointer_id: 1,
+            device_id: "test".to_string(),
+            source: InputSource::WinTab,
+            phase: InputPhase::Move,
+            x_px: 0.0,
+            y_px: 0.0,
+            pressure_0_1: pressure,
+            tilt_x_deg: 0.0,
+            tilt_y_deg: 0.0,
+            rotation_deg: 0.0,
+            host_time_us: 1_000,
+            device_time_us: None,
+            tool_type,
+            tool_serial,
+            wheel_0_1: None,
+            distance_0_1: None,
+            coalesced: None,
+            predicted: false,
+            prediction_index: None,
+        })
+    }
+
+    fn changes(events: &[TabletEventV3]) -> Vec<&ToolChangeV3> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                TabletEventV3::ToolChanged(change) => Some(change),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn eraser_end_switches_tool() {
+        let mut tracker = ToolTracker::new(ToolProfileStore::in_memory());
+        let mut events = vec![
+            sample(ToolTypeV3::Pen, Some(7), 0.5),
+            sample(ToolTypeV3::Pen, Some(7), 0.6),
+        ];
+        tracker.process(&mut events);
+        assert_eq!(events.len(), 3);
+        let change = changes(&events)[0];
+        assert_eq!(change.tool_type, ToolTypeV3::Pen);
+        assert_eq!(change.profile_key, "pen:7");
+        assert_eq!(change.tool_id, None);
+
+        let mut events = vec![sample(ToolTypeV3::Eraser, Some(7), 0.5)];
+        tracker.process(&mut events);
+        assert!(matches!(events[0], TabletEventV3::ToolChanged(_)));
+        let change = changes(&events)[0];
+        assert_eq!(change.profile_key, "eraser");
+        assert_eq!(change.tool_id.as_deref(), Some("eraser"));
+
+        let mut events = vec![sample(ToolTypeV3::Eraser, Some(7), 0.5)];
+        tracker.process(&mut events);
+        assert!(changes(&events).is_empty());
+        assert_eq!(tracker.known_tools().len(), 2);
+    }
+
+    #[test]
+    fn serial_profile_overrides_type_profile_and_persists() {
+        let path = std::env::temp_dir().join(format!(
+            "sutu-tablet-tools-{}-{}.json",
+            std::process::id(),
+            line!()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let mut store = ToolProfileStore::load(&path);
+        store
+            .set(
+                "pen",
+                Some(ToolProfileV1 {
+                    brush_preset_id: Some("ink".to_string()),
+                    ..ToolProfileV1::default()
+                }),
+            )
+            .expect("save pen");
+        store
+            .set(
+                &tool_profile_key(ToolTypeV3::Pen, Some(0xabc)),
+                Some(ToolProfileV1 {
+                    pressure_curve: Some(PressureCurve::Hard),
+                    brush_preset_id: Some("pencil".to_string()),
+                    ..ToolProfileV1::default()
+                }),
+            )
+            .expect("save serial");
+
+        let reloaded = ToolProfileStore::load(&path);
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(reloaded.profiles(), store.profiles());
+
+        let mut tracker = ToolTracker::new(reloaded);
+        let mut events = vec![sample(ToolTypeV3::Pen, Some(0xabc), 0.5)];
+        tracker.process(&mut events);
+        let change = changes(&events)[0];
+        assert_eq!(change.profile_key, "pen:abc");
+        assert_eq!(change.brush_preset_id.as_deref(), Some("pencil"));
+        let TabletEventV3::Input(shaped) = &events[1] else {
+            panic!("expected sample");
+        };
+        assert_eq!(shaped.pressure_0_1, PressureCurve::Hard.apply(0.5));
+
+        let mut events = vec![sample(ToolTypeV3::Pen, Some(0xdef), 0.5)];
+        tracker.process(&mut events);
+        let change = changes(&events)[0];
+        assert_eq!(change.profile_key, "pen");
+        assert_eq!(change.brush_preset_id.as_deref(), Some("ink"));
+    }
+}
@@ -2,17 +2,39 @@
 //!
 //! Provides low-latency tablet input on Windows via the WinTab API.
 //! This is the preferred backend for Wacom tablets.
+//!
+//! Pen barrel buttons come from `pkButtons`. Express keys, touch rings and touch
+//! strips use the Wacom extensions (`WTX_EXPKEYS2` / `WTX_TOUCHRING` /
+//! `WTX_TOUCHSTRIP`): the context overrides those controls and their
+//! `WT_PACKETEXT` messages are read by a subclass of the context window, which
+//! hands them to `WinTabPadAdapter`. Drivers without the extensions keep pads
+//! silent.
+//!
+//! The tablet maps onto the whole virtual desktop unless the configured
+//! `TabletMappingV1` selects a monitor; its active area, rotation and aspect lock
+//! are applied by the shared `CoordinateMapper`.
 
+#[cfg(target_os = "windows")]
+use super::backend::TabletDialKindV3;
 use super::backend::{
-    default_event_queue_capacity, InputEventQueue, InputQueueMetrics, TabletBackend, TabletConfig,
-    TabletEventV3, TabletInfo, TabletStatus,
+    default_event_queue_capacity, InputEventQueue, InputQueueMetrics, NativeTabletEventV3,
+    TabletBackend, TabletConfig, TabletEventV3, TabletInfo, TabletStatus,
 };
 #[cfg(target_os = "windows")]
-use super::krita_v3::{CoordinateMapper, WinTabAdapter};
+use super::krita_v3::wintab_pad_adapter::{
+    ExtPropertyRaw, PacketExtRaw, EXT_MASK, EXT_TAG, TABLET_PROPERTY_CONTROLCOUNT,
+    TABLET_PROPERTY_FUNCCOUNT, TABLET_PROPERTY_MAX, TABLET_PROPERTY_MIN, TABLET_PROPERTY_OVERRIDE,
+    WTI_EXTENSIONS, WTX_EXPKEYS2, WTX_TOUCHRING, WTX_TOUCHSTRIP, WT_PACKETEXT,
+};
+use super::krita_v3::TabletMappingV1;
+#[cfg(target_os = "windows")]
+use super::krita_v3::{BarrelButtonTracker, CoordinateMapper, WinTabAdapter, WinTabPadAdapter};
 #[cfg(target_os = "windows")]
 use std::collections::HashMap;
 #[cfg(target_os = "windows")]
 use std::ffi::c_void;
+#[cfg(target_os = "windows")]
+use std::sync::atomic::AtomicIsize;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 #[cfg(target_os = "windows")]
@@ -22,15 +44,16 @@ use std::thread::JoinHandle;
 use std::time::Duration;
 
 #[cfg(target_os = "windows")]
-use windows::Win32::Foundation::{HWND, POINT, RECT};
+use windows::Win32::Foundation::{BOOL, HWND, LPARAM, LRESULT, POINT, RECT, WPARAM};
 #[cfg(target_os = "windows")]
-use windows::Win32::Graphics::Gdi::ScreenToClient;
+use windows::Win32::Graphics::Gdi::{EnumDisplayMonitors, ScreenToClient, HDC, HMONITOR};
 #[cfg(target_os = "windows")]
 use windows::Win32::UI::HiDpi::GetDpiForWindow;
 #[cfg(target_os = "windows")]
 use windows::Win32::UI::WindowsAndMessaging::{
-    GetClientRect, GetForegroundWindow, GetSystemMetrics, SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN,
-    SM_XVIRTUALSCREEN, SM_YVIRTUALSCREEN,
+    CallWindowProcW, GetClientRect, GetForegroundWindow, GetSystemMetrics, SetWindowLongPtrW,
+    GWLP_WNDPROC, SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN, SM_XVIRTUALSCREEN, SM_YVIRTUALSCREEN,
+    WNDPROC,
 };
 #[cfg(target_os = "windows")]
 use wintab_lite::{cast_void, Packet, AXIS, CXO, DVC, HCTX, INT, LOGCONTEXT, LPVOID, WTI, WTPKT};
@@ -48,6 +71,17 @@ type WTPacketsGetFn = unsafe extern "C" fn(*mut HCTX, INT, LPVOID) -> INT;
 type WTEnableFn = unsafe extern "C" fn(*mut HCTX, i32) -> i32;
 #[cfg(target_os = "windows")]
 type WTOverlapFn = unsafe extern "C" fn(*mut HCTX, i32) -> i32;
+/// `WTInfoA` with a raw category, for `WTI_EXTENSIONS + n`
+#[cfg(target_os = "windows")]
+type WTInfoRawFn = unsafe extern "C" fn(u32, u32, LPVOID) -> u32;
+#[cfg(target_os = "windows")]
+type WTPacketFn = unsafe extern "C" fn(*mut HCTX, u32, LPVOID) -> i32;
+#[cfg(target_os = "windows")]
+type WTExtFn = unsafe extern "C" fn(*mut HCTX, u32, LPVOID) -> i32;
+
+/// `WT_PACKET` for the default message base; only posted because of `CXO::MESSAGES`
+#[cfg(target_os = "windows")]
+const WT_PACKET: u32 = 0x7FF0;
 
 #[cfg(target_os = "windows")]
 const CONTEXT_REENABLE_INTERVAL_LOOPS: u64 = 100;
@@ -88,6 +122,8 @@ pub struct WinTabBackend {
     #[cfg(target_os = "windows")]
     pressure_max: f32,
     #[cfg(target_os = "windows")]
+    z_range: Option<(i32, i32)>,
+    #[cfg(target_os = "windows")]
     hwnd: Option<isize>, // Window handle for WinTab context
 }
 
@@ -108,6 +144,8 @@ impl WinTabBackend {
             #[cfg(target_os = "windows")]
             pressure_max: 32767.0,
             #[cfg(target_os = "windows")]
+            z_range: None,
+            #[cfg(target_os = "windows")]
             hwnd: None,
         }
     }
@@ -118,6 +156,49 @@ impl WinTabBackend {
         self.hwnd = Some(hwnd);
     }
 
+    /// Apply a new tablet mapping, reopening the context if it is running
+    #[cfg(target_os = "windows")]
+    pub fn set_mapping(&mut self, mapping: TabletMappingV1) -> Result<(), String> {
+        self.config.mapping = mapping;
+        if self.running.load(Ordering::SeqCst) {
+            self.stop();
+            self.start()?;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    pub fn set_mapping(&mut self, _mapping: TabletMappingV1) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Monitor rectangles in `EnumDisplayMonitors` order, which Tauri also reports
+    #[cfg(target_os = "windows")]
+    fn monitor_rects() -> Vec<RECT> {
+        unsafe extern "system" fn collect(
+            _monitor: HMONITOR,
+            _hdc: HDC,
+            rect: *mut RECT,
+            data: LPARAM,
+        ) -> BOOL {
+            let rects = &mut *(data.0 as *mut Vec<RECT>);
+            if !rect.is_null() {
+                rects.push(*rect);
+            }
+            BOOL(1)
+        }
+        let mut rects: Vec<RECT> = Vec::new();
+        unsafe {
+            let _ = EnumDisplayMonitors(
+                HDC::default(),
+                None,
+                Some(collect),
+                LPARAM(&mut rects as *mut Vec<RECT> as isize),
+            );
+        }
+        rects
+    }
+
     #[cfg(target_os = "windows")]
     #[allow(clippy::type_complexity)]
     fn load_wintab_functions() -> Result<
@@ -219,6 +300,219 @@ impl WinTabBackend {
 
         Some((device_name.to_string(), pressure_range, supports_tilt))
     }
+
+    /// Z axis range; only some tablets report hover height
+    #[cfg(target_os = "windows")]
+    fn query_z_range(wt_info: WTInfoFn) -> Option<(i32, i32)> {
+        let mut z_axis = AXIS::default();
+        let result = unsafe { wt_info(WTI::DEVICES, DVC::Z as u32, cast_void!(z_axis)) };
+        (result > 0 && z_axis.axMax > z_axis.axMin).then_some((z_axis.axMin, z_axis.axMax))
+    }
+}
+
+/// Extension entry points; drivers without them get no pad support
+#[cfg(target_os = "windows")]
+#[derive(Clone, Copy)]
+struct PadFunctions {
+    wt_info_raw: WTInfoRawFn,
+    wt_packet: WTPacketFn,
+    wt_ext_get: WTExtFn,
+    wt_ext_set: WTExtFn,
+}
+
+#[cfg(target_os = "windows")]
+impl PadFunctions {
+    fn load(lib: &libloading::Library) -> Option<Self> {
+        // SAFETY: the signatures match the WinTab 1.4 exports
+        unsafe {
+            Some(Self {
+                wt_info_raw: *lib.get::<WTInfoRawFn>(b"WTInfoA").ok()?,
+                wt_packet: *lib.get::<WTPacketFn>(b"WTPacket").ok()?,
+                wt_ext_get: *lib.get::<WTExtFn>(b"WTExtGet").ok()?,
+                wt_ext_set: *lib.get::<WTExtFn>(b"WTExtSet").ok()?,
+            })
+        }
+    }
+
+    /// Combined packet mask of the three pad extensions. `PacketExtRaw` has a
+    /// fixed layout, so all three must be present.
+    fn extension_mask(&self) -> Option<u32> {
+        let mut masks = [None; 3];
+        for index in 0..64 {
+            let mut tag: u32 = 0;
+            // SAFETY: EXT_TAG writes a UINT
+            let size =
+                unsafe { (self.wt_info_raw)(WTI_EXTENSIONS + index, EXT_TAG, cast_void!(tag)) };
+            if size == 0 {
+                break;
+            }
+            let Some(slot) = [WTX_EXPKEYS2, WTX_TOUCHRING, WTX_TOUCHSTRIP]
+                .iter()
+                .position(|&wanted| wanted == tag)
+            else {
+                continue;
+            };
+            let mut mask: u32 = 0;
+            // SAFETY: EXT_MASK writes a WTPKT (DWORD)
+            unsafe {
+                (self.wt_info_raw)(WTI_EXTENSIONS + index, EXT_MASK, cast_void!(mask));
+            }
+            masks[slot] = Some(mask);
+        }
+        Some(masks[0]? | masks[1]? | masks[2]?)
+    }
+
+    fn property(&self, context: *mut HCTX, tag: u32, control: u8, function: u8, id: u16) -> u32 {
+        let mut property = ExtPropertyRaw::new(0, control, function, id, 0);
+        // SAFETY: `property` has room for the 4-byte values queried here
+        let ok = unsafe { (self.wt_ext_get)(context, tag, cast_void!(property)) };
+        if ok != 0 {
+            property.value()
+        } else {
+            0
+        }
+    }
+
+    /// Take over (or hand back) every function of every pad control on the
+    /// first tablet, recording ring and strip ranges in `adapter`
+    fn set_overrides(
+        &self,
+        context: *mut HCTX,
+        enabled: bool,
+        mut adapter: Option<&mut WinTabPadAdapter>,
+    ) {
+        for tag in [WTX_EXPKEYS2, WTX_TOUCHRING, WTX_TOUCHSTRIP] {
+            let controls = self.property(context, tag, 0, 0, TABLET_PROPERTY_CONTROLCOUNT);
+            for control in 0..controls.min(u8::MAX as u32) as u8 {
+                let functions = self.property(context, tag, control, 0, TABLET_PROPERTY_FUNCCOUNT);
+                for function in 0..functions.min(u8::MAX as u32) as u8 {
+                    let mut property = ExtPropertyRaw::new(
+                        0,
+                        control,
+                        function,
+                        TABLET_PROPERTY_OVERRIDE,
+                        enabled as u32,
+                    );
+                    // SAFETY: `property` holds a BOOL value
+                    unsafe {
+                        (self.wt_ext_set)(context, tag, cast_void!(property));
+                    }
+                }
+                let kind = match tag {
+                    WTX_TOUCHRING => TabletDialKindV3::TouchRing,
+                    WTX_TOUCHSTRIP => TabletDialKindV3::TouchStrip,
+                    _ => continue,
+                };
+                if let Some(adapter) = adapter.as_deref_mut() {
+                    let min = self.property(context, tag, control, 0, TABLET_PROPERTY_MIN);
+                    let max = self.property(context, tag, control, 0, TABLET_PROPERTY_MAX);
+                    adapter.set_slider_range(kind, control as u32, min, max);
+                }
+            }
+        }
+    }
+}
+
+/// State shared with the context window's subclassed procedure
+#[cfg(target_os = "windows")]
+struct PadHook {
+    context: usize,
+    functions: PadFunctions,
+    adapter: WinTabPadAdapter,
+    events: Arc<InputEventQueue>,
+}
+
+#[cfg(target_os = "windows")]
+static PAD_HOOK: parking_lot::Mutex<Option<PadHook>> = parking_lot::Mutex::new(None);
+/// Original window procedure; kept after removal for messages already in flight
+#[cfg(target_os = "windows")]
+static PAD_PREVIOUS_PROC: AtomicIsize = AtomicIsize::new(0);
+
+#[cfg(target_os = "windows")]
+unsafe extern "system" fn pad_window_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    if msg == WT_PACKETEXT || msg == WT_PACKET {
+        let mut hook = PAD_HOOK.lock();
+        if let Some(hook) = hook
+            .as_mut()
+            .filter(|hook| hook.context == lparam.0 as usize)
+        {
+            if msg == WT_PACKETEXT {
+                let mut packet = PacketExtRaw::default();
+                // SAFETY: the context mask holds exactly the three pad extensions
+                let ok = (hook.functions.wt_packet)(
+                    hook.context as *mut HCTX,
+                    wparam.0 as u32,
+                    cast_void!(packet),
+                );
+                if ok != 0 {
+                    let mut out = Vec::new();
+                    hook.adapter
+                        .process_packet(&packet, super::current_time_us(), &mut out);
+                    for event in out {
+                        let _ = hook.events.enqueue_event(event);
+                    }
+                }
+            }
+            // `WT_PACKET` only announces pen packets the poll thread reads anyway
+            return LRESULT(0);
+        }
+    }
+    let previous: WNDPROC = std::mem::transmute(PAD_PREVIOUS_PROC.load(Ordering::SeqCst));
+    CallWindowProcW(previous, hwnd, msg, wparam, lparam)
+}
+
+/// Override the pad controls and subclass `hwnd` to receive their packets
+#[cfg(target_os = "windows")]
+fn install_pad_hook(
+    hwnd: HWND,
+    context: *mut HCTX,
+    functions: PadFunctions,
+    events: Arc<InputEventQueue>,
+) -> bool {
+    let mut adapter = WinTabPadAdapter::new("wintab_pad_0".to_string());
+    functions.set_overrides(context, true, Some(&mut adapter));
+    *PAD_HOOK.lock() = Some(PadHook {
+        context: context as usize,
+        functions,
+        adapter,
+        events,
+    });
+    // SAFETY: `hwnd` belongs to this process and `pad_window_proc` forwards every
+    // other message to the procedure it replaces
+    let previous =
+        unsafe { SetWindowLongPtrW(hwnd, GWLP_WNDPROC, pad_window_proc as usize as isize) };
+    if previous == 0 {
+        functions.set_overrides(context, false, None);
+        *PAD_HOOK.lock() = None;
+        return false;
+    }
+    PAD_PREVIOUS_PROC.store(previous, Ordering::SeqCst);
+    true
+}
+
+/// Restore the window procedure, hand the controls back and release held keys
+#[cfg(target_os = "windows")]
+fn remove_pad_hook(hwnd: HWND) {
+    let previous = PAD_PREVIOUS_PROC.load(Ordering::SeqCst);
+    // SAFETY: restores the procedure saved by `install_pad_hook`
+    unsafe {
+        SetWindowLongPtrW(hwnd, GWLP_WNDPROC, previous);
+    }
+    let Some(mut hook) = PAD_HOOK.lock().take() else {
+        return;
+    };
+    hook.functions
+        .set_overrides(hook.context as *mut HCTX, false, None);
+    let mut out = Vec::new();
+    hook.adapter.release_all(super::current_time_us(), &mut out);
+    for event in out {
+        let _ = hook.events.enqueue_event(event);
+    }
 }
 
 impl Default for WinTabBackend {
@@ -244,6 +538,7 @@ impl TabletBackend for WinTabBackend {
             Self::query_device_info(wt_info).ok_or_else(|| "No tablet device found".to_string())?;
 
         self.pressure_max = pressure_range.1 as f32;
+        self.z_range = Self::query_z_range(wt_info);
 
         self.info = Some(TabletInfo {
             name,
@@ -284,17 +579,24 @@ impl TabletBackend for WinTabBackend {
         let events = self.events.clone();
         let polling_interval_ms = 1000 / self.config.polling_rate_hz as u64;
         let pressure_max = self.pressure_max;
+        let z_range = self.z_range;
+        let mapping = self.config.mapping;
         // Pressure curve shaping is handled by shared runtime config paths.
         let hwnd_value = self.hwnd; // Copy the stored HWND
 
         let handle = thread::spawn(move || {
             // Load WinTab functions in this thread
-            let Ok((_lib, wt_info, wt_open, wt_close, wt_packets_get, wt_enable, wt_overlap)) =
+            let Ok((lib, wt_info, wt_open, wt_close, wt_packets_get, wt_enable, wt_overlap)) =
                 WinTabBackend::load_wintab_functions()
             else {
                 tracing::error!("[WinTab] Failed to load functions in poll thread");
                 return;
             };
+            let pad = PadFunctions::load(&lib)
+                .and_then(|functions| Some((functions, functions.extension_mask()?)));
+            if pad.is_none() {
+                tracing::info!("[WinTab] Driver lacks pad extensions; express keys disabled");
+            }
 
             // Get default context (DEFCONTEXT for digitizer-relative coordinates)
             let mut log_context = LOGCONTEXT::default();
@@ -328,6 +630,13 @@ impl TabletBackend for WinTabBackend {
             log_context.lcPktMode = WTPKT::empty(); // All fields in absolute mode
             log_context.lcMoveMask =
                 WTPKT::X | WTPKT::Y | WTPKT::NORMAL_PRESSURE | WTPKT::ORIENTATION | WTPKT::ROTATION;
+            if let Some((_, pad_mask)) = pad {
+                // Extension bits only shape `WT_PACKETEXT`; `Packet` is unaffected.
+                // Pad packets are only posted to contexts that send messages.
+                log_context.lcPktData =
+                    WTPKT::from_bits_retain(log_context.lcPktData.bits() | pad_mask);
+                log_context.lcOptions |= CXO::MESSAGES;
+            }
 
             // Flip Y axis (tablet Y is inverted by default)
             let default_y_extent = log_context.lcOutExtXYZ.y;
@@ -356,6 +665,13 @@ impl TabletBackend for WinTabBackend {
             }
             tracing::info!("[WinTab] Context enabled and set to top of overlap order");
 
+            let pad_hooked = pad.is_some_and(|(functions, _)| {
+                install_pad_hook(hwnd_val, context_ptr, functions, events.clone())
+            });
+            if pad.is_some() {
+                tracing::info!("[WinTab] Pad controls hooked: {}", pad_hooked);
+            }
+
             tracing::info!(
                 "[WinTab] Polling thread started at {}Hz, context opened successfully",
                 1000 / polling_interval_ms
@@ -419,15 +735,37 @@ impl TabletBackend for WinTabBackend {
                 virtual_height
             );
 
+            let selected_monitor = mapping
+                .monitor
+                .and_then(|index| WinTabBackend::monitor_rects().get(index as usize).copied());
+            let (output_left, output_top, output_width, output_height) = match selected_monitor {
+                Some(rect) => (
+                    rect.left,
+                    rect.top,
+                    (rect.right - rect.left).max(1) as f32,
+                    (rect.bottom - rect.top).max(1) as f32,
+                ),
+                None => (virtual_left, virtual_top, virtual_width, virtual_height),
+            };
+            tracing::info!(
+                "[WinTab] Output area: origin=({}, {}), size={}x{}, mapping={:?}",
+                output_left,
+                output_top,
+                output_width,
+                output_height,
+                mapping
+            );
+
             let mapper = CoordinateMapper::with_axis_range(
-                virtual_width,
-                virtual_height,
+                output_width,
+                output_height,
                 map_x_min,
                 map_x_max,
                 map_y_min,
                 map_y_max,
                 false,
-            );
+            )
+            .with_mapping(&mapping);
             tracing::info!(
                 "[WinTab] Context map in_org=({}, {}), in_ext=({}, {}), out_org=({}, {}), out_ext=({}, {})",
                 log_context.lcInOrgXYZ.x,
@@ -454,6 +792,7 @@ impl TabletBackend for WinTabBackend {
             let mut adapters: HashMap<u32, WinTabAdapter> = HashMap::new();
 
             let mut was_in_proximity = false;
+            let mut barrel = BarrelButtonTracker::new();
             let mut loop_count: u64 = 0;
 
             while running.load(Ordering::SeqCst) {
@@ -498,11 +837,25 @@ impl TabletBackend for WinTabBackend {
 
                         if in_proximity && !was_in_proximity {
                             let _ = events.enqueue_event(TabletEventV3::ProximityEnter);
-                        } else if !in_proximity && was_in_proximity {
+                        }
+                        let pointer_id = packet.pkCursor;
+                        // pkButtons bit 0 is the tip, bits 1-2 the barrel buttons
+                        let barrel_mask = if in_proximity {
+                            (packet.pkButtons.0 >> 1) & 0b11
+                        } else {
+                            0
+                        };
+                        for button in barrel.update(
+                            barrel_mask,
+                            &format!("wintab_cursor_{}", pointer_id),
+                            super::current_time_us(),
+                        ) {
+                            let _ = events.enqueue_event(TabletEventV3::Button(button));
+                        }
+                        if !in_proximity && was_in_proximity {
                             let _ = events.enqueue_event(TabletEventV3::ProximityLeave);
                         }
                         was_in_proximity = in_proximity;
-                        let pointer_id = packet.pkCursor;
                         let adapter = adapters.entry(pointer_id).or_insert_with(|| {
                             WinTabAdapter::new(
                                 pointer_id,
@@ -510,12 +863,13 @@ impl TabletBackend for WinTabBackend {
                                 pressure_max,
                                 mapper,
                             )
+                            .with_distance_range(z_range)
                         });
                         if let Some(mut sample) =
                             adapter.convert_packet(packet, super::current_time_us())
                         {
-                            let screen_x = sample.x_px + virtual_left as f32;
-                            let screen_y = sample.y_px + virtual_top as f32;
+                            let screen_x = sample.x_px + output_left as f32;
+                            let screen_y = sample.y_px + output_top as f32;
                             let mut client_point = POINT {
                                 x: screen_x.round() as i32,
                                 y: screen_y.round() as i32,
@@ -562,6 +916,9 @@ impl TabletBackend for WinTabBackend {
             }
 
             // Cleanup
+            if pad_hooked {
+                remove_pad_hook(hwnd_val);
+            }
             unsafe {
                 wt_close(context_ptr);
             }
@@ -602,6 +959,10 @@ impl TabletBackend for WinTabBackend {
         self.events.drain_into(events, super::current_time_us)
     }
 
+    fn take_hover(&mut self) -> Option<NativeTabletEventV3> {
+        self.events.take_hover()
+    }
+
     fn queue_metrics(&self) -> InputQueueMetrics {
         self.events.metrics_snapshot()
     }
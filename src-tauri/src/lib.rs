@@ -15,7 +15,7 @@ pub mod file;
 pub mod input;
 pub mod pattern;
 
-use tauri::http::{Response, StatusCode};
+use tauri::http::{Request, Response, StatusCode};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 /// Initialize the application
@@ -108,6 +108,102 @@ fn build_not_found() -> Response<Vec<u8>> {
         .expect("failed to build response")
 }
 
+/// Serve a `project://` request other than `/brush-preview/`
+fn project_response(request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let path = request.uri().path();
+    tracing::trace!("project:// request: {}", path);
+
+    // Parse path: /layer/{id} or /thumbnail or /brush/{id} or /pattern/{id}
+    if let Some(layer_id) = path.strip_prefix("/layer/") {
+        tracing::trace!("Looking up layer in cache: {}", layer_id);
+        if let Some(cached) = file::get_cached_layer(layer_id) {
+            tracing::trace!(
+                "Cache HIT: {} ({} bytes, type: {})",
+                layer_id,
+                cached.data.len(),
+                cached.mime_type
+            );
+            // Use special response for raw RGBA data (uncompressed or LZ4)
+            if cached.mime_type == "image/x-rgba" {
+                let width = cached.width.unwrap_or(0);
+                let height = cached.height.unwrap_or(0);
+                return build_rgba_response(cached.data, width, height);
+            }
+            if cached.mime_type == "image/x-rgba-lz4" {
+                let width = cached.width.unwrap_or(0);
+                let height = cached.height.unwrap_or(0);
+                return build_rgba_lz4_response(cached.data, width, height);
+            }
+            return build_response(cached.data, cached.mime_type);
+        } else {
+            tracing::warn!("Cache MISS: {}", layer_id);
+        }
+    } else if let Some(brush_id) = path.strip_prefix("/brush/") {
+        // Brush texture endpoint: /brush/{id}
+        tracing::trace!("Looking up brush in cache: {}", brush_id);
+        if let Some(cached) = brush::get_cached_brush(brush_id) {
+            tracing::trace!(
+                "Brush cache HIT: {} ({} bytes, {}x{})",
+                brush_id,
+                cached.data.len(),
+                cached.width,
+                cached.height
+            );
+            return build_gray_lz4_response(cached.data, cached.width, cached.height);
+        } else {
+            tracing::warn!("Brush cache MISS: {}", brush_id);
+        }
+    } else if let Some(pattern_id) = path.strip_prefix("/pattern/") {
+        // Pattern texture endpoint: /pattern/{id}
+        tracing::trace!("Looking up pattern in cache: {}", pattern_id);
+        let thumb = request.uri().query().and_then(|q| {
+            q.split('&').find_map(|part| {
+                let v = part.strip_prefix("thumb=")?;
+                v.parse::<u32>().ok()
+            })
+        });
+
+        if let Some(size) = thumb {
+            if let Some(cached) = brush::get_cached_pattern_thumb(pattern_id, size) {
+                tracing::trace!(
+                    "Pattern thumb cache HIT: {} ({} bytes, {}x{})",
+                    pattern_id,
+                    cached.data.len(),
+                    cached.width,
+                    cached.height
+                );
+                return build_rgba_lz4_response(cached.data, cached.width, cached.height);
+            }
+        } else if let Some(cached) = brush::get_cached_pattern(pattern_id) {
+            tracing::trace!(
+                "Pattern cache HIT: {} ({} bytes, {}x{})",
+                pattern_id,
+                cached.data.len(),
+                cached.width,
+                cached.height
+            );
+            return build_rgba_lz4_response(cached.data, cached.width, cached.height);
+        }
+
+        tracing::warn!("Pattern cache MISS: {} (thumb={:?})", pattern_id, thumb);
+    } else if path == "/thumbnail" {
+        if let Some(cached) = file::get_cached_thumbnail() {
+            return build_response(cached.data, cached.mime_type);
+        }
+    }
+
+    build_not_found()
+}
+
+/// Render a preset preview stroke: `project://brush-preview/{id}`
+fn brush_preview_response(preset_id: &str) -> Response<Vec<u8>> {
+    if let Some(preview) = brush::get_brush_preview(preset_id) {
+        return build_rgba_lz4_response(preview.data, preview.width, preview.height);
+    }
+    tracing::warn!("Brush preview MISS: {}", preset_id);
+    build_not_found()
+}
+
 /// Run the Tauri application
 ///
 /// # Panics
@@ -123,90 +219,15 @@ pub fn run() {
         // Register custom protocol for serving layer images
         // Usage: <img src="project://layer/{layer_id}" />
         //        <img src="project://thumbnail" />
-        .register_uri_scheme_protocol("project", |_ctx, request| {
-            let path = request.uri().path();
-            tracing::trace!("project:// request: {}", path);
-
-            // Parse path: /layer/{id} or /thumbnail or /brush/{id}
-            if let Some(layer_id) = path.strip_prefix("/layer/") {
-                tracing::trace!("Looking up layer in cache: {}", layer_id);
-                if let Some(cached) = file::get_cached_layer(layer_id) {
-                    tracing::trace!(
-                        "Cache HIT: {} ({} bytes, type: {})",
-                        layer_id,
-                        cached.data.len(),
-                        cached.mime_type
-                    );
-                    // Use special response for raw RGBA data (uncompressed or LZ4)
-                    if cached.mime_type == "image/x-rgba" {
-                        let width = cached.width.unwrap_or(0);
-                        let height = cached.height.unwrap_or(0);
-                        return build_rgba_response(cached.data, width, height);
-                    }
-                    if cached.mime_type == "image/x-rgba-lz4" {
-                        let width = cached.width.unwrap_or(0);
-                        let height = cached.height.unwrap_or(0);
-                        return build_rgba_lz4_response(cached.data, width, height);
-                    }
-                    return build_response(cached.data, cached.mime_type);
-                } else {
-                    tracing::warn!("Cache MISS: {}", layer_id);
-                }
-            } else if let Some(brush_id) = path.strip_prefix("/brush/") {
-                // Brush texture endpoint: /brush/{id}
-                tracing::trace!("Looking up brush in cache: {}", brush_id);
-                if let Some(cached) = brush::get_cached_brush(brush_id) {
-                    tracing::trace!(
-                        "Brush cache HIT: {} ({} bytes, {}x{})",
-                        brush_id,
-                        cached.data.len(),
-                        cached.width,
-                        cached.height
-                    );
-                    return build_gray_lz4_response(cached.data, cached.width, cached.height);
-                } else {
-                    tracing::warn!("Brush cache MISS: {}", brush_id);
-                }
-            } else if let Some(pattern_id) = path.strip_prefix("/pattern/") {
-                // Pattern texture endpoint: /pattern/{id}
-                tracing::trace!("Looking up pattern in cache: {}", pattern_id);
-                let thumb = request.uri().query().and_then(|q| {
-                    q.split('&').find_map(|part| {
-                        let v = part.strip_prefix("thumb=")?;
-                        v.parse::<u32>().ok()
-                    })
-                });
-
-                if let Some(size) = thumb {
-                    if let Some(cached) = brush::get_cached_pattern_thumb(pattern_id, size) {
-                        tracing::trace!(
-                            "Pattern thumb cache HIT: {} ({} bytes, {}x{})",
-                            pattern_id,
-                            cached.data.len(),
-                            cached.width,
-                            cached.height
-                        );
-                        return build_rgba_lz4_response(cached.data, cached.width, cached.height);
-                    }
-                } else if let Some(cached) = brush::get_cached_pattern(pattern_id) {
-                    tracing::trace!(
-                        "Pattern cache HIT: {} ({} bytes, {}x{})",
-                        pattern_id,
-                        cached.data.len(),
-                        cached.width,
-                        cached.height
-                    );
-                    return build_rgba_lz4_response(cached.data, cached.width, cached.height);
-                }
-
-                tracing::warn!("Pattern cache MISS: {} (thumb={:?})", pattern_id, thumb);
-            } else if path == "/thumbnail" {
-                if let Some(cached) = file::get_cached_thumbnail() {
-                    return build_response(cached.data, cached.mime_type);
-                }
+        .register_asynchronous_uri_scheme_protocol("project", |_ctx, request, responder| {
+            // Preview strokes are rendered on a miss; keep that off the protocol
+            // thread so a grid of previews does not stall other requests
+            if let Some(preset_id) = request.uri().path().strip_prefix("/brush-preview/") {
+                let preset_id = preset_id.to_string();
+                rayon::spawn(move || responder.respond(brush_preview_response(&preset_id)));
+                return;
             }
-
-            build_not_found()
+            responder.respond(project_response(&request));
         })
         .invoke_handler(tauri::generate_handler![
             commands::create_document,
@@ -221,10 +242,20 @@ pub fn run() {
             commands::get_tablet_status,
             commands::set_wintab_trace_enabled,
             commands::get_wintab_trace_enabled,
+            commands::start_input_recording,
+            commands::stop_input_recording,
+            commands::get_tablet_tool_profiles,
+            commands::set_tablet_tool_profile,
+            commands::set_tablet_cursor_pose_config,
+            commands::get_tablet_bindings,
+            commands::set_tablet_binding,
+            commands::get_tablet_mapping,
+            commands::set_tablet_mapping,
             commands::push_pointer_event,
             commands::stamp_soft_dab,
             commands::import_abr_file,
             commands::import_abr_to_brush_library,
+            commands::import_brush_packs,
             // Brush Library
             commands::get_brush_library,
             commands::rename_brush_preset,
@@ -234,6 +265,19 @@ pub fn run() {
             commands::delete_brush_group,
             commands::save_brush_preset,
             commands::save_brush_preset_as,
+            commands::set_brush_preset_tags,
+            commands::set_brush_preset_favorite,
+            commands::record_brush_preset_use,
+            commands::search_brush_presets,
+            commands::list_brush_preset_revisions,
+            commands::get_brush_preset_revision,
+            commands::diff_brush_preset_revisions,
+            commands::revert_brush_preset,
+            commands::export_brush_bundle,
+            commands::import_brush_bundle,
+            commands::sync_libraries_with_folder,
+            commands::get_library_sync_state,
+            commands::verify_libraries,
             // File operations
             commands::save_project,
             commands::save_project_v2,
@@ -246,6 +290,12 @@ pub fn run() {
             commands::get_patterns,
             commands::import_pat_file,
             commands::add_pattern_from_brush,
+            commands::add_pattern_from_image,
+            commands::add_pattern_from_region,
+            commands::get_pattern_offset_preview,
+            commands::set_pattern_tags,
+            commands::record_pattern_use,
+            commands::search_patterns,
             commands::delete_pattern,
             commands::rename_pattern,
             commands::move_pattern_to_group,
@@ -0,0 +1,165 @@
+//! Pattern pixels from image files and canvas regions.
+//!
+//! Patterns are opaque like Photoshop's, so transparent pixels are flattened
+//! onto white before an optional grayscale conversion and edge blending.
+
+use std::path::Path;
+
+use super::seamless::blend_edges;
+use super::types::{PatternCreateOptions, PatternMode, PatternRect};
+
+/// Largest accepted pattern side, in pixels
+pub const MAX_PATTERN_SIZE: u32 = 4096;
+
+/// Decoded RGBA pixels ready for the pattern library
+#[derive(Debug, Clone)]
+pub struct PatternPixels {
+    pub rgba: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub mode: PatternMode,
+}
+
+/// Decode a PNG, JPEG, WebP, TIFF (or other supported) file to RGBA
+pub fn decode_image_file(path: &Path) -> Result<(Vec<u8>, u32, u32), String> {
+    let bytes = std::fs::read(path)
+        .map_err(|e| format!("Failed to read image {}: {}", path.display(), e))?;
+    decode_image(&bytes).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Decode encoded image bytes to RGBA
+pub fn decode_image(bytes: &[u8]) -> Result<(Vec<u8>, u32, u32), String> {
+    let image = image::load_from_memory(bytes)
+        .map_err(|e| format!("Failed to decode image: {}", e))?
+        .to_rgba8();
+    let (width, height) = image.dimensions();
+    Ok((image.into_raw(), width, height))
+}
+
+/// Copy `rect` out of an RGBA image, clipped to the image bounds
+pub fn crop_rgba(
+    rgba: &[u8],
+    width: u32,
+    height: u32,
+    rect: PatternRect,
+) -> Result<(Vec<u8>, u32, u32), String> {
+    let x0 = rect.x.min(width);
+    let y0 = rect.y.min(height);
+    let x1 = rect.x.saturating_add(rect.width).min(width);
+    let y1 = rect.y.saturating_add(rect.height).min(height);
+    if x1 <= x0 || y1 <= y0 {
+        return Err(format!(
+            "Region {}x{} at ({}, {}) is outside the {}x{} layer",
+            rect.width, rect.height, rect.x, rect.y, width, height
+        ));
+    }
+
+    let row_bytes = (x1 - x0) as usize * 4;
+    let mut out = Vec::with_capacity(row_bytes * (y1 - y0) as usize);
+    for y in y0..y1 {
+        let start = (y as usize * width as usize + x0 as usize) * 4;
+        out.extend_from_slice(&rgba[start..start + row_bytes]);
+    }
+    Ok((out, x1 - x0, y1 - y0))
+}
+
+/// Apply creation options: size check, flattening, edge blending and grayscale
+pub fn prepare_pixels(
+    mut rgba: Vec<u8>,
+    mut width: u32,
+    mut height: u32,
+    options: &PatternCreateOptions,
+) -> Result<PatternPixels, String> {
+    if width == 0 || height == 0 {
+        return Err("Pattern image is empty".to_string());
+    }
+    if width > MAX_PATTERN_SIZE || height > MAX_PATTERN_SIZE {
+        return Err(format!(
+            "Pattern is {}x{}; the maximum is {}x{}",
+            width, height, MAX_PATTERN_SIZE, MAX_PATTERN_SIZE
+        ));
+    }
+
+    flatten_onto_white(&mut rgba);
+    if let Some(band) = options.blend_edges.filter(|band| *band > 0) {
+        (rgba, width, height) = blend_edges(&rgba, width, height, band);
+    }
+
+    let mode = if options.grayscale {
+        to_grayscale(&mut rgba);
+        PatternMode::Grayscale
+    } else {
+        PatternMode::RGB
+    };
+
+    Ok(PatternPixels {
+        rgba,
+        width,
+        height,
+        mode,
+    })
+}
+
+fn flatten_onto_white(rgba: &mut [u8]) {
+    for pixel in rgba.chunks_exact_mut(4) {
+        let alpha = pixel[3] as u32;
+        for channel in &mut pixel[..3] {
+            *channel = ((*channel as u32 * alpha + 255 * (255 - alpha) + 127) / 255) as u8;
+        }
+        pixel[3] = 255;
+    }
+}
+
+/// Rec. 601 luma, written back to all three channels
+fn to_grayscale(rgba: &mut [u8]) {
+    for pixel in rgba.chunks_exact_mut(4) {
+        let luma =
+            (299 * pixel[0] as u32 + 587 * pixel[1] as u32 + 114 * pixel[2] as u32 + 500) / 1000;
+        pixel[..3].fill(luma as u8);
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crop_clips_to_bounds_and_grayscale_flattens_alpha() {
+        // 3x2: red, green, transparent / blue, white, black
+        let rgba = vec![
+            255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 0, 0, //
+            0, 0, 255, 255, 255, 255, 255, 255, 0, 0, 0, 255,
+        ];
+        let (cropped, width, height) = crop_rgba(
+            &rgba,
+            3,
+            2,
+            PatternRect {
+                x: 1,
+                y: 0,
+                width: 10,
+                height: 1,
+            },
+        )
+        .unwrap();
+        assert_eq!((width, height), (2, 1));
+        assert_eq!(cropped, vec![0, 255, 0, 255, 0, 0, 0, 0]);
+
+        let options = PatternCreateOptions {
+            grayscale: true,
+            ..Default::default()
+        };
+        let pixels = prepare_pixels(cropped, width, height, &options).unwrap();
+        assert_eq!(pixels.mode, PatternMode::Grayscale);
+        assert_eq!(pixels.rgba, vec![150, 150, 150, 255, 255, 255, 255, 255]);
+
+        let outside = PatternRect {
+            x: 5,
+            y: 0,
+            width: 2,
+            height: 2,
+        };
+        assert!(crop_rgba(&rgba, 3, 2, outside).is_err());
+    }
+}
@@ -11,10 +11,20 @@ use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
+use super::create;
 use super::pat::{parse_pat_file, ParsedPattern};
-use super::types::{AddPatternFromBrushResult, ImportResult, PatternMode, PatternResource};
+use super::search::{self, PatternSearchFilters};
+use super::types::{
+    AddPatternFromBrushResult, ImportResult, PatternCreateOptions, PatternMode, PatternRect,
+    PatternResource,
+};
 use crate::app_meta::APP_CONFIG_DIR_NAME;
+use crate::brush::cache::is_safe_blob_id;
+use crate::brush::index_file::{self, IndexLoad, UsageEntry, UsageIndex};
+use crate::brush::integrity::repair_group_lists;
+use crate::brush::library::normalize_tags;
 use crate::brush::pattern_cache;
+use crate::input::current_time_ms;
 
 /// Pattern library index (persisted to disk)
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -25,6 +35,18 @@ pub struct PatternIndex {
     pub groups: HashMap<String, Vec<String>>,
 }
 
+const INDEX_FILE_NAME: &str = "index.json";
+const INDEX_SCHEMA_VERSION: u32 = 1;
+
+/// Group and source for patterns rebuilt from the cache after index corruption
+const RECOVERED_GROUP: &str = "Recovered";
+const RECOVERED_SOURCE: &str = "recovered";
+
+/// Default groups for user-made patterns
+const IMAGE_GROUP: &str = "From Images";
+const CANVAS_GROUP: &str = "From Canvas";
+const CANVAS_SOURCE: &str = "canvas";
+
 /// Global pattern library instance
 static LIBRARY: RwLock<Option<PatternLibrary>> = RwLock::new(None);
 
@@ -35,8 +57,13 @@ pub struct PatternLibrary {
     index: PatternIndex,
     /// Library directory path
     library_dir: PathBuf,
+    /// Pick stats, saved to their own side file
+    usage: UsageIndex,
     /// Dirty flag (needs save)
     dirty: bool,
+    usage_dirty: bool,
+    /// Why saving is refused: the index failed to read or is from a newer version
+    read_only: Option<String>,
 }
 
 impl PatternLibrary {
@@ -45,51 +72,119 @@ impl PatternLibrary {
         Self {
             index: PatternIndex::default(),
             library_dir,
+            usage: UsageIndex::default(),
             dirty: false,
+            usage_dirty: false,
+            read_only: None,
         }
     }
 
     /// Load library from disk
     pub fn load(library_dir: PathBuf) -> Self {
-        let index_path = library_dir.join("index.json");
-        let index = if index_path.exists() {
-            match std::fs::read_to_string(&index_path) {
-                Ok(json) => serde_json::from_str(&json).unwrap_or_default(),
-                Err(e) => {
-                    tracing::warn!("Failed to load pattern index: {}", e);
-                    PatternIndex::default()
-                }
+        Self::load_with_cache_dir(library_dir, &pattern_cache::get_pattern_cache_dir())
+    }
+
+    /// Load library from disk; a corrupt index is quarantined and rebuilt from
+    /// the patterns stored in `cache_dir`. An unreadable or newer index is rebuilt
+    /// the same way but left on disk, and the library stays read-only.
+    pub fn load_with_cache_dir(library_dir: PathBuf, cache_dir: &Path) -> Self {
+        let mut read_only = None;
+        let index_path = library_dir.join(INDEX_FILE_NAME);
+        let (mut index, dirty) = match index_file::load_index::<PatternIndex>(
+            &index_path,
+            INDEX_SCHEMA_VERSION,
+            &[migrate_unversioned],
+        ) {
+            IndexLoad::Loaded {
+                index,
+                migrated_from,
+            } => (index, migrated_from.is_some()),
+            IndexLoad::Missing => (PatternIndex::default(), false),
+            IndexLoad::Corrupt { error, quarantined } => {
+                let rebuilt = rebuild_index(cache_dir);
+                tracing::warn!(
+                    "Pattern index was corrupt ({}), moved to {:?}; rebuilt {} patterns",
+                    error,
+                    quarantined,
+                    rebuilt.patterns.len()
+                );
+                (rebuilt, true)
+            }
+            IndexLoad::Unavailable { error } => {
+                let rebuilt = rebuild_index(cache_dir);
+                tracing::warn!(
+                    "Pattern index is unavailable ({}); using {} rebuilt patterns read-only",
+                    error,
+                    rebuilt.patterns.len()
+                );
+                read_only = Some(error);
+                (rebuilt, false)
             }
-        } else {
-            PatternIndex::default()
         };
 
+        // Pick times in the side file are newer than the copies in the index
+        let mut usage = index_file::load_usage(&library_dir).unwrap_or_else(|error| {
+            read_only = Some(error);
+            UsageIndex::default()
+        });
+        let usage_count = usage.entries.len();
+        usage
+            .entries
+            .retain(|id, entry| match index.patterns.get_mut(id) {
+                Some(pattern) => {
+                    pattern.last_used_ms = entry.last_used_ms;
+                    true
+                }
+                None => false,
+            });
+        let usage_dirty = usage.entries.len() != usage_count;
+
         tracing::info!(
             "Loaded pattern library: {} patterns, {} groups",
             index.patterns.len(),
             index.groups.len()
         );
 
-        Self {
+        let mut library = Self {
             index,
             library_dir,
-            dirty: false,
+            usage,
+            dirty,
+            usage_dirty,
+            read_only,
+        };
+        // Persist migrations and rebuilds right away
+        if let Err(e) = library.save() {
+            tracing::warn!("Failed to save pattern index after load: {}", e);
         }
+        library
     }
 
     /// Save library index to disk
     pub fn save(&mut self) -> std::io::Result<()> {
-        if !self.dirty {
+        if !self.dirty && !self.usage_dirty {
             return Ok(());
         }
+        if let Some(reason) = &self.read_only {
+            return Err(std::io::Error::other(format!(
+                "Pattern library is read-only: {}",
+                reason
+            )));
+        }
 
-        std::fs::create_dir_all(&self.library_dir)?;
-        let index_path = self.library_dir.join("index.json");
-        let json = serde_json::to_string_pretty(&self.index)?;
-        std::fs::write(&index_path, json)?;
-        self.dirty = false;
-
-        tracing::debug!("Saved pattern library index");
+        if self.dirty {
+            index_file::save_index(
+                &self.library_dir.join(INDEX_FILE_NAME),
+                INDEX_SCHEMA_VERSION,
+                &self.index,
+            )?;
+            self.dirty = false;
+            tracing::debug!("Saved pattern library index");
+        }
+        if self.usage_dirty {
+            index_file::save_usage(&self.library_dir, &self.usage)?;
+            self.usage_dirty = false;
+        }
         Ok(())
     }
 
@@ -121,6 +216,23 @@ impl PatternLibrary {
         self.index.groups.keys().cloned().collect()
     }
 
+    /// Group name -> pattern IDs, as stored (may list missing patterns)
+    pub(crate) fn groups(&self) -> &HashMap<String, Vec<String>> {
+        &self.index.groups
+    }
+
+    /// Drop group entries for missing patterns and list every pattern in its group
+    pub(crate) fn rebuild_groups(&mut self) {
+        repair_group_lists(
+            &mut self.index.groups,
+            self.index
+                .patterns
+                .iter()
+                .map(|(id, pattern)| (id.as_str(), pattern.group.as_deref())),
+        );
+        self.dirty = true;
+    }
+
     /// Import patterns from a .pat file
     pub fn import_pat_file(&mut self, path: &Path) -> Result<ImportResult, String> {
         let patterns = parse_pat_file(path).map_err(|e| e.to_string())?;
@@ -132,13 +244,22 @@ impl PatternLibrary {
             .unwrap_or("Imported")
             .to_string();
 
-        let source = path.to_string_lossy().to_string();
+        self.import_parsed_patterns(patterns, &path.to_string_lossy(), &group_name)
+    }
+
+    /// Add patterns parsed from a .pat file to `group_name`, skipping duplicates
+    pub fn import_parsed_patterns(
+        &mut self,
+        patterns: Vec<ParsedPattern>,
+        source: &str,
+        group_name: &str,
+    ) -> Result<ImportResult, String> {
         let mut imported_count = 0;
         let mut skipped_count = 0;
         let mut pattern_ids = Vec::new();
 
         for parsed in patterns {
-            match self.add_parsed_pattern(parsed, &source, Some(group_name.clone())) {
+            match self.add_parsed_pattern(parsed, source, Some(group_name.to_string())) {
                 Ok(id) => {
                     pattern_ids.push(id);
                     imported_count += 1;
@@ -155,6 +276,7 @@ impl PatternLibrary {
 
         self.dirty = true;
         let _ = self.save();
+        pattern_cache::spawn_thumbnail_generation(pattern_ids.clone());
 
         Ok(ImportResult {
             imported_count,
@@ -183,8 +305,8 @@ impl PatternLibrary {
             }
         }
 
-        // Generate ID
-        let id = if parsed.id.is_empty() {
+        // Generate ID; file-supplied IDs name cache files, so only plain tokens are kept
+        let id = if !is_safe_blob_id(&parsed.id) {
             content_hash[..16].to_string()
         } else {
             parsed.id.clone()
@@ -210,6 +332,8 @@ impl PatternLibrary {
             mode: parsed.mode,
             source: source.to_string(),
             group: group.clone(),
+            tags: Vec::new(),
+            last_used_ms: None,
         };
 
         // Add to index
@@ -254,8 +378,11 @@ impl PatternLibrary {
             }
         }
 
-        // Use brush ID as pattern ID
-        let base_id = format!("brush_{}", brush_id);
+        // Use brush ID as pattern ID when it is a plain token
+        let mut base_id = format!("brush_{}", brush_id);
+        if !is_safe_blob_id(&base_id) {
+            base_id = content_hash[..16].to_string();
+        }
         let mut id = base_id.clone();
         if let Some(existing) = self.index.patterns.get(&id) {
             if existing.content_hash != content_hash {
@@ -288,6 +415,8 @@ impl PatternLibrary {
             mode,
             source: "user-added".to_string(),
             group: Some("From Brushes".to_string()),
+            tags: Vec::new(),
+            last_used_ms: None,
         };
 
         self.index.patterns.insert(id.clone(), resource.clone());
@@ -299,6 +428,7 @@ impl PatternLibrary {
 
         self.dirty = true;
         let _ = self.save();
+        pattern_cache::spawn_thumbnail_generation(vec![resource.id.clone()]);
 
         Ok(AddPatternFromBrushResult {
             added: true,
@@ -306,6 +436,175 @@ impl PatternLibrary {
         })
     }
 
+    /// Import a pattern shipped with its pixels (e.g. from a brush bundle).
+    /// Returns the library ID and whether it was added (false for a duplicate).
+    pub fn import_pattern(
+        &mut self,
+        resource: &PatternResource,
+        rgba_data: Vec<u8>,
+        source: &str,
+    ) -> Result<(String, bool), String> {
+        let expected_len = resource.width as usize * resource.height as usize * 4;
+        if resource.width == 0 || resource.height == 0 || rgba_data.len() != expected_len {
+            return Err(format!(
+                "Pattern {} has {} bytes, expected {}x{} RGBA",
+                resource.id,
+                rgba_data.len(),
+                resource.width,
+                resource.height
+            ));
+        }
+
+        let mut hasher = Sha256::new();
+        hasher.update(&rgba_data);
+        let content_hash = hex::encode(hasher.finalize());
+
+        if let Some(existing) = self
+            .index
+            .patterns
+            .values()
+            .find(|existing| existing.content_hash == content_hash)
+        {
+            return Ok((existing.id.clone(), false));
+        }
+
+        // Incoming IDs name cache files, so anything but a plain token is replaced
+        let base_id = if !is_safe_blob_id(resource.id.trim()) {
+            content_hash[..16].to_string()
+        } else {
+            resource.id.trim().to_string()
+        };
+        let mut id = base_id.clone();
+        let mut suffix: u32 = 1;
+        while self.index.patterns.contains_key(&id) {
+            id = format!("{}_{}", base_id, suffix);
+            suffix += 1;
+        }
+
+        pattern_cache::cache_pattern_rgba(
+            id.clone(),
+            rgba_data,
+            resource.width,
+            resource.height,
+            resource.name.clone(),
+            resource.mode.name().to_string(),
+        );
+
+        let imported = PatternResource {
+            id: id.clone(),
+            name: resource.name.clone(),
+            content_hash,
+            width: resource.width,
+            height: resource.height,
+            mode: resource.mode,
+            source: source.to_string(),
+            group: resource.group.clone(),
+            tags: resource.tags.clone(),
+            last_used_ms: None,
+        };
+        self.index.patterns.insert(id.clone(), imported);
+        if let Some(group_name) = &resource.group {
+            self.index
+                .groups
+                .entry(group_name.clone())
+                .or_default()
+                .push(id.clone());
+        }
+
+        self.dirty = true;
+        let _ = self.save();
+        pattern_cache::spawn_thumbnail_generation(vec![id.clone()]);
+
+        Ok((id, true))
+    }
+
+    /// Add a pattern from an image file (PNG, JPEG, WebP, TIFF...)
+    pub fn add_from_image(
+        &mut self,
+        path: &Path,
+        options: &PatternCreateOptions,
+    ) -> Result<AddPatternFromBrushResult, String> {
+        let (rgba, width, height) = create::decode_image_file(path)?;
+        let default_name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("Pattern")
+            .to_string();
+        let source = path.to_string_lossy().to_string();
+        self.add_created(
+            rgba,
+            width,
+            height,
+            options,
+            default_name,
+            &source,
+            IMAGE_GROUP,
+        )
+    }
+
+    /// Add a pattern from a region of an encoded layer image (e.g. a layer PNG
+    /// exported by the canvas). `rect` is in layer pixels and is clipped.
+    pub fn add_from_region(
+        &mut self,
+        layer_image: &[u8],
+        rect: PatternRect,
+        options: &PatternCreateOptions,
+    ) -> Result<AddPatternFromBrushResult, String> {
+        let (rgba, width, height) = create::decode_image(layer_image)?;
+        let (rgba, width, height) = create::crop_rgba(&rgba, width, height, rect)?;
+        self.add_created(
+            rgba,
+            width,
+            height,
+            options,
+            "Canvas Pattern".to_string(),
+            CANVAS_SOURCE,
+            CANVAS_GROUP,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn add_created(
+        &mut self,
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        options: &PatternCreateOptions,
+        default_name: String,
+        source: &str,
+        default_group: &str,
+    ) -> Result<AddPatternFromBrushResult, String> {
+        let pixels = create::prepare_pixels(rgba, width, height, options)?;
+        let non_empty = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(ToOwned::to_owned)
+        };
+        let resource = PatternResource {
+            id: String::new(),
+            name: non_empty(&options.name).unwrap_or(default_name),
+            content_hash: String::new(),
+            width: pixels.width,
+            height: pixels.height,
+            mode: pixels.mode,
+            source: source.to_string(),
+            group: Some(non_empty(&options.group).unwrap_or_else(|| default_group.to_string())),
+            tags: Vec::new(),
+            last_used_ms: None,
+        };
+
+        let (id, added) = self.import_pattern(&resource, pixels.rgba, source)?;
+        let pattern = self
+            .index
+            .patterns
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| format!("Pattern not found: {}", id))?;
+        Ok(AddPatternFromBrushResult { added, pattern })
+    }
+
     /// Delete a pattern
     pub fn delete_pattern(&mut self, id: &str) -> Result<(), String> {
         if self.index.patterns.remove(id).is_none() {
@@ -314,6 +613,9 @@ impl PatternLibrary {
 
         // Remove cached binary payloads (full + thumbnails)
         pattern_cache::delete_cached_pattern(id);
+        if self.usage.entries.remove(id).is_some() {
+            self.usage_dirty = true;
+        }
 
         // Remove from all groups
         for group in self.index.groups.values_mut() {
@@ -330,6 +632,81 @@ impl PatternLibrary {
         Ok(())
     }
 
+    /// Apply patterns merged from a sync replica. Added patterns must already
+    /// have their bitmaps in the pattern cache.
+    pub fn apply_sync_changes(
+        &mut self,
+        added: Vec<PatternResource>,
+        deletions: &[String],
+    ) -> Result<(), String> {
+        let added_ids: Vec<String> = added.iter().map(|p| p.id.clone()).collect();
+        for pattern in added {
+            if let Some(group) = pattern.group.clone() {
+                self.index
+                    .groups
+                    .entry(group)
+                    .or_default()
+                    .push(pattern.id.clone());
+            }
+            self.index.patterns.insert(pattern.id.clone(), pattern);
+        }
+        self.dirty = true;
+
+        for id in deletions {
+            if self.index.patterns.contains_key(id) {
+                self.delete_pattern(id)?;
+            }
+        }
+        pattern_cache::spawn_thumbnail_generation(added_ids);
+        self.save().map_err(|e| e.to_string())
+    }
+
+    /// Replace a pattern's tags (trimmed, deduped case-insensitively, sorted)
+    pub fn set_pattern_tags(
+        &mut self,
+        id: &str,
+        tags: Vec<String>,
+    ) -> Result<PatternResource, String> {
+        let pattern = self.pattern_mut(id)?;
+        pattern.tags = normalize_tags(tags);
+        let updated = pattern.clone();
+        self.dirty = true;
+        self.save().map_err(|e| e.to_string())?;
+        Ok(updated)
+    }
+
+    /// Remember that a pattern was picked, for recent-use sorting. Only the usage
+    /// side file is written, not the index.
+    pub fn record_pattern_use(&mut self, id: &str, now_ms: u64) -> Result<PatternResource, String> {
+        let pattern = self.pattern_mut(id)?;
+        pattern.last_used_ms = Some(now_ms);
+        let updated = pattern.clone();
+        let entry = self.usage.entries.entry(id.to_string()).or_default();
+        *entry = UsageEntry {
+            use_count: entry.use_count.saturating_add(1),
+            last_used_ms: Some(now_ms),
+        };
+        self.usage_dirty = true;
+        self.save().map_err(|e| e.to_string())?;
+        Ok(updated)
+    }
+
+    /// Fuzzy search over names, tags and groups with mode/size filters
+    pub fn search_patterns(
+        &self,
+        query: &str,
+        filters: &PatternSearchFilters,
+    ) -> Vec<PatternResource> {
+        search::search_patterns(self.index.patterns.values(), query, filters)
+    }
+
+    fn pattern_mut(&mut self, id: &str) -> Result<&mut PatternResource, String> {
+        self.index
+            .patterns
+            .get_mut(id)
+            .ok_or_else(|| format!("Pattern not found: {}", id))
+    }
+
     /// Rename a pattern
     pub fn rename_pattern(&mut self, id: &str, new_name: String) -> Result<(), String> {
         let pattern = self
@@ -406,6 +783,48 @@ impl PatternLibrary {
     }
 }
 
+/// Version 0 files (written before schema versioning) already match version 1
+fn migrate_unversioned(_value: &mut serde_json::Value) -> Result<(), String> {
+    Ok(())
+}
+
+/// Rebuild the index from full-size patterns in the cache directory
+fn rebuild_index(cache_dir: &Path) -> PatternIndex {
+    let mut index = PatternIndex::default();
+    for (id, cached) in pattern_cache::read_cached_patterns_in_dir(cache_dir) {
+        let Ok(rgba) = lz4_flex::decompress_size_prepended(&cached.data) else {
+            continue;
+        };
+        let content_hash = hex::encode(Sha256::digest(&rgba));
+        let name = if cached.name.is_empty() {
+            id.clone()
+        } else {
+            cached.name
+        };
+        index.patterns.insert(
+            id.clone(),
+            PatternResource {
+                id: id.clone(),
+                name,
+                content_hash,
+                width: cached.width,
+                height: cached.height,
+                mode: PatternMode::from_name(&cached.mode).unwrap_or(PatternMode::RGB),
+                source: RECOVERED_SOURCE.to_string(),
+                group: Some(RECOVERED_GROUP.to_string()),
+                tags: Vec::new(),
+                last_used_ms: None,
+            },
+        );
+        index
+            .groups
+            .entry(RECOVERED_GROUP.to_string())
+            .or_default()
+            .push(id);
+    }
+    index
+}
+
 // === Global library operations ===
 
 /// Get the library directory path
@@ -419,8 +838,10 @@ fn get_library_dir() -> PathBuf {
 /// Initialize the global pattern library
 pub fn init_library() {
     let library_dir = get_library_dir();
-    let mut guard = LIBRARY.write();
-    *guard = Some(PatternLibrary::load(library_dir));
+    let library = PatternLibrary::load(library_dir);
+    // Fill in thumbnails missing from earlier versions or interrupted imports
+    pattern_cache::spawn_thumbnail_generation(library.index.patterns.keys().cloned().collect());
+    *LIBRARY.write() = Some(library);
     tracing::info!("Pattern library initialized");
 }
 
@@ -433,6 +854,25 @@ pub fn get_all_patterns() -> Vec<PatternResource> {
         .unwrap_or_default()
 }
 
+/// Get a pattern's metadata by ID
+pub fn get_pattern(id: &str) -> Option<PatternResource> {
+    let guard = LIBRARY.read();
+    guard.as_ref().and_then(|lib| lib.get_pattern(id).cloned())
+}
+
+/// Import a pattern with its RGBA pixels, deduplicating by content hash
+pub fn import_pattern(
+    resource: &PatternResource,
+    rgba_data: Vec<u8>,
+    source: &str,
+) -> Result<(String, bool), String> {
+    let mut guard = LIBRARY.write();
+    let lib = guard
+        .as_mut()
+        .ok_or_else(|| "Library not initialized".to_string())?;
+    lib.import_pattern(resource, rgba_data, source)
+}
+
 /// Import a .pat file into the library
 pub fn import_pat_file(path: &Path) -> Result<ImportResult, String> {
     let mut guard = LIBRARY.write();
@@ -442,6 +882,15 @@ pub fn import_pat_file(path: &Path) -> Result<ImportResult, String> {
     lib.import_pat_file(path)
 }
 
+/// Add already parsed .pat patterns to a group
+pub fn import_parsed_patterns(
+    patterns: Vec<ParsedPattern>,
+    source: &str,
+    group_name: &str,
+) -> Result<ImportResult, String> {
+    with_library_write(|lib| lib.import_parsed_patterns(patterns, source, group_name))
+}
+
 /// Add pattern from brush to library
 pub fn add_from_brush(
     brush_id: &str,
@@ -458,6 +907,23 @@ pub fn add_from_brush(
     lib.add_from_brush(brush_id, name, rgba_data, width, height, mode)
 }
 
+/// Add a pattern from an image file
+pub fn add_from_image(
+    path: &Path,
+    options: &PatternCreateOptions,
+) -> Result<AddPatternFromBrushResult, String> {
+    with_library_write(|lib| lib.add_from_image(path, options))
+}
+
+/// Add a pattern from a region of an encoded layer image
+pub fn add_from_region(
+    layer_image: &[u8],
+    rect: PatternRect,
+    options: &PatternCreateOptions,
+) -> Result<AddPatternFromBrushResult, String> {
+    with_library_write(|lib| lib.add_from_region(layer_image, rect, options))
+}
+
 /// Delete a pattern
 pub fn delete_pattern(id: &str) -> Result<(), String> {
     let mut guard = LIBRARY.write();
@@ -494,6 +960,36 @@ pub fn rename_group(old_name: &str, new_name: String) -> Result<(), String> {
     lib.rename_group(old_name, new_name)
 }
 
+/// Replace a pattern's tags
+pub fn set_pattern_tags(id: &str, tags: Vec<String>) -> Result<PatternResource, String> {
+    with_library_write(|lib| lib.set_pattern_tags(id, tags))
+}
+
+/// Remember that a pattern was picked
+pub fn record_pattern_use(id: &str) -> Result<PatternResource, String> {
+    with_library_write(|lib| lib.record_pattern_use(id, current_time_ms()))
+}
+
+/// Search the global pattern library
+pub fn search_patterns(query: &str, filters: &PatternSearchFilters) -> Vec<PatternResource> {
+    let guard = LIBRARY.read();
+    guard
+        .as_ref()
+        .map(|lib| lib.search_patterns(query, filters))
+        .unwrap_or_default()
+}
+
+/// Run `f` against the global pattern library
+pub(crate) fn with_library_write<T>(
+    f: impl FnOnce(&mut PatternLibrary) -> Result<T, String>,
+) -> Result<T, String> {
+    let mut guard = LIBRARY.write();
+    let lib = guard
+        .as_mut()
+        .ok_or_else(|| "Library not initialized".to_string())?;
+    f(lib)
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
@@ -591,4 +1087,133 @@ mod tests {
 
         let _ = std::fs::remove_dir_all(&test_root);
     }
+
+    #[test]
+    fn import_replaces_ids_that_are_not_plain_tokens() {
+        let test_root = create_test_dir("unsafe_id");
+        let mut library = PatternLibrary::new(test_root.join("patterns"));
+
+        let result = library
+            .add_from_brush(
+                "../../escape",
+                "Escape".to_string(),
+                vec![77u8; 2 * 2 * 4],
+                2,
+                2,
+                PatternMode::RGB,
+            )
+            .unwrap();
+
+        assert!(result.added);
+        assert!(is_safe_blob_id(&result.pattern.id));
+        assert_eq!(result.pattern.id, result.pattern.content_hash[..16]);
+
+        let _ = std::fs::remove_dir_all(&test_root);
+    }
+
+    #[test]
+    fn pattern_picks_persist_without_rewriting_the_index() {
+        let test_root = create_test_dir("usage");
+        let dir = test_root.join("patterns");
+        let mut library = PatternLibrary::new(dir.clone());
+        let id = library
+            .add_from_brush(
+                "picked",
+                "Picked".to_string(),
+                vec![11u8; 2 * 2 * 4],
+                2,
+                2,
+                PatternMode::RGB,
+            )
+            .unwrap()
+            .pattern
+            .id;
+        let index_before = std::fs::read(dir.join(INDEX_FILE_NAME)).unwrap();
+
+        library.record_pattern_use(&id, 42).unwrap();
+        assert_eq!(
+            std::fs::read(dir.join(INDEX_FILE_NAME)).unwrap(),
+            index_before
+        );
+        let reloaded = PatternLibrary::load_with_cache_dir(dir, &test_root.join("cache"));
+        assert_eq!(reloaded.get_pattern(&id).unwrap().last_used_ms, Some(42));
+
+        let _ = std::fs::remove_dir_all(&test_root);
+    }
+
+    #[test]
+    fn add_from_image_blends_edges_and_dedupes() {
+        let test_root = create_test_dir("from_image");
+        std::fs::create_dir_all(&test_root).unwrap();
+        let unique = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let path = test_root.join(format!("Canvas {}.png", unique));
+        image::RgbaImage::from_fn(16, 8, |x, y| {
+            image::Rgba([(x * 16) as u8, (y * 32) as u8, 0, 255])
+        })
+        .save(&path)
+        .unwrap();
+
+        let mut library = PatternLibrary::new(test_root.join("patterns"));
+        let options = PatternCreateOptions {
+            grayscale: true,
+            blend_edges: Some(2),
+            ..Default::default()
+        };
+        let first = library.add_from_image(&path, &options).unwrap();
+        assert!(first.added);
+        assert_eq!(first.pattern.name, format!("Canvas {}", unique));
+        assert_eq!(first.pattern.group.as_deref(), Some(IMAGE_GROUP));
+        assert_eq!((first.pattern.width, first.pattern.height), (14, 6));
+        assert_eq!(first.pattern.mode, PatternMode::Grayscale);
+
+        let second = library.add_from_image(&path, &options).unwrap();
+        assert!(!second.added);
+        assert_eq!(second.pattern.id, first.pattern.id);
+
+        let _ = library.delete_pattern(&first.pattern.id);
+        let _ = std::fs::remove_dir_all(&test_root);
+    }
+
+    #[test]
+    fn truncated_index_is_quarantined_and_rebuilt_from_cache() {
+        let test_root = create_test_dir("truncated");
+        let library_dir = test_root.join("patterns");
+        let cache_dir = test_root.join("pattern_cache");
+        let rgba = vec![64u8; 3 * 2 * 4];
+        pattern_cache::save_pattern_to_disk_in_dir(
+            &cache_dir,
+            "pat-1",
+            &pattern_cache::CachedPattern {
+                data: lz4_flex::compress_prepend_size(&rgba),
+                width: 3,
+                height: 2,
+                name: "Canvas".to_string(),
+                mode: PatternMode::Grayscale.name().to_string(),
+            },
+        );
+        std::fs::create_dir_all(cache_dir.join("thumb_64")).unwrap();
+
+        std::fs::create_dir_all(&library_dir).unwrap();
+        std::fs::write(
+            library_dir.join(INDEX_FILE_NAME),
+            br#"{"patterns": {"pat-1": {"#,
+        )
+        .unwrap();
+
+        let library = PatternLibrary::load_with_cache_dir(library_dir.clone(), &cache_dir);
+        let pattern = library.get_pattern("pat-1").unwrap();
+        assert_eq!(pattern.name, "Canvas");
+        assert_eq!((pattern.width, pattern.height), (3, 2));
+        assert_eq!(pattern.mode, PatternMode::Grayscale);
+        assert_eq!(pattern.content_hash, hex::encode(Sha256::digest(&rgba)));
+        assert_eq!(library.get_group_patterns(RECOVERED_GROUP).len(), 1);
+
+        let reloaded = PatternLibrary::load_with_cache_dir(library_dir, &cache_dir);
+        assert_eq!(reloaded.get_all_patterns().len(), 1);
+
+        let _ = std::fs::remove_dir_all(&test_root);
+    }
 }
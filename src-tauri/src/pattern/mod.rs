@@ -4,11 +4,19 @@
 //! - Pattern storage and retrieval (Content-Addressable Storage)
 //! - .pat file import
 //! - ABR pattern integration
+//! - Patterns from image files and canvas regions, with seamless tiling helpers
+//! - Tags, fuzzy search and recent-use sorting
 
+pub mod create;
 pub mod library;
 pub mod pat;
+pub mod seamless;
+pub mod search;
 pub mod types;
 
 pub use library::PatternLibrary;
 pub use pat::parse_pat_file;
-pub use types::{AddPatternFromBrushResult, ImportResult, PatternMode, PatternResource};
+pub use types::{
+    AddPatternFromBrushResult, ImportResult, PatternCreateOptions, PatternMode, PatternRect,
+    PatternResource,
+};
@@ -80,6 +80,8 @@ impl ParsedPattern {
             mode: self.mode,
             source: source.to_string(),
             group,
+            tags: Vec::new(),
+            last_used_ms: None,
         }
     }
 
@@ -0,0 +1,120 @@
+//! Tiling helpers for user-made patterns.
+//!
+//! [`offset_rgba`] wraps an image by an offset (like Photoshop's Offset filter)
+//! so the seams show up in the middle for previewing. [`blend_edges`] makes an
+//! image tileable by crossfading a band at each edge into the opposite edge.
+
+/// Shift an RGBA image by `(dx, dy)` pixels, wrapping around the edges
+pub fn offset_rgba(rgba: &[u8], width: u32, height: u32, dx: i64, dy: i64) -> Vec<u8> {
+    let (w, h) = (width as i64, height as i64);
+    let mut out = vec![0u8; rgba.len()];
+    if w == 0 || h == 0 {
+        return out;
+    }
+    for y in 0..h {
+        let target_y = (y + dy).rem_euclid(h);
+        for x in 0..w {
+            let target_x = (x + dx).rem_euclid(w);
+            let src = ((y * w + x) * 4) as usize;
+            let dst = ((target_y * w + target_x) * 4) as usize;
+            out[dst..dst + 4].copy_from_slice(&rgba[src..src + 4]);
+        }
+    }
+    out
+}
+
+/// Preview of how an image tiles: wrapped by half its size so the edges meet
+/// in the middle
+pub fn offset_preview(rgba: &[u8], width: u32, height: u32) -> Vec<u8> {
+    offset_rgba(rgba, width, height, width as i64 / 2, height as i64 / 2)
+}
+
+/// Make an image tileable by crossfading `band` pixels at the start of each
+/// axis with the pixels past its end. The result is smaller by the band (capped
+/// at half the size) on each axis.
+pub fn blend_edges(rgba: &[u8], width: u32, height: u32, band: u32) -> (Vec<u8>, u32, u32) {
+    let (horizontal, width) = blend_axis(rgba, width, height, band.min(width / 2), true);
+    let (blended, height) = blend_axis(&horizontal, width, height, band.min(height / 2), false);
+    (blended, width, height)
+}
+
+/// Crossfade along x (`horizontal`) or y. Returns the image and the new length
+/// of that axis.
+fn blend_axis(rgba: &[u8], width: u32, height: u32, band: u32, horizontal: bool) -> (Vec<u8>, u32) {
+    let (w, h) = (width as usize, height as usize);
+    let band = band as usize;
+    let (out_w, out_h) = if horizontal {
+        (w - band, h)
+    } else {
+        (w, h - band)
+    };
+    let kept = if horizontal { out_w } else { out_h };
+
+    let mut out = Vec::with_capacity(out_w * out_h * 4);
+    for y in 0..out_h {
+        for x in 0..out_w {
+            let pos = if horizontal { x } else { y };
+            let src = (y * w + x) * 4;
+            if pos >= band {
+                out.extend_from_slice(&rgba[src..src + 4]);
+                continue;
+            }
+            // Weight of the original pixel grows across the band; the wrapped
+            // pixel continues the image from its new end
+            let t = (pos + 1) as f32 / (band + 1) as f32;
+            let wrapped = if horizontal {
+                (y * w + x + kept) * 4
+            } else {
+                ((y + kept) * w + x) * 4
+            };
+            for channel in 0..4 {
+                let a = rgba[wrapped + channel] as f32;
+                let b = rgba[src + channel] as f32;
+                out.push((a + (b - a) * t).round() as u8);
+            }
+        }
+    }
+    (out, kept as u32)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    /// Horizontal gray ramp: 0, 10, 20, ...
+    fn ramp(width: u32, height: u32) -> Vec<u8> {
+        (0..height)
+            .flat_map(|_| (0..width).flat_map(|x| [(x * 10) as u8, 0, 0, 255]))
+            .collect()
+    }
+
+    #[test]
+    fn offset_wraps_and_blend_removes_the_seam() {
+        let image = ramp(4, 1);
+        let shifted = offset_rgba(&image, 4, 1, 1, 0);
+        let reds: Vec<u8> = shifted.chunks(4).map(|p| p[0]).collect();
+        assert_eq!(reds, vec![30, 0, 10, 20]);
+        assert_eq!(
+            offset_preview(&image, 4, 1),
+            offset_rgba(&image, 4, 1, 2, 0)
+        );
+
+        let image = ramp(20, 2);
+        let (blended, width, height) = blend_edges(&image, 20, 2, 4);
+        assert_eq!((width, height), (16, 1));
+        let reds: Vec<u8> = blended.chunks(4).map(|p| p[0]).collect();
+        // Every step, including the wrap from the last pixel to the first, is small
+        for i in 0..reds.len() {
+            let next = reds[(i + 1) % reds.len()];
+            assert!(
+                (next as i32 - reds[i] as i32).abs() <= 40,
+                "jump at {}: {:?}",
+                i,
+                reds
+            );
+        }
+        // Without blending the wrap jumps from 190 back to 0
+        assert_eq!(image[19 * 4], 190);
+    }
+}
@@ -0,0 +1,189 @@
+//! Pattern library search: fuzzy name/tag matching plus mode and size filters.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+use super::types::{PatternMode, PatternResource};
+use crate::brush::search::fuzzy_score;
+
+/// Result ordering for `search_patterns`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PatternSort {
+    /// Best match first
+    #[default]
+    Relevance,
+    Name,
+    /// Most recent first; patterns never used are left out
+    RecentlyUsed,
+    /// Largest side first
+    Size,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PatternSearchFilters {
+    /// Patterns must carry every one of these tags (case-insensitive)
+    pub tags: Vec<String>,
+    pub group: Option<String>,
+    pub mode: Option<PatternMode>,
+    /// Bounds on the larger side in pixels (inclusive)
+    pub min_size: Option<u32>,
+    pub max_size: Option<u32>,
+    pub sort: PatternSort,
+    pub limit: Option<usize>,
+}
+
+/// Best score of one query token against a pattern's name, tags and group
+fn token_score(token: &str, pattern: &PatternResource) -> Option<u32> {
+    let name = fuzzy_score(token, &pattern.name);
+    let tag = pattern
+        .tags
+        .iter()
+        .filter_map(|tag| fuzzy_score(token, tag))
+        .max()
+        .map(|score| score / 2);
+    let group = pattern
+        .group
+        .as_deref()
+        .and_then(|group| fuzzy_score(token, group))
+        .map(|score| score / 4);
+    [name, tag, group].into_iter().flatten().max()
+}
+
+fn matches_filters(pattern: &PatternResource, filters: &PatternSearchFilters) -> bool {
+    let has_all_tags = filters.tags.iter().all(|wanted| {
+        pattern
+            .tags
+            .iter()
+            .any(|tag| tag.eq_ignore_ascii_case(wanted.trim()))
+    });
+    if !has_all_tags {
+        return false;
+    }
+    if let Some(group) = filters.group.as_deref() {
+        if pattern.group.as_deref() != Some(group) {
+            return false;
+        }
+    }
+    if filters.mode.is_some_and(|mode| mode != pattern.mode) {
+        return false;
+    }
+    if filters.sort == PatternSort::RecentlyUsed && pattern.last_used_ms.is_none() {
+        return false;
+    }
+
+    let size = pattern.width.max(pattern.height);
+    size >= filters.min_size.unwrap_or(0) && size <= filters.max_size.unwrap_or(u32::MAX)
+}
+
+/// Patterns matching every whitespace-separated query token and all filters
+pub fn search_patterns<'a>(
+    patterns: impl Iterator<Item = &'a PatternResource>,
+    query: &str,
+    filters: &PatternSearchFilters,
+) -> Vec<PatternResource> {
+    let query = query.to_lowercase();
+    let tokens: Vec<&str> = query.split_whitespace().collect();
+
+    let mut hits: Vec<(u32, &PatternResource)> = patterns
+        .filter(|pattern| matches_filters(pattern, filters))
+        .filter_map(|pattern| {
+            tokens
+                .iter()
+                .map(|token| token_score(token, pattern))
+                .sum::<Option<u32>>()
+                .map(|score| (score, pattern))
+        })
+        .collect();
+
+    let by_name = |a: &PatternResource, b: &PatternResource| {
+        a.name
+            .to_lowercase()
+            .cmp(&b.name.to_lowercase())
+            .then_with(|| a.id.cmp(&b.id))
+    };
+    hits.sort_by(|(score_a, a), (score_b, b)| {
+        let primary = match filters.sort {
+            PatternSort::Relevance => score_b.cmp(score_a),
+            PatternSort::Name => Ordering::Equal,
+            PatternSort::RecentlyUsed => b.last_used_ms.cmp(&a.last_used_ms),
+            PatternSort::Size => b.width.max(b.height).cmp(&a.width.max(a.height)),
+        };
+        primary.then_with(|| by_name(a, b))
+    });
+
+    let limit = filters.limit.unwrap_or(usize::MAX);
+    hits.into_iter()
+        .take(limit)
+        .map(|(_, pattern)| pattern.clone())
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn pattern(id: &str, name: &str, size: u32, mode: PatternMode) -> PatternResource {
+        PatternResource {
+            id: id.to_string(),
+            name: name.to_string(),
+            content_hash: String::new(),
+            width: size,
+            height: size,
+            mode,
+            source: "test".to_string(),
+            group: None,
+            tags: Vec::new(),
+            last_used_ms: None,
+        }
+    }
+
+    #[test]
+    fn search_filters_by_mode_size_and_tags_and_sorts_by_recent_use() {
+        let mut canvas = pattern("a", "Canvas Coarse", 256, PatternMode::Grayscale);
+        canvas.tags = vec!["Fabric".to_string()];
+        canvas.last_used_ms = Some(10);
+        let mut linen = pattern("b", "Linen", 64, PatternMode::Grayscale);
+        linen.tags = vec!["fabric".to_string()];
+        linen.last_used_ms = Some(20);
+        let rock = pattern("c", "Rock", 512, PatternMode::RGB);
+        let all = [canvas, linen, rock];
+
+        let fabric = PatternSearchFilters {
+            tags: vec!["fabric".to_string()],
+            ..Default::default()
+        };
+        let names = |hits: Vec<PatternResource>| -> Vec<String> {
+            hits.into_iter().map(|p| p.name).collect()
+        };
+        assert_eq!(
+            names(search_patterns(all.iter(), "", &fabric)),
+            vec!["Canvas Coarse", "Linen"]
+        );
+        assert_eq!(
+            names(search_patterns(all.iter(), "cnv", &fabric)),
+            vec!["Canvas Coarse"]
+        );
+
+        let large_gray = PatternSearchFilters {
+            mode: Some(PatternMode::Grayscale),
+            min_size: Some(128),
+            ..Default::default()
+        };
+        assert_eq!(
+            names(search_patterns(all.iter(), "", &large_gray)),
+            vec!["Canvas Coarse"]
+        );
+
+        let recent = PatternSearchFilters {
+            sort: PatternSort::RecentlyUsed,
+            ..Default::default()
+        };
+        assert_eq!(
+            names(search_patterns(all.iter(), "", &recent)),
+            vec!["Linen", "Canvas Coarse"]
+        );
+    }
+}
@@ -33,6 +33,16 @@ impl PatternMode {
         }
     }
 
+    /// Parse a display name produced by [`PatternMode::name`]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Grayscale" => Some(Self::Grayscale),
+            "RGB" => Some(Self::RGB),
+            "Indexed" => Some(Self::Indexed),
+            _ => None,
+        }
+    }
+
     /// Get channel count
     pub fn channels(&self) -> usize {
         match self {
@@ -68,6 +78,14 @@ pub struct PatternResource {
 
     /// Group name (optional)
     pub group: Option<String>,
+
+    /// User tags (trimmed, deduped case-insensitively, sorted)
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Last time the pattern was picked for a brush texture (Unix ms)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used_ms: Option<u64>,
 }
 
 /// Import result for pattern files
@@ -83,7 +101,7 @@ pub struct ImportResult {
     pub pattern_ids: Vec<String>,
 }
 
-/// Result for adding a brush-attached pattern into library
+/// Result for adding a single pattern (from a brush, image file or canvas region)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AddPatternFromBrushResult {
     /// True if a new pattern is added; false if an existing duplicate is reused
@@ -91,3 +109,27 @@ pub struct AddPatternFromBrushResult {
     /// The resolved pattern in the library (new or existing)
     pub pattern: PatternResource,
 }
+
+/// Pixel rectangle within a layer image
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatternRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Options for creating a pattern from an image file or canvas region
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PatternCreateOptions {
+    /// Display name; defaults to the file name or "Canvas Pattern"
+    pub name: Option<String>,
+    /// Group; defaults to "From Images" or "From Canvas"
+    pub group: Option<String>,
+    /// Store as a grayscale pattern
+    pub grayscale: bool,
+    /// Crossfade this many pixels of each edge into the opposite edge so the
+    /// pattern tiles seamlessly (the result shrinks by that much per axis)
+    pub blend_edges: Option<u32>,
+}
@@ -366,6 +366,7 @@ function App() {
         backend: tabletSettings.backend,
         pollingRate: tabletSettings.pollingRate,
         pressureCurve: tabletSettings.pressureCurve,
+        pressureCurvePoints: tabletSettings.pressureCurvePoints,
         backpressureMode: tabletSettings.backpressureMode,
       });
 
@@ -1,22 +1,32 @@
 import { useEffect, useMemo, useState } from 'react';
-import { confirm, open } from '@tauri-apps/plugin-dialog';
+import { confirm, open, save } from '@tauri-apps/plugin-dialog';
 import {
   ArrowRightLeft,
   ChevronDown,
   ChevronRight,
+  Download,
   Edit2,
+  FolderOpen,
   FolderPlus,
+  RefreshCw,
+  RotateCcw,
   Search,
+  ShieldCheck,
+  Star,
+  Tag,
   Trash2,
   Upload,
   X,
 } from 'lucide-react';
 import { BrushPresetThumbnail } from '@/components/BrushPanel/BrushPresetThumbnail';
 import {
+  ORIGINAL_PRESET_REVISION,
   useBrushLibraryStore,
+  type BulkImportProgress,
   useGroupedBrushPresets,
   useSelectedPresetIdForCurrentTool,
 } from '@/stores/brushLibrary';
+import { useToastStore } from '@/stores/toast';
 import { useI18n } from '@/i18n';
 import './BrushLibraryPanel.css';
 
@@ -31,18 +41,28 @@ export function BrushLibraryPanel({ isOpen, onClose }: BrushLibraryPanelProps):
   const presetCount = useBrushLibraryStore((state) => state.presets.length);
   const tipsCount = useBrushLibraryStore((state) => state.tips.length);
   const selectedPresetId = useSelectedPresetIdForCurrentTool();
+  const pushToast = useToastStore((state) => state.pushToast);
+  const [importProgress, setImportProgress] = useState<BulkImportProgress | null>(null);
   const {
     isLoading,
     error,
     searchQuery,
     setSearchQuery,
     loadLibrary,
-    importAbrFile,
+    importBrushBundle,
+    importBrushPacks,
+    exportBrushBundle,
+    syncWithFolder,
+    getSyncState,
+    verifyLibraries,
     renamePreset,
     deletePreset,
     deleteGroup,
     movePresetToGroup,
     renameGroup,
+    setPresetTags,
+    setPresetFavorite,
+    revertPreset,
     applyPresetById,
     clearError,
   } = useBrushLibraryStore();
@@ -74,7 +94,13 @@ export function BrushLibraryPanel({ isOpen, onClose }: BrushLibraryPanelProps):
     try {
       const selected = await open({
         multiple: true,
-        filters: [{ name: 'Photoshop Brushes', extensions: ['abr'] }],
+        filters: [
+          { name: 'Brushes', extensions: ['abr', 'sutubrush', 'zip', 'pat'] },
+          { name: 'Photoshop Brushes', extensions: ['abr'] },
+          { name: 'Photoshop Patterns', extensions: ['pat'] },
+          { name: 'Brush Packs', extensions: ['zip'] },
+          { name: 'Sutu Brush Bundle', extensions: ['sutubrush'] },
+        ],
       });
 
       if (!selected) {
@@ -82,14 +108,128 @@ export function BrushLibraryPanel({ isOpen, onClose }: BrushLibraryPanelProps):
       }
 
       const paths = Array.isArray(selected) ? selected : [selected];
+      const packPaths: string[] = [];
       for (const path of paths) {
-        await importAbrFile(path);
+        if (path.toLowerCase().endsWith('.sutubrush')) {
+          await importBrushBundle(path);
+        } else {
+          packPaths.push(path);
+        }
       }
+      await runPackImport(packPaths);
     } catch (err) {
       console.error('[BrushLibrary] import failed', err);
     }
   };
 
+  const handleImportFolder = async () => {
+    try {
+      const selected = await open({
+        directory: true,
+        multiple: true,
+        title: t('brushLibrary.importFolder'),
+      });
+      if (!selected) {
+        return;
+      }
+      await runPackImport(Array.isArray(selected) ? selected : [selected]);
+    } catch (err) {
+      console.error('[BrushLibrary] folder import failed', err);
+    }
+  };
+
+  const runPackImport = async (paths: string[]) => {
+    if (paths.length === 0) {
+      return;
+    }
+    try {
+      const result = await importBrushPacks(paths, setImportProgress);
+      if (result.errors.length > 0) {
+        console.warn('[BrushLibrary] bulk import errors', result.errors);
+        pushToast(
+          t('brushLibrary.toast.bulkImportPartial', {
+            fileTotal: result.importedFileCount,
+            errorTotal: result.errors.length,
+          }),
+          { variant: 'info' }
+        );
+      } else if (paths.length > 1 || result.importedFileCount > 1) {
+        pushToast(
+          t('brushLibrary.toast.bulkImportDone', {
+            fileTotal: result.importedFileCount,
+            presetTotal: result.importedPresetCount,
+          }),
+          { variant: 'success' }
+        );
+      }
+    } finally {
+      setImportProgress(null);
+    }
+  };
+
+  const handleExport = async () => {
+    if (!selectedPreset) {
+      return;
+    }
+
+    try {
+      const path = await save({
+        defaultPath: `${selectedPreset.name}.sutubrush`,
+        filters: [{ name: 'Sutu Brush Bundle', extensions: ['sutubrush'] }],
+      });
+      if (!path) {
+        return;
+      }
+      await exportBrushBundle(path, [selectedPreset.id]);
+    } catch (err) {
+      console.error('[BrushLibrary] export failed', err);
+    }
+  };
+
+  const handleSync = async () => {
+    try {
+      const state = await getSyncState();
+      const folder = await open({
+        directory: true,
+        defaultPath: state.replicaDir ?? undefined,
+        title: t('brushLibrary.syncWithFolder'),
+      });
+      if (!folder || Array.isArray(folder)) {
+        return;
+      }
+      await syncWithFolder(folder);
+    } catch (err) {
+      console.error('[BrushLibrary] sync failed', err);
+    }
+  };
+
+  const handleVerify = async () => {
+    try {
+      const report = await verifyLibraries(false);
+      if (report.issues.length === 0) {
+        pushToast(t('brushLibrary.toast.libraryHealthy'), { variant: 'success' });
+        return;
+      }
+      const confirmed = await confirm(
+        t('brushLibrary.confirm.repairLibrary', { issueTotal: report.issues.length }),
+        { title: t('brushLibrary.title'), kind: 'warning' }
+      );
+      if (!confirmed) {
+        return;
+      }
+      const repaired = await verifyLibraries(true);
+      pushToast(
+        t('brushLibrary.toast.libraryRepaired', {
+          repairedTotal: repaired.repairedCount,
+          unresolvedTotal: repaired.unresolvedCount,
+        }),
+        { variant: repaired.unresolvedCount > 0 ? 'info' : 'success' }
+      );
+    } catch (err) {
+      console.error('[BrushLibrary] verify failed', err);
+    }
+  };
+
   const handleDelete = async () => {
     const presetId = selectedPresetId;
     if (!presetId) {
@@ -127,6 +267,55 @@ export function BrushLibraryPanel({ isOpen, onClose }: BrushLibraryPanelProps):
     }
   };
 
+  const handleRestoreOriginal = async () => {
+    if (!selectedPreset) {
+      return;
+    }
+
+    const confirmed = await confirm(
+      t('brushLibrary.confirm.restoreOriginal', { presetName: selectedPreset.name }),
+      { title: t('brushLibrary.title'), kind: 'warning' }
+    );
+    if (!confirmed) {
+      return;
+    }
+
+    try {
+      await revertPreset(selectedPreset.id, ORIGINAL_PRESET_REVISION);
+    } catch (err) {
+      console.error('[BrushLibrary] restore original failed', err);
+    }
+  };
+
+  const handleToggleFavorite = async () => {
+    if (!selectedPreset) {
+      return;
+    }
+
+    try {
+      await setPresetFavorite(selectedPreset.id, !selectedPreset.favorite);
+    } catch (err) {
+      console.error('[BrushLibrary] favorite failed', err);
+    }
+  };
+
+  const handleEditTags = async () => {
+    if (!selectedPreset) {
+      return;
+    }
+
+    const input = window.prompt(t('brushLibrary.prompt.tags'), selectedPreset.tags.join(', '));
+    if (input === null) {
+      return;
+    }
+
+    try {
+      await setPresetTags(selectedPreset.id, input.split(','));
+    } catch (err) {
+      console.error('[BrushLibrary] edit tags failed', err);
+    }
+  };
+
   const handleMovePreset = async () => {
     if (!selectedPreset) {
       return;
@@ -235,6 +424,38 @@ export function BrushLibraryPanel({ isOpen, onClose }: BrushLibraryPanelProps):
               <Upload size={14} />
               {t('brushLibrary.importAbr')}
             </button>
+            <button
+              className="brush-library-btn"
+              onClick={handleImportFolder}
+              disabled={isLoading}
+              title={t('brushLibrary.importFolder')}
+            >
+              <FolderOpen size={14} />
+            </button>
+            <button
+              className="brush-library-btn"
+              onClick={handleExport}
+              disabled={!selectedPresetId}
+              title={t('brushLibrary.exportSelectedPreset')}
+            >
+              <Download size={14} />
+            </button>
+            <button
+              className="brush-library-btn"
+              onClick={handleSync}
+              disabled={isLoading}
+              title={t('brushLibrary.syncWithFolder')}
+            >
+              <RefreshCw size={14} />
+            </button>
+            <button
+              className="brush-library-btn"
+              onClick={handleVerify}
+              disabled={isLoading}
+              title={t('brushLibrary.verifyLibrary')}
+            >
+              <ShieldCheck size={14} />
+            </button>
             <button
               className="brush-library-btn"
               onClick={handleDelete}
@@ -259,6 +480,30 @@ export function BrushLibraryPanel({ isOpen, onClose }: BrushLibraryPanelProps):
             >
               <ArrowRightLeft size={14} />
             </button>
+            <button
+              className={`brush-library-btn ${selectedPreset?.favorite ? 'active' : ''}`}
+              onClick={handleToggleFavorite}
+              disabled={!selectedPresetId}
+              title={t('brushLibrary.toggleFavorite')}
+            >
+              <Star size={14} fill={selectedPreset?.favorite ? 'currentColor' : 'none'} />
+            </button>
+            <button
+              className="brush-library-btn"
+              onClick={handleEditTags}
+              disabled={!selectedPresetId}
+              title={t('brushLibrary.editTags')}
+            >
+              <Tag size={14} />
+            </button>
+            <button
+              className="brush-library-btn"
+              onClick={handleRestoreOriginal}
+              disabled={!selectedPresetId}
+              title={t('brushLibrary.restoreOriginal')}
+            >
+              <RotateCcw size={14} />
+            </button>
           </div>
         </div>
 
@@ -271,7 +516,14 @@ export function BrushLibraryPanel({ isOpen, onClose }: BrushLibraryPanelProps):
 
         <div className="brush-library-content">
           {isLoading ? (
-            <div className="brush-library-loading">{t('brushLibrary.loading')}</div>
+            <div className="brush-library-loading">
+              {importProgress
+                ? t('brushLibrary.importProgress', {
+                    completed: importProgress.completed,
+                    total: importProgress.total,
+                  })
+                : t('brushLibrary.loading')}
+            </div>
           ) : groupedPresets.length === 0 ? (
             <div className="brush-library-empty">
               <FolderPlus size={48} strokeWidth={1} />
@@ -349,13 +601,20 @@ export function BrushLibraryPanel({ isOpen, onClose }: BrushLibraryPanelProps):
                           onClick={() => {
                             applyPresetById(preset.id);
                           }}
-                          title={preset.name}
+                          title={
+                            preset.tags.length > 0
+                              ? `${preset.name} (${preset.tags.join(', ')})`
+                              : preset.name
+                          }
                         >
                           <BrushPresetThumbnail
                             preset={preset}
                             size={48}
                             className="brush-grid-thumb"
                           />
+                          {preset.favorite && (
+                            <Star size={10} className="brush-grid-favorite" fill="currentColor" />
+                          )}
                           <span className="brush-grid-name">{preset.name}</span>
                         </button>
                       ))}
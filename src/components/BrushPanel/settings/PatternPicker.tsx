@@ -205,6 +205,9 @@ export function PatternPicker({
     setIsOpen(false);
     // Pre-load pattern immediately to avoid delay on first stroke
     void patternManager.loadPattern(patternId);
+    if (allPatterns.some((p) => p.id === patternId)) {
+      usePatternLibraryStore.getState().recordPatternUse(patternId);
+    }
   }
 
   function renderGridContent(): JSX.Element | JSX.Element[] {
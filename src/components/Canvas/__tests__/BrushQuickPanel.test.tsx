@@ -14,6 +14,9 @@ function createPreset(id: string, name: string, group: string | null): BrushLibr
     group,
     source: 'test',
     contentHash: `hash-${id}`,
+    tags: [],
+    favorite: false,
+    useCount: 0,
   };
 }
 
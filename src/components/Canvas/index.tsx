@@ -10,7 +10,11 @@ import { useDocumentStore } from '@/stores/document';
 import { useViewportStore } from '@/stores/viewport';
 import { createHistoryEntryId, type HistoryEntry, useHistoryStore } from '@/stores/history';
 import { useSettingsStore } from '@/stores/settings';
-import { useTabletStore } from '@/stores/tablet';
+import {
+  setTabletCursorPoseConfig,
+  useTabletStore,
+  type TabletCursorControlV1,
+} from '@/stores/tablet';
 import { usePanelStore } from '@/stores/panel';
 import { useSelectionHandler } from './useSelectionHandler';
 import { useCursor } from './useCursor';
@@ -26,6 +30,8 @@ import {
 import { useMoveTool } from './useMoveTool';
 import { useGlobalExports } from './useGlobalExports';
 import { useKeyboardShortcuts } from './useKeyboardShortcuts';
+import { useTabletActions } from './useTabletActions';
+import { useTouchGestures } from './useTouchGestures';
 import { usePointerHandlers } from './usePointerHandlers';
 import { useStrokeProcessor } from './useStrokeProcessor';
 import { useGradientTool, type GradientPreviewPayload } from './useGradientTool';
@@ -2328,6 +2334,8 @@ export function Canvas() {
 
   // Alt eyedropper switching - must be after finishCurrentStroke to avoid TDZ
   useAltEyedropper(previousToolRef, finishCurrentStroke);
+  useTabletActions({ handleUndo, handleRedo, onBeforeSwitch: finishCurrentStroke });
+  useTouchGestures(containerRef);
 
   const { spacePressed } = useKeyboardShortcuts({
     currentTool,
@@ -2465,6 +2473,27 @@ export function Canvas() {
     [brushTexture]
   );
 
+  // Native hover stream rotates/squashes the cursor like pen-driven Shape Dynamics
+  const tabletCursorPose = useTabletStore((state) => state.cursorPose);
+  const cursorAngleControl = shapeDynamics.angleControl;
+  const cursorRoundnessControl = shapeDynamics.roundnessControl;
+  const cursorMinimumRoundness = shapeDynamics.minimumRoundness;
+  useEffect(() => {
+    const toCursorControl = (control: string): TabletCursorControlV1 =>
+      shapeDynamicsEnabled && (control === 'penTilt' || control === 'rotation') ? control : 'off';
+    void setTabletCursorPoseConfig({
+      angleControl: toCursorControl(cursorAngleControl),
+      roundnessControl: toCursorControl(cursorRoundnessControl),
+      minimumRoundness: cursorMinimumRoundness,
+    });
+  }, [shapeDynamicsEnabled, cursorAngleControl, cursorRoundnessControl, cursorMinimumRoundness]);
+  const cursorBrushAngle = tabletCursorPose
+    ? (brushAngle + tabletCursorPose.angle_offset_deg) % 360
+    : brushAngle;
+  const cursorBrushRoundness = tabletCursorPose
+    ? Math.max(1, brushRoundness * tabletCursorPose.roundness_scale)
+    : brushRoundness;
+
   const { cursorStyle, showDomCursor, showEyedropperDomCursor, resolvedDomCursorPath } = useCursor({
     currentTool,
     currentSize,
@@ -2475,8 +2504,8 @@ export function Canvas() {
     containerRef,
     brushCursorRef,
     eyedropperCursorRef,
-    brushRoundness,
-    brushAngle,
+    brushRoundness: cursorBrushRoundness,
+    brushAngle: cursorBrushAngle,
     brushTexture: cursorBrushTexture,
     forceDomCursor: forceDomCursorDebug,
     canvasRef,
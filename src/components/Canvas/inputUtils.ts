@@ -305,6 +305,20 @@ export function mapNativeWindowPxToCanvasPoint(
   const bottom = rect.top + rectHeight;
   const outOfView = xPx < rect.left || xPx > right || yPx < rect.top || yPx > bottom;
   recordNativeCoordinateSample(source, outOfView);
+  return windowPxToCanvasPoint(canvas, rect, xPx, yPx);
+}
+
+/** Same mapping as `mapNativeWindowPxToCanvasPoint`, without coordinate diagnostics */
+export function windowPxToCanvasPoint(
+  canvas: HTMLCanvasElement,
+  rect: { left: number; top: number; width: number; height: number },
+  xPx: number,
+  yPx: number
+): { x: number; y: number } {
+  const rectWidth =
+    Number.isFinite(rect.width) && rect.width > 0 ? rect.width : Math.max(1, canvas.width);
+  const rectHeight =
+    Number.isFinite(rect.height) && rect.height > 0 ? rect.height : Math.max(1, canvas.height);
   const scaleX = canvas.width / rectWidth;
   const scaleY = canvas.height / rectHeight;
   return {
@@ -1,13 +1,13 @@
 import { useCallback, useEffect, useRef, type RefObject, type MutableRefObject } from 'react';
 import { ToolType } from '@/stores/tool';
-import { clearPointBuffer, useTabletStore } from '@/stores/tablet';
+import { clearPointBuffer, getPredictedTail, useTabletStore } from '@/stores/tablet';
 import { LatencyProfiler } from '@/benchmark/LatencyProfiler';
 import { LagometerMonitor } from '@/benchmark/LagometerMonitor';
 import { FPSCounter } from '@/benchmark/FPSCounter';
 import { BrushRenderConfig } from './useBrushRenderer';
 import { LayerRenderer } from '@/utils/layerRenderer';
 import type { GpuStrokeCommitResult, RenderBackend, StrokeCompositeMode } from '@/gpu';
-import { isNativeTabletStreamingState } from './inputUtils';
+import { isNativeTabletStreamingState, windowPxToCanvasPoint } from './inputUtils';
 import { logTabletTrace } from '@/utils/tabletTrace';
 
 const MAX_POINTS_PER_FRAME = 80;
@@ -241,6 +241,49 @@ export function useStrokeProcessor({
     [getShiftLineGuide, scale, isDrawingRef]
   );
 
+  // Predicted input is drawn as a plain round stroke on the display canvas only:
+  // the next composite replaces it, so it never reaches the layer or history
+  const renderPredictedTail = useCallback(
+    (ctx: CanvasRenderingContext2D) => {
+      if (currentTool !== 'brush' || !isDrawingRef.current || !isStrokeActive()) return;
+      if (!isNativeTabletStreamingState(useTabletStore.getState())) return;
+      const tail = getPredictedTail();
+      const start = lastRenderedPosRef.current;
+      const canvas = canvasRef.current;
+      if (tail.length === 0 || !start || !canvas) return;
+
+      const config = getBrushConfig();
+      const rect = canvas.getBoundingClientRect();
+      ctx.save();
+      ctx.globalAlpha = config.opacity;
+      ctx.strokeStyle = config.color;
+      ctx.lineCap = 'round';
+      ctx.lineJoin = 'round';
+      let from = start;
+      for (const point of tail) {
+        const canvasPoint = windowPxToCanvasPoint(canvas, rect, point.x_px, point.y_px);
+        const mapped = constrainShiftLinePoint(canvasPoint.x, canvasPoint.y);
+        const pressure = config.pressureSizeEnabled ? point.pressure : 1;
+        ctx.lineWidth = Math.max(1, config.size * pressure);
+        ctx.beginPath();
+        ctx.moveTo(from.x, from.y);
+        ctx.lineTo(mapped.x, mapped.y);
+        ctx.stroke();
+        from = mapped;
+      }
+      ctx.restore();
+    },
+    [
+      currentTool,
+      isDrawingRef,
+      isStrokeActive,
+      lastRenderedPosRef,
+      canvasRef,
+      getBrushConfig,
+      constrainShiftLinePoint,
+    ]
+  );
+
   const compositeAndRenderWithPreview = useCallback(() => {
     if (useGpuDisplay) {
       renderGpuFrame(isStrokeActive());
@@ -258,6 +301,7 @@ export function useStrokeProcessor({
         }
       }
 
+      renderPredictedTail(ctx);
       renderGuideLine(ctx);
       return;
     }
@@ -293,6 +337,7 @@ export function useStrokeProcessor({
       }
     }
 
+    renderPredictedTail(ctx);
     renderGuideLine(ctx);
   }, [
     useGpuDisplay,
@@ -305,6 +350,7 @@ export function useStrokeProcessor({
     getPreviewCompositeMode,
     getDebugRects,
     activeLayerId,
+    renderPredictedTail,
     renderGuideLine,
     canvasRef,
     layerRendererRef,
@@ -0,0 +1,114 @@
+import { useEffect, useRef } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import { useToolStore, type ToolType } from '@/stores/tool';
+import type { TabletActionEventV1 } from '@/stores/tablet';
+import { stepBrushSizeBySliderProgress } from '@/utils/sliderScales';
+
+interface UseTabletActionsParams {
+  handleUndo: () => void;
+  handleRedo: () => void;
+  /** Called before a temporary tool switch (e.g. to finish the current stroke) */
+  onBeforeSwitch?: () => void | Promise<void>;
+}
+
+/**
+ * Hook to run editor actions bound to pen buttons, express keys and touch rings.
+ *
+ * Bindings are resolved by the backend and arrive as `tablet-action-v1` events:
+ * - One-shot actions (undo, redo, size steps, eraser toggle) run on press
+ * - colorPick switches to the eyedropper while held, like Alt
+ * - brushSize scales with ring/strip travel (a full turn spans the size slider)
+ */
+export function useTabletActions({
+  handleUndo,
+  handleRedo,
+  onBeforeSwitch,
+}: UseTabletActionsParams): void {
+  const pickPreviousToolRef = useRef<ToolType | null>(null);
+  const eraserPreviousToolRef = useRef<ToolType | null>(null);
+
+  useEffect(() => {
+    const handleAction = (action: TabletActionEventV1) => {
+      const store = useToolStore.getState();
+
+      if (action.action === 'colorPick') {
+        if (action.pressed) {
+          if (store.currentTool === 'brush' || store.currentTool === 'eraser') {
+            onBeforeSwitch?.();
+            pickPreviousToolRef.current = store.currentTool;
+            store.setTool('eyedropper');
+          }
+        } else if (pickPreviousToolRef.current && store.currentTool === 'eyedropper') {
+          store.setTool(pickPreviousToolRef.current);
+          pickPreviousToolRef.current = null;
+        }
+        return;
+      }
+
+      if (!action.pressed) {
+        return;
+      }
+
+      switch (action.action) {
+        case 'undo':
+          handleUndo();
+          break;
+        case 'redo':
+          handleRedo();
+          break;
+        case 'brushSizeUp':
+          store.setCurrentSize(stepBrushSizeBySliderProgress(store.getCurrentSize(), 1));
+          break;
+        case 'brushSizeDown':
+          store.setCurrentSize(stepBrushSizeBySliderProgress(store.getCurrentSize(), -1));
+          break;
+        case 'brushSize':
+          if (action.amount !== 0) {
+            store.setCurrentSize(
+              stepBrushSizeBySliderProgress(
+                store.getCurrentSize(),
+                action.amount > 0 ? 1 : -1,
+                1,
+                1000,
+                Math.abs(action.amount)
+              )
+            );
+          }
+          break;
+        case 'toggleEraser':
+          onBeforeSwitch?.();
+          if (store.currentTool === 'eraser') {
+            store.setTool(eraserPreviousToolRef.current ?? 'brush');
+            eraserPreviousToolRef.current = null;
+          } else {
+            eraserPreviousToolRef.current = store.currentTool;
+            store.setTool('eraser');
+          }
+          break;
+      }
+    };
+
+    let disposed = false;
+    let unlisten: (() => void) | null = null;
+    void listen<TabletActionEventV1>('tablet-action-v1', (event) => {
+      if (event.payload && typeof event.payload === 'object') {
+        handleAction(event.payload);
+      }
+    })
+      .then((fn) => {
+        if (disposed) {
+          fn();
+        } else {
+          unlisten = fn;
+        }
+      })
+      .catch((error) => {
+        console.error('[Tablet] Failed to listen for tablet actions:', error);
+      });
+
+    return () => {
+      disposed = true;
+      unlisten?.();
+    };
+  }, [handleUndo, handleRedo, onBeforeSwitch]);
+}
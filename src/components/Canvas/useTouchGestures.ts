@@ -0,0 +1,93 @@
+import { useEffect, type RefObject } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import { pushPointerEvent, type InputPhase, type TouchGestureV1 } from '@/stores/tablet';
+import { useViewportStore } from '@/stores/viewport';
+
+function touchPhase(type: string): InputPhase | null {
+  switch (type) {
+    case 'pointerdown':
+      return 'down';
+    case 'pointermove':
+      return 'move';
+    case 'pointerup':
+    case 'pointercancel':
+      return 'up';
+    default:
+      return null;
+  }
+}
+
+/**
+ * Hook to route finger input into the backend gesture recognizer.
+ *
+ * Touch pointer events on the canvas container are stopped in the capture phase so
+ * they never reach the stroke handlers, and are forwarded as `touch` samples. The
+ * recognizer answers on `tablet-gesture-v1`:
+ * - Two-finger transforms pan and zoom the viewport around the finger centre
+ * - Taps are bound to actions in the backend and arrive via `tablet-action-v1`
+ *
+ * The viewport has no rotation, so `rotation_deg` is ignored for now.
+ */
+export function useTouchGestures(containerRef: RefObject<HTMLElement | null>): void {
+  useEffect(() => {
+    const container = containerRef.current;
+    if (!container) return;
+
+    const handlePointer = (e: PointerEvent) => {
+      if (e.pointerType !== 'touch') return;
+      const phase = touchPhase(e.type);
+      if (!phase) return;
+      e.stopPropagation();
+      const rect = container.getBoundingClientRect();
+      void pushPointerEvent(e.clientX - rect.left, e.clientY - rect.top, e.pressure, 0, 0, {
+        pointerId: e.pointerId,
+        phase,
+        toolType: 'touch',
+      });
+    };
+
+    const types = ['pointerdown', 'pointermove', 'pointerup', 'pointercancel'] as const;
+    for (const type of types) {
+      container.addEventListener(type, handlePointer, { capture: true });
+    }
+    return () => {
+      for (const type of types) {
+        container.removeEventListener(type, handlePointer, { capture: true });
+      }
+    };
+  }, [containerRef]);
+
+  useEffect(() => {
+    const handleGesture = (gesture: TouchGestureV1) => {
+      if (gesture.kind !== 'transform' || gesture.phase !== 'update') return;
+      const viewport = useViewportStore.getState();
+      viewport.pan(gesture.pan_x_px, gesture.pan_y_px);
+      if (gesture.scale !== 1) {
+        viewport.setScale(viewport.scale * gesture.scale, gesture.center_x_px, gesture.center_y_px);
+      }
+    };
+
+    let disposed = false;
+    let unlisten: (() => void) | null = null;
+    void listen<TouchGestureV1>('tablet-gesture-v1', (event) => {
+      if (event.payload && typeof event.payload === 'object') {
+        handleGesture(event.payload);
+      }
+    })
+      .then((fn) => {
+        if (disposed) {
+          fn();
+        } else {
+          unlisten = fn;
+        }
+      })
+      .catch((error) => {
+        console.error('[Tablet] Failed to listen for touch gestures:', error);
+      });
+
+    return () => {
+      disposed = true;
+      unlisten?.();
+    };
+  }, []);
+}
@@ -3,24 +3,47 @@
  *
  * A modal panel for managing patterns:
  * - View all patterns in a grid
- * - Import .pat files
+ * - Import .pat files and images, or capture the selection as a pattern
+ * - Optional grayscale conversion and seamless edge blending
  * - Delete/rename patterns
  * - Organize into groups
  */
 
-import { useEffect, useState, useCallback } from 'react';
-import { X, Upload, Trash2, Edit2, FolderPlus, Search } from 'lucide-react';
+import { useEffect, useMemo, useState, useCallback } from 'react';
+import {
+  X,
+  Upload,
+  Trash2,
+  Edit2,
+  FolderPlus,
+  Search,
+  SquareDashed,
+  Contrast,
+  Grid3x3,
+  Eye,
+  Tag,
+} from 'lucide-react';
 import { open } from '@tauri-apps/plugin-dialog';
 import { LZ4Image } from '@/components/common/LZ4Image';
 import {
   usePatternLibraryStore,
   useGroupedPatterns,
   getPatternThumbnailUrl,
+  PatternCreateOptions,
+  PatternGroup,
   PatternResource,
+  PatternSort,
 } from '@/stores/pattern';
+import { useDocumentStore } from '@/stores/document';
+import { useSelectionStore } from '@/stores/selection';
 import { useI18n } from '@/i18n';
 import './PatternLibraryPanel.css';
 
+const IMAGE_EXTENSIONS = ['png', 'jpg', 'jpeg', 'webp', 'tif', 'tiff'];
+
+/** Edge band crossfaded when "seamless" is on (capped at half the pattern size) */
+const SEAMLESS_BLEND_EDGES = 32;
+
 interface PatternLibraryPanelProps {
   isOpen: boolean;
   onClose: () => void;
@@ -34,7 +57,8 @@ export function PatternLibraryPanel({
   onSelect,
 }: PatternLibraryPanelProps): JSX.Element | null {
   const { t } = useI18n();
-  const patternCount = usePatternLibraryStore((s) => s.patterns.length);
+  const patterns = usePatternLibraryStore((s) => s.patterns);
+  const patternCount = patterns.length;
   const {
     isLoading,
     error,
@@ -42,8 +66,13 @@ export function PatternLibraryPanel({
     setSearchQuery,
     loadPatterns,
     importPatFile,
+    addPatternFromImage,
+    addPatternFromRegion,
+    getPatternOffsetPreview,
     deletePattern,
     renamePattern,
+    setPatternTags,
+    searchPatterns,
     clearError,
   } = usePatternLibraryStore();
 
@@ -51,6 +80,43 @@ export function PatternLibraryPanel({
   const [selectedId, setSelectedId] = useState<string | null>(null);
   const [editingId, setEditingId] = useState<string | null>(null);
   const [editName, setEditName] = useState('');
+  const [grayscale, setGrayscale] = useState(false);
+  const [seamless, setSeamless] = useState(false);
+  const [previewUrl, setPreviewUrl] = useState<string | null>(null);
+  const [sort, setSort] = useState<PatternSort>('relevance');
+  const [sortedPatterns, setSortedPatterns] = useState<PatternResource[] | null>(null);
+  const hasSelection = useSelectionStore((s) => s.hasSelection);
+  const activeLayerId = useDocumentStore((s) => s.activeLayerId);
+
+  const createOptions = useMemo<PatternCreateOptions>(
+    () => ({ grayscale, blendEdges: seamless ? SEAMLESS_BLEND_EDGES : null }),
+    [grayscale, seamless]
+  );
+
+  // Non-default orderings come from the backend search as one flat list
+  useEffect(() => {
+    if (!isOpen || sort === 'relevance') {
+      setSortedPatterns(null);
+      return;
+    }
+    let cancelled = false;
+    searchPatterns(searchQuery, { sort })
+      .then((results) => {
+        if (!cancelled) setSortedPatterns(results);
+      })
+      .catch((e) => console.error('Pattern search failed:', e));
+    return () => {
+      cancelled = true;
+    };
+  }, [isOpen, sort, searchQuery, patterns, searchPatterns]);
+
+  const displayedGroups = useMemo<PatternGroup[]>(
+    () =>
+      sortedPatterns
+        ? [{ name: t(`patternLibrary.sort.${sort}`), patterns: sortedPatterns }]
+        : groupedPatterns,
+    [sortedPatterns, groupedPatterns, sort, t]
+  );
 
   // Load patterns on open
   useEffect(() => {
@@ -64,19 +130,69 @@ export function PatternLibraryPanel({
     try {
       const selected = await open({
         multiple: true,
-        filters: [{ name: 'Pattern Files', extensions: ['pat'] }],
+        filters: [
+          { name: 'Patterns and Images', extensions: ['pat', ...IMAGE_EXTENSIONS] },
+          { name: 'Pattern Files', extensions: ['pat'] },
+          { name: 'Images', extensions: IMAGE_EXTENSIONS },
+        ],
       });
 
       if (selected) {
         const paths = Array.isArray(selected) ? selected : [selected];
         for (const path of paths) {
-          await importPatFile(path);
+          if (path.toLowerCase().endsWith('.pat')) {
+            await importPatFile(path);
+          } else {
+            await addPatternFromImage(path, createOptions);
+          }
         }
       }
     } catch (e) {
       console.error('Import failed:', e);
     }
-  }, [importPatFile]);
+  }, [importPatFile, addPatternFromImage, createOptions]);
+
+  // Capture the selection bounds of the active layer, clipped to the layer
+  // (layers cover the whole document)
+  const handleAddFromSelection = async () => {
+    const bounds = useSelectionStore.getState().bounds;
+    if (!activeLayerId || !bounds) {
+      return;
+    }
+    const { width: layerWidth, height: layerHeight } = useDocumentStore.getState();
+    const left = Math.max(0, Math.floor(bounds.x));
+    const top = Math.max(0, Math.floor(bounds.y));
+    const right = Math.min(layerWidth, Math.ceil(bounds.x + bounds.width));
+    const bottom = Math.min(layerHeight, Math.ceil(bounds.y + bounds.height));
+    if (right <= left || bottom <= top) {
+      return;
+    }
+    try {
+      const result = await addPatternFromRegion(
+        activeLayerId,
+        { x: left, y: top, width: right - left, height: bottom - top },
+        createOptions
+      );
+      setSelectedId(result.pattern.id);
+    } catch (e) {
+      console.error('Add from selection failed:', e);
+    }
+  };
+
+  const handleTogglePreview = async () => {
+    if (previewUrl) {
+      setPreviewUrl(null);
+      return;
+    }
+    if (!selectedId) {
+      return;
+    }
+    try {
+      setPreviewUrl(await getPatternOffsetPreview(selectedId));
+    } catch (e) {
+      console.error('Tiling preview failed:', e);
+    }
+  };
 
   // Handle delete
   const handleDelete = useCallback(async () => {
@@ -107,6 +223,18 @@ export function PatternLibraryPanel({
     }
   };
 
+  const handleEditTags = async () => {
+    const pattern = patterns.find((p) => p.id === selectedId);
+    if (!pattern) return;
+    const input = window.prompt(t('patternLibrary.prompt.tags'), (pattern.tags ?? []).join(', '));
+    if (input === null) return;
+    try {
+      await setPatternTags(pattern.id, input.split(','));
+    } catch (e) {
+      console.error('Set tags failed:', e);
+    }
+  };
+
   // Handle selection
   const handlePatternClick = (pattern: PatternResource) => {
     setSelectedId(pattern.id);
@@ -147,6 +275,18 @@ export function PatternLibraryPanel({
               onChange={(e) => setSearchQuery(e.target.value)}
             />
           </div>
+          <select
+            className="pattern-library-sort"
+            value={sort}
+            onChange={(e) => setSort(e.target.value as PatternSort)}
+            title={t('patternLibrary.sortBy')}
+          >
+            {(['relevance', 'name', 'recentlyUsed', 'size'] as const).map((value) => (
+              <option key={value} value={value}>
+                {t(`patternLibrary.sort.${value}`)}
+              </option>
+            ))}
+          </select>
 
           <div className="pattern-library-actions">
             <button
@@ -157,6 +297,36 @@ export function PatternLibraryPanel({
               <Upload size={14} />
               {t('patternLibrary.import')}
             </button>
+            <button
+              className="pattern-library-btn"
+              onClick={handleAddFromSelection}
+              disabled={!hasSelection || !activeLayerId}
+              title={t('patternLibrary.addFromSelection')}
+            >
+              <SquareDashed size={14} />
+            </button>
+            <button
+              className={`pattern-library-btn ${grayscale ? 'active' : ''}`}
+              onClick={() => setGrayscale((value) => !value)}
+              title={t('patternLibrary.grayscale')}
+            >
+              <Contrast size={14} />
+            </button>
+            <button
+              className={`pattern-library-btn ${seamless ? 'active' : ''}`}
+              onClick={() => setSeamless((value) => !value)}
+              title={t('patternLibrary.makeSeamless')}
+            >
+              <Grid3x3 size={14} />
+            </button>
+            <button
+              className={`pattern-library-btn ${previewUrl ? 'active' : ''}`}
+              onClick={handleTogglePreview}
+              disabled={!selectedId && !previewUrl}
+              title={t('patternLibrary.previewTiling')}
+            >
+              <Eye size={14} />
+            </button>
             <button
               className="pattern-library-btn"
               onClick={handleDelete}
@@ -168,9 +338,7 @@ export function PatternLibraryPanel({
             <button
               className="pattern-library-btn"
               onClick={() => {
-                const pattern = groupedPatterns
-                  .flatMap((g) => g.patterns)
-                  .find((p) => p.id === selectedId);
+                const pattern = patterns.find((p) => p.id === selectedId);
                 if (pattern) handleRenameStart(pattern);
               }}
               disabled={!selectedId}
@@ -178,6 +346,14 @@ export function PatternLibraryPanel({
             >
               <Edit2 size={14} />
             </button>
+            <button
+              className="pattern-library-btn"
+              onClick={handleEditTags}
+              disabled={!selectedId}
+              title={t('patternLibrary.editTags')}
+            >
+              <Tag size={14} />
+            </button>
           </div>
         </div>
 
@@ -189,11 +365,17 @@ export function PatternLibraryPanel({
           </div>
         )}
 
+        {previewUrl && (
+          <div className="pattern-library-preview" onClick={() => setPreviewUrl(null)}>
+            <img src={previewUrl} alt={t('patternLibrary.previewTiling')} />
+          </div>
+        )}
+
         {/* Content */}
         <div className="pattern-library-content">
           {isLoading ? (
             <div className="pattern-library-loading">{t('patternLibrary.loading')}</div>
-          ) : groupedPatterns.length === 0 ? (
+          ) : displayedGroups.length === 0 ? (
             <div className="pattern-library-empty">
               <FolderPlus size={48} strokeWidth={1} />
               <h3>{t('patternLibrary.noPatterns')}</h3>
@@ -204,7 +386,7 @@ export function PatternLibraryPanel({
               </button>
             </div>
           ) : (
-            groupedPatterns.map((group) => (
+            displayedGroups.map((group) => (
               <div key={group.name} className="pattern-group">
                 <div className="pattern-group-header">
                   <span>{group.name}</span>
@@ -217,7 +399,11 @@ export function PatternLibraryPanel({
                       className={`pattern-grid-item ${pattern.id === selectedId ? 'selected' : ''}`}
                       onClick={() => handlePatternClick(pattern)}
                       onDoubleClick={() => handlePatternDoubleClick(pattern)}
-                      title={`${pattern.name}\n${pattern.width}×${pattern.height} ${pattern.mode}`}
+                      title={[
+                        pattern.name,
+                        `${pattern.width}×${pattern.height} ${pattern.mode}`,
+                        ...(pattern.tags?.length ? [pattern.tags.join(', ')] : []),
+                      ].join('\n')}
                     >
                       <div className="pattern-thumbnail">
                         <LZ4Image
@@ -66,6 +66,9 @@ function getBackpressureModeLabel(mode: InputBackpressureMode, t: (key: string)
   if (mode === 'latency_capped') {
     return t('settings.tablet.inputPipeline.latencyCapped');
   }
+  if (mode === 'coalescing') {
+    return t('settings.tablet.inputPipeline.coalescing');
+  }
   return t('settings.tablet.inputPipeline.lossless');
 }
 
@@ -485,6 +488,7 @@ function TabletSettings() {
   const backendSwitchOptions = {
     pollingRate: tablet.pollingRate,
     pressureCurve: tablet.pressureCurve,
+    pressureCurvePoints: tablet.pressureCurvePoints,
     backpressureMode: tablet.backpressureMode,
   };
 
@@ -743,6 +747,7 @@ function TabletSettings() {
             <option value="latency_capped">
               {t('settings.tablet.inputPipeline.latencyCapped')}
             </option>
+            <option value="coalescing">{t('settings.tablet.inputPipeline.coalescing')}</option>
           </select>
         </div>
         <div className="settings-row">
@@ -755,6 +760,10 @@ function TabletSettings() {
           <span>{t('settings.tablet.inputPipeline.queueDropped')}</span>
           <span>{queueMetrics.dropped}</span>
         </div>
+        <div className="settings-row">
+          <span>{t('settings.tablet.inputPipeline.queueCoalesced')}</span>
+          <span>{queueMetrics.coalesced ?? 0}</span>
+        </div>
         <div className="settings-row">
           <span>{t('settings.tablet.inputPipeline.queueDepth')}</span>
           <span>
@@ -1,6 +1,7 @@
 import { create } from 'zustand';
-import { invoke } from '@tauri-apps/api/core';
+import { Channel, invoke } from '@tauri-apps/api/core';
 import { useToolStore } from '@/stores/tool';
+import { usePatternLibraryStore } from '@/stores/pattern';
 import { prewarmBrushTextures } from '@/utils/brushLoader';
 import {
   useSettingsStore,
@@ -26,6 +27,47 @@ export interface BrushLibraryPreset extends BrushPreset {
   group: string | null;
   source: string;
   contentHash: string;
+  tags: string[];
+  favorite: boolean;
+  useCount: number;
+  lastUsedMs?: number | null;
+  modifiedMs?: number | null;
+}
+
+export type BrushPresetSort = 'relevance' | 'name' | 'recentlyUsed' | 'mostUsed';
+
+export interface BrushPresetSearchFilters {
+  tags?: string[];
+  group?: string | null;
+  favoritesOnly?: boolean;
+  textured?: boolean | null;
+  dual?: boolean | null;
+  computed?: boolean | null;
+  minSize?: number | null;
+  maxSize?: number | null;
+  /** 'recentlyUsed' leaves out presets that were never used */
+  sort?: BrushPresetSort;
+  limit?: number | null;
+}
+
+export type BrushPresetRevisionKind = 'original' | 'save' | 'revert';
+
+/** Revision 0 is the original import snapshot and is never evicted */
+export const ORIGINAL_PRESET_REVISION = 0;
+
+export interface BrushPresetRevisionSummary {
+  revision: number;
+  savedAtMs: number;
+  kind: BrushPresetRevisionKind;
+  revertedFrom: number | null;
+  /** Fields changed since the previous revision (dotted camelCase paths) */
+  changedFields: string[];
+}
+
+export interface BrushPresetFieldChange {
+  field: string;
+  before: unknown;
+  after: unknown;
 }
 
 export interface BrushLibraryGroup {
@@ -47,6 +89,91 @@ export interface BrushLibraryImportResult {
   snapshot: BrushLibrarySnapshot;
 }
 
+export interface BrushBundleImportResult extends BrushLibraryImportResult {
+  importedPatternCount: number;
+  skippedPatternCount: number;
+}
+
+export interface BulkImportFileError {
+  path: string;
+  error: string;
+}
+
+export interface BulkImportProgress {
+  completed: number;
+  total: number;
+  path: string;
+  error: string | null;
+}
+
+export interface BulkImportResult extends BrushLibraryImportResult {
+  importedPatternCount: number;
+  skippedPatternCount: number;
+  importedFileCount: number;
+  errors: BulkImportFileError[];
+}
+
+export interface BrushBundleExportResult {
+  presetCount: number;
+  tipCount: number;
+  patternCount: number;
+}
+
+export interface LibrarySyncReport {
+  pulledPresets: number;
+  pushedPresets: number;
+  deletedLocalPresets: number;
+  deletedRemotePresets: number;
+  /** Conflict copies created for presets edited on both sides */
+  conflicts: number;
+  pulledTips: number;
+  pushedTips: number;
+  pulledPatterns: number;
+  pushedPatterns: number;
+  deletedLocalPatterns: number;
+  deletedRemotePatterns: number;
+}
+
+export interface LibrarySyncState {
+  replicaDir: string | null;
+  lastSyncedMs: number | null;
+}
+
+export type LibraryIssueKind =
+  | 'missingTipBlob'
+  | 'missingTip'
+  | 'missingPattern'
+  | 'missingPatternBlob'
+  | 'danglingGroupEntry'
+  | 'ungroupedEntry'
+  | 'orphanTipBlob'
+  | 'orphanPatternBlob';
+
+export type LibraryRepair =
+  | 'relinkedByUuid'
+  | 'relinkedByName'
+  | 'restoredFromCache'
+  | 'groupsRebuilt'
+  | 'deleted';
+
+export interface LibraryIssue {
+  library: 'brushes' | 'patterns';
+  kind: LibraryIssueKind;
+  /** Preset, tip, pattern or bitmap ID with the problem */
+  id: string;
+  /** Missing tip or pattern, group, or thumbnail folder */
+  target: string | null;
+  /** How repair fixed it; null when left as is */
+  repair: LibraryRepair | null;
+  relinkedTo: string | null;
+}
+
+export interface LibraryIntegrityReport {
+  issues: LibraryIssue[];
+  repairedCount: number;
+  unresolvedCount: number;
+}
+
 interface BrushLibraryPresetPayload {
   preset: BrushPreset;
   tipId: string | null;
@@ -103,17 +230,43 @@ interface BrushLibraryState {
   groups: BrushLibraryGroup[];
   selectedPresetByTool: BrushPresetSelectionByTool;
   searchQuery: string;
+  /** Ids returned by `search_brush_presets` for `searchQuery`, best match first */
+  searchResultIds: string[] | null;
   hasLoaded: boolean;
   isLoading: boolean;
   error: string | null;
 
   loadLibrary: () => Promise<void>;
   importAbrFile: (path: string) => Promise<BrushLibraryImportResult>;
+  importBrushBundle: (path: string) => Promise<BrushBundleImportResult>;
+  /** Import .abr/.pat files, folders and .zip archives; one failing file never aborts the rest */
+  importBrushPacks: (
+    paths: string[],
+    onProgress?: (progress: BulkImportProgress) => void
+  ) => Promise<BulkImportResult>;
+  exportBrushBundle: (
+    path: string,
+    presetIds: string[],
+    groupNames?: string[]
+  ) => Promise<BrushBundleExportResult>;
+  syncWithFolder: (folder: string) => Promise<LibrarySyncReport>;
+  getSyncState: () => Promise<LibrarySyncState>;
+  /** Find dangling references and orphan bitmaps; `repair` fixes what it can */
+  verifyLibraries: (repair: boolean) => Promise<LibraryIntegrityReport>;
   renamePreset: (id: string, newName: string) => Promise<void>;
   deletePreset: (id: string) => Promise<void>;
   deleteGroup: (groupName: string) => Promise<void>;
   movePresetToGroup: (id: string, group: string) => Promise<void>;
   renameGroup: (oldName: string, newName: string) => Promise<void>;
+  setPresetTags: (id: string, tags: string[]) => Promise<void>;
+  setPresetFavorite: (id: string, favorite: boolean) => Promise<void>;
+  listPresetRevisions: (id: string) => Promise<BrushPresetRevisionSummary[]>;
+  diffPresetRevisions: (id: string, from: number, to: number) => Promise<BrushPresetFieldChange[]>;
+  revertPreset: (id: string, revision: number) => Promise<BrushLibraryPreset>;
+  searchPresets: (
+    query: string,
+    filters?: BrushPresetSearchFilters
+  ) => Promise<BrushLibraryPreset[]>;
   saveActivePreset: () => Promise<BrushLibraryPreset | null>;
   saveActivePresetAs: (
     newName: string,
@@ -134,6 +287,9 @@ function normalizePreset(preset: BrushLibraryPreset): BrushLibraryPreset {
     ...preset,
     tipId: preset.tipId ?? (preset.hasTexture ? preset.id : null),
     group: preset.group ?? null,
+    tags: preset.tags ?? [],
+    favorite: preset.favorite ?? false,
+    useCount: preset.useCount ?? 0,
   };
 }
 
@@ -370,6 +526,11 @@ export const useBrushLibraryStore = create<BrushLibraryState>((set, get) => {
     }
     set(nextState);
     persistSelectionToSettings(sanitizedSelection);
+    // Re-run an active search so new and renamed presets show up in the results
+    const { searchQuery, setSearchQuery } = get();
+    if (searchQuery.trim()) {
+      setSearchQuery(searchQuery);
+    }
     const prewarmCandidates = buildTexturePrewarmCandidates(snapshot);
     prewarmBrushTextures(prewarmCandidates, BRUSH_TEXTURE_PREWARM_LIMIT);
   }
@@ -379,12 +540,20 @@ export const useBrushLibraryStore = create<BrushLibraryState>((set, get) => {
     commitSnapshot(snapshot, selection);
   }
 
+  function replacePreset(updated: BrushLibraryPreset): void {
+    const next = normalizePreset(updated);
+    set((state) => ({
+      presets: state.presets.map((preset) => (preset.id === next.id ? next : preset)),
+    }));
+  }
+
   return {
     presets: [],
     tips: [],
     groups: [],
     selectedPresetByTool: readSelectionFromSettings(),
     searchQuery: '',
+    searchResultIds: null,
     hasLoaded: false,
     isLoading: false,
     error: null,
@@ -424,6 +593,97 @@ export const useBrushLibraryStore = create<BrushLibraryState>((set, get) => {
       }
     },
 
+    importBrushBundle: async (path: string) => {
+      set({ isLoading: true, error: null });
+      try {
+        const result = await invoke<BrushBundleImportResult>('import_brush_bundle', { path });
+        const normalized = {
+          ...result,
+          snapshot: normalizeSnapshot(result.snapshot),
+        };
+        commitSnapshot(normalized.snapshot, get().selectedPresetByTool, false);
+        if (result.importedPatternCount > 0) {
+          void usePatternLibraryStore.getState().loadPatterns();
+        }
+        return normalized;
+      } catch (err) {
+        set({ isLoading: false, error: String(err) });
+        throw err;
+      }
+    },
+
+    importBrushPacks: async (
+      paths: string[],
+      onProgress?: (progress: BulkImportProgress) => void
+    ) => {
+      set({ isLoading: true, error: null });
+      try {
+        const channel = new Channel<BulkImportProgress>();
+        if (onProgress) {
+          channel.onmessage = onProgress;
+        }
+        const result = await invoke<BulkImportResult>('import_brush_packs', {
+          paths,
+          options: buildImportAbrOptions(),
+          onProgress: channel,
+        });
+        const normalized = {
+          ...result,
+          snapshot: normalizeSnapshot(result.snapshot),
+        };
+        commitSnapshot(normalized.snapshot, get().selectedPresetByTool, false);
+        if (normalized.importedPatternCount > 0) {
+          void usePatternLibraryStore.getState().loadPatterns();
+        }
+        return normalized;
+      } catch (err) {
+        set({ isLoading: false, error: String(err) });
+        throw err;
+      }
+    },
+
+    exportBrushBundle: async (path: string, presetIds: string[], groupNames: string[] = []) => {
+      try {
+        return await invoke<BrushBundleExportResult>('export_brush_bundle', {
+          path,
+          presetIds,
+          groupNames,
+        });
+      } catch (err) {
+        set({ error: String(err) });
+        throw err;
+      }
+    },
+
+    syncWithFolder: async (folder: string) => {
+      set({ isLoading: true, error: null });
+      try {
+        const report = await invoke<LibrarySyncReport>('sync_libraries_with_folder', { folder });
+        const snapshot = await fetchLibrarySnapshot();
+        commitSnapshot(snapshot, get().selectedPresetByTool, false);
+        if (report.pulledPatterns > 0 || report.deletedLocalPatterns > 0) {
+          void usePatternLibraryStore.getState().loadPatterns();
+        }
+        return report;
+      } catch (err) {
+        set({ isLoading: false, error: String(err) });
+        throw err;
+      }
+    },
+
+    getSyncState: async () => invoke<LibrarySyncState>('get_library_sync_state'),
+
+    verifyLibraries: async (repair: boolean) => {
+      const report = await invoke<LibraryIntegrityReport>('verify_libraries', { repair });
+      if (report.repairedCount > 0) {
+        await reloadSnapshot(get().selectedPresetByTool);
+        if (report.issues.some((issue) => issue.library === 'patterns' && issue.repair)) {
+          void usePatternLibraryStore.getState().loadPatterns();
+        }
+      }
+      return report;
+    },
+
     renamePreset: async (id: string, newName: string) => {
       try {
         await invoke('rename_brush_preset', { id, newName });
@@ -449,6 +709,57 @@ export const useBrushLibraryStore = create<BrushLibraryState>((set, get) => {
       }
     },
 
+    setPresetTags: async (id: string, tags: string[]) => {
+      try {
+        replacePreset(await invoke<BrushLibraryPreset>('set_brush_preset_tags', { id, tags }));
+      } catch (err) {
+        set({ error: String(err) });
+        throw err;
+      }
+    },
+
+    setPresetFavorite: async (id: string, favorite: boolean) => {
+      try {
+        replacePreset(
+          await invoke<BrushLibraryPreset>('set_brush_preset_favorite', { id, favorite })
+        );
+      } catch (err) {
+        set({ error: String(err) });
+        throw err;
+      }
+    },
+
+    listPresetRevisions: async (id: string) =>
+      invoke<BrushPresetRevisionSummary[]>('list_brush_preset_revisions', { id }),
+
+    diffPresetRevisions: async (id: string, from: number, to: number) =>
+      invoke<BrushPresetFieldChange[]>('diff_brush_preset_revisions', { id, from, to }),
+
+    revertPreset: async (id: string, revision: number) => {
+      try {
+        const reverted = normalizePreset(
+          await invoke<BrushLibraryPreset>('revert_brush_preset', { id, revision })
+        );
+        replacePreset(reverted);
+        const tool = getSelectionToolFromToolStore();
+        if (getSelectedPresetIdForTool(get().selectedPresetByTool, tool) === id) {
+          applyPresetToToolStore(reverted, get().tips);
+        }
+        return reverted;
+      } catch (err) {
+        set({ error: String(err) });
+        throw err;
+      }
+    },
+
+    searchPresets: async (query: string, filters?: BrushPresetSearchFilters) => {
+      const results = await invoke<BrushLibraryPreset[]>('search_brush_presets', {
+        query,
+        filters: filters ?? null,
+      });
+      return results.map(normalizePreset);
+    },
+
     deleteGroup: async (groupName: string) => {
       try {
         await invoke('delete_brush_group', { groupName });
@@ -533,6 +844,12 @@ export const useBrushLibraryStore = create<BrushLibraryState>((set, get) => {
       applyPresetToToolStore(preset, tips);
       const nextSelection = setSelectedPresetIdForTool(get().selectedPresetByTool, tool, id);
       commitSelection(nextSelection);
+
+      void invoke<BrushLibraryPreset>('record_brush_preset_use', { id })
+        .then(replacePreset)
+        .catch((err) => {
+          console.warn('[BrushLibrary] Failed to record preset use:', err);
+        });
     },
 
     applyMainTip: (tipId: string | null) => {
@@ -585,7 +902,23 @@ export const useBrushLibraryStore = create<BrushLibraryState>((set, get) => {
       set({ selectedPresetByTool: fromSettings });
     },
 
-    setSearchQuery: (query: string) => set({ searchQuery: query }),
+    setSearchQuery: (query: string) => {
+      set({ searchQuery: query });
+      if (!query.trim()) {
+        set({ searchResultIds: null });
+        return;
+      }
+      void get()
+        .searchPresets(query)
+        .then((results) => {
+          if (get().searchQuery === query) {
+            set({ searchResultIds: results.map((preset) => preset.id) });
+          }
+        })
+        .catch((err) => {
+          console.warn('[BrushLibrary] Preset search failed:', err);
+        });
+    },
 
     clearError: () => set({ error: null }),
   };
@@ -599,17 +932,17 @@ export function useSelectedPresetIdForCurrentTool(): string | null {
 export function useFilteredBrushPresets(): BrushLibraryPreset[] {
   const presets = useBrushLibraryStore((state) => state.presets);
   const searchQuery = useBrushLibraryStore((state) => state.searchQuery);
+  const searchResultIds = useBrushLibraryStore((state) => state.searchResultIds);
 
-  const query = searchQuery.trim().toLowerCase();
-  if (!query) {
+  // Until the first backend search for a query returns, keep showing everything
+  if (!searchQuery.trim() || !searchResultIds) {
     return presets;
   }
 
-  return presets.filter((preset) => {
-    const nameMatch = preset.name.toLowerCase().includes(query);
-    const groupMatch = (preset.group ?? '').toLowerCase().includes(query);
-    return nameMatch || groupMatch;
-  });
+  const presetMap = new Map(presets.map((preset) => [preset.id, preset]));
+  return searchResultIds
+    .map((id) => presetMap.get(id))
+    .filter((preset): preset is BrushLibraryPreset => !!preset);
 }
 
 export interface GroupedBrushPresets {
@@ -36,6 +36,10 @@ export interface PatternResource {
   source: string;
   /** Group name (optional) */
   group: string | null;
+  /** User tags */
+  tags?: string[];
+  /** Last time the pattern was picked (Unix ms) */
+  last_used_ms?: number | null;
 }
 
 /** Import result from .pat file */
@@ -56,6 +60,44 @@ export interface AddPatternFromBrushResult {
   pattern: PatternResource;
 }
 
+/** Pixel rectangle within a layer */
+export interface PatternRect {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+/** Options for creating a pattern from an image file or canvas region */
+export interface PatternCreateOptions {
+  /** Display name (defaults to the file name or "Canvas Pattern") */
+  name?: string;
+  /** Group (defaults to "From Images" or "From Canvas") */
+  group?: string;
+  /** Store as a grayscale pattern */
+  grayscale?: boolean;
+  /** Crossfade this many edge pixels so the pattern tiles; shrinks the pattern */
+  blendEdges?: number | null;
+}
+
+/** Result ordering for pattern search */
+export type PatternSort = 'relevance' | 'name' | 'recentlyUsed' | 'size';
+
+/** Filters for pattern search; sizes bound the larger side in pixels */
+export interface PatternSearchFilters {
+  tags?: string[];
+  group?: string | null;
+  mode?: PatternMode | null;
+  minSize?: number | null;
+  maxSize?: number | null;
+  sort?: PatternSort;
+  limit?: number | null;
+}
+
+type LayerExportWindow = Window & {
+  __getLayerImageBytes?: (layerId: string) => Promise<number[] | undefined>;
+};
+
 /** Pattern group with patterns */
 export interface PatternGroup {
   name: string;
@@ -83,6 +125,19 @@ interface PatternLibraryState {
   importPatFile: (path: string) => Promise<PatternImportResult>;
   /** Add current brush-attached pattern to library */
   addPatternFromBrush: (patternId: string, name?: string) => Promise<AddPatternFromBrushResult>;
+  /** Add an image file (PNG/JPEG/WebP/TIFF) as a pattern */
+  addPatternFromImage: (
+    path: string,
+    options?: PatternCreateOptions
+  ) => Promise<AddPatternFromBrushResult>;
+  /** Add a region of a layer (in layer pixels) as a pattern */
+  addPatternFromRegion: (
+    layerId: string,
+    rect: PatternRect,
+    options?: PatternCreateOptions
+  ) => Promise<AddPatternFromBrushResult>;
+  /** PNG data URL of a pattern wrapped by half its size, to check its seams */
+  getPatternOffsetPreview: (id: string) => Promise<string>;
   /** Delete a pattern */
   deletePattern: (id: string) => Promise<void>;
   /** Rename a pattern */
@@ -91,12 +146,28 @@ interface PatternLibraryState {
   moveToGroup: (id: string, group: string) => Promise<void>;
   /** Rename a group */
   renameGroup: (oldName: string, newName: string) => Promise<void>;
+  /** Replace a pattern's tags */
+  setPatternTags: (id: string, tags: string[]) => Promise<void>;
+  /** Remember that a pattern was picked (fire-and-forget) */
+  recordPatternUse: (id: string) => void;
+  /** Fuzzy search by name, tag and group with filters */
+  searchPatterns: (query: string, filters?: PatternSearchFilters) => Promise<PatternResource[]>;
   /** Set search query */
   setSearchQuery: (query: string) => void;
   /** Clear error */
   clearError: () => void;
 }
 
+function upsertPattern(patterns: PatternResource[], pattern: PatternResource): PatternResource[] {
+  const index = patterns.findIndex((p) => p.id === pattern.id);
+  if (index >= 0) {
+    const next = patterns.slice();
+    next[index] = pattern;
+    return next;
+  }
+  return [...patterns, pattern];
+}
+
 export const usePatternLibraryStore = create<PatternLibraryState>((set, get) => ({
   patterns: [],
   isLoading: false,
@@ -133,16 +204,45 @@ export const usePatternLibraryStore = create<PatternLibraryState>((set, get) =>
         name,
       });
 
-      set((state) => {
-        const index = state.patterns.findIndex((p) => p.id === result.pattern.id);
-        if (index >= 0) {
-          const next = state.patterns.slice();
-          next[index] = result.pattern;
-          return { patterns: next };
-        }
-        return { patterns: [...state.patterns, result.pattern] };
+      set((state) => ({ patterns: upsertPattern(state.patterns, result.pattern) }));
+
+      return result;
+    } catch (e) {
+      set({ error: String(e) });
+      throw e;
+    }
+  },
+
+  addPatternFromImage: async (path: string, options?: PatternCreateOptions) => {
+    try {
+      const result = await invoke<AddPatternFromBrushResult>('add_pattern_from_image', {
+        path,
+        options,
       });
+      set((state) => ({ patterns: upsertPattern(state.patterns, result.pattern) }));
+      return result;
+    } catch (e) {
+      set({ error: String(e) });
+      throw e;
+    }
+  },
 
+  addPatternFromRegion: async (
+    layerId: string,
+    rect: PatternRect,
+    options?: PatternCreateOptions
+  ) => {
+    try {
+      const layerImage = await (window as LayerExportWindow).__getLayerImageBytes?.(layerId);
+      if (!layerImage) {
+        throw new Error(`Layer image unavailable: ${layerId}`);
+      }
+      const result = await invoke<AddPatternFromBrushResult>('add_pattern_from_region', {
+        layerImage,
+        rect,
+        options,
+      });
+      set((state) => ({ patterns: upsertPattern(state.patterns, result.pattern) }));
       return result;
     } catch (e) {
       set({ error: String(e) });
@@ -150,6 +250,9 @@ export const usePatternLibraryStore = create<PatternLibraryState>((set, get) =>
     }
   },
 
+  getPatternOffsetPreview: async (id: string) =>
+    invoke<string>('get_pattern_offset_preview', { id }),
+
   deletePattern: async (id: string) => {
     try {
       await invoke('delete_pattern', { id });
@@ -202,6 +305,27 @@ export const usePatternLibraryStore = create<PatternLibraryState>((set, get) =>
     }
   },
 
+  setPatternTags: async (id: string, tags: string[]) => {
+    try {
+      const pattern = await invoke<PatternResource>('set_pattern_tags', { id, tags });
+      set((state) => ({ patterns: upsertPattern(state.patterns, pattern) }));
+    } catch (e) {
+      set({ error: String(e) });
+      throw e;
+    }
+  },
+
+  recordPatternUse: (id: string) => {
+    void invoke<PatternResource>('record_pattern_use', { id })
+      .then((pattern) => set((state) => ({ patterns: upsertPattern(state.patterns, pattern) })))
+      .catch((err) => {
+        console.warn('[PatternLibrary] Failed to record pattern use:', err);
+      });
+  },
+
+  searchPatterns: async (query: string, filters?: PatternSearchFilters) =>
+    invoke<PatternResource[]>('search_patterns', { query, filters: filters ?? null }),
+
   setSearchQuery: (query: string) => set({ searchQuery: query }),
 
   clearError: () => set({ error: null }),
@@ -220,7 +344,10 @@ export function useFilteredPatterns(): PatternResource[] {
 
   const lowerQuery = query.toLowerCase();
   return patterns.filter(
-    (p) => p.name.toLowerCase().includes(lowerQuery) || p.group?.toLowerCase().includes(lowerQuery)
+    (p) =>
+      p.name.toLowerCase().includes(lowerQuery) ||
+      p.group?.toLowerCase().includes(lowerQuery) ||
+      p.tags?.some((tag) => tag.toLowerCase().includes(lowerQuery))
   );
 }
 
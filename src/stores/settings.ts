@@ -80,7 +80,7 @@ export interface TabletSettings {
   maxBrushSpeedPxPerMs: number;
   brushSpeedSmoothingSamples: number;
   lowPressureAdaptiveSmoothingEnabled: boolean;
-  backpressureMode: 'lossless' | 'latency_capped';
+  backpressureMode: 'lossless' | 'latency_capped' | 'coalescing';
   autoStart: boolean;
 }
 
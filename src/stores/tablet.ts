@@ -3,6 +3,12 @@ import { invoke } from '@tauri-apps/api/core';
 import { listen, UnlistenFn } from '@tauri-apps/api/event';
 import { detectPlatformKind } from '@/utils/platform';
 import { isTabletInputTraceEnabled, logTabletTrace } from '@/utils/tabletTrace';
+import {
+  getCustomPressureCurvePoints,
+  type PressureCurveControlPoint,
+} from '@/utils/pressureCurve';
+import { useToolStore, type ToolType } from '@/stores/tool';
+import { useBrushLibraryStore } from '@/stores/brushLibrary';
 
 function resolveDefaultRequestedBackend(): BackendType {
   const platformKind = detectPlatformKind();
@@ -15,8 +21,8 @@ const DEFAULT_REQUESTED_BACKEND: BackendType = resolveDefaultRequestedBackend();
 
 // Types matching Rust backend
 export type TabletStatus = 'Disconnected' | 'Connected' | 'Error';
-export type BackendType = 'wintab' | 'macnative' | 'pointerevent';
-export type InputBackpressureMode = 'lossless' | 'latency_capped';
+export type BackendType = 'wintab' | 'macnative' | 'linuxevdev' | 'pointerevent';
+export type InputBackpressureMode = 'lossless' | 'latency_capped' | 'coalescing';
 
 export interface TabletInfo {
   name: string;
@@ -30,6 +36,7 @@ export interface InputQueueMetrics {
   enqueued: number;
   dequeued: number;
   dropped: number;
+  coalesced: number;
   max_depth: number;
   current_depth: number;
   latency_p50_us: number;
@@ -57,6 +64,7 @@ export interface TabletStatusResponse {
   backpressure_mode: InputBackpressureMode;
   queue_metrics: InputQueueMetrics;
   v3_diagnostics: TabletV3Diagnostics;
+  known_tools: KnownTabletToolV3[];
   info: TabletInfo | null;
 }
 
@@ -68,11 +76,39 @@ interface TabletStatusStatePatch {
   backpressureMode: InputBackpressureMode;
   queueMetrics: InputQueueMetrics;
   v3Diagnostics: TabletV3Diagnostics;
+  knownTools: KnownTabletToolV3[];
   info: TabletInfo | null;
 }
 
-export type InputSource = 'wintab' | 'pointerevent' | 'macnative';
+export type InputSource = 'wintab' | 'pointerevent' | 'macnative' | 'linuxevdev';
 export type InputPhase = 'hover' | 'down' | 'move' | 'up';
+export type TabletToolType = 'pen' | 'eraser' | 'airbrush' | 'mouse' | 'touch';
+
+export interface TabletToolChangeV3 {
+  pointer_id: number;
+  tool_type: TabletToolType;
+  tool_serial?: number | null;
+  profile_key: string;
+  tool_id?: string | null;
+  brush_preset_id?: string | null;
+}
+
+export interface KnownTabletToolV3 {
+  tool_type: TabletToolType;
+  tool_serial: number | null;
+  pointer_id: number;
+  device_id: string;
+  source: InputSource;
+  profile_key: string;
+  last_seen_us: number;
+}
+
+/** Per-tool settings persisted by the backend, keyed by `profile_key` */
+export interface TabletToolProfileV1 {
+  pressureCurve?: unknown;
+  toolId?: string;
+  brushPresetId?: string;
+}
 
 export interface NativeTabletEventV3 {
   seq: number;
@@ -89,6 +125,119 @@ export interface NativeTabletEventV3 {
   rotation_deg: number;
   host_time_us: number;
   device_time_us?: number | null;
+  tool_type?: TabletToolType;
+  tool_serial?: number | null;
+  wheel_0_1?: number | null;
+  distance_0_1?: number | null;
+  /** Present when congestion merged several Move samples into this one */
+  coalesced?: { sample_count: number; max_pressure_0_1: number } | null;
+  /** Extrapolated ahead of the real sample with the same `seq` */
+  predicted?: boolean;
+  /** 1-based position of a predicted sample after that real sample */
+  prediction_index?: number | null;
+}
+
+export type TabletCursorControlV1 = 'off' | 'penTilt' | 'rotation';
+
+export interface TabletCursorPoseConfigV1 {
+  angleControl: TabletCursorControlV1;
+  roundnessControl: TabletCursorControlV1;
+  minimumRoundness: number;
+}
+
+/** Applied on top of the brush angle/roundness while the pen hovers */
+export interface TabletCursorPoseV1 {
+  angle_offset_deg: number;
+  roundness_scale: number;
+  opacity: number;
+}
+
+/** Throttled latest-value hover sample (`tablet-hover-v1`), separate from stroke input */
+export interface TabletHoverSampleV1 {
+  pointer_id: number;
+  tool_type: TabletToolType;
+  x_px: number;
+  y_px: number;
+  distance_0_1: number | null;
+  tilt_x_deg: number;
+  tilt_y_deg: number;
+  rotation_deg: number;
+  host_time_us: number;
+  cursor: TabletCursorPoseV1;
+}
+
+export interface TabletButtonEventV3 {
+  kind: 'pen_barrel' | 'express_key';
+  /** Barrel buttons count from 1, express keys from 0 */
+  index: number;
+  pressed: boolean;
+  device_id: string;
+  host_time_us: number;
+}
+
+export interface TabletDialEventV3 {
+  kind: 'touch_ring' | 'touch_strip';
+  index: number;
+  /** null once the finger lifts */
+  position_0_1: number | null;
+  delta: number;
+  device_id: string;
+  host_time_us: number;
+}
+
+export type TabletActionV1 =
+  | 'undo'
+  | 'redo'
+  | 'colorPick'
+  | 'toggleEraser'
+  | 'brushSizeUp'
+  | 'brushSizeDown'
+  | 'brushSize';
+
+/** Bound action fired by a pen button, express key or touch ring (`tablet-action-v1`) */
+export interface TabletActionEventV1 {
+  action: TabletActionV1;
+  pressed: boolean;
+  /** Signed ring/strip travel for `brushSize`, otherwise 0 */
+  amount: number;
+  control: string;
+}
+
+export type TouchGesturePhaseV1 = 'begin' | 'update' | 'end';
+
+/** Touch gesture recognized by the backend (`tablet-gesture-v1`); never part of a stroke */
+export type TouchGestureV1 =
+  | {
+      kind: 'transform';
+      phase: TouchGesturePhaseV1;
+      center_x_px: number;
+      center_y_px: number;
+      /** Deltas relative to the previous update */
+      pan_x_px: number;
+      pan_y_px: number;
+      scale: number;
+      rotation_deg: number;
+    }
+  | { kind: 'tap'; fingers: number };
+
+/** Clockwise rotation of the physical tablet ('180' for left-handed use) */
+export type TabletRotationV1 = '0' | '90' | '180' | '270';
+
+/** Rectangle in normalized tablet coordinates (0..1, origin top-left) */
+export interface NormalizedRectV1 {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+/** How the tablet surface maps onto the screen (WinTab and evdev backends) */
+export interface TabletMappingV1 {
+  activeArea: NormalizedRectV1;
+  rotation: TabletRotationV1;
+  lockAspect: boolean;
+  /** Index into the system monitor list; omitted keeps the backend default */
+  monitor?: number;
 }
 
 export interface RawInputPoint {
@@ -115,6 +264,10 @@ export interface TabletInputPoint
 
 export type TabletEventV3 =
   | { Input: NativeTabletEventV3 }
+  | { Predicted: NativeTabletEventV3 }
+  | { ToolChanged: TabletToolChangeV3 }
+  | { Button: TabletButtonEventV3 }
+  | { Dial: TabletDialEventV3 }
   | 'ProximityEnter'
   | 'ProximityLeave'
   | { StatusChanged: TabletStatus };
@@ -144,6 +297,8 @@ const POINT_BUFFER_SIZE = 512;
 // Shared sample buffer (kept outside Zustand to avoid high-frequency re-rendering).
 let pointBuffer: TabletInputPoint[] = [];
 let pointBufferEpoch = 0;
+// Predicted samples past the newest buffered point; replaced by every real sample.
+let predictedTail: TabletInputPoint[] = [];
 let nativeTraceStrokeActive = false;
 const SEQ_REWIND_RESET_THRESHOLD = 32;
 const SEQ_REWIND_LOW_SEQ_MAX = 8;
@@ -254,9 +409,36 @@ export function getLatestPoint(): TabletInputPoint | null {
 
 export function clearPointBuffer(): void {
   pointBuffer = [];
+  predictedTail = [];
   bumpPointBufferEpoch();
 }
 
+/**
+ * Provisional samples extrapolated past the newest real point of the active stroke,
+ * in prediction order. Empty once a newer real sample or the stroke end arrived.
+ */
+export function getPredictedTail(): readonly TabletInputPoint[] {
+  return predictedTail;
+}
+
+function addPredictedPoint(point: TabletInputPoint): void {
+  const latest = pointBuffer[pointBuffer.length - 1];
+  // Only predictions extending the newest real sample of a stroke in progress
+  if (
+    !latest ||
+    latest.phase === 'up' ||
+    latest.seq !== point.seq ||
+    latest.stroke_id !== point.stroke_id
+  ) {
+    return;
+  }
+  const previous = predictedTail[predictedTail.length - 1];
+  if (previous && (previous.prediction_index ?? 0) >= (point.prediction_index ?? 0)) {
+    predictedTail = [];
+  }
+  predictedTail.push(point);
+}
+
 export function getPointBufferEpoch(): number {
   return pointBufferEpoch;
 }
@@ -270,6 +452,7 @@ function toTabletStatusStatePatch(response: TabletStatusResponse): TabletStatusS
     backpressureMode: response.backpressure_mode,
     queueMetrics: response.queue_metrics,
     v3Diagnostics: response.v3_diagnostics,
+    knownTools: response.known_tools ?? [],
     info: response.info,
   };
 }
@@ -282,6 +465,10 @@ interface TabletState {
   backpressureMode: InputBackpressureMode;
   queueMetrics: InputQueueMetrics;
   v3Diagnostics: TabletV3Diagnostics;
+  knownTools: KnownTabletToolV3[];
+  activeTool: TabletToolChangeV3 | null;
+  /** Only replaced when the pose changes, so cursor consumers re-render rarely */
+  cursorPose: TabletCursorPoseV1 | null;
   bufferEpoch: number;
   nativeIngressTick: number;
   info: TabletInfo | null;
@@ -296,6 +483,7 @@ interface TabletState {
     backend?: BackendType;
     pollingRate?: number;
     pressureCurve?: string;
+    pressureCurvePoints?: PressureCurveControlPoint[];
     backpressureMode?: InputBackpressureMode;
   }) => Promise<void>;
   switchBackend: (
@@ -303,6 +491,7 @@ interface TabletState {
     options?: {
       pollingRate?: number;
       pressureCurve?: string;
+      pressureCurvePoints?: PressureCurveControlPoint[];
       backpressureMode?: InputBackpressureMode;
     }
   ) => Promise<boolean>;
@@ -319,6 +508,7 @@ const EMPTY_QUEUE_METRICS: InputQueueMetrics = {
   enqueued: 0,
   dequeued: 0,
   dropped: 0,
+  coalesced: 0,
   max_depth: 0,
   current_depth: 0,
   latency_p50_us: 0,
@@ -356,6 +546,7 @@ function normalizeRotationDeg(value: number): number {
 function normalizeInputSource(value: string): InputSource {
   if (value === 'wintab' || value === 'win_tab') return 'wintab';
   if (value === 'macnative' || value === 'mac_native') return 'macnative';
+  if (value === 'linuxevdev' || value === 'linux_evdev') return 'linuxevdev';
   if (value === 'pointerevent' || value === 'pointer_event') return 'pointerevent';
   return 'pointerevent';
 }
@@ -365,6 +556,53 @@ function normalizeDeviceTimeUs(value: number | null | undefined, hostTimeUs: num
   return Math.max(0, Math.round(value));
 }
 
+const TABLET_BINDABLE_TOOLS: ReadonlySet<string> = new Set<ToolType>([
+  'brush',
+  'eraser',
+  'eyedropper',
+  'gradient',
+  'move',
+  'select',
+  'lasso',
+  'zoom',
+]);
+
+let latestHoverSample: TabletHoverSampleV1 | null = null;
+
+export function getLatestTabletHover(): TabletHoverSampleV1 | null {
+  return latestHoverSample;
+}
+
+function isSameCursorPose(a: TabletCursorPoseV1 | null, b: TabletCursorPoseV1): boolean {
+  return (
+    a !== null &&
+    a.angle_offset_deg === b.angle_offset_deg &&
+    a.roundness_scale === b.roundness_scale &&
+    a.opacity === b.opacity
+  );
+}
+
+// Tool that was active before a bound tablet tool (e.g. the eraser end) took over
+let toolBeforeTabletSwitch: ToolType | null = null;
+
+function applyTabletToolChange(change: TabletToolChangeV3): void {
+  const toolStore = useToolStore.getState();
+  const boundTool =
+    change.tool_id && TABLET_BINDABLE_TOOLS.has(change.tool_id)
+      ? (change.tool_id as ToolType)
+      : null;
+  if (boundTool) {
+    toolBeforeTabletSwitch ??= toolStore.currentTool;
+    toolStore.setTool(boundTool);
+  } else if (toolBeforeTabletSwitch) {
+    toolStore.setTool(toolBeforeTabletSwitch);
+    toolBeforeTabletSwitch = null;
+  }
+  if (change.brush_preset_id) {
+    useBrushLibraryStore.getState().applyPresetById(change.brush_preset_id);
+  }
+}
+
 function normalizeTabletInputPoint(payload: NativeTabletEventV3): TabletInputPoint {
   const hostTimeUs = Math.max(0, Math.round(payload.host_time_us));
   const deviceTimeUs = normalizeDeviceTimeUs(payload.device_time_us, hostTimeUs);
@@ -404,6 +642,9 @@ export const useTabletStore = create<TabletState>((set, get) => ({
   backpressureMode: 'lossless',
   queueMetrics: { ...EMPTY_QUEUE_METRICS },
   v3Diagnostics: { ...EMPTY_V3_DIAGNOSTICS },
+  knownTools: [],
+  activeTool: null,
+  cursorPose: null,
   bufferEpoch: getPointBufferEpoch(),
   nativeIngressTick: 0,
   info: null,
@@ -419,6 +660,10 @@ export const useTabletStore = create<TabletState>((set, get) => ({
         backend: options.backend,
         pollingRate: options.pollingRate,
         pressureCurve: options.pressureCurve,
+        pressureCurvePoints: getCustomPressureCurvePoints(
+          options.pressureCurve,
+          options.pressureCurvePoints
+        ),
         backpressureMode: options.backpressureMode,
       });
 
@@ -456,6 +701,10 @@ export const useTabletStore = create<TabletState>((set, get) => ({
         backend,
         pollingRate: options.pollingRate,
         pressureCurve: options.pressureCurve,
+        pressureCurvePoints: getCustomPressureCurvePoints(
+          options.pressureCurve,
+          options.pressureCurvePoints
+        ),
         backpressureMode: options.backpressureMode,
       });
 
@@ -469,6 +718,7 @@ export const useTabletStore = create<TabletState>((set, get) => ({
       });
       clearPointBuffer();
       nativeTraceStrokeActive = false;
+      latestHoverSample = null;
       set({ bufferEpoch: getPointBufferEpoch() });
       logTabletTrace('frontend.backend.switch.success', {
         requested_backend: backend,
@@ -549,6 +799,7 @@ export const useTabletStore = create<TabletState>((set, get) => ({
             }
             nativeTraceStrokeActive = false;
           }
+          predictedTail = [];
           const addResult = addPointToBuffer(point);
           if (addResult.seqRewindReset) {
             set({ bufferEpoch: addResult.bufferEpoch });
@@ -577,6 +828,16 @@ export const useTabletStore = create<TabletState>((set, get) => ({
               };
             });
           }
+        } else if (typeof payload === 'object' && payload !== null && 'Predicted' in payload) {
+          addPredictedPoint(normalizeTabletInputPoint(payload.Predicted));
+        } else if (typeof payload === 'object' && payload !== null && 'ToolChanged' in payload) {
+          logTabletTrace('frontend.recv.tool_changed', {
+            tool_type: payload.ToolChanged.tool_type,
+            tool_serial: payload.ToolChanged.tool_serial ?? null,
+            profile_key: payload.ToolChanged.profile_key,
+          });
+          set({ activeTool: payload.ToolChanged });
+          applyTabletToolChange(payload.ToolChanged);
         } else if (payload === 'ProximityEnter') {
           logTabletTrace('frontend.recv.proximity_enter', {});
           get()._setProximity(true);
@@ -585,7 +846,8 @@ export const useTabletStore = create<TabletState>((set, get) => ({
           nativeTraceStrokeActive = false;
           get()._setProximity(false);
           clearPointBuffer();
-          set({ currentPoint: null, bufferEpoch: getPointBufferEpoch() });
+          latestHoverSample = null;
+          set({ currentPoint: null, cursorPose: null, bufferEpoch: getPointBufferEpoch() });
         } else if (typeof payload === 'object' && payload !== null && 'StatusChanged' in payload) {
           logTabletTrace('frontend.recv.status_changed', {
             status: payload.StatusChanged,
@@ -623,9 +885,21 @@ export const useTabletStore = create<TabletState>((set, get) => ({
         }
       );
 
+      const unlistenHover = await listen<TabletHoverSampleV1>('tablet-hover-v1', (event) => {
+        const hover = event.payload;
+        if (!hover || typeof hover !== 'object') {
+          return;
+        }
+        latestHoverSample = hover;
+        if (!isSameCursorPose(get().cursorPose, hover.cursor)) {
+          set({ cursorPose: hover.cursor });
+        }
+      });
+
       const unlisten: UnlistenFn = () => {
         unlistenV3();
         unlistenEmitterMetrics();
+        unlistenHover();
       };
 
       set({ unlisten });
@@ -652,10 +926,12 @@ export const useTabletStore = create<TabletState>((set, get) => ({
 
       clearPointBuffer();
       nativeTraceStrokeActive = false;
+      latestHoverSample = null;
       set({
         isStreaming: false,
         unlisten: null,
         currentPoint: null,
+        cursorPose: null,
         bufferEpoch: getPointBufferEpoch(),
         nativeIngressTick: 0,
       });
@@ -680,10 +956,12 @@ export const useTabletStore = create<TabletState>((set, get) => ({
     }
     clearPointBuffer();
     nativeTraceStrokeActive = false;
+    latestHoverSample = null;
     set({
       unlisten: null,
       isStreaming: false,
       currentPoint: null,
+      cursorPose: null,
       bufferEpoch: getPointBufferEpoch(),
       nativeIngressTick: 0,
     });
@@ -704,6 +982,8 @@ export async function pushPointerEvent(
     pointerId?: number;
     phase?: InputPhase;
     deviceTimeUs?: number;
+    toolType?: TabletToolType;
+    wheel?: number;
   }
 ): Promise<void> {
   try {
@@ -729,9 +1009,47 @@ export async function pushPointerEvent(
         pointerId: options?.pointerId,
         phase: options?.phase,
         deviceTimeUs: options?.deviceTimeUs,
+        toolType: options?.toolType,
+        wheel: options?.wheel,
       },
     });
   } catch (error) {
     console.error('[Tablet] Push pointer event failed:', error);
   }
 }
+
+export async function getTabletToolProfiles(): Promise<Record<string, TabletToolProfileV1>> {
+  return invoke<Record<string, TabletToolProfileV1>>('get_tablet_tool_profiles');
+}
+
+export async function setTabletToolProfile(
+  key: string,
+  profile: TabletToolProfileV1 | null
+): Promise<void> {
+  await invoke('set_tablet_tool_profile', { key, profile });
+}
+
+export async function getTabletBindings(): Promise<Record<string, TabletActionV1>> {
+  return invoke<Record<string, TabletActionV1>>('get_tablet_bindings');
+}
+
+export async function setTabletBinding(key: string, action: TabletActionV1 | null): Promise<void> {
+  await invoke('set_tablet_binding', { key, action });
+}
+
+export async function getTabletMapping(): Promise<TabletMappingV1> {
+  return invoke<TabletMappingV1>('get_tablet_mapping');
+}
+
+/** Persist the mapping; resolves to the stored value with the active area clamped */
+export async function setTabletMapping(mapping: TabletMappingV1): Promise<TabletMappingV1> {
+  return invoke<TabletMappingV1>('set_tablet_mapping', { mapping });
+}
+
+export async function setTabletCursorPoseConfig(config: TabletCursorPoseConfigV1): Promise<void> {
+  try {
+    await invoke('set_tablet_cursor_pose_config', { config });
+  } catch (error) {
+    console.error('[Tablet] Set cursor pose config failed:', error);
+  }
+}
@@ -2,6 +2,7 @@ import { describe, expect, it } from 'vitest';
 import {
   PRESSURE_CURVE_LUT_SIZE,
   buildPressureCurveLut,
+  getCustomPressureCurvePoints,
   getPressureCurvePresetPoints,
   normalizePressureCurvePoints,
   samplePressureCurveLut,
@@ -45,4 +46,16 @@ describe('pressureCurve utils', () => {
     expect(mid).toBeGreaterThan(0.5);
     expect(above).toBeCloseTo(1, 6);
   });
+
+  it('only reports points that differ from the named preset as custom', () => {
+    const softPoints = getPressureCurvePresetPoints('soft');
+    expect(getCustomPressureCurvePoints('soft', softPoints)).toBeUndefined();
+    expect(getCustomPressureCurvePoints('linear', undefined)).toBeUndefined();
+    const edited = [
+      { x: 0, y: 0 },
+      { x: 0.5, y: 0.6 },
+      { x: 1, y: 1 },
+    ];
+    expect(getCustomPressureCurvePoints('linear', edited)).toEqual(edited);
+  });
 });
@@ -202,6 +202,30 @@ export function getPressureCurvePresetPoints(
   }
 }
 
+/**
+ * Points to send to the tablet backend, or undefined when they only reproduce
+ * the named preset so the backend keeps the preset's own curve.
+ */
+export function getCustomPressureCurvePoints(
+  preset: string | undefined,
+  points: readonly PressureCurveControlPoint[] | undefined
+): PressureCurveControlPoint[] | undefined {
+  if (!points) return undefined;
+  const presetPoints = getPressureCurvePresetPoints(
+    preset === 'soft' || preset === 'hard' || preset === 'scurve' ? preset : 'linear'
+  );
+  const matchesPreset =
+    points.length === presetPoints.length &&
+    points.every((point, index) => {
+      const presetPoint = presetPoints[index]!;
+      return (
+        Math.abs(point.x - presetPoint.x) <= MIN_X_GAP &&
+        Math.abs(point.y - presetPoint.y) <= MIN_X_GAP
+      );
+    });
+  return matchesPreset ? undefined : points.map((point) => ({ x: point.x, y: point.y }));
+}
+
 export function normalizePressureCurvePoints(
   points: readonly PressureCurveControlPoint[]
 ): PressureCurveControlPoint[] {